@@ -0,0 +1,33 @@
+//! Sequential vs parallel `add_technical_indicators` on daily and minute data
+//!
+//! Run with `cargo bench --features parallel`.
+
+mod common;
+
+use common::bars;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rustalib::indicators::{
+    add_technical_indicators_parallel, add_technical_indicators_with_config, IndicatorConfig,
+};
+
+fn add_indicators(c: &mut Criterion) {
+    let config = IndicatorConfig::default();
+    let mut group = c.benchmark_group("add_technical_indicators");
+    group.sample_size(10);
+
+    // Ten years of daily bars and one year of minute bars
+    for (label, rows) in [("daily", 2_520), ("minute", 98_280)] {
+        let df = bars(rows);
+        group.bench_with_input(BenchmarkId::new("sequential", label), &df, |b, df| {
+            b.iter(|| add_technical_indicators_with_config(&mut df.clone(), &config).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", label), &df, |b, df| {
+            b.iter(|| add_technical_indicators_parallel(&mut df.clone(), &config).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, add_indicators);
+criterion_main!(benches);
@@ -0,0 +1,56 @@
+//! Synthetic market data shared by the benchmarks
+//!
+//! Each benchmark uses only some of these helpers.
+#![allow(dead_code)]
+
+use polars::prelude::*;
+
+/// One day in milliseconds
+pub const DAY_MS: i64 = 86_400_000;
+
+/// One minute in milliseconds
+pub const MINUTE_MS: i64 = 60_000;
+
+/// Synthetic OHLCV bars following a deterministic random walk
+pub fn bars(n: usize) -> DataFrame {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut price = 100.0;
+    let mut close = Vec::with_capacity(n);
+    for _ in 0..n {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        price *= 1.0 + ((state % 2001) as f64 - 1000.0) / 100_000.0;
+        close.push(price);
+    }
+
+    df! {
+        "open" => close.iter().map(|c| c * 0.999).collect::<Vec<_>>(),
+        "high" => close.iter().map(|c| c * 1.004).collect::<Vec<_>>(),
+        "low" => close.iter().map(|c| c * 0.996).collect::<Vec<_>>(),
+        "close" => &close,
+        "volume" => vec![1_000.0; n],
+    }
+    .unwrap()
+}
+
+/// [`bars`] with a UTC "timestamp" column, one bar every `step_ms`
+/// milliseconds from 2024-01-01
+pub fn timestamped_bars(n: usize, step_ms: i64) -> DataFrame {
+    let start = 1_704_067_200_000_i64;
+    let timestamp = Series::new(
+        "timestamp".into(),
+        (0..n as i64)
+            .map(|i| start + i * step_ms)
+            .collect::<Vec<_>>(),
+    )
+    .cast(&DataType::Datetime(
+        TimeUnit::Milliseconds,
+        Some("UTC".into()),
+    ))
+    .unwrap();
+
+    let mut df = bars(n);
+    df.insert_column(0, timestamp).unwrap();
+    df
+}
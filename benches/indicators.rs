@@ -0,0 +1,52 @@
+//! Single indicators on a million rows and the full indicator suite
+//!
+//! Run with `cargo bench --bench indicators`. See `benches/README.md` for
+//! the recorded baseline and how to compare against it.
+
+mod common;
+
+use common::bars;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rustalib::indicators::moving_averages::calculate_ema;
+use rustalib::indicators::oscillators::calculate_rsi;
+use rustalib::indicators::volatility::calculate_atr;
+use rustalib::indicators::{add_technical_indicators_with_config, IndicatorConfig};
+
+const ROWS: usize = 1_000_000;
+
+fn single_indicators(c: &mut Criterion) {
+    let df = bars(ROWS);
+    let mut group = c.benchmark_group("indicator_1m_rows");
+    group.sample_size(10);
+
+    group.bench_function("calculate_ema_20", |b| {
+        b.iter(|| calculate_ema(&df, "close", 20).unwrap())
+    });
+    group.bench_function("calculate_rsi_14", |b| {
+        b.iter(|| calculate_rsi(&df, 14, "close").unwrap())
+    });
+    group.bench_function("calculate_atr_14", |b| {
+        b.iter(|| calculate_atr(&df, 14).unwrap())
+    });
+
+    group.finish();
+}
+
+fn indicator_suite(c: &mut Criterion) {
+    let config = IndicatorConfig::default();
+    let mut group = c.benchmark_group("add_technical_indicators_sequential");
+    group.sample_size(10);
+
+    // Ten years of daily bars and one year of minute bars
+    for (label, rows) in [("daily", 2_520), ("minute", 98_280)] {
+        let df = bars(rows);
+        group.bench_with_input(BenchmarkId::from_parameter(label), &df, |b, df| {
+            b.iter(|| add_technical_indicators_with_config(&mut df.clone(), &config).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, single_indicators, indicator_suite);
+criterion_main!(benches);
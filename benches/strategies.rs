@@ -0,0 +1,52 @@
+//! Backtests of the bundled strategies on daily and minute bars
+//!
+//! Run with `cargo bench --bench strategies`. See `benches/README.md` for
+//! the recorded baseline and how to compare against it.
+
+mod common;
+
+use common::{timestamped_bars, DAY_MS, MINUTE_MS};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use polars::prelude::*;
+use rustalib::strategy::builder::ConfigStrategy;
+use rustalib::strategy::crypto::grid_trading;
+
+const RSI_MACD: &str = r#"{
+    "name": "rsi macd",
+    "indicators": [
+        { "type": "rsi", "name": "rsi", "period": 14 },
+        { "type": "macd", "name": "macd", "fast_period": 12, "slow_period": 26, "signal_period": 9 }
+    ],
+    "entry": [
+        { "type": "below", "left": "rsi", "right": 40 },
+        { "type": "crosses_above", "left": "macd", "right": "macd_signal" }
+    ],
+    "exit": [{ "type": "above", "left": "rsi", "right": 60 }],
+    "risk": { "fee_rate": 0.001, "stop_loss_pct": 5.0 }
+}"#;
+
+fn strategy_runs(c: &mut Criterion) {
+    let strategy = ConfigStrategy::from_json(RSI_MACD).unwrap();
+    let mut group = c.benchmark_group("strategy_run");
+    group.sample_size(10);
+
+    // Ten years of daily bars and one year of minute bars
+    for (label, rows, step) in [("daily", 2_520, DAY_MS), ("minute", 98_280, MINUTE_MS)] {
+        let df = timestamped_bars(rows, step);
+        group.bench_with_input(BenchmarkId::new("config_rsi_macd", label), &df, |b, df| {
+            b.iter(|| strategy.run(df).unwrap())
+        });
+
+        let close = df.column("close").unwrap().f64().unwrap();
+        let (low, high) = (close.min().unwrap(), close.max().unwrap());
+        let params = grid_trading::StrategyParams::new(low, high);
+        group.bench_with_input(BenchmarkId::new("grid_trading", label), &df, |b, df| {
+            b.iter(|| grid_trading::run_strategy(df, &params).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, strategy_runs);
+criterion_main!(benches);
@@ -16,7 +16,8 @@ fn main() -> Result<(), PolarsError> {
     let df = DataFrame::new(vec![close_prices.clone().into()])?;
 
     // Calculate Bollinger Bands with standard parameters (20-period, 2 standard deviations)
-    let (middle, upper, lower) = calculate_bollinger_bands(&df, 20, 2.0, "close")?;
+    let bands = calculate_bollinger_bands(&df, 20, 2.0, "close")?;
+    let (middle, upper, lower) = (bands.middle, bands.upper, bands.lower);
 
     // Print the Bollinger Bands values
     println!("Middle Band (SMA):");
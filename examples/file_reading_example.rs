@@ -1,4 +1,3 @@
-use polars::prelude::*;
 use rustalib::util::file_utils::{read_csv, read_csv_default, read_financial_data, read_parquet};
 use std::error::Error;
 
@@ -41,7 +41,8 @@ fn main() -> Result<(), PolarsError> {
 
     // Calculate and display Bollinger Bands
     println!("\n----- Bollinger Bands -----");
-    let (bb_middle, bb_upper, bb_lower) = calculate_bollinger_bands(&df, 20, 2.0, "close")?;
+    let bands = calculate_bollinger_bands(&df, 20, 2.0, "close")?;
+    let (bb_middle, bb_upper, bb_lower) = (bands.middle, bands.upper, bands.lower);
     println!(
         "BB Middle(20,2): {}",
         bb_middle.f64()?.get(25).unwrap_or(f64::NAN)
@@ -57,20 +58,18 @@ fn main() -> Result<(), PolarsError> {
 
     // Calculate and display MACD
     println!("\n----- MACD -----");
-    let (macd_line, signal_line) = calculate_macd(&df, 12, 26, 9, "close")?;
+    let macd = calculate_macd(&df, 12, 26, 9, "close")?;
     println!(
         "MACD Line: {}",
-        macd_line.f64()?.get(28).unwrap_or(f64::NAN)
+        macd.macd.f64()?.get(28).unwrap_or(f64::NAN)
     );
     println!(
         "Signal Line: {}",
-        signal_line.f64()?.get(28).unwrap_or(f64::NAN)
+        macd.signal.f64()?.get(28).unwrap_or(f64::NAN)
     );
     println!(
         "Histogram: {}",
-        (macd_line.f64()? - signal_line.f64()?)
-            .get(28)
-            .unwrap_or(f64::NAN)
+        macd.histogram.f64()?.get(28).unwrap_or(f64::NAN)
     );
     println!("MACD Interpretation:");
     println!("  MACD Line crosses above Signal Line: Bullish signal");
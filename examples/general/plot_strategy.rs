@@ -0,0 +1,53 @@
+// Example: Charting a Backtest
+// Runs a grid trading strategy on synthetic minute bars and writes an SVG
+// chart with the trades, a moving average overlay, equity and drawdown.
+//
+// cargo run --example general_plot_strategy --features plot
+
+use polars::prelude::*;
+use rustalib::indicators::moving_averages::calculate_sma;
+use rustalib::plot::{plot_strategy_result_with_options, PlotOptions};
+use rustalib::strategy::crypto::grid_trading::{run_strategy, StrategyParams};
+use rustalib::TaResult;
+
+fn main() -> TaResult<()> {
+    let bars = 240;
+    let close: Vec<f64> = (0..bars)
+        .map(|i| 100.0 + 4.0 * (i as f64 / 12.0).sin() + 0.01 * i as f64)
+        .collect();
+    let open: Vec<f64> = (0..bars)
+        .map(|i| if i == 0 { 100.0 } else { close[i - 1] })
+        .collect();
+    let high: Vec<f64> = (0..bars).map(|i| open[i].max(close[i]) + 0.4).collect();
+    let low: Vec<f64> = (0..bars).map(|i| open[i].min(close[i]) - 0.4).collect();
+    let timestamps: Vec<String> = (0..bars)
+        .map(|i| format!("2024-01-02 {:02}:{:02}:00", 9 + i / 60, i % 60))
+        .collect();
+
+    let mut df = df! {
+        "timestamp" => timestamps,
+        "open" => open,
+        "high" => high,
+        "low" => low,
+        "close" => close,
+    }?;
+    let sma = calculate_sma(&df, "close", 20)?.with_name("sma_20".into());
+    df.with_column(sma)?;
+
+    let signals = run_strategy(&df, &StrategyParams::new(95.0, 107.0))?;
+
+    let options = PlotOptions {
+        title: Some("Grid trading 95-107".to_string()),
+        overlays: vec!["sma_20".to_string()],
+        ..Default::default()
+    };
+    let path = std::env::temp_dir().join("rustalib_grid_trading.svg");
+    plot_strategy_result_with_options(&df, &signals, &path, &options)?;
+
+    println!(
+        "{} trades, chart written to {}",
+        signals.trades.len(),
+        path.display()
+    );
+    Ok(())
+}
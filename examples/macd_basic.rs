@@ -17,14 +17,17 @@ fn main() -> Result<(), PolarsError> {
 
     // Calculate MACD with standard parameters
     // fast_period = 12, slow_period = 26, signal_period = 9
-    let (macd, signal) = calculate_macd(&df, 12, 26, 9, "close")?;
+    let macd = calculate_macd(&df, 12, 26, 9, "close")?;
 
-    // Print the MACD and signal values
+    // Print the MACD, signal and histogram values
     println!("MACD values:");
-    println!("{}", macd);
+    println!("{}", macd.macd);
 
     println!("\nMACD Signal line:");
-    println!("{}", signal);
+    println!("{}", macd.signal);
+
+    println!("\nMACD Histogram:");
+    println!("{}", macd.histogram);
 
     // Show how to interpret MACD values
     println!("\nBasic MACD interpretation:");
@@ -1,6 +1,115 @@
 // Example: Iron Condor Strategy
-// This is a placeholder file. Implement your iron condor example here.
+// Selects iron condor legs from a synthetic options chain and backtests the
+// strategy over a few months of daily closes.
 
-fn main() {
-    println!("Iron Condor example placeholder.");
+use chrono::{Duration, TimeZone, Utc};
+use polars::prelude::*;
+use rustalib::indicators::options::black_scholes_price;
+use rustalib::strategy::options::iron_condor::{run_strategy, select_iron_condor, StrategyParams};
+
+fn main() -> Result<(), PolarsError> {
+    println!("Iron Condor Example");
+    println!("===================\n");
+
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+    let days = 90;
+
+    // Daily closes oscillating around 100
+    let closes: Vec<f64> = (0..days)
+        .map(|d| 100.0 + 5.0 * (d as f64 / 10.0).sin())
+        .collect();
+    let times: Vec<String> = (0..days)
+        .map(|d| {
+            (start + Duration::days(d as i64))
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .collect();
+
+    // Weekly chain snapshots with monthly expiries, priced at 25% volatility
+    let mut quote_time = Vec::new();
+    let mut strike = Vec::new();
+    let mut expiry = Vec::new();
+    let mut option_type = Vec::new();
+    let mut price = Vec::new();
+    for d in (0..days).step_by(7) {
+        let now = start + Duration::days(d as i64);
+        for month in 1..=4 {
+            let exp = start + Duration::days(30 * month);
+            if exp <= now {
+                continue;
+            }
+            let t = (exp - now).num_days() as f64 / 365.0;
+            for k in (80..=120).step_by(5) {
+                for is_call in [true, false] {
+                    quote_time.push(times[d].clone());
+                    strike.push(k as f64);
+                    expiry.push(exp.format("%Y-%m-%d").to_string());
+                    option_type.push(if is_call { "call" } else { "put" });
+                    price.push(black_scholes_price(
+                        closes[d], k as f64, t, 0.02, 0.0, 0.25, is_call,
+                    ));
+                }
+            }
+        }
+    }
+
+    let underlying = df! {
+        "timestamp" => times.clone(),
+        "close" => closes.clone(),
+    }?;
+    let chain = df! {
+        "timestamp" => quote_time,
+        "strike" => strike,
+        "expiry" => expiry,
+        "option_type" => option_type,
+        "price" => price,
+    }?;
+
+    let params = StrategyParams::default();
+
+    // Leg selection on the first snapshot
+    let first_snapshot = chain
+        .clone()
+        .lazy()
+        .filter(col("timestamp").eq(lit(times[0].clone())))
+        .collect()?;
+    if let Some(condor) = select_iron_condor(&first_snapshot, closes[0], start, &params)? {
+        println!("Legs selected on {}:", times[0]);
+        for leg in condor.legs() {
+            println!(
+                "  {} {:>6.1} {}  price {:.2}  IV {:.1}%  delta {:+.2}",
+                if leg.ratio > 0.0 { "buy " } else { "sell" },
+                leg.strike,
+                if leg.is_call { "call" } else { "put " },
+                leg.entry_price,
+                leg.implied_volatility * 100.0,
+                leg.delta,
+            );
+        }
+        let (lower, upper) = condor.breakevens();
+        println!(
+            "  credit {:.2}, max loss {:.2}",
+            condor.credit,
+            condor.max_loss()
+        );
+        println!("  breakevens {:.2} / {:.2}", lower, upper);
+        println!(
+            "  expected move ±{:.2}, probability of profit {:.1}%\n",
+            condor.expected_move,
+            condor.probability_of_profit * 100.0
+        );
+    }
+
+    // Full backtest
+    let signals = run_strategy(&underlying, &chain, &params)?;
+    println!("Trades:");
+    println!("{}", signals.trade_log()?);
+
+    let report = signals.performance_report();
+    println!("Total trades: {}", report.total_trades);
+    println!("Win rate: {:.1}%", report.win_rate);
+    println!("Total P&L: {:.2}", report.total_pnl);
+
+    Ok(())
 }
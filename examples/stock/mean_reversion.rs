@@ -183,7 +183,8 @@ fn main() -> Result<(), PolarsError> {
     df.with_column(sma_20)?;
 
     // 2. Calculate Bollinger Bands
-    let (middle, upper, lower) = calculate_bollinger_bands(&df, 20, 2.0, "close")?;
+    let bands = calculate_bollinger_bands(&df, 20, 2.0, "close")?;
+    let (middle, upper, lower) = (bands.middle, bands.upper, bands.lower);
     println!("Middle Band length: {}, Upper Band length: {}, Lower Band length: {}, DataFrame height: {}", 
              middle.len(), upper.len(), lower.len(), df.height());
     df.with_column(middle)?;
@@ -299,7 +300,7 @@ fn calculate_z_score(df: &mut DataFrame, window: usize) -> Result<(), PolarsErro
             z_scores.push(f64::NAN);
         } else {
             let window_slice = close.slice((i - window) as i64, window);
-            let window_vec: Vec<f64> = window_slice.iter().filter_map(|x| x).collect();
+            let window_vec: Vec<f64> = window_slice.iter().flatten().collect();
 
             if window_vec.is_empty() {
                 z_scores.push(f64::NAN);
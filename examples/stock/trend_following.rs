@@ -186,27 +186,12 @@ fn main() -> Result<(), PolarsError> {
     df.with_column(Series::new("sma_50".into(), sma_50_values))?;
 
     // 2. Calculate MACD
-    let (macd_line, signal_line) = calculate_macd(&df, 12, 26, 9, "close")?;
-
-    // Add MACD and signal line to the dataframe
-    df.with_column(macd_line.with_name("macd".into()))?;
-    df.with_column(signal_line.with_name("macd_signal".into()))?;
-
-    // Calculate MACD histogram
-    // First get the values as f64 arrays
-    let macd_vals = df.column("macd")?.f64()?;
-    let signal_vals = df.column("macd_signal")?.f64()?;
-
-    // Then calculate the difference
-    let mut histogram = Vec::with_capacity(macd_vals.len());
-    for i in 0..macd_vals.len() {
-        let macd = macd_vals.get(i).unwrap_or(f64::NAN);
-        let signal = signal_vals.get(i).unwrap_or(f64::NAN);
-        histogram.push(macd - signal);
-    }
+    let macd = calculate_macd(&df, 12, 26, 9, "close")?;
 
-    // Add the histogram to the dataframe
-    df.with_column(Series::new("macd_histogram".into(), histogram))?;
+    // Add MACD, signal line and histogram to the dataframe
+    df.with_column(macd.macd.with_name("macd".into()))?;
+    df.with_column(macd.signal.with_name("macd_signal".into()))?;
+    df.with_column(macd.histogram.with_name("macd_histogram".into()))?;
 
     // 3. Calculate ADX (Average Directional Index) for trend strength
     let adx = calculate_adx(&df, 14)?;
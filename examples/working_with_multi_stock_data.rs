@@ -2,9 +2,10 @@ use polars::prelude::*;
 use rustalib::indicators::{
     moving_averages::{calculate_ema, calculate_sma},
     oscillators::{calculate_macd, calculate_rsi},
-    volatility::{calculate_atr, calculate_bollinger_bands},
+    volatility::{calculate_atr, calculate_bollinger_bands_with_metrics},
     volume::calculate_obv,
 };
+use rustalib::performance::calculate_benchmark_report;
 use std::convert::TryInto;
 
 /// This example demonstrates how to load and process data from multiple stock CSV files
@@ -24,6 +25,7 @@ fn main() -> Result<(), PolarsError> {
     }
 
     let mut all_metrics = Vec::new();
+    let mut all_closes = Vec::new();
 
     println!("Analyzing technical indicators across multiple stocks...");
 
@@ -82,34 +84,33 @@ fn main() -> Result<(), PolarsError> {
             ])
             .collect()?;
 
+        // Keep closes by date for the benchmark comparison
+        all_closes.push(
+            df.select(["date", "close"])?
+                .lazy()
+                .select([
+                    col("date"),
+                    col("close").cast(DataType::Float64).alias(*ticker),
+                ])
+                .collect()?,
+        );
+
         // Calculate various technical indicators
         let sma_20 = calculate_sma(&df, "close", 20)?;
         let ema_20 = calculate_ema(&df, "close", 20)?;
         let rsi_14 = calculate_rsi(&df, 14, "close")?;
         let atr_14 = calculate_atr(&df, 14)?;
-        let (bb_mid, bb_upper, bb_lower) = calculate_bollinger_bands(&df, 20, 2.0, "close")?;
+        let bands = calculate_bollinger_bands_with_metrics(&df, 20, 2.0, "close")?;
+        let (bb_mid, bb_upper, bb_lower) = (bands.middle, bands.upper, bands.lower);
         let obv = calculate_obv(&df)?;
-        let (macd_line, macd_signal) = calculate_macd(&df, 12, 26, 9, "close")?;
-
-        // Calculate Bollinger Band width as (Upper - Lower) / Middle
-        let mut bb_width = Vec::with_capacity(df.height());
-        let bb_mid_ca = bb_mid.f64()?;
-        let bb_upper_ca = bb_upper.f64()?;
-        let bb_lower_ca = bb_lower.f64()?;
+        let macd = calculate_macd(&df, 12, 26, 9, "close")?;
+        let (macd_line, macd_signal) = (macd.macd, macd.signal);
 
-        for i in 0..df.height() {
-            let mid = bb_mid_ca.get(i).unwrap_or(f64::NAN);
-            let upper = bb_upper_ca.get(i).unwrap_or(f64::NAN);
-            let lower = bb_lower_ca.get(i).unwrap_or(f64::NAN);
-
-            if mid.is_nan() || upper.is_nan() || lower.is_nan() || mid.abs() < 1e-10 {
-                bb_width.push(f64::NAN);
-            } else {
-                bb_width.push((upper - lower) / mid);
-            }
-        }
-
-        let bb_width_series = Series::new("bb_width".into(), bb_width);
+        // Bollinger Band width as (Upper - Lower) / Middle
+        let bb_width_series = bands
+            .bandwidth
+            .expect("bandwidth is requested")
+            .with_name("bb_width".into());
 
         // Calculate volatility as ATR / Close
         let mut volatility = Vec::with_capacity(df.height());
@@ -301,5 +302,45 @@ fn main() -> Result<(), PolarsError> {
         highest_obv_trend.ticker, highest_obv_trend.obv_trend
     );
 
+    // Returns relative to an equal-weight basket of all tickers, on common dates
+    let mut closes = all_closes[0].clone();
+    for other in &all_closes[1..] {
+        closes = closes.inner_join(other, ["date"], ["date"])?;
+    }
+    let mut basket = vec![0.0; closes.height()];
+    for ticker in &tickers {
+        let prices: Vec<f64> = closes
+            .column(ticker)?
+            .f64()?
+            .iter()
+            .map(|v| v.unwrap_or(f64::NAN))
+            .collect();
+        for (value, price) in basket.iter_mut().zip(&prices) {
+            *value += price / prices[0] / tickers.len() as f64;
+        }
+    }
+    closes.with_column(Series::new("basket".into(), basket))?;
+
+    println!("\n=== Versus Equal-Weight Basket ===");
+    println!(
+        "{:<6} {:>9} {:>6} {:>9} {:>6} {:>9} {:>9} {:>10}",
+        "Ticker", "Alpha", "Beta", "Track Err", "IR", "Up Cap", "Down Cap", "Rel DD"
+    );
+    println!("------------------------------------------------------------------------");
+    for ticker in &tickers {
+        let report = calculate_benchmark_report(&closes, ticker, "basket", 252.0)?;
+        println!(
+            "{:<6} {:>8.2}% {:>6.2} {:>8.2}% {:>6.2} {:>8.1}% {:>8.1}% {:>9.2}%",
+            ticker,
+            report.alpha,
+            report.beta,
+            report.tracking_error,
+            report.information_ratio,
+            report.up_capture,
+            report.down_capture,
+            report.max_relative_drawdown
+        );
+    }
+
     Ok(())
 }
@@ -0,0 +1,199 @@
+use crate::backtest::{
+    run_backtest, BacktestParams, BarContext, EventStrategy, OrderSide, OrderType,
+};
+use crate::error::{TaError, TaResult};
+use crate::strategy::{OutputColumns, StrategySignals};
+use polars::prelude::*;
+
+/// An entry order with a linked stop loss and take-profit target
+///
+/// Submitted with [`ExchangeSimulator::submit_bracket`](super::ExchangeSimulator::submit_bracket).
+/// Once the entry fills, a stop order at `stop` and a limit order at
+/// `target` close the filled quantity from the next bar on. A fill of either
+/// exit reduces the other by the same quantity, and once the exits have
+/// closed the position the rest of the entry is cancelled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BracketOrder {
+    /// Side of the entry; the exits take the opposite side
+    pub side: OrderSide,
+    /// Quantity to enter
+    pub quantity: f64,
+    /// Market, limit or stop entry
+    pub entry: OrderType,
+    /// Stop loss price, on the losing side of the entry
+    pub stop: f64,
+    /// Take-profit price, on the winning side of the entry
+    pub target: f64,
+}
+
+impl BracketOrder {
+    /// Create a bracket order
+    ///
+    /// # Arguments
+    ///
+    /// * `side` - Side of the entry
+    /// * `quantity` - Quantity to enter (must be positive)
+    /// * `entry` - Entry order type
+    /// * `stop` - Stop loss price: below a limit or stop buy price and the
+    ///   target of a buy, above them for a sell
+    /// * `target` - Take-profit price
+    ///
+    /// # Returns
+    ///
+    /// The bracket, or `TaError::InvalidParameter` when the stop and target
+    /// are on the wrong side of each other or of the entry price
+    pub fn new(
+        side: OrderSide,
+        quantity: f64,
+        entry: OrderType,
+        stop: f64,
+        target: f64,
+    ) -> TaResult<Self> {
+        if !(quantity > 0.0 && quantity.is_finite()) {
+            return Err(TaError::invalid_parameter("quantity", "must be positive"));
+        }
+        let sign = side.sign();
+        if !(sign * (target - stop) > 0.0 && (target - stop).is_finite()) {
+            return Err(TaError::invalid_parameter(
+                "stop",
+                "must be on the losing side of the target",
+            ));
+        }
+        if let OrderType::Limit { price } | OrderType::Stop { price } = entry {
+            if !(sign * (price - stop) > 0.0 && sign * (target - price) > 0.0) {
+                return Err(TaError::invalid_parameter(
+                    "entry",
+                    "price must lie between the stop and the target",
+                ));
+            }
+        }
+
+        Ok(Self {
+            side,
+            quantity,
+            entry,
+            stop,
+            target,
+        })
+    }
+
+    /// Price of a limit or stop entry, `None` for a market entry
+    pub fn entry_price(&self) -> Option<f64> {
+        match self.entry {
+            OrderType::Market => None,
+            OrderType::Limit { price } | OrderType::Stop { price } => Some(price),
+        }
+    }
+}
+
+/// Linked "bracket_entry", "bracket_stop" and "bracket_target" columns
+///
+/// Each row holds the levels of the bracket placed on that bar, so the
+/// three levels of one bracket can never be misaligned. Market entries and
+/// bars without a bracket are null.
+pub fn bracket_columns(brackets: &[Option<BracketOrder>]) -> [Series; 3] {
+    let levels = |level: fn(&BracketOrder) -> Option<f64>| -> Vec<Option<f64>> {
+        brackets
+            .iter()
+            .map(|b| b.as_ref().and_then(level))
+            .collect()
+    };
+    [
+        Series::new("bracket_entry".into(), levels(BracketOrder::entry_price)),
+        Series::new("bracket_stop".into(), levels(|b| Some(b.stop))),
+        Series::new("bracket_target".into(), levels(|b| Some(b.target))),
+    ]
+}
+
+/// Submits the bracket of a bar whenever the position is flat
+struct BracketSignals<'a> {
+    brackets: &'a [Option<BracketOrder>],
+}
+
+impl EventStrategy for BracketSignals<'_> {
+    fn on_bar(&mut self, ctx: &mut BarContext<'_>) {
+        let Some(bracket) = self.brackets[ctx.index] else {
+            return;
+        };
+        if ctx.exchange.position().is_flat() {
+            // A new bracket replaces an entry that has not filled yet
+            ctx.exchange.cancel_all();
+            ctx.exchange.submit_bracket(&bracket);
+        }
+    }
+}
+
+/// Backtests one bracket order per signal bar
+///
+/// While flat, the bracket of a bar is submitted at its close, replacing an
+/// unfilled entry of an earlier bracket; brackets of bars with an open
+/// position are ignored. Entries, stops and targets all go through the
+/// [`ExchangeSimulator`](super::ExchangeSimulator), so trades and equity
+/// account for every leg consistently.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with the time column and the open, high, low and
+///   close columns
+/// * `params` - Exchange settings and column names
+/// * `brackets` - Bracket to place on each bar, `None` for no signal
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the StrategySignals of
+/// [`run_backtest`]; `indicator_values` also holds the
+/// [`bracket_columns`] unless only signals are kept
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::backtest::{run_brackets, BacktestParams, BracketOrder, OrderSide, OrderType};
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"],
+///     "open" => [100.0, 100.5, 101.0, 102.0],
+///     "high" => [101.0, 101.0, 102.0, 103.5],
+///     "low" => [99.0, 100.0, 100.5, 101.5],
+///     "close" => [100.0, 100.8, 101.5, 103.0],
+/// }
+/// .unwrap();
+///
+/// // Buy at the next open with a stop at 98.0 and a target at 103.0
+/// let bracket = BracketOrder::new(OrderSide::Buy, 10.0, OrderType::Market, 98.0, 103.0).unwrap();
+/// let brackets = [Some(bracket), None, None, None];
+///
+/// let signals = run_brackets(&df, &BacktestParams::default(), &brackets).unwrap();
+/// assert_eq!(signals.buy_signals, [0, 1, 0, 0]);
+/// assert_eq!(signals.sell_signals, [0, 0, 0, 1]);
+/// assert_eq!(signals.trades[0].exit_price, 103.0);
+/// assert_eq!(signals.trades[0].pnl, 25.0);
+///
+/// let stops = signals.indicator_values.column("bracket_stop").unwrap();
+/// assert_eq!(stops.f64().unwrap().get(0), Some(98.0));
+/// ```
+pub fn run_brackets(
+    df: &DataFrame,
+    params: &BacktestParams,
+    brackets: &[Option<BracketOrder>],
+) -> PolarsResult<StrategySignals> {
+    if brackets.len() != df.height() {
+        return Err(TaError::invalid_parameter(
+            "brackets",
+            format!(
+                "expected one entry per bar ({}), got {}",
+                df.height(),
+                brackets.len()
+            ),
+        )
+        .into());
+    }
+
+    let mut signals = run_backtest(df, params, &mut BracketSignals { brackets })?;
+    if params.output_columns != OutputColumns::SignalsOnly {
+        for series in bracket_columns(brackets) {
+            signals.indicator_values.with_column(series)?;
+        }
+    }
+    Ok(signals)
+}
@@ -0,0 +1,255 @@
+use crate::backtest::{ExchangeSimulator, Fill, OrderSide};
+use crate::performance::{self, MatchingMethod, TradeSide};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::column_map::ColumnMap;
+use crate::util::dataframe_utils::column_nan_values;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One OHLCV bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bar {
+    /// Bar timestamp
+    pub time: DateTime<Utc>,
+    /// Opening price
+    pub open: f64,
+    /// Highest price
+    pub high: f64,
+    /// Lowest price
+    pub low: f64,
+    /// Closing price
+    pub close: f64,
+    /// Traded volume (NaN when unknown)
+    pub volume: f64,
+}
+
+/// What an event-driven strategy sees of the current bar
+pub struct BarContext<'a> {
+    /// Index of the bar in the input DataFrame
+    pub index: usize,
+    /// The bar's prices
+    pub bar: &'a Bar,
+    /// Exchange to submit and cancel orders at; orders submitted now work
+    /// from the next bar
+    pub exchange: &'a mut ExchangeSimulator,
+}
+
+/// A strategy reacting to bars and fills
+///
+/// Strategies typically calculate their indicators up front and look them
+/// up by `ctx.index`.
+pub trait EventStrategy {
+    /// Called at the close of every bar, after the bar's fills
+    fn on_bar(&mut self, ctx: &mut BarContext<'_>);
+
+    /// Called for each fill, before `on_bar` of the same bar
+    fn on_fill(&mut self, _fill: &Fill, _ctx: &mut BarContext<'_>) {}
+}
+
+/// Parameters of an event-driven backtest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BacktestParams {
+    /// Column with bar timestamps
+    pub time_column: String,
+    /// Names of the OHLCV columns in the price data
+    pub columns: ColumnMap,
+    /// Fee charged per fill as a fraction of notional (e.g. 0.001 = 0.1%)
+    pub fee_rate: f64,
+    /// Slippage of market and stop fills, in basis points
+    pub slippage_bps: f64,
+    /// Largest share of a bar's volume the fills of that bar may take; the
+    /// rest of an order fills on later bars
+    pub max_volume_share: Option<f64>,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl Default for BacktestParams {
+    fn default() -> Self {
+        Self {
+            time_column: "timestamp".to_string(),
+            columns: ColumnMap::default(),
+            fee_rate: 0.0,
+            slippage_bps: 0.0,
+            max_volume_share: None,
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for BacktestParams {}
+
+/// Runs an event-driven strategy over a price history
+///
+/// For every bar the exchange first fills the working orders, the strategy
+/// is told of each fill and then of the bar itself. Bars with a missing
+/// timestamp or close are skipped.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with the time column and the open, high, low and
+///   close columns; volume is only needed with `max_volume_share`
+/// * `params` - Exchange settings and column names
+/// * `strategy` - The strategy
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing StrategySignals where the signals
+/// count the fills per bar, trades are matched from the fills first in,
+/// first out, and `indicator_values` holds a "backtest_position" column with
+/// the signed quantity held at each close (key) and a "backtest_open_orders"
+/// column with the number of working orders
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::backtest::{run_backtest, BacktestParams, BarContext, EventStrategy, Fill};
+/// use rustalib::backtest::{OrderSide, OrderType};
+///
+/// /// Buy at the open, then sell at a limit 2.0 above the fill
+/// struct TakeProfit {
+///     entered: bool,
+/// }
+///
+/// impl EventStrategy for TakeProfit {
+///     fn on_bar(&mut self, ctx: &mut BarContext<'_>) {
+///         if !self.entered {
+///             ctx.exchange.submit(OrderSide::Buy, 10.0, OrderType::Market);
+///             self.entered = true;
+///         }
+///     }
+///
+///     fn on_fill(&mut self, fill: &Fill, ctx: &mut BarContext<'_>) {
+///         if fill.side == OrderSide::Buy {
+///             let target = OrderType::Limit { price: fill.price + 2.0 };
+///             ctx.exchange.submit(OrderSide::Sell, fill.quantity, target);
+///         }
+///     }
+/// }
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"],
+///     "open" => [100.0, 100.5, 101.0, 102.0],
+///     "high" => [101.0, 101.5, 102.0, 103.0],
+///     "low" => [99.0, 100.0, 100.5, 101.5],
+///     "close" => [100.5, 101.0, 101.8, 102.5],
+/// }
+/// .unwrap();
+///
+/// let signals = run_backtest(&df, &BacktestParams::default(), &mut TakeProfit { entered: false }).unwrap();
+/// assert_eq!(signals.buy_signals, [0, 1, 0, 0]);
+/// assert_eq!(signals.sell_signals, [0, 0, 0, 1]);
+/// assert_eq!(signals.trades.len(), 1);
+/// assert_eq!(signals.trades[0].exit_price, 102.5);
+/// assert_eq!(signals.trades[0].pnl, 20.0);
+/// ```
+pub fn run_backtest<S: EventStrategy>(
+    df: &DataFrame,
+    params: &BacktestParams,
+    strategy: &mut S,
+) -> PolarsResult<StrategySignals> {
+    let columns = &params.columns;
+    let timestamps = parse_timestamp_column(df, &params.time_column)?;
+    let [open, high, low, close] = [&columns.open, &columns.high, &columns.low, &columns.close]
+        .map(|name| column_nan_values(df, name));
+    let (open, high, low, close) = (open?, high?, low?, close?);
+    let volume = if df.schema().contains(&columns.volume) {
+        column_nan_values(df, &columns.volume)?
+    } else {
+        vec![f64::NAN; df.height()]
+    };
+
+    let mut exchange =
+        ExchangeSimulator::new(params.fee_rate).with_slippage_bps(params.slippage_bps);
+    if let Some(share) = params.max_volume_share {
+        exchange = exchange.with_max_volume_share(share);
+    }
+
+    let n = df.height();
+    let mut buy_signals = vec![0; n];
+    let mut sell_signals = vec![0; n];
+    let mut position = vec![f64::NAN; n];
+    let mut open_orders = vec![0u32; n];
+    let mut equity = vec![f64::NAN; n];
+    let mut exposure = vec![f64::NAN; n];
+
+    for i in 0..n {
+        let Some(time) = timestamps[i].filter(|_| close[i].is_finite()) else {
+            continue;
+        };
+        let bar = Bar {
+            time,
+            open: open[i],
+            high: high[i],
+            low: low[i],
+            close: close[i],
+            volume: volume[i],
+        };
+
+        let fills = exchange.process_bar(i, &bar);
+        let mut ctx = BarContext {
+            index: i,
+            bar: &bar,
+            exchange: &mut exchange,
+        };
+        for fill in &fills {
+            match fill.side {
+                OrderSide::Buy => buy_signals[i] += 1,
+                OrderSide::Sell => sell_signals[i] += 1,
+            }
+            strategy.on_fill(fill, &mut ctx);
+        }
+        strategy.on_bar(&mut ctx);
+
+        let held = exchange.position();
+        position[i] = held.quantity;
+        open_orders[i] = exchange.open_orders().count() as u32;
+        equity[i] = held.equity(bar.close);
+        exposure[i] = held.quantity.abs() * bar.close;
+    }
+
+    let trades = performance::match_fills(
+        exchange.fills().iter().map(|fill| performance::Fill {
+            time: fill.time,
+            side: match fill.side {
+                OrderSide::Buy => TradeSide::Long,
+                OrderSide::Sell => TradeSide::Short,
+            },
+            quantity: fill.quantity,
+            price: fill.price,
+            fee: fill.fee,
+        }),
+        MatchingMethod::Fifo,
+    );
+
+    let indicator_values = build_indicator_values(
+        df,
+        &params.time_column,
+        &columns.close,
+        vec![
+            (Series::new("backtest_position".into(), position), true),
+            (
+                Series::new("backtest_open_orders".into(), open_orders),
+                false,
+            ),
+        ],
+        params.output_columns,
+    )?;
+    let [equity_curve, drawdown, exposure] = equity_outputs(equity, exposure);
+
+    Ok(StrategySignals {
+        buy_signals,
+        sell_signals,
+        indicator_values,
+        trades,
+        equity_curve,
+        drawdown,
+        exposure,
+    })
+}
@@ -0,0 +1,338 @@
+use crate::backtest::{
+    Bar, BracketOrder, Fill, Order, OrderId, OrderSide, OrderStatus, OrderType, Position,
+};
+
+/// Simulated exchange matching orders against OHLCV bars
+///
+/// Orders work from the bar after the one they were submitted on and are
+/// processed in submission order:
+///
+/// - market orders fill at the open
+/// - limit orders fill at the open when it is already better than the
+///   limit, otherwise at the limit when the bar's range reaches it
+/// - stop orders trigger when the range reaches the stop and fill at the
+///   stop, or at the open when the bar gaps through it; a partly filled
+///   stop keeps filling at later opens
+///
+/// Market and stop fills pay the slippage; limit fills do not. With a
+/// volume share set, the fills of one bar share at most that share of the
+/// bar's volume and the rest of an order stays working.
+///
+/// Bracket orders ([`submit_bracket`](Self::submit_bracket)) add a stop
+/// loss and a target once their entry fills. Both work from the next bar;
+/// when a bar reaches both, the stop fills first.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use rustalib::backtest::{Bar, ExchangeSimulator, OrderSide, OrderStatus, OrderType};
+///
+/// let bar = |open, high, low, close| Bar {
+///     time: Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap(),
+///     open, high, low, close, volume: 1_000.0,
+/// };
+/// let mut exchange = ExchangeSimulator::new(0.0).with_max_volume_share(0.1);
+///
+/// let limit = exchange.submit(OrderSide::Buy, 150.0, OrderType::Limit { price: 99.0 });
+/// assert!(exchange.process_bar(1, &bar(100.0, 101.0, 99.5, 100.5)).is_empty());
+///
+/// // The range reaches the limit, but only 100 shares trade
+/// let fills = exchange.process_bar(2, &bar(100.0, 100.5, 98.5, 99.0));
+/// assert_eq!((fills[0].quantity, fills[0].price), (100.0, 99.0));
+/// assert_eq!(exchange.order(limit).unwrap().status, OrderStatus::PartiallyFilled);
+///
+/// exchange.process_bar(3, &bar(98.0, 99.0, 97.5, 98.5));
+/// assert_eq!(exchange.order(limit).unwrap().status, OrderStatus::Filled);
+/// assert_eq!(exchange.position().quantity, 150.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExchangeSimulator {
+    fee_rate: f64,
+    slippage: f64,
+    max_volume_share: Option<f64>,
+    orders: Vec<Order>,
+    fills: Vec<Fill>,
+    position: Position,
+    next_bar: usize,
+    brackets: Vec<Bracket>,
+}
+
+/// A bracket's entry, exit levels and, once the entry has filled, its exits
+#[derive(Debug, Clone, Copy)]
+struct Bracket {
+    entry: OrderId,
+    stop: f64,
+    target: f64,
+    exits: Option<(OrderId, OrderId)>,
+}
+
+impl ExchangeSimulator {
+    /// Create an exchange charging `fee_rate` of notional per fill, without
+    /// slippage or volume limits
+    pub fn new(fee_rate: f64) -> Self {
+        Self {
+            fee_rate,
+            slippage: 0.0,
+            max_volume_share: None,
+            orders: Vec::new(),
+            fills: Vec::new(),
+            position: Position::default(),
+            next_bar: 0,
+            brackets: Vec::new(),
+        }
+    }
+
+    /// Move market and stop fills against the order by `bps` basis points
+    pub fn with_slippage_bps(mut self, bps: f64) -> Self {
+        self.slippage = bps / 10_000.0;
+        self
+    }
+
+    /// Limit the fills of each bar to `share` of its volume (e.g. 0.1)
+    pub fn with_max_volume_share(mut self, share: f64) -> Self {
+        self.max_volume_share = Some(share);
+        self
+    }
+
+    /// Submit an order, working from the next bar
+    ///
+    /// # Returns
+    ///
+    /// The identifier of the new order
+    pub fn submit(&mut self, side: OrderSide, quantity: f64, order_type: OrderType) -> OrderId {
+        let id = self.orders.len();
+        self.orders.push(Order {
+            id,
+            side,
+            order_type,
+            quantity: quantity.abs(),
+            filled_quantity: 0.0,
+            average_fill_price: f64::NAN,
+            status: OrderStatus::Open,
+            working_from: self.next_bar,
+            triggered: false,
+            linked: None,
+        });
+        id
+    }
+
+    /// Submit a bracket: the entry order now, and once it fills a stop and a
+    /// target limit order for the filled quantity that cancel each other
+    ///
+    /// Later fills of the entry add to the exits; once the exits have closed
+    /// the position, the rest of the entry is cancelled.
+    ///
+    /// # Returns
+    ///
+    /// The identifier of the entry order
+    pub fn submit_bracket(&mut self, bracket: &BracketOrder) -> OrderId {
+        let entry = self.submit(bracket.side, bracket.quantity, bracket.entry);
+        self.brackets.push(Bracket {
+            entry,
+            stop: bracket.stop,
+            target: bracket.target,
+            exits: None,
+        });
+        entry
+    }
+
+    /// The stop and target orders of the bracket entered with `entry`, once
+    /// the entry has filled
+    pub fn bracket_exits(&self, entry: OrderId) -> Option<(OrderId, OrderId)> {
+        self.brackets
+            .iter()
+            .find(|b| b.entry == entry)
+            .and_then(|b| b.exits)
+    }
+
+    /// Cancel the unfilled rest of an order
+    ///
+    /// # Returns
+    ///
+    /// `true` when the order was still working
+    pub fn cancel(&mut self, id: OrderId) -> bool {
+        match self.orders.get_mut(id) {
+            Some(order) if order.is_active() => {
+                order.status = OrderStatus::Cancelled;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Cancel every working order
+    pub fn cancel_all(&mut self) {
+        for order in self.orders.iter_mut().filter(|o| o.is_active()) {
+            order.status = OrderStatus::Cancelled;
+        }
+    }
+
+    /// An order by identifier
+    pub fn order(&self, id: OrderId) -> Option<&Order> {
+        self.orders.get(id)
+    }
+
+    /// All orders in submission order
+    pub fn orders(&self) -> &[Order] {
+        &self.orders
+    }
+
+    /// Orders that can still fill
+    pub fn open_orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.iter().filter(|o| o.is_active())
+    }
+
+    /// All fills so far
+    pub fn fills(&self) -> &[Fill] {
+        &self.fills
+    }
+
+    /// The net position
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Match the working orders against a bar
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the bar; orders submitted before this call work
+    ///   on it, orders submitted after it from the next bar
+    /// * `bar` - Prices and volume of the bar
+    ///
+    /// # Returns
+    ///
+    /// The fills of this bar in order of execution
+    pub fn process_bar(&mut self, index: usize, bar: &Bar) -> Vec<Fill> {
+        let mut capacity = self
+            .max_volume_share
+            .map(|share| share * bar.volume)
+            .filter(|c| c.is_finite())
+            .unwrap_or(f64::INFINITY);
+        let mut fills = Vec::new();
+
+        for k in 0..self.orders.len() {
+            let order = &mut self.orders[k];
+            if !order.is_active() || index < order.working_from {
+                continue;
+            }
+            // A triggered stop fills what is left as a market order
+            let price = if order.triggered {
+                OrderType::Market.fill_price(order.side, bar)
+            } else {
+                order.order_type.fill_price(order.side, bar)
+            };
+            let Some(price) = price else {
+                continue;
+            };
+            order.triggered = matches!(order.order_type, OrderType::Stop { .. });
+            let quantity = order.remaining().min(capacity);
+            if quantity <= 0.0 {
+                continue;
+            }
+            capacity -= quantity;
+
+            let price = if !matches!(order.order_type, OrderType::Limit { .. }) {
+                price * (1.0 + order.side.sign() * self.slippage)
+            } else {
+                price
+            };
+            let filled = order.filled_quantity + quantity;
+            order.average_fill_price = if order.filled_quantity > 0.0 {
+                (order.average_fill_price * order.filled_quantity + price * quantity) / filled
+            } else {
+                price
+            };
+            order.filled_quantity = filled;
+            order.status = if order.remaining() <= 1e-12 {
+                OrderStatus::Filled
+            } else {
+                OrderStatus::PartiallyFilled
+            };
+
+            let fill = Fill {
+                order_id: order.id,
+                bar: index,
+                time: bar.time,
+                side: order.side,
+                quantity,
+                price,
+                fee: price * quantity * self.fee_rate,
+            };
+            let linked = order.linked;
+            self.position.apply(&fill);
+            fills.push(fill);
+
+            if let Some(sibling) = linked.and_then(|id| self.orders.get_mut(id)) {
+                if sibling.is_active() {
+                    sibling.quantity = (sibling.quantity - quantity).max(sibling.filled_quantity);
+                    if sibling.remaining() <= 1e-12 {
+                        sibling.status = OrderStatus::Cancelled;
+                    }
+                }
+            }
+        }
+
+        self.next_bar = index + 1;
+        self.update_brackets(&fills);
+        self.fills.extend(fills.iter().cloned());
+        fills
+    }
+
+    /// Submit or enlarge the exits of brackets whose entry filled, and cancel
+    /// the entries of brackets whose exits have closed the position
+    fn update_brackets(&mut self, fills: &[Fill]) {
+        for fill in fills {
+            let Some(b) = self.brackets.iter().position(|b| {
+                b.entry == fill.order_id
+                    || b.exits.is_some_and(|(stop, target)| {
+                        fill.order_id == stop || fill.order_id == target
+                    })
+            }) else {
+                continue;
+            };
+            let bracket = self.brackets[b];
+            let working = bracket.exits.filter(|&(stop, target)| {
+                self.orders[stop].is_active() || self.orders[target].is_active()
+            });
+
+            if fill.order_id != bracket.entry {
+                if working.is_none() {
+                    self.cancel(bracket.entry);
+                }
+                continue;
+            }
+            match working {
+                Some((stop, target)) => {
+                    for id in [stop, target] {
+                        let order = &mut self.orders[id];
+                        if order.is_active() {
+                            order.quantity += fill.quantity;
+                        }
+                    }
+                }
+                None => {
+                    let side = fill.side.opposite();
+                    let stop = self.submit(
+                        side,
+                        fill.quantity,
+                        OrderType::Stop {
+                            price: bracket.stop,
+                        },
+                    );
+                    let target = self.submit(
+                        side,
+                        fill.quantity,
+                        OrderType::Limit {
+                            price: bracket.target,
+                        },
+                    );
+                    self.orders[stop].linked = Some(target);
+                    self.orders[target].linked = Some(stop);
+                    self.brackets[b].exits = Some((stop, target));
+                }
+            }
+        }
+    }
+}
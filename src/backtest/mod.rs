@@ -0,0 +1,27 @@
+//! # Event-Driven Backtesting
+//!
+//! The strategies in [`strategy`](crate::strategy) decide on each bar from
+//! signal vectors. This module simulates trading order by order instead, for
+//! strategies whose logic depends on the state of their orders: scaling in
+//! and out, resting limit orders, stops and linked exits.
+//!
+//! - [`Order`], [`Fill`] and [`Position`] model orders, their executions and
+//!   the resulting net position
+//! - [`ExchangeSimulator`] fills market, limit and stop orders against OHLCV
+//!   bars, with fees, slippage and partial fills limited by volume
+//! - [`BracketOrder`] links an entry to a stop loss and a target that
+//!   cancel each other; [`run_brackets`] backtests one bracket per signal bar
+//! - [`EventStrategy`] and [`run_backtest`] drive a strategy bar by bar and
+//!   report the run as [`StrategySignals`](crate::strategy::StrategySignals)
+
+mod bracket;
+mod engine;
+mod exchange;
+mod order;
+mod position;
+
+pub use bracket::{bracket_columns, run_brackets, BracketOrder};
+pub use engine::{run_backtest, BacktestParams, Bar, BarContext, EventStrategy};
+pub use exchange::ExchangeSimulator;
+pub use order::{Fill, Order, OrderId, OrderSide, OrderStatus, OrderType};
+pub use position::Position;
@@ -0,0 +1,163 @@
+use crate::backtest::Bar;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Identifier of an order, unique within one [`ExchangeSimulator`](super::ExchangeSimulator)
+pub type OrderId = usize;
+
+/// Direction of an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl OrderSide {
+    /// +1.0 for buys, -1.0 for sells
+    pub fn sign(&self) -> f64 {
+        match self {
+            OrderSide::Buy => 1.0,
+            OrderSide::Sell => -1.0,
+        }
+    }
+
+    /// The side that closes a position opened by this side
+    pub fn opposite(&self) -> Self {
+        match self {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+}
+
+/// How an order is priced
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OrderType {
+    /// Fill at the open of the next bar
+    Market,
+    /// Fill at `price` or better once the bar trades through it
+    Limit { price: f64 },
+    /// Become a market order once the bar trades through `price`: a buy
+    /// stop above the market, a sell stop below it
+    Stop { price: f64 },
+}
+
+impl OrderType {
+    /// Price at which an order of this type fills on `bar`, if it does
+    ///
+    /// Market orders fill at the open. Limit orders fill at the open when
+    /// it is already at or better than the limit, otherwise at the limit
+    /// when the bar's range reaches it. Stop orders fill at the stop when
+    /// the range reaches it, or at the open when the bar gaps through it.
+    /// A missing open is taken to be the close.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use rustalib::backtest::{Bar, OrderSide, OrderType};
+    ///
+    /// let bar = Bar { time: Utc::now(), open: 100.0, high: 103.0, low: 99.0, close: 102.0, volume: f64::NAN };
+    /// assert_eq!(OrderType::Limit { price: 99.5 }.fill_price(OrderSide::Buy, &bar), Some(99.5));
+    /// assert_eq!(OrderType::Limit { price: 98.0 }.fill_price(OrderSide::Buy, &bar), None);
+    /// assert_eq!(OrderType::Stop { price: 101.0 }.fill_price(OrderSide::Buy, &bar), Some(101.0));
+    /// assert_eq!(OrderType::Stop { price: 99.5 }.fill_price(OrderSide::Buy, &bar), Some(100.0));
+    /// ```
+    pub fn fill_price(&self, side: OrderSide, bar: &Bar) -> Option<f64> {
+        let sign = side.sign();
+        let open = if bar.open.is_finite() {
+            bar.open
+        } else {
+            bar.close
+        };
+        let (high, low) = (bar.high.max(open), bar.low.min(open));
+        // The most favorable price of the bar for a limit order of this side
+        // and the most adverse one, which triggers a stop
+        let (best, worst) = if sign > 0.0 { (low, high) } else { (high, low) };
+
+        match *self {
+            OrderType::Market => Some(open),
+            OrderType::Limit { price } if sign * (open - price) <= 0.0 => Some(open),
+            OrderType::Limit { price } if sign * (best - price) <= 0.0 => Some(price),
+            OrderType::Stop { price } if sign * (open - price) >= 0.0 => Some(open),
+            OrderType::Stop { price } if sign * (worst - price) >= 0.0 => Some(price),
+            OrderType::Limit { .. } | OrderType::Stop { .. } => None,
+        }
+    }
+}
+
+/// Life-cycle state of an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    /// Waiting for a fill
+    Open,
+    /// Filled in part, the rest still working
+    PartiallyFilled,
+    /// Completely filled
+    Filled,
+    /// Cancelled before it was completely filled
+    Cancelled,
+}
+
+/// An order working at the exchange
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    /// Identifier assigned on submission
+    pub id: OrderId,
+    /// Buy or sell
+    pub side: OrderSide,
+    /// Market, limit or stop
+    pub order_type: OrderType,
+    /// Quantity ordered
+    pub quantity: f64,
+    /// Quantity filled so far
+    pub filled_quantity: f64,
+    /// Volume-weighted price of the fills so far (NaN before the first)
+    pub average_fill_price: f64,
+    /// Life-cycle state
+    pub status: OrderStatus,
+    /// Index of the first bar the order can fill on, the one after the bar
+    /// it was submitted during
+    pub working_from: usize,
+    /// Stop orders: whether the stop price has been reached
+    pub triggered: bool,
+    /// One-cancels-other sibling, e.g. the target of a bracket's stop; a
+    /// fill of this order reduces the sibling by the same quantity
+    pub linked: Option<OrderId>,
+}
+
+impl Order {
+    /// Quantity still to be filled
+    pub fn remaining(&self) -> f64 {
+        (self.quantity - self.filled_quantity).max(0.0)
+    }
+
+    /// Whether the order can still fill
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.status,
+            OrderStatus::Open | OrderStatus::PartiallyFilled
+        )
+    }
+}
+
+/// An execution of (part of) an order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    /// Order the fill belongs to
+    pub order_id: OrderId,
+    /// Index of the bar the fill happened on
+    pub bar: usize,
+    /// Time of that bar
+    pub time: DateTime<Utc>,
+    /// Buy or sell
+    pub side: OrderSide,
+    /// Quantity filled
+    pub quantity: f64,
+    /// Fill price, including slippage
+    pub price: f64,
+    /// Fee charged for the fill
+    pub fee: f64,
+}
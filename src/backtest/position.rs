@@ -0,0 +1,75 @@
+use crate::backtest::Fill;
+use crate::performance::TradeSide;
+
+/// Net position built up from fills, with average-cost accounting
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Position {
+    /// Signed quantity: positive long, negative short
+    pub quantity: f64,
+    /// Average entry price of the open quantity (0.0 when flat)
+    pub average_price: f64,
+    /// P&L of the quantity closed so far, before fees
+    pub realized_pnl: f64,
+    /// Fees paid so far
+    pub fees: f64,
+}
+
+impl Position {
+    /// Update the position with a fill
+    ///
+    /// Fills against the position realize P&L on the closed quantity at the
+    /// average price; a fill larger than the position reverses it, opening
+    /// the rest at the fill price.
+    pub fn apply(&mut self, fill: &Fill) {
+        let signed = fill.side.sign() * fill.quantity;
+        self.fees += fill.fee;
+
+        if self.quantity == 0.0 || self.quantity.signum() == signed.signum() {
+            let total = self.quantity + signed;
+            self.average_price = (self.average_price * self.quantity.abs()
+                + fill.price * signed.abs())
+                / total.abs();
+            self.quantity = total;
+            return;
+        }
+
+        let closed = signed.abs().min(self.quantity.abs());
+        self.realized_pnl += closed * (fill.price - self.average_price) * self.quantity.signum();
+        let total = self.quantity + signed;
+        if total.abs() < 1e-12 {
+            self.quantity = 0.0;
+            self.average_price = 0.0;
+        } else {
+            if total.signum() != self.quantity.signum() {
+                self.average_price = fill.price;
+            }
+            self.quantity = total;
+        }
+    }
+
+    /// P&L of the open quantity at `price`
+    pub fn unrealized_pnl(&self, price: f64) -> f64 {
+        self.quantity * (price - self.average_price)
+    }
+
+    /// Realized plus unrealized P&L at `price`, after fees
+    pub fn equity(&self, price: f64) -> f64 {
+        self.realized_pnl + self.unrealized_pnl(price) - self.fees
+    }
+
+    /// Direction of the position, `None` when flat
+    pub fn side(&self) -> Option<TradeSide> {
+        if self.quantity > 0.0 {
+            Some(TradeSide::Long)
+        } else if self.quantity < 0.0 {
+            Some(TradeSide::Short)
+        } else {
+            None
+        }
+    }
+
+    /// Whether no quantity is open
+    pub fn is_flat(&self) -> bool {
+        self.quantity == 0.0
+    }
+}
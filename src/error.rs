@@ -0,0 +1,88 @@
+//! # Errors
+//!
+//! [`TaError`] classifies the failures of this crate so callers can react to
+//! them programmatically instead of matching on message strings.
+//!
+//! Indicator and strategy functions return `PolarsResult`, as they are built
+//! on Polars operations. `TaError` converts to and from `PolarsError` in both
+//! directions: a missing column travels as `PolarsError::ColumnNotFound`, so
+//! `TaError::from` recovers [`TaError::MissingColumn`] from any function, and
+//! `?` turns a `TaError` into a `PolarsError` inside functions returning
+//! `PolarsResult`.
+//!
+//! ```
+//! use polars::prelude::*;
+//! use rustalib::indicators::moving_averages::calculate_sma;
+//! use rustalib::TaError;
+//!
+//! let df = df! { "close" => [1.0, 2.0, 3.0] }.unwrap();
+//!
+//! let err = TaError::from(calculate_sma(&df, "adj_close", 2).unwrap_err());
+//! assert!(matches!(err, TaError::MissingColumn(column) if column.contains("adj_close")));
+//! ```
+
+use crate::util::validation::ValidationReport;
+use polars::prelude::*;
+use thiserror::Error;
+
+/// Result type for operations failing with [`TaError`]
+pub type TaResult<T> = Result<T, TaError>;
+
+/// Failure kinds of indicator calculations, strategies and utilities
+#[derive(Debug, Error)]
+pub enum TaError {
+    /// A required column is absent from the input
+    #[error("missing column '{0}'")]
+    MissingColumn(String),
+    /// Fewer rows than a calculation needs
+    #[error("not enough data for {indicator}: needs {needed} rows, got {got}")]
+    InsufficientData {
+        indicator: String,
+        needed: usize,
+        got: usize,
+    },
+    /// A parameter or configuration value outside its valid range
+    #[error("invalid parameter '{name}': {reason}")]
+    InvalidParameter { name: String, reason: String },
+    /// Input data failing validation
+    #[error(transparent)]
+    InvalidData(#[from] ValidationReport),
+    /// Reading or writing a file failed
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A calculation failed for a reason not covered above
+    #[error("computation error: {0}")]
+    ComputationError(String),
+    /// An error raised by Polars
+    #[error(transparent)]
+    Polars(PolarsError),
+}
+
+impl TaError {
+    /// Shorthand for [`TaError::InvalidParameter`]
+    pub fn invalid_parameter(name: impl Into<String>, reason: impl Into<String>) -> Self {
+        TaError::InvalidParameter {
+            name: name.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl From<PolarsError> for TaError {
+    fn from(err: PolarsError) -> Self {
+        match err {
+            PolarsError::ColumnNotFound(column) => TaError::MissingColumn(column.to_string()),
+            err => TaError::Polars(err),
+        }
+    }
+}
+
+impl From<TaError> for PolarsError {
+    fn from(err: TaError) -> Self {
+        match err {
+            TaError::MissingColumn(column) => PolarsError::ColumnNotFound(column.into()),
+            TaError::Polars(err) => err,
+            err => PolarsError::ComputeError(err.to_string().into()),
+        }
+    }
+}
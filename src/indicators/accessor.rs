@@ -0,0 +1,207 @@
+//! # DataFrame Accessor
+//!
+//! [`TaDataFrameExt`] adds a `ta()` namespace to DataFrames, in the style of
+//! pandas-ta: each method computes one indicator, appends its columns to the
+//! DataFrame in place and returns the names of the added columns.
+//!
+//! Prices are read from the standard "open", "high", "low", "close" and
+//! "volume" columns. Output columns are named through a [`ColumnNaming`]
+//! policy ("sma_20", "macd_signal_12_26_9" by default); a column that already
+//! has the generated name is replaced.
+//!
+//! ## Example
+//!
+//! ```
+//! use polars::prelude::*;
+//! use rustalib::indicators::TaDataFrameExt;
+//!
+//! let close: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 / 5.0).sin()).collect();
+//! let mut df = df! { "close" => &close }.unwrap();
+//!
+//! let sma = df.ta().sma("close", 20).unwrap();
+//! let rsi = df.ta().rsi(14).unwrap();
+//! let macd = df.ta().macd(12, 26, 9).unwrap();
+//!
+//! assert_eq!(sma, "sma_20");
+//! assert_eq!(rsi, "rsi_14");
+//! assert_eq!(
+//!     macd,
+//!     ["macd_12_26_9", "macd_signal_12_26_9", "macd_histogram_12_26_9"]
+//! );
+//! assert_eq!(df.width(), 6);
+//! ```
+
+use crate::indicators::momentum::calculate_roc;
+use crate::indicators::moving_averages::{calculate_ema, calculate_sma, calculate_wma};
+use crate::indicators::naming::ColumnNaming;
+use crate::indicators::oscillators::{calculate_macd, calculate_rsi, calculate_stochastic};
+use crate::indicators::volatility::{calculate_atr, calculate_bollinger_bands};
+use crate::indicators::volume::calculate_obv;
+use polars::prelude::*;
+use std::fmt::Display;
+
+/// Adds the [`Ta`] indicator namespace to [`DataFrame`]
+pub trait TaDataFrameExt {
+    /// Indicator namespace appending columns to this DataFrame
+    fn ta(&mut self) -> Ta<'_>;
+}
+
+impl TaDataFrameExt for DataFrame {
+    fn ta(&mut self) -> Ta<'_> {
+        Ta {
+            df: self,
+            naming: ColumnNaming::default(),
+        }
+    }
+}
+
+/// Indicator namespace of a DataFrame, created by [`TaDataFrameExt::ta`]
+pub struct Ta<'a> {
+    df: &'a mut DataFrame,
+    naming: ColumnNaming,
+}
+
+impl Ta<'_> {
+    /// Name the appended columns with `naming`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use polars::prelude::*;
+    /// use rustalib::indicators::naming::{ColumnNaming, NamingConvention};
+    /// use rustalib::indicators::TaDataFrameExt;
+    ///
+    /// let mut df = df! { "close" => (0..30).map(f64::from).collect::<Vec<_>>() }.unwrap();
+    /// let naming = ColumnNaming {
+    ///     convention: NamingConvention::ScreamingSnake,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(df.ta().with_naming(naming).ema("close", 10).unwrap(), "EMA_10");
+    /// ```
+    pub fn with_naming(mut self, naming: ColumnNaming) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Simple moving average of `column`, named "sma_{period}"
+    pub fn sma(self, column: &str, period: usize) -> PolarsResult<String> {
+        let sma = calculate_sma(self.df, column, period)?;
+        self.append_one(sma, "sma", &[&period])
+    }
+
+    /// Exponential moving average of `column`, named "ema_{period}"
+    pub fn ema(self, column: &str, period: usize) -> PolarsResult<String> {
+        let ema = calculate_ema(self.df, column, period)?;
+        self.append_one(ema, "ema", &[&period])
+    }
+
+    /// Weighted moving average of `column`, named "wma_{period}"
+    pub fn wma(self, column: &str, period: usize) -> PolarsResult<String> {
+        let wma = calculate_wma(self.df, column, period)?;
+        self.append_one(wma, "wma", &[&period])
+    }
+
+    /// Relative Strength Index of the close, named "rsi_{period}"
+    pub fn rsi(self, period: usize) -> PolarsResult<String> {
+        let rsi = calculate_rsi(self.df, period, "close")?;
+        self.append_one(rsi, "rsi", &[&period])
+    }
+
+    /// Rate of change of the close, named "roc_{period}"
+    pub fn roc(self, period: usize) -> PolarsResult<String> {
+        let roc = calculate_roc(self.df, period, "close")?;
+        self.append_one(roc, "roc", &[&period])
+    }
+
+    /// MACD line, signal line and histogram of the close
+    ///
+    /// # Returns
+    ///
+    /// The names of the MACD, signal and histogram columns
+    pub fn macd(self, fast: usize, slow: usize, signal: usize) -> PolarsResult<Vec<String>> {
+        let macd = calculate_macd(self.df, fast, slow, signal, "close")?;
+        self.append(
+            vec![macd.macd, macd.signal, macd.histogram],
+            &["macd", "macd_signal", "macd_histogram"],
+            &[&fast, &slow, &signal],
+        )
+    }
+
+    /// Bollinger Bands of the close
+    ///
+    /// `num_std` appears in the names in compact form: bands two standard
+    /// deviations wide around a 20-bar mean are "bb_middle_20_2",
+    /// "bb_upper_20_2" and "bb_lower_20_2".
+    ///
+    /// # Returns
+    ///
+    /// The names of the middle, upper and lower band columns
+    pub fn bbands(self, period: usize, num_std: f64) -> PolarsResult<Vec<String>> {
+        let bands = calculate_bollinger_bands(self.df, period, num_std, "close")?;
+        let num_std = num_std.to_string().replace('.', "_");
+        self.append(
+            vec![bands.middle, bands.upper, bands.lower],
+            &["bb_middle", "bb_upper", "bb_lower"],
+            &[&period, &num_std],
+        )
+    }
+
+    /// Average True Range from the high, low and close, named "atr_{period}"
+    pub fn atr(self, period: usize) -> PolarsResult<String> {
+        let atr = calculate_atr(self.df, period)?;
+        self.append_one(atr, "atr", &[&period])
+    }
+
+    /// Stochastic %K and %D from the high, low and close
+    ///
+    /// # Returns
+    ///
+    /// The names of the %K and %D columns
+    pub fn stoch(
+        self,
+        k_period: usize,
+        d_period: usize,
+        slowing: usize,
+    ) -> PolarsResult<Vec<String>> {
+        let (k, d) = calculate_stochastic(self.df, k_period, d_period, slowing)?;
+        self.append(
+            vec![k, d],
+            &["stoch_k", "stoch_d"],
+            &[&k_period, &slowing, &d_period],
+        )
+    }
+
+    /// On-Balance Volume from the close and volume, named "obv"
+    pub fn obv(self) -> PolarsResult<String> {
+        let obv = calculate_obv(self.df)?;
+        self.append_one(obv, "obv", &[])
+    }
+
+    /// Append one series under the canonical name of `indicator`
+    fn append_one(
+        self,
+        series: Series,
+        indicator: &str,
+        params: &[&dyn Display],
+    ) -> PolarsResult<String> {
+        Ok(self.append(vec![series], &[indicator], params)?.remove(0))
+    }
+
+    /// Append series under the names of `indicators`, which share the
+    /// parameters `params`, with the naming policy applied
+    fn append(
+        self,
+        series: Vec<Series>,
+        indicators: &[&str],
+        params: &[&dyn Display],
+    ) -> PolarsResult<Vec<String>> {
+        let mut names = Vec::with_capacity(series.len());
+        for (series, indicator) in series.into_iter().zip(indicators) {
+            let name = self.naming.name(indicator, params);
+            self.df
+                .with_column(series.with_name(name.as_str().into()))?;
+            names.push(name);
+        }
+        Ok(names)
+    }
+}
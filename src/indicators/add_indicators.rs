@@ -1,3 +1,6 @@
+use crate::error::{TaError, TaResult};
+use crate::indicators::naming::ColumnNaming;
+use crate::indicators::spec::IndicatorSpec;
 use crate::indicators::{
     moving_averages::{calculate_ema, calculate_sma},
     oscillators::{calculate_macd, calculate_rsi},
@@ -5,9 +8,12 @@ use crate::indicators::{
         calculate_atr, calculate_bb_b, calculate_bollinger_bands, calculate_gk_volatility,
     },
 };
-use crate::util::dataframe_utils::ensure_f64_column;
+use crate::util::column_map::ColumnMap;
+use crate::util::dataframe_utils::{ensure_f64_column, fixed_window};
 use crate::util::time_utils::create_cyclical_time_features;
 use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 /// Adds all technical indicators to the DataFrame
 ///
@@ -19,9 +25,181 @@ use polars::prelude::*;
 ///
 /// Returns a PolarsResult containing the enhanced DataFrame
 pub fn add_technical_indicators(df: &mut DataFrame) -> PolarsResult<DataFrame> {
+    add_technical_indicators_with_columns(df, &ColumnMap::default())
+}
+
+/// Adds all technical indicators to a DataFrame with non-standard OHLCV names
+///
+/// Same output as [`add_technical_indicators`], reading prices from the
+/// columns named in `columns`. Input columns keep their names.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame to add indicators to
+/// * `columns` - Names of the OHLCV columns in `df`
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the enhanced DataFrame
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::add_technical_indicators_with_columns;
+/// use rustalib::util::column_map::ColumnMap;
+///
+/// let close: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 / 5.0).sin()).collect();
+/// let mut df = df! {
+///     "Open" => &close,
+///     "High" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "Low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "Adj Close" => &close,
+/// }
+/// .unwrap();
+///
+/// let columns = ColumnMap::detect(&df);
+/// let result = add_technical_indicators_with_columns(&mut df, &columns).unwrap();
+/// assert!(result.column("rsi_14").is_ok());
+/// assert!(result.column("Adj Close").is_ok());
+/// ```
+pub fn add_technical_indicators_with_columns(
+    df: &mut DataFrame,
+    columns: &ColumnMap,
+) -> PolarsResult<DataFrame> {
+    add_technical_indicators_with_config(
+        df,
+        &IndicatorConfig::default().with_columns(columns.clone()),
+    )
+}
+
+/// Adds the indicators selected in an [`IndicatorConfig`] to the DataFrame
+///
+/// The enabled groups are added as in [`add_technical_indicators`], followed
+/// by the columns of `config.indicators` in list order. Output columns of
+/// the listed indicators must not already exist in `df` or be produced
+/// twice, so user data is never overwritten by them.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame to add indicators to
+/// * `config` - Indicator groups, extra indicators and OHLCV column names
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the enhanced DataFrame, or an error
+/// when a listed indicator has a zero period or an output name that is
+/// already taken
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::{add_technical_indicators_with_config, IndicatorConfig, IndicatorSpec};
+///
+/// let close: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 / 5.0).sin()).collect();
+/// let mut df = df! {
+///     "open" => &close,
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+/// }
+/// .unwrap();
+///
+/// let config = IndicatorConfig::none()
+///     .with_indicator(IndicatorSpec::Sma {
+///         name: "sma_10".to_string(),
+///         period: 10,
+///         column: "close".to_string(),
+///     })
+///     .with_indicator(IndicatorSpec::Rsi {
+///         name: "rsi_7".to_string(),
+///         period: 7,
+///         column: "close".to_string(),
+///     });
+/// let result = add_technical_indicators_with_config(&mut df, &config).unwrap();
+/// assert_eq!(result.width(), 6);
+/// assert!(result.column("sma_10").is_ok());
+///
+/// // Listed indicators never overwrite existing columns
+/// assert!(add_technical_indicators_with_config(&mut df, &config).is_err());
+/// ```
+pub fn add_technical_indicators_with_config(
+    df: &mut DataFrame,
+    config: &IndicatorConfig,
+) -> PolarsResult<DataFrame> {
+    add_features(df, config, |tasks| {
+        tasks.iter().map(|task| task()).collect()
+    })
+}
+
+/// Adds the indicators selected in an [`IndicatorConfig`], computing them in
+/// parallel
+///
+/// Same output as [`add_technical_indicators_with_config`]. The indicators
+/// are independent, so each is computed on its own rayon task and the
+/// results are attached in the usual column order. Requires the `parallel`
+/// feature.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame to add indicators to
+/// * `config` - Indicator groups, extra indicators and OHLCV column names
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the enhanced DataFrame
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::{
+///     add_technical_indicators, add_technical_indicators_parallel, IndicatorConfig,
+/// };
+///
+/// let close: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 / 5.0).sin()).collect();
+/// let df = df! {
+///     "open" => &close,
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+/// }
+/// .unwrap();
+///
+/// let config = IndicatorConfig::default();
+/// let parallel = add_technical_indicators_parallel(&mut df.clone(), &config).unwrap();
+/// let sequential = add_technical_indicators(&mut df.clone()).unwrap();
+/// assert!(parallel.equals_missing(&sequential));
+/// ```
+#[cfg(feature = "parallel")]
+pub fn add_technical_indicators_parallel(
+    df: &mut DataFrame,
+    config: &IndicatorConfig,
+) -> PolarsResult<DataFrame> {
+    use rayon::prelude::*;
+
+    add_features(df, config, |tasks| {
+        tasks.par_iter().map(|task| task()).collect()
+    })
+}
+
+/// Computation of one or more feature columns
+type FeatureTask<'a> = Box<dyn Fn() -> PolarsResult<Vec<Series>> + Send + Sync + 'a>;
+
+/// Compute every selected feature with `run` and attach the results to `df`
+///
+/// `run` evaluates the tasks and returns their results in task order.
+fn add_features(
+    df: &mut DataFrame,
+    config: &IndicatorConfig,
+    run: impl FnOnce(&[FeatureTask<'_>]) -> PolarsResult<Vec<Vec<Series>>>,
+) -> PolarsResult<DataFrame> {
+    config.check_indicators(df)?;
+    let columns = &config.columns;
+
     // Convert numeric columns to Float64 by mutating in-place via Column
-    let numeric_columns = ["open", "high", "low", "close", "volume"];
-    for col_name in numeric_columns {
+    for (_, col_name) in columns.pairs() {
         // Skip if column doesn't exist
         if !df.schema().contains(col_name) {
             continue;
@@ -30,28 +208,103 @@ pub fn add_technical_indicators(df: &mut DataFrame) -> PolarsResult<DataFrame> {
         ensure_f64_column(df, col_name)?;
     }
 
-    // Calculate moving averages
-    let sma20 = calculate_sma(df, "close", 20)?.with_name("sma_20".into());
-    let sma50 = calculate_sma(df, "close", 50)?.with_name("sma_50".into());
-    let ema20 = calculate_ema(df, "close", 20)?.with_name("ema_20".into());
-
-    // Calculate oscillators
-    let rsi = calculate_rsi(df, 14, "close")?.with_name("rsi_14".into());
-    let (macd, macd_signal) = calculate_macd(df, 12, 26, 9, "close")?;
-    let macd = macd.with_name("macd".into());
-    let macd_signal = macd_signal.with_name("macd_signal".into());
-
-    // Calculate volatility indicators
-    let (bb_middle, bb_upper, bb_lower) = calculate_bollinger_bands(df, 20, 2.0, "close")?;
-    let bb_middle = bb_middle.with_name("bb_middle".into());
-    let bb_upper = bb_upper.with_name("bb_upper".into());
-    let bb_lower = bb_lower.with_name("bb_lower".into());
-    let bb_b = calculate_bb_b(df, 20, 2.0, "close")?.with_name("bb_b".into());
-    let atr = calculate_atr(df, 14)?.with_name("atr_14".into());
-    let gk_vol = calculate_gk_volatility(df, 10)?.with_name("gk_volatility".into());
-
-    // Calculate price dynamics
-    let close = df.column("close")?.f64()?;
+    // Indicators read the price columns under their standard names
+    let ohlcv = columns.select_ohlcv(df)?;
+    let data = columns.with_standard_names(df)?;
+    let features = {
+        let mut tasks = feature_tasks(config, &ohlcv, df);
+        for spec in &config.indicators {
+            let data = &data;
+            tasks.push(Box::new(move || spec.calculate(data)));
+        }
+        run(&tasks)?
+    };
+
+    for feature in features.into_iter().flatten() {
+        let name = config.naming.format(feature.name());
+        df.with_column(feature.with_name(name.into()))?;
+    }
+
+    Ok(df.clone())
+}
+
+/// The independent computations of the groups enabled in `config`, in
+/// output column order
+///
+/// `data` holds the price columns under their standard names; `input` is the
+/// original DataFrame, read for the optional "time" column.
+fn feature_tasks<'a>(
+    config: &IndicatorConfig,
+    data: &'a DataFrame,
+    input: &'a DataFrame,
+) -> Vec<FeatureTask<'a>> {
+    let named = |series: Series, name: &str| series.with_name(name.into());
+    let mut tasks: Vec<FeatureTask<'a>> = Vec::new();
+
+    if config.moving_averages {
+        tasks.push(Box::new(move || {
+            Ok(vec![named(calculate_sma(data, "close", 20)?, "sma_20")])
+        }));
+        tasks.push(Box::new(move || {
+            Ok(vec![named(calculate_sma(data, "close", 50)?, "sma_50")])
+        }));
+        tasks.push(Box::new(move || {
+            Ok(vec![named(calculate_ema(data, "close", 20)?, "ema_20")])
+        }));
+    }
+
+    if config.oscillators {
+        tasks.push(Box::new(move || {
+            Ok(vec![named(calculate_rsi(data, 14, "close")?, "rsi_14")])
+        }));
+        tasks.push(Box::new(move || {
+            let macd = calculate_macd(data, 12, 26, 9, "close")?;
+            Ok(vec![
+                named(macd.macd, "macd"),
+                named(macd.signal, "macd_signal"),
+            ])
+        }));
+    }
+
+    if config.volatility {
+        tasks.push(Box::new(move || {
+            let bands = calculate_bollinger_bands(data, 20, 2.0, "close")?;
+            Ok(vec![
+                named(bands.middle, "bb_middle"),
+                named(bands.upper, "bb_upper"),
+                named(bands.lower, "bb_lower"),
+            ])
+        }));
+        tasks.push(Box::new(move || {
+            Ok(vec![named(calculate_bb_b(data, 20, 2.0, "close")?, "bb_b")])
+        }));
+        tasks.push(Box::new(move || {
+            Ok(vec![named(calculate_atr(data, 14)?, "atr_14")])
+        }));
+        tasks.push(Box::new(move || {
+            Ok(vec![named(
+                calculate_gk_volatility(data, 10)?,
+                "gk_volatility",
+            )])
+        }));
+    }
+
+    if config.price_features {
+        tasks.push(Box::new(move || price_features(data)));
+    }
+
+    if config.time_features && input.schema().contains("time") {
+        tasks.push(Box::new(move || {
+            create_cyclical_time_features(input, "time", "%Y-%m-%d %H:%M:%S UTC")
+        }));
+    }
+
+    tasks
+}
+
+/// Returns, daily range, close lags and short-term volatility
+fn price_features(data: &DataFrame) -> PolarsResult<Vec<Series>> {
+    let close = data.column("close")?.f64()?;
     let prev_close = close.shift(1);
 
     // Calculate percentage returns
@@ -60,8 +313,8 @@ pub fn add_technical_indicators(df: &mut DataFrame) -> PolarsResult<DataFrame> {
         .into_series();
 
     // Calculate daily price range
-    let high = df.column("high")?.f64()?;
-    let low = df.column("low")?.f64()?;
+    let high = data.column("high")?.f64()?;
+    let low = data.column("low")?.f64()?;
     let price_range = ((high.clone() - low.clone()) / close.clone())
         .with_name("price_range".into())
         .into_series();
@@ -78,8 +331,431 @@ pub fn add_technical_indicators(df: &mut DataFrame) -> PolarsResult<DataFrame> {
         .into_series();
 
     // Shorter-term volatility (15-min window)
-    let mut vol_15min = Vec::with_capacity(df.height());
-    for i in 0..df.height() {
+    let volatility_15min = calculate_volatility_15min(close);
+
+    Ok(vec![
+        returns,
+        price_range,
+        close_lag_5.into_series(),
+        close_lag_15.into_series(),
+        close_lag_30.into_series(),
+        returns_5min,
+        volatility_15min,
+    ])
+}
+
+/// Indicators computed by [`add_technical_indicators_with_config`] and
+/// [`add_technical_indicators_lazy`]
+///
+/// The group flags select the fixed columns of [`add_technical_indicators`];
+/// `indicators` lists further indicators with chosen periods and output
+/// names. Start from [`IndicatorConfig::none`] to compute only the listed
+/// indicators. Every output column is named through `naming`;
+/// [`IndicatorConfig::output_columns`] reports the resulting names.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndicatorConfig {
+    /// sma_20, sma_50 and ema_20
+    pub moving_averages: bool,
+    /// rsi_14, macd and macd_signal
+    pub oscillators: bool,
+    /// Bollinger bands, bb_b, atr_14 and gk_volatility
+    pub volatility: bool,
+    /// returns, price_range, close lags, returns_5min and volatility_15min
+    pub price_features: bool,
+    /// Cyclical hour/day-of-week features from a "time" column, when present
+    pub time_features: bool,
+    /// Additional indicators, added after the groups in list order
+    pub indicators: Vec<IndicatorSpec>,
+    /// Names of the OHLCV columns in the input
+    pub columns: ColumnMap,
+    /// Naming policy applied to every output column
+    pub naming: ColumnNaming,
+}
+
+impl Default for IndicatorConfig {
+    fn default() -> Self {
+        Self {
+            moving_averages: true,
+            oscillators: true,
+            volatility: true,
+            price_features: true,
+            time_features: true,
+            indicators: Vec::new(),
+            columns: ColumnMap::default(),
+            naming: ColumnNaming::default(),
+        }
+    }
+}
+
+impl IndicatorConfig {
+    /// A config with every group disabled and no indicators listed
+    pub fn none() -> Self {
+        Self {
+            moving_averages: false,
+            oscillators: false,
+            volatility: false,
+            price_features: false,
+            time_features: false,
+            ..Self::default()
+        }
+    }
+
+    /// Add an indicator to the list
+    pub fn with_indicator(mut self, indicator: IndicatorSpec) -> Self {
+        self.indicators.push(indicator);
+        self
+    }
+
+    /// Read prices from the columns named in `columns`
+    pub fn with_columns(mut self, columns: ColumnMap) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Name output columns with `naming`
+    pub fn with_naming(mut self, naming: ColumnNaming) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Names of the columns this config adds, in output order
+    ///
+    /// The names have the naming policy applied. Time features are included
+    /// when enabled, although they are only added for input with a "time"
+    /// column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustalib::indicators::naming::{ColumnNaming, NamingConvention};
+    /// use rustalib::indicators::IndicatorConfig;
+    ///
+    /// let config = IndicatorConfig {
+    ///     moving_averages: true,
+    ///     ..IndicatorConfig::none()
+    /// }
+    /// .with_naming(ColumnNaming {
+    ///     convention: NamingConvention::ScreamingSnake,
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(config.output_columns(), ["SMA_20", "SMA_50", "EMA_20"]);
+    /// ```
+    pub fn output_columns(&self) -> Vec<String> {
+        self.group_columns()
+            .into_iter()
+            .map(str::to_string)
+            .chain(self.indicators.iter().flat_map(IndicatorSpec::output_names))
+            .map(|name| self.naming.format(&name))
+            .collect()
+    }
+
+    /// Canonical names of the columns added by the enabled groups
+    fn group_columns(&self) -> Vec<&'static str> {
+        let groups: [(bool, &[&str]); 5] = [
+            (self.moving_averages, &["sma_20", "sma_50", "ema_20"]),
+            (self.oscillators, &["rsi_14", "macd", "macd_signal"]),
+            (
+                self.volatility,
+                &[
+                    "bb_middle",
+                    "bb_upper",
+                    "bb_lower",
+                    "bb_b",
+                    "atr_14",
+                    "gk_volatility",
+                ],
+            ),
+            (
+                self.price_features,
+                &[
+                    "returns",
+                    "price_range",
+                    "close_lag_5",
+                    "close_lag_15",
+                    "close_lag_30",
+                    "returns_5min",
+                    "volatility_15min",
+                ],
+            ),
+            (
+                self.time_features,
+                &["hour_sin", "hour_cos", "day_of_week_sin", "day_of_week_cos"],
+            ),
+        ];
+        groups
+            .into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .flat_map(|(_, names)| names.iter().copied())
+            .collect()
+    }
+
+    /// Reject listed indicators with a zero period or an output column that
+    /// exists in `df` or is produced twice
+    fn check_indicators(&self, df: &DataFrame) -> TaResult<()> {
+        let groups: Vec<String> = self
+            .group_columns()
+            .into_iter()
+            .map(|name| self.naming.format(name))
+            .collect();
+        let mut outputs = HashSet::new();
+        for indicator in &self.indicators {
+            if indicator.periods().contains(&0) {
+                return Err(TaError::invalid_parameter(
+                    "indicators",
+                    format!("indicator '{}' has a zero period", indicator.name()),
+                ));
+            }
+            for name in indicator.output_names() {
+                let name = self.naming.format(&name);
+                if df.schema().contains(&name)
+                    || groups.contains(&name)
+                    || !outputs.insert(name.clone())
+                {
+                    return Err(TaError::invalid_parameter(
+                        "indicators",
+                        format!("column '{name}' already exists"),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adds the selected technical indicators to a LazyFrame
+///
+/// Every indicator is expressed as a column expression, so nothing is
+/// computed until the frame is collected and the query optimizer sees the
+/// whole pipeline. With the default config the collected output matches
+/// [`add_technical_indicators`]. Indicators that need the full series
+/// (rolling windows, recursive smoothing) block predicate pushdown past
+/// this step, so filters applied afterwards never change indicator values.
+/// Columns of `config.indicators` follow the groups; unlike the eager
+/// functions, a listed indicator named like an existing column replaces it.
+///
+/// # Arguments
+///
+/// * `lf` - LazyFrame with "open", "high", "low", "close" (and optionally "volume" and "time") columns,
+///   or the columns named in `config.columns`
+/// * `config` - Indicator groups to compute
+///
+/// # Returns
+///
+/// Returns the LazyFrame with the indicator columns appended
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::{add_technical_indicators_lazy, IndicatorConfig, IndicatorSpec};
+///
+/// let close: Vec<f64> = (0..60).map(|i| 100.0 + i as f64).collect();
+/// let df = df! {
+///     "open" => &close,
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+/// }
+/// .unwrap();
+///
+/// let config = IndicatorConfig {
+///     oscillators: false,
+///     ..Default::default()
+/// }
+/// .with_indicator(IndicatorSpec::Wma {
+///     name: "wma_10".to_string(),
+///     period: 10,
+///     column: "close".to_string(),
+/// });
+/// let result = add_technical_indicators_lazy(df.lazy(), &config)
+///     .filter(col("close").gt(lit(150.0)))
+///     .collect()
+///     .unwrap();
+///
+/// assert_eq!(result.height(), 9);
+/// assert!(result.column("sma_50").is_ok());
+/// assert!(result.column("rsi_14").is_err());
+/// assert!(result.column("wma_10").is_ok());
+/// ```
+pub fn add_technical_indicators_lazy(lf: LazyFrame, config: &IndicatorConfig) -> LazyFrame {
+    // Resolving the schema only plans the query; a failure resurfaces on collect
+    let schema = lf.clone().collect_schema().ok();
+    let has_column = |name: &str| schema.as_ref().is_some_and(|s| s.contains(name));
+
+    let columns = &config.columns;
+    let price = |name: &str| col(columns.resolve(name));
+    let name = |canonical: &str| config.naming.format(canonical);
+
+    let casts: Vec<Expr> = columns
+        .pairs()
+        .into_iter()
+        .filter(|(_, name)| has_column(name))
+        .map(|(_, name)| col(name).cast(DataType::Float64))
+        .collect();
+    let lf = lf.with_columns(casts);
+    let mut exprs = Vec::new();
+
+    if config.moving_averages {
+        exprs.push(
+            price("close")
+                .rolling_mean(fixed_window(20, 20))
+                .alias(name("sma_20")),
+        );
+        exprs.push(
+            price("close")
+                .rolling_mean(fixed_window(50, 50))
+                .alias(name("sma_50")),
+        );
+        exprs.push(eager_indicator(
+            columns,
+            &["close"],
+            &name("ema_20"),
+            |df| calculate_ema(df, "close", 20),
+        ));
+    }
+
+    if config.oscillators {
+        exprs.push(eager_indicator(
+            columns,
+            &["close"],
+            &name("rsi_14"),
+            |df| calculate_rsi(df, 14, "close"),
+        ));
+        exprs.push(eager_indicator(columns, &["close"], &name("macd"), |df| {
+            Ok(calculate_macd(df, 12, 26, 9, "close")?.macd)
+        }));
+        exprs.push(eager_indicator(
+            columns,
+            &["close"],
+            &name("macd_signal"),
+            |df| Ok(calculate_macd(df, 12, 26, 9, "close")?.signal),
+        ));
+    }
+
+    if config.volatility {
+        // Mirrors calculate_bollinger_bands: population std, 0.0 bands during warm-up
+        let middle = price("close").rolling_mean(fixed_window(20, 20));
+        let width = lit(2.0)
+            * price("close").rolling_std(RollingOptionsFixedWindow {
+                fn_params: Some(RollingFnParams::Var(RollingVarParams { ddof: 0 })),
+                ..fixed_window(20, 20)
+            });
+        let upper = (middle.clone() + width.clone()).fill_null(lit(0.0));
+        let lower = (middle.clone() - width).fill_null(lit(0.0));
+        exprs.push(middle.alias(name("bb_middle")));
+        exprs.push(upper.clone().alias(name("bb_upper")));
+        exprs.push(lower.clone().alias(name("bb_lower")));
+        exprs.push(((price("close") - lower.clone()) / (upper - lower)).alias(name("bb_b")));
+        exprs.push(eager_indicator(
+            columns,
+            &["high", "low", "close"],
+            &name("atr_14"),
+            |df| calculate_atr(df, 14),
+        ));
+        exprs.push(eager_indicator(
+            columns,
+            &["open", "high", "low", "close"],
+            &name("gk_volatility"),
+            |df| calculate_gk_volatility(df, 10),
+        ));
+    }
+
+    if config.price_features {
+        let prev_close = price("close").shift(lit(1));
+        let close_lag_5 = price("close").shift(lit(5));
+        exprs.push(((price("close") - prev_close.clone()) / prev_close).alias(name("returns")));
+        exprs.push(((price("high") - price("low")) / price("close")).alias(name("price_range")));
+        exprs.push(close_lag_5.clone().alias(name("close_lag_5")));
+        exprs.push(price("close").shift(lit(15)).alias(name("close_lag_15")));
+        exprs.push(price("close").shift(lit(30)).alias(name("close_lag_30")));
+        exprs.push(
+            ((price("close") - close_lag_5.clone()) / close_lag_5).alias(name("returns_5min")),
+        );
+        exprs.push(eager_indicator(
+            columns,
+            &["close"],
+            &name("volatility_15min"),
+            |df| Ok(calculate_volatility_15min(df.column("close")?.f64()?)),
+        ));
+    }
+
+    if config.time_features && has_column("time") {
+        for (idx, feature) in ["hour_sin", "hour_cos", "day_of_week_sin", "day_of_week_cos"]
+            .into_iter()
+            .enumerate()
+        {
+            exprs.push(
+                col("time")
+                    .apply(
+                        move |time| {
+                            let df = DataFrame::new(vec![time.with_name("time".into())])?;
+                            let features = create_cyclical_time_features(
+                                &df,
+                                "time",
+                                "%Y-%m-%d %H:%M:%S UTC",
+                            )?;
+                            Ok(Some(features[idx].clone().into_column()))
+                        },
+                        GetOutput::from_type(DataType::Float64),
+                    )
+                    .alias(name(feature)),
+            );
+        }
+    }
+
+    for spec in &config.indicators {
+        let inputs = spec.input_columns();
+        for (idx, output) in spec.output_names().iter().enumerate() {
+            let spec = spec.clone();
+            exprs.push(eager_indicator(
+                columns,
+                &inputs,
+                &name(output),
+                move |df| Ok(spec.calculate(df)?.swap_remove(idx)),
+            ));
+        }
+    }
+
+    lf.with_columns(exprs)
+}
+
+/// Wrap an eager indicator function as a deferred whole-column expression
+///
+/// The input columns, given by standard name and read from the columns
+/// `columns` maps them to, are gathered into a DataFrame under their standard
+/// names (cheap, columns are reference counted) and passed to `f` at collect
+/// time.
+fn eager_indicator<F>(columns: &ColumnMap, inputs: &[&str], name: &str, f: F) -> Expr
+where
+    F: Fn(&DataFrame) -> PolarsResult<Series> + Send + Sync + 'static,
+{
+    let names: Vec<PlSmallStr> = inputs.iter().map(|c| (*c).into()).collect();
+    let exprs: Vec<Expr> = inputs.iter().map(|c| col(columns.resolve(c))).collect();
+
+    exprs[0]
+        .clone()
+        .apply_many(
+            move |columns: &mut [Column]| {
+                let df = DataFrame::new(
+                    columns
+                        .iter()
+                        .zip(&names)
+                        .map(|(column, name)| column.clone().with_name(name.clone()))
+                        .collect(),
+                )?;
+                Ok(Some(f(&df)?.into_column()))
+            },
+            &exprs[1..],
+            GetOutput::from_type(DataType::Float64),
+        )
+        .alias(name)
+}
+
+/// Standard deviation of the 15 one-bar returns preceding each bar (0.0 during warm-up)
+fn calculate_volatility_15min(close: &Float64Chunked) -> Series {
+    let mut vol_15min = Vec::with_capacity(close.len());
+    for i in 0..close.len() {
         if i < 15 {
             vol_15min.push(0.0);
             continue;
@@ -111,43 +787,5 @@ pub fn add_technical_indicators(df: &mut DataFrame) -> PolarsResult<DataFrame> {
             returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
         vol_15min.push(variance.sqrt());
     }
-    let volatility_15min = Series::new("volatility_15min".into(), vol_15min);
-
-    // Time-based features
-    let mut time_features = Vec::new();
-    if df.schema().contains("time") {
-        time_features = create_cyclical_time_features(df, "time", "%Y-%m-%d %H:%M:%S UTC")?;
-    }
-
-    // Add all features to the DataFrame
-    let mut features_to_add = vec![
-        sma20,
-        sma50,
-        ema20,
-        rsi,
-        macd,
-        macd_signal,
-        bb_middle,
-        bb_upper,
-        bb_lower,
-        bb_b,
-        atr,
-        gk_vol,
-        returns,
-        price_range,
-        close_lag_5.into_series(),
-        close_lag_15.into_series(),
-        close_lag_30.into_series(),
-        returns_5min,
-        volatility_15min,
-    ];
-
-    // Add time features if available
-    features_to_add.extend(time_features);
-
-    for feature in features_to_add {
-        df.with_column(feature)?;
-    }
-
-    Ok(df.clone())
+    Series::new("volatility_15min".into(), vol_15min)
 }
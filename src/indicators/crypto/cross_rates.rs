@@ -0,0 +1,207 @@
+//! # Cross Rates and Stablecoin Pairs
+//!
+//! Most crypto assets trade against stablecoins, so a cross such as ETH/BTC
+//! is often only available as ETH/USDT and BTC/USDT. These helpers derive a
+//! synthetic cross from two pairs sharing a quote currency, aligned on
+//! common timestamps, and measure how far a stablecoin trades from its peg.
+
+use crate::util::column_map::ColumnMap;
+use crate::util::dataframe_utils::column_nan_values;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// Derives a synthetic cross rate from two pairs with the same quote currency
+///
+/// For ETH/USDT as `base` and BTC/USDT as `quote` the result is ETH/BTC.
+/// Only timestamps present in both inputs are kept, in ascending order.
+///
+/// - open and close are the ratios of the legs' opens and closes
+/// - high and low are the extremes of the open, the close and the ratios of
+///   the legs' highs and of their lows, which assumes the legs peak and
+///   bottom together, as highly correlated crypto assets mostly do
+/// - volume, in units of the base asset, is the smaller of the base leg's
+///   volume and the quote leg's volume converted at the cross close: a
+///   synthetic trade needs both legs, so the thinner one limits it
+///
+/// Open, high, low and volume are only produced when both inputs have the
+/// respective columns.
+///
+/// # Arguments
+///
+/// * `base` - Pair of the cross's base asset, e.g. ETH/USDT
+/// * `quote` - Pair of the cross's quote asset, e.g. BTC/USDT
+/// * `time_column` - Timestamp column of both inputs
+/// * `columns` - OHLCV column names of both inputs, also used for the output
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with a "timestamp" column
+/// and the cross rate's OHLCV columns; prices with a non-positive quote leg
+/// are NaN
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::crypto::calculate_cross_rate;
+/// use rustalib::util::column_map::ColumnMap;
+///
+/// let eth = df! {
+///     "timestamp" => ["2024-01-01 00:00:00", "2024-01-01 00:01:00", "2024-01-01 00:02:00"],
+///     "close" => [2000.0, 2100.0, 2050.0],
+///     "volume" => [10.0, 20.0, 15.0],
+/// }
+/// .unwrap();
+/// let btc = df! {
+///     "timestamp" => ["2024-01-01 00:00:00", "2024-01-01 00:02:00"],
+///     "close" => [40000.0, 41000.0],
+///     "volume" => [1.0, 0.5],
+/// }
+/// .unwrap();
+///
+/// let eth_btc = calculate_cross_rate(&eth, &btc, "timestamp", &ColumnMap::default()).unwrap();
+/// assert_eq!(eth_btc.height(), 2);
+/// let close = eth_btc.column("close").unwrap().f64().unwrap();
+/// assert_eq!(close.get(0), Some(0.05));
+/// // 10 ETH traded on the ETH leg, 20 ETH worth on the BTC leg
+/// assert_eq!(eth_btc.column("volume").unwrap().f64().unwrap().get(0), Some(10.0));
+/// ```
+pub fn calculate_cross_rate(
+    base: &DataFrame,
+    quote: &DataFrame,
+    time_column: &str,
+    columns: &ColumnMap,
+) -> PolarsResult<DataFrame> {
+    let quote_rows: HashMap<DateTime<Utc>, usize> = parse_timestamp_column(quote, time_column)?
+        .into_iter()
+        .enumerate()
+        .filter_map(|(row, time)| Some((time?, row)))
+        .collect();
+    let mut rows: Vec<(DateTime<Utc>, usize, usize)> = parse_timestamp_column(base, time_column)?
+        .into_iter()
+        .enumerate()
+        .filter_map(|(row, time)| {
+            let time = time?;
+            Some((time, row, *quote_rows.get(&time)?))
+        })
+        .collect();
+    rows.sort_by_key(|&(time, _, _)| time);
+    rows.dedup_by_key(|&mut (time, _, _)| time);
+
+    let in_both = |name: &str| base.schema().contains(name) && quote.schema().contains(name);
+    let legs = |name: &str| -> PolarsResult<(Vec<f64>, Vec<f64>)> {
+        let (b, q) = (
+            column_nan_values(base, name)?,
+            column_nan_values(quote, name)?,
+        );
+        Ok(rows.iter().map(|&(_, i, j)| (b[i], q[j])).unzip())
+    };
+    let ratios = |(b, q): (Vec<f64>, Vec<f64>)| -> Vec<f64> {
+        b.iter()
+            .zip(&q)
+            .map(|(b, q)| if *q > 0.0 { b / q } else { f64::NAN })
+            .collect()
+    };
+
+    let close = ratios(legs(&columns.close)?);
+    let open = if in_both(&columns.open) {
+        Some(ratios(legs(&columns.open)?))
+    } else {
+        None
+    };
+
+    let mut output = vec![Column::new(
+        "timestamp".into(),
+        rows.iter()
+            .map(|(time, _, _)| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .collect::<Vec<_>>(),
+    )];
+    if let Some(open) = &open {
+        output.push(Column::new(columns.open.as_str().into(), open));
+    }
+
+    if in_both(&columns.high) && in_both(&columns.low) {
+        let highs = ratios(legs(&columns.high)?);
+        let lows = ratios(legs(&columns.low)?);
+        let extremes = |k: usize| {
+            let open = open.as_ref().map_or(f64::NAN, |open| open[k]);
+            [highs[k], lows[k], open, close[k]]
+        };
+        // f64::max and f64::min skip NaN
+        let high: Vec<f64> = (0..rows.len())
+            .map(|k| extremes(k).into_iter().fold(f64::NAN, f64::max))
+            .collect();
+        let low: Vec<f64> = (0..rows.len())
+            .map(|k| extremes(k).into_iter().fold(f64::NAN, f64::min))
+            .collect();
+        output.push(Column::new(columns.high.as_str().into(), high));
+        output.push(Column::new(columns.low.as_str().into(), low));
+    }
+
+    output.push(Column::new(columns.close.as_str().into(), &close));
+
+    if in_both(&columns.volume) {
+        let (base_volume, quote_volume) = legs(&columns.volume)?;
+        let volume: Vec<f64> = (0..rows.len())
+            .map(|k| {
+                let converted = quote_volume[k] / close[k];
+                if base_volume[k].is_nan() || converted.is_nan() {
+                    f64::NAN
+                } else {
+                    base_volume[k].min(converted)
+                }
+            })
+            .collect();
+        output.push(Column::new(columns.volume.as_str().into(), volume));
+    }
+
+    DataFrame::new(output)
+}
+
+/// Calculates how far a stablecoin trades from its peg
+///
+/// Formula: (price / peg - 1) * 10000
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the stablecoin's price, e.g. USDC/USDT
+/// * `price_column` - Column with the price
+/// * `peg` - Price the coin is pegged at (usually 1.0)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the deviation in basis points named
+/// "peg_deviation_bps"; positive above the peg
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::crypto::calculate_peg_deviation;
+///
+/// let df = df! { "close" => [1.0, 0.995] }.unwrap();
+/// let deviation = calculate_peg_deviation(&df, "close", 1.0).unwrap();
+/// let deviation = deviation.f64().unwrap();
+/// assert_eq!(deviation.get(0), Some(0.0));
+/// assert!((deviation.get(1).unwrap() + 50.0).abs() < 1e-9);
+/// ```
+pub fn calculate_peg_deviation(
+    df: &DataFrame,
+    price_column: &str,
+    peg: f64,
+) -> PolarsResult<Series> {
+    if !(peg > 0.0 && peg.is_finite()) {
+        return Err(PolarsError::ComputeError(
+            "Peg deviation requires a positive peg".into(),
+        ));
+    }
+
+    let deviation: Vec<f64> = column_nan_values(df, price_column)?
+        .into_iter()
+        .map(|price| (price / peg - 1.0) * 10_000.0)
+        .collect();
+
+    Ok(Series::new("peg_deviation_bps".into(), deviation))
+}
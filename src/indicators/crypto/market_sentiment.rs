@@ -0,0 +1,290 @@
+//! # Market Sentiment Indicators
+//!
+//! Turns external sentiment series supplied by the user (funding rates,
+//! social volume, the crypto fear & greed index, ...) into smoothed,
+//! standardized indicator columns and a composite sentiment z-score.
+//! Sentiment data usually arrives on its own schedule, so
+//! [`align_sentiment`] first joins it onto the bar timestamps.
+
+use crate::error::TaError;
+use crate::indicators::stats::rolling_zscore;
+use crate::util::dataframe_utils::column_nan_values;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::Duration;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One external sentiment series
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SentimentSource {
+    /// Name used in the output columns, e.g. "fear_greed"
+    pub name: String,
+    /// Column with the raw values
+    pub column: String,
+    /// Weight in the composite; a negative weight counts the series
+    /// contrarian (high values bearish)
+    pub weight: f64,
+}
+
+impl SentimentSource {
+    /// Create a source
+    pub fn new(name: impl Into<String>, column: impl Into<String>, weight: f64) -> Self {
+        Self {
+            name: name.into(),
+            column: column.into(),
+            weight,
+        }
+    }
+}
+
+/// Smoothed and standardized sentiment indicators with a composite score
+///
+/// For every source, the raw values are smoothed with an EMA of
+/// `smoothing_span` bars ("sentiment_{name}") and standardized against the
+/// trailing `zscore_window` bars ("sentiment_{name}_z"). The composite
+/// "sentiment_composite_z" is the weighted mean of the z-scores available
+/// on each bar, divided by the sum of their absolute weights, so it stays
+/// on the z-score scale.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::crypto::market_sentiment::SentimentIndicators;
+///
+/// let df = df! {
+///     "fear_greed" => [10.0, 20.0, 30.0, 60.0],
+///     "funding" => [0.01, 0.01, 0.02, 0.03],
+/// }
+/// .unwrap();
+///
+/// let sentiment = SentimentIndicators {
+///     smoothing_span: 1,
+///     zscore_window: 3,
+///     ..Default::default()
+/// }
+/// .with_source("fear_greed", "fear_greed", 1.0)
+/// .with_source("funding", "funding", 1.0);
+///
+/// let columns = sentiment.calculate(&df).unwrap();
+/// let names: Vec<_> = columns.iter().map(|s| s.name().as_str()).collect();
+/// assert_eq!(names[..2], ["sentiment_fear_greed", "sentiment_fear_greed_z"]);
+///
+/// // 30 against 10, 20, 30: mean 20, standard deviation 10
+/// assert_eq!(columns[1].f64().unwrap().get(2), Some(1.0));
+/// let composite = columns[4].f64().unwrap().get(2).unwrap();
+/// assert!(composite > 1.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SentimentIndicators {
+    /// Series to combine
+    pub sources: Vec<SentimentSource>,
+    /// Span of the EMA smoothing the raw values; 1 for none
+    pub smoothing_span: usize,
+    /// Trailing bars the smoothed values are standardized against (at least 2)
+    pub zscore_window: usize,
+}
+
+impl Default for SentimentIndicators {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            smoothing_span: 3,
+            zscore_window: 30,
+        }
+    }
+}
+
+impl SentimentIndicators {
+    /// Add a source
+    pub fn with_source(
+        mut self,
+        name: impl Into<String>,
+        column: impl Into<String>,
+        weight: f64,
+    ) -> Self {
+        self.sources
+            .push(SentimentSource::new(name, column, weight));
+        self
+    }
+
+    /// Calculates the indicators
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - DataFrame with one column per source, aligned to the bars
+    ///   (see [`align_sentiment`]); missing values are skipped by the EMA
+    ///
+    /// # Returns
+    ///
+    /// Returns a PolarsResult containing "sentiment_{name}" and
+    /// "sentiment_{name}_z" for each source in order, then
+    /// "sentiment_composite_z"
+    pub fn calculate(&self, df: &DataFrame) -> PolarsResult<Vec<Series>> {
+        if self.sources.is_empty() {
+            return Err(TaError::invalid_parameter("sources", "must not be empty").into());
+        }
+        if self.smoothing_span == 0 {
+            return Err(TaError::invalid_parameter("smoothing_span", "must be positive").into());
+        }
+        if self.zscore_window < 2 {
+            return Err(TaError::invalid_parameter("zscore_window", "must be at least 2").into());
+        }
+
+        let n = df.height();
+        let mut columns = Vec::with_capacity(2 * self.sources.len() + 1);
+        let mut weighted = vec![0.0; n];
+        let mut weights = vec![0.0; n];
+
+        for source in &self.sources {
+            let raw = column_nan_values(df, &source.column)?;
+            let smoothed = ema(&raw, self.smoothing_span);
+            let zscores = rolling_zscore(&smoothed, self.zscore_window);
+
+            for (i, z) in zscores.iter().enumerate() {
+                if z.is_finite() {
+                    weighted[i] += source.weight * z;
+                    weights[i] += source.weight.abs();
+                }
+            }
+            columns.push(Series::new(
+                format!("sentiment_{}", source.name).into(),
+                smoothed,
+            ));
+            columns.push(Series::new(
+                format!("sentiment_{}_z", source.name).into(),
+                zscores,
+            ));
+        }
+
+        let composite: Vec<f64> = weighted
+            .iter()
+            .zip(&weights)
+            .map(|(sum, weight)| {
+                if *weight > 0.0 {
+                    sum / weight
+                } else {
+                    f64::NAN
+                }
+            })
+            .collect();
+        columns.push(Series::new("sentiment_composite_z".into(), composite));
+        Ok(columns)
+    }
+}
+
+/// EMA that skips missing values, NaN until the first value and on gaps
+fn ema(values: &[f64], span: usize) -> Vec<f64> {
+    let alpha = 2.0 / (span as f64 + 1.0);
+    let mut state = f64::NAN;
+    values
+        .iter()
+        .map(|&v| {
+            if v.is_nan() {
+                return f64::NAN;
+            }
+            state = if state.is_nan() {
+                v
+            } else {
+                alpha * v + (1.0 - alpha) * state
+            };
+            state
+        })
+        .collect()
+}
+
+/// Joins sentiment observations onto bar timestamps
+///
+/// Each bar takes the latest observation at or before its timestamp, so no
+/// bar sees sentiment published after it. Observations older than
+/// `max_age` are treated as missing.
+///
+/// # Arguments
+///
+/// * `bars` - DataFrame with the bars (e.g. OHLCV)
+/// * `bar_time` - Timestamp column of `bars`
+/// * `sentiment` - DataFrame with the sentiment observations, in any order
+/// * `sentiment_time` - Timestamp column of `sentiment`
+/// * `max_age` - Oldest observation a bar may use, `None` for no limit
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing `bars` with every other column of
+/// `sentiment` appended as Float64, or InvalidParameter when a sentiment
+/// column name is already used by `bars`
+///
+/// # Example
+///
+/// ```
+/// use chrono::Duration;
+/// use polars::prelude::*;
+/// use rustalib::indicators::crypto::market_sentiment::align_sentiment;
+///
+/// let bars = df! {
+///     "timestamp" => ["2024-01-01 12:00:00", "2024-01-02 12:00:00", "2024-01-05 12:00:00"],
+///     "close" => [42_000.0, 43_000.0, 44_000.0],
+/// }
+/// .unwrap();
+/// let fear_greed = df! {
+///     "date" => ["2024-01-02", "2024-01-01"],
+///     "fear_greed" => [71.0, 65.0],
+/// }
+/// .unwrap();
+///
+/// let aligned = align_sentiment(&bars, "timestamp", &fear_greed, "date", Some(Duration::days(2))).unwrap();
+/// let values = aligned.column("fear_greed").unwrap().f64().unwrap();
+/// assert_eq!(values.get(0), Some(65.0));
+/// assert_eq!(values.get(1), Some(71.0));
+/// // The last observation is three days old
+/// assert_eq!(values.get(2), None);
+/// ```
+pub fn align_sentiment(
+    bars: &DataFrame,
+    bar_time: &str,
+    sentiment: &DataFrame,
+    sentiment_time: &str,
+    max_age: Option<Duration>,
+) -> PolarsResult<DataFrame> {
+    let bar_times = parse_timestamp_column(bars, bar_time)?;
+    let observed = parse_timestamp_column(sentiment, sentiment_time)?;
+
+    let mut order: Vec<usize> = (0..sentiment.height())
+        .filter(|&i| observed[i].is_some())
+        .collect();
+    order.sort_by_key(|&i| observed[i]);
+
+    let rows: Vec<Option<IdxSize>> = bar_times
+        .iter()
+        .map(|time| {
+            let time = (*time)?;
+            let latest = order.partition_point(|&i| observed[i] <= Some(time));
+            let row = order[latest.checked_sub(1)?];
+            let age = time - observed[row]?;
+            max_age
+                .is_none_or(|max_age| age <= max_age)
+                .then_some(row as IdxSize)
+        })
+        .collect();
+    let rows = IdxCa::from_iter_options("rows".into(), rows.into_iter());
+
+    let mut aligned = bars.clone();
+    for column in sentiment.get_columns() {
+        if column.name().as_str() == sentiment_time {
+            continue;
+        }
+        if bars.schema().contains(column.name()) {
+            return Err(TaError::invalid_parameter(
+                column.name().as_str(),
+                "is already a column of the bars",
+            )
+            .into());
+        }
+        let values = column
+            .as_materialized_series()
+            .cast(&DataType::Float64)?
+            .take(&rows)?;
+        aligned.with_column(values)?;
+    }
+    Ok(aligned)
+}
@@ -0,0 +1,21 @@
+//! # Crypto Market Indicators
+//!
+//! This module provides indicators specialized for crypto markets.
+//!
+//! ## Available Indicator Groups
+//!
+//! - [`cross_rates`](cross_rates/index.html): Synthetic cross rates from stablecoin-quoted pairs and peg deviation
+//! - [`market_sentiment`](market_sentiment/index.html): Smoothed and standardized external sentiment series with a composite score
+//! - [`perpetuals`](perpetuals/index.html): Funding, basis, open interest and leverage indicators for perpetual futures
+
+pub mod cross_rates;
+pub mod market_sentiment;
+pub mod perpetuals;
+
+// Re-export common functions for convenient access
+pub use cross_rates::{calculate_cross_rate, calculate_peg_deviation};
+pub use market_sentiment::{align_sentiment, SentimentIndicators, SentimentSource};
+pub use perpetuals::{
+    calculate_basis, calculate_funding_accumulation, calculate_leverage_ratio,
+    calculate_open_interest_change, generate_funding_signals, generate_open_interest_signals,
+};
@@ -0,0 +1,282 @@
+//! # Perpetual Futures Indicators
+//!
+//! Indicators computed from perpetual swap market data supplied by the user
+//! (funding rates, perpetual and spot prices, open interest and exchange
+//! reserves), plus signal helpers built on top of them.
+
+use crate::util::dataframe_utils::check_window_size;
+use polars::prelude::*;
+
+/// Calculates accumulated funding over a rolling window
+///
+/// Summing funding rates shows the carry paid by longs (positive) or shorts
+/// (negative) over the window, which is a gauge of crowded positioning.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing funding rate data
+/// * `funding_column` - Column with per-interval funding rates (e.g. 0.0001 = 0.01%)
+/// * `window` - Number of funding intervals to sum, or 0 for a cumulative sum
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the accumulated funding Series named
+/// "funding_accum_{window}" (or "funding_accum" when cumulative)
+pub fn calculate_funding_accumulation(
+    df: &DataFrame,
+    funding_column: &str,
+    window: usize,
+) -> PolarsResult<Series> {
+    check_window_size(df, window, "Funding accumulation")?;
+
+    let funding = df.column(funding_column)?.f64()?;
+    let mut values = Vec::with_capacity(df.height());
+
+    if window == 0 {
+        let mut total = 0.0;
+        for rate in funding.into_iter() {
+            total += rate.filter(|r| !r.is_nan()).unwrap_or(0.0);
+            values.push(total);
+        }
+        return Ok(Series::new("funding_accum".into(), values));
+    }
+
+    let mut sum = 0.0;
+    for i in 0..df.height() {
+        sum += funding.get(i).filter(|r| !r.is_nan()).unwrap_or(0.0);
+        if i >= window {
+            sum -= funding
+                .get(i - window)
+                .filter(|r| !r.is_nan())
+                .unwrap_or(0.0);
+        }
+        values.push(if i + 1 >= window { sum } else { f64::NAN });
+    }
+
+    Ok(Series::new(
+        format!("funding_accum_{window}").into(),
+        values,
+    ))
+}
+
+/// Calculates the basis of a perpetual (or futures) contract against spot
+///
+/// Formula: (perp - spot) / spot * 100
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing aligned perpetual and spot prices
+/// * `perp_column` - Column with perpetual contract prices
+/// * `spot_column` - Column with spot prices
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the basis Series in percent named "basis_pct"
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::crypto::calculate_basis;
+///
+/// let df = df! {
+///     "perp" => [101.0, 99.5],
+///     "spot" => [100.0, 100.0],
+/// }
+/// .unwrap();
+///
+/// let basis = calculate_basis(&df, "perp", "spot").unwrap();
+/// assert_eq!(basis.f64().unwrap().get(0), Some(1.0));
+/// ```
+pub fn calculate_basis(
+    df: &DataFrame,
+    perp_column: &str,
+    spot_column: &str,
+) -> PolarsResult<Series> {
+    if !df.schema().contains(perp_column) || !df.schema().contains(spot_column) {
+        return Err(PolarsError::ComputeError(
+            format!("Basis calculation requires {perp_column} and {spot_column} columns").into(),
+        ));
+    }
+
+    let perp = df.column(perp_column)?.f64()?;
+    let spot = df.column(spot_column)?.f64()?;
+
+    let basis: Vec<f64> = perp
+        .into_iter()
+        .zip(spot)
+        .map(|(p, s)| match (p, s) {
+            (Some(p), Some(s)) if s != 0.0 => (p - s) / s * 100.0,
+            _ => f64::NAN,
+        })
+        .collect();
+
+    Ok(Series::new("basis_pct".into(), basis))
+}
+
+/// Calculates the percentage change in open interest
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing open interest data
+/// * `oi_column` - Column with open interest (contracts or notional)
+/// * `period` - Number of bars to compare against
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the open interest change Series in
+/// percent named "oi_change_{period}"
+pub fn calculate_open_interest_change(
+    df: &DataFrame,
+    oi_column: &str,
+    period: usize,
+) -> PolarsResult<Series> {
+    check_window_size(df, period + 1, "Open interest change")?;
+
+    let oi = df.column(oi_column)?.f64()?;
+    let prev_oi = oi.shift(period as i64);
+
+    let change: Vec<f64> = oi
+        .into_iter()
+        .zip(&prev_oi)
+        .map(|(current, prev)| match (current, prev) {
+            (Some(c), Some(p)) if p != 0.0 => (c / p - 1.0) * 100.0,
+            _ => f64::NAN,
+        })
+        .collect();
+
+    Ok(Series::new(format!("oi_change_{period}").into(), change))
+}
+
+/// Calculates the estimated leverage ratio
+///
+/// The estimated leverage ratio divides open interest by the coin reserves
+/// held on the exchange. Rising values mean more leverage is being taken
+/// against the same collateral, which raises the risk of liquidation cascades.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing open interest and exchange reserve data
+/// * `oi_column` - Column with open interest, in the same unit as the reserves
+/// * `reserve_column` - Column with exchange reserves
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the leverage ratio Series named
+/// "estimated_leverage_ratio"
+pub fn calculate_leverage_ratio(
+    df: &DataFrame,
+    oi_column: &str,
+    reserve_column: &str,
+) -> PolarsResult<Series> {
+    if !df.schema().contains(oi_column) || !df.schema().contains(reserve_column) {
+        return Err(PolarsError::ComputeError(
+            format!("Leverage ratio requires {oi_column} and {reserve_column} columns").into(),
+        ));
+    }
+
+    let oi = df.column(oi_column)?.f64()?;
+    let reserves = df.column(reserve_column)?.f64()?;
+
+    let ratio: Vec<f64> = oi
+        .into_iter()
+        .zip(reserves)
+        .map(|(o, r)| match (o, r) {
+            (Some(o), Some(r)) if r > 0.0 => o / r,
+            _ => f64::NAN,
+        })
+        .collect();
+
+    Ok(Series::new("estimated_leverage_ratio".into(), ratio))
+}
+
+/// Generate contrarian signals from accumulated funding
+///
+/// Persistently positive funding means longs are crowded and paying to stay
+/// in the trade, which often precedes long squeezes; persistently negative
+/// funding signals crowded shorts.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing funding rate data
+/// * `funding_column` - Column with per-interval funding rates
+/// * `window` - Number of funding intervals to accumulate
+/// * `threshold` - Absolute accumulated funding that counts as crowded (e.g. 0.003)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing an i32 Series named "funding_signal":
+/// 1 when shorts are crowded, -1 when longs are crowded, 0 otherwise
+pub fn generate_funding_signals(
+    df: &DataFrame,
+    funding_column: &str,
+    window: usize,
+    threshold: f64,
+) -> PolarsResult<Series> {
+    let accumulated = calculate_funding_accumulation(df, funding_column, window)?;
+
+    let signals: Vec<i32> = accumulated
+        .f64()?
+        .into_iter()
+        .map(|value| match value {
+            Some(v) if v >= threshold => -1,
+            Some(v) if v <= -threshold => 1,
+            _ => 0,
+        })
+        .collect();
+
+    Ok(Series::new("funding_signal".into(), signals))
+}
+
+/// Generate trend-confirmation signals from price and open interest changes
+///
+/// Rising prices with rising open interest indicate new longs entering
+/// (bullish confirmation); falling prices with rising open interest indicate
+/// new shorts (bearish confirmation). Moves on falling open interest are
+/// position unwinds and produce no signal.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing price and open interest data
+/// * `price_column` - Column with prices (usually "close")
+/// * `oi_column` - Column with open interest
+/// * `period` - Number of bars over which changes are measured
+/// * `min_oi_change` - Minimum open interest increase in percent to count
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing an i32 Series named "oi_signal"
+/// with values 1 (bullish), -1 (bearish) or 0
+pub fn generate_open_interest_signals(
+    df: &DataFrame,
+    price_column: &str,
+    oi_column: &str,
+    period: usize,
+    min_oi_change: f64,
+) -> PolarsResult<Series> {
+    let oi_change = calculate_open_interest_change(df, oi_column, period)?;
+    let oi_change = oi_change.f64()?;
+    let price = df.column(price_column)?.f64()?;
+    let prev_price = price.shift(period as i64);
+
+    let signals: Vec<i32> = (0..df.height())
+        .map(|i| {
+            let (Some(p), Some(prev), Some(oi)) =
+                (price.get(i), prev_price.get(i), oi_change.get(i))
+            else {
+                return 0;
+            };
+            if oi.is_nan() || oi < min_oi_change {
+                0
+            } else if p > prev {
+                1
+            } else if p < prev {
+                -1
+            } else {
+                0
+            }
+        })
+        .collect();
+
+    Ok(Series::new("oi_signal".into(), signals))
+}
@@ -0,0 +1,331 @@
+//! Hilbert Transform core shared by the HT cycle indicators
+//!
+//! A bar-by-bar port of the loop the TA-Lib `HT_*` functions share: a
+//! 4-bar WMA smooths the price, a Hilbert Transform with separate odd and
+//! even bar state splits it into in-phase and quadrature components, and a
+//! homodyne discriminator turns those into the dominant cycle period. The
+//! phase comes from a DFT of the smoothed price over one period, and the
+//! trend mode from the sine wave crossings, phase rate and distance to an
+//! instantaneous trendline.
+
+const A: f64 = 0.0962;
+const B: f64 = 0.5769;
+
+/// How long a TA-Lib `HT_*` function warms up
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Warmup {
+    /// Bars the WMA runs, seed included, before the transform starts
+    smoothing: usize,
+    /// Bars needed before the first value
+    lookback: usize,
+}
+
+/// Warm-up of TA-Lib's HT_DCPERIOD and HT_PHASOR: 9 WMA bars after the
+/// seed, first value at bar 32
+pub(super) const DCPERIOD_WARMUP: Warmup = Warmup {
+    smoothing: 12,
+    lookback: 32,
+};
+
+/// Warm-up of TA-Lib's HT_DCPHASE, HT_SINE and HT_TRENDMODE: 34 WMA bars
+/// after the seed, first value at bar 63
+pub(super) const DCPHASE_WARMUP: Warmup = Warmup {
+    smoothing: 37,
+    lookback: 63,
+};
+
+/// Length of the smoothed and raw price history, the longest period
+const HISTORY: usize = 50;
+
+/// One Hilbert Transform filter with separate state for odd and even bars
+#[derive(Default)]
+struct HilbertFilter {
+    history: [[f64; 3]; 2],
+    prev: [f64; 2],
+    prev_input: [f64; 2],
+}
+
+impl HilbertFilter {
+    fn apply(&mut self, input: f64, parity: usize, index: usize, adjusted_period: f64) -> f64 {
+        let scaled = A * input;
+        let mut value = -self.history[parity][index] + scaled;
+        self.history[parity][index] = scaled;
+        value -= self.prev[parity];
+        self.prev[parity] = B * self.prev_input[parity];
+        value += self.prev[parity];
+        self.prev_input[parity] = input;
+        value * adjusted_period
+    }
+}
+
+/// The last [`HISTORY`] values of a series
+struct History([f64; HISTORY]);
+
+impl Default for History {
+    fn default() -> Self {
+        Self([0.0; HISTORY])
+    }
+}
+
+/// Values of the transform on one bar
+#[derive(Debug, Clone, Copy)]
+pub(super) struct HilbertBar {
+    /// Smoothed dominant cycle period, TA-Lib's HT_DCPERIOD
+    pub smooth_period: f64,
+    /// In-phase component, TA-Lib's HT_PHASOR inphase
+    pub in_phase: f64,
+    /// Quadrature component, TA-Lib's HT_PHASOR quadrature
+    pub quadrature: f64,
+    /// Dominant cycle phase in degrees, TA-Lib's HT_DCPHASE
+    pub dc_phase: f64,
+    /// Sine of the phase, TA-Lib's HT_SINE sine
+    pub sine: f64,
+    /// Sine of the phase advanced 45 degrees, TA-Lib's HT_SINE leadsine
+    pub lead_sine: f64,
+    /// 1 in a trend and 0 in a cycle, TA-Lib's HT_TRENDMODE
+    pub trend_mode: i32,
+}
+
+/// Bar-by-bar Hilbert Transform state
+pub(super) struct HilbertTransform {
+    bars: usize,
+    // Bars before the transform starts
+    smoothing: usize,
+    // 4-bar WMA of the price
+    wma_window: [f64; 4],
+    wma_sub: f64,
+    wma_sum: f64,
+    detrender: HilbertFilter,
+    q1: HilbertFilter,
+    ji: HilbertFilter,
+    jq: HilbertFilter,
+    filter_index: usize,
+    // Detrender values three bars back, kept per parity
+    i1_prev2: [f64; 2],
+    i1_prev3: [f64; 2],
+    prev_i2: f64,
+    prev_q2: f64,
+    re: f64,
+    im: f64,
+    period: f64,
+    smooth_period: f64,
+    // Most recent smoothed prices, newest at `history_index`
+    smooth_prices: History,
+    history_index: usize,
+    // Most recent raw prices, bar `b` at `b % HISTORY`
+    prices: History,
+    dc_phase: f64,
+    sine: f64,
+    lead_sine: f64,
+    // Last three period means behind the trendline, newest first
+    trend_means: [f64; 3],
+    bars_in_trend: usize,
+}
+
+impl HilbertTransform {
+    pub fn new(warmup: Warmup) -> Self {
+        Self {
+            bars: 0,
+            smoothing: warmup.smoothing,
+            wma_window: [0.0; 4],
+            wma_sub: 0.0,
+            wma_sum: 0.0,
+            detrender: HilbertFilter::default(),
+            q1: HilbertFilter::default(),
+            ji: HilbertFilter::default(),
+            jq: HilbertFilter::default(),
+            filter_index: 0,
+            i1_prev2: [0.0; 2],
+            i1_prev3: [0.0; 2],
+            prev_i2: 0.0,
+            prev_q2: 0.0,
+            re: 0.0,
+            im: 0.0,
+            period: 0.0,
+            smooth_period: 0.0,
+            smooth_prices: History::default(),
+            history_index: 0,
+            prices: History::default(),
+            dc_phase: 0.0,
+            sine: 0.0,
+            lead_sine: 0.0,
+            trend_means: [0.0; 3],
+            bars_in_trend: 0,
+        }
+    }
+
+    /// Feed the next price; `None` while the WMA is filling
+    pub fn next(&mut self, price: f64) -> Option<HilbertBar> {
+        let bar = self.bars;
+        self.bars += 1;
+        self.prices.0[bar % HISTORY] = price;
+
+        // TA-Lib seeds the WMA with three prices, then runs it until the
+        // transform starts: 9 more bars for the period, 34 for the phase
+        let trailing = self.wma_window[0];
+        self.wma_window.rotate_left(1);
+        self.wma_window[3] = price;
+        if bar < 3 {
+            self.wma_sub += price;
+            self.wma_sum += price * (bar + 1) as f64;
+            return None;
+        }
+        self.wma_sub += price - trailing;
+        self.wma_sum += price * 4.0;
+        let smoothed = self.wma_sum * 0.1;
+        self.wma_sum -= self.wma_sub;
+        if bar < self.smoothing {
+            return None;
+        }
+        self.smooth_prices.0[self.history_index] = smoothed;
+
+        let adjusted = 0.075 * self.period + 0.54;
+        let parity = bar % 2;
+        let index = self.filter_index;
+        let detrender = self.detrender.apply(smoothed, parity, index, adjusted);
+        let q1 = self.q1.apply(detrender, parity, index, adjusted);
+        let i1 = self.i1_prev3[parity];
+        let ji = self.ji.apply(i1, parity, index, adjusted);
+        let jq = self.jq.apply(q1, parity, index, adjusted);
+        if parity == 0 {
+            self.filter_index = (self.filter_index + 1) % 3;
+        }
+        // The other parity's detrender becomes its I1 three bars later
+        let other = 1 - parity;
+        self.i1_prev3[other] = self.i1_prev2[other];
+        self.i1_prev2[other] = detrender;
+
+        let q2 = 0.2 * (q1 + ji) + 0.8 * self.prev_q2;
+        let i2 = 0.2 * (i1 - jq) + 0.8 * self.prev_i2;
+        self.re = 0.2 * (i2 * self.prev_i2 + q2 * self.prev_q2) + 0.8 * self.re;
+        self.im = 0.2 * (i2 * self.prev_q2 - q2 * self.prev_i2) + 0.8 * self.im;
+        self.prev_q2 = q2;
+        self.prev_i2 = i2;
+
+        let previous = self.period;
+        if self.im != 0.0 && self.re != 0.0 {
+            self.period = 360.0 / (self.im / self.re).atan().to_degrees();
+        }
+        self.period = self
+            .period
+            .min(1.5 * previous)
+            .max(0.67 * previous)
+            .clamp(6.0, 50.0);
+        self.period = 0.2 * self.period + 0.8 * previous;
+        self.smooth_period = 0.33 * self.period + 0.67 * self.smooth_period;
+
+        let previous_phase = self.dc_phase;
+        self.update_phase();
+        let (previous_sine, previous_lead) = (self.sine, self.lead_sine);
+        self.sine = self.dc_phase.to_radians().sin();
+        self.lead_sine = (self.dc_phase + 45.0).to_radians().sin();
+        let trend_mode = self.trend_mode(bar, previous_phase, previous_sine, previous_lead);
+
+        self.history_index = (self.history_index + 1) % HISTORY;
+        Some(HilbertBar {
+            smooth_period: self.smooth_period,
+            in_phase: i1,
+            quadrature: q1,
+            dc_phase: self.dc_phase,
+            sine: self.sine,
+            lead_sine: self.lead_sine,
+            trend_mode,
+        })
+    }
+
+    /// Phase of the smoothed price over one dominant cycle
+    fn update_phase(&mut self) {
+        let period = (self.smooth_period + 0.5) as usize;
+        let (mut real, mut imag) = (0.0, 0.0);
+        for i in 0..period.min(HISTORY) {
+            let slot = (self.history_index + HISTORY - i) % HISTORY;
+            let angle = (i as f64 * 360f64.to_radians()) / period as f64;
+            real += angle.sin() * self.smooth_prices.0[slot];
+            imag += angle.cos() * self.smooth_prices.0[slot];
+        }
+
+        // With no imaginary part TA-Lib turns the previous phase a quarter
+        if imag.abs() > 0.0 {
+            self.dc_phase = (real / imag).atan().to_degrees();
+        } else if real < 0.0 {
+            self.dc_phase -= 90.0;
+        } else if real > 0.0 {
+            self.dc_phase += 90.0;
+        }
+        self.dc_phase += 90.0;
+        // Compensate for the one bar lag of the WMA
+        self.dc_phase += 360.0 / self.smooth_period;
+        if imag < 0.0 {
+            self.dc_phase += 180.0;
+        }
+        if self.dc_phase > 315.0 {
+            self.dc_phase -= 360.0;
+        }
+    }
+
+    /// 1 while price trends, 0 while it cycles
+    fn trend_mode(
+        &mut self,
+        bar: usize,
+        previous_phase: f64,
+        previous_sine: f64,
+        previous_lead: f64,
+    ) -> i32 {
+        let period = (self.smooth_period + 0.5) as usize;
+        let mut mean: f64 = (0..period.min(HISTORY).min(bar + 1))
+            .map(|i| self.prices.0[(bar - i) % HISTORY])
+            .sum();
+        if period > 0 {
+            mean /= period as f64;
+        }
+        let [t1, t2, t3] = self.trend_means;
+        let trendline = (4.0 * mean + 3.0 * t1 + 2.0 * t2 + t3) / 10.0;
+        self.trend_means = [mean, t1, t2];
+
+        let mut trend = 1;
+        // A sine wave crossing starts a new count of bars in the trend
+        let crossed = (self.sine > self.lead_sine && previous_sine <= previous_lead)
+            || (self.sine < self.lead_sine && previous_sine >= previous_lead);
+        if crossed {
+            self.bars_in_trend = 0;
+            trend = 0;
+        }
+        self.bars_in_trend += 1;
+        if (self.bars_in_trend as f64) < 0.5 * self.smooth_period {
+            trend = 0;
+        }
+        // A phase advancing at about the cycle rate means a cycle
+        let rate = 360.0 / self.smooth_period;
+        let change = self.dc_phase - previous_phase;
+        if self.smooth_period != 0.0 && change > 0.67 * rate && change < 1.5 * rate {
+            trend = 0;
+        }
+        let smoothed = self.smooth_prices.0[self.history_index];
+        if trendline != 0.0 && ((smoothed - trendline) / trendline).abs() >= 0.015 {
+            trend = 1;
+        }
+        trend
+    }
+}
+
+/// Run the transform over a column, starting at its first valid value
+///
+/// Returns one entry per row, `None` during the warm-up and from the first
+/// missing value after the start on.
+pub(super) fn hilbert_bars(values: &[Option<f64>], warmup: Warmup) -> Vec<Option<HilbertBar>> {
+    let mut out = vec![None; values.len()];
+    let Some(start) = values.iter().position(|v| v.is_some_and(|v| !v.is_nan())) else {
+        return out;
+    };
+    let mut transform = HilbertTransform::new(warmup);
+    for (row, value) in values.iter().enumerate().skip(start) {
+        let Some(price) = value.filter(|v| !v.is_nan()) else {
+            break;
+        };
+        let bar = transform.next(price);
+        if row - start >= warmup.lookback {
+            out[row] = bar;
+        }
+    }
+    out
+}
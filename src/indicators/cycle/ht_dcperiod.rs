@@ -1,6 +1,12 @@
+use super::hilbert::{hilbert_bars, DCPERIOD_WARMUP};
 use polars::prelude::*;
 
-/// Placeholder for Hilbert Transform - Dominant Cycle Period
+/// Calculates the Hilbert Transform - Dominant Cycle Period (HT_DCPERIOD)
+///
+/// Ehlers' estimate of the length in bars of the dominant price cycle, from
+/// a homodyne discriminator on the Hilbert Transform of the WMA-smoothed
+/// price, bounded to 6..50 bars and smoothed. Matches TA-Lib, including its
+/// 32-bar lookback.
 ///
 /// # Arguments
 ///
@@ -9,17 +15,38 @@ use polars::prelude::*;
 ///
 /// # Returns
 ///
-/// Returns a PolarsResult containing the dominant cycle period Series
+/// Returns a PolarsResult containing the dominant cycle period Series named
+/// "ht_dcperiod", NaN for the first 32 rows after the first valid value and
+/// from the first missing value after it on
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::cycle::calculate_ht_dcperiod;
+///
+/// // A sine wave with a 20-bar cycle
+/// let close: Vec<f64> = (0..200)
+///     .map(|i| 100.0 + (i as f64 * std::f64::consts::TAU / 20.0).sin())
+///     .collect();
+/// let df = df! { "close" => close }.unwrap();
+///
+/// let period = calculate_ht_dcperiod(&df, "close").unwrap();
+/// let period = period.f64().unwrap();
+/// assert!(period.get(31).unwrap().is_nan());
+/// assert!((period.get(199).unwrap() - 20.0).abs() < 1.0);
+/// ```
 pub fn calculate_ht_dcperiod(df: &DataFrame, column: &str) -> PolarsResult<Series> {
-    // Note: This is a complex indicator that requires the full Hilbert Transform
-    // implementation. For now, we'll return a placeholder.
-    let series = df.column(column)?.f64()?.clone();
-    let mut result = Vec::with_capacity(series.len());
-
-    // Just return NaN values for all points as placeholder
-    for _ in 0..series.len() {
-        result.push(f64::NAN);
-    }
+    let values: Vec<Option<f64>> = df
+        .column(column)?
+        .cast(&DataType::Float64)?
+        .f64()?
+        .iter()
+        .collect();
+    let result: Vec<f64> = hilbert_bars(&values, DCPERIOD_WARMUP)
+        .into_iter()
+        .map(|bar| bar.map_or(f64::NAN, |bar| bar.smooth_period))
+        .collect();
 
     Ok(Series::new("ht_dcperiod".into(), result))
 }
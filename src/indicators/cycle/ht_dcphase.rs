@@ -1,6 +1,11 @@
+use super::hilbert::{hilbert_bars, DCPHASE_WARMUP};
 use polars::prelude::*;
 
-/// Placeholder for Hilbert Transform - Dominant Cycle Phase
+/// Calculates the Hilbert Transform - Dominant Cycle Phase (HT_DCPHASE)
+///
+/// The position within the dominant cycle, in degrees from -45 to 315,
+/// found by correlating one cycle period of WMA-smoothed price with a sine
+/// and a cosine. Matches TA-Lib, including its 63-bar lookback.
 ///
 /// # Arguments
 ///
@@ -9,16 +14,20 @@ use polars::prelude::*;
 ///
 /// # Returns
 ///
-/// Returns a PolarsResult containing the dominant cycle phase Series
+/// Returns a PolarsResult containing the dominant cycle phase Series named
+/// "ht_dcphase", NaN for the first 63 rows after the first valid value and
+/// from the first missing value after it on
 pub fn calculate_ht_dcphase(df: &DataFrame, column: &str) -> PolarsResult<Series> {
-    // This is also a complex indicator requiring full HT implementation
-    let series = df.column(column)?.f64()?.clone();
-    let mut result = Vec::with_capacity(series.len());
-
-    // Just return NaN values for all points as placeholder
-    for _ in 0..series.len() {
-        result.push(f64::NAN);
-    }
+    let values: Vec<Option<f64>> = df
+        .column(column)?
+        .cast(&DataType::Float64)?
+        .f64()?
+        .iter()
+        .collect();
+    let result: Vec<f64> = hilbert_bars(&values, DCPHASE_WARMUP)
+        .into_iter()
+        .map(|bar| bar.map_or(f64::NAN, |bar| bar.dc_phase))
+        .collect();
 
     Ok(Series::new("ht_dcphase".into(), result))
 }
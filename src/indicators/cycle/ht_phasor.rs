@@ -1,6 +1,11 @@
+use super::hilbert::{hilbert_bars, DCPERIOD_WARMUP};
 use polars::prelude::*;
 
-/// Placeholder for Hilbert Transform - Phasor Components
+/// Calculates the Hilbert Transform - Phasor Components (HT_PHASOR)
+///
+/// The in-phase and quadrature components of the detrended, WMA-smoothed
+/// price; the quadrature leads the in-phase component by 90 degrees of the
+/// dominant cycle. Matches TA-Lib, including its 32-bar lookback.
 ///
 /// # Arguments
 ///
@@ -9,17 +14,20 @@ use polars::prelude::*;
 ///
 /// # Returns
 ///
-/// Returns a PolarsResult containing the tuple of (inphase, quadrature) Series
+/// Returns a PolarsResult containing the tuple of (inphase, quadrature)
+/// Series, NaN for the first 32 rows after the first valid value and from
+/// the first missing value after it on
 pub fn calculate_ht_phasor(df: &DataFrame, column: &str) -> PolarsResult<(Series, Series)> {
-    let series = df.column(column)?.f64()?.clone();
-    let mut inphase = Vec::with_capacity(series.len());
-    let mut quadrature = Vec::with_capacity(series.len());
-
-    // Just return NaN values for all points as placeholder
-    for _ in 0..series.len() {
-        inphase.push(f64::NAN);
-        quadrature.push(f64::NAN);
-    }
+    let values: Vec<Option<f64>> = df
+        .column(column)?
+        .cast(&DataType::Float64)?
+        .f64()?
+        .iter()
+        .collect();
+    let (inphase, quadrature): (Vec<f64>, Vec<f64>) = hilbert_bars(&values, DCPERIOD_WARMUP)
+        .into_iter()
+        .map(|bar| bar.map_or((f64::NAN, f64::NAN), |bar| (bar.in_phase, bar.quadrature)))
+        .unzip();
 
     Ok((
         Series::new("inphase".into(), inphase),
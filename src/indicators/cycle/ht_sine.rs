@@ -1,6 +1,12 @@
+use super::hilbert::{hilbert_bars, DCPHASE_WARMUP};
 use polars::prelude::*;
 
-/// Placeholder for Hilbert Transform - SineWave
+/// Calculates the Hilbert Transform - SineWave (HT_SINE)
+///
+/// The sine of the dominant cycle phase and of the phase advanced by 45
+/// degrees. In a cycling market the lead sine crossing the sine marks cycle
+/// turns; in a trend the two lines run flat and apart. Matches TA-Lib,
+/// including its 63-bar lookback.
 ///
 /// # Arguments
 ///
@@ -9,17 +15,20 @@ use polars::prelude::*;
 ///
 /// # Returns
 ///
-/// Returns a PolarsResult containing the tuple of (sine, leadsine) Series
+/// Returns a PolarsResult containing the tuple of (sine, leadsine) Series,
+/// NaN for the first 63 rows after the first valid value and from the first
+/// missing value after it on
 pub fn calculate_ht_sine(df: &DataFrame, column: &str) -> PolarsResult<(Series, Series)> {
-    let series = df.column(column)?.f64()?.clone();
-    let mut sine = Vec::with_capacity(series.len());
-    let mut leadsine = Vec::with_capacity(series.len());
-
-    // Just return NaN values for all points as placeholder
-    for _ in 0..series.len() {
-        sine.push(f64::NAN);
-        leadsine.push(f64::NAN);
-    }
+    let values: Vec<Option<f64>> = df
+        .column(column)?
+        .cast(&DataType::Float64)?
+        .f64()?
+        .iter()
+        .collect();
+    let (sine, leadsine): (Vec<f64>, Vec<f64>) = hilbert_bars(&values, DCPHASE_WARMUP)
+        .into_iter()
+        .map(|bar| bar.map_or((f64::NAN, f64::NAN), |bar| (bar.sine, bar.lead_sine)))
+        .unzip();
 
     Ok((
         Series::new("sine".into(), sine),
@@ -1,6 +1,13 @@
+use super::hilbert::{hilbert_bars, DCPHASE_WARMUP};
 use polars::prelude::*;
 
-/// Placeholder for Hilbert Transform - Trend vs Cycle Mode
+/// Calculates the Hilbert Transform - Trend vs Cycle Mode (HT_TRENDMODE)
+///
+/// Ehlers' classification of each bar as trending or cycling. A bar is
+/// cycling when the sine wave lines crossed less than half a cycle ago or
+/// the phase advances at about the cycle rate, unless the smoothed price is
+/// at least 1.5% away from the instantaneous trendline. Matches TA-Lib,
+/// including its 63-bar lookback.
 ///
 /// # Arguments
 ///
@@ -9,15 +16,40 @@ use polars::prelude::*;
 ///
 /// # Returns
 ///
-/// Returns a PolarsResult containing the trend mode Series (0 for cycle, 1 for trend)
+/// Returns a PolarsResult containing the trend mode Series named
+/// "ht_trendmode" (0 for cycle, 1 for trend), NaN for the first 63 rows
+/// after the first valid value and from the first missing value after it on
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::cycle::calculate_ht_trendmode;
+///
+/// // A steady 1% a bar rise trends; a sine wave around a flat level cycles
+/// let rising: Vec<f64> = (0..150).map(|i| 100.0 * 1.01f64.powi(i)).collect();
+/// let cycling: Vec<f64> = (0..150)
+///     .map(|i| 100.0 + (i as f64 * std::f64::consts::TAU / 20.0).sin())
+///     .collect();
+///
+/// let mode = |close: Vec<f64>| {
+///     let df = df! { "close" => close }.unwrap();
+///     calculate_ht_trendmode(&df, "close").unwrap().f64().unwrap().get(149).unwrap()
+/// };
+/// assert_eq!(mode(rising), 1.0);
+/// assert_eq!(mode(cycling), 0.0);
+/// ```
 pub fn calculate_ht_trendmode(df: &DataFrame, column: &str) -> PolarsResult<Series> {
-    let series = df.column(column)?.f64()?.clone();
-    let mut result = Vec::with_capacity(series.len());
-
-    // Just return NaN values for all points as placeholder
-    for _ in 0..series.len() {
-        result.push(f64::NAN);
-    }
+    let values: Vec<Option<f64>> = df
+        .column(column)?
+        .cast(&DataType::Float64)?
+        .f64()?
+        .iter()
+        .collect();
+    let result: Vec<f64> = hilbert_bars(&values, DCPHASE_WARMUP)
+        .into_iter()
+        .map(|bar| bar.map_or(f64::NAN, |bar| f64::from(bar.trend_mode)))
+        .collect();
 
     Ok(Series::new("ht_trendmode".into(), result))
 }
@@ -1,5 +1,6 @@
 // Cycle indicators module
 
+mod hilbert;
 mod ht_dcperiod;
 mod ht_dcphase;
 mod ht_phasor;
@@ -0,0 +1,174 @@
+use crate::indicators::cycle::calculate_ht_dcperiod;
+use crate::indicators::volatility::calculate_atr;
+use crate::util::dataframe_utils::{column_nan_values, nan_values};
+use polars::prelude::*;
+
+/// Where [`calculate_adaptive_rsi`] takes its lookback from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RsiPeriodSource {
+    /// A fraction of the Hilbert Transform dominant cycle period
+    /// ([`calculate_ht_dcperiod`]); Ehlers uses half the cycle
+    DominantCycle {
+        /// Share of the cycle period used as the lookback
+        fraction: f64,
+    },
+    /// A base period shortened when the ATR is above its average and
+    /// lengthened when it is below
+    Volatility {
+        /// Lookback at average volatility, also the ATR window
+        base_period: usize,
+        /// Number of bars the ATR is averaged over
+        window: usize,
+    },
+}
+
+/// Parameters for [`calculate_adaptive_rsi`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdaptiveRsiConfig {
+    /// How the lookback is chosen on each bar
+    pub source: RsiPeriodSource,
+    /// Shortest lookback
+    pub min_period: usize,
+    /// Longest lookback
+    pub max_period: usize,
+}
+
+impl Default for AdaptiveRsiConfig {
+    fn default() -> Self {
+        Self {
+            source: RsiPeriodSource::DominantCycle { fraction: 0.5 },
+            min_period: 3,
+            max_period: 30,
+        }
+    }
+}
+
+/// Calculates an RSI whose lookback adapts to the market
+///
+/// On each bar the lookback is chosen from the dominant cycle or from
+/// volatility (see [`RsiPeriodSource`]), rounded and bounded to
+/// `min_period..=max_period`. The RSI is then the share of up moves in the
+/// total absolute move over that many bars, as in Ehlers' cycle-adaptive
+/// RSI; a fixed lookback would need the Wilder average of every bar before
+/// it, which a changing period cannot carry over.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with the price column, plus "high", "low" and "close"
+///   for a volatility lookback
+/// * `column` - Price column, e.g. "close"
+/// * `config` - Lookback source and bounds
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with:
+/// * adaptive_rsi - RSI from 0 to 100, NaN until the lookback is known and
+///   covered by valid prices
+/// * adaptive_rsi_period - Lookback used on each bar, NaN until it is known
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::day_trading::{calculate_adaptive_rsi, AdaptiveRsiConfig};
+///
+/// // A sine wave with a 20-bar cycle gets half-cycle lookbacks
+/// let close: Vec<f64> = (0..200)
+///     .map(|i| 100.0 + (i as f64 * std::f64::consts::TAU / 20.0).sin())
+///     .collect();
+/// let df = df! { "close" => close }.unwrap();
+///
+/// let rsi = calculate_adaptive_rsi(&df, "close", &AdaptiveRsiConfig::default()).unwrap();
+/// let period = rsi.column("adaptive_rsi_period").unwrap().f64().unwrap();
+/// assert_eq!(period.get(199), Some(10.0));
+/// let rsi = rsi.column("adaptive_rsi").unwrap().f64().unwrap();
+/// assert!((0.0..=100.0).contains(&rsi.get(199).unwrap()));
+/// ```
+pub fn calculate_adaptive_rsi(
+    df: &DataFrame,
+    column: &str,
+    config: &AdaptiveRsiConfig,
+) -> PolarsResult<DataFrame> {
+    if config.min_period == 0 || config.min_period > config.max_period {
+        return Err(PolarsError::ComputeError(
+            "Adaptive RSI needs 0 < min_period <= max_period".into(),
+        ));
+    }
+
+    let raw_period: Vec<f64> = match config.source {
+        RsiPeriodSource::DominantCycle { fraction } => {
+            if fraction <= 0.0 {
+                return Err(PolarsError::ComputeError(
+                    "Adaptive RSI cycle fraction must be positive".into(),
+                ));
+            }
+            calculate_ht_dcperiod(df, column)?
+                .f64()?
+                .iter()
+                .map(|p| p.unwrap_or(f64::NAN) * fraction)
+                .collect()
+        }
+        RsiPeriodSource::Volatility {
+            base_period,
+            window,
+        } => {
+            if window == 0 {
+                return Err(PolarsError::ComputeError(
+                    "Adaptive RSI volatility window must be positive".into(),
+                ));
+            }
+            let atr = nan_values(calculate_atr(df, base_period)?.f64()?);
+            (0..atr.len())
+                .map(|i| {
+                    if i + 1 < window {
+                        return f64::NAN;
+                    }
+                    let average = atr[i + 1 - window..=i].iter().sum::<f64>() / window as f64;
+                    base_period as f64 * average / atr[i]
+                })
+                .collect()
+        }
+    };
+
+    let price = column_nan_values(df, column)?;
+    let (min, max) = (config.min_period as f64, config.max_period as f64);
+    let period: Vec<f64> = raw_period
+        .iter()
+        .map(|p| {
+            if p.is_finite() {
+                p.round().clamp(min, max)
+            } else {
+                f64::NAN
+            }
+        })
+        .collect();
+
+    let rsi: Vec<f64> = period
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            if p.is_nan() || i < *p as usize {
+                return f64::NAN;
+            }
+            let (mut up, mut down) = (0.0, 0.0);
+            for k in i + 1 - *p as usize..=i {
+                let change = price[k] - price[k - 1];
+                if change > 0.0 {
+                    up += change;
+                } else {
+                    down -= change;
+                }
+            }
+            if up + down == 0.0 {
+                50.0
+            } else {
+                100.0 * up / (up + down)
+            }
+        })
+        .collect();
+
+    DataFrame::new(vec![
+        Series::new("adaptive_rsi".into(), rsi).into(),
+        Series::new("adaptive_rsi_period".into(), period).into(),
+    ])
+}
@@ -0,0 +1,76 @@
+use crate::util::calendar::{calculate_session_ids, MarketCalendar};
+use polars::prelude::*;
+
+/// Detect price gaps between consecutive trading sessions
+///
+/// Session boundaries come from `calendar` rather than from the row index
+/// or the calendar date, so gaps are measured from the last bar of the
+/// previous session to the first bar of the next one even across weekends,
+/// holidays, half days and overnight sessions.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with OHLC data and a timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar defining the sessions
+/// * `price_threshold` - Minimum absolute gap in percent to be reported
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a tuple of (gap_size_pct, gap_type) Series.
+/// Both are non-zero only on the first bar of a session with a significant gap;
+/// gap_type is 1 for gap up and -1 for gap down.
+pub fn analyze_session_gaps(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+    price_threshold: f64,
+) -> PolarsResult<(Series, Series)> {
+    for col in ["open", "close"] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Gap analysis requires '{col}' column").into(),
+            ));
+        }
+    }
+
+    let open = df.column("open")?.f64()?;
+    let close = df.column("close")?.f64()?;
+    let session_ids = calculate_session_ids(df, time_column, calendar)?;
+    let session_ids = session_ids.u32()?;
+
+    let mut gap_size = vec![0.0; df.height()];
+    let mut gap_type = vec![0i32; df.height()];
+    let mut current_session: Option<u32> = None;
+    let mut last_close: Option<f64> = None;
+
+    for i in 0..df.height() {
+        // Bars outside every session neither open a session nor set its close
+        let Some(session) = session_ids.get(i) else {
+            continue;
+        };
+
+        if current_session != Some(session) {
+            let current_open = open.get(i).unwrap_or(f64::NAN);
+            if let Some(prev_close) = last_close {
+                if !current_open.is_nan() && prev_close != 0.0 {
+                    let gap_pct = (current_open - prev_close) / prev_close * 100.0;
+                    if gap_pct.abs() >= price_threshold {
+                        gap_size[i] = gap_pct;
+                        gap_type[i] = if gap_pct > 0.0 { 1 } else { -1 };
+                    }
+                }
+            }
+            current_session = Some(session);
+        }
+
+        if let Some(c) = close.get(i).filter(|c| !c.is_nan()) {
+            last_close = Some(c);
+        }
+    }
+
+    Ok((
+        Series::new("gap_size_pct".into(), gap_size),
+        Series::new("gap_type".into(), gap_type),
+    ))
+}
@@ -0,0 +1,241 @@
+use crate::util::calendar::{calculate_session_ids, MarketCalendar};
+use crate::util::dataframe_utils::column_nan_values;
+use polars::prelude::*;
+
+/// Gap that fits none of the other classes
+pub const GAP_COMMON: i32 = 1;
+/// Gap out of a trendless range
+pub const GAP_BREAKAWAY: i32 = 2;
+/// Gap in the direction of an established trend
+pub const GAP_CONTINUATION: i32 = 3;
+/// Gap in the direction of an extended trend
+pub const GAP_EXHAUSTION: i32 = 4;
+
+/// Parameters for [`analyze_gap_fills`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GapConfig {
+    /// Minimum absolute gap in percent to be analyzed
+    pub min_gap_pct: f64,
+    /// Number of prior sessions that define the range and trend
+    pub lookback_sessions: usize,
+    /// Prior-session move in percent from which the market counts as trending
+    pub trend_threshold_pct: f64,
+    /// Prior-session move in percent from which a trend counts as extended
+    pub exhaustion_threshold_pct: f64,
+    /// Upper edges in percent of the gap size buckets, ascending; the last
+    /// bucket is open-ended
+    pub bucket_edges: Vec<f64>,
+}
+
+impl Default for GapConfig {
+    fn default() -> Self {
+        Self {
+            min_gap_pct: 0.5,
+            lookback_sessions: 20,
+            trend_threshold_pct: 5.0,
+            exhaustion_threshold_pct: 15.0,
+            bucket_edges: vec![1.0, 2.0, 4.0],
+        }
+    }
+}
+
+/// Classify session gaps and track whether they fill
+///
+/// A gap is the move from the previous session's last close to the
+/// session's first open, with sessions from `calendar`. Gaps of at least
+/// `min_gap_pct` are classified against the prior `lookback_sessions`
+/// sessions, whose net move is the trend:
+///
+/// * exhaustion ([`GAP_EXHAUSTION`]) - with a trend of at least
+///   `exhaustion_threshold_pct`
+/// * continuation ([`GAP_CONTINUATION`]) - with a trend of at least
+///   `trend_threshold_pct`
+/// * breakaway ([`GAP_BREAKAWAY`]) - out of the prior sessions' high-low
+///   range while the trend is below `trend_threshold_pct`
+/// * common ([`GAP_COMMON`]) - everything else, including gaps before
+///   `lookback_sessions` sessions of history
+///
+/// A gap up fills on the first bar of its session whose low reaches the
+/// previous close, a gap down on the first whose high does.
+///
+/// "gap_filled" and "gap_fill_bars" describe the whole session and are only
+/// known once it ends, so they are for research, not for signals. Every
+/// other column only uses data up to its bar: "gap_unfilled" is true on the
+/// bars of a session whose gap has not filled yet, and
+/// "gap_fill_probability" is the share of earlier gaps in the same size
+/// bucket that filled within their session (NaN before the first one).
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with OHLC data and a timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar defining the sessions
+/// * `config` - Gap threshold, classification and bucket parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with one row per input row
+/// and "gap_size_pct", "gap_class", "gap_bucket", "gap_filled",
+/// "gap_fill_bars", "gap_fill_probability" and "gap_unfilled" columns. All
+/// but "gap_unfilled" are set on the first bar of a session with a gap and
+/// are 0, false, null or NaN elsewhere.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::day_trading::{analyze_gap_fills, GapConfig, GAP_COMMON};
+/// use rustalib::util::calendar::MarketCalendar;
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-02 10:00", "2024-01-03 10:00", "2024-01-03 11:00", "2024-01-03 12:00"],
+///     "open" => [100.0, 102.0, 101.5, 100.5],
+///     "high" => [100.5, 102.5, 101.8, 100.8],
+///     "low" => [99.5, 101.2, 100.6, 99.8],
+///     "close" => [100.0, 101.5, 100.7, 100.2],
+/// }
+/// .unwrap();
+///
+/// let gaps = analyze_gap_fills(&df, "timestamp", &MarketCalendar::crypto(), &GapConfig::default())
+///     .unwrap();
+/// assert_eq!(gaps.column("gap_size_pct").unwrap().f64().unwrap().get(1), Some(2.0));
+/// assert_eq!(gaps.column("gap_class").unwrap().i32().unwrap().get(1), Some(GAP_COMMON));
+/// // The third bar of the session trades back down to the prior close of 100
+/// assert_eq!(gaps.column("gap_fill_bars").unwrap().u32().unwrap().get(1), Some(2));
+/// let unfilled = gaps.column("gap_unfilled").unwrap().bool().unwrap();
+/// assert_eq!(unfilled.get(2), Some(true));
+/// assert_eq!(unfilled.get(3), Some(false));
+/// ```
+pub fn analyze_gap_fills(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+    config: &GapConfig,
+) -> PolarsResult<DataFrame> {
+    for col in ["open", "high", "low", "close"] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Gap analysis requires '{col}' column").into(),
+            ));
+        }
+    }
+
+    let to_vec = |name: &str| column_nan_values(df, name);
+    let open = to_vec("open")?;
+    let high = to_vec("high")?;
+    let low = to_vec("low")?;
+    let close = to_vec("close")?;
+    let sessions = group_sessions(&calculate_session_ids(df, time_column, calendar)?)?;
+
+    let height = df.height();
+    let mut gap_size = vec![0.0; height];
+    let mut gap_class = vec![0i32; height];
+    let mut gap_bucket: Vec<Option<i32>> = vec![None; height];
+    let mut gap_filled = vec![false; height];
+    let mut gap_fill_bars: Vec<Option<u32>> = vec![None; height];
+    let mut fill_probability = vec![f64::NAN; height];
+    let mut gap_unfilled = vec![false; height];
+    // (gaps, fills) per size bucket over completed sessions
+    let mut bucket_history = vec![(0u32, 0u32); config.bucket_edges.len() + 1];
+
+    let session_close = |rows: &[usize]| rows.iter().rev().map(|&r| close[r]).find(|c| !c.is_nan());
+    for (s, rows) in sessions.iter().enumerate().skip(1) {
+        let first = rows[0];
+        let Some(prev_close) = session_close(&sessions[s - 1]) else {
+            continue;
+        };
+        let gap_pct = (open[first] - prev_close) / prev_close * 100.0;
+        if gap_pct.is_nan() || gap_pct.abs() < config.min_gap_pct {
+            continue;
+        }
+        let direction = gap_pct.signum();
+
+        gap_size[first] = gap_pct;
+        gap_class[first] = GAP_COMMON;
+        if s >= config.lookback_sessions && config.lookback_sessions > 0 {
+            let prior = &sessions[s - config.lookback_sessions..s];
+            let range_high = prior
+                .iter()
+                .flatten()
+                .map(|&r| high[r])
+                .fold(f64::NEG_INFINITY, f64::max);
+            let range_low = prior
+                .iter()
+                .flatten()
+                .map(|&r| low[r])
+                .fold(f64::INFINITY, f64::min);
+            let start_close = session_close(&sessions[s - config.lookback_sessions]);
+            let trend = start_close.map_or(f64::NAN, |c| (prev_close - c) / c * 100.0);
+            let with_trend = trend * direction;
+
+            gap_class[first] = if with_trend >= config.exhaustion_threshold_pct {
+                GAP_EXHAUSTION
+            } else if with_trend >= config.trend_threshold_pct {
+                GAP_CONTINUATION
+            } else if trend.abs() < config.trend_threshold_pct
+                && (open[first] > range_high || open[first] < range_low)
+            {
+                GAP_BREAKAWAY
+            } else {
+                GAP_COMMON
+            };
+        }
+
+        let bucket = config
+            .bucket_edges
+            .iter()
+            .position(|&edge| gap_pct.abs() < edge)
+            .unwrap_or(config.bucket_edges.len());
+        gap_bucket[first] = Some(bucket as i32);
+        let (gaps, fills) = bucket_history[bucket];
+        if gaps > 0 {
+            fill_probability[first] = fills as f64 / gaps as f64;
+        }
+
+        let fill = rows.iter().position(|&r| {
+            if direction > 0.0 {
+                low[r] <= prev_close
+            } else {
+                high[r] >= prev_close
+            }
+        });
+        for (k, &r) in rows.iter().enumerate() {
+            gap_unfilled[r] = fill.is_none_or(|f| k < f);
+        }
+        if let Some(f) = fill {
+            gap_filled[first] = true;
+            gap_fill_bars[first] = Some(f as u32);
+        }
+        bucket_history[bucket].0 += 1;
+        bucket_history[bucket].1 += fill.is_some() as u32;
+    }
+
+    DataFrame::new(vec![
+        Series::new("gap_size_pct".into(), gap_size).into(),
+        Series::new("gap_class".into(), gap_class).into(),
+        Series::new("gap_bucket".into(), gap_bucket).into(),
+        Series::new("gap_filled".into(), gap_filled).into(),
+        Series::new("gap_fill_bars".into(), gap_fill_bars).into(),
+        Series::new("gap_fill_probability".into(), fill_probability).into(),
+        Series::new("gap_unfilled".into(), gap_unfilled).into(),
+    ])
+}
+
+/// Row indices of each session in order; rows outside every session are left out
+fn group_sessions(session_ids: &Series) -> PolarsResult<Vec<Vec<usize>>> {
+    let mut sessions: Vec<Vec<usize>> = Vec::new();
+    let mut current = None;
+    for (i, id) in session_ids.u32()?.iter().enumerate() {
+        let Some(id) = id else {
+            continue;
+        };
+        if current != Some(id) {
+            sessions.push(Vec::new());
+            current = Some(id);
+        }
+        if let Some(rows) = sessions.last_mut() {
+            rows.push(i);
+        }
+    }
+    Ok(sessions)
+}
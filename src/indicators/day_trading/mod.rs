@@ -9,9 +9,34 @@
 //! - Volume-price relationship indicators for short timeframes
 //! - Market microstructure indicators for order flow analysis
 //! - Volatility indicators calibrated for intraday movements
+//! - Session statistics such as the opening range's share of the day's
+//!   range, the close location and VWAP drift
 
 use polars::prelude::*;
 
+mod adaptive_rsi;
+mod gap_analysis;
+mod gap_fill;
+mod opening_range;
+mod order_flow;
+mod session_stats;
+mod session_volatility;
+mod vwap_bands;
+
+pub use adaptive_rsi::{calculate_adaptive_rsi, AdaptiveRsiConfig, RsiPeriodSource};
+pub use gap_analysis::analyze_session_gaps;
+pub use gap_fill::{
+    analyze_gap_fills, GapConfig, GAP_BREAKAWAY, GAP_COMMON, GAP_CONTINUATION, GAP_EXHAUSTION,
+};
+pub use opening_range::{add_opening_range_breakout, OpeningRangeConfig};
+pub use order_flow::{estimate_order_flow, VolumeSplit};
+pub use session_stats::{add_session_stats, calculate_session_stats, SessionStatsConfig};
+pub use session_volatility::{
+    add_session_atr_bands, calculate_session_atr, calculate_volatility_profile, SessionAtrConfig,
+    SessionGapHandling,
+};
+pub use vwap_bands::{add_vwap_bands, BandDeviation, VwapBandConfig};
+
 /// Calculate intraday momentum oscillator
 ///
 /// A faster-responding version of RSI optimized for intraday trading.
@@ -32,20 +57,42 @@ pub fn intraday_momentum_oscillator(df: &DataFrame, _period: usize) -> Result<Se
 
 /// Calculate order flow imbalance
 ///
-/// Measures the imbalance between buying and selling pressure
-/// based on tick-by-tick data and trade direction.
+/// Measures the imbalance between buying and selling pressure. OHLCV bars
+/// carry no trade direction, so each bar's volume is split by where the
+/// close sits in its range (see [`VolumeSplit::CloseLocation`]); use
+/// [`estimate_order_flow`] for the tick rule, cumulative delta and delta
+/// divergence.
 ///
 /// # Arguments
 ///
-/// * `df` - DataFrame with tick data including trade direction
-/// * `volume_weighted` - Whether to weight the imbalance by volume
+/// * `df` - DataFrame with high, low, close and volume columns
+/// * `volume_weighted` - Whether to report the imbalance in volume (buying
+///   minus selling volume) rather than as a fraction of the bar's volume
 ///
 /// # Returns
 ///
-/// * `Result<Series, PolarsError>` - Series with imbalance values
-pub fn order_flow_imbalance(df: &DataFrame, _volume_weighted: bool) -> Result<Series, PolarsError> {
-    // Placeholder implementation
-    let values = vec![0.0; df.height()];
+/// * `Result<Series, PolarsError>` - Series with imbalance values, from -1
+///   (all selling) to 1 (all buying) unless volume weighted; NaN for bars
+///   with a missing value
+pub fn order_flow_imbalance(df: &DataFrame, volume_weighted: bool) -> Result<Series, PolarsError> {
+    let (buy_volume, sell_volume) = order_flow::split_volume(df, VolumeSplit::CloseLocation)?;
+
+    let values: Vec<f64> = buy_volume
+        .iter()
+        .zip(&sell_volume)
+        .map(|(b, s)| {
+            if volume_weighted {
+                b - s
+            } else if b + s > 0.0 {
+                (b - s) / (b + s)
+            } else if b.is_nan() {
+                f64::NAN
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
     Ok(Series::new("order_flow_imbalance".into(), values))
 }
 
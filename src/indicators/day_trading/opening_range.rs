@@ -0,0 +1,176 @@
+use crate::indicators::volatility::calculate_atr;
+use crate::util::calendar::MarketCalendar;
+use crate::util::time_utils::parse_timestamp_column;
+use polars::prelude::*;
+
+/// Parameters for [`add_opening_range_breakout`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpeningRangeConfig {
+    /// Length of the opening range in minutes (typically 5, 15, 30 or 60)
+    pub range_minutes: i64,
+    /// ATR window for the breakout targets, in bars
+    pub atr_period: usize,
+    /// Target distance beyond the broken range edge, in ATRs
+    pub target_atr_multiple: f64,
+}
+
+impl Default for OpeningRangeConfig {
+    fn default() -> Self {
+        Self {
+            range_minutes: 30,
+            atr_period: 14,
+            target_atr_multiple: 2.0,
+        }
+    }
+}
+
+/// Add opening range breakout (ORB) levels and signals to intraday data
+///
+/// The opening range of a session is the high and low of the bars that
+/// start less than `range_minutes` after the session open, with sessions and
+/// the open from `calendar`. Bar timestamps are taken as bar start times.
+/// Once the range is complete, the first close above its high is an upside
+/// breakout and the first close below its low a downside one. A breakout
+/// fails when a later close falls back inside the range; the session can
+/// then break out again in either direction.
+///
+/// # Arguments
+///
+/// * `df` - Intraday DataFrame with OHLC data and a timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar defining the sessions
+/// * `config` - Range length and target parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a copy of `df` with these columns:
+///
+/// * "orb_high", "orb_low", "orb_mid" - range levels, NaN until the range is
+///   complete and outside sessions
+/// * "orb_breakout" - 1 for an upside and -1 for a downside breakout on the
+///   breakout bar, 0 otherwise
+/// * "orb_failure" - the direction of a failed breakout on the bar it
+///   fails, 0 otherwise
+/// * "orb_target" - broken range edge ± `target_atr_multiple` ATRs (at the
+///   breakout bar) while a breakout is active, NaN otherwise
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::day_trading::{add_opening_range_breakout, OpeningRangeConfig};
+/// use rustalib::util::calendar::MarketCalendar;
+///
+/// let df = df! {
+///     "timestamp" => [
+///         "2024-01-02 00:00", "2024-01-02 00:15", "2024-01-02 00:30", "2024-01-02 00:45",
+///     ],
+///     "open" => [100.0, 100.5, 100.8, 101.8],
+///     "high" => [101.0, 100.9, 102.0, 102.0],
+///     "low" => [99.5, 100.2, 100.7, 100.5],
+///     "close" => [100.5, 100.8, 101.8, 100.6],
+/// }
+/// .unwrap();
+///
+/// let config = OpeningRangeConfig { range_minutes: 30, atr_period: 2, ..Default::default() };
+/// let orb = add_opening_range_breakout(&df, "timestamp", &MarketCalendar::crypto(), &config)
+///     .unwrap();
+/// assert_eq!(orb.column("orb_high").unwrap().f64().unwrap().get(2), Some(101.0));
+/// assert_eq!(orb.column("orb_breakout").unwrap().i32().unwrap().get(2), Some(1));
+/// assert_eq!(orb.column("orb_failure").unwrap().i32().unwrap().get(3), Some(1));
+/// ```
+pub fn add_opening_range_breakout(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+    config: &OpeningRangeConfig,
+) -> PolarsResult<DataFrame> {
+    if config.range_minutes <= 0 {
+        return Err(PolarsError::ComputeError(
+            "Opening range length must be positive".into(),
+        ));
+    }
+
+    let timestamps = parse_timestamp_column(df, time_column)?;
+    let high = df.column("high")?.f64()?;
+    let low = df.column("low")?.f64()?;
+    let close = df.column("close")?.f64()?;
+    let atr = calculate_atr(df, config.atr_period)?;
+    let atr = atr.f64()?;
+
+    let height = df.height();
+    let mut orb_high = vec![f64::NAN; height];
+    let mut orb_low = vec![f64::NAN; height];
+    let mut orb_mid = vec![f64::NAN; height];
+    let mut breakout = vec![0i32; height];
+    let mut failure = vec![0i32; height];
+    let mut target = vec![f64::NAN; height];
+
+    let mut session = None;
+    let mut range = (f64::NEG_INFINITY, f64::INFINITY);
+    // Direction and target of the active breakout
+    let mut active: Option<(i32, f64)> = None;
+
+    for i in 0..height {
+        let Some(ts) = timestamps[i] else {
+            continue;
+        };
+        let (Some(date), Some(minutes)) =
+            (calendar.session_date(ts), calendar.minutes_since_open(ts))
+        else {
+            continue;
+        };
+        if session != Some(date) {
+            session = Some(date);
+            range = (f64::NEG_INFINITY, f64::INFINITY);
+            active = None;
+        }
+
+        if minutes < config.range_minutes {
+            if let (Some(h), Some(l)) = (high.get(i), low.get(i)) {
+                range = (range.0.max(h), range.1.min(l));
+            }
+            continue;
+        }
+        let (range_high, range_low) = range;
+        if !range_high.is_finite() || !range_low.is_finite() {
+            continue;
+        }
+        orb_high[i] = range_high;
+        orb_low[i] = range_low;
+        orb_mid[i] = (range_high + range_low) / 2.0;
+
+        let Some(c) = close.get(i).filter(|c| !c.is_nan()) else {
+            continue;
+        };
+        match active {
+            Some((direction, _)) if c <= range_high && c >= range_low => {
+                failure[i] = direction;
+                active = None;
+            }
+            Some(_) => {}
+            None => {
+                let distance = config.target_atr_multiple * atr.get(i).unwrap_or(f64::NAN);
+                if c > range_high {
+                    breakout[i] = 1;
+                    active = Some((1, range_high + distance));
+                } else if c < range_low {
+                    breakout[i] = -1;
+                    active = Some((-1, range_low - distance));
+                }
+            }
+        }
+        if let Some((_, level)) = active {
+            target[i] = level;
+        }
+    }
+
+    let mut result = df.clone();
+    result.with_column(Series::new("orb_high".into(), orb_high))?;
+    result.with_column(Series::new("orb_low".into(), orb_low))?;
+    result.with_column(Series::new("orb_mid".into(), orb_mid))?;
+    result.with_column(Series::new("orb_breakout".into(), breakout))?;
+    result.with_column(Series::new("orb_failure".into(), failure))?;
+    result.with_column(Series::new("orb_target".into(), target))?;
+    Ok(result)
+}
@@ -0,0 +1,169 @@
+use crate::util::dataframe_utils::check_window_size;
+use polars::prelude::*;
+
+/// How a bar's volume is split into buying and selling volume
+///
+/// Both are approximations for OHLCV data without trade-level aggressor
+/// flags; they estimate who was in control of the bar, not actual order flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VolumeSplit {
+    /// Buying share is where the close sits in the bar's range,
+    /// (close - low) / (high - low); a bar with no range splits evenly
+    #[default]
+    CloseLocation,
+    /// All volume is buying on an up close and selling on a down close; an
+    /// unchanged close repeats the last direction (the tick rule), and the
+    /// first bar and bars before any change split evenly
+    TickRule,
+}
+
+/// Estimate buying and selling volume, delta and delta divergence from OHLCV
+///
+/// Delta is buying minus selling volume for the bar and cumulative delta its
+/// running sum. Delta divergence compares the net change of the close and of
+/// the cumulative delta over `divergence_window` bars: 1 when price fell
+/// while delta rose (bullish), -1 when price rose while delta fell
+/// (bearish), 0 otherwise.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with high, low, close and volume columns
+/// * `method` - How each bar's volume is split
+/// * `divergence_window` - Number of bars the divergence compares over
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with one row per input row
+/// and "buy_volume", "sell_volume", "delta", "cumulative_delta" and
+/// "delta_divergence" columns. Bars with a missing value have NaN volumes
+/// and delta and leave the cumulative delta unchanged.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::day_trading::{estimate_order_flow, VolumeSplit};
+///
+/// // Price grinds higher, but each bar closes near its low
+/// let df = df! {
+///     "high" => [101.0, 102.0, 103.0, 104.0],
+///     "low" => [99.0, 100.0, 101.0, 102.0],
+///     "close" => [99.5, 100.5, 101.5, 102.5],
+///     "volume" => [1000.0, 1000.0, 1000.0, 1000.0],
+/// }
+/// .unwrap();
+///
+/// let flow = estimate_order_flow(&df, VolumeSplit::CloseLocation, 3).unwrap();
+/// let delta = flow.column("delta").unwrap().f64().unwrap();
+/// assert_eq!(delta.get(0), Some(-500.0));
+/// let divergence = flow.column("delta_divergence").unwrap().i32().unwrap();
+/// assert_eq!(divergence.get(3), Some(-1));
+/// ```
+pub fn estimate_order_flow(
+    df: &DataFrame,
+    method: VolumeSplit,
+    divergence_window: usize,
+) -> PolarsResult<DataFrame> {
+    check_window_size(df, divergence_window + 1, "Order flow")?;
+    if divergence_window == 0 {
+        return Err(PolarsError::ComputeError(
+            "Order flow divergence window must be positive".into(),
+        ));
+    }
+
+    let (buy_volume, sell_volume) = split_volume(df, method)?;
+    let close = df.column("close")?.f64()?;
+
+    let delta: Vec<f64> = buy_volume
+        .iter()
+        .zip(&sell_volume)
+        .map(|(b, s)| b - s)
+        .collect();
+    let mut cumulative_delta = Vec::with_capacity(df.height());
+    let mut running = 0.0;
+    for d in &delta {
+        if !d.is_nan() {
+            running += d;
+        }
+        cumulative_delta.push(running);
+    }
+
+    let mut divergence = vec![0i32; df.height()];
+    for i in divergence_window..df.height() {
+        let (Some(current), Some(previous)) = (close.get(i), close.get(i - divergence_window))
+        else {
+            continue;
+        };
+        let price_change = current - previous;
+        let delta_change = cumulative_delta[i] - cumulative_delta[i - divergence_window];
+        divergence[i] = if price_change < 0.0 && delta_change > 0.0 {
+            1
+        } else if price_change > 0.0 && delta_change < 0.0 {
+            -1
+        } else {
+            0
+        };
+    }
+
+    DataFrame::new(vec![
+        Series::new("buy_volume".into(), buy_volume).into(),
+        Series::new("sell_volume".into(), sell_volume).into(),
+        Series::new("delta".into(), delta).into(),
+        Series::new("cumulative_delta".into(), cumulative_delta).into(),
+        Series::new("delta_divergence".into(), divergence).into(),
+    ])
+}
+
+/// Split each bar's volume into (buying, selling) volume, NaN where a value
+/// is missing
+pub(super) fn split_volume(
+    df: &DataFrame,
+    method: VolumeSplit,
+) -> PolarsResult<(Vec<f64>, Vec<f64>)> {
+    for col in ["high", "low", "close", "volume"] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Order flow requires '{col}' column").into(),
+            ));
+        }
+    }
+
+    let high = df.column("high")?.f64()?;
+    let low = df.column("low")?.f64()?;
+    let close = df.column("close")?.f64()?;
+    let volume = df.column("volume")?.f64()?;
+
+    let mut buy_volume = vec![f64::NAN; df.height()];
+    let mut sell_volume = vec![f64::NAN; df.height()];
+    let mut previous_close: Option<f64> = None;
+    let mut last_direction = 0.0;
+
+    for i in 0..df.height() {
+        let (Some(h), Some(l), Some(c), Some(v)) =
+            (high.get(i), low.get(i), close.get(i), volume.get(i))
+        else {
+            continue;
+        };
+
+        let buy_share = match method {
+            VolumeSplit::CloseLocation if h > l => (c - l) / (h - l),
+            VolumeSplit::CloseLocation => 0.5,
+            VolumeSplit::TickRule => {
+                if let Some(prev) = previous_close {
+                    if c > prev {
+                        last_direction = 1.0;
+                    } else if c < prev {
+                        last_direction = -1.0;
+                    }
+                }
+                previous_close = Some(c);
+                (last_direction + 1.0) / 2.0
+            }
+        };
+
+        buy_volume[i] = v * buy_share;
+        sell_volume[i] = v * (1.0 - buy_share);
+    }
+
+    Ok((buy_volume, sell_volume))
+}
@@ -0,0 +1,326 @@
+use crate::util::calendar::MarketCalendar;
+use crate::util::dataframe_utils::column_nan_values;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::NaiveDate;
+use polars::prelude::*;
+
+/// Parameters for [`calculate_session_stats`] and [`add_session_stats`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionStatsConfig {
+    /// Length of the opening window in minutes
+    pub opening_minutes: i64,
+}
+
+impl Default for SessionStatsConfig {
+    fn default() -> Self {
+        Self {
+            opening_minutes: 30,
+        }
+    }
+}
+
+/// Running statistics of one session
+struct SessionState {
+    date: NaiveDate,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+    price_volume: f64,
+    /// High and low of the opening window
+    opening: (f64, f64),
+    /// Session VWAP at the end of the opening window, NaN until then
+    opening_vwap: f64,
+    opening_done: bool,
+}
+
+impl SessionState {
+    fn new(date: NaiveDate) -> Self {
+        Self {
+            date,
+            high: f64::NAN,
+            low: f64::NAN,
+            close: f64::NAN,
+            volume: 0.0,
+            price_volume: 0.0,
+            opening: (f64::NAN, f64::NAN),
+            opening_vwap: f64::NAN,
+            opening_done: false,
+        }
+    }
+
+    fn add_bar(&mut self, h: f64, l: f64, c: f64, v: f64, in_opening: bool) {
+        if !in_opening && !self.opening_done {
+            self.opening_done = true;
+            self.opening_vwap = self.vwap();
+        }
+        // f64::max and f64::min skip NaN
+        self.high = self.high.max(h);
+        self.low = self.low.min(l);
+        if in_opening {
+            self.opening = (self.opening.0.max(h), self.opening.1.min(l));
+        }
+        if !c.is_nan() {
+            self.close = c;
+        }
+        let typical = (h + l + c) / 3.0;
+        if !typical.is_nan() && !v.is_nan() {
+            self.volume += v;
+            self.price_volume += typical * v;
+        }
+    }
+
+    fn vwap(&self) -> f64 {
+        if self.volume > 0.0 {
+            self.price_volume / self.volume
+        } else {
+            f64::NAN
+        }
+    }
+
+    fn range(&self) -> f64 {
+        let range = self.high - self.low;
+        if range > 0.0 {
+            range
+        } else {
+            f64::NAN
+        }
+    }
+
+    fn opening_range_pct(&self) -> f64 {
+        if !self.opening_done {
+            return f64::NAN;
+        }
+        (self.opening.0 - self.opening.1) / self.range() * 100.0
+    }
+
+    fn close_location(&self) -> f64 {
+        ((self.close - self.low) - (self.high - self.close)) / self.range()
+    }
+
+    fn vwap_drift_pct(&self) -> f64 {
+        (self.vwap() / self.opening_vwap - 1.0) * 100.0
+    }
+}
+
+/// Walks the bars session by session
+///
+/// Returns the session-to-date state after every bar (`None` outside
+/// sessions) through `on_bar`, and the final state of every session.
+fn walk_sessions(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+    config: &SessionStatsConfig,
+    mut on_bar: impl FnMut(usize, Option<&SessionState>),
+) -> PolarsResult<Vec<SessionState>> {
+    if config.opening_minutes <= 0 {
+        return Err(PolarsError::ComputeError(
+            "Session statistics opening window must be positive".into(),
+        ));
+    }
+    for col in ["high", "low", "close", "volume"] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Session statistics require '{col}' column").into(),
+            ));
+        }
+    }
+
+    let timestamps = parse_timestamp_column(df, time_column)?;
+    let values = |col: &str| column_nan_values(df, col);
+    let (high, low, close, volume) = (
+        values("high")?,
+        values("low")?,
+        values("close")?,
+        values("volume")?,
+    );
+
+    let mut sessions: Vec<SessionState> = Vec::new();
+    for i in 0..df.height() {
+        let position = timestamps[i]
+            .and_then(|ts| Some((calendar.session_date(ts)?, calendar.minutes_since_open(ts)?)));
+        let Some((date, minutes)) = position else {
+            on_bar(i, None);
+            continue;
+        };
+        if sessions.last().is_none_or(|s| s.date != date) {
+            sessions.push(SessionState::new(date));
+        }
+        let session = sessions.last_mut().expect("session was just pushed");
+        session.add_bar(
+            high[i],
+            low[i],
+            close[i],
+            volume[i],
+            minutes < config.opening_minutes,
+        );
+        on_bar(i, Some(session));
+    }
+    Ok(sessions)
+}
+
+/// Summarize each session of intraday data
+///
+/// Per session:
+///
+/// * "opening_range_pct" - the range of the bars starting within
+///   `opening_minutes` of the open, in percent of the whole session's range;
+///   near 100 when the open set the day's extremes, low on trend days
+/// * "close_location" - where the close sits in the session range, from -1
+///   at the low to 1 at the high: ((close - low) - (high - close)) / range
+/// * "vwap_drift_pct" - change of the session VWAP from the end of the
+///   opening window to the close, in percent; VWAP uses the typical price
+///   (high + low + close) / 3
+///
+/// # Arguments
+///
+/// * `df` - Intraday DataFrame with "high", "low", "close" and "volume"
+///   columns and a timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar defining the sessions
+/// * `config` - Opening window length
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing one row per session with
+/// "session_date" ("%Y-%m-%d"), "session_high", "session_low",
+/// "session_close", "session_volume", "session_vwap" and the statistics
+/// above; statistics are NaN for sessions that never leave the opening
+/// window or have no range
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::day_trading::{calculate_session_stats, SessionStatsConfig};
+/// use rustalib::util::calendar::MarketCalendar;
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-02 00:00", "2024-01-02 00:15", "2024-01-02 00:30"],
+///     "high" => [101.0, 100.5, 103.0],
+///     "low" => [99.0, 99.5, 100.5],
+///     "close" => [100.0, 100.0, 102.5],
+///     "volume" => [100.0, 100.0, 200.0],
+/// }
+/// .unwrap();
+///
+/// let stats = calculate_session_stats(
+///     &df,
+///     "timestamp",
+///     &MarketCalendar::crypto(),
+///     &SessionStatsConfig::default(),
+/// )
+/// .unwrap();
+/// let value = |name: &str| stats.column(name).unwrap().f64().unwrap().get(0).unwrap();
+///
+/// // The first 30 minutes ranged 99.0 to 101.0 of a 99.0 to 103.0 day
+/// assert_eq!(value("opening_range_pct"), 50.0);
+/// assert_eq!(value("close_location"), 0.75);
+/// // VWAP rose from 100.0 after the opening window to 101.0
+/// assert_eq!(value("session_vwap"), 101.0);
+/// assert!((value("vwap_drift_pct") - 1.0).abs() < 1e-9);
+/// ```
+pub fn calculate_session_stats(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+    config: &SessionStatsConfig,
+) -> PolarsResult<DataFrame> {
+    let sessions = walk_sessions(df, time_column, calendar, config, |_, _| {})?;
+    let stat = |f: fn(&SessionState) -> f64| sessions.iter().map(f).collect::<Vec<f64>>();
+
+    DataFrame::new(vec![
+        Column::new(
+            "session_date".into(),
+            sessions
+                .iter()
+                .map(|s| s.date.format("%Y-%m-%d").to_string())
+                .collect::<Vec<_>>(),
+        ),
+        Column::new("session_high".into(), stat(|s| s.high)),
+        Column::new("session_low".into(), stat(|s| s.low)),
+        Column::new("session_close".into(), stat(|s| s.close)),
+        Column::new("session_volume".into(), stat(|s| s.volume)),
+        Column::new("session_vwap".into(), stat(SessionState::vwap)),
+        Column::new(
+            "opening_range_pct".into(),
+            stat(SessionState::opening_range_pct),
+        ),
+        Column::new("close_location".into(), stat(SessionState::close_location)),
+        Column::new("vwap_drift_pct".into(), stat(SessionState::vwap_drift_pct)),
+    ])
+}
+
+/// Add session-to-date statistics to every bar of intraday data
+///
+/// The statistics of [`calculate_session_stats`], each computed from the
+/// session's bars up to and including the current one, so no bar sees the
+/// rest of its session. The opening range share and the VWAP drift are NaN
+/// until the opening window is over; the close location uses the bar's
+/// close within the session range so far.
+///
+/// # Arguments
+///
+/// * `df` - Intraday DataFrame with "high", "low", "close" and "volume"
+///   columns and a timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar defining the sessions
+/// * `config` - Opening window length
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a copy of `df` with
+/// "opening_range_pct", "close_location" and "vwap_drift_pct" columns, NaN
+/// outside sessions
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::day_trading::{add_session_stats, SessionStatsConfig};
+/// use rustalib::util::calendar::MarketCalendar;
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-02 00:00", "2024-01-02 00:15", "2024-01-02 00:30"],
+///     "high" => [101.0, 100.5, 103.0],
+///     "low" => [99.0, 99.5, 100.5],
+///     "close" => [100.0, 100.0, 102.5],
+///     "volume" => [100.0, 100.0, 200.0],
+/// }
+/// .unwrap();
+///
+/// let bars = add_session_stats(
+///     &df,
+///     "timestamp",
+///     &MarketCalendar::crypto(),
+///     &SessionStatsConfig::default(),
+/// )
+/// .unwrap();
+/// let share = bars.column("opening_range_pct").unwrap().f64().unwrap();
+/// assert!(share.get(1).unwrap().is_nan());
+/// assert_eq!(share.get(2), Some(50.0));
+/// ```
+pub fn add_session_stats(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+    config: &SessionStatsConfig,
+) -> PolarsResult<DataFrame> {
+    let mut opening_range = vec![f64::NAN; df.height()];
+    let mut close_location = vec![f64::NAN; df.height()];
+    let mut vwap_drift = vec![f64::NAN; df.height()];
+    walk_sessions(df, time_column, calendar, config, |i, session| {
+        if let Some(session) = session {
+            opening_range[i] = session.opening_range_pct();
+            close_location[i] = session.close_location();
+            vwap_drift[i] = session.vwap_drift_pct();
+        }
+    })?;
+
+    let mut result = df.clone();
+    result.with_column(Series::new("opening_range_pct".into(), opening_range))?;
+    result.with_column(Series::new("close_location".into(), close_location))?;
+    result.with_column(Series::new("vwap_drift_pct".into(), vwap_drift))?;
+    Ok(result)
+}
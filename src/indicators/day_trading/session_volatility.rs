@@ -0,0 +1,336 @@
+use crate::util::calendar::MarketCalendar;
+use crate::util::time_utils::parse_timestamp_column;
+use polars::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+/// How [`calculate_session_atr`] treats the first bar of each session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionGapHandling {
+    /// Leave the bar out of the average; the ATR carries over unchanged
+    SkipFirstBar,
+    /// Use the bar's high-low range, ignoring the gap from the previous close
+    RangeOnly,
+}
+
+/// Parameters for [`add_session_atr_bands`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionAtrConfig {
+    /// ATR window, in bars
+    pub window: usize,
+    /// Treatment of the bar that opens each session
+    pub gap_handling: SessionGapHandling,
+    /// Band distance from the close, in session ATRs
+    pub band_multiple: f64,
+}
+
+impl Default for SessionAtrConfig {
+    fn default() -> Self {
+        Self {
+            window: 14,
+            gap_handling: SessionGapHandling::RangeOnly,
+            band_multiple: 2.0,
+        }
+    }
+}
+
+/// Calculates an Average True Range that ignores overnight gaps
+///
+/// Within a session the true range is the usual one and the average uses
+/// Wilder's smoothing like [`calculate_atr`](crate::indicators::volatility::calculate_atr).
+/// The gap between sessions never enters it: the first bar of a session is
+/// either left out or counted by its high-low range, per `gap_handling`.
+/// Bars outside every session are left out, keeping the previous value.
+///
+/// # Arguments
+///
+/// * `df` - Intraday DataFrame with "high", "low" and "close" columns and a
+///   timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar defining the sessions
+/// * `window` - Number of true ranges averaged (typically 14)
+/// * `gap_handling` - Treatment of the first bar of each session
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named "session_atr_{window}",
+/// NaN until `window` true ranges have been seen
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::day_trading::{calculate_session_atr, SessionGapHandling};
+/// use rustalib::util::calendar::MarketCalendar;
+///
+/// // Two sessions of 1.0-wide bars with a 10.0 gap between them
+/// let df = df! {
+///     "timestamp" => [
+///         "2024-01-02 15:00", "2024-01-02 15:01", "2024-01-02 15:02",
+///         "2024-01-03 15:00", "2024-01-03 15:01",
+///     ],
+///     "high" => [101.0, 101.0, 101.0, 111.0, 111.0],
+///     "low" => [100.0, 100.0, 100.0, 110.0, 110.0],
+///     "close" => [100.5, 100.5, 100.5, 110.5, 110.5],
+/// }
+/// .unwrap();
+///
+/// let atr = calculate_session_atr(
+///     &df,
+///     "timestamp",
+///     &MarketCalendar::nyse(),
+///     2,
+///     SessionGapHandling::RangeOnly,
+/// )
+/// .unwrap();
+/// let atr: Vec<_> = atr.f64().unwrap().into_no_null_iter().skip(1).collect();
+/// assert_eq!(atr, [1.0, 1.0, 1.0, 1.0]);
+/// ```
+pub fn calculate_session_atr(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+    window: usize,
+    gap_handling: SessionGapHandling,
+) -> PolarsResult<Series> {
+    if window == 0 {
+        return Err(PolarsError::ComputeError(
+            "Session ATR window must be positive".into(),
+        ));
+    }
+    for col in ["high", "low", "close"] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Session ATR requires '{col}' column").into(),
+            ));
+        }
+    }
+
+    let timestamps = parse_timestamp_column(df, time_column)?;
+    let high = df.column("high")?.cast(&DataType::Float64)?;
+    let low = df.column("low")?.cast(&DataType::Float64)?;
+    let close = df.column("close")?.cast(&DataType::Float64)?;
+    let (high, low, close) = (high.f64()?, low.f64()?, close.f64()?);
+
+    let mut atr_values = vec![f64::NAN; df.height()];
+    let mut session = None;
+    let mut prev_close: Option<f64> = None;
+    // Running sum until the window is full, Wilder's average after
+    let mut seen = 0usize;
+    let mut atr = 0.0;
+
+    for i in 0..df.height() {
+        let date = timestamps[i].and_then(|ts| calendar.session_date(ts));
+        let bar = match (date, high.get(i), low.get(i)) {
+            (Some(date), Some(h), Some(l)) if !h.is_nan() && !l.is_nan() => {
+                let opens_session = session != Some(date);
+                session = Some(date);
+                let tr = match prev_close {
+                    Some(pc) if !opens_session => (h - l).max((h - pc).abs()).max((l - pc).abs()),
+                    _ if opens_session && gap_handling == SessionGapHandling::SkipFirstBar => {
+                        f64::NAN
+                    }
+                    _ => h - l,
+                };
+                prev_close = close.get(i).filter(|c| !c.is_nan());
+                tr
+            }
+            _ => f64::NAN,
+        };
+
+        if !bar.is_nan() {
+            seen += 1;
+            if seen <= window {
+                atr += bar;
+                if seen == window {
+                    atr /= window as f64;
+                }
+            } else {
+                atr = ((window as f64 - 1.0) * atr + bar) / window as f64;
+            }
+        }
+        if seen >= window {
+            atr_values[i] = atr;
+        }
+    }
+
+    Ok(Series::new(
+        format!("session_atr_{window}").into(),
+        atr_values,
+    ))
+}
+
+/// Add session ATR bands around the close to intraday data
+///
+/// See [`calculate_session_atr`] for how gaps are handled.
+///
+/// # Arguments
+///
+/// * `df` - Intraday DataFrame with "high", "low" and "close" columns and a
+///   timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar defining the sessions
+/// * `config` - ATR window, gap handling and band width
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a copy of `df` with "session_atr",
+/// "session_atr_upper" and "session_atr_lower" columns, the bands at the
+/// close ± `band_multiple` session ATRs
+pub fn add_session_atr_bands(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+    config: &SessionAtrConfig,
+) -> PolarsResult<DataFrame> {
+    let atr = calculate_session_atr(
+        df,
+        time_column,
+        calendar,
+        config.window,
+        config.gap_handling,
+    )?;
+    let close = df.column("close")?.cast(&DataType::Float64)?;
+
+    let (upper, lower): (Vec<f64>, Vec<f64>) = close
+        .f64()?
+        .iter()
+        .zip(atr.f64()?.iter())
+        .map(|(c, a)| match (c, a) {
+            (Some(c), Some(a)) => (c + config.band_multiple * a, c - config.band_multiple * a),
+            _ => (f64::NAN, f64::NAN),
+        })
+        .unzip();
+
+    let mut result = df.clone();
+    result.with_column(atr.with_name("session_atr".into()))?;
+    result.with_column(Series::new("session_atr_upper".into(), upper))?;
+    result.with_column(Series::new("session_atr_lower".into(), lower))?;
+    Ok(result)
+}
+
+/// Calculate the expected bar range by time of session
+///
+/// Bars are grouped by minutes since the session open into buckets of
+/// `bucket_minutes`. The expected range of a bar is the mean high-low range
+/// of its bucket over the previous `lookback_sessions` sessions that traded
+/// in it; the current session is never included, so the profile can size
+/// stops on the bar it is read. Minute bars around the open and close
+/// usually range much wider than midday ones, which a single ATR averages
+/// away.
+///
+/// # Arguments
+///
+/// * `df` - Intraday DataFrame with "high" and "low" columns and a timestamp
+///   column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar defining the sessions
+/// * `lookback_sessions` - Number of past sessions averaged per bucket
+/// * `bucket_minutes` - Width of a time-of-session bucket in minutes
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with:
+/// * expected_range - Mean past range of the bar's bucket, NaN without
+///   history or outside sessions
+/// * range_ratio - The bar's range over its expected range
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::day_trading::calculate_volatility_profile;
+/// use rustalib::util::calendar::MarketCalendar;
+///
+/// // The first minute of each session ranges 2.0, the second 0.5
+/// let df = df! {
+///     "timestamp" => [
+///         "2024-01-02 14:30", "2024-01-02 14:31",
+///         "2024-01-03 14:30", "2024-01-03 14:31",
+///         "2024-01-04 14:30", "2024-01-04 14:31",
+///     ],
+///     "high" => [102.0, 100.5, 102.0, 100.5, 103.0, 100.5],
+///     "low" => [100.0, 100.0, 100.0, 100.0, 100.0, 100.0],
+/// }
+/// .unwrap();
+///
+/// let profile = calculate_volatility_profile(&df, "timestamp", &MarketCalendar::nyse(), 20, 1)
+///     .unwrap();
+/// let expected = profile.column("expected_range").unwrap().f64().unwrap();
+/// assert_eq!(expected.get(4), Some(2.0));
+/// assert_eq!(expected.get(5), Some(0.5));
+/// let ratio = profile.column("range_ratio").unwrap().f64().unwrap();
+/// assert_eq!(ratio.get(4), Some(1.5));
+/// ```
+pub fn calculate_volatility_profile(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+    lookback_sessions: usize,
+    bucket_minutes: i64,
+) -> PolarsResult<DataFrame> {
+    if lookback_sessions == 0 || bucket_minutes <= 0 {
+        return Err(PolarsError::ComputeError(
+            "Volatility profile lookback and bucket size must be positive".into(),
+        ));
+    }
+    for col in ["high", "low"] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Volatility profile requires '{col}' column").into(),
+            ));
+        }
+    }
+
+    let timestamps = parse_timestamp_column(df, time_column)?;
+    let high = df.column("high")?.cast(&DataType::Float64)?;
+    let low = df.column("low")?.cast(&DataType::Float64)?;
+    let (high, low) = (high.f64()?, low.f64()?);
+
+    let mut expected = vec![f64::NAN; df.height()];
+    let mut ratio = vec![f64::NAN; df.height()];
+    // Mean range per bucket of past sessions, most recent last
+    let mut history: HashMap<i64, VecDeque<f64>> = HashMap::new();
+    // Range sum and bar count per bucket of the current session
+    let mut current: HashMap<i64, (f64, usize)> = HashMap::new();
+    let mut session = None;
+
+    for i in 0..df.height() {
+        let Some(ts) = timestamps[i] else {
+            continue;
+        };
+        let (Some(date), Some(minutes)) =
+            (calendar.session_date(ts), calendar.minutes_since_open(ts))
+        else {
+            continue;
+        };
+        if session != Some(date) {
+            session = Some(date);
+            for (bucket, (sum, count)) in current.drain() {
+                let past = history.entry(bucket).or_default();
+                past.push_back(sum / count as f64);
+                if past.len() > lookback_sessions {
+                    past.pop_front();
+                }
+            }
+        }
+
+        let bucket = minutes.div_euclid(bucket_minutes);
+        if let Some(past) = history.get(&bucket) {
+            expected[i] = past.iter().sum::<f64>() / past.len() as f64;
+        }
+        if let (Some(h), Some(l)) = (high.get(i), low.get(i)) {
+            let range = h - l;
+            if !range.is_nan() {
+                ratio[i] = range / expected[i];
+                let entry = current.entry(bucket).or_insert((0.0, 0));
+                entry.0 += range;
+                entry.1 += 1;
+            }
+        }
+    }
+
+    DataFrame::new(vec![
+        Series::new("expected_range".into(), expected).into(),
+        Series::new("range_ratio".into(), ratio).into(),
+    ])
+}
@@ -0,0 +1,183 @@
+use crate::indicators::naming::indicator_name;
+use crate::util::dataframe_utils::{check_window_size, column_nan_values};
+use polars::prelude::*;
+
+/// How the width of one VWAP band is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BandDeviation {
+    /// Root mean square distance of the close from VWAP
+    #[default]
+    StdDev,
+    /// Mean absolute distance of the close from VWAP, less sensitive to
+    /// single outlier bars
+    MeanAbsolute,
+}
+
+/// Parameters for [`add_vwap_bands`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VwapBandConfig {
+    /// Band multiples of the deviation, one upper and lower band each
+    pub multiples: Vec<f64>,
+    /// Deviation measure
+    pub deviation: BandDeviation,
+    /// Number of trailing bars the deviation is measured over
+    pub window: usize,
+}
+
+impl Default for VwapBandConfig {
+    fn default() -> Self {
+        Self {
+            multiples: vec![1.0, 2.0, 3.0],
+            deviation: BandDeviation::StdDev,
+            window: 20,
+        }
+    }
+}
+
+/// Add VWAP deviation bands and band signals to a DataFrame
+///
+/// The deviation is the distance of the close from VWAP over the trailing
+/// `window` bars, measured as set by `config.deviation`. For every multiple
+/// `m` the bands are VWAP ± `m` deviations, and two signal columns mark
+/// how price meets them:
+///
+/// * touch - 1 when the high reaches the upper band, -1 when the low reaches
+///   the lower band, 0 otherwise (including bars that reach both)
+/// * cross - 1 when the close moves above the upper band from at or below
+///   it, -1 when it moves below the lower band from at or above it
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with high, low and close columns and a VWAP column
+/// * `vwap_column` - VWAP column, e.g. from
+///   [`calculate_session_vwap`](crate::indicators::moving_averages::calculate_session_vwap)
+/// * `config` - Band multiples, deviation measure and window
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a copy of `df` with a "vwap_deviation"
+/// column and, per multiple, "vwap_upper_{m}", "vwap_lower_{m}",
+/// "vwap_touch_{m}" and "vwap_cross_{m}" columns, where `m` is written with
+/// an underscore for the decimal point (1.5 becomes "1_5"). Bands are NaN
+/// until the first full window.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::day_trading::{add_vwap_bands, VwapBandConfig};
+///
+/// // Eight bars oscillating one point around a VWAP of 100, then a jump
+/// let mut close: Vec<f64> = (0..8).map(|i| if i % 2 == 0 { 101.0 } else { 99.0 }).collect();
+/// close.push(103.0);
+/// let df = df! {
+///     "high" => close.iter().map(|c| c + 0.5).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 0.5).collect::<Vec<_>>(),
+///     "close" => &close,
+///     "vwap" => vec![100.0; 9],
+/// }
+/// .unwrap();
+///
+/// let config = VwapBandConfig {
+///     multiples: vec![2.0],
+///     window: 8,
+///     ..VwapBandConfig::default()
+/// };
+/// let bands = add_vwap_bands(&df, "vwap", &config).unwrap();
+/// let upper = bands.column("vwap_upper_2").unwrap().f64().unwrap();
+/// let cross = bands.column("vwap_cross_2").unwrap().i32().unwrap();
+/// assert_eq!(upper.get(7), Some(102.0));
+/// // The close of 103 clears the widened upper band
+/// assert!(upper.get(8).unwrap() < 103.0);
+/// assert_eq!(cross.get(8), Some(1));
+/// ```
+pub fn add_vwap_bands(
+    df: &DataFrame,
+    vwap_column: &str,
+    config: &VwapBandConfig,
+) -> PolarsResult<DataFrame> {
+    for col in ["high", "low", "close", vwap_column] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("VWAP bands require '{col}' column").into(),
+            ));
+        }
+    }
+    check_window_size(df, config.window, "VWAP bands")?;
+    if config.window == 0 {
+        return Err(PolarsError::ComputeError(
+            "VWAP band window must be positive".into(),
+        ));
+    }
+
+    let to_vec = |name: &str| column_nan_values(df, name);
+    let high = to_vec("high")?;
+    let low = to_vec("low")?;
+    let close = to_vec("close")?;
+    let vwap = to_vec(vwap_column)?;
+
+    let distance: Vec<f64> = close.iter().zip(&vwap).map(|(c, v)| c - v).collect();
+    let mut deviation = vec![f64::NAN; df.height()];
+    for i in config.window - 1..df.height() {
+        let window = &distance[i + 1 - config.window..=i];
+        deviation[i] = match config.deviation {
+            BandDeviation::StdDev => {
+                (window.iter().map(|d| d * d).sum::<f64>() / config.window as f64).sqrt()
+            }
+            BandDeviation::MeanAbsolute => {
+                window.iter().map(|d| d.abs()).sum::<f64>() / config.window as f64
+            }
+        };
+    }
+
+    let mut result = df.clone();
+    for &multiple in &config.multiples {
+        let upper: Vec<f64> = vwap
+            .iter()
+            .zip(&deviation)
+            .map(|(v, d)| v + multiple * d)
+            .collect();
+        let lower: Vec<f64> = vwap
+            .iter()
+            .zip(&deviation)
+            .map(|(v, d)| v - multiple * d)
+            .collect();
+
+        let touch: Vec<i32> = (0..df.height())
+            .map(|i| match (high[i] >= upper[i], low[i] <= lower[i]) {
+                (true, false) => 1,
+                (false, true) => -1,
+                _ => 0,
+            })
+            .collect();
+        let mut cross = vec![0i32; df.height()];
+        for i in 1..df.height() {
+            if close[i] > upper[i] && close[i - 1] <= upper[i - 1] {
+                cross[i] = 1;
+            } else if close[i] < lower[i] && close[i - 1] >= lower[i - 1] {
+                cross[i] = -1;
+            }
+        }
+
+        let m = multiple.to_string().replace('.', "_");
+        result.with_column(Series::new(
+            indicator_name("vwap_upper", &[&m]).into(),
+            upper,
+        ))?;
+        result.with_column(Series::new(
+            indicator_name("vwap_lower", &[&m]).into(),
+            lower,
+        ))?;
+        result.with_column(Series::new(
+            indicator_name("vwap_touch", &[&m]).into(),
+            touch,
+        ))?;
+        result.with_column(Series::new(
+            indicator_name("vwap_cross", &[&m]).into(),
+            cross,
+        ))?;
+    }
+    result.with_column(Series::new("vwap_deviation".into(), deviation))?;
+
+    Ok(result)
+}
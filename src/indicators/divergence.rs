@@ -0,0 +1,296 @@
+//! # Divergence Detection
+//!
+//! Regular and hidden divergences between price and an indicator such as
+//! RSI, MACD or OBV, found by matching swing pivots of both series.
+//!
+//! A swing low is a value below the `left_bars` values before it and not
+//! above the `right_bars` values after it (a swing high the reverse), so a
+//! pivot is only known `right_bars` bars later. Each price pivot is paired
+//! with the nearest indicator pivot of the same kind within
+//! `pivot_tolerance` bars, and consecutive pairs are compared:
+//!
+//! | Kind | Price | Indicator |
+//! |------|-------|-----------|
+//! | Regular bullish | lower low | higher low |
+//! | Hidden bullish | higher low | lower low |
+//! | Regular bearish | higher high | lower high |
+//! | Hidden bearish | lower high | higher high |
+//!
+//! Regular divergences hint at reversals, hidden ones at trend continuation.
+
+use crate::util::dataframe_utils::column_valid_values;
+use polars::prelude::*;
+
+/// Code of a regular bullish divergence in the "divergence" column
+pub const REGULAR_BULLISH: i32 = 1;
+/// Code of a hidden bullish divergence in the "divergence" column
+pub const HIDDEN_BULLISH: i32 = 2;
+/// Code of a regular bearish divergence in the "divergence" column
+pub const REGULAR_BEARISH: i32 = -1;
+/// Code of a hidden bearish divergence in the "divergence" column
+pub const HIDDEN_BEARISH: i32 = -2;
+
+/// Kind of divergence between price and an indicator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DivergenceKind {
+    /// Price makes a lower low while the indicator makes a higher low
+    RegularBullish,
+    /// Price makes a higher low while the indicator makes a lower low
+    HiddenBullish,
+    /// Price makes a higher high while the indicator makes a lower high
+    RegularBearish,
+    /// Price makes a lower high while the indicator makes a higher high
+    HiddenBearish,
+}
+
+impl DivergenceKind {
+    /// Code of the kind in the "divergence" column
+    pub fn code(self) -> i32 {
+        match self {
+            DivergenceKind::RegularBullish => REGULAR_BULLISH,
+            DivergenceKind::HiddenBullish => HIDDEN_BULLISH,
+            DivergenceKind::RegularBearish => REGULAR_BEARISH,
+            DivergenceKind::HiddenBearish => HIDDEN_BEARISH,
+        }
+    }
+
+    /// Whether the divergence points to rising prices
+    pub fn is_bullish(self) -> bool {
+        matches!(
+            self,
+            DivergenceKind::RegularBullish | DivergenceKind::HiddenBullish
+        )
+    }
+
+    /// Whether the divergence is a regular (reversal) divergence
+    pub fn is_regular(self) -> bool {
+        matches!(
+            self,
+            DivergenceKind::RegularBullish | DivergenceKind::RegularBearish
+        )
+    }
+}
+
+/// Pivot and matching parameters for divergence detection
+#[derive(Debug, Clone, PartialEq)]
+pub struct DivergenceConfig {
+    /// Bars before a pivot that it must exceed
+    pub left_bars: usize,
+    /// Bars after a pivot that it must not be exceeded by
+    pub right_bars: usize,
+    /// Most bars between a price pivot and its indicator pivot
+    pub pivot_tolerance: usize,
+    /// Fewest bars between the two price pivots of a divergence
+    pub min_pivot_distance: usize,
+    /// Most bars between the two price pivots of a divergence
+    pub max_pivot_distance: usize,
+    /// Whether hidden divergences are reported as well as regular ones
+    pub include_hidden: bool,
+}
+
+impl Default for DivergenceConfig {
+    fn default() -> Self {
+        Self {
+            left_bars: 5,
+            right_bars: 5,
+            pivot_tolerance: 2,
+            min_pivot_distance: 5,
+            max_pivot_distance: 60,
+            include_hidden: true,
+        }
+    }
+}
+
+/// A divergence between two pairs of price and indicator pivots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    /// Kind of divergence
+    pub kind: DivergenceKind,
+    /// Row of the earlier price pivot
+    pub first_pivot: usize,
+    /// Row of the later price pivot
+    pub second_pivot: usize,
+    /// Row of the indicator pivot matched to `first_pivot`
+    pub first_indicator_pivot: usize,
+    /// Row of the indicator pivot matched to `second_pivot`
+    pub second_indicator_pivot: usize,
+    /// First row on which both later pivots are confirmed
+    pub confirmed_at: usize,
+}
+
+/// Find divergences between a price column and an indicator column
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with both columns
+/// * `price_column` - Price column, e.g. "close"
+/// * `indicator_column` - Indicator column, e.g. an RSI
+/// * `config` - Pivot and matching parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the divergences in order of
+/// confirmation
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::divergence::{find_divergences, DivergenceConfig, DivergenceKind};
+///
+/// // Price falls to a lower low at row 8 while the oscillator bottoms higher
+/// let df = df! {
+///     "close" =>     [10.0, 9.0, 8.0, 9.0, 10.0, 9.0, 8.0, 7.0, 6.0, 7.0, 8.0, 9.0],
+///     "indicator" => [50.0, 40.0, 30.0, 40.0, 50.0, 45.0, 40.0, 38.0, 35.0, 45.0, 55.0, 60.0],
+/// }
+/// .unwrap();
+/// let config = DivergenceConfig {
+///     left_bars: 2,
+///     right_bars: 2,
+///     min_pivot_distance: 3,
+///     ..Default::default()
+/// };
+///
+/// let divergences = find_divergences(&df, "close", "indicator", &config).unwrap();
+/// assert_eq!(divergences.len(), 1);
+/// assert_eq!(divergences[0].kind, DivergenceKind::RegularBullish);
+/// assert_eq!((divergences[0].first_pivot, divergences[0].second_pivot), (2, 8));
+/// assert_eq!(divergences[0].confirmed_at, 10);
+/// ```
+pub fn find_divergences(
+    df: &DataFrame,
+    price_column: &str,
+    indicator_column: &str,
+    config: &DivergenceConfig,
+) -> PolarsResult<Vec<Divergence>> {
+    if config.left_bars == 0 || config.right_bars == 0 {
+        return Err(PolarsError::ComputeError(
+            "Divergence pivots need at least one bar on each side".into(),
+        ));
+    }
+    let price = column_valid_values(df, price_column)?;
+    let indicator = column_valid_values(df, indicator_column)?;
+
+    let mut divergences = Vec::new();
+    for lows in [true, false] {
+        let price_pivots = swing_pivots(&price, config.left_bars, config.right_bars, lows);
+        let indicator_pivots = swing_pivots(&indicator, config.left_bars, config.right_bars, lows);
+
+        // Pair each price pivot with the nearest indicator pivot
+        let pairs: Vec<(usize, usize)> = price_pivots
+            .iter()
+            .filter_map(|&p| {
+                indicator_pivots
+                    .iter()
+                    .filter(|&&q| p.abs_diff(q) <= config.pivot_tolerance)
+                    .min_by_key(|&&q| p.abs_diff(q))
+                    .map(|&q| (p, q))
+            })
+            .collect();
+
+        for window in pairs.windows(2) {
+            let ((p1, q1), (p2, q2)) = (window[0], window[1]);
+            let distance = p2 - p1;
+            if distance < config.min_pivot_distance || distance > config.max_pivot_distance {
+                continue;
+            }
+            // Lows: price falling while the indicator rises is bullish
+            let price_lower = price[p2] < price[p1];
+            let price_higher = price[p2] > price[p1];
+            let indicator_lower = indicator[q2] < indicator[q1];
+            let indicator_higher = indicator[q2] > indicator[q1];
+            let kind = match (
+                lows,
+                price_lower,
+                price_higher,
+                indicator_lower,
+                indicator_higher,
+            ) {
+                (true, true, _, _, true) => DivergenceKind::RegularBullish,
+                (true, _, true, true, _) => DivergenceKind::HiddenBullish,
+                (false, _, true, true, _) => DivergenceKind::RegularBearish,
+                (false, true, _, _, true) => DivergenceKind::HiddenBearish,
+                _ => continue,
+            };
+            if !kind.is_regular() && !config.include_hidden {
+                continue;
+            }
+            divergences.push(Divergence {
+                kind,
+                first_pivot: p1,
+                second_pivot: p2,
+                first_indicator_pivot: q1,
+                second_indicator_pivot: q2,
+                confirmed_at: p2.max(q2) + config.right_bars,
+            });
+        }
+    }
+
+    divergences.sort_by_key(|d| (d.confirmed_at, d.second_pivot));
+    Ok(divergences)
+}
+
+/// Mark divergences between a price column and an indicator column per bar
+///
+/// Each divergence is reported on its confirmation bar, so the columns use
+/// no future data. When several divergences confirm on the same bar, the
+/// one found first by [`find_divergences`] is kept.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with both columns
+/// * `price_column` - Price column, e.g. "close"
+/// * `indicator_column` - Indicator column, e.g. an RSI
+/// * `config` - Pivot and matching parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with a "divergence" column
+/// ([`REGULAR_BULLISH`], [`HIDDEN_BULLISH`], [`REGULAR_BEARISH`],
+/// [`HIDDEN_BEARISH`] or 0) and "divergence_first_pivot" and
+/// "divergence_second_pivot" columns with the rows of the price pivots, null
+/// on bars without a divergence
+pub fn detect_divergence(
+    df: &DataFrame,
+    price_column: &str,
+    indicator_column: &str,
+    config: &DivergenceConfig,
+) -> PolarsResult<DataFrame> {
+    let n = df.height();
+    let mut code = vec![0i32; n];
+    let mut first: Vec<Option<u32>> = vec![None; n];
+    let mut second: Vec<Option<u32>> = vec![None; n];
+    for divergence in find_divergences(df, price_column, indicator_column, config)? {
+        let row = divergence.confirmed_at;
+        if row < n && code[row] == 0 {
+            code[row] = divergence.kind.code();
+            first[row] = Some(divergence.first_pivot as u32);
+            second[row] = Some(divergence.second_pivot as u32);
+        }
+    }
+
+    DataFrame::new(vec![
+        Series::new("divergence".into(), code).into(),
+        Series::new("divergence_first_pivot".into(), first).into(),
+        Series::new("divergence_second_pivot".into(), second).into(),
+    ])
+}
+
+/// Rows of swing lows (`lows`) or swing highs of a series
+fn swing_pivots(values: &[Option<f64>], left: usize, right: usize, lows: bool) -> Vec<usize> {
+    // `a` beats `b` when it is further in the pivot's direction
+    let beats = |a: f64, b: f64| if lows { a < b } else { a > b };
+    (left..values.len().saturating_sub(right))
+        .filter(|&i| {
+            let Some(pivot) = values[i] else {
+                return false;
+            };
+            values[i - left..i]
+                .iter()
+                .all(|v| v.is_some_and(|v| beats(pivot, v)))
+                && values[i + 1..=i + right]
+                    .iter()
+                    .all(|v| v.is_some_and(|v| !beats(v, pivot)))
+        })
+        .collect()
+}
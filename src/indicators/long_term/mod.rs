@@ -10,6 +10,7 @@
 //! - Long-term sentiment and valuation metrics
 //! - Multi-month to multi-year pattern recognition
 
+use crate::indicators::divergence::{detect_divergence, DivergenceConfig};
 use polars::prelude::*;
 
 /// Calculate secular trend strength
@@ -88,28 +89,38 @@ pub fn long_term_valuation_metrics(
 
 /// Detect long-term divergences
 ///
-/// Identifies divergences between price and various indicators
-/// over long timeframes for potential trend reversals.
+/// Identifies regular divergences between price and an indicator over long
+/// timeframes for potential trend reversals, using wide swing pivots (ten
+/// bars on each side) up to 250 bars apart. See
+/// [`divergence`](crate::indicators::divergence) for the matching rules.
 ///
 /// # Arguments
 ///
 /// * `df` - DataFrame with price and indicator data
 /// * `price_col` - Name of the price column
 /// * `indicator_col` - Name of the indicator column to compare with price
-/// * `min_divergence_periods` - Minimum periods for a valid divergence
+/// * `min_divergence_periods` - Minimum bars between the two price pivots
 ///
 /// # Returns
 ///
-/// * `Result<Series, PolarsError>` - Series with divergence signals
+/// * `Result<Series, PolarsError>` - Series named "lt_divergence" with 1 for
+///   a bullish and -1 for a bearish divergence on the bar that confirms it,
+///   0 otherwise
 pub fn long_term_divergence_detector(
     df: &DataFrame,
-    _price_col: &str,
-    _indicator_col: &str,
-    _min_divergence_periods: usize,
+    price_col: &str,
+    indicator_col: &str,
+    min_divergence_periods: usize,
 ) -> Result<Series, PolarsError> {
-    // Placeholder implementation
-    let signals = vec![0i32; df.height()]; // -1 = bearish divergence, 0 = none, 1 = bullish divergence
-    Ok(Series::new("lt_divergence".into(), signals))
+    let config = DivergenceConfig {
+        left_bars: 10,
+        right_bars: 10,
+        pivot_tolerance: 3,
+        min_pivot_distance: min_divergence_periods,
+        max_pivot_distance: 250.max(min_divergence_periods),
+        include_hidden: false,
+    };
+    divergence_signals(df, price_col, indicator_col, &config, "lt_divergence")
 }
 
 /// Calculate long-term support and resistance zones
@@ -188,21 +199,58 @@ pub fn fundamental_trend_correlation(
 
 /// Find long term price - indicator divergences
 ///
+/// Regular and hidden divergences with the default
+/// [`DivergenceConfig`] pivots.
+///
 /// # Arguments
 ///
 /// * `df` - DataFrame with indicator data
 /// * `price_col` - Column name for price
 /// * `indicator_col` - Column name for indicator
-/// * `min_divergence_periods` - Minimum periods to confirm divergence
+/// * `min_divergence_periods` - Minimum bars between the two price pivots
+///
+/// # Returns
+///
+/// * `Result<Series, PolarsError>` - Series named "price_indicator_divergence"
+///   with 1 for a bullish and -1 for a bearish divergence on the bar that
+///   confirms it, 0 otherwise
 pub fn find_price_indicator_divergence(
     df: &DataFrame,
-    _price_col: &str,
-    _indicator_col: &str,
-    _min_divergence_periods: usize,
+    price_col: &str,
+    indicator_col: &str,
+    min_divergence_periods: usize,
 ) -> Result<Series, PolarsError> {
-    // Placeholder implementation
-    let signals = vec![0i32; df.height()]; // -1 = bearish divergence, 0 = none, 1 = bullish divergence
-    Ok(Series::new("price_indicator_divergence".into(), signals))
+    let defaults = DivergenceConfig::default();
+    let config = DivergenceConfig {
+        min_pivot_distance: min_divergence_periods,
+        max_pivot_distance: defaults.max_pivot_distance.max(min_divergence_periods),
+        ..defaults
+    };
+    divergence_signals(
+        df,
+        price_col,
+        indicator_col,
+        &config,
+        "price_indicator_divergence",
+    )
+}
+
+/// Divergence direction per bar: 1 bullish, -1 bearish, 0 none
+fn divergence_signals(
+    df: &DataFrame,
+    price_col: &str,
+    indicator_col: &str,
+    config: &DivergenceConfig,
+    name: &str,
+) -> Result<Series, PolarsError> {
+    let detected = detect_divergence(df, price_col, indicator_col, config)?;
+    let signals: Vec<i32> = detected
+        .column("divergence")?
+        .i32()?
+        .into_no_null_iter()
+        .map(i32::signum)
+        .collect();
+    Ok(Series::new(name.into(), signals))
 }
 
 /// Identify significant support/resistance levels
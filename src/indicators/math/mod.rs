@@ -1,3 +1,7 @@
+use crate::indicators::naming::indicator_name;
+use crate::util::dataframe_utils::{
+    fixed_window, nan_to_null, nan_values, rolling_values, sliding_max, sliding_min,
+};
 use polars::prelude::*;
 
 /// Vector arithmetic addition
@@ -120,6 +124,9 @@ pub fn calculate_div(df: &DataFrame, col1: &str, col2: &str) -> PolarsResult<Ser
 
 /// Find maximum value over a specified window
 ///
+/// Missing values are skipped; the first `window - 1` rows and windows
+/// without any value are NaN. Runs in linear time regardless of `window`.
+///
 /// # Arguments
 ///
 /// * `df` - DataFrame containing the data
@@ -129,6 +136,19 @@ pub fn calculate_div(df: &DataFrame, col1: &str, col2: &str) -> PolarsResult<Ser
 /// # Returns
 ///
 /// Returns a PolarsResult containing the MAX Series
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::math::calculate_max;
+///
+/// let df = df! { "close" => [1.0, 3.0, f64::NAN, 2.0, 1.0] }.unwrap();
+/// let max = calculate_max(&df, "close", 2).unwrap();
+/// let values: Vec<f64> = max.f64().unwrap().into_no_null_iter().collect();
+/// assert!(values[0].is_nan());
+/// assert_eq!(&values[1..], &[3.0, 3.0, 2.0, 2.0]);
+/// ```
 pub fn calculate_max(df: &DataFrame, column: &str, window: usize) -> PolarsResult<Series> {
     if !df.schema().contains(column) {
         return Err(PolarsError::ComputeError(
@@ -136,38 +156,12 @@ pub fn calculate_max(df: &DataFrame, column: &str, window: usize) -> PolarsResul
         ));
     }
 
-    let series = df.column(column)?.f64()?;
-
-    let mut max_values = Vec::with_capacity(df.height());
-
-    // Fill initial values with NaN
-    for _i in 0..window - 1 {
-        max_values.push(f64::NAN);
-    }
-
-    // Calculate max for each window
-    for i in window - 1..df.height() {
-        let mut max_val = f64::NEG_INFINITY;
-        let mut all_nan = true;
-
-        for j in 0..window {
-            let val = series.get(i - j).unwrap_or(f64::NAN);
-            if !val.is_nan() {
-                max_val = max_val.max(val);
-                all_nan = false;
-            }
-        }
-
-        if all_nan {
-            max_values.push(f64::NAN);
-        } else {
-            max_values.push(max_val);
-        }
-    }
+    // NaN and null values are skipped; windows without any value are NaN
+    let values = nan_values(df.column(column)?.f64()?);
 
     Ok(Series::new(
-        format!("{column}_max_{window}").into(),
-        max_values,
+        indicator_name(&format!("{column}_max"), &[&window]).into(),
+        sliding_max(&values, window),
     ))
 }
 
@@ -189,38 +183,12 @@ pub fn calculate_min(df: &DataFrame, column: &str, window: usize) -> PolarsResul
         ));
     }
 
-    let series = df.column(column)?.f64()?;
-
-    let mut min_values = Vec::with_capacity(df.height());
-
-    // Fill initial values with NaN
-    for _i in 0..window - 1 {
-        min_values.push(f64::NAN);
-    }
-
-    // Calculate min for each window
-    for i in window - 1..df.height() {
-        let mut min_val = f64::INFINITY;
-        let mut all_nan = true;
-
-        for j in 0..window {
-            let val = series.get(i - j).unwrap_or(f64::NAN);
-            if !val.is_nan() {
-                min_val = min_val.min(val);
-                all_nan = false;
-            }
-        }
-
-        if all_nan {
-            min_values.push(f64::NAN);
-        } else {
-            min_values.push(min_val);
-        }
-    }
+    // NaN and null values are skipped; windows without any value are NaN
+    let values = nan_values(df.column(column)?.f64()?);
 
     Ok(Series::new(
-        format!("{column}_min_{window}").into(),
-        min_values,
+        indicator_name(&format!("{column}_min"), &[&window]).into(),
+        sliding_min(&values, window),
     ))
 }
 
@@ -242,38 +210,13 @@ pub fn calculate_sum(df: &DataFrame, column: &str, window: usize) -> PolarsResul
         ));
     }
 
-    let series = df.column(column)?.f64()?;
-
-    let mut sum_values = Vec::with_capacity(df.height());
-
-    // Fill initial values with NaN
-    for _i in 0..window - 1 {
-        sum_values.push(f64::NAN);
-    }
-
-    // Calculate sum for each window
-    for i in window - 1..df.height() {
-        let mut sum = 0.0;
-        let mut all_nan = true;
-
-        for j in 0..window {
-            let val = series.get(i - j).unwrap_or(f64::NAN);
-            if !val.is_nan() {
-                sum += val;
-                all_nan = false;
-            }
-        }
-
-        if all_nan {
-            sum_values.push(f64::NAN);
-        } else {
-            sum_values.push(sum);
-        }
-    }
+    // NaN and null values are skipped; windows without any value are NaN
+    let series = nan_to_null(df.column(column)?.f64()?);
+    let sum = series.rolling_sum(fixed_window(window, 1))?;
 
     Ok(Series::new(
-        format!("{column}_sum_{window}").into(),
-        sum_values,
+        indicator_name(&format!("{column}_sum"), &[&window]).into(),
+        rolling_values(&sum, window - 1)?,
     ))
 }
 
@@ -293,31 +236,15 @@ pub fn calculate_rolling_sum(
     column_name: &str,
     window: usize,
 ) -> PolarsResult<Series> {
-    // Get the column
-    let column = df.column(column_name)?.f64()?;
-    let n = column.len();
-
-    // Initialize a new vector for the results
-    let mut result = Vec::with_capacity(n);
-
-    // Calculate the first window-1 values which are null
-    for _i in 0..window - 1 {
-        result.push(f64::NAN);
-    }
+    // Missing values count as zero
+    let column = df.column(column_name)?.f64()?.fill_null_with_values(0.0)?;
+    let sum = column
+        .into_series()
+        .rolling_sum(fixed_window(window, window))?;
 
-    // Calculate the remaining values
-    for i in window - 1..n {
-        let mut sum = 0.0;
-        for j in 0..window {
-            sum += column.get(i - j).unwrap_or(0.0);
-        }
-        result.push(sum);
-    }
-
-    // Return the result as a Series
     Ok(Series::new(
-        format!("{}_sum{}", column_name, window).into(),
-        result,
+        indicator_name(&format!("{column_name}_sum"), &[&window]).into(),
+        rolling_values(&sum, window - 1)?,
     ))
 }
 
@@ -337,31 +264,15 @@ pub fn calculate_rolling_avg(
     column_name: &str,
     window: usize,
 ) -> PolarsResult<Series> {
-    // Get the column
-    let column = df.column(column_name)?.f64()?;
-    let n = column.len();
-
-    // Initialize a new vector for the results
-    let mut result = Vec::with_capacity(n);
-
-    // Calculate the first window-1 values which are null
-    for _i in 0..window - 1 {
-        result.push(f64::NAN);
-    }
+    // Missing values count as zero
+    let column = df.column(column_name)?.f64()?.fill_null_with_values(0.0)?;
+    let avg = column
+        .into_series()
+        .rolling_mean(fixed_window(window, window))?;
 
-    // Calculate the remaining values
-    for i in window - 1..n {
-        let mut sum = 0.0;
-        for j in 0..window {
-            sum += column.get(i - j).unwrap_or(0.0);
-        }
-        result.push(sum / window as f64);
-    }
-
-    // Return the result as a Series
     Ok(Series::new(
-        format!("{}_avg{}", column_name, window).into(),
-        result,
+        indicator_name(&format!("{column_name}_avg"), &[&window]).into(),
+        rolling_values(&avg, window - 1)?,
     ))
 }
 
@@ -381,48 +292,20 @@ pub fn calculate_rolling_std(
     column_name: &str,
     window: usize,
 ) -> PolarsResult<Series> {
-    // Get the column
-    let column = df.column(column_name)?.f64()?;
-    let n = column.len();
-
-    // Initialize a new vector for the results
-    let mut result = Vec::with_capacity(n);
-
-    // Calculate the first window-1 values which are null
-    for _i in 0..window - 1 {
-        result.push(f64::NAN);
+    // Missing values count as zero
+    let column = df.column(column_name)?.f64()?.fill_null_with_values(0.0)?;
+    let std = column
+        .into_series()
+        .rolling_std(fixed_window(window, window))?;
+
+    // Sample standard deviation; a single-value window has none and is zero
+    let mut result = rolling_values(&std, window - 1)?;
+    if window == 1 {
+        result.iter_mut().for_each(|v| *v = 0.0);
     }
 
-    // Calculate the remaining values
-    for i in window - 1..n {
-        let mut sum = 0.0;
-        let mut sum_sq = 0.0;
-
-        for j in 0..window {
-            let value = column.get(i - j).unwrap_or(0.0);
-            sum += value;
-            sum_sq += value * value;
-        }
-
-        let avg = sum / window as f64;
-        let variance = if window > 1 {
-            (sum_sq - sum * avg) / (window as f64 - 1.0)
-        } else {
-            0.0
-        };
-
-        if variance < 0.0 {
-            // Due to floating point errors, variance can be slightly negative
-            // when it should be zero. In this case, just return 0.0.
-            result.push(0.0);
-        } else {
-            result.push(variance.sqrt());
-        }
-    }
-
-    // Return the result as a Series
     Ok(Series::new(
-        format!("{}_std{}", column_name, window).into(),
+        indicator_name(&format!("{column_name}_std"), &[&window]).into(),
         result,
     ))
 }
@@ -470,7 +353,7 @@ pub fn calculate_rate_of_change(
 
     // Return the result as a Series
     Ok(Series::new(
-        format!("{}_roc{}", column_name, period).into(),
+        indicator_name(&format!("{column_name}_roc"), &[&period]).into(),
         result,
     ))
 }
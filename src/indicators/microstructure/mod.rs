@@ -0,0 +1,32 @@
+//! # Market Microstructure Indicators
+//!
+//! This module works on trade and quote tick data rather than bars. Tick
+//! DataFrames use these columns:
+//!
+//! - `price` and `size` - the trade
+//! - `side` (optional) - aggressor side, "buy"/"sell" or a signed number
+//! - `bid` and `ask` (optional) - the prevailing quote at the trade
+//!
+//! ## Available Indicators
+//!
+//! - Trade classification and rolling trade imbalance
+//! - Quoted and effective spreads
+//! - VPIN-style order flow toxicity
+//! - Order book depth imbalance, microprice and liquidity from L2
+//!   snapshots (`bid_price_{k}`, `bid_size_{k}`, `ask_price_{k}`,
+//!   `ask_size_{k}` per level), aligned to bars
+//!
+//! Ticks become OHLCV bars for the rest of the crate with
+//! [`aggregate_ticks`](crate::util::dataframe_utils::aggregate_ticks).
+
+mod order_book;
+mod spread;
+mod trade_sign;
+mod vpin;
+
+pub use order_book::{
+    align_book_features, calculate_book_features, BookAggregation, BookFeatureConfig,
+};
+pub use spread::calculate_spreads;
+pub use trade_sign::{calculate_trade_imbalance, classify_trades};
+pub use vpin::calculate_vpin;
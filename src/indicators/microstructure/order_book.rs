@@ -0,0 +1,315 @@
+//! # Order Book Snapshot Indicators
+//!
+//! Features of L2 order book snapshots: how the resting size is split
+//! between bids and asks, and fair-value estimates that account for it.
+//! Snapshots arrive far more often than bars, so [`align_book_features`]
+//! combines the snapshots of each bar for use in intraday strategies.
+
+use crate::error::TaError;
+use crate::util::dataframe_utils::{column_nan_values, series_nan_values};
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+
+/// Settings for [`calculate_book_features`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookFeatureConfig {
+    /// Levels per side used for depth, imbalance and the weighted mid
+    pub levels: usize,
+    /// Distances from the mid, in basis points, at which resting liquidity
+    /// is measured
+    pub distances_bps: Vec<f64>,
+    /// Timestamp column, carried into the features when present
+    pub time_column: String,
+}
+
+impl Default for BookFeatureConfig {
+    fn default() -> Self {
+        Self {
+            levels: 5,
+            distances_bps: vec![10.0, 50.0],
+            time_column: "timestamp".to_string(),
+        }
+    }
+}
+
+/// How the snapshots within a bar are combined by [`align_book_features`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BookAggregation {
+    /// The latest snapshot of the bar
+    #[default]
+    Last,
+    /// The mean of the bar's snapshots, skipping missing values
+    Mean,
+}
+
+/// Calculate depth imbalance, microprice, weighted mid and liquidity
+/// features from L2 order book snapshots
+///
+/// Snapshots have one row each, with level `k` (1 = best) of the book in
+/// "bid_price_{k}", "bid_size_{k}", "ask_price_{k}" and "ask_size_{k}".
+///
+/// - "book_mid": midpoint of the best bid and ask
+/// - "book_microprice": best bid and ask weighted by the opposite side's
+///   size, (bid * ask_size + ask * bid_size) / (bid_size + ask_size), which
+///   leans towards the side more likely to trade through
+/// - "book_weighted_mid": average of the size-weighted bid and ask prices
+///   over `levels` levels
+/// - "book_imbalance": (bid depth - ask depth) / (bid depth + ask depth)
+///   over `levels` levels, from -1 (all asks) to 1 (all bids)
+/// - "book_bid_depth_{d}bps" and "book_ask_depth_{d}bps": size resting
+///   within `d` basis points of the mid, for every distance
+///
+/// # Arguments
+///
+/// * `book` - DataFrame of snapshots
+/// * `config` - Levels, distances and time column
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing one row per snapshot with the time
+/// column (when present) and the features, NaN where the best quotes are
+/// missing or crossed
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::microstructure::{calculate_book_features, BookFeatureConfig};
+///
+/// let book = df! {
+///     "bid_price_1" => [100.0], "bid_size_1" => [3.0],
+///     "bid_price_2" => [99.9], "bid_size_2" => [1.0],
+///     "ask_price_1" => [100.1], "ask_size_1" => [1.0],
+///     "ask_price_2" => [100.2], "ask_size_2" => [5.0],
+/// }
+/// .unwrap();
+///
+/// let config = BookFeatureConfig { levels: 2, distances_bps: vec![10.0], ..Default::default() };
+/// let features = calculate_book_features(&book, &config).unwrap();
+/// let value = |name: &str| features.column(name).unwrap().f64().unwrap().get(0).unwrap();
+///
+/// // Three bids against one ask at the top: the microprice leans up
+/// assert!((value("book_microprice") - 100.075).abs() < 1e-9);
+/// assert!((value("book_imbalance") + 0.2).abs() < 1e-9);
+/// // 10 bps around 100.05 reaches down to 99.95 and up to 100.15
+/// assert_eq!(value("book_bid_depth_10bps"), 3.0);
+/// assert_eq!(value("book_ask_depth_10bps"), 1.0);
+/// ```
+pub fn calculate_book_features(
+    book: &DataFrame,
+    config: &BookFeatureConfig,
+) -> PolarsResult<DataFrame> {
+    if config.levels == 0 {
+        return Err(PolarsError::ComputeError(
+            "Order book features require at least one level".into(),
+        ));
+    }
+
+    let column = |name: String| -> PolarsResult<Vec<f64>> {
+        if !book.schema().contains(&name) {
+            return Err(PolarsError::ComputeError(
+                format!("Order book features require '{name}' column").into(),
+            ));
+        }
+        column_nan_values(book, &name)
+    };
+    let side = |prefix: &str| -> PolarsResult<Vec<(Vec<f64>, Vec<f64>)>> {
+        (1..=config.levels)
+            .map(|k| {
+                Ok((
+                    column(format!("{prefix}_price_{k}"))?,
+                    column(format!("{prefix}_size_{k}"))?,
+                ))
+            })
+            .collect()
+    };
+    let (bids, asks) = (side("bid")?, side("ask")?);
+
+    let n = book.height();
+    let mut mid = vec![f64::NAN; n];
+    let mut microprice = vec![f64::NAN; n];
+    let mut weighted_mid = vec![f64::NAN; n];
+    let mut imbalance = vec![f64::NAN; n];
+    let mut bid_depth = vec![vec![f64::NAN; n]; config.distances_bps.len()];
+    let mut ask_depth = vec![vec![f64::NAN; n]; config.distances_bps.len()];
+
+    for i in 0..n {
+        let (bid, bid_size) = (bids[0].0[i], bids[0].1[i]);
+        let (ask, ask_size) = (asks[0].0[i], asks[0].1[i]);
+        if !(ask >= bid && bid > 0.0) {
+            continue;
+        }
+        let m = (bid + ask) / 2.0;
+        mid[i] = m;
+        if bid_size + ask_size > 0.0 {
+            microprice[i] = (bid * ask_size + ask * bid_size) / (bid_size + ask_size);
+        }
+
+        // Resting levels of one side as (price, size), skipping missing ones
+        let levels = |side: &[(Vec<f64>, Vec<f64>)]| -> Vec<(f64, f64)> {
+            side.iter()
+                .map(|(price, size)| (price[i], size[i]))
+                .filter(|(price, size)| price.is_finite() && size.is_finite())
+                .collect()
+        };
+        let (bid_levels, ask_levels) = (levels(&bids), levels(&asks));
+        let depth = |levels: &[(f64, f64)]| levels.iter().map(|(_, size)| size).sum::<f64>();
+        let vwap = |levels: &[(f64, f64)]| {
+            levels.iter().map(|(price, size)| price * size).sum::<f64>() / depth(levels)
+        };
+        let (total_bid, total_ask) = (depth(&bid_levels), depth(&ask_levels));
+        if total_bid + total_ask > 0.0 {
+            imbalance[i] = (total_bid - total_ask) / (total_bid + total_ask);
+        }
+        if total_bid > 0.0 && total_ask > 0.0 {
+            weighted_mid[i] = (vwap(&bid_levels) + vwap(&ask_levels)) / 2.0;
+        }
+
+        for (d, distance) in config.distances_bps.iter().enumerate() {
+            let (floor, ceiling) = (
+                m * (1.0 - distance / 10_000.0),
+                m * (1.0 + distance / 10_000.0),
+            );
+            bid_depth[d][i] = bid_levels
+                .iter()
+                .filter(|(price, _)| *price >= floor)
+                .map(|(_, size)| size)
+                .sum();
+            ask_depth[d][i] = ask_levels
+                .iter()
+                .filter(|(price, _)| *price <= ceiling)
+                .map(|(_, size)| size)
+                .sum();
+        }
+    }
+
+    let mut columns = Vec::with_capacity(4 + 2 * config.distances_bps.len() + 1);
+    if book.schema().contains(&config.time_column) {
+        columns.push(book.column(&config.time_column)?.clone());
+    }
+    columns.push(Column::new("book_mid".into(), mid));
+    columns.push(Column::new("book_microprice".into(), microprice));
+    columns.push(Column::new("book_weighted_mid".into(), weighted_mid));
+    columns.push(Column::new("book_imbalance".into(), imbalance));
+    for ((distance, bid), ask) in config.distances_bps.iter().zip(bid_depth).zip(ask_depth) {
+        columns.push(Column::new(
+            format!("book_bid_depth_{distance}bps").into(),
+            bid,
+        ));
+        columns.push(Column::new(
+            format!("book_ask_depth_{distance}bps").into(),
+            ask,
+        ));
+    }
+    DataFrame::new(columns)
+}
+
+/// Join order book features onto bars
+///
+/// Bar timestamps mark the start of each bar: a bar covers the snapshots
+/// from its timestamp up to the next bar's, and the last bar every later
+/// snapshot. Snapshots before the first bar are dropped.
+///
+/// # Arguments
+///
+/// * `bars` - DataFrame of bars in ascending time order
+/// * `bar_time` - Timestamp column of `bars`
+/// * `features` - Per-snapshot features, e.g. from [`calculate_book_features`]
+/// * `feature_time` - Timestamp column of `features`
+/// * `aggregation` - How the snapshots of a bar are combined
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing `bars` with every other column of
+/// `features` appended as Float64, null for bars without snapshots, or
+/// InvalidParameter when a feature column name is already used by `bars`
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::microstructure::{align_book_features, BookAggregation};
+///
+/// let bars = df! {
+///     "timestamp" => ["2024-01-02 10:00:00", "2024-01-02 10:01:00"],
+///     "close" => [100.0, 100.2],
+/// }
+/// .unwrap();
+/// let features = df! {
+///     "timestamp" => ["2024-01-02 10:00:30", "2024-01-02 10:00:50", "2024-01-02 10:01:10"],
+///     "book_imbalance" => [0.25, 0.75, -0.5],
+/// }
+/// .unwrap();
+///
+/// let aligned = align_book_features(&bars, "timestamp", &features, "timestamp", BookAggregation::Mean).unwrap();
+/// let imbalance = aligned.column("book_imbalance").unwrap().f64().unwrap();
+/// assert_eq!(imbalance.get(0), Some(0.5));
+/// assert_eq!(imbalance.get(1), Some(-0.5));
+/// ```
+pub fn align_book_features(
+    bars: &DataFrame,
+    bar_time: &str,
+    features: &DataFrame,
+    feature_time: &str,
+    aggregation: BookAggregation,
+) -> PolarsResult<DataFrame> {
+    let bar_times = parse_timestamp_column(bars, bar_time)?;
+    let starts: Vec<(DateTime<Utc>, usize)> = bar_times
+        .iter()
+        .enumerate()
+        .filter_map(|(row, time)| Some(((*time)?, row)))
+        .collect();
+    if starts.windows(2).any(|w| w[1].0 < w[0].0) {
+        return Err(PolarsError::ComputeError(
+            "Order book alignment requires bars in ascending time order".into(),
+        ));
+    }
+
+    // Bar of each snapshot, with the snapshot's time for ordering
+    let snapshot_times = parse_timestamp_column(features, feature_time)?;
+    let mut snapshots: Vec<(DateTime<Utc>, usize, usize)> = snapshot_times
+        .iter()
+        .enumerate()
+        .filter_map(|(row, time)| {
+            let time = (*time)?;
+            let bar = starts.partition_point(|(start, _)| *start <= time);
+            Some((time, row, starts.get(bar.checked_sub(1)?)?.1))
+        })
+        .collect();
+    snapshots.sort_by_key(|&(time, _, _)| time);
+
+    let mut aligned = bars.clone();
+    for column in features.get_columns() {
+        if column.name().as_str() == feature_time {
+            continue;
+        }
+        if bars.schema().contains(column.name()) {
+            return Err(TaError::invalid_parameter(
+                column.name().as_str(),
+                "is already a column of the bars",
+            )
+            .into());
+        }
+        let values = series_nan_values(column.as_materialized_series())?;
+
+        let mut sums = vec![0.0; bars.height()];
+        let mut counts = vec![0usize; bars.height()];
+        let mut last = vec![f64::NAN; bars.height()];
+        for &(_, row, bar) in &snapshots {
+            last[bar] = values[row];
+            if !values[row].is_nan() {
+                sums[bar] += values[row];
+                counts[bar] += 1;
+            }
+        }
+        let combined: Vec<Option<f64>> = (0..bars.height())
+            .map(|bar| match aggregation {
+                BookAggregation::Last => Some(last[bar]).filter(|v| !v.is_nan()),
+                BookAggregation::Mean => (counts[bar] > 0).then(|| sums[bar] / counts[bar] as f64),
+            })
+            .collect();
+        aligned.with_column(Series::new(column.name().clone(), combined))?;
+    }
+    Ok(aligned)
+}
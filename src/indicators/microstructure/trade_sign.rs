@@ -0,0 +1,174 @@
+use crate::util::dataframe_utils::check_window_size;
+use polars::prelude::*;
+
+/// Classify each trade as buyer- (1) or seller-initiated (-1)
+///
+/// A "side" column is used when present, either as "buy"/"sell" strings
+/// ("b"/"s" also work, in any case) or as a signed number. Without one the
+/// Lee-Ready rule applies: a trade above the quote midpoint is a buy, below
+/// it a sell, and at the midpoint - or with no "bid"/"ask" columns - the tick
+/// rule decides (up from the last different price is a buy, down a sell).
+/// Trades that cannot be classified are 0.
+///
+/// # Arguments
+///
+/// * `df` - Tick DataFrame with a "price" column and optionally "side",
+///   "bid" and "ask" columns
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing an Int32 Series named "trade_sign"
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::microstructure::classify_trades;
+///
+/// let ticks = df! {
+///     "price" => [100.02, 100.00, 100.01, 100.01],
+///     "bid" => [100.00, 100.00, 100.00, 100.00],
+///     "ask" => [100.02, 100.02, 100.02, 100.02],
+/// }
+/// .unwrap();
+///
+/// let signs = classify_trades(&ticks).unwrap();
+/// // At the ask, at the bid, then twice at the midpoint after an uptick
+/// assert_eq!(signs.i32().unwrap().to_vec(), vec![Some(1), Some(-1), Some(1), Some(1)]);
+/// ```
+pub fn classify_trades(df: &DataFrame) -> PolarsResult<Series> {
+    if !df.schema().contains("price") {
+        return Err(PolarsError::ComputeError(
+            "Trade classification requires 'price' column".into(),
+        ));
+    }
+
+    if let Some(side) = df.schema().get("side") {
+        let signs: Vec<i32> = if side.is_string() {
+            df.column("side")?
+                .str()?
+                .iter()
+                .map(
+                    |s| match s.map(|s| s.trim().to_ascii_lowercase()).as_deref() {
+                        Some("buy" | "b") => 1,
+                        Some("sell" | "s") => -1,
+                        _ => 0,
+                    },
+                )
+                .collect()
+        } else {
+            df.column("side")?
+                .cast(&DataType::Float64)?
+                .f64()?
+                .iter()
+                .map(|s| match s {
+                    Some(s) if s > 0.0 => 1,
+                    Some(s) if s < 0.0 => -1,
+                    _ => 0,
+                })
+                .collect()
+        };
+        return Ok(Series::new("trade_sign".into(), signs));
+    }
+
+    let price = df.column("price")?.f64()?;
+    let quotes = if df.schema().contains("bid") && df.schema().contains("ask") {
+        Some((df.column("bid")?.f64()?, df.column("ask")?.f64()?))
+    } else {
+        None
+    };
+
+    let mut signs = vec![0i32; df.height()];
+    let mut last_price: Option<f64> = None;
+    let mut tick_sign = 0;
+    for (i, sign) in signs.iter_mut().enumerate() {
+        let Some(p) = price.get(i).filter(|p| !p.is_nan()) else {
+            continue;
+        };
+        if let Some(last) = last_price {
+            if p > last {
+                tick_sign = 1;
+            } else if p < last {
+                tick_sign = -1;
+            }
+        }
+        last_price = Some(p);
+
+        let mid = quotes.and_then(|(bid, ask)| match (bid.get(i), ask.get(i)) {
+            (Some(b), Some(a)) if !b.is_nan() && !a.is_nan() => Some((b + a) / 2.0),
+            _ => None,
+        });
+        *sign = match mid {
+            Some(mid) if p > mid => 1,
+            Some(mid) if p < mid => -1,
+            _ => tick_sign,
+        };
+    }
+
+    Ok(Series::new("trade_sign".into(), signs))
+}
+
+/// Calculate the rolling trade imbalance
+///
+/// Signed volume over total volume across the trailing `window` trades,
+/// from -1 (all selling) to 1 (all buying), with trades signed by
+/// [`classify_trades`].
+///
+/// # Arguments
+///
+/// * `df` - Tick DataFrame with "price" and "size" columns, see
+///   [`classify_trades`] for the optional columns
+/// * `window` - Number of trades in each window
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named "trade_imbalance", NaN
+/// until the first full window and for windows without volume
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::microstructure::calculate_trade_imbalance;
+///
+/// let ticks = df! {
+///     "price" => [10.0, 10.0, 10.0],
+///     "size" => [300.0, 100.0, 100.0],
+///     "side" => ["buy", "sell", "buy"],
+/// }
+/// .unwrap();
+///
+/// let imbalance = calculate_trade_imbalance(&ticks, 3).unwrap();
+/// assert_eq!(imbalance.f64().unwrap().get(2), Some(0.6));
+/// ```
+pub fn calculate_trade_imbalance(df: &DataFrame, window: usize) -> PolarsResult<Series> {
+    check_window_size(df, window, "Trade imbalance")?;
+    if window == 0 {
+        return Err(PolarsError::ComputeError(
+            "Trade imbalance window must be positive".into(),
+        ));
+    }
+
+    let signs = classify_trades(df)?;
+    let signs = signs.i32()?;
+    let size = df.column("size")?.f64()?;
+
+    let (signed, total): (Vec<f64>, Vec<f64>) = signs
+        .iter()
+        .zip(size)
+        .map(|(sign, size)| match (sign, size) {
+            (Some(sign), Some(size)) if !size.is_nan() => (sign as f64 * size, size),
+            _ => (0.0, 0.0),
+        })
+        .unzip();
+
+    let mut imbalance = vec![f64::NAN; df.height()];
+    for i in window - 1..df.height() {
+        let volume: f64 = total[i + 1 - window..=i].iter().sum();
+        if volume > 0.0 {
+            imbalance[i] = signed[i + 1 - window..=i].iter().sum::<f64>() / volume;
+        }
+    }
+
+    Ok(Series::new("trade_imbalance".into(), imbalance))
+}
@@ -10,6 +10,7 @@
 //!
 //! - [`stock`](stock/index.html): Indicators for stock/equity markets
 //! - [`options`](options/index.html): Indicators for options trading
+//! - [`crypto`](crypto/index.html): Indicators for crypto spot and perpetual futures markets
 //!
 //! ## Traditional Indicator Categories
 //!
@@ -23,26 +24,36 @@
 //! - [`pattern_recognition`](pattern_recognition/index.html): Indicators that identify chart patterns
 //! - [`price_transform`](price_transform/index.html): Indicators that transform price data
 //! - [`stats`](stats/index.html): Statistical indicators
+//! - [`state_space`](state_space/index.html): Kalman filter price smoothing and dynamic regression
 //! - [`math`](math/index.html): Mathematical utility functions
+//! - [`microstructure`](microstructure/index.html): Spread, trade imbalance and toxicity indicators from tick data
 //!
 //! ## Timeframe-Specific Indicator Modules
 //!
 //! - [`day_trading`](day_trading/index.html): Indicators optimized for intraday trading
 //! - [`short_term`](short_term/index.html): Indicators optimized for short-term trading (days to weeks)
 //! - [`long_term`](long_term/index.html): Indicators optimized for long-term analysis (weeks to months)
+//!
+//! ## Signal Helpers
+//!
+//! - [`signal_utils`](signal_utils/index.html): Crossovers, bars since and extremes since a condition
+//! - [`divergence`](divergence/index.html): Regular and hidden divergences between price and an indicator
 
 // Asset-specific indicator modules
+pub mod crypto;
 pub mod options;
 pub mod stock;
 
 // Traditional indicator category modules
 pub mod cycle;
 pub mod math;
+pub mod microstructure;
 pub mod momentum;
 pub mod moving_averages;
 pub mod oscillators;
 pub mod pattern_recognition;
 pub mod price_transform;
+pub mod state_space;
 pub mod stats;
 pub mod trend;
 pub mod volatility;
@@ -54,16 +65,30 @@ pub mod long_term;
 pub mod short_term;
 
 // Utility modules
+pub mod accessor;
 pub mod add_indicators;
+pub mod divergence;
+pub mod naming;
+pub mod preset;
+pub mod signal_utils;
+pub mod spec;
 pub mod test_util;
 
-// Re-export add_technical_indicators function
-pub use add_indicators::add_technical_indicators;
+// Re-export add_technical_indicators functions
+pub use accessor::TaDataFrameExt;
+#[cfg(feature = "parallel")]
+pub use add_indicators::add_technical_indicators_parallel;
+pub use add_indicators::{
+    add_technical_indicators, add_technical_indicators_lazy, add_technical_indicators_with_columns,
+    add_technical_indicators_with_config, IndicatorConfig,
+};
+pub use preset::IndicatorPreset;
+pub use spec::IndicatorSpec;
 
 // Re-export commonly used indicators for convenient access
 pub use momentum::calculate_roc;
 pub use moving_averages::{calculate_ema, calculate_sma, calculate_vwap, calculate_wma};
-pub use oscillators::{calculate_macd, calculate_rsi};
+pub use oscillators::{calculate_macd, calculate_rsi, MacdOutput};
 pub use volatility::{calculate_atr, calculate_bollinger_bands};
 pub use volume::{calculate_cmf, calculate_mfi, calculate_obv};
 
@@ -1,8 +1,26 @@
-use crate::util::dataframe_utils::check_window_size;
+use crate::util::dataframe_utils::{check_window_size, fixed_window, rolling_values};
 use polars::prelude::*;
 
+/// How the CMO averages gains and losses
+///
+/// See CHANGELOG.md for the versions in which the default changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CmoSmoothing {
+    /// Behavior up to 1.0.8: sums of the last `window` gains and losses,
+    /// NaN on flat prices
+    Legacy,
+    /// Wilder's smoothing like RSI, as in TA-Lib: the first average is the
+    /// mean over the first `window` changes, at index `window`, and flat
+    /// prices give 0.0
+    #[default]
+    Wilder,
+}
+
 /// Calculates Chande Momentum Oscillator (CMO)
-/// Formula: CMO = 100 * ((Sum of gains - Sum of losses) / (Sum of gains + Sum of losses))
+/// Formula: CMO = 100 * ((Avg gain - Avg loss) / (Avg gain + Avg loss))
+///
+/// The averages use Wilder's smoothing ([`CmoSmoothing::Wilder`]); use
+/// [`calculate_cmo_with_smoothing`] to reproduce the legacy behavior.
 ///
 /// The CMO indicator is similar to other momentum oscillators but has a different formula.
 /// It oscillates between -100 and +100, with overbought/oversold typically at +/-50.
@@ -17,65 +35,118 @@ use polars::prelude::*;
 ///
 /// Returns a PolarsResult containing the CMO Series
 pub fn calculate_cmo(df: &DataFrame, window: usize, column: &str) -> PolarsResult<Series> {
+    calculate_cmo_with_smoothing(df, window, column, CmoSmoothing::default())
+}
+
+/// Calculates Chande Momentum Oscillator (CMO) with explicit smoothing
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data
+/// * `window` - Window size for CMO calculation (typically 14)
+/// * `column` - Column name to use for calculations (default "close")
+/// * `smoothing` - How gains and losses are averaged
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the CMO Series
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::momentum::{calculate_cmo_with_smoothing, CmoSmoothing};
+///
+/// // Changes +2, -1, +1
+/// let df = df! { "close" => [10.0, 12.0, 11.0, 12.0] }.unwrap();
+///
+/// // Mean gain 1.0 and loss 0.5, then (1.0 + 1.0) / 2 and 0.5 / 2
+/// let wilder = calculate_cmo_with_smoothing(&df, 2, "close", CmoSmoothing::Wilder).unwrap();
+/// assert_eq!(wilder.f64().unwrap().get(3), Some(100.0 * 0.75 / 1.25));
+///
+/// // Gains 1.0 and losses 1.0 over the last two bars
+/// let legacy = calculate_cmo_with_smoothing(&df, 2, "close", CmoSmoothing::Legacy).unwrap();
+/// assert_eq!(legacy.f64().unwrap().get(3), Some(0.0));
+/// ```
+pub fn calculate_cmo_with_smoothing(
+    df: &DataFrame,
+    window: usize,
+    column: &str,
+    smoothing: CmoSmoothing,
+) -> PolarsResult<Series> {
     check_window_size(df, window, "CMO")?;
 
     let price = df.column(column)?.f64()?;
-    let mut cmo_values = Vec::with_capacity(df.height());
 
-    // Fill initial values with NaN
-    for _ in 0..window {
-        cmo_values.push(f64::NAN);
+    // Change of each bar against the previous one; changes involving a
+    // missing price are skipped
+    let changes: Vec<Option<f64>> = std::iter::once(None)
+        .chain(
+            price
+                .iter()
+                .zip(price.iter().skip(1))
+                .map(|(previous, current)| match (previous, current) {
+                    (Some(previous), Some(current)) if !(current - previous).is_nan() => {
+                        Some(current - previous)
+                    }
+                    _ => None,
+                }),
+        )
+        .collect();
+    if smoothing == CmoSmoothing::Legacy {
+        return cmo_from_sums(&changes, window);
     }
 
-    if df.height() <= window {
-        return Ok(Series::new("cmo".into(), cmo_values));
-    }
-
-    // Calculate price changes
-    let mut changes = Vec::with_capacity(df.height() - 1);
-
-    for i in 1..df.height() {
-        let current = price.get(i).unwrap_or(f64::NAN);
-        let previous = price.get(i - 1).unwrap_or(f64::NAN);
-
-        if !current.is_nan() && !previous.is_nan() {
-            changes.push(current - previous);
+    let mut cmo_values = vec![f64::NAN; df.height()];
+    let (mut avg_gain, mut avg_loss, mut seen) = (0.0, 0.0, 0usize);
+    for (i, change) in changes.iter().enumerate() {
+        let Some(change) = *change else {
+            continue;
+        };
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        seen += 1;
+        if seen <= window {
+            // Sums until the first `window` changes are in
+            avg_gain += gain;
+            avg_loss += loss;
+            if seen < window {
+                continue;
+            }
+            avg_gain /= window as f64;
+            avg_loss /= window as f64;
         } else {
-            changes.push(f64::NAN);
+            avg_gain = (avg_gain * (window - 1) as f64 + gain) / window as f64;
+            avg_loss = (avg_loss * (window - 1) as f64 + loss) / window as f64;
         }
+        cmo_values[i] = if avg_gain + avg_loss > 0.0 {
+            100.0 * (avg_gain - avg_loss) / (avg_gain + avg_loss)
+        } else {
+            0.0
+        };
     }
 
-    // Calculate CMO for each window
-    for i in window..df.height() {
-        let mut sum_gains = 0.0;
-        let mut sum_losses = 0.0;
-        let mut valid_periods = 0;
+    Ok(Series::new("cmo".into(), cmo_values))
+}
 
-        // Sum up gains and losses in the window
-        for j in (i - window)..(i) {
-            if j >= changes.len() {
-                continue;
-            }
+/// CMO from rolling sums of gains and losses, the legacy definition
+fn cmo_from_sums(changes: &[Option<f64>], window: usize) -> PolarsResult<Series> {
+    let gains: Float64Chunked = changes.iter().map(|c| c.map(|c| c.max(0.0))).collect();
+    let losses: Float64Chunked = changes.iter().map(|c| c.map(|c| (-c).max(0.0))).collect();
 
-            let change = changes[j];
-            if !change.is_nan() {
-                valid_periods += 1;
-                if change > 0.0 {
-                    sum_gains += change;
-                } else if change < 0.0 {
-                    sum_losses += change.abs();
-                }
-            }
-        }
+    let sum_gains = gains.into_series().rolling_sum(fixed_window(window, 1))?;
+    let sum_losses = losses.into_series().rolling_sum(fixed_window(window, 1))?;
 
-        // Calculate CMO
-        if valid_periods > 0 && (sum_gains + sum_losses) > 0.0 {
-            let cmo = 100.0 * ((sum_gains - sum_losses) / (sum_gains + sum_losses));
-            cmo_values.push(cmo);
-        } else {
-            cmo_values.push(f64::NAN);
-        }
-    }
+    let cmo_values: Vec<f64> = rolling_values(&sum_gains, window)?
+        .into_iter()
+        .zip(rolling_values(&sum_losses, window)?)
+        .map(|(sum_gains, sum_losses)| {
+            if sum_gains + sum_losses > 0.0 {
+                100.0 * ((sum_gains - sum_losses) / (sum_gains + sum_losses))
+            } else {
+                f64::NAN
+            }
+        })
+        .collect();
 
     Ok(Series::new("cmo".into(), cmo_values))
 }
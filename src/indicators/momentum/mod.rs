@@ -13,10 +13,10 @@ mod rsi;
 // Re-export indicators
 pub use bop::calculate_bop;
 pub use cci::calculate_cci;
-pub use cmo::calculate_cmo;
+pub use cmo::{calculate_cmo, calculate_cmo_with_smoothing, CmoSmoothing};
 pub use mom::calculate_mom;
 pub use roc::calculate_roc;
 pub use rocp::calculate_rocp;
 pub use rocr::calculate_rocr;
 pub use rocr100::calculate_rocr100;
-pub use rsi::calculate_rsi;
+pub use rsi::{calculate_rsi, calculate_rsi_with_alignment, RsiChangeAlignment};
@@ -1,10 +1,28 @@
+use crate::indicators::oscillators;
 use crate::util::dataframe_utils::check_window_size;
 use polars::prelude::*;
 
+/// Which price change each RSI bar after the first adds to the averages
+///
+/// See CHANGELOG.md for the versions in which the default changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RsiChangeAlignment {
+    /// Behavior up to 1.0.8: each bar after the first smooths in the
+    /// previous bar's change, and missing prices count as no change
+    Legacy,
+    /// Each bar smooths in its own change, the values of
+    /// [`oscillators::calculate_rsi`]
+    #[default]
+    CurrentBar,
+}
+
 /// Calculates Relative Strength Index (RSI)
 /// Formula: RSI = 100 - (100 / (1 + RS))
 /// where RS = Average Gain / Average Loss
 ///
+/// Each bar smooths in its own change ([`RsiChangeAlignment::CurrentBar`]);
+/// use [`calculate_rsi_with_alignment`] to reproduce the legacy behavior.
+///
 /// # Arguments
 ///
 /// * `df` - DataFrame containing the price data
@@ -13,77 +31,96 @@ use polars::prelude::*;
 ///
 /// # Returns
 ///
-/// Returns a PolarsResult containing the RSI Series
+/// Returns a PolarsResult containing the RSI Series, the values of
+/// [`oscillators::calculate_rsi`] under the name "rsi"
 pub fn calculate_rsi(df: &DataFrame, window: usize, column: &str) -> PolarsResult<Series> {
-    check_window_size(df, window, "RSI")?;
-
-    let price = df.column(column)?.f64()?;
-    let mut rsi_values = Vec::with_capacity(df.height());
+    calculate_rsi_with_alignment(df, window, column, RsiChangeAlignment::default())
+}
 
-    // Fill initial values with NaN until we have enough data
-    for _ in 0..window {
-        rsi_values.push(f64::NAN);
-    }
+/// Calculates Relative Strength Index (RSI) with an explicit change alignment
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data
+/// * `window` - Window size for RSI (typically 14)
+/// * `column` - Column name to use for calculations (default "close")
+/// * `alignment` - Which change each bar adds to the averages
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the RSI Series named "rsi"
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::momentum::{calculate_rsi_with_alignment, RsiChangeAlignment};
+///
+/// // Changes +2, -2, +2
+/// let df = df! { "close" => [10.0, 12.0, 10.0, 12.0] }.unwrap();
+///
+/// // Averages (1.5, 0.5) after bar 3's +2
+/// let current =
+///     calculate_rsi_with_alignment(&df, 2, "close", RsiChangeAlignment::CurrentBar).unwrap();
+/// assert_eq!(current.f64().unwrap().get(3), Some(75.0));
+///
+/// // Averages (0.5, 1.5) after bar 2's -2 again
+/// let legacy = calculate_rsi_with_alignment(&df, 2, "close", RsiChangeAlignment::Legacy).unwrap();
+/// assert_eq!(legacy.f64().unwrap().get(3), Some(25.0));
+/// ```
+pub fn calculate_rsi_with_alignment(
+    df: &DataFrame,
+    window: usize,
+    column: &str,
+    alignment: RsiChangeAlignment,
+) -> PolarsResult<Series> {
+    check_window_size(df, window, "RSI")?;
 
+    // Too short for a first value
     if df.height() <= window {
-        return Ok(Series::new("rsi".into(), rsi_values));
+        return Ok(Series::new("rsi".into(), vec![f64::NAN; df.height()]));
     }
 
-    // Calculate first differences
-    let mut gains = Vec::with_capacity(df.height() - 1);
-    let mut losses = Vec::with_capacity(df.height() - 1);
-
-    for i in 1..df.height() {
-        let current = price.get(i).unwrap_or(f64::NAN);
-        let previous = price.get(i - 1).unwrap_or(f64::NAN);
-
-        if current.is_nan() || previous.is_nan() {
-            gains.push(0.0);
-            losses.push(0.0);
-            continue;
-        }
-
-        let change = current - previous;
-
-        if change > 0.0 {
-            gains.push(change);
-            losses.push(0.0);
-        } else {
-            gains.push(0.0);
-            losses.push(change.abs());
+    match alignment {
+        RsiChangeAlignment::Legacy => rsi_with_previous_change(df, window, column),
+        RsiChangeAlignment::CurrentBar => {
+            Ok(oscillators::calculate_rsi(df, window, column)?.with_name("rsi".into()))
         }
     }
+}
 
-    // Calculate first average gain and loss
-    let mut avg_gain = gains.iter().take(window).sum::<f64>() / window as f64;
-    let mut avg_loss = losses.iter().take(window).sum::<f64>() / window as f64;
-
-    // Calculate first RSI
-    let rs = if avg_loss == 0.0 {
-        100.0
-    } else {
-        avg_gain / avg_loss
-    };
-    let rsi = 100.0 - (100.0 / (1.0 + rs));
-    rsi_values.push(rsi);
-
-    // Calculate remaining RSI values using smoothed averages
-    for i in window + 1..df.height() {
-        let idx = i - 1;
-        let gain = gains[idx - 1];
-        let loss = losses[idx - 1];
-
-        // Smoothed averages formula
-        avg_gain = ((avg_gain * (window - 1) as f64) + gain) / window as f64;
-        avg_loss = ((avg_loss * (window - 1) as f64) + loss) / window as f64;
+/// RSI whose bars after the first smooth in the previous bar's change, the
+/// legacy definition
+fn rsi_with_previous_change(df: &DataFrame, window: usize, column: &str) -> PolarsResult<Series> {
+    let price = df.column(column)?.f64()?;
+    let (gains, losses): (Vec<f64>, Vec<f64>) = (1..df.height())
+        .map(|i| match (price.get(i), price.get(i - 1)) {
+            (Some(current), Some(previous)) if !current.is_nan() && !previous.is_nan() => {
+                let change = current - previous;
+                (change.max(0.0), (-change).max(0.0))
+            }
+            _ => (0.0, 0.0),
+        })
+        .unzip();
 
+    let rsi = |avg_gain: f64, avg_loss: f64| {
         let rs = if avg_loss == 0.0 {
             100.0
         } else {
             avg_gain / avg_loss
         };
-        let rsi = 100.0 - (100.0 / (1.0 + rs));
-        rsi_values.push(rsi);
+        100.0 - (100.0 / (1.0 + rs))
+    };
+
+    let mut rsi_values = vec![f64::NAN; window];
+    let mut avg_gain = gains.iter().take(window).sum::<f64>() / window as f64;
+    let mut avg_loss = losses.iter().take(window).sum::<f64>() / window as f64;
+    rsi_values.push(rsi(avg_gain, avg_loss));
+
+    for i in window + 1..df.height() {
+        avg_gain = (avg_gain * (window - 1) as f64 + gains[i - 2]) / window as f64;
+        avg_loss = (avg_loss * (window - 1) as f64 + losses[i - 2]) / window as f64;
+        rsi_values.push(rsi(avg_gain, avg_loss));
     }
 
     Ok(Series::new("rsi".into(), rsi_values))
@@ -1,8 +1,25 @@
 use crate::util::dataframe_utils::check_window_size;
 use polars::prelude::*;
 
+/// How the EMA treats missing (null or NaN) input values
+///
+/// See CHANGELOG.md for the versions in which the default changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmaNanHandling {
+    /// Behavior up to 1.0.8: nulls are read as 0.0 and a NaN input makes
+    /// every later value NaN
+    Legacy,
+    /// Missing inputs are skipped: the seed SMA uses the first `window`
+    /// valid values and a missing bar carries the previous EMA forward
+    #[default]
+    Skip,
+}
+
 /// Calculates Exponential Moving Average (EMA)
 ///
+/// Missing values are skipped ([`EmaNanHandling::Skip`]); use
+/// [`calculate_ema_with_nan_handling`] to reproduce the legacy behavior.
+///
 /// # Arguments
 ///
 /// * `df` - DataFrame containing the input data
@@ -13,9 +30,49 @@ use polars::prelude::*;
 ///
 /// Returns a PolarsResult containing the EMA Series
 pub fn calculate_ema(df: &DataFrame, column: &str, window: usize) -> PolarsResult<Series> {
+    calculate_ema_with_nan_handling(df, column, window, EmaNanHandling::default())
+}
+
+/// Calculates Exponential Moving Average (EMA) with explicit missing-value handling
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the input data
+/// * `column` - Column name to calculate EMA on
+/// * `window` - Window size for the EMA
+/// * `nan_handling` - How null and NaN inputs are treated
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the EMA Series
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::moving_averages::{calculate_ema_with_nan_handling, EmaNanHandling};
+///
+/// let df = df! { "close" => [1.0, 2.0, 3.0, f64::NAN, 5.0] }.unwrap();
+///
+/// let skip = calculate_ema_with_nan_handling(&df, "close", 2, EmaNanHandling::Skip).unwrap();
+/// assert!(!skip.f64().unwrap().get(4).unwrap().is_nan());
+///
+/// let legacy = calculate_ema_with_nan_handling(&df, "close", 2, EmaNanHandling::Legacy).unwrap();
+/// assert!(legacy.f64().unwrap().get(4).unwrap().is_nan());
+/// ```
+pub fn calculate_ema_with_nan_handling(
+    df: &DataFrame,
+    column: &str,
+    window: usize,
+    nan_handling: EmaNanHandling,
+) -> PolarsResult<Series> {
     // Check we have enough data
     check_window_size(df, window, "EMA")?;
 
+    if nan_handling == EmaNanHandling::Skip {
+        return ema_skipping_missing(df.column(column)?.f64()?, window);
+    }
+
     let series = df.column(column)?.f64()?.clone().into_series();
     let series_ca = series.f64()?;
     let alpha = 2.0 / (window as f64 + 1.0);
@@ -49,3 +106,29 @@ pub fn calculate_ema(df: &DataFrame, column: &str, window: usize) -> PolarsResul
 
     Ok(Series::new("ema".into(), ema_values))
 }
+
+/// EMA that ignores null and NaN inputs
+fn ema_skipping_missing(values: &Float64Chunked, window: usize) -> PolarsResult<Series> {
+    let alpha = 2.0 / (window as f64 + 1.0);
+    let mut ema_values = Vec::with_capacity(values.len());
+    let mut seed_sum = 0.0;
+    let mut seed_count = 0;
+    let mut prev_ema = f64::NAN;
+
+    for value in values {
+        match value.filter(|v| !v.is_nan()) {
+            Some(price) if seed_count < window => {
+                seed_sum += price;
+                seed_count += 1;
+                if seed_count == window {
+                    prev_ema = seed_sum / window as f64;
+                }
+            }
+            Some(price) => prev_ema = alpha * price + (1.0 - alpha) * prev_ema,
+            None => {}
+        }
+        ema_values.push(prev_ema);
+    }
+
+    Ok(Series::new("ema".into(), ema_values))
+}
@@ -10,5 +10,7 @@ pub mod wma;
 pub use ema::*;
 pub use hull::calculate_hma;
 pub use sma::*;
-pub use vwap::calculate_vwap;
+pub use vwap::{
+    calculate_anchored_vwap, calculate_anchored_vwap_at, calculate_session_vwap, calculate_vwap,
+};
 pub use wma::*;
@@ -1,4 +1,7 @@
-use crate::util::dataframe_utils::check_window_size;
+use crate::util::calendar::{calculate_session_ids, MarketCalendar};
+use crate::util::dataframe_utils::{check_window_size, nan_values};
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
 use polars::prelude::*;
 
 /// Calculates Volume-Weighted Average Price (VWAP)
@@ -7,6 +10,9 @@ use polars::prelude::*;
 /// (price multiplied by the number of shares traded) and then dividing by the
 /// total shares traded for the day.
 ///
+/// The cumulative form never resets; use [`calculate_session_vwap`] for
+/// intraday data and [`calculate_anchored_vwap`] to start from a chosen bar.
+///
 /// # Arguments
 ///
 /// * `df` - DataFrame containing high, low, close, and volume data
@@ -93,3 +99,221 @@ pub fn calculate_vwap(df: &DataFrame, lookback: usize) -> PolarsResult<Series> {
 
     Ok(Series::new("vwap".into(), vwap_values))
 }
+
+/// Calculates session-anchored VWAP, restarting at the first bar of every
+/// trading session
+///
+/// Sessions come from `calendar` (see
+/// [`calculate_session_ids`](crate::util::calendar::calculate_session_ids)),
+/// so the anchor resets each trading day even across weekends, holidays and
+/// overnight sessions. Bars outside every session, such as pre- and
+/// post-market bars, are NaN and do not count towards any session.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing high, low, close, volume and timestamp data
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar defining the sessions
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the session VWAP Series named "session_vwap"
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::moving_averages::calculate_session_vwap;
+/// use rustalib::util::calendar::MarketCalendar;
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-02 10:00:00", "2024-01-02 11:00:00", "2024-01-03 10:00:00"],
+///     "high" => [10.0, 12.0, 20.0],
+///     "low" => [10.0, 12.0, 20.0],
+///     "close" => [10.0, 12.0, 20.0],
+///     "volume" => [100.0, 300.0, 50.0],
+/// }
+/// .unwrap();
+///
+/// let vwap = calculate_session_vwap(&df, "timestamp", &MarketCalendar::crypto()).unwrap();
+/// let vwap = vwap.f64().unwrap();
+/// assert_eq!(vwap.get(1), Some(11.5));
+/// // The second day starts over from its own first bar
+/// assert_eq!(vwap.get(2), Some(20.0));
+/// ```
+pub fn calculate_session_vwap(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+) -> PolarsResult<Series> {
+    let (typical, volume) = typical_price_volume(df)?;
+    let session_ids = calculate_session_ids(df, time_column, calendar)?;
+
+    let mut vwap_values = vec![f64::NAN; df.height()];
+    let mut current_session = None;
+    let mut cumulative = (0.0, 0.0);
+    for (i, session) in session_ids.u32()?.iter().enumerate() {
+        let Some(session) = session else {
+            continue;
+        };
+        if current_session != Some(session) {
+            current_session = Some(session);
+            cumulative = (0.0, 0.0);
+        }
+        vwap_values[i] = accumulate(&mut cumulative, typical[i], volume[i]);
+    }
+
+    Ok(Series::new("session_vwap".into(), vwap_values))
+}
+
+/// Calculates anchored VWAP, accumulating from the bar at `anchor_index`
+/// onwards
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing high, low, close and volume data
+/// * `anchor_index` - Row index of the first bar in the VWAP
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the anchored VWAP Series named
+/// "anchored_vwap", NaN before the anchor
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::moving_averages::calculate_anchored_vwap;
+///
+/// let df = df! {
+///     "high" => [10.0, 12.0, 14.0],
+///     "low" => [10.0, 12.0, 14.0],
+///     "close" => [10.0, 12.0, 14.0],
+///     "volume" => [100.0, 100.0, 300.0],
+/// }
+/// .unwrap();
+///
+/// let vwap = calculate_anchored_vwap(&df, 1).unwrap();
+/// let vwap = vwap.f64().unwrap();
+/// assert!(vwap.get(0).unwrap().is_nan());
+/// assert_eq!(vwap.get(1), Some(12.0));
+/// assert_eq!(vwap.get(2), Some(13.5));
+/// ```
+pub fn calculate_anchored_vwap(df: &DataFrame, anchor_index: usize) -> PolarsResult<Series> {
+    if anchor_index >= df.height() {
+        return Err(PolarsError::ComputeError(
+            format!(
+                "Anchored VWAP anchor index {anchor_index} is past the last row ({})",
+                df.height()
+            )
+            .into(),
+        ));
+    }
+
+    let (typical, volume) = typical_price_volume(df)?;
+
+    let mut vwap_values = vec![f64::NAN; df.height()];
+    let mut cumulative = (0.0, 0.0);
+    for i in anchor_index..df.height() {
+        vwap_values[i] = accumulate(&mut cumulative, typical[i], volume[i]);
+    }
+
+    Ok(Series::new("anchored_vwap".into(), vwap_values))
+}
+
+/// Calculates anchored VWAP from the first bar at or after `anchor`
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing high, low, close, volume and timestamp data
+/// * `time_column` - Name of the timestamp column
+/// * `anchor` - Time of the anchor, e.g. an earnings release or a swing low
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the anchored VWAP Series named
+/// "anchored_vwap", NaN before the anchor
+///
+/// # Example
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use polars::prelude::*;
+/// use rustalib::indicators::moving_averages::calculate_anchored_vwap_at;
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-02", "2024-01-03", "2024-01-04"],
+///     "high" => [10.0, 12.0, 14.0],
+///     "low" => [10.0, 12.0, 14.0],
+///     "close" => [10.0, 12.0, 14.0],
+///     "volume" => [100.0, 100.0, 300.0],
+/// }
+/// .unwrap();
+///
+/// let anchor = Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0).unwrap();
+/// let vwap = calculate_anchored_vwap_at(&df, "timestamp", anchor).unwrap();
+/// assert_eq!(vwap.f64().unwrap().get(2), Some(13.5));
+/// ```
+pub fn calculate_anchored_vwap_at(
+    df: &DataFrame,
+    time_column: &str,
+    anchor: DateTime<Utc>,
+) -> PolarsResult<Series> {
+    let anchor_index = parse_timestamp_column(df, time_column)?
+        .iter()
+        .position(|ts| ts.is_some_and(|ts| ts >= anchor))
+        .ok_or_else(|| {
+            PolarsError::ComputeError(
+                format!("Anchored VWAP anchor {anchor} is after the last bar").into(),
+            )
+        })?;
+
+    calculate_anchored_vwap(df, anchor_index)
+}
+
+/// Typical price (high + low + close) / 3 and volume per bar, NaN where a
+/// value is missing
+fn typical_price_volume(df: &DataFrame) -> PolarsResult<(Vec<f64>, Vec<f64>)> {
+    for col in ["high", "low", "close", "volume"] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                "VWAP calculation requires high, low, close and volume columns".into(),
+            ));
+        }
+    }
+
+    let high = df.column("high")?.f64()?;
+    let low = df.column("low")?.f64()?;
+    let close = df.column("close")?.f64()?;
+    let volume = df.column("volume")?.f64()?;
+
+    let typical = high
+        .iter()
+        .zip(low)
+        .zip(close)
+        .map(|((h, l), c)| match (h, l, c) {
+            (Some(h), Some(l), Some(c)) => (h + l + c) / 3.0,
+            _ => f64::NAN,
+        })
+        .collect();
+    let volume = nan_values(volume);
+
+    Ok((typical, volume))
+}
+
+/// Add a bar to the running (price * volume, volume) sums and return the
+/// VWAP so far
+///
+/// Bars with a missing price or volume are skipped. Until some volume has
+/// traded the VWAP is the typical price of the bar, or NaN if it is missing.
+fn accumulate(cumulative: &mut (f64, f64), typical: f64, volume: f64) -> f64 {
+    if !typical.is_nan() && !volume.is_nan() {
+        cumulative.0 += typical * volume;
+        cumulative.1 += volume;
+    }
+    if cumulative.1 > 0.0 {
+        cumulative.0 / cumulative.1
+    } else {
+        typical
+    }
+}
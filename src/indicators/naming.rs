@@ -0,0 +1,88 @@
+//! # Column Naming
+//!
+//! Every indicator names its output from a canonical snake_case name: the
+//! indicator, then its parameters, joined by underscores ("sma_20",
+//! "macd_signal_12_26_9", "close_sum_5"). [`indicator_name`] builds these
+//! defaults. A [`ColumnNaming`] policy restyles canonical names and adds an
+//! optional prefix and suffix; [`IndicatorConfig`](super::IndicatorConfig)
+//! applies it to the columns of
+//! [`add_technical_indicators_with_config`](super::add_technical_indicators_with_config)
+//! and reports the resulting names, so code reading the output does not
+//! hard-code them.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Style of generated column names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NamingConvention {
+    /// Lowercase words and parameters joined by underscores: "sma_20"
+    #[default]
+    Snake,
+    /// Uppercase words and parameters joined by underscores, as in
+    /// pandas-ta: "SMA_20"
+    ScreamingSnake,
+    /// Lowercase with no separators: "sma20"
+    Compact,
+}
+
+/// Naming policy for indicator output columns
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::naming::{ColumnNaming, NamingConvention};
+///
+/// let naming = ColumnNaming {
+///     convention: NamingConvention::ScreamingSnake,
+///     prefix: "ta_".to_string(),
+///     ..Default::default()
+/// };
+/// assert_eq!(naming.format("macd_signal_12_26_9"), "ta_MACD_SIGNAL_12_26_9");
+/// assert_eq!(naming.name("sma", &[&20]), "ta_SMA_20");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnNaming {
+    /// Style applied to the canonical name
+    pub convention: NamingConvention,
+    /// Text put before every name, unchanged by the convention
+    pub prefix: String,
+    /// Text put after every name, unchanged by the convention
+    pub suffix: String,
+}
+
+impl ColumnNaming {
+    /// Restyle a canonical snake_case name and add the prefix and suffix
+    pub fn format(&self, canonical: &str) -> String {
+        let styled = match self.convention {
+            NamingConvention::Snake => canonical.to_string(),
+            NamingConvention::ScreamingSnake => canonical.to_uppercase(),
+            NamingConvention::Compact => canonical.replace('_', ""),
+        };
+        format!("{}{styled}{}", self.prefix, self.suffix)
+    }
+
+    /// Name of an indicator column with the given parameters
+    pub fn name(&self, indicator: &str, params: &[&dyn Display]) -> String {
+        self.format(&indicator_name(indicator, params))
+    }
+}
+
+/// Canonical name of an indicator column: the indicator followed by its
+/// parameters, joined by underscores
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::naming::indicator_name;
+///
+/// assert_eq!(indicator_name("macd_signal", &[&12, &26, &9]), "macd_signal_12_26_9");
+/// assert_eq!(indicator_name("obv", &[]), "obv");
+/// ```
+pub fn indicator_name(indicator: &str, params: &[&dyn Display]) -> String {
+    params.iter().fold(indicator.to_string(), |name, param| {
+        format!("{name}_{param}")
+    })
+}
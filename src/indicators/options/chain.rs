@@ -0,0 +1,275 @@
+//! # Options Chain Analysis
+//!
+//! Enriches an options chain with implied volatility, Greeks, moneyness and
+//! time to expiry, and flags contracts that trade away from a model price.
+
+use crate::indicators::options::pricing::{OptionSpec, PricingModel};
+use crate::indicators::options::probability::{probability_itm, probability_of_touch};
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// Settings for [`analyze_options_chain`]
+#[derive(Debug, Clone)]
+pub struct ChainAnalysisParams {
+    /// Time at which the chain is valued
+    pub valuation_time: DateTime<Utc>,
+    /// Continuously compounded risk-free rate as a decimal
+    pub risk_free_rate: f64,
+    /// Continuous dividend yield of the underlying as a decimal
+    pub dividend_yield: f64,
+    /// Volatility for model prices; None uses the median IV of each expiry
+    pub model_volatility: Option<f64>,
+    /// Relative deviation from the model price that counts as mispriced (e.g. 0.1 = 10%)
+    pub mispricing_threshold: f64,
+    /// Column with the market price of each contract
+    pub price_column: String,
+    /// Model used for IV, Greeks and model prices
+    pub model: PricingModel,
+}
+
+impl ChainAnalysisParams {
+    /// Create settings for a valuation time with a 2% rate, no dividends,
+    /// per-expiry median IV, a 10% mispricing threshold, a "price" column
+    /// and Black-Scholes pricing
+    pub fn new(valuation_time: DateTime<Utc>) -> Self {
+        Self {
+            valuation_time,
+            risk_free_rate: 0.02,
+            dividend_yield: 0.0,
+            model_volatility: None,
+            mispricing_threshold: 0.1,
+            price_column: "price".to_string(),
+            model: PricingModel::BlackScholes,
+        }
+    }
+}
+
+/// Analyze an options chain for a security
+///
+/// Processes a complete options chain to calculate implied volatility,
+/// Greeks, moneyness and days to expiry, and flags contracts whose market
+/// price deviates from the model price by more than the threshold. All
+/// analytics use `params.model`, so American contracts and cash dividends
+/// are handled by choosing [`PricingModel::Binomial`]. The model
+/// price uses `model_volatility`, or the median IV of the contract's expiry
+/// so that contracts are compared with their own smile.
+///
+/// # Arguments
+///
+/// * `underlying_price` - Current price of the underlying
+/// * `options_data` - DataFrame with one row per contract and columns:
+///   - `strike`: strike price
+///   - `expiry`: expiration date or time (string, Date or Datetime)
+///   - `option_type`: "call"/"put" (or "c"/"p", case-insensitive)
+///   - the market price column named by `params.price_column`
+/// * `params` - Valuation settings
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the chain with added columns
+/// "days_to_expiry", "moneyness" (spot / strike), "implied_volatility",
+/// "delta", "gamma", "theta" (per day), "vega" and "rho" (per 1%),
+/// "model_price", "price_deviation_pct", "mispricing_flag"
+/// (1 = rich, -1 = cheap, 0 = fair), and "probability_itm" and
+/// "probability_touch" at the contract's IV (see
+/// [`probability`](super::probability)). Contracts without a solvable IV get
+/// NaN analytics and a 0 flag.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use polars::prelude::*;
+/// use rustalib::indicators::options::chain::{analyze_options_chain, ChainAnalysisParams};
+///
+/// let chain = df! {
+///     "strike" => [95.0, 100.0, 105.0],
+///     "expiry" => ["2024-03-15", "2024-03-15", "2024-03-15"],
+///     "option_type" => ["call", "call", "call"],
+///     "price" => [7.1, 3.9, 3.5],
+/// }
+/// .unwrap();
+///
+/// let params = ChainAnalysisParams::new(Utc.with_ymd_and_hms(2024, 2, 14, 0, 0, 0).unwrap());
+/// let result = analyze_options_chain(100.0, &chain, &params).unwrap();
+///
+/// let flags = result.column("mispricing_flag").unwrap().i32().unwrap();
+/// assert_eq!(flags.get(2), Some(1)); // the 105 call trades rich to its expiry
+/// ```
+pub fn analyze_options_chain(
+    underlying_price: f64,
+    options_data: &DataFrame,
+    params: &ChainAnalysisParams,
+) -> PolarsResult<DataFrame> {
+    for col in [
+        "strike",
+        "expiry",
+        "option_type",
+        params.price_column.as_str(),
+    ] {
+        if !options_data.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Options chain analysis requires '{col}' column").into(),
+            ));
+        }
+    }
+    if underlying_price <= 0.0 {
+        return Err(PolarsError::ComputeError(
+            "Underlying price must be positive".into(),
+        ));
+    }
+
+    let n = options_data.height();
+    let strikes = options_data.column("strike")?.cast(&DataType::Float64)?;
+    let strikes = strikes.f64()?;
+    let prices = options_data
+        .column(&params.price_column)?
+        .cast(&DataType::Float64)?;
+    let prices = prices.f64()?;
+    let types = options_data.column("option_type")?.str()?;
+    let expiries = parse_timestamp_column(options_data, "expiry")?;
+
+    let mut days_to_expiry = vec![f64::NAN; n];
+    let mut moneyness = vec![f64::NAN; n];
+    let mut iv = vec![f64::NAN; n];
+    let mut is_call = vec![None; n];
+
+    for i in 0..n {
+        is_call[i] = types.get(i).and_then(parse_option_type);
+        let (Some(strike), Some(expiry)) = (strikes.get(i), expiries[i]) else {
+            continue;
+        };
+        let days = (expiry - params.valuation_time).num_seconds() as f64 / 86_400.0;
+        days_to_expiry[i] = days;
+        moneyness[i] = underlying_price / strike;
+
+        if let (Some(call), Some(price)) = (is_call[i], prices.get(i)) {
+            let spec = contract_spec(underlying_price, strike, days, call, params);
+            iv[i] = params.model.implied_volatility(&spec, price);
+        }
+    }
+
+    // Median IV per expiry is the reference smile level when no volatility is given
+    let mut by_expiry: HashMap<DateTime<Utc>, Vec<f64>> = HashMap::new();
+    for i in 0..n {
+        if let Some(expiry) = expiries[i] {
+            if !iv[i].is_nan() {
+                by_expiry.entry(expiry).or_default().push(iv[i]);
+            }
+        }
+    }
+    let median_iv: HashMap<DateTime<Utc>, f64> = by_expiry
+        .into_iter()
+        .map(|(expiry, mut values)| {
+            values.sort_by(|a, b| a.total_cmp(b));
+            let mid = values.len() / 2;
+            let median = if values.len() % 2 == 0 {
+                (values[mid - 1] + values[mid]) / 2.0
+            } else {
+                values[mid]
+            };
+            (expiry, median)
+        })
+        .collect();
+
+    let mut delta = vec![f64::NAN; n];
+    let mut gamma = vec![f64::NAN; n];
+    let mut theta = vec![f64::NAN; n];
+    let mut vega = vec![f64::NAN; n];
+    let mut rho = vec![f64::NAN; n];
+    let mut model_price = vec![f64::NAN; n];
+    let mut deviation = vec![f64::NAN; n];
+    let mut flag = vec![0i32; n];
+    let mut prob_itm = vec![f64::NAN; n];
+    let mut prob_touch = vec![f64::NAN; n];
+
+    for i in 0..n {
+        let (Some(call), Some(strike), Some(expiry)) = (is_call[i], strikes.get(i), expiries[i])
+        else {
+            continue;
+        };
+        if iv[i].is_nan() {
+            continue;
+        }
+        let spec = contract_spec(underlying_price, strike, days_to_expiry[i], call, params);
+
+        let greeks = params.model.greeks(&spec, iv[i]);
+        delta[i] = greeks.delta;
+        gamma[i] = greeks.gamma;
+        theta[i] = greeks.theta;
+        vega[i] = greeks.vega;
+        rho[i] = greeks.rho;
+        prob_itm[i] = probability_itm(&spec, iv[i]);
+        prob_touch[i] = probability_of_touch(&spec, iv[i]);
+
+        let reference_vol = params
+            .model_volatility
+            .or_else(|| median_iv.get(&expiry).copied())
+            .unwrap_or(f64::NAN);
+        let model = params.model.price(&spec, reference_vol);
+        model_price[i] = model;
+
+        if let Some(market) = prices.get(i) {
+            if model > 0.0 {
+                let relative = (market - model) / model;
+                deviation[i] = relative * 100.0;
+                flag[i] = if relative > params.mispricing_threshold {
+                    1
+                } else if relative < -params.mispricing_threshold {
+                    -1
+                } else {
+                    0
+                };
+            }
+        }
+    }
+
+    let mut result = options_data.clone();
+    for series in [
+        Series::new("days_to_expiry".into(), days_to_expiry),
+        Series::new("moneyness".into(), moneyness),
+        Series::new("implied_volatility".into(), iv),
+        Series::new("delta".into(), delta),
+        Series::new("gamma".into(), gamma),
+        Series::new("theta".into(), theta),
+        Series::new("vega".into(), vega),
+        Series::new("rho".into(), rho),
+        Series::new("model_price".into(), model_price),
+        Series::new("price_deviation_pct".into(), deviation),
+        Series::new("mispricing_flag".into(), flag),
+        Series::new("probability_itm".into(), prob_itm),
+        Series::new("probability_touch".into(), prob_touch),
+    ] {
+        result.with_column(series)?;
+    }
+
+    Ok(result)
+}
+
+/// Whether an option type label denotes a call: "call"/"c" or "put"/"p", case-insensitive
+pub(crate) fn parse_option_type(label: &str) -> Option<bool> {
+    match label.to_ascii_lowercase().as_str() {
+        "call" | "c" => Some(true),
+        "put" | "p" => Some(false),
+        _ => None,
+    }
+}
+
+fn contract_spec(
+    spot: f64,
+    strike: f64,
+    days_to_expiry: f64,
+    is_call: bool,
+    params: &ChainAnalysisParams,
+) -> OptionSpec {
+    OptionSpec {
+        spot,
+        strike,
+        time_to_expiry: days_to_expiry / 365.0,
+        risk_free_rate: params.risk_free_rate,
+        dividend_yield: params.dividend_yield,
+        is_call,
+    }
+}
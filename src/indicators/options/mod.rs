@@ -6,10 +6,31 @@
 //!
 //! - [`implied_volatility`](implied_volatility/index.html): Indicators based on implied volatility analysis
 //! - [`greeks`](greeks/index.html): Indicators and calculations for option Greeks
+//! - [`pricing`](pricing/index.html): Black-Scholes pricing, analytical Greeks and implied volatility
+//! - [`binomial`](binomial/index.html): Binomial tree pricing for American options and cash dividends
+//! - [`chain`](chain/index.html): Full options chain analysis (IV, Greeks, mispricing flags)
+//! - [`payoff`](payoff/index.html): Position payoff and Greeks across price × time × IV-shift grids
+//! - [`probability`](probability/index.html): Expected move, probability ITM/touch and probability of profit
 
+pub mod binomial;
+pub mod chain;
 pub mod greeks;
 pub mod implied_volatility;
+pub mod payoff;
+pub mod pricing;
+pub mod probability;
 
 // Re-export common types and functions for convenient access
+pub use binomial::{BinomialPricer, CashDividend, ExerciseStyle};
+pub use chain::{analyze_options_chain, ChainAnalysisParams};
 pub use greeks::GreeksCalculator;
 pub use implied_volatility::IVSurface;
+pub use payoff::{evaluate_payoff, PayoffGridParams, PayoffProfile, PositionLeg};
+pub use pricing::{
+    black_scholes_greeks, black_scholes_price, implied_volatility, OptionGreeks, OptionSpec,
+    PricingModel,
+};
+pub use probability::{
+    expected_move, expected_move_from_straddle, expiry_breakevens, probability_itm,
+    probability_of_profit, probability_of_touch, PayoffLeg,
+};
@@ -0,0 +1,355 @@
+//! # Options Position Payoff Engine
+//!
+//! Values a multi-leg options position with Black-Scholes over a grid of
+//! underlying prices, days forward and implied volatility shifts, with the
+//! combined Greeks at every point, and summarizes the position's maximum
+//! profit, maximum loss and breakevens at the first expiry.
+
+use crate::indicators::options::pricing::{black_scholes_greeks, black_scholes_price};
+use crate::indicators::options::probability::{expiry_breakevens, PayoffLeg};
+use polars::prelude::*;
+
+/// One option of a position valued by [`evaluate_payoff`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionLeg {
+    /// Strike price
+    pub strike: f64,
+    /// Whether the contract is a call (true) or put (false)
+    pub is_call: bool,
+    /// Contracts held: positive when bought, negative when sold
+    pub quantity: f64,
+    /// Price per contract paid (long) or received (short) at entry
+    pub premium: f64,
+    /// Calendar days from today to expiry
+    pub days_to_expiry: f64,
+    /// Implied volatility of the contract as a decimal
+    pub implied_volatility: f64,
+}
+
+/// Grid and market settings for [`evaluate_payoff`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayoffGridParams {
+    /// Lowest underlying price of the grid
+    pub price_min: f64,
+    /// Highest underlying price of the grid
+    pub price_max: f64,
+    /// Number of evenly spaced prices from `price_min` to `price_max`
+    pub price_steps: usize,
+    /// Calendar days from today at which the position is valued
+    pub days_forward: Vec<f64>,
+    /// Shifts added to every leg's implied volatility (e.g. -0.05 for -5 points)
+    pub iv_shifts: Vec<f64>,
+    /// Continuously compounded risk-free rate as a decimal
+    pub risk_free_rate: f64,
+    /// Continuous dividend yield of the underlying as a decimal
+    pub dividend_yield: f64,
+    /// Underlying units per contract; values and Greeks are scaled by it
+    pub contract_multiplier: f64,
+}
+
+impl PayoffGridParams {
+    /// Create a grid of 81 prices within ±20% of `spot`, valued today and at
+    /// the first expiry of `legs` without IV shifts, with a 2% rate, no
+    /// dividends and a multiplier of 1
+    pub fn new(spot: f64, legs: &[PositionLeg]) -> Self {
+        let first_expiry = legs
+            .iter()
+            .map(|leg| leg.days_to_expiry)
+            .fold(f64::INFINITY, f64::min);
+        let mut days_forward = vec![0.0];
+        if first_expiry.is_finite() && first_expiry > 0.0 {
+            days_forward.push(first_expiry);
+        }
+        Self {
+            price_min: spot * 0.8,
+            price_max: spot * 1.2,
+            price_steps: 81,
+            days_forward,
+            iv_shifts: vec![0.0],
+            risk_free_rate: 0.02,
+            dividend_yield: 0.0,
+            contract_multiplier: 1.0,
+        }
+    }
+}
+
+/// Result of [`evaluate_payoff`]
+#[derive(Debug, Clone)]
+pub struct PayoffProfile {
+    /// One row per grid point with columns "price", "days_forward",
+    /// "iv_shift", "value" (position value), "pnl" (value minus entry cost)
+    /// and the combined "delta", "gamma", "theta" (per day), "vega" and
+    /// "rho" (per 1%)
+    pub grid: DataFrame,
+    /// Largest P&L at the first expiry; infinite when unbounded
+    pub max_profit: f64,
+    /// Largest loss at the first expiry as a positive amount; infinite when unbounded
+    pub max_loss: f64,
+    /// Underlying prices at the first expiry where the P&L crosses zero
+    pub breakevens: Vec<f64>,
+}
+
+/// Value a multi-leg options position across price, time and volatility
+///
+/// Every leg is priced with Black-Scholes at its implied volatility plus the
+/// grid's IV shift and its remaining time to expiry; legs past expiry are
+/// worth their intrinsic value. Greeks of the position are the
+/// quantity-weighted sums of the leg Greeks, with expired legs contributing
+/// only their intrinsic delta.
+///
+/// Maximum profit, maximum loss and breakevens refer to the first expiry.
+/// When all legs expire together they follow exactly from the piecewise
+/// linear expiry payoff; otherwise the legs still open are priced at their
+/// IV and the extremes and breakevens are read from the price grid.
+///
+/// # Arguments
+///
+/// * `legs` - Options of the position
+/// * `params` - Grid and market settings
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`PayoffProfile`]; values, P&L and
+/// Greeks are scaled by the quantity and contract multiplier
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::options::payoff::{evaluate_payoff, PayoffGridParams, PositionLeg};
+///
+/// // Bull call spread: buy the 100 call for 3.00, sell the 110 call for 1.00
+/// let leg = |strike, quantity, premium| PositionLeg {
+///     strike,
+///     is_call: true,
+///     quantity,
+///     premium,
+///     days_to_expiry: 30.0,
+///     implied_volatility: 0.25,
+/// };
+/// let legs = [leg(100.0, 1.0, 3.0), leg(110.0, -1.0, 1.0)];
+///
+/// let mut params = PayoffGridParams::new(100.0, &legs);
+/// params.iv_shifts = vec![-0.05, 0.0, 0.05];
+/// let profile = evaluate_payoff(&legs, &params).unwrap();
+///
+/// assert_eq!(profile.max_profit, 8.0);
+/// assert_eq!(profile.max_loss, 2.0);
+/// assert_eq!(profile.breakevens, vec![102.0]);
+/// // 81 prices × 2 dates × 3 IV shifts
+/// assert_eq!(profile.grid.height(), 486);
+/// ```
+pub fn evaluate_payoff(
+    legs: &[PositionLeg],
+    params: &PayoffGridParams,
+) -> PolarsResult<PayoffProfile> {
+    if legs.is_empty() {
+        return Err(PolarsError::ComputeError(
+            "Payoff evaluation requires at least one leg".into(),
+        ));
+    }
+    if params.price_steps < 2 || params.price_min <= 0.0 || params.price_max <= params.price_min {
+        return Err(PolarsError::ComputeError(
+            "Payoff grid requires at least two prices in a positive, increasing range".into(),
+        ));
+    }
+    if params.days_forward.is_empty() || params.iv_shifts.is_empty() {
+        return Err(PolarsError::ComputeError(
+            "Payoff grid requires at least one date and one IV shift".into(),
+        ));
+    }
+
+    let step = (params.price_max - params.price_min) / (params.price_steps - 1) as f64;
+    let prices: Vec<f64> = (0..params.price_steps)
+        .map(|i| params.price_min + i as f64 * step)
+        .collect();
+    let cost: f64 = legs.iter().map(|leg| leg.quantity * leg.premium).sum();
+    let scale = params.contract_multiplier;
+
+    let points = prices.len() * params.days_forward.len() * params.iv_shifts.len();
+    let mut columns: [Vec<f64>; 10] = std::array::from_fn(|_| Vec::with_capacity(points));
+    for &days in &params.days_forward {
+        for &shift in &params.iv_shifts {
+            for &price in &prices {
+                let point = position_point(legs, price, days, shift, params);
+                let row = [
+                    price,
+                    days,
+                    shift,
+                    point.value * scale,
+                    (point.value - cost) * scale,
+                    point.delta * scale,
+                    point.gamma * scale,
+                    point.theta * scale,
+                    point.vega * scale,
+                    point.rho * scale,
+                ];
+                for (column, value) in columns.iter_mut().zip(row) {
+                    column.push(value);
+                }
+            }
+        }
+    }
+    let [price, days_forward, iv_shift, value, pnl, delta, gamma, theta, vega, rho] = columns;
+    let grid = df! {
+        "price" => price,
+        "days_forward" => days_forward,
+        "iv_shift" => iv_shift,
+        "value" => value,
+        "pnl" => pnl,
+        "delta" => delta,
+        "gamma" => gamma,
+        "theta" => theta,
+        "vega" => vega,
+        "rho" => rho,
+    }?;
+
+    let first_expiry = legs
+        .iter()
+        .map(|leg| leg.days_to_expiry)
+        .fold(f64::INFINITY, f64::min);
+    let (max_profit, max_loss, breakevens) =
+        if legs.iter().all(|leg| leg.days_to_expiry == first_expiry) {
+            expiry_extremes(legs)
+        } else {
+            grid_extremes(legs, &prices, first_expiry, params)
+        };
+
+    Ok(PayoffProfile {
+        grid,
+        max_profit: max_profit * scale,
+        max_loss: max_loss * scale,
+        breakevens,
+    })
+}
+
+/// Value and Greeks of one position unit at a grid point
+struct PositionPoint {
+    value: f64,
+    delta: f64,
+    gamma: f64,
+    theta: f64,
+    vega: f64,
+    rho: f64,
+}
+
+fn position_point(
+    legs: &[PositionLeg],
+    price: f64,
+    days_forward: f64,
+    iv_shift: f64,
+    params: &PayoffGridParams,
+) -> PositionPoint {
+    let mut point = PositionPoint {
+        value: 0.0,
+        delta: 0.0,
+        gamma: 0.0,
+        theta: 0.0,
+        vega: 0.0,
+        rho: 0.0,
+    };
+    for leg in legs {
+        let time = (leg.days_to_expiry - days_forward) / 365.0;
+        let volatility = (leg.implied_volatility + iv_shift).max(0.0);
+        let q = leg.quantity;
+        point.value += q * black_scholes_price(
+            price,
+            leg.strike,
+            time,
+            params.risk_free_rate,
+            params.dividend_yield,
+            volatility,
+            leg.is_call,
+        );
+
+        if time <= 0.0 || volatility <= 0.0 {
+            // Expired (or riskless) legs only move with the underlying when in the money
+            point.delta += match (leg.is_call, price > leg.strike, price < leg.strike) {
+                (true, true, _) => q,
+                (false, _, true) => -q,
+                _ => 0.0,
+            };
+            continue;
+        }
+        let greeks = black_scholes_greeks(
+            price,
+            leg.strike,
+            time,
+            params.risk_free_rate,
+            params.dividend_yield,
+            volatility,
+            leg.is_call,
+        );
+        point.delta += q * greeks.delta;
+        point.gamma += q * greeks.gamma;
+        point.theta += q * greeks.theta;
+        point.vega += q * greeks.vega;
+        point.rho += q * greeks.rho;
+    }
+    point
+}
+
+/// Exact extremes and breakevens of legs sharing one expiry
+fn expiry_extremes(legs: &[PositionLeg]) -> (f64, f64, Vec<f64>) {
+    let payoff: Vec<PayoffLeg> = legs
+        .iter()
+        .map(|leg| PayoffLeg {
+            strike: leg.strike,
+            is_call: leg.is_call,
+            quantity: leg.quantity,
+            premium: leg.premium,
+        })
+        .collect();
+    let profit = |price: f64| payoff.iter().map(|leg| leg.profit_at(price)).sum::<f64>();
+
+    // Profit is linear between strikes and beyond the last strike moves with the calls
+    let knots = std::iter::once(0.0).chain(legs.iter().map(|leg| leg.strike));
+    let (mut max_profit, mut min_profit) = (f64::NEG_INFINITY, f64::INFINITY);
+    for price in knots {
+        let p = profit(price);
+        max_profit = max_profit.max(p);
+        min_profit = min_profit.min(p);
+    }
+    let upside_slope: f64 = legs
+        .iter()
+        .filter(|leg| leg.is_call)
+        .map(|leg| leg.quantity)
+        .sum();
+    if upside_slope > 0.0 {
+        max_profit = f64::INFINITY;
+    } else if upside_slope < 0.0 {
+        min_profit = f64::NEG_INFINITY;
+    }
+
+    (max_profit, -min_profit, expiry_breakevens(&payoff))
+}
+
+/// Extremes and breakevens read from the price grid at the first expiry
+fn grid_extremes(
+    legs: &[PositionLeg],
+    prices: &[f64],
+    first_expiry: f64,
+    params: &PayoffGridParams,
+) -> (f64, f64, Vec<f64>) {
+    let cost: f64 = legs.iter().map(|leg| leg.quantity * leg.premium).sum();
+    let pnl: Vec<f64> = prices
+        .iter()
+        .map(|&price| position_point(legs, price, first_expiry, 0.0, params).value - cost)
+        .collect();
+
+    let max_profit = pnl.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let min_profit = pnl.iter().copied().fold(f64::INFINITY, f64::min);
+    let mut breakevens = Vec::new();
+    for i in 1..pnl.len() {
+        let (a, b) = (pnl[i - 1], pnl[i]);
+        if a == 0.0 {
+            breakevens.push(prices[i - 1]);
+        } else if a * b < 0.0 {
+            breakevens.push(prices[i - 1] + a / (a - b) * (prices[i] - prices[i - 1]));
+        }
+    }
+    if pnl.last() == Some(&0.0) {
+        breakevens.push(prices[prices.len() - 1]);
+    }
+
+    (max_profit, -min_profit, breakevens)
+}
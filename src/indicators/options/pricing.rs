@@ -0,0 +1,369 @@
+//! # Option Pricing Models
+//!
+//! Closed-form Black-Scholes-Merton pricing with continuous dividend yield,
+//! analytical Greeks and an implied volatility solver, plus [`PricingModel`]
+//! which selects between Black-Scholes and the binomial tree in
+//! [`binomial`](super::binomial) behind one price/Greeks/IV interface.
+
+use crate::indicators::options::binomial::BinomialPricer;
+use serde::{Deserialize, Serialize};
+
+/// Option Greeks for a single contract
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptionGreeks {
+    /// Change in option price per 1.0 change in the underlying
+    pub delta: f64,
+    /// Change in delta per 1.0 change in the underlying
+    pub gamma: f64,
+    /// Change in option price per calendar day
+    pub theta: f64,
+    /// Change in option price per 1 percentage point of volatility
+    pub vega: f64,
+    /// Change in option price per 1 percentage point of the risk-free rate
+    pub rho: f64,
+}
+
+/// Contract and market inputs shared by the pricing models
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionSpec {
+    /// Current price of the underlying
+    pub spot: f64,
+    /// Strike price
+    pub strike: f64,
+    /// Time to expiration in years
+    pub time_to_expiry: f64,
+    /// Continuously compounded risk-free rate as a decimal
+    pub risk_free_rate: f64,
+    /// Continuous dividend yield as a decimal
+    pub dividend_yield: f64,
+    /// Whether the option is a call (true) or put (false)
+    pub is_call: bool,
+}
+
+impl OptionSpec {
+    /// Create a contract without dividend yield
+    pub fn new(
+        spot: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        is_call: bool,
+    ) -> Self {
+        Self {
+            spot,
+            strike,
+            time_to_expiry,
+            risk_free_rate,
+            dividend_yield: 0.0,
+            is_call,
+        }
+    }
+}
+
+/// Model used to price options, compute Greeks and solve for IV
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PricingModel {
+    /// Closed-form Black-Scholes-Merton (European exercise)
+    #[default]
+    BlackScholes,
+    /// Cox-Ross-Rubinstein tree (American or European, cash dividends)
+    Binomial(BinomialPricer),
+}
+
+impl PricingModel {
+    /// Price an option at a given volatility
+    pub fn price(&self, spec: &OptionSpec, volatility: f64) -> f64 {
+        match self {
+            PricingModel::BlackScholes => black_scholes_price(
+                spec.spot,
+                spec.strike,
+                spec.time_to_expiry,
+                spec.risk_free_rate,
+                spec.dividend_yield,
+                volatility,
+                spec.is_call,
+            ),
+            PricingModel::Binomial(pricer) => pricer.price(spec, volatility),
+        }
+    }
+
+    /// Greeks at a given volatility
+    pub fn greeks(&self, spec: &OptionSpec, volatility: f64) -> OptionGreeks {
+        match self {
+            PricingModel::BlackScholes => black_scholes_greeks(
+                spec.spot,
+                spec.strike,
+                spec.time_to_expiry,
+                spec.risk_free_rate,
+                spec.dividend_yield,
+                volatility,
+                spec.is_call,
+            ),
+            PricingModel::Binomial(pricer) => pricer.greeks(spec, volatility),
+        }
+    }
+
+    /// Implied volatility of a market price (NaN when unsolvable)
+    pub fn implied_volatility(&self, spec: &OptionSpec, option_price: f64) -> f64 {
+        bisect_volatility(option_price, spec.time_to_expiry, |v| self.price(spec, v))
+    }
+}
+
+/// Price a European option with the Black-Scholes-Merton model
+///
+/// # Arguments
+///
+/// * `spot` - Current price of the underlying
+/// * `strike` - Strike price
+/// * `time_to_expiry` - Time to expiration in years
+/// * `risk_free_rate` - Continuously compounded risk-free rate as a decimal
+/// * `dividend_yield` - Continuous dividend yield as a decimal
+/// * `volatility` - Volatility as a decimal (e.g. 0.20 for 20%)
+/// * `is_call` - Whether the option is a call (true) or put (false)
+///
+/// # Returns
+///
+/// The option price; intrinsic value at or after expiry
+pub fn black_scholes_price(
+    spot: f64,
+    strike: f64,
+    time_to_expiry: f64,
+    risk_free_rate: f64,
+    dividend_yield: f64,
+    volatility: f64,
+    is_call: bool,
+) -> f64 {
+    if time_to_expiry <= 0.0 || volatility <= 0.0 {
+        return intrinsic_value(spot, strike, is_call);
+    }
+
+    let (d1, d2) = d1_d2(
+        spot,
+        strike,
+        time_to_expiry,
+        risk_free_rate,
+        dividend_yield,
+        volatility,
+    );
+    let spot_df = (-dividend_yield * time_to_expiry).exp();
+    let strike_df = (-risk_free_rate * time_to_expiry).exp();
+
+    if is_call {
+        spot * spot_df * norm_cdf(d1) - strike * strike_df * norm_cdf(d2)
+    } else {
+        strike * strike_df * norm_cdf(-d2) - spot * spot_df * norm_cdf(-d1)
+    }
+}
+
+/// Calculate analytical Black-Scholes-Merton Greeks
+///
+/// # Arguments
+///
+/// * `spot` - Current price of the underlying
+/// * `strike` - Strike price
+/// * `time_to_expiry` - Time to expiration in years
+/// * `risk_free_rate` - Continuously compounded risk-free rate as a decimal
+/// * `dividend_yield` - Continuous dividend yield as a decimal
+/// * `volatility` - Volatility as a decimal
+/// * `is_call` - Whether the option is a call (true) or put (false)
+///
+/// # Returns
+///
+/// The Greeks; all NaN when time or volatility is not positive
+pub fn black_scholes_greeks(
+    spot: f64,
+    strike: f64,
+    time_to_expiry: f64,
+    risk_free_rate: f64,
+    dividend_yield: f64,
+    volatility: f64,
+    is_call: bool,
+) -> OptionGreeks {
+    if time_to_expiry <= 0.0 || volatility <= 0.0 {
+        return OptionGreeks {
+            delta: f64::NAN,
+            gamma: f64::NAN,
+            theta: f64::NAN,
+            vega: f64::NAN,
+            rho: f64::NAN,
+        };
+    }
+
+    let (d1, d2) = d1_d2(
+        spot,
+        strike,
+        time_to_expiry,
+        risk_free_rate,
+        dividend_yield,
+        volatility,
+    );
+    let sqrt_t = time_to_expiry.sqrt();
+    let spot_df = (-dividend_yield * time_to_expiry).exp();
+    let strike_df = (-risk_free_rate * time_to_expiry).exp();
+    let pdf_d1 = norm_pdf(d1);
+
+    let gamma = spot_df * pdf_d1 / (spot * volatility * sqrt_t);
+    let vega = spot * spot_df * pdf_d1 * sqrt_t / 100.0;
+    let decay = -spot * spot_df * pdf_d1 * volatility / (2.0 * sqrt_t);
+
+    let (delta, theta, rho) = if is_call {
+        (
+            spot_df * norm_cdf(d1),
+            decay - risk_free_rate * strike * strike_df * norm_cdf(d2)
+                + dividend_yield * spot * spot_df * norm_cdf(d1),
+            strike * time_to_expiry * strike_df * norm_cdf(d2) / 100.0,
+        )
+    } else {
+        (
+            spot_df * (norm_cdf(d1) - 1.0),
+            decay + risk_free_rate * strike * strike_df * norm_cdf(-d2)
+                - dividend_yield * spot * spot_df * norm_cdf(-d1),
+            -strike * time_to_expiry * strike_df * norm_cdf(-d2) / 100.0,
+        )
+    };
+
+    OptionGreeks {
+        delta,
+        gamma,
+        theta: theta / 365.0,
+        vega,
+        rho,
+    }
+}
+
+/// Calculate implied volatility from an option price
+///
+/// Uses the bisection method between 0.1% and 400% volatility.
+///
+/// # Arguments
+///
+/// * `spot` - Current price of the underlying
+/// * `strike` - Strike price
+/// * `time_to_expiry` - Time to expiration in years
+/// * `risk_free_rate` - Continuously compounded risk-free rate as a decimal
+/// * `dividend_yield` - Continuous dividend yield as a decimal
+/// * `option_price` - Market price of the option
+/// * `is_call` - Whether the option is a call (true) or put (false)
+///
+/// # Returns
+///
+/// Implied volatility as a decimal, or NaN when the price lies outside the
+/// range the model can produce (e.g. below intrinsic value)
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::options::pricing::{black_scholes_price, implied_volatility};
+///
+/// let price = black_scholes_price(100.0, 105.0, 0.5, 0.03, 0.0, 0.25, true);
+/// let iv = implied_volatility(100.0, 105.0, 0.5, 0.03, 0.0, price, true);
+/// assert!((iv - 0.25).abs() < 1e-4);
+/// ```
+pub fn implied_volatility(
+    spot: f64,
+    strike: f64,
+    time_to_expiry: f64,
+    risk_free_rate: f64,
+    dividend_yield: f64,
+    option_price: f64,
+    is_call: bool,
+) -> f64 {
+    let price_at = |volatility: f64| {
+        black_scholes_price(
+            spot,
+            strike,
+            time_to_expiry,
+            risk_free_rate,
+            dividend_yield,
+            volatility,
+            is_call,
+        )
+    };
+    bisect_volatility(option_price, time_to_expiry, price_at)
+}
+
+/// Bisection search for the volatility at which `price_at` matches `option_price`
+pub(crate) fn bisect_volatility<F>(option_price: f64, time_to_expiry: f64, price_at: F) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let mut low = 0.001;
+    let mut high = 4.0;
+    let accuracy = 1e-6;
+    let max_iterations = 100;
+
+    if !option_price.is_finite() || option_price <= 0.0 || time_to_expiry <= 0.0 {
+        return f64::NAN;
+    }
+    if price_at(low) > option_price || price_at(high) < option_price {
+        return f64::NAN;
+    }
+
+    for _ in 0..max_iterations {
+        let mid = (low + high) / 2.0;
+        let diff = price_at(mid) - option_price;
+
+        if diff.abs() < accuracy {
+            return mid;
+        }
+        if diff > 0.0 {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+
+    (low + high) / 2.0
+}
+
+/// Value of exercising immediately
+pub(crate) fn intrinsic_value(spot: f64, strike: f64, is_call: bool) -> f64 {
+    if is_call {
+        (spot - strike).max(0.0)
+    } else {
+        (strike - spot).max(0.0)
+    }
+}
+
+fn d1_d2(
+    spot: f64,
+    strike: f64,
+    time_to_expiry: f64,
+    risk_free_rate: f64,
+    dividend_yield: f64,
+    volatility: f64,
+) -> (f64, f64) {
+    let vol_sqrt_t = volatility * time_to_expiry.sqrt();
+    let d1 = ((spot / strike).ln()
+        + (risk_free_rate - dividend_yield + 0.5 * volatility.powi(2)) * time_to_expiry)
+        / vol_sqrt_t;
+    (d1, d1 - vol_sqrt_t)
+}
+
+/// Standard normal probability density
+pub(crate) fn norm_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Standard normal cumulative distribution (Abramowitz-Stegun 26.2.17, error < 7.5e-8)
+pub(crate) fn norm_cdf(x: f64) -> f64 {
+    if x > 8.0 {
+        return 1.0;
+    }
+    if x < -8.0 {
+        return 0.0;
+    }
+
+    let t = 1.0 / (1.0 + 0.2316419 * x.abs());
+    let poly = t
+        * (0.319381530
+            + t * (-0.356563782 + t * (1.781477937 + t * (-1.821255978 + t * 1.330274429))));
+    let tail = norm_pdf(x) * poly;
+
+    if x >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
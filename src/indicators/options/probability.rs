@@ -0,0 +1,335 @@
+//! # Expected Move and Probability Analytics
+//!
+//! Expected move of the underlying from implied volatility or the ATM
+//! straddle, probabilities of finishing beyond or touching a strike, and the
+//! probability of profit of a multi-leg position held to expiry.
+//!
+//! Probabilities assume the underlying follows a geometric Brownian motion
+//! with risk-neutral drift `r - q` and constant volatility, i.e. the same
+//! lognormal distribution the Black-Scholes model prices with.
+
+use crate::indicators::options::pricing::{norm_cdf, OptionSpec};
+
+/// One option of a position evaluated at expiry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PayoffLeg {
+    /// Strike price
+    pub strike: f64,
+    /// Whether the contract is a call (true) or put (false)
+    pub is_call: bool,
+    /// Contracts held: positive when bought, negative when sold
+    pub quantity: f64,
+    /// Price per contract paid (long) or received (short) at entry
+    pub premium: f64,
+}
+
+impl PayoffLeg {
+    /// Profit of the leg if the underlying settles at `price`
+    pub fn profit_at(&self, price: f64) -> f64 {
+        let intrinsic = if self.is_call {
+            (price - self.strike).max(0.0)
+        } else {
+            (self.strike - price).max(0.0)
+        };
+        self.quantity * (intrinsic - self.premium)
+    }
+}
+
+/// One-standard-deviation expected move implied by volatility
+///
+/// # Arguments
+///
+/// * `spot` - Current price of the underlying
+/// * `implied_volatility` - Annualized volatility as a decimal
+/// * `time_to_expiry` - Time horizon in years
+///
+/// # Returns
+///
+/// The expected absolute move `spot × σ × √t`; NaN for non-positive inputs
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::options::probability::expected_move;
+///
+/// // 20% IV over a quarter of a year moves a 100 stock by about 10
+/// assert!((expected_move(100.0, 0.2, 0.25) - 10.0).abs() < 1e-12);
+/// ```
+pub fn expected_move(spot: f64, implied_volatility: f64, time_to_expiry: f64) -> f64 {
+    if spot <= 0.0 || implied_volatility <= 0.0 || time_to_expiry <= 0.0 {
+        return f64::NAN;
+    }
+    spot * implied_volatility * time_to_expiry.sqrt()
+}
+
+/// Expected move priced by the at-the-money straddle
+///
+/// The straddle price is the move the market charges for, so it is quoted
+/// directly as the expected move. Under Black-Scholes it is about 0.8 times
+/// the one-standard-deviation move from [`expected_move`].
+///
+/// # Arguments
+///
+/// * `call_price` - Price of the at-the-money call
+/// * `put_price` - Price of the at-the-money put at the same strike and expiry
+///
+/// # Returns
+///
+/// The straddle price; NaN when either price is missing or negative
+pub fn expected_move_from_straddle(call_price: f64, put_price: f64) -> f64 {
+    if call_price.is_nan() || put_price.is_nan() || call_price < 0.0 || put_price < 0.0 {
+        return f64::NAN;
+    }
+    call_price + put_price
+}
+
+/// Probability that an option finishes in the money
+///
+/// # Arguments
+///
+/// * `spec` - Contract and market inputs
+/// * `volatility` - Volatility as a decimal
+///
+/// # Returns
+///
+/// Probability that the underlying settles above the strike for a call or
+/// below it for a put; 0 or 1 at or after expiry or without volatility
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::options::probability::probability_itm;
+/// use rustalib::indicators::options::OptionSpec;
+///
+/// let call = OptionSpec::new(100.0, 110.0, 0.5, 0.02, true);
+/// let put = OptionSpec { is_call: false, ..call.clone() };
+/// let p_call = probability_itm(&call, 0.25);
+/// assert!(p_call > 0.2 && p_call < 0.4);
+/// assert!((p_call + probability_itm(&put, 0.25) - 1.0).abs() < 1e-6);
+/// ```
+pub fn probability_itm(spec: &OptionSpec, volatility: f64) -> f64 {
+    let above = probability_above(
+        spec.spot,
+        spec.strike,
+        spec.time_to_expiry,
+        spec.risk_free_rate,
+        spec.dividend_yield,
+        volatility,
+    );
+    if spec.is_call {
+        above
+    } else {
+        1.0 - above
+    }
+}
+
+/// Probability that the underlying touches the strike before expiry
+///
+/// Uses the first-passage probability of a drifted Brownian motion in log
+/// price, so it is roughly twice [`probability_itm`] for out-of-the-money
+/// contracts. Calls test the strike from below and puts from above.
+///
+/// # Arguments
+///
+/// * `spec` - Contract and market inputs
+/// * `volatility` - Volatility as a decimal
+///
+/// # Returns
+///
+/// The touch probability; 1 when the option is already in the money
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::options::probability::{probability_itm, probability_of_touch};
+/// use rustalib::indicators::options::OptionSpec;
+///
+/// let put = OptionSpec::new(100.0, 90.0, 0.25, 0.0, false);
+/// let touch = probability_of_touch(&put, 0.3);
+/// let finish = probability_itm(&put, 0.3);
+/// assert!(touch > finish && (touch / finish - 2.0).abs() < 0.2);
+/// ```
+pub fn probability_of_touch(spec: &OptionSpec, volatility: f64) -> f64 {
+    let barrier_above = spec.is_call;
+    if (barrier_above && spec.spot >= spec.strike) || (!barrier_above && spec.spot <= spec.strike) {
+        return 1.0;
+    }
+    if spec.time_to_expiry <= 0.0 || volatility <= 0.0 || spec.strike <= 0.0 {
+        return 0.0;
+    }
+
+    // Distance to the barrier and drift of log price, both oriented towards the barrier
+    let sign = if barrier_above { 1.0 } else { -1.0 };
+    let distance = sign * (spec.strike / spec.spot).ln();
+    let drift = sign * (spec.risk_free_rate - spec.dividend_yield - 0.5 * volatility.powi(2));
+    let vol_sqrt_t = volatility * spec.time_to_expiry.sqrt();
+    let t = spec.time_to_expiry;
+
+    let direct = norm_cdf((drift * t - distance) / vol_sqrt_t);
+    let reflected = (2.0 * drift * distance / volatility.powi(2)).exp()
+        * norm_cdf((-drift * t - distance) / vol_sqrt_t);
+    (direct + reflected).clamp(0.0, 1.0)
+}
+
+/// Underlying prices at expiry where a position's profit crosses zero
+///
+/// # Arguments
+///
+/// * `legs` - Options of the position, all expiring together
+///
+/// # Returns
+///
+/// Breakeven prices in ascending order
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::options::probability::{expiry_breakevens, PayoffLeg};
+///
+/// // Long 100 straddle bought for 4 + 3
+/// let legs = [
+///     PayoffLeg { strike: 100.0, is_call: true, quantity: 1.0, premium: 4.0 },
+///     PayoffLeg { strike: 100.0, is_call: false, quantity: 1.0, premium: 3.0 },
+/// ];
+/// assert_eq!(expiry_breakevens(&legs), vec![93.0, 107.0]);
+/// ```
+pub fn expiry_breakevens(legs: &[PayoffLeg]) -> Vec<f64> {
+    let profit = |price: f64| legs.iter().map(|leg| leg.profit_at(price)).sum::<f64>();
+    let mut roots: Vec<f64> = Vec::new();
+    let mut push = |root: f64| {
+        if roots.last().is_none_or(|last| (root - last).abs() > 1e-9) {
+            roots.push(root);
+        }
+    };
+
+    // Profit is linear between strikes, so each segment has at most one crossing
+    let knots = strike_knots(legs);
+    for pair in knots.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let (fa, fb) = (profit(a), profit(b));
+        if fa == 0.0 && a > 0.0 {
+            push(a);
+        } else if fa * fb < 0.0 {
+            push(a - fa * (b - a) / (fb - fa));
+        }
+    }
+    let last = *knots.last().unwrap();
+    let slope: f64 = legs
+        .iter()
+        .filter(|leg| leg.is_call)
+        .map(|leg| leg.quantity)
+        .sum();
+    let f_last = profit(last);
+    if f_last == 0.0 {
+        push(last);
+    } else if slope != 0.0 && -f_last / slope > 0.0 {
+        push(last - f_last / slope);
+    }
+    roots
+}
+
+/// Probability that a position held to expiry ends with a profit
+///
+/// # Arguments
+///
+/// * `legs` - Options of the position with their entry premiums, all expiring together
+/// * `spot` - Current price of the underlying
+/// * `time_to_expiry` - Time to expiration in years
+/// * `risk_free_rate` - Continuously compounded risk-free rate as a decimal
+/// * `dividend_yield` - Continuous dividend yield as a decimal
+/// * `volatility` - Volatility as a decimal
+///
+/// # Returns
+///
+/// Probability that the settlement price lies where the position's profit
+/// after premiums is positive; 0 or 1 at expiry or without volatility
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::options::probability::{probability_of_profit, PayoffLeg};
+///
+/// // Bull put spread: sell the 95 put for 1.50, buy the 90 put for 0.50
+/// let legs = [
+///     PayoffLeg { strike: 95.0, is_call: false, quantity: -1.0, premium: 1.5 },
+///     PayoffLeg { strike: 90.0, is_call: false, quantity: 1.0, premium: 0.5 },
+/// ];
+/// let pop = probability_of_profit(&legs, 100.0, 30.0 / 365.0, 0.02, 0.0, 0.25);
+/// assert!(pop > 0.7 && pop < 0.9);
+/// ```
+pub fn probability_of_profit(
+    legs: &[PayoffLeg],
+    spot: f64,
+    time_to_expiry: f64,
+    risk_free_rate: f64,
+    dividend_yield: f64,
+    volatility: f64,
+) -> f64 {
+    let profit = |price: f64| legs.iter().map(|leg| leg.profit_at(price)).sum::<f64>();
+    let above = |price: f64| {
+        probability_above(
+            spot,
+            price,
+            time_to_expiry,
+            risk_free_rate,
+            dividend_yield,
+            volatility,
+        )
+    };
+
+    // Intervals between strikes and breakevens have a constant profit sign
+    let mut bounds = strike_knots(legs);
+    bounds.extend(expiry_breakevens(legs));
+    bounds.sort_by(|a, b| a.total_cmp(b));
+    bounds.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+    bounds.push(f64::INFINITY);
+
+    bounds
+        .windows(2)
+        .filter(|pair| {
+            let probe = if pair[1].is_finite() {
+                (pair[0] + pair[1]) / 2.0
+            } else {
+                pair[0] * 2.0 + 1.0
+            };
+            profit(probe) > 0.0
+        })
+        .map(|pair| above(pair[0]) - above(pair[1]))
+        .sum::<f64>()
+        .clamp(0.0, 1.0)
+}
+
+/// Zero followed by the distinct strikes in ascending order
+fn strike_knots(legs: &[PayoffLeg]) -> Vec<f64> {
+    let mut knots: Vec<f64> = std::iter::once(0.0)
+        .chain(legs.iter().map(|leg| leg.strike))
+        .collect();
+    knots.sort_by(|a, b| a.total_cmp(b));
+    knots.dedup();
+    knots
+}
+
+/// Risk-neutral probability that the underlying settles above `level`
+fn probability_above(
+    spot: f64,
+    level: f64,
+    time_to_expiry: f64,
+    risk_free_rate: f64,
+    dividend_yield: f64,
+    volatility: f64,
+) -> f64 {
+    if level <= 0.0 {
+        return 1.0;
+    }
+    if level.is_infinite() {
+        return 0.0;
+    }
+    if time_to_expiry <= 0.0 || volatility <= 0.0 {
+        return if spot > level { 1.0 } else { 0.0 };
+    }
+    let vol_sqrt_t = volatility * time_to_expiry.sqrt();
+    let d2 = ((spot / level).ln()
+        + (risk_free_rate - dividend_yield - 0.5 * volatility.powi(2)) * time_to_expiry)
+        / vol_sqrt_t;
+    norm_cdf(d2)
+}
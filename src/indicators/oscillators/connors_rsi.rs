@@ -0,0 +1,188 @@
+use crate::error::TaError;
+use crate::indicators::momentum::calculate_roc;
+use crate::indicators::moving_averages::calculate_sma;
+use crate::indicators::naming::indicator_name;
+use crate::indicators::oscillators::calculate_rsi;
+use crate::indicators::stats::calculate_percent_rank;
+use crate::util::dataframe_utils::column_nan_values;
+use polars::prelude::*;
+
+/// Calculates Connors RSI
+///
+/// The mean of three short-term oscillators: an RSI of the price, an RSI of
+/// the up/down streak (the number of consecutive closes above, or below as a
+/// negative count, the one before) and the Percent Rank of the one-bar rate
+/// of change. It reacts to a few bars of selling much faster than RSI(14),
+/// which suits short-term mean reversion.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data
+/// * `column` - Column to calculate on (typically "close")
+/// * `rsi_period` - RSI period of the price (typically 3)
+/// * `streak_period` - RSI period of the streak (typically 2)
+/// * `rank_period` - Number of past one-bar changes ranked (typically 100)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series from 0 to 100 named
+/// "connors_rsi_{rsi_period}_{streak_period}_{rank_period}", NaN until
+/// `rank_period + 1` bars have been seen
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::oscillators::calculate_connors_rsi;
+///
+/// let close: Vec<f64> = (0..150).map(|i| 100.0 + (i as f64 * 0.7).sin() * 5.0).collect();
+/// let df = df! { "close" => close }.unwrap();
+///
+/// let crsi = calculate_connors_rsi(&df, "close", 3, 2, 100).unwrap();
+/// assert_eq!(crsi.name().as_str(), "connors_rsi_3_2_100");
+/// let crsi = crsi.f64().unwrap();
+/// assert!(crsi.get(100).unwrap().is_nan());
+/// assert!((0.0..=100.0).contains(&crsi.get(101).unwrap()));
+/// ```
+pub fn calculate_connors_rsi(
+    df: &DataFrame,
+    column: &str,
+    rsi_period: usize,
+    streak_period: usize,
+    rank_period: usize,
+) -> PolarsResult<Series> {
+    for (name, period) in [
+        ("rsi_period", rsi_period),
+        ("streak_period", streak_period),
+        ("rank_period", rank_period),
+    ] {
+        if period == 0 {
+            return Err(TaError::invalid_parameter(name, "must be positive").into());
+        }
+    }
+
+    let price = column_nan_values(df, column)?;
+
+    let mut streak = vec![0.0; price.len()];
+    for i in 1..price.len() {
+        let change = price[i] - price[i - 1];
+        streak[i] = if change.is_nan() {
+            f64::NAN
+        } else if change > 0.0 {
+            streak[i - 1].max(0.0) + 1.0
+        } else if change < 0.0 {
+            streak[i - 1].min(0.0) - 1.0
+        } else {
+            0.0
+        };
+    }
+
+    let price_df = df! { "price" => &price }?;
+    let price_rsi = calculate_rsi(&price_df, rsi_period, "price")?;
+    let streak_rsi = calculate_rsi(&df! { "streak" => streak }?, streak_period, "streak")?;
+    let roc = calculate_roc(&price_df, 1, "price")?;
+    let rank = calculate_percent_rank(&df! { "roc" => roc }?, "roc", rank_period)?;
+
+    let crsi: Vec<f64> = price_rsi
+        .f64()?
+        .iter()
+        .zip(streak_rsi.f64()?.iter())
+        .zip(rank.f64()?.iter())
+        .map(|((r, s), p)| match (r, s, p) {
+            (Some(r), Some(s), Some(p)) => (r + s + p) / 3.0,
+            _ => f64::NAN,
+        })
+        .collect();
+
+    Ok(Series::new(
+        indicator_name("connors_rsi", &[&rsi_period, &streak_period, &rank_period]).into(),
+        crsi,
+    ))
+}
+
+/// Parameters for [`calculate_rsi_pullback`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RsiPullbackConfig {
+    /// RSI period of the pullback
+    pub rsi_period: usize,
+    /// SMA period of the trend filter
+    pub trend_period: usize,
+    /// RSI below which a pullback in an uptrend is a long setup
+    pub oversold: f64,
+    /// RSI above which a rally in a downtrend is a short setup
+    pub overbought: f64,
+}
+
+impl Default for RsiPullbackConfig {
+    fn default() -> Self {
+        Self {
+            rsi_period: 2,
+            trend_period: 200,
+            oversold: 10.0,
+            overbought: 90.0,
+        }
+    }
+}
+
+/// Calculates the short-period RSI pullback setup
+///
+/// Connors' 2-period RSI strategy: buy short, sharp pullbacks in an uptrend
+/// and sell rallies in a downtrend, the trend being the price against its
+/// long SMA.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data
+/// * `column` - Column to calculate on (typically "close")
+/// * `config` - RSI period, trend period and thresholds
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named "rsi_pullback": 1 when
+/// the price is above its SMA and the RSI below `oversold`, -1 when it is
+/// below its SMA and the RSI above `overbought`, 0 otherwise
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::oscillators::{calculate_rsi_pullback, RsiPullbackConfig};
+///
+/// // A steady uptrend, then two sharp down closes
+/// let mut close: Vec<f64> = (0..30).map(|i| 100.0 + i as f64).collect();
+/// close.extend([125.0, 121.0]);
+/// let df = df! { "close" => close }.unwrap();
+///
+/// let config = RsiPullbackConfig {
+///     trend_period: 20,
+///     ..Default::default()
+/// };
+/// let setup = calculate_rsi_pullback(&df, "close", &config).unwrap();
+/// let setup = setup.i32().unwrap();
+/// assert_eq!(setup.get(30), Some(0));
+/// assert_eq!(setup.get(31), Some(1));
+/// ```
+pub fn calculate_rsi_pullback(
+    df: &DataFrame,
+    column: &str,
+    config: &RsiPullbackConfig,
+) -> PolarsResult<Series> {
+    let price = df.column(column)?.cast(&DataType::Float64)?;
+    let price_df = DataFrame::new(vec![price.clone().with_name("price".into())])?;
+    let rsi = calculate_rsi(&price_df, config.rsi_period, "price")?;
+    let trend = calculate_sma(&price_df, "price", config.trend_period)?;
+
+    let setup: Vec<i32> = price
+        .f64()?
+        .iter()
+        .zip(rsi.f64()?.iter())
+        .zip(trend.f64()?.iter())
+        .map(|((p, r), t)| match (p, r, t) {
+            (Some(p), Some(r), Some(t)) if p > t && r < config.oversold => 1,
+            (Some(p), Some(r), Some(t)) if p < t && r > config.overbought => -1,
+            _ => 0,
+        })
+        .collect();
+
+    Ok(Series::new("rsi_pullback".into(), setup))
+}
@@ -1,3 +1,4 @@
+use crate::util::dataframe_utils::{fixed_window, rolling_values};
 use polars::prelude::*;
 
 /// Calculate Detrended Price Oscillator (DPO)
@@ -5,20 +6,27 @@ use polars::prelude::*;
 /// Returns a Series with DPO values
 pub fn calculate_dpo(df: &DataFrame, close_col: &str, period: usize) -> PolarsResult<Series> {
     let close = df.column(close_col)?.f64()?;
-    let len = df.height();
-    let mut dpo = vec![f64::NAN; len];
     let shift = period / 2 + 1;
 
-    for (i, _) in (0..len).enumerate() {
-        if i + 1 >= period {
-            let sma: f64 = close
-                .slice((i + 1 - period) as i64, period)
-                .mean()
-                .unwrap_or(f64::NAN);
-            if i >= shift {
-                dpo[i] = close.get(i - shift).unwrap_or(f64::NAN) - sma;
-            }
-        }
-    }
+    // Mean of the non-null prices in each window; NaN prices propagate
+    let sum = close
+        .fill_null_with_values(0.0)?
+        .into_series()
+        .rolling_sum(fixed_window(period, period))?;
+    let count = close
+        .is_not_null()
+        .into_series()
+        .cast(&DataType::Float64)?
+        .rolling_sum(fixed_window(period, period))?;
+    let sma = (&sum / &count)?;
+
+    let dpo: Vec<f64> = rolling_values(&sma, period - 1)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, sma)| match i.checked_sub(shift) {
+            Some(lagged) => close.get(lagged).unwrap_or(f64::NAN) - sma,
+            None => f64::NAN,
+        })
+        .collect();
     Ok(Series::new("dpo".into(), dpo))
 }
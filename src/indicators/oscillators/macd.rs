@@ -1,6 +1,19 @@
 use crate::indicators::moving_averages::calculate_ema;
+use crate::indicators::naming::indicator_name;
 use crate::util::dataframe_utils::check_window_size;
 use polars::prelude::*;
+use std::fmt::Display;
+
+/// MACD line, signal line and histogram
+#[derive(Debug, Clone)]
+pub struct MacdOutput {
+    /// Fast EMA minus slow EMA, named "macd_{fast}_{slow}_{signal}"
+    pub macd: Series,
+    /// EMA of the MACD line, named "macd_signal_{fast}_{slow}_{signal}"
+    pub signal: Series,
+    /// MACD line minus signal line, named "macd_histogram_{fast}_{slow}_{signal}"
+    pub histogram: Series,
+}
 
 /// Calculates Moving Average Convergence Divergence (MACD)
 ///
@@ -14,71 +27,82 @@ use polars::prelude::*;
 ///
 /// # Returns
 ///
-/// Returns a PolarsResult containing tuple of (MACD, Signal) Series
+/// Returns a PolarsResult containing the MACD, signal and histogram Series.
+/// The MACD line is null until the slow EMA is seeded, `slow_period` bars
+/// in; the signal line and histogram are null until the signal EMA is
+/// seeded on the first `signal_period` MACD values, `slow_period +
+/// signal_period - 1` bars in.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::oscillators::calculate_macd;
+///
+/// let close: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 / 5.0).sin()).collect();
+/// let df = df! { "close" => &close }.unwrap();
+///
+/// let macd = calculate_macd(&df, 12, 26, 9, "close").unwrap();
+/// let histogram = macd.histogram.f64().unwrap();
+/// assert_eq!(macd.macd.f64().unwrap().get(24), None);
+/// assert!(macd.macd.f64().unwrap().get(25).is_some());
+/// assert_eq!(histogram.get(32), None);
+/// assert!(histogram.get(33).is_some());
+/// assert_eq!(macd.histogram.name().as_str(), "macd_histogram_12_26_9");
+/// ```
 pub fn calculate_macd(
     df: &DataFrame,
     fast_period: usize,
     slow_period: usize,
     signal_period: usize,
     column: &str,
-) -> PolarsResult<(Series, Series)> {
+) -> PolarsResult<MacdOutput> {
     // Check we have enough data for the longest period (slow_period)
     check_window_size(df, slow_period, "MACD")?;
 
     let ema_fast = calculate_ema(df, column, fast_period)?;
     let ema_slow = calculate_ema(df, column, slow_period)?;
 
-    let macd = (&ema_fast - &ema_slow)?;
-
-    // Create a temporary DataFrame with MACD series for calculating the signal
-    let macd_series = macd.clone();
-    let temp_df = DataFrame::new(vec![macd_series.with_name(column.into()).into()])?;
-
-    // Calculate the signal line as an EMA of the MACD
-    let signal = calculate_ema(&temp_df, column, signal_period)?;
-
-    // Replace NaN values in signal with zeros at positions where MACD has values
-    let macd_ca = macd.f64()?;
-    let signal_ca = signal.f64()?;
-
-    let mut signal_vec: Vec<f64> = Vec::with_capacity(signal.len());
+    // Null until both EMAs are seeded
+    let macd: Float64Chunked = ema_fast
+        .f64()?
+        .iter()
+        .zip(ema_slow.f64()?.iter())
+        .map(|(fast, slow)| match (fast, slow) {
+            (Some(fast), Some(slow)) if !fast.is_nan() && !slow.is_nan() => Some(fast - slow),
+            _ => None,
+        })
+        .collect();
 
-    for i in 0..signal.len() {
-        if i < slow_period - 1 {
-            // Keep first slow_period-1 values as NaN to match MACD
-            signal_vec.push(f64::NAN);
-        } else if i < slow_period - 1 + signal_period {
-            // For index positions where signal might be NaN but MACD has values,
-            // use non-NaN values or 0.0
-            if let Some(macd_val) = macd_ca.get(i) {
-                if !macd_val.is_nan() {
-                    // Signal might be NaN here, use 0.0 as initial value
-                    signal_vec.push(0.0);
-                } else {
-                    signal_vec.push(f64::NAN);
-                }
-            } else {
-                signal_vec.push(f64::NAN);
-            }
-        } else {
-            // For positions where signal should have valid values
-            let val = signal_ca.get(i).unwrap_or(0.0);
-            if val.is_nan() && macd_ca.get(i).is_some_and(|v| !v.is_nan()) {
-                signal_vec.push(0.0);
-            } else {
-                signal_vec.push(val);
-            }
-        }
-    }
+    // The signal EMA skips the warm-up and seeds on the first
+    // `signal_period` MACD values
+    let temp_df = DataFrame::new(vec![macd
+        .clone()
+        .into_series()
+        .with_name(column.into())
+        .into()])?;
+    let signal: Float64Chunked = calculate_ema(&temp_df, column, signal_period)?
+        .f64()?
+        .iter()
+        .map(|v| v.filter(|v| !v.is_nan()))
+        .collect();
 
-    let macd_name = format!("macd_{0}_{1}", fast_period, slow_period);
-    let signal_name = format!(
-        "macd_signal_{0}_{1}_{2}",
-        fast_period, slow_period, signal_period
-    );
+    let histogram: Float64Chunked = macd
+        .iter()
+        .zip(signal.iter())
+        .map(|(macd, signal)| Some(macd? - signal?))
+        .collect();
 
-    Ok((
-        macd.with_name(macd_name.into()),
-        Series::new(signal_name.into(), signal_vec),
-    ))
+    let params: [&dyn Display; 3] = [&fast_period, &slow_period, &signal_period];
+    Ok(MacdOutput {
+        macd: macd
+            .with_name(indicator_name("macd", &params).into())
+            .into_series(),
+        signal: signal
+            .with_name(indicator_name("macd_signal", &params).into())
+            .into_series(),
+        histogram: histogram
+            .with_name(indicator_name("macd_histogram", &params).into())
+            .into_series(),
+    })
 }
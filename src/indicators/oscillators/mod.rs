@@ -3,6 +3,7 @@
 use polars::prelude::*;
 
 // Module declarations
+pub mod connors_rsi;
 pub mod dpo;
 pub mod macd;
 pub mod ppo;
@@ -14,8 +15,9 @@ pub mod ultimate_oscillator;
 pub mod williams_r;
 
 // Re-export functions
+pub use connors_rsi::{calculate_connors_rsi, calculate_rsi_pullback, RsiPullbackConfig};
 pub use dpo::calculate_dpo;
-pub use macd::calculate_macd;
+pub use macd::{calculate_macd, MacdOutput};
 pub use ppo::calculate_ppo;
 pub use rsi::calculate_rsi;
 pub use stoch_rsi::calculate_stoch_rsi;
@@ -38,13 +40,24 @@ pub use williams_r::calculate_williams_r;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::indicators::oscillators::add_oscillator_indicators;
+/// use rustalib::indicators::oscillators::add_oscillator_indicators;
 ///
-/// // Create or load a DataFrame with OHLCV data
-/// let df = DataFrame::default(); // Replace with actual data
+/// let close: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 / 4.0).sin() * 5.0).collect();
+/// let high: Vec<f64> = close.iter().map(|c| c + 1.0).collect();
+/// let low: Vec<f64> = close.iter().map(|c| c - 1.0).collect();
+/// let df = df! {
+///     "open" => &close,
+///     "high" => high,
+///     "low" => low,
+///     "close" => &close,
+///     "volume" => vec![1000.0; 60],
+/// }
+/// .unwrap();
 ///
-/// // Add oscillator indicators
 /// let df_with_indicators = add_oscillator_indicators(&df).unwrap();
+/// for name in ["rsi_14", "macd_12_26_9", "williams_r_14", "stoch_k_14_3_3"] {
+///     assert!(df_with_indicators.schema().contains(name));
+/// }
 /// ```
 pub fn add_oscillator_indicators(df: &DataFrame) -> PolarsResult<DataFrame> {
     let mut result_df = df.clone();
@@ -54,9 +67,10 @@ pub fn add_oscillator_indicators(df: &DataFrame) -> PolarsResult<DataFrame> {
     result_df.with_column(rsi_14)?;
 
     // MACD
-    let (macd, macd_signal) = calculate_macd(df, 12, 26, 9, "close")?;
-    result_df.with_column(macd)?;
-    result_df.with_column(macd_signal)?;
+    let macd = calculate_macd(df, 12, 26, 9, "close")?;
+    result_df.with_column(macd.macd)?;
+    result_df.with_column(macd.signal)?;
+    result_df.with_column(macd.histogram)?;
 
     // Williams %R
     let williams_r_14 = calculate_williams_r(df, 14)?;
@@ -1,3 +1,4 @@
+use crate::indicators::naming::indicator_name;
 use polars::prelude::*;
 
 /// Calculates Relative Strength Index (RSI)
@@ -84,7 +85,7 @@ pub fn calculate_rsi(df: &DataFrame, window: usize, column: &str) -> PolarsResul
         avg_gain / avg_loss
     };
     let rsi_val = 100.0 - (100.0 / (1.0 + rs));
-    rsi[window - 1] = rsi_val;
+    rsi.push(rsi_val);
 
     // Calculate smoothed RSI for the rest of the series
     for i in window + 1..df.height() {
@@ -102,5 +103,5 @@ pub fn calculate_rsi(df: &DataFrame, window: usize, column: &str) -> PolarsResul
         rsi.push(rsi_val);
     }
 
-    Ok(Series::new(format!("rsi_{}", window).into(), rsi))
+    Ok(Series::new(indicator_name("rsi", &[&window]).into(), rsi))
 }
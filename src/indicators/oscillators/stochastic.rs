@@ -1,4 +1,10 @@
+use crate::indicators::naming::indicator_name;
+use crate::util::dataframe_utils::{
+    complete_windows, fixed_window, nan_to_null, nan_values, rolling_values, sliding_max,
+    sliding_min,
+};
 use polars::prelude::*;
+use std::fmt::Display;
 
 /// Calculates the Stochastic Oscillator, which consists of %K and %D lines
 ///
@@ -26,13 +32,19 @@ use polars::prelude::*;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::indicators::oscillators::calculate_stochastic;
+/// use rustalib::indicators::oscillators::calculate_stochastic;
 ///
-/// // Create or load a DataFrame with OHLCV data
-/// let df = DataFrame::default(); // Replace with actual data
+/// // Every close is at the top of its 3-bar range
+/// let high = [10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0];
+/// let low = [9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0];
+/// let df = df! { "high" => high, "low" => low, "close" => high }.unwrap();
 ///
-/// // Calculate Stochastic Oscillator with default parameters
-/// let (stoch_k, stoch_d) = calculate_stochastic(&df, 14, 3, 3).unwrap();
+/// let (stoch_k, stoch_d) = calculate_stochastic(&df, 3, 2, 2).unwrap();
+/// let (stoch_k, stoch_d) = (stoch_k.f64().unwrap(), stoch_d.f64().unwrap());
+/// assert!(stoch_k.get(2).unwrap().is_nan());
+/// assert_eq!(stoch_k.get(3), Some(100.0));
+/// assert!(stoch_d.get(3).unwrap().is_nan());
+/// assert_eq!(stoch_d.get(4), Some(100.0));
 /// ```
 pub fn calculate_stochastic(
     df: &DataFrame,
@@ -52,119 +64,49 @@ pub fn calculate_stochastic(
         ));
     }
 
-    // Extract required columns
-    let high = df.column("high")?.f64()?;
-    let low = df.column("low")?.f64()?;
+    // Any missing high or low leaves the window undefined
+    let high = nan_values(df.column("high")?.f64()?);
+    let low = nan_values(df.column("low")?.f64()?);
     let close = df.column("close")?.f64()?;
 
-    // Calculate raw %K values
-    let mut raw_k_values = Vec::with_capacity(df.height());
-
-    // Fill initial values with NaN
-    for _ in 0..k_period - 1 {
-        raw_k_values.push(f64::NAN);
-    }
+    let complete_high = complete_windows(&high, k_period);
+    let complete_low = complete_windows(&low, k_period);
+    let highest_highs = sliding_max(&high, k_period);
+    let lowest_lows = sliding_min(&low, k_period);
 
     // Calculate raw %K for each data point
-    for i in k_period - 1..df.height() {
-        let mut highest_high = f64::NEG_INFINITY;
-        let mut lowest_low = f64::INFINITY;
-        let mut valid_data = true;
-
-        // Find highest high and lowest low in the period
-        for j in i - (k_period - 1)..=i {
-            let h = high.get(j).unwrap_or(f64::NAN);
-            let l = low.get(j).unwrap_or(f64::NAN);
-
-            if h.is_nan() || l.is_nan() {
-                valid_data = false;
-                break;
-            }
-
-            highest_high = highest_high.max(h);
-            lowest_low = lowest_low.min(l);
-        }
-
-        if !valid_data || (highest_high - lowest_low).abs() < 1e-10 {
-            raw_k_values.push(f64::NAN);
-        } else {
-            let c = close.get(i).unwrap_or(f64::NAN);
-            if c.is_nan() {
-                raw_k_values.push(f64::NAN);
-            } else {
-                let raw_k = 100.0 * (c - lowest_low) / (highest_high - lowest_low);
-                raw_k_values.push(raw_k);
+    let raw_k_values: Float64Chunked = (0..df.height())
+        .map(|i| {
+            let (highest_high, lowest_low) = (highest_highs[i], lowest_lows[i]);
+            if !complete_high[i] || !complete_low[i] || (highest_high - lowest_low).abs() < 1e-10 {
+                return None;
             }
-        }
-    }
-
-    // Apply slowing to %K (if slowing > 1)
-    let mut k_values = Vec::with_capacity(df.height());
-
-    // Fill initial values with NaN - ensure we have NaN for all values before k_period + slowing - 1
-    let k_offset = k_period + slowing - 1;
-    for _ in 0..k_offset {
-        k_values.push(f64::NAN);
-    }
-
-    // Calculate slowed %K
-    for i in k_offset..df.height() {
-        let mut sum = 0.0;
-        let mut count = 0;
-        let mut has_nan = false;
-
-        for j in 0..slowing {
-            let val = raw_k_values[i - j];
-            if val.is_nan() {
-                has_nan = true;
-                break;
-            }
-            sum += val;
-            count += 1;
-        }
-
-        if has_nan || count == 0 {
-            k_values.push(f64::NAN);
-        } else {
-            k_values.push(sum / count as f64);
-        }
-    }
+            let c = close.get(i)?;
+            Some(100.0 * (c - lowest_low) / (highest_high - lowest_low))
+        })
+        .collect();
+
+    // Apply slowing to %K; a window with any missing value stays NaN. The
+    // first raw %K is at `k_period - 1` and the first slowed one `slowing - 1`
+    // bars later
+    let k_offset = k_period + slowing - 2;
+    let k_values = rolling_values(
+        &nan_to_null(&raw_k_values).rolling_mean(fixed_window(slowing, slowing))?,
+        k_offset,
+    )?;
 
     // Calculate %D (SMA of %K)
-    let mut d_values = Vec::with_capacity(df.height());
-
-    // Fill initial values with NaN
     let d_offset = k_offset + d_period - 1;
-    for _ in 0..d_offset {
-        d_values.push(f64::NAN);
-    }
-
-    // Calculate %D
-    for i in d_offset..df.height() {
-        let mut sum = 0.0;
-        let mut count = 0;
-        let mut has_nan = false;
-
-        for j in 0..d_period {
-            let val = k_values[i - j];
-            if val.is_nan() {
-                has_nan = true;
-                break;
-            }
-            sum += val;
-            count += 1;
-        }
-
-        if has_nan || count == 0 {
-            d_values.push(f64::NAN);
-        } else {
-            d_values.push(sum / count as f64);
-        }
-    }
+    let d_values = rolling_values(
+        &nan_to_null(&Float64Chunked::from_slice("k".into(), &k_values))
+            .rolling_mean(fixed_window(d_period, d_period))?,
+        d_offset,
+    )?;
 
     // Create Series with names that reflect parameters
-    let k_name = format!("stoch_k_{}_{}_{}", k_period, slowing, d_period);
-    let d_name = format!("stoch_d_{}_{}_{}", k_period, slowing, d_period);
+    let params: [&dyn Display; 3] = [&k_period, &slowing, &d_period];
+    let k_name = indicator_name("stoch_k", &params);
+    let d_name = indicator_name("stoch_d", &params);
 
     Ok((
         Series::new(k_name.into(), k_values),
@@ -1,3 +1,5 @@
+use crate::indicators::naming::indicator_name;
+use crate::util::dataframe_utils::{complete_windows, nan_values, sliding_max, sliding_min};
 use polars::prelude::*;
 
 /// Calculates the Williams %R oscillator
@@ -23,13 +25,20 @@ use polars::prelude::*;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::indicators::oscillators::calculate_williams_r;
+/// use rustalib::indicators::oscillators::calculate_williams_r;
 ///
-/// // Create or load a DataFrame with OHLCV data
-/// let df = DataFrame::default(); // Replace with actual data
+/// let df = df! {
+///     "high" => [10.0, 11.0, 12.0, 11.0],
+///     "low" => [9.0, 10.0, 11.0, 9.5],
+///     "close" => [9.5, 10.5, 12.0, 9.0],
+/// }
+/// .unwrap();
 ///
-/// // Calculate Williams %R with period 14
-/// let williams_r = calculate_williams_r(&df, 14).unwrap();
+/// let williams_r = calculate_williams_r(&df, 3).unwrap();
+/// let williams_r = williams_r.f64().unwrap();
+/// // At the 3-bar high, then below the 3-bar low of 9.5 on a 9.0 close
+/// assert_eq!(williams_r.get(2), Some(0.0));
+/// assert!((williams_r.get(3).unwrap() - -120.0).abs() < 1e-9);
 /// ```
 pub fn calculate_williams_r(df: &DataFrame, window: usize) -> PolarsResult<Series> {
     // Validate required columns
@@ -44,53 +53,30 @@ pub fn calculate_williams_r(df: &DataFrame, window: usize) -> PolarsResult<Serie
         ));
     }
 
-    // Extract required columns
-    let high = df.column("high")?.f64()?;
-    let low = df.column("low")?.f64()?;
+    // Any missing high or low leaves the window undefined
+    let high = nan_values(df.column("high")?.f64()?);
+    let low = nan_values(df.column("low")?.f64()?);
     let close = df.column("close")?.f64()?;
 
-    // Calculate Williams %R
-    let mut williams_r_values = Vec::with_capacity(df.height());
+    let complete_high = complete_windows(&high, window);
+    let complete_low = complete_windows(&low, window);
+    let highest_highs = sliding_max(&high, window);
+    let lowest_lows = sliding_min(&low, window);
 
-    // Fill initial values with NaN
-    for _ in 0..window - 1 {
-        williams_r_values.push(f64::NAN);
-    }
-
-    // Calculate Williams %R for the remaining data points
-    for i in window - 1..df.height() {
-        let mut highest_high = f64::NEG_INFINITY;
-        let mut lowest_low = f64::INFINITY;
-        let mut valid_data = true;
-
-        // Find highest high and lowest low in the window
-        for j in i - (window - 1)..=i {
-            let h = high.get(j).unwrap_or(f64::NAN);
-            let l = low.get(j).unwrap_or(f64::NAN);
-
-            if h.is_nan() || l.is_nan() {
-                valid_data = false;
-                break;
-            }
-
-            highest_high = highest_high.max(h);
-            lowest_low = lowest_low.min(l);
-        }
-
-        if !valid_data || (highest_high - lowest_low).abs() < 1e-10 {
-            williams_r_values.push(f64::NAN);
-        } else {
-            let c = close.get(i).unwrap_or(f64::NAN);
-            if c.is_nan() {
-                williams_r_values.push(f64::NAN);
+    // Calculate Williams %R for each data point
+    let williams_r_values: Vec<f64> = (0..df.height())
+        .map(|i| {
+            let (highest_high, lowest_low) = (highest_highs[i], lowest_lows[i]);
+            if !complete_high[i] || !complete_low[i] || (highest_high - lowest_low).abs() < 1e-10 {
+                f64::NAN
             } else {
-                let williams_r = ((highest_high - c) / (highest_high - lowest_low)) * -100.0;
-                williams_r_values.push(williams_r);
+                let c = close.get(i).unwrap_or(f64::NAN);
+                ((highest_high - c) / (highest_high - lowest_low)) * -100.0
             }
-        }
-    }
+        })
+        .collect();
 
     // Create Series with Williams %R values
-    let name = format!("williams_r_{}", window);
+    let name = indicator_name("williams_r", &[&window]);
     Ok(Series::new(name.into(), williams_r_values))
 }
@@ -0,0 +1,290 @@
+//! Experimental Elliott-wave-style labeling of ZigZag swings
+//!
+//! Heuristics only: the labels and confidence scores are features for
+//! research, not a wave count an analyst would sign off on.
+
+use crate::indicators::trend::{swing_pivots, SwingPivot, ZigZagThreshold};
+use polars::prelude::*;
+
+/// Code of an impulse in the "elliott_pattern" column
+pub const ELLIOTT_IMPULSE: i32 = 1;
+/// Code of a corrective sequence in the "elliott_pattern" column
+pub const ELLIOTT_CORRECTIVE: i32 = 2;
+
+/// Parameters for [`label_elliott_waves`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElliottConfig {
+    /// Reversal threshold of the ZigZag swings the waves are built from
+    pub threshold: ZigZagThreshold,
+    /// Sequences scoring below this confidence (0 to 1) are dropped
+    pub min_confidence: f64,
+}
+
+impl Default for ElliottConfig {
+    fn default() -> Self {
+        Self {
+            threshold: ZigZagThreshold::Percent(5.0),
+            min_confidence: 0.0,
+        }
+    }
+}
+
+/// Wave length ratios of a labeled sequence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WaveRatios {
+    /// Five-wave move in the direction of the trend
+    Impulse {
+        /// Wave 2 length over wave 1 length
+        wave2_retracement: f64,
+        /// Wave 3 length over wave 1 length
+        wave3_extension: f64,
+        /// Wave 4 length over wave 3 length
+        wave4_retracement: f64,
+        /// Wave 5 length over wave 1 length
+        wave5_to_wave1: f64,
+    },
+    /// A-B-C move against the preceding impulse
+    Corrective {
+        /// Wave B length over wave A length
+        b_retracement: f64,
+        /// Wave C length over wave A length
+        c_to_a: f64,
+    },
+}
+
+/// A labeled impulse or corrective sequence
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveSequence {
+    /// Kind of sequence and its wave ratios
+    pub ratios: WaveRatios,
+    /// Whether the first wave rises
+    pub rising: bool,
+    /// Start pivot followed by the end pivot of each wave: six for an
+    /// impulse, four for a corrective sequence
+    pub pivots: Vec<SwingPivot>,
+    /// Mean of the guideline scores, from 0 to 1
+    pub confidence: f64,
+    /// Row on which the last pivot was confirmed; `None` while it may still
+    /// move
+    pub confirmed_at: Option<usize>,
+}
+
+impl WaveSequence {
+    /// Code of the sequence in the "elliott_pattern" column
+    pub fn pattern_code(&self) -> i32 {
+        match self.ratios {
+            WaveRatios::Impulse { .. } => ELLIOTT_IMPULSE,
+            WaveRatios::Corrective { .. } => ELLIOTT_CORRECTIVE,
+        }
+    }
+}
+
+/// Label impulse and corrective sequences in ZigZag swings (experimental)
+///
+/// Six consecutive pivots form an impulse when they satisfy the hard rules:
+/// wave 2 does not retrace past the start of wave 1, wave 3 is not the
+/// shortest of waves 1, 3 and 5, wave 4 does not enter wave 1's range and
+/// waves 3 and 5 make new extremes. The confidence averages how close the
+/// guidelines are met: wave 2 retracing 61.8% of wave 1, wave 3 extending
+/// to at least 161.8% of wave 1, wave 4 retracing 38.2% of wave 3 and wave 5
+/// matching wave 1.
+///
+/// The four pivots after an impulse form a corrective sequence when wave B
+/// retraces part of wave A and wave C ends beyond wave A, scored by B
+/// retracing 61.8% of A and C matching A. Sequences do not overlap except at
+/// their shared end pivot.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with "high" and "low" columns, plus "close" for an ATR
+///   threshold
+/// * `config` - Swing threshold and minimum confidence
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the sequences in order
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::pattern_recognition::{label_elliott_waves, ElliottConfig, WaveRatios};
+/// use rustalib::indicators::trend::ZigZagThreshold;
+///
+/// // Five waves up from 100 to 150, then an A-B-C down to 125
+/// let path = [100.0, 120.0, 108.0, 145.0, 135.0, 150.0, 135.0, 144.0, 125.0, 130.0];
+/// let mut close = Vec::new();
+/// for leg in path.windows(2) {
+///     for step in 0..4 {
+///         close.push(leg[0] + (leg[1] - leg[0]) * step as f64 / 4.0);
+///     }
+/// }
+/// close.push(130.0);
+/// let df = df! { "high" => &close, "low" => &close }.unwrap();
+///
+/// let config = ElliottConfig {
+///     threshold: ZigZagThreshold::Percent(3.0),
+///     ..Default::default()
+/// };
+/// let sequences = label_elliott_waves(&df, &config).unwrap();
+/// assert_eq!(sequences.len(), 2);
+/// assert!(sequences[0].rising);
+/// let WaveRatios::Impulse { wave3_extension, .. } = sequences[0].ratios else {
+///     panic!("expected an impulse");
+/// };
+/// assert!((wave3_extension - 37.0 / 20.0).abs() < 1e-9);
+/// assert!(matches!(sequences[1].ratios, WaveRatios::Corrective { .. }));
+/// ```
+pub fn label_elliott_waves(
+    df: &DataFrame,
+    config: &ElliottConfig,
+) -> PolarsResult<Vec<WaveSequence>> {
+    let pivots = swing_pivots(df, config.threshold)?;
+
+    let mut sequences = Vec::new();
+    let mut i = 0;
+    while i + 5 < pivots.len() {
+        let Some(impulse) = impulse(&pivots[i..=i + 5]) else {
+            i += 1;
+            continue;
+        };
+        i += 5;
+        let correction = pivots.get(i..=i + 3).and_then(corrective);
+        sequences.push(impulse);
+        if let Some(correction) = correction {
+            sequences.push(correction);
+            i += 3;
+        }
+    }
+
+    sequences.retain(|s| s.confidence >= config.min_confidence);
+    Ok(sequences)
+}
+
+/// Label Elliott-wave-style sequences per bar (experimental)
+///
+/// Each bar after a sequence's start pivot, up to and including its end
+/// pivot, gets the sequence and the number of the wave it falls in. The
+/// labels use pivots that are only known later, so they describe the past
+/// and are not trading signals.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with "high" and "low" columns, plus "close" for an ATR
+///   threshold
+/// * `config` - Swing threshold and minimum confidence
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with:
+/// * elliott_pattern - [`ELLIOTT_IMPULSE`], [`ELLIOTT_CORRECTIVE`] or 0
+/// * elliott_wave - Wave number, 1 to 5 in an impulse and 1 to 3 for A to C
+///   in a corrective sequence, 0 outside a sequence
+/// * elliott_confidence - Confidence of the sequence, NaN outside one
+/// * elliott_wave3_extension - Wave 3 over wave 1 length of the impulse, NaN
+///   outside one
+pub fn calculate_elliott_waves(df: &DataFrame, config: &ElliottConfig) -> PolarsResult<DataFrame> {
+    let n = df.height();
+    let mut pattern = vec![0i32; n];
+    let mut wave = vec![0i32; n];
+    let mut confidence = vec![f64::NAN; n];
+    let mut extension = vec![f64::NAN; n];
+
+    for sequence in label_elliott_waves(df, config)? {
+        let wave3_extension = match sequence.ratios {
+            WaveRatios::Impulse {
+                wave3_extension, ..
+            } => wave3_extension,
+            WaveRatios::Corrective { .. } => f64::NAN,
+        };
+        for (number, leg) in sequence.pivots.windows(2).enumerate() {
+            for row in leg[0].index + 1..=leg[1].index {
+                pattern[row] = sequence.pattern_code();
+                wave[row] = number as i32 + 1;
+                confidence[row] = sequence.confidence;
+                extension[row] = wave3_extension;
+            }
+        }
+    }
+
+    DataFrame::new(vec![
+        Series::new("elliott_pattern".into(), pattern).into(),
+        Series::new("elliott_wave".into(), wave).into(),
+        Series::new("elliott_confidence".into(), confidence).into(),
+        Series::new("elliott_wave3_extension".into(), extension).into(),
+    ])
+}
+
+/// Score an impulse on six pivots, or `None` when a hard rule fails
+fn impulse(pivots: &[SwingPivot]) -> Option<WaveSequence> {
+    let rising = !pivots[0].is_high;
+    // Prices in the direction of the impulse, so it always moves up
+    let v: Vec<f64> = pivots
+        .iter()
+        .map(|p| if rising { p.price } else { -p.price })
+        .collect();
+    let len = |k: usize| (v[k] - v[k - 1]).abs();
+    let (w1, w2, w3, w4, w5) = (len(1), len(2), len(3), len(4), len(5));
+
+    let rules = v[2] > v[0] && v[3] > v[1] && v[4] > v[1] && v[5] > v[3];
+    let wave3_shortest = w3 < w1 && w3 < w5;
+    if !rules || wave3_shortest {
+        return None;
+    }
+
+    let ratios = WaveRatios::Impulse {
+        wave2_retracement: w2 / w1,
+        wave3_extension: w3 / w1,
+        wave4_retracement: w4 / w3,
+        wave5_to_wave1: w5 / w1,
+    };
+    let scores = [
+        closeness(w2 / w1, 0.618, 0.382),
+        ((w3 / w1 - 1.0) / 0.618).clamp(0.0, 1.0),
+        closeness(w4 / w3, 0.382, 0.382),
+        closeness(w5 / w1, 1.0, 0.618),
+    ];
+    Some(sequence(ratios, rising, pivots, &scores))
+}
+
+/// Score a corrective sequence on four pivots, or `None` when a rule fails
+fn corrective(pivots: &[SwingPivot]) -> Option<WaveSequence> {
+    let rising = !pivots[0].is_high;
+    let v: Vec<f64> = pivots
+        .iter()
+        .map(|p| if rising { p.price } else { -p.price })
+        .collect();
+    let len = |k: usize| (v[k] - v[k - 1]).abs();
+    let (a, b, c) = (len(1), len(2), len(3));
+
+    if !(v[2] > v[0] && v[3] > v[1]) {
+        return None;
+    }
+
+    let ratios = WaveRatios::Corrective {
+        b_retracement: b / a,
+        c_to_a: c / a,
+    };
+    let scores = [closeness(b / a, 0.618, 0.382), closeness(c / a, 1.0, 0.618)];
+    Some(sequence(ratios, rising, pivots, &scores))
+}
+
+fn sequence(
+    ratios: WaveRatios,
+    rising: bool,
+    pivots: &[SwingPivot],
+    scores: &[f64],
+) -> WaveSequence {
+    WaveSequence {
+        ratios,
+        rising,
+        pivots: pivots.to_vec(),
+        confidence: scores.iter().sum::<f64>() / scores.len() as f64,
+        confirmed_at: pivots[pivots.len() - 1].confirmed_at,
+    }
+}
+
+/// 1 at `target`, falling linearly to 0 at `tolerance` away from it
+fn closeness(value: f64, target: f64, tolerance: f64) -> f64 {
+    (1.0 - (value - target).abs() / tolerance).max(0.0)
+}
@@ -1,6 +1,15 @@
 // Pattern Recognition module
 
 mod candlestick;
+mod elliott;
+mod pattern_store;
+mod support_resistance;
 
 // Re-export pattern recognition functions
 pub use candlestick::recognize_patterns;
+pub use elliott::{
+    calculate_elliott_waves, label_elliott_waves, ElliottConfig, WaveRatios, WaveSequence,
+    ELLIOTT_CORRECTIVE, ELLIOTT_IMPULSE,
+};
+pub use pattern_store::{Detection, PatternKind, PatternStore};
+pub use support_resistance::{detect_support_resistance, LevelParams};
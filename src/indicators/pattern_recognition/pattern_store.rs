@@ -0,0 +1,265 @@
+use polars::prelude::*;
+use std::collections::BTreeMap;
+
+/// Category of a detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternKind {
+    /// Horizontal support level
+    Support,
+    /// Horizontal resistance level
+    Resistance,
+    /// Candlestick pattern (doji, hammer, engulfing, ...)
+    Candlestick,
+    /// Chart pattern (head and shoulders, triangle, flag, ...)
+    Chart,
+    /// Harmonic pattern (Gartley, bat, butterfly, ...)
+    Harmonic,
+}
+
+impl PatternKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PatternKind::Support => "support",
+            PatternKind::Resistance => "resistance",
+            PatternKind::Candlestick => "candlestick",
+            PatternKind::Chart => "chart",
+            PatternKind::Harmonic => "harmonic",
+        }
+    }
+}
+
+/// A detected pattern or level
+///
+/// Bars are row indices of the DataFrame the detection was made on. A
+/// detection is known from `confirmed_bar` onward and stays active until
+/// `invalidated_bar` (e.g. the bar that broke a level).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    /// Category of the detection
+    pub kind: PatternKind,
+    /// Pattern name (e.g. "hammer", "gartley", "swing_resistance")
+    pub name: String,
+    /// First bar of the formation
+    pub start_bar: usize,
+    /// Bar at which the detection became known
+    pub confirmed_bar: usize,
+    /// Bar at which the detection was broken or invalidated, if any
+    pub invalidated_bar: Option<usize>,
+    /// Level price or pattern target, if the pattern has one
+    pub price: Option<f64>,
+    /// 1 for bullish, -1 for bearish, 0 for neutral
+    pub direction: i32,
+    /// Detector-specific strength (e.g. number of touches of a level)
+    pub strength: f64,
+    /// Additional detector-specific attributes
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Detection {
+    /// Create a neutral detection without price, strength or metadata
+    pub fn new(kind: PatternKind, name: &str, start_bar: usize, confirmed_bar: usize) -> Self {
+        Self {
+            kind,
+            name: name.to_string(),
+            start_bar,
+            confirmed_bar,
+            invalidated_bar: None,
+            price: None,
+            direction: 0,
+            strength: 0.0,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// Whether the detection is known and not yet invalidated at `bar`
+    pub fn is_active_at(&self, bar: usize) -> bool {
+        self.confirmed_bar <= bar && self.invalidated_bar.is_none_or(|b| b > bar)
+    }
+}
+
+/// Queryable collection of detected patterns and levels
+///
+/// Detectors add their results once; strategies then query the store bar by
+/// bar instead of re-scanning DataFrames. Queries only return detections
+/// confirmed at or before the queried bar, so they are free of look-ahead.
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::pattern_recognition::{Detection, PatternKind, PatternStore};
+///
+/// let mut store = PatternStore::new();
+/// let mut level = Detection::new(PatternKind::Resistance, "swing_resistance", 3, 10);
+/// level.price = Some(105.0);
+/// let id = store.add(level);
+/// store.invalidate(id, 20);
+///
+/// assert!(store.active_levels_at(9, PatternKind::Resistance).is_empty());
+/// assert_eq!(store.active_levels_at(15, PatternKind::Resistance).len(), 1);
+/// assert!(store.active_levels_at(20, PatternKind::Resistance).is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PatternStore {
+    detections: Vec<Detection>,
+}
+
+impl PatternStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a detection and return its id
+    pub fn add(&mut self, detection: Detection) -> usize {
+        self.detections.push(detection);
+        self.detections.len() - 1
+    }
+
+    /// Mark a detection as invalidated at `bar`; ignored for unknown ids
+    pub fn invalidate(&mut self, id: usize, bar: usize) {
+        if let Some(detection) = self.detections.get_mut(id) {
+            detection.invalidated_bar = Some(bar);
+        }
+    }
+
+    /// Detection with the given id
+    pub fn get(&self, id: usize) -> Option<&Detection> {
+        self.detections.get(id)
+    }
+
+    /// Mutable detection with the given id
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Detection> {
+        self.detections.get_mut(id)
+    }
+
+    /// Number of detections
+    pub fn len(&self) -> usize {
+        self.detections.len()
+    }
+
+    /// Whether the store has no detections
+    pub fn is_empty(&self) -> bool {
+        self.detections.is_empty()
+    }
+
+    /// All detections in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = &Detection> {
+        self.detections.iter()
+    }
+
+    /// Detections of one kind
+    pub fn of_kind(&self, kind: PatternKind) -> impl Iterator<Item = &Detection> {
+        self.detections.iter().filter(move |d| d.kind == kind)
+    }
+
+    /// Detections known and not invalidated at `bar`
+    pub fn active_at(&self, bar: usize) -> impl Iterator<Item = &Detection> {
+        self.detections.iter().filter(move |d| d.is_active_at(bar))
+    }
+
+    /// Detections whose formation overlaps the bars `start..=end`
+    pub fn in_range(&self, start: usize, end: usize) -> impl Iterator<Item = &Detection> {
+        self.detections.iter().filter(move |d| {
+            let last = d.invalidated_bar.unwrap_or(usize::MAX).max(d.confirmed_bar);
+            d.start_bar <= end && last >= start
+        })
+    }
+
+    /// Active, unbroken levels of one kind at `bar`, sorted by price
+    pub fn active_levels_at(&self, bar: usize, kind: PatternKind) -> Vec<&Detection> {
+        let mut levels: Vec<&Detection> = self
+            .active_at(bar)
+            .filter(|d| d.kind == kind && d.price.is_some())
+            .collect();
+        levels.sort_by(|a, b| a.price.unwrap().total_cmp(&b.price.unwrap()));
+        levels
+    }
+
+    /// Closest active resistance at or above `price` at `bar`
+    pub fn nearest_resistance(&self, bar: usize, price: f64) -> Option<&Detection> {
+        self.active_levels_at(bar, PatternKind::Resistance)
+            .into_iter()
+            .find(|d| d.price.unwrap() >= price)
+    }
+
+    /// Closest active support at or below `price` at `bar`
+    pub fn nearest_support(&self, bar: usize, price: f64) -> Option<&Detection> {
+        self.active_levels_at(bar, PatternKind::Support)
+            .into_iter()
+            .rev()
+            .find(|d| d.price.unwrap() <= price)
+    }
+
+    /// Append every detection of another store
+    pub fn extend(&mut self, other: PatternStore) {
+        self.detections.extend(other.detections);
+    }
+
+    /// Record the non-zero values of a per-bar signal column as detections
+    ///
+    /// Each non-zero bar becomes a single-bar detection named after the
+    /// column, with the sign of the value as direction and its magnitude as
+    /// strength. Use this to collect the output of column-based detectors.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - DataFrame holding the signal column
+    /// * `column` - Numeric column where non-zero values mark a detection
+    /// * `kind` - Category assigned to the detections
+    ///
+    /// # Returns
+    ///
+    /// Returns a PolarsResult containing the number of detections added
+    pub fn record_signal_column(
+        &mut self,
+        df: &DataFrame,
+        column: &str,
+        kind: PatternKind,
+    ) -> PolarsResult<usize> {
+        let values = df.column(column)?.cast(&DataType::Float64)?;
+        let values = values.f64()?;
+        let before = self.detections.len();
+
+        for (bar, value) in values.into_iter().enumerate() {
+            let Some(value) = value.filter(|v| *v != 0.0 && !v.is_nan()) else {
+                continue;
+            };
+            let mut detection = Detection::new(kind, column, bar, bar);
+            detection.direction = value.signum() as i32;
+            detection.strength = value.abs();
+            self.add(detection);
+        }
+
+        Ok(self.detections.len() - before)
+    }
+
+    /// All detections as a DataFrame, one row per detection
+    ///
+    /// Columns: "id", "kind", "name", "start_bar", "confirmed_bar",
+    /// "invalidated_bar" (null while active), "price", "direction",
+    /// "strength" and "metadata" ("key=value" pairs joined by ";").
+    pub fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        let d = &self.detections;
+        df! {
+            "id" => (0..d.len() as u32).collect::<Vec<_>>(),
+            "kind" => d.iter().map(|x| x.kind.as_str()).collect::<Vec<_>>(),
+            "name" => d.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(),
+            "start_bar" => d.iter().map(|x| x.start_bar as u32).collect::<Vec<_>>(),
+            "confirmed_bar" => d.iter().map(|x| x.confirmed_bar as u32).collect::<Vec<_>>(),
+            "invalidated_bar" => d.iter().map(|x| x.invalidated_bar.map(|b| b as u32)).collect::<Vec<_>>(),
+            "price" => d.iter().map(|x| x.price).collect::<Vec<_>>(),
+            "direction" => d.iter().map(|x| x.direction).collect::<Vec<_>>(),
+            "strength" => d.iter().map(|x| x.strength).collect::<Vec<_>>(),
+            "metadata" => d
+                .iter()
+                .map(|x| {
+                    x.metadata
+                        .iter()
+                        .map(|(k, v)| format!("{k}={v}"))
+                        .collect::<Vec<_>>()
+                        .join(";")
+                })
+                .collect::<Vec<_>>(),
+        }
+    }
+}
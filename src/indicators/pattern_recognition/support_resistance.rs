@@ -0,0 +1,190 @@
+use crate::indicators::pattern_recognition::pattern_store::{Detection, PatternKind, PatternStore};
+use crate::util::dataframe_utils::column_nan_values;
+use polars::prelude::*;
+
+/// Parameters for [`detect_support_resistance`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelParams {
+    /// Bars on each side a swing high/low must exceed
+    pub swing_strength: usize,
+    /// Distance in percent within which swings count as the same level
+    pub tolerance_pct: f64,
+    /// Swings needed before a level is reported
+    pub min_touches: usize,
+}
+
+impl Default for LevelParams {
+    fn default() -> Self {
+        Self {
+            swing_strength: 2,
+            tolerance_pct: 0.5,
+            min_touches: 2,
+        }
+    }
+}
+
+/// Level being built from swing points
+struct CandidateLevel {
+    kind: PatternKind,
+    touches: Vec<f64>,
+    first_bar: usize,
+    detection: Option<usize>,
+    broken: bool,
+}
+
+impl CandidateLevel {
+    fn price(&self) -> f64 {
+        self.touches.iter().sum::<f64>() / self.touches.len() as f64
+    }
+}
+
+/// Detect horizontal support and resistance levels from swing points
+///
+/// Swing highs and lows are confirmed `swing_strength` bars after they
+/// occur. Swings within `tolerance_pct` of an unbroken level of the same
+/// type add a touch to it; a level is reported once it has `min_touches`
+/// touches, at the price of their mean. A resistance is broken by a close
+/// more than `tolerance_pct` above it and a support by a close more than
+/// `tolerance_pct` below it. Everything is processed bar by bar, so the
+/// store never reports a level before the data could have shown it.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with "high", "low" and "close" columns
+/// * `params` - Detection parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a [`PatternStore`] with "swing_support"
+/// and "swing_resistance" detections; strength is the number of touches and
+/// the metadata records it as "touches"
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::pattern_recognition::{detect_support_resistance, LevelParams, PatternKind};
+///
+/// // Two rejections at 110, then a breakout
+/// let high = [100.0, 105.0, 110.0, 104.0, 101.0, 106.0, 110.0, 105.0, 102.0, 108.0, 114.0, 116.0];
+/// let low: Vec<f64> = high.iter().map(|h| h - 3.0).collect();
+/// let close: Vec<f64> = high.iter().map(|h| h - 1.0).collect();
+/// let df = df! { "high" => high, "low" => low, "close" => close }.unwrap();
+///
+/// let store = detect_support_resistance(&df, &LevelParams::default()).unwrap();
+///
+/// let before_breakout = store.active_levels_at(9, PatternKind::Resistance);
+/// assert_eq!(before_breakout.len(), 1);
+/// assert_eq!(before_breakout[0].price, Some(110.0));
+/// assert!(store.active_levels_at(11, PatternKind::Resistance).is_empty());
+/// ```
+pub fn detect_support_resistance(
+    df: &DataFrame,
+    params: &LevelParams,
+) -> PolarsResult<PatternStore> {
+    for col in ["high", "low", "close"] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Support/resistance detection requires '{col}' column").into(),
+            ));
+        }
+    }
+    if params.swing_strength == 0 || params.min_touches == 0 {
+        return Err(PolarsError::ComputeError(
+            "Swing strength and minimum touches must be positive".into(),
+        ));
+    }
+
+    let high = column_nan_values(df, "high")?;
+    let low = column_nan_values(df, "low")?;
+    let close = df.column("close")?.cast(&DataType::Float64)?;
+    let close = close.f64()?;
+
+    let tolerance = params.tolerance_pct / 100.0;
+    let strength = params.swing_strength;
+    let mut store = PatternStore::new();
+    let mut candidates: Vec<CandidateLevel> = Vec::new();
+
+    for bar in 0..df.height() {
+        // Breaks on this bar's close
+        if let Some(c) = close.get(bar).filter(|c| !c.is_nan()) {
+            for level in candidates.iter_mut().filter(|l| !l.broken) {
+                let price = level.price();
+                let broken = match level.kind {
+                    PatternKind::Resistance => c > price * (1.0 + tolerance),
+                    _ => c < price * (1.0 - tolerance),
+                };
+                if broken {
+                    level.broken = true;
+                    if let Some(id) = level.detection {
+                        store.invalidate(id, bar);
+                    }
+                }
+            }
+        }
+
+        // Swing centred `strength` bars ago is confirmed on this bar
+        if bar < 2 * strength {
+            continue;
+        }
+        let pivot = bar - strength;
+        let window = pivot - strength..=bar;
+        for kind in [PatternKind::Resistance, PatternKind::Support] {
+            let (values, extreme) = match kind {
+                PatternKind::Resistance => (&high, high[pivot]),
+                _ => (&low, low[pivot]),
+            };
+            let is_swing = !extreme.is_nan()
+                && window.clone().filter(|&j| j != pivot).all(|j| match kind {
+                    PatternKind::Resistance => extreme > values[j],
+                    _ => extreme < values[j],
+                });
+            if !is_swing {
+                continue;
+            }
+
+            let existing = candidates.iter_mut().find(|l| {
+                l.kind == kind && !l.broken && (extreme - l.price()).abs() <= l.price() * tolerance
+            });
+            let level = match existing {
+                Some(level) => {
+                    level.touches.push(extreme);
+                    level
+                }
+                None => {
+                    candidates.push(CandidateLevel {
+                        kind,
+                        touches: vec![extreme],
+                        first_bar: pivot,
+                        detection: None,
+                        broken: false,
+                    });
+                    candidates.last_mut().unwrap()
+                }
+            };
+
+            if level.touches.len() < params.min_touches {
+                continue;
+            }
+            let touches = level.touches.len();
+            let id = *level.detection.get_or_insert_with(|| {
+                let name = match kind {
+                    PatternKind::Resistance => "swing_resistance",
+                    _ => "swing_support",
+                };
+                let mut detection = Detection::new(kind, name, level.first_bar, bar);
+                detection.direction = if kind == PatternKind::Support { 1 } else { -1 };
+                store.add(detection)
+            });
+            if let Some(detection) = store.get_mut(id) {
+                detection.price = Some(level.price());
+                detection.strength = touches as f64;
+                detection
+                    .metadata
+                    .insert("touches".to_string(), touches.to_string());
+            }
+        }
+    }
+
+    Ok(store)
+}
@@ -0,0 +1,187 @@
+//! # Indicator Presets
+//!
+//! Curated indicator sets with periods suited to a trading timeframe. Each
+//! preset is an [`IndicatorConfig`] listing its indicators, so it runs
+//! through [`add_technical_indicators_with_config`] (or the lazy and
+//! parallel variants) and can be extended with further indicators or a
+//! naming policy like any other config.
+//!
+//! | Preset | Bars | Indicators |
+//! |--------|------|------------|
+//! | [`ScalpingM1`](IndicatorPreset::ScalpingM1) | 1 minute | ema_9, ema_21, rsi_7, macd (6/13/5), bb (20, 2.0), atr_14, keltner_trend_20 (1.5), time features |
+//! | [`SwingDaily`](IndicatorPreset::SwingDaily) | 1 day | ema_20, sma_50, sma_200, rsi_14, macd (12/26/9), bb (20, 2.0), atr_14, adx_14, keltner_trend_20 (2.0) |
+//! | [`PositionWeekly`](IndicatorPreset::PositionWeekly) | 1 week | sma_10, sma_40, rsi_14, macd (12/26/9), atr_10, adx_14, keltner_trend_10 (2.5) |
+//!
+//! MACD adds "macd", "macd_signal" and "macd_histogram"; Bollinger Bands
+//! add "bb_middle", "bb_upper" and "bb_lower".
+//!
+//! [`add_technical_indicators_with_config`]: super::add_technical_indicators_with_config
+
+use crate::indicators::add_indicators::IndicatorConfig;
+use crate::indicators::naming::indicator_name;
+use crate::indicators::spec::IndicatorSpec;
+use serde::{Deserialize, Serialize};
+
+/// A curated indicator set for one trading timeframe
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::{add_technical_indicators_with_config, IndicatorPreset};
+///
+/// let close: Vec<f64> = (0..250).map(|i| 100.0 + (i as f64 / 10.0).sin() * 5.0).collect();
+/// let mut df = df! {
+///     "open" => &close,
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+/// }
+/// .unwrap();
+///
+/// let config = IndicatorPreset::SwingDaily.config();
+/// let result = add_technical_indicators_with_config(&mut df, &config).unwrap();
+/// for name in config.output_columns() {
+///     assert!(result.column(&name).is_ok());
+/// }
+/// assert!(result.column("sma_200").is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndicatorPreset {
+    /// One-minute bars: fast EMAs, a short RSI, a quick MACD, volatility
+    /// bands, a tight Keltner trend filter and time-of-day features
+    ScalpingM1,
+    /// Daily bars: the 20/50/200 moving averages, classic RSI and MACD,
+    /// volatility bands, trend strength and a Keltner trend filter
+    SwingDaily,
+    /// Weekly bars: the 10/40-week averages (about 50 and 200 days), RSI,
+    /// MACD, trend strength and a wide Keltner trend filter
+    PositionWeekly,
+}
+
+impl IndicatorPreset {
+    /// Every preset, from the shortest timeframe to the longest
+    pub const ALL: [IndicatorPreset; 3] = [
+        IndicatorPreset::ScalpingM1,
+        IndicatorPreset::SwingDaily,
+        IndicatorPreset::PositionWeekly,
+    ];
+
+    /// Indicator config adding the preset's indicators and nothing else
+    pub fn config(self) -> IndicatorConfig {
+        let config = IndicatorConfig {
+            time_features: self == IndicatorPreset::ScalpingM1,
+            ..IndicatorConfig::none()
+        };
+        self.indicators()
+            .into_iter()
+            .fold(config, IndicatorConfig::with_indicator)
+    }
+
+    /// Indicators listed by the preset, in output order
+    pub fn indicators(self) -> Vec<IndicatorSpec> {
+        match self {
+            IndicatorPreset::ScalpingM1 => vec![
+                ema(9),
+                ema(21),
+                rsi(7),
+                macd(6, 13, 5),
+                bollinger_bands(20, 2.0),
+                atr(14),
+                keltner_trend(20, 1.5),
+            ],
+            IndicatorPreset::SwingDaily => vec![
+                ema(20),
+                sma(50),
+                sma(200),
+                rsi(14),
+                macd(12, 26, 9),
+                bollinger_bands(20, 2.0),
+                atr(14),
+                adx(14),
+                keltner_trend(20, 2.0),
+            ],
+            IndicatorPreset::PositionWeekly => vec![
+                sma(10),
+                sma(40),
+                rsi(14),
+                macd(12, 26, 9),
+                atr(10),
+                adx(14),
+                keltner_trend(10, 2.5),
+            ],
+        }
+    }
+}
+
+impl From<IndicatorPreset> for IndicatorConfig {
+    fn from(preset: IndicatorPreset) -> Self {
+        preset.config()
+    }
+}
+
+fn sma(period: usize) -> IndicatorSpec {
+    IndicatorSpec::Sma {
+        name: indicator_name("sma", &[&period]),
+        period,
+        column: "close".to_string(),
+    }
+}
+
+fn ema(period: usize) -> IndicatorSpec {
+    IndicatorSpec::Ema {
+        name: indicator_name("ema", &[&period]),
+        period,
+        column: "close".to_string(),
+    }
+}
+
+fn rsi(period: usize) -> IndicatorSpec {
+    IndicatorSpec::Rsi {
+        name: indicator_name("rsi", &[&period]),
+        period,
+        column: "close".to_string(),
+    }
+}
+
+fn macd(fast_period: usize, slow_period: usize, signal_period: usize) -> IndicatorSpec {
+    IndicatorSpec::Macd {
+        name: "macd".to_string(),
+        fast_period,
+        slow_period,
+        signal_period,
+        column: "close".to_string(),
+    }
+}
+
+fn bollinger_bands(period: usize, num_std: f64) -> IndicatorSpec {
+    IndicatorSpec::BollingerBands {
+        name: "bb".to_string(),
+        period,
+        num_std,
+        column: "close".to_string(),
+    }
+}
+
+fn atr(period: usize) -> IndicatorSpec {
+    IndicatorSpec::Atr {
+        name: indicator_name("atr", &[&period]),
+        period,
+    }
+}
+
+fn adx(period: usize) -> IndicatorSpec {
+    IndicatorSpec::Adx {
+        name: indicator_name("adx", &[&period]),
+        period,
+    }
+}
+
+fn keltner_trend(period: usize, multiplier: f64) -> IndicatorSpec {
+    IndicatorSpec::KeltnerTrend {
+        name: indicator_name("keltner_trend", &[&period]),
+        period,
+        multiplier,
+    }
+}
@@ -1,27 +1,30 @@
+use crate::error::TaError;
 use polars::prelude::*;
 
 /// Calculates Average Price
-/// Formula: (High + Low) / 2
+/// Formula: (Open + High + Low + Close) / 4
 ///
 /// # Arguments
 ///
-/// * `df` - DataFrame containing the price data with high and low columns
+/// * `df` - DataFrame containing the price data with open, high, low and
+///   close columns
 ///
 /// # Returns
 ///
 /// Returns a PolarsResult containing the Average Price Series
 pub fn calculate_avgprice(df: &DataFrame) -> PolarsResult<Series> {
-    if !df.schema().contains("high") || !df.schema().contains("low") {
-        return Err(PolarsError::ComputeError(
-            "Average Price calculation requires high and low columns".into(),
-        ));
+    for col in ["open", "high", "low", "close"] {
+        if !df.schema().contains(col) {
+            return Err(TaError::MissingColumn(col.to_string()).into());
+        }
     }
 
+    let open = df.column("open")?.f64()?;
     let high = df.column("high")?.f64()?;
     let low = df.column("low")?.f64()?;
+    let close = df.column("close")?.f64()?;
 
-    // Add high and low and divide by 2
-    let avg_price = (high + low) / 2.0;
+    let avg_price = (&(&(open + high) + low) + close) / 4.0;
 
     Ok(avg_price.into_series().with_name("avgprice".into()))
 }
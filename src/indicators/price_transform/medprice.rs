@@ -11,7 +11,6 @@ use polars::prelude::*;
 ///
 /// Returns a PolarsResult containing the Median Price Series
 pub fn calculate_medprice(df: &DataFrame) -> PolarsResult<Series> {
-    // Note: This is actually the same as avgprice but kept separate for naming consistency with TA-Lib
     if !df.schema().contains("high") || !df.schema().contains("low") {
         return Err(PolarsError::ComputeError(
             "Median Price calculation requires high and low columns".into(),
@@ -0,0 +1,161 @@
+use crate::indicators::stats::rolling_zscore;
+use crate::util::time_utils::parse_timestamp_column;
+use polars::prelude::*;
+
+/// An external macro series (rates, DXY, VIX, ...) supplied by the user
+#[derive(Debug, Clone, Copy)]
+pub struct MacroSeries<'a> {
+    /// Short name used in output column names (e.g. "vix")
+    pub name: &'a str,
+    /// DataFrame with the macro observations
+    pub data: &'a DataFrame,
+    /// Column with observation timestamps
+    pub time_column: &'a str,
+    /// Column with the macro values
+    pub value_column: &'a str,
+    /// Weight in the composite; use a negative weight for series where high
+    /// values mean risk-off (e.g. VIX, DXY for equities)
+    pub weight: f64,
+}
+
+/// Join z-scored macro series and their composite onto a price DataFrame
+///
+/// Each macro series is z-scored on its own observation frequency over a
+/// rolling window, then attached to every price bar as of the bar's time
+/// (the latest observation at or before the bar). The composite is the
+/// weighted mean of the available z-scores, with weights normalized by
+/// their absolute sum, so positive values mean a supportive macro backdrop.
+///
+/// # Arguments
+///
+/// * `df` - Price DataFrame
+/// * `time_column` - Column with the price bar timestamps
+/// * `series` - Macro series to join
+/// * `window` - Number of macro observations for each z-score
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a copy of `df` with "macro_{name}_z"
+/// columns and a "macro_composite_z" column (NaN before enough history)
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::short_term::{add_macro_regime_features, MacroSeries};
+///
+/// let prices = df! {
+///     "timestamp" => ["2024-01-03 15:00:00", "2024-01-04 15:00:00", "2024-01-04 16:00:00"],
+///     "close" => [100.0, 99.0, 98.5],
+/// }
+/// .unwrap();
+/// let vix = df! {
+///     "date" => ["2024-01-02", "2024-01-03", "2024-01-04"],
+///     "value" => [13.0, 14.0, 18.0],
+/// }
+/// .unwrap();
+///
+/// let series = [MacroSeries {
+///     name: "vix",
+///     data: &vix,
+///     time_column: "date",
+///     value_column: "value",
+///     weight: -1.0,
+/// }];
+/// let features = add_macro_regime_features(&prices, "timestamp", &series, 2).unwrap();
+///
+/// // Both bars on Jan 4 see that day's VIX spike as a negative composite
+/// let composite = features.column("macro_composite_z").unwrap().f64().unwrap();
+/// assert!(composite.get(1).unwrap() < 0.0);
+/// assert_eq!(composite.get(1), composite.get(2));
+/// ```
+pub fn add_macro_regime_features(
+    df: &DataFrame,
+    time_column: &str,
+    series: &[MacroSeries],
+    window: usize,
+) -> PolarsResult<DataFrame> {
+    if series.is_empty() {
+        return Err(PolarsError::ComputeError(
+            "Macro regime features require at least one macro series".into(),
+        ));
+    }
+    if window < 2 {
+        return Err(PolarsError::ComputeError(
+            "Macro z-score window must be at least 2".into(),
+        ));
+    }
+
+    let bar_times = parse_timestamp_column(df, time_column)?;
+    let mut result = df.clone();
+    let mut weighted_sum = vec![0.0; df.height()];
+    let mut weight_total = vec![0.0; df.height()];
+
+    for macro_series in series {
+        for col in [macro_series.time_column, macro_series.value_column] {
+            if !macro_series.data.schema().contains(col) {
+                return Err(PolarsError::ComputeError(
+                    format!(
+                        "Macro series '{}' requires '{col}' column",
+                        macro_series.name
+                    )
+                    .into(),
+                ));
+            }
+        }
+
+        let times = parse_timestamp_column(macro_series.data, macro_series.time_column)?;
+        let values = macro_series
+            .data
+            .column(macro_series.value_column)?
+            .cast(&DataType::Float64)?;
+        let values = values.f64()?;
+
+        let mut observations: Vec<_> = times
+            .into_iter()
+            .zip(values)
+            .filter_map(|(t, v)| Some((t?, v.filter(|v| !v.is_nan())?)))
+            .collect();
+        observations.sort_by_key(|(t, _)| *t);
+
+        let zscores = rolling_zscore(
+            &observations.iter().map(|(_, v)| *v).collect::<Vec<_>>(),
+            window,
+        );
+
+        // As-of join: latest observation at or before each bar
+        let aligned: Vec<f64> = bar_times
+            .iter()
+            .map(|bar_time| {
+                let Some(bar_time) = bar_time else {
+                    return f64::NAN;
+                };
+                match observations.partition_point(|(t, _)| t <= bar_time) {
+                    0 => f64::NAN,
+                    idx => zscores[idx - 1],
+                }
+            })
+            .collect();
+
+        for (i, &z) in aligned.iter().enumerate() {
+            if !z.is_nan() {
+                weighted_sum[i] += macro_series.weight * z;
+                weight_total[i] += macro_series.weight.abs();
+            }
+        }
+
+        result.with_column(Series::new(
+            format!("macro_{}_z", macro_series.name).into(),
+            aligned,
+        ))?;
+    }
+
+    let composite: Vec<f64> = weighted_sum
+        .iter()
+        .zip(&weight_total)
+        .map(|(&sum, &total)| if total > 0.0 { sum / total } else { f64::NAN })
+        .collect();
+    result.with_column(Series::new("macro_composite_z".into(), composite))?;
+
+    Ok(result)
+}
@@ -8,8 +8,19 @@
 //! - Swing trading momentum indicators
 //! - Short-term trend identification tools
 //! - Pattern recognition for multi-day setups
-//! - Market regime detection for daily timeframes
+//! - Market regime detection for daily timeframes from the Hurst exponent,
+//!   ADX and volatility ratio, optionally conditioned on z-scored macro
+//!   series (rates, DXY, VIX)
 
+mod macro_regime;
+mod regime;
+
+pub use macro_regime::{add_macro_regime_features, MacroSeries};
+pub use regime::{
+    calculate_market_regime, RegimeConfig, REGIME_RANGING, REGIME_TRANSITIONAL, REGIME_TRENDING,
+};
+
+use crate::indicators::oscillators::{calculate_rsi_pullback, RsiPullbackConfig};
 use polars::prelude::*;
 
 /// Calculate swing strength index
@@ -36,22 +47,83 @@ pub fn swing_strength_index(df: &DataFrame, _period: usize) -> Result<Series, Po
 /// Identifies whether the market is in a trending, ranging,
 /// or transitional regime for short-term trading.
 ///
+/// Runs [`calculate_market_regime`] with the default thresholds, a Hurst
+/// exponent and volatility-ratio baseline over `trend_period` bars and an
+/// ADX and ATR over `atr_period` bars. Use [`calculate_market_regime`]
+/// directly for the component columns or custom thresholds.
+///
 /// # Arguments
 ///
 /// * `df` - DataFrame with price data
-/// * `atr_period` - Period for ATR calculation (volatility)
-/// * `trend_period` - Period for trend calculation
+/// * `atr_period` - Period for the ADX and ATR calculations
+/// * `trend_period` - Period for the Hurst exponent (at least 16)
 ///
 /// # Returns
 ///
 /// * `Result<Series, PolarsError>` - Series with regime values (1 = trending, 0 = ranging, -1 = transitional)
 pub fn short_term_regime_detector(
     df: &DataFrame,
-    _atr_period: usize,
-    _trend_period: usize,
+    atr_period: usize,
+    trend_period: usize,
 ) -> Result<Series, PolarsError> {
-    // Placeholder implementation
-    let values = vec![0i32; df.height()];
+    let config = RegimeConfig {
+        hurst_window: trend_period,
+        adx_period: atr_period,
+        atr_period,
+        volatility_window: trend_period,
+        ..RegimeConfig::default()
+    };
+    let regime = calculate_market_regime(df, &config)?;
+    Ok(regime
+        .column("market_regime")?
+        .as_materialized_series()
+        .clone())
+}
+
+/// Detect short-term market regimes conditioned on macro context
+///
+/// Runs [`short_term_regime_detector`] and marks every bar where the macro
+/// composite z-score is at or below `-stress_threshold` as transitional, so
+/// strategies stand aside when the macro backdrop turns hostile.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with price data and a macro composite column, e.g. from
+///   [`add_macro_regime_features`]
+/// * `atr_period` - Period for ATR calculation (volatility)
+/// * `trend_period` - Period for trend calculation
+/// * `macro_column` - Column with the macro composite z-score (usually "macro_composite_z")
+/// * `stress_threshold` - Composite z-score magnitude treated as macro stress (e.g. 1.5)
+///
+/// # Returns
+///
+/// * `Result<Series, PolarsError>` - Series named "market_regime" (1 = trending, 0 = ranging, -1 = transitional)
+pub fn short_term_regime_detector_with_macro(
+    df: &DataFrame,
+    atr_period: usize,
+    trend_period: usize,
+    macro_column: &str,
+    stress_threshold: f64,
+) -> Result<Series, PolarsError> {
+    if !df.schema().contains(macro_column) {
+        return Err(PolarsError::ComputeError(
+            format!("Macro regime detection requires {macro_column} column").into(),
+        ));
+    }
+
+    let regime = short_term_regime_detector(df, atr_period, trend_period)?;
+    let composite = df.column(macro_column)?.f64()?;
+
+    let values: Vec<i32> = regime
+        .i32()?
+        .into_iter()
+        .zip(composite)
+        .map(|(r, z)| match z {
+            Some(z) if z <= -stress_threshold => -1,
+            _ => r.unwrap_or(0),
+        })
+        .collect();
+
     Ok(Series::new("market_regime".into(), values))
 }
 
@@ -137,6 +209,11 @@ pub fn find_swing_points(
 
 /// Generate mean reversion signals based on oversold/overbought conditions
 ///
+/// The 2-period RSI pullback ([`calculate_rsi_pullback`]) on "close": 1.0
+/// when the close is above its `trend_period` SMA and RSI(2) below
+/// `oversold_threshold`, -1.0 when it is below the SMA and RSI(2) above
+/// `100 - oversold_threshold`, 0.0 otherwise.
+///
 /// # Arguments
 ///
 /// * `df` - DataFrame with OHLC data
@@ -144,12 +221,19 @@ pub fn find_swing_points(
 /// * `oversold_threshold` - Threshold to identify oversold conditions
 pub fn mean_reversion_signals(
     df: &DataFrame,
-    _trend_period: usize,
-    _oversold_threshold: f64,
+    trend_period: usize,
+    oversold_threshold: f64,
 ) -> Result<Series, PolarsError> {
-    // Placeholder implementation
-    let values = vec![0.0; df.height()];
-    Ok(Series::new("mean_reversion_signals".into(), values))
+    let config = RsiPullbackConfig {
+        trend_period,
+        oversold: oversold_threshold,
+        overbought: 100.0 - oversold_threshold,
+        ..Default::default()
+    };
+    let signals = calculate_rsi_pullback(df, "close", &config)?;
+    Ok(signals
+        .cast(&DataType::Float64)?
+        .with_name("mean_reversion_signals".into()))
 }
 
 /// Detect chart patterns for swing trading
@@ -0,0 +1,160 @@
+use crate::indicators::stats::calculate_hurst;
+use crate::indicators::trend::calculate_adx;
+use crate::indicators::volatility::calculate_atr;
+use crate::util::dataframe_utils::{check_window_size, nan_values};
+use polars::prelude::*;
+
+/// Regime value for a trending market
+pub const REGIME_TRENDING: i32 = 1;
+/// Regime value for a ranging market
+pub const REGIME_RANGING: i32 = 0;
+/// Regime value for a transitional market
+pub const REGIME_TRANSITIONAL: i32 = -1;
+
+/// Parameters for [`calculate_market_regime`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegimeConfig {
+    /// Number of log returns in each Hurst exponent window (at least 16)
+    pub hurst_window: usize,
+    /// Hurst exponent at or above which price action counts as trending
+    pub hurst_trending: f64,
+    /// Hurst exponent at or below which price action counts as ranging
+    pub hurst_ranging: f64,
+    /// ADX window
+    pub adx_period: usize,
+    /// ADX at or above which the market counts as trending
+    pub adx_trending: f64,
+    /// ADX at or below which the market counts as ranging
+    pub adx_ranging: f64,
+    /// ATR window for the volatility ratio
+    pub atr_period: usize,
+    /// Number of bars of ATR averaged into the volatility ratio's baseline
+    pub volatility_window: usize,
+    /// Volatility ratio at or above which an unconfirmed trend is transitional
+    pub volatility_expansion: f64,
+}
+
+impl Default for RegimeConfig {
+    fn default() -> Self {
+        Self {
+            hurst_window: 100,
+            hurst_trending: 0.55,
+            hurst_ranging: 0.45,
+            adx_period: 14,
+            adx_trending: 25.0,
+            adx_ranging: 20.0,
+            atr_period: 14,
+            volatility_window: 50,
+            volatility_expansion: 1.5,
+        }
+    }
+}
+
+/// Classify each bar as trending, ranging or transitional
+///
+/// The Hurst exponent (see [`calculate_hurst`]) and the ADX each vote: a
+/// value at or above its trending threshold is a trend vote, one at or below
+/// its ranging threshold a range vote. More trend votes than range votes is
+/// trending, more range votes is ranging and a tie is transitional. The
+/// volatility ratio, ATR over its average across `volatility_window` bars,
+/// marks expansions: at or above `volatility_expansion` the bar is
+/// transitional unless both votes say trending. Bars where any component is
+/// still warming up are ranging (0).
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with high, low and close columns
+/// * `config` - Windows and thresholds
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with "hurst", "adx",
+/// "volatility_ratio" and "market_regime" (1 = trending, 0 = ranging,
+/// -1 = transitional) columns, one row per input row
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::short_term::{calculate_market_regime, RegimeConfig, REGIME_TRENDING};
+///
+/// // A steady climb with shallow pullbacks
+/// let close: Vec<f64> = (0..120)
+///     .map(|i| 100.0 + i as f64 + 2.0 * (i as f64 / 8.0).sin())
+///     .collect();
+/// let high: Vec<f64> = close.iter().map(|c| c + 0.5).collect();
+/// let low: Vec<f64> = close.iter().map(|c| c - 0.5).collect();
+/// let df = df! { "high" => high, "low" => low, "close" => close }.unwrap();
+///
+/// let config = RegimeConfig {
+///     hurst_window: 64,
+///     volatility_window: 20,
+///     ..RegimeConfig::default()
+/// };
+/// let regime = calculate_market_regime(&df, &config).unwrap();
+/// let labels = regime.column("market_regime").unwrap().i32().unwrap();
+/// assert_eq!(labels.get(119), Some(REGIME_TRENDING));
+/// ```
+pub fn calculate_market_regime(df: &DataFrame, config: &RegimeConfig) -> PolarsResult<DataFrame> {
+    check_window_size(df, config.hurst_window + 1, "Market regime")?;
+    if config.volatility_window == 0 {
+        return Err(PolarsError::ComputeError(
+            "Market regime volatility window must be positive".into(),
+        ));
+    }
+
+    let hurst = calculate_hurst(df, "close", config.hurst_window)?;
+    let adx = calculate_adx(df, config.adx_period)?;
+    let atr = calculate_atr(df, config.atr_period)?;
+
+    let atr = nan_values(atr.f64()?);
+    let mut volatility_ratio = vec![f64::NAN; df.height()];
+    for (i, ratio) in volatility_ratio.iter_mut().enumerate() {
+        let recent: Vec<f64> = atr[(i + 1).saturating_sub(config.volatility_window)..=i]
+            .iter()
+            .copied()
+            .filter(|v| !v.is_nan())
+            .collect();
+        let baseline = recent.iter().sum::<f64>() / recent.len() as f64;
+        if baseline > 0.0 {
+            *ratio = atr[i] / baseline;
+        }
+    }
+
+    let regime: Vec<i32> = hurst
+        .f64()?
+        .iter()
+        .zip(adx.f64()?.iter())
+        .zip(&volatility_ratio)
+        .map(|((hurst, adx), &ratio)| {
+            let (Some(hurst), Some(adx)) = (hurst, adx) else {
+                return REGIME_RANGING;
+            };
+            if hurst.is_nan() || adx.is_nan() || ratio.is_nan() {
+                return REGIME_RANGING;
+            }
+
+            let trend_votes =
+                (hurst >= config.hurst_trending) as i32 + (adx >= config.adx_trending) as i32;
+            let range_votes =
+                (hurst <= config.hurst_ranging) as i32 + (adx <= config.adx_ranging) as i32;
+
+            if ratio >= config.volatility_expansion && trend_votes < 2 {
+                REGIME_TRANSITIONAL
+            } else if trend_votes > range_votes {
+                REGIME_TRENDING
+            } else if range_votes > trend_votes {
+                REGIME_RANGING
+            } else {
+                REGIME_TRANSITIONAL
+            }
+        })
+        .collect();
+
+    DataFrame::new(vec![
+        hurst.with_name("hurst".into()).into(),
+        adx.with_name("adx".into()).into(),
+        Series::new("volatility_ratio".into(), volatility_ratio).into(),
+        Series::new("market_regime".into(), regime).into(),
+    ])
+}
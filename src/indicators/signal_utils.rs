@@ -0,0 +1,247 @@
+//! # Signal Utilities
+//!
+//! Pine Script style helpers for turning indicator columns into signals.
+//!
+//! - [`cross_over`] and [`cross_under`]: One column crossing another
+//! - [`bars_since`]: Bars since a condition was last true
+//! - [`highest_since`] and [`lowest_since`]: Extremes of a column since a
+//!   condition was last true
+//!
+//! Null and NaN values never produce a cross; a condition that is null
+//! counts as false.
+
+use crate::util::dataframe_utils::column_valid_values;
+use polars::prelude::*;
+
+/// Detect bars where `col_a` crosses above `col_b`
+///
+/// True when `col_a` is above `col_b` on a bar and was at or below it on the
+/// previous bar, like Pine Script's `ta.crossover`.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing both columns
+/// * `col_a` - Column that crosses
+/// * `col_b` - Column that is crossed
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a boolean Series named
+/// "{col_a}_cross_over_{col_b}"
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::signal_utils::cross_over;
+///
+/// let df = df! {
+///     "fast" => [1.0, 2.0, 3.0, 2.0, 4.0],
+///     "slow" => [2.0, 2.0, 2.5, 2.5, 2.5],
+/// }
+/// .unwrap();
+///
+/// let crosses = cross_over(&df, "fast", "slow").unwrap();
+/// let crosses: Vec<_> = crosses.bool().unwrap().into_no_null_iter().collect();
+/// assert_eq!(crosses, [false, false, true, false, true]);
+/// ```
+pub fn cross_over(df: &DataFrame, col_a: &str, col_b: &str) -> PolarsResult<Series> {
+    let flags = crosses(
+        &column_valid_values(df, col_a)?,
+        &column_valid_values(df, col_b)?,
+        true,
+    );
+    Ok(Series::new(
+        format!("{col_a}_cross_over_{col_b}").into(),
+        flags,
+    ))
+}
+
+/// Detect bars where `col_a` crosses below `col_b`
+///
+/// True when `col_a` is below `col_b` on a bar and was at or above it on the
+/// previous bar, like Pine Script's `ta.crossunder`.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing both columns
+/// * `col_a` - Column that crosses
+/// * `col_b` - Column that is crossed
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a boolean Series named
+/// "{col_a}_cross_under_{col_b}"
+pub fn cross_under(df: &DataFrame, col_a: &str, col_b: &str) -> PolarsResult<Series> {
+    let flags = crosses(
+        &column_valid_values(df, col_a)?,
+        &column_valid_values(df, col_b)?,
+        false,
+    );
+    Ok(Series::new(
+        format!("{col_a}_cross_under_{col_b}").into(),
+        flags,
+    ))
+}
+
+/// Count the bars since a condition was last true
+///
+/// Zero on bars where the condition holds, like Pine Script's
+/// `ta.barssince`.
+///
+/// # Arguments
+///
+/// * `condition` - Boolean Series, e.g. from [`cross_over`]
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a UInt32 Series named "bars_since",
+/// null before the condition is first true
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::signal_utils::bars_since;
+///
+/// let condition = Series::new("signal".into(), [false, true, false, false, true]);
+/// let bars = bars_since(&condition).unwrap();
+/// let bars: Vec<_> = bars.u32().unwrap().into_iter().collect();
+/// assert_eq!(bars, [None, Some(0), Some(1), Some(2), Some(0)]);
+/// ```
+pub fn bars_since(condition: &Series) -> PolarsResult<Series> {
+    let mut last: Option<usize> = None;
+    let bars: Vec<Option<u32>> = condition_values(condition)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, holds)| {
+            if holds {
+                last = Some(i);
+            }
+            last.map(|l| (i - l) as u32)
+        })
+        .collect();
+    Ok(Series::new("bars_since".into(), bars))
+}
+
+/// Track the highest value of a column since a condition was last true
+///
+/// The bar where the condition holds starts a new range, so its own value is
+/// the first in it.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the column
+/// * `column` - Column to track
+/// * `condition` - Boolean Series with one value per row of `df`
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named "{column}_highest_since",
+/// NaN before the condition is first true
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::signal_utils::highest_since;
+///
+/// let df = df! { "high" => [5.0, 3.0, 4.0, 2.0, 1.0] }.unwrap();
+/// let entry = Series::new("entry".into(), [false, true, false, false, true]);
+///
+/// let highest = highest_since(&df, "high", &entry).unwrap();
+/// let highest: Vec<_> = highest.f64().unwrap().into_no_null_iter().skip(1).collect();
+/// assert_eq!(highest, [3.0, 4.0, 4.0, 1.0]);
+/// ```
+pub fn highest_since(df: &DataFrame, column: &str, condition: &Series) -> PolarsResult<Series> {
+    extreme_since(df, column, condition, "highest_since", f64::max)
+}
+
+/// Track the lowest value of a column since a condition was last true
+///
+/// The bar where the condition holds starts a new range, so its own value is
+/// the first in it.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the column
+/// * `column` - Column to track
+/// * `condition` - Boolean Series with one value per row of `df`
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named "{column}_lowest_since",
+/// NaN before the condition is first true
+pub fn lowest_since(df: &DataFrame, column: &str, condition: &Series) -> PolarsResult<Series> {
+    extreme_since(df, column, condition, "lowest_since", f64::min)
+}
+
+/// Flags where `lhs` crosses `rhs` upward (`above`) or downward
+pub(crate) fn crosses(lhs: &[Option<f64>], rhs: &[Option<f64>], above: bool) -> Vec<bool> {
+    let pair = |i: usize| match (lhs[i], rhs[i]) {
+        (Some(l), Some(r)) => Some((l, r)),
+        _ => None,
+    };
+    (0..lhs.len().min(rhs.len()))
+        .map(|i| {
+            let (Some((l, r)), Some((pl, pr))) = (pair(i), i.checked_sub(1).and_then(pair)) else {
+                return false;
+            };
+            if above {
+                pl <= pr && l > r
+            } else {
+                pl >= pr && l < r
+            }
+        })
+        .collect()
+}
+
+fn extreme_since(
+    df: &DataFrame,
+    column: &str,
+    condition: &Series,
+    suffix: &str,
+    pick: fn(f64, f64) -> f64,
+) -> PolarsResult<Series> {
+    let values = column_valid_values(df, column)?;
+    let condition = condition_values(condition)?;
+    if condition.len() != values.len() {
+        return Err(PolarsError::ComputeError(
+            format!(
+                "Condition has {} values for {} rows of '{column}'",
+                condition.len(),
+                values.len()
+            )
+            .into(),
+        ));
+    }
+
+    let mut extreme: Option<f64> = None;
+    let result: Vec<f64> = values
+        .iter()
+        .zip(condition)
+        .map(|(value, holds)| {
+            if holds {
+                extreme = Some(value.unwrap_or(f64::NAN));
+            } else if let (Some(current), Some(value)) = (extreme, value) {
+                // A range started on a missing value takes the first valid one
+                extreme = Some(if current.is_nan() {
+                    *value
+                } else {
+                    pick(current, *value)
+                });
+            }
+            extreme.unwrap_or(f64::NAN)
+        })
+        .collect();
+    Ok(Series::new(format!("{column}_{suffix}").into(), result))
+}
+
+/// A boolean Series as flags with nulls as false
+fn condition_values(condition: &Series) -> PolarsResult<Vec<bool>> {
+    Ok(condition
+        .bool()?
+        .iter()
+        .map(|v| v.unwrap_or(false))
+        .collect())
+}
@@ -0,0 +1,269 @@
+use crate::indicators::cycle::calculate_ht_trendmode;
+use crate::indicators::moving_averages::{calculate_ema, calculate_sma, calculate_wma};
+use crate::indicators::oscillators::{calculate_macd, calculate_rsi};
+use crate::indicators::state_space::calculate_kalman_smoother;
+use crate::indicators::trend::{calculate_adx, calculate_choppiness_index};
+use crate::indicators::volatility::{
+    calculate_atr, calculate_bollinger_bands, calculate_keltner_trend,
+};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+fn default_close() -> String {
+    "close".to_string()
+}
+
+/// An indicator with its parameters and output column name
+///
+/// Single-output indicators produce a column called `name`; MACD adds
+/// "<name>_signal" and "<name>_histogram", Bollinger Bands produce
+/// "<name>_middle", "<name>_upper" and "<name>_lower", and the Kalman
+/// smoother adds "<name>_slope".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IndicatorSpec {
+    /// Simple moving average
+    Sma {
+        name: String,
+        period: usize,
+        #[serde(default = "default_close")]
+        column: String,
+    },
+    /// Exponential moving average
+    Ema {
+        name: String,
+        period: usize,
+        #[serde(default = "default_close")]
+        column: String,
+    },
+    /// Weighted moving average
+    Wma {
+        name: String,
+        period: usize,
+        #[serde(default = "default_close")]
+        column: String,
+    },
+    /// Relative Strength Index
+    Rsi {
+        name: String,
+        period: usize,
+        #[serde(default = "default_close")]
+        column: String,
+    },
+    /// MACD line, signal line and histogram
+    Macd {
+        name: String,
+        fast_period: usize,
+        slow_period: usize,
+        signal_period: usize,
+        #[serde(default = "default_close")]
+        column: String,
+    },
+    /// Bollinger Bands
+    BollingerBands {
+        name: String,
+        period: usize,
+        num_std: f64,
+        #[serde(default = "default_close")]
+        column: String,
+    },
+    /// Average True Range (needs "high", "low" and "close")
+    Atr { name: String, period: usize },
+    /// Average Directional Index (needs "high", "low" and "close")
+    Adx { name: String, period: usize },
+    /// Choppiness Index, 0 to 100 (needs "high", "low" and "close")
+    ///
+    /// Use it as an entry filter that sits out choppy markets, e.g. a
+    /// `below` condition against 61.8 next to the entry signal.
+    ///
+    /// ```
+    /// use rustalib::indicators::IndicatorSpec;
+    ///
+    /// let spec: IndicatorSpec =
+    ///     serde_json::from_str(r#"{ "type": "choppiness", "name": "chop_14", "period": 14 }"#)
+    ///         .unwrap();
+    /// assert_eq!(spec.input_columns(), ["high", "low", "close"]);
+    /// ```
+    Choppiness { name: String, period: usize },
+    /// Keltner Channel trend filter: 1 after a close above the upper band,
+    /// -1 after a close below the lower band, 0 once the close crosses back
+    /// through the middle (needs "high", "low" and "close")
+    KeltnerTrend {
+        name: String,
+        period: usize,
+        multiplier: f64,
+    },
+    /// Kalman filter price smoother: the filtered level and its slope per bar
+    KalmanSmoother {
+        name: String,
+        process_variance: f64,
+        measurement_variance: f64,
+        #[serde(default = "default_close")]
+        column: String,
+    },
+    /// Hilbert Transform trend mode: 1 while trending, 0 while cycling
+    ///
+    /// Use it as a regime filter, e.g. an `above` condition against 0.5 to
+    /// take trend-following entries only in trends.
+    ///
+    /// ```
+    /// use rustalib::indicators::IndicatorSpec;
+    ///
+    /// let spec: IndicatorSpec =
+    ///     serde_json::from_str(r#"{ "type": "ht_trend_mode", "name": "trend_mode" }"#).unwrap();
+    /// assert_eq!(spec.input_columns(), ["close"]);
+    /// ```
+    HtTrendMode {
+        name: String,
+        #[serde(default = "default_close")]
+        column: String,
+    },
+}
+
+impl IndicatorSpec {
+    /// Base name of the indicator's output columns
+    pub fn name(&self) -> &str {
+        match self {
+            IndicatorSpec::Sma { name, .. }
+            | IndicatorSpec::Ema { name, .. }
+            | IndicatorSpec::Wma { name, .. }
+            | IndicatorSpec::Rsi { name, .. }
+            | IndicatorSpec::Macd { name, .. }
+            | IndicatorSpec::BollingerBands { name, .. }
+            | IndicatorSpec::Atr { name, .. }
+            | IndicatorSpec::Adx { name, .. }
+            | IndicatorSpec::Choppiness { name, .. }
+            | IndicatorSpec::KeltnerTrend { name, .. }
+            | IndicatorSpec::KalmanSmoother { name, .. }
+            | IndicatorSpec::HtTrendMode { name, .. } => name,
+        }
+    }
+
+    /// Names of the columns this indicator produces
+    pub fn output_names(&self) -> Vec<String> {
+        let name = self.name();
+        match self {
+            IndicatorSpec::Macd { .. } => vec![
+                name.to_string(),
+                format!("{name}_signal"),
+                format!("{name}_histogram"),
+            ],
+            IndicatorSpec::BollingerBands { .. } => vec![
+                format!("{name}_middle"),
+                format!("{name}_upper"),
+                format!("{name}_lower"),
+            ],
+            IndicatorSpec::KalmanSmoother { .. } => vec![name.to_string(), format!("{name}_slope")],
+            _ => vec![name.to_string()],
+        }
+    }
+
+    /// Standard names of the input columns the indicator reads
+    pub fn input_columns(&self) -> Vec<&str> {
+        match self {
+            IndicatorSpec::Sma { column, .. }
+            | IndicatorSpec::Ema { column, .. }
+            | IndicatorSpec::Wma { column, .. }
+            | IndicatorSpec::Rsi { column, .. }
+            | IndicatorSpec::Macd { column, .. }
+            | IndicatorSpec::BollingerBands { column, .. }
+            | IndicatorSpec::KalmanSmoother { column, .. }
+            | IndicatorSpec::HtTrendMode { column, .. } => vec![column],
+            IndicatorSpec::Atr { .. }
+            | IndicatorSpec::Adx { .. }
+            | IndicatorSpec::Choppiness { .. }
+            | IndicatorSpec::KeltnerTrend { .. } => vec!["high", "low", "close"],
+        }
+    }
+
+    /// Window lengths of the indicator
+    pub(crate) fn periods(&self) -> Vec<usize> {
+        match self {
+            IndicatorSpec::Sma { period, .. }
+            | IndicatorSpec::Ema { period, .. }
+            | IndicatorSpec::Wma { period, .. }
+            | IndicatorSpec::Rsi { period, .. }
+            | IndicatorSpec::BollingerBands { period, .. }
+            | IndicatorSpec::Atr { period, .. }
+            | IndicatorSpec::Adx { period, .. }
+            | IndicatorSpec::Choppiness { period, .. }
+            | IndicatorSpec::KeltnerTrend { period, .. } => vec![*period],
+            IndicatorSpec::Macd {
+                fast_period,
+                slow_period,
+                signal_period,
+                ..
+            } => vec![*fast_period, *slow_period, *signal_period],
+            IndicatorSpec::KalmanSmoother { .. } | IndicatorSpec::HtTrendMode { .. } => Vec::new(),
+        }
+    }
+
+    /// Calculate the indicator's columns
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - DataFrame with the input columns the indicator needs
+    ///
+    /// # Returns
+    ///
+    /// Returns a PolarsResult containing one Series per name in
+    /// [`output_names`](Self::output_names), in the same order
+    pub fn calculate(&self, df: &DataFrame) -> PolarsResult<Vec<Series>> {
+        let series = match self {
+            IndicatorSpec::Sma { period, column, .. } => vec![calculate_sma(df, column, *period)?],
+            IndicatorSpec::Ema { period, column, .. } => vec![calculate_ema(df, column, *period)?],
+            IndicatorSpec::Wma { period, column, .. } => vec![calculate_wma(df, column, *period)?],
+            IndicatorSpec::Rsi { period, column, .. } => vec![calculate_rsi(df, *period, column)?],
+            IndicatorSpec::Macd {
+                fast_period,
+                slow_period,
+                signal_period,
+                column,
+                ..
+            } => {
+                let macd = calculate_macd(df, *fast_period, *slow_period, *signal_period, column)?;
+                vec![macd.macd, macd.signal, macd.histogram]
+            }
+            IndicatorSpec::BollingerBands {
+                period,
+                num_std,
+                column,
+                ..
+            } => {
+                let bands = calculate_bollinger_bands(df, *period, *num_std, column)?;
+                vec![bands.middle, bands.upper, bands.lower]
+            }
+            IndicatorSpec::Atr { period, .. } => vec![calculate_atr(df, *period)?],
+            IndicatorSpec::Adx { period, .. } => vec![calculate_adx(df, *period)?],
+            IndicatorSpec::Choppiness { period, .. } => {
+                vec![calculate_choppiness_index(df, *period)?]
+            }
+            IndicatorSpec::KeltnerTrend {
+                period, multiplier, ..
+            } => vec![calculate_keltner_trend(df, *period, *multiplier)?],
+            IndicatorSpec::KalmanSmoother {
+                process_variance,
+                measurement_variance,
+                column,
+                ..
+            } => {
+                let (level, slope) = calculate_kalman_smoother(
+                    df,
+                    column,
+                    *process_variance,
+                    *measurement_variance,
+                )?;
+                vec![level, slope]
+            }
+            IndicatorSpec::HtTrendMode { column, .. } => {
+                vec![calculate_ht_trendmode(df, column)?]
+            }
+        };
+
+        Ok(series
+            .into_iter()
+            .zip(self.output_names())
+            .map(|(s, name)| s.with_name(name.into()))
+            .collect())
+    }
+}
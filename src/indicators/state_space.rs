@@ -0,0 +1,244 @@
+//! # State-Space Models
+//!
+//! Kalman filters that track hidden quantities from noisy prices: the level
+//! and slope of a price for trend following, and the time-varying beta of
+//! one price on another for dynamic hedge ratios. Both are causal filters:
+//! the value at a bar uses that bar and the ones before it, never later
+//! bars, so they can be traded on without look-ahead.
+
+use crate::util::dataframe_utils::column_nan_values;
+use polars::prelude::*;
+
+/// Smooths a price with a local linear trend Kalman filter
+///
+/// The price is modelled as a hidden level that moves by a hidden slope
+/// each bar, observed with noise. `process_variance` is the variance of the
+/// random acceleration driving the level and slope, `measurement_variance`
+/// the variance of the observation noise; only their ratio matters for the
+/// shape of the output. A small ratio gives a smooth, slow line, a large
+/// one follows the price closely. Unlike a moving average the slope comes
+/// with the level, so the filter lags little behind steady trends.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price column
+/// * `column` - Price column; missing values are skipped, carrying the
+///   predicted level forward
+/// * `process_variance` - Variance of the random acceleration (typically
+///   0.01 of `measurement_variance`)
+/// * `measurement_variance` - Variance of the observation noise
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing `(level, slope)` Series named
+/// "kalman_level" and "kalman_slope" (price change per bar), NaN before the
+/// first price
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::state_space::calculate_kalman_smoother;
+///
+/// // A steady uptrend of 0.5 per bar with alternating noise
+/// let close: Vec<f64> = (0..60).map(|i| 100.0 + 0.5 * i as f64 + if i % 2 == 0 { 0.4 } else { -0.4 }).collect();
+/// let df = df! { "close" => close }.unwrap();
+///
+/// let (level, slope) = calculate_kalman_smoother(&df, "close", 0.01, 1.0).unwrap();
+/// assert!((level.f64().unwrap().get(59).unwrap() - 129.5).abs() < 0.3);
+/// assert!((slope.f64().unwrap().get(59).unwrap() - 0.5).abs() < 0.05);
+/// ```
+pub fn calculate_kalman_smoother(
+    df: &DataFrame,
+    column: &str,
+    process_variance: f64,
+    measurement_variance: f64,
+) -> PolarsResult<(Series, Series)> {
+    check_variances(process_variance, measurement_variance, "Kalman smoother")?;
+    let prices = df.column(column)?.cast(&DataType::Float64)?;
+    let prices = prices.f64()?;
+
+    // Constant-velocity model with white-noise acceleration
+    let q = process_variance;
+    let noise = [[q / 3.0, q / 2.0], [q / 2.0, q]];
+    let r = measurement_variance;
+
+    let mut level = Vec::with_capacity(df.height());
+    let mut slope = Vec::with_capacity(df.height());
+    let mut state: Option<([f64; 2], [[f64; 2]; 2])> = None;
+
+    for price in prices.iter() {
+        let price = price.filter(|p| !p.is_nan());
+        state = match (state, price) {
+            (None, None) => None,
+            (None, Some(price)) => Some(([price, 0.0], [[r, 0.0], [0.0, r]])),
+            (Some((x, p)), price) => {
+                // Predict: level += slope
+                let x = [x[0] + x[1], x[1]];
+                let p = [
+                    [
+                        p[0][0] + p[0][1] + p[1][0] + p[1][1] + noise[0][0],
+                        p[0][1] + p[1][1] + noise[0][1],
+                    ],
+                    [p[1][0] + p[1][1] + noise[1][0], p[1][1] + noise[1][1]],
+                ];
+                match price {
+                    None => Some((x, p)),
+                    Some(price) => {
+                        let variance = p[0][0] + r;
+                        let gain = [p[0][0] / variance, p[1][0] / variance];
+                        let error = price - x[0];
+                        Some((
+                            [x[0] + gain[0] * error, x[1] + gain[1] * error],
+                            [
+                                [p[0][0] - gain[0] * p[0][0], p[0][1] - gain[0] * p[0][1]],
+                                [p[1][0] - gain[1] * p[0][0], p[1][1] - gain[1] * p[0][1]],
+                            ],
+                        ))
+                    }
+                }
+            }
+        };
+        let (l, s) = state.map_or((f64::NAN, f64::NAN), |(x, _)| (x[0], x[1]));
+        level.push(l);
+        slope.push(s);
+    }
+
+    Ok((
+        Series::new("kalman_level".into(), level),
+        Series::new("kalman_slope".into(), slope),
+    ))
+}
+
+/// Estimates a time-varying regression of one price on another with a
+/// Kalman filter
+///
+/// Models `y = beta * x + alpha + noise` with beta and alpha following
+/// random walks, the dynamic hedge ratio used in pairs trading. `delta`
+/// sets how fast the coefficients may drift: their random walks have
+/// variance `delta / (1 - delta)`, so values near 0 give a nearly constant
+/// beta and larger values adapt faster.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing both price columns
+/// * `y_column` - Dependent price column
+/// * `x_column` - Independent price column; bars where either is missing
+///   carry the previous estimate
+/// * `delta` - Drift of the coefficients, in (0, 1) (typically 1e-4)
+/// * `observation_variance` - Variance of the regression noise, in price
+///   units squared (typically 1e-3)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing `(beta, alpha)` Series named
+/// "kalman_beta" and "kalman_alpha", updated with each bar's prices, NaN
+/// before the first bar with both prices
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::state_space::calculate_kalman_regression;
+///
+/// // y = 1.5 * x until bar 100, then y = 2.5 * x
+/// let x: Vec<f64> = (0..200).map(|i| 20.0 + 5.0 * (i as f64 / 10.0).sin()).collect();
+/// let y: Vec<f64> = x.iter().enumerate().map(|(i, x)| if i < 100 { 1.5 } else { 2.5 } * x).collect();
+/// let df = df! { "y" => y, "x" => x }.unwrap();
+///
+/// let (beta, _) = calculate_kalman_regression(&df, "y", "x", 1e-3, 1e-3).unwrap();
+/// let beta = beta.f64().unwrap();
+/// assert!((beta.get(99).unwrap() - 1.5).abs() < 0.02);
+/// assert!((beta.get(199).unwrap() - 2.5).abs() < 0.02);
+/// ```
+pub fn calculate_kalman_regression(
+    df: &DataFrame,
+    y_column: &str,
+    x_column: &str,
+    delta: f64,
+    observation_variance: f64,
+) -> PolarsResult<(Series, Series)> {
+    let column = |name: &str| column_nan_values(df, name);
+    let states = kalman_regression(
+        &column(y_column)?,
+        &column(x_column)?,
+        delta,
+        observation_variance,
+    )?;
+
+    Ok((
+        Series::new(
+            "kalman_beta".into(),
+            states.iter().map(|s| s[0]).collect::<Vec<_>>(),
+        ),
+        Series::new(
+            "kalman_alpha".into(),
+            states.iter().map(|s| s[1]).collect::<Vec<_>>(),
+        ),
+    ))
+}
+
+/// `[beta, alpha]` after updating with each bar of `y` and `x`; NaN before
+/// the first bar with both values
+pub(crate) fn kalman_regression(
+    y: &[f64],
+    x: &[f64],
+    delta: f64,
+    observation_variance: f64,
+) -> PolarsResult<Vec<[f64; 2]>> {
+    if delta <= 0.0 || delta >= 1.0 {
+        return Err(PolarsError::ComputeError(
+            "Kalman regression delta must lie in (0, 1)".into(),
+        ));
+    }
+    check_variances(delta, observation_variance, "Kalman regression")?;
+    let transition = delta / (1.0 - delta);
+
+    // State [beta, alpha] and its covariance, zero before any observation
+    let mut state = [0.0, 0.0];
+    let mut cov = [[0.0, 0.0], [0.0, 0.0]];
+    let mut started = false;
+    let mut states = Vec::with_capacity(y.len());
+
+    for (&y, &x) in y.iter().zip(x) {
+        let prior = [
+            [cov[0][0] + transition, cov[0][1]],
+            [cov[1][0], cov[1][1] + transition],
+        ];
+        if y.is_nan() || x.is_nan() {
+            if started {
+                cov = prior;
+            }
+            states.push(if started { state } else { [f64::NAN; 2] });
+            continue;
+        }
+        started = true;
+
+        let h = [x, 1.0];
+        let ph = [
+            prior[0][0] * h[0] + prior[0][1] * h[1],
+            prior[1][0] * h[0] + prior[1][1] * h[1],
+        ];
+        let variance = h[0] * ph[0] + h[1] * ph[1] + observation_variance;
+        let error = y - (h[0] * state[0] + h[1] * state[1]);
+        let gain = [ph[0] / variance, ph[1] / variance];
+        state = [state[0] + gain[0] * error, state[1] + gain[1] * error];
+        cov = [
+            [prior[0][0] - gain[0] * ph[0], prior[0][1] - gain[0] * ph[1]],
+            [prior[1][0] - gain[1] * ph[0], prior[1][1] - gain[1] * ph[1]],
+        ];
+        states.push(state);
+    }
+
+    Ok(states)
+}
+
+fn check_variances(process: f64, measurement: f64, indicator: &str) -> PolarsResult<()> {
+    if process > 0.0 && measurement > 0.0 {
+        Ok(())
+    } else {
+        Err(PolarsError::ComputeError(
+            format!("{indicator} variances must be positive").into(),
+        ))
+    }
+}
@@ -0,0 +1,453 @@
+use crate::indicators::options::pricing::norm_cdf;
+use crate::util::dataframe_utils::column_valid_values;
+use polars::prelude::*;
+
+/// How [`adf_test`] chooses the number of lagged differences in its
+/// regression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdfLags {
+    /// Use exactly this many lags
+    Fixed(usize),
+    /// Pick the count from 0 up to this maximum with the lowest Akaike
+    /// information criterion; `12 * (n / 100)^(1/4)` is the usual maximum
+    Aic(usize),
+}
+
+/// Outcome of an augmented Dickey-Fuller test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdfResult {
+    /// t-statistic of the lagged level; more negative is more stationary
+    pub statistic: f64,
+    /// MacKinnon (1994) approximate p-value of the statistic
+    pub p_value: f64,
+    /// MacKinnon (2010) critical values at 1%, 5% and 10% for `n_obs`
+    pub critical_values: [f64; 3],
+    /// Lagged differences in the regression
+    pub lags: usize,
+    /// Observations in the regression
+    pub n_obs: usize,
+}
+
+impl AdfResult {
+    /// Whether the unit root is rejected at `significance` (e.g. 0.05)
+    pub fn is_stationary(&self, significance: f64) -> bool {
+        self.p_value < significance
+    }
+}
+
+/// Outcome of an Engle-Granger cointegration test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CointegrationResult {
+    /// ADF t-statistic of the regression residuals
+    pub statistic: f64,
+    /// MacKinnon (1994) approximate p-value for two variables
+    pub p_value: f64,
+    /// MacKinnon (2010) critical values at 1%, 5% and 10% for `n_obs`
+    pub critical_values: [f64; 3],
+    /// Lagged differences in the residual ADF regression
+    pub lags: usize,
+    /// Observations in the residual ADF regression
+    pub n_obs: usize,
+    /// Slope of the regression of the first series on the second
+    pub hedge_ratio: f64,
+    /// Intercept of the regression of the first series on the second
+    pub intercept: f64,
+    /// Half-life of mean reversion of the residual spread in bars (see
+    /// [`estimate_half_life`]), NaN when it does not revert
+    pub half_life: f64,
+}
+
+impl CointegrationResult {
+    /// Whether no cointegration is rejected at `significance` (e.g. 0.05)
+    pub fn is_cointegrated(&self, significance: f64) -> bool {
+        self.p_value < significance
+    }
+}
+
+/// Augmented Dickey-Fuller test for a unit root
+///
+/// Regresses each change of the series on a constant, the previous level
+/// and `lags` previous changes. A series with a unit root (a random walk)
+/// has a level coefficient of zero; a strongly negative t-statistic rejects
+/// the unit root in favor of a stationary, mean-reverting series.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the data
+/// * `column` - Column to test; rows with a missing value are skipped
+/// * `lags` - Number of lagged differences, or how to choose it
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`AdfResult`], or an error when
+/// there are too few values for the regression
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stats::{adf_test, AdfLags};
+///
+/// let mut seed = 7u64;
+/// let mut noise = move || {
+///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+///     (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+/// };
+/// let (mut walk, mut reverting) = (vec![0.0], vec![0.0]);
+/// for i in 1..250 {
+///     let shock = noise();
+///     walk.push(walk[i - 1] + shock);
+///     reverting.push(0.5 * reverting[i - 1] + shock);
+/// }
+/// let df = df! { "walk" => walk, "reverting" => reverting }.unwrap();
+///
+/// let result = adf_test(&df, "reverting", AdfLags::Aic(15)).unwrap();
+/// assert!(result.statistic < result.critical_values[0]);
+/// assert!(result.is_stationary(0.01));
+///
+/// let result = adf_test(&df, "walk", AdfLags::Fixed(1)).unwrap();
+/// assert!(!result.is_stationary(0.05));
+/// ```
+pub fn adf_test(df: &DataFrame, column: &str, lags: AdfLags) -> PolarsResult<AdfResult> {
+    let values: Vec<f64> = column_valid_values(df, column)?
+        .into_iter()
+        .flatten()
+        .collect();
+    let (statistic, lags, n_obs) = adf_statistic(&values, lags, true)?;
+    Ok(AdfResult {
+        statistic,
+        p_value: mackinnon_p_value(statistic, 1),
+        critical_values: mackinnon_critical_values(1, n_obs),
+        lags,
+        n_obs,
+    })
+}
+
+/// Engle-Granger two-step cointegration test
+///
+/// Regresses the first series on the second with a constant, then runs an
+/// ADF test without a constant on the residuals. Cointegrated series have
+/// stationary residuals, so their spread at the estimated hedge ratio
+/// reverts to its mean: the basis of a pairs trade. The statistic is
+/// compared with MacKinnon's tables for two variables, which are stricter
+/// than the plain ADF ones since the hedge ratio is estimated.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the data
+/// * `y_column` - First price series (the dependent variable)
+/// * `x_column` - Second price series; rows where either is missing are skipped
+/// * `lags` - Number of lagged differences in the residual ADF regression,
+///   or how to choose it
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`CointegrationResult`], or an
+/// error when there are too few values or the second series is constant
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stats::{engle_granger_test, AdfLags};
+///
+/// let mut seed = 11u64;
+/// let mut noise = move || {
+///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+///     (seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+/// };
+/// // b is a random walk, a = 1.5 * b + 10 plus a mean-reverting spread
+/// let (mut b, mut spread) = (vec![50.0], vec![0.0]);
+/// for i in 1..250 {
+///     b.push(b[i - 1] + noise());
+///     spread.push(0.6 * spread[i - 1] + 0.5 * noise());
+/// }
+/// let a: Vec<f64> = b.iter().zip(&spread).map(|(b, s)| 1.5 * b + 10.0 + s).collect();
+/// let df = df! { "a" => a, "b" => b }.unwrap();
+///
+/// let result = engle_granger_test(&df, "a", "b", AdfLags::Aic(10)).unwrap();
+/// assert!(result.is_cointegrated(0.01));
+/// assert!((result.hedge_ratio - 1.5).abs() < 0.05);
+/// assert!(result.half_life < 5.0);
+/// ```
+pub fn engle_granger_test(
+    df: &DataFrame,
+    y_column: &str,
+    x_column: &str,
+    lags: AdfLags,
+) -> PolarsResult<CointegrationResult> {
+    let (y, x): (Vec<f64>, Vec<f64>) = column_valid_values(df, y_column)?
+        .into_iter()
+        .zip(column_valid_values(df, x_column)?)
+        .filter_map(|(y, x)| Some((y?, x?)))
+        .unzip();
+
+    let rows: Vec<Vec<f64>> = x.iter().map(|&x| vec![1.0, x]).collect();
+    let fit = ols(&rows, &y).ok_or_else(|| {
+        PolarsError::ComputeError(
+            format!("Engle-Granger test requires a non-constant {x_column} column").into(),
+        )
+    })?;
+    let (intercept, hedge_ratio) = (fit.coefficients[0], fit.coefficients[1]);
+    let residuals: Vec<f64> = y
+        .iter()
+        .zip(&x)
+        .map(|(y, x)| y - intercept - hedge_ratio * x)
+        .collect();
+
+    let (statistic, lags, n_obs) = adf_statistic(&residuals, lags, false)?;
+    Ok(CointegrationResult {
+        statistic,
+        p_value: mackinnon_p_value(statistic, 2),
+        critical_values: mackinnon_critical_values(2, n_obs),
+        lags,
+        n_obs,
+        hedge_ratio,
+        intercept,
+        half_life: estimate_half_life(&residuals),
+    })
+}
+
+/// Estimates the half-life of mean reversion of a spread
+///
+/// Regresses each change of the spread on its previous value. For a
+/// mean-reverting spread the slope `lambda` is negative and deviations
+/// decay by half in `-ln(2) / lambda` bars.
+///
+/// # Arguments
+///
+/// * `spread` - Spread values in time order
+///
+/// # Returns
+///
+/// Returns the half-life in bars, or NaN when the spread shows no mean
+/// reversion, has fewer than three values, or contains NaN
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::stats::estimate_half_life;
+///
+/// // Deviations shrink by 20% per bar
+/// let spread: Vec<f64> = (0..30).map(|i| 0.8f64.powi(i)).collect();
+/// let half_life = estimate_half_life(&spread);
+/// assert!((half_life - 2.0_f64.ln() / 0.2).abs() < 1e-9);
+///
+/// let trending: Vec<f64> = (0..30).map(|i| 1.1f64.powi(i)).collect();
+/// assert!(estimate_half_life(&trending).is_nan());
+/// ```
+pub fn estimate_half_life(spread: &[f64]) -> f64 {
+    if spread.len() < 3 || spread.iter().any(|s| s.is_nan()) {
+        return f64::NAN;
+    }
+    let n = (spread.len() - 1) as f64;
+    let lagged = &spread[..spread.len() - 1];
+    let mean_x = lagged.iter().sum::<f64>() / n;
+    let mean_dy = (spread[spread.len() - 1] - spread[0]) / n;
+    let (mut sxy, mut sxx) = (0.0, 0.0);
+    for (x, next) in lagged.iter().zip(&spread[1..]) {
+        sxy += (x - mean_x) * (next - x - mean_dy);
+        sxx += (x - mean_x).powi(2);
+    }
+    let lambda = sxy / sxx;
+    if sxx > 0.0 && lambda < 0.0 {
+        -std::f64::consts::LN_2 / lambda
+    } else {
+        f64::NAN
+    }
+}
+
+/// ADF t-statistic, lag count and observations of `values`
+fn adf_statistic(
+    values: &[f64],
+    lags: AdfLags,
+    constant: bool,
+) -> PolarsResult<(f64, usize, usize)> {
+    let diffs: Vec<f64> = values.windows(2).map(|w| w[1] - w[0]).collect();
+
+    // Regressors of the change at diff index t: level before it, `lags`
+    // earlier changes and the constant
+    let design = |lags: usize, start: usize| -> (Vec<Vec<f64>>, Vec<f64>) {
+        (start..diffs.len())
+            .map(|t| {
+                let mut row = vec![values[t]];
+                row.extend((1..=lags).map(|j| diffs[t - j]));
+                if constant {
+                    row.push(1.0);
+                }
+                (row, diffs[t])
+            })
+            .unzip()
+    };
+    let too_short = || {
+        PolarsError::ComputeError(
+            format!(
+                "ADF test has too few values ({}) for its regression",
+                values.len()
+            )
+            .into(),
+        )
+    };
+
+    let lags = match lags {
+        AdfLags::Fixed(lags) => lags,
+        AdfLags::Aic(max_lags) => {
+            if diffs.len() <= max_lags {
+                return Err(too_short());
+            }
+            // Compare every lag count on the sample of the longest
+            let mut best = (0, f64::INFINITY);
+            for lags in 0..=max_lags {
+                let (rows, y) = design(lags, max_lags);
+                let Some(fit) = ols(&rows, &y) else { continue };
+                let n = y.len() as f64;
+                let aic = n * (fit.ssr / n).ln() + 2.0 * rows[0].len() as f64;
+                if aic < best.1 {
+                    best = (lags, aic);
+                }
+            }
+            best.0
+        }
+    };
+    if diffs.len() <= lags {
+        return Err(too_short());
+    }
+
+    let (rows, y) = design(lags, lags);
+    let fit = ols(&rows, &y).ok_or_else(too_short)?;
+    Ok((fit.coefficients[0] / fit.standard_errors[0], lags, y.len()))
+}
+
+/// Least-squares fit of `y` on the regressor rows
+struct OlsFit {
+    coefficients: Vec<f64>,
+    standard_errors: Vec<f64>,
+    ssr: f64,
+}
+
+/// Ordinary least squares; None when the regressors are collinear or there
+/// are no residual degrees of freedom
+fn ols(rows: &[Vec<f64>], y: &[f64]) -> Option<OlsFit> {
+    let k = rows.first()?.len();
+    if y.len() <= k {
+        return None;
+    }
+
+    // Invert X'X by Gauss-Jordan elimination on [X'X | I]
+    let mut a = vec![vec![0.0; 2 * k]; k];
+    let mut xty = vec![0.0; k];
+    for (row, &target) in rows.iter().zip(y) {
+        for i in 0..k {
+            xty[i] += row[i] * target;
+            for j in 0..k {
+                a[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    for (i, row) in a.iter_mut().enumerate() {
+        row[k + i] = 1.0;
+    }
+    for col in 0..k {
+        let pivot = (col..k).max_by(|&p, &q| a[p][col].abs().total_cmp(&a[q][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        let scale = a[col][col];
+        a[col].iter_mut().for_each(|v| *v /= scale);
+        for r in 0..k {
+            if r != col {
+                let factor = a[r][col];
+                if factor != 0.0 {
+                    let pivot_row = a[col].clone();
+                    a[r].iter_mut()
+                        .zip(pivot_row)
+                        .for_each(|(v, p)| *v -= factor * p);
+                }
+            }
+        }
+    }
+    let inverse: Vec<&[f64]> = a.iter().map(|row| &row[k..]).collect();
+
+    let coefficients: Vec<f64> = inverse
+        .iter()
+        .map(|row| row.iter().zip(&xty).map(|(a, b)| a * b).sum())
+        .collect();
+    let ssr: f64 = rows
+        .iter()
+        .zip(y)
+        .map(|(row, target)| {
+            let fitted: f64 = row.iter().zip(&coefficients).map(|(x, b)| x * b).sum();
+            (target - fitted).powi(2)
+        })
+        .sum();
+    let variance = ssr / (y.len() - k) as f64;
+    let standard_errors = (0..k).map(|i| (variance * inverse[i][i]).sqrt()).collect();
+
+    Some(OlsFit {
+        coefficients,
+        standard_errors,
+        ssr,
+    })
+}
+
+/// MacKinnon (1994) approximate p-value of a unit-root t-statistic with a
+/// constant, for `n_vars` variables (1 for ADF, 2 for Engle-Granger)
+fn mackinnon_p_value(statistic: f64, n_vars: usize) -> f64 {
+    // (max, min, switch point, small-p polynomial, large-p polynomial)
+    let (max, min, star, small, large): (f64, f64, f64, [f64; 3], [f64; 4]) = match n_vars {
+        1 => (
+            2.74,
+            -18.83,
+            -1.61,
+            [2.1659, 1.4412, 0.038269],
+            [1.7339, 0.93202, -0.12745, -0.010368],
+        ),
+        _ => (
+            0.92,
+            -18.86,
+            -2.62,
+            [2.92, 1.5012, 0.039796],
+            [2.1945, 0.64695, -0.29198, -0.042377],
+        ),
+    };
+    if statistic.is_nan() {
+        return f64::NAN;
+    }
+    if statistic > max {
+        return 1.0;
+    }
+    if statistic < min {
+        return 0.0;
+    }
+    let poly = |coefficients: &[f64]| {
+        coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |acc, c| acc * statistic + c)
+    };
+    if statistic <= star {
+        norm_cdf(poly(&small))
+    } else {
+        norm_cdf(poly(&large))
+    }
+}
+
+/// MacKinnon (2010) critical values at 1%, 5% and 10% with a constant, for
+/// `n_vars` variables and `n_obs` observations
+fn mackinnon_critical_values(n_vars: usize, n_obs: usize) -> [f64; 3] {
+    let surfaces: [[f64; 4]; 3] = match n_vars {
+        1 => [
+            [-3.43035, -6.5393, -16.786, -79.433],
+            [-2.86154, -2.8903, -4.234, -40.040],
+            [-2.56677, -1.5384, -2.809, 0.0],
+        ],
+        _ => [
+            [-3.89644, -10.9519, -33.527, 0.0],
+            [-3.33613, -6.1101, -6.823, 0.0],
+            [-3.04445, -4.2412, -2.720, 0.0],
+        ],
+    };
+    let t = n_obs as f64;
+    surfaces.map(|[b0, b1, b2, b3]| b0 + b1 / t + b2 / t.powi(2) + b3 / t.powi(3))
+}
@@ -0,0 +1,74 @@
+use crate::util::dataframe_utils::check_window_size;
+use polars::prelude::*;
+
+/// Calculates Pearson's Correlation Coefficient between two columns over a
+/// rolling window
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the data
+/// * `column1` - First column
+/// * `column2` - Second column
+/// * `window` - Number of bars in each window (typically 30)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the Correlation Series, NaN for windows
+/// with a missing value or a constant column
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stats::calculate_correl;
+///
+/// let df = df! {
+///     "a" => [1.0, 2.0, 3.0, 4.0],
+///     "b" => [2.0, 4.0, 6.0, 5.0],
+/// }
+/// .unwrap();
+/// let correl = calculate_correl(&df, "a", "b", 3).unwrap();
+/// assert!((correl.f64().unwrap().get(2).unwrap() - 1.0).abs() < 1e-12);
+/// ```
+pub fn calculate_correl(
+    df: &DataFrame,
+    column1: &str,
+    column2: &str,
+    window: usize,
+) -> PolarsResult<Series> {
+    check_window_size(df, window, "Correlation")?;
+
+    if !df.schema().contains(column1) || !df.schema().contains(column2) {
+        return Err(PolarsError::ComputeError(
+            format!("Correlation requires {column1} and {column2} columns").into(),
+        ));
+    }
+
+    let x = df.column(column1)?.f64()?;
+    let y = df.column(column2)?.f64()?;
+    let n = window as f64;
+
+    let mut correl_values = vec![f64::NAN; window - 1];
+    for i in window - 1..df.height() {
+        let (mut sum_x, mut sum_y, mut sum_xy, mut sum_x2, mut sum_y2) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for j in i + 1 - window..=i {
+            let x = x.get(j).unwrap_or(f64::NAN);
+            let y = y.get(j).unwrap_or(f64::NAN);
+            sum_x += x;
+            sum_y += y;
+            sum_xy += x * y;
+            sum_x2 += x * x;
+            sum_y2 += y * y;
+        }
+
+        let covariance = sum_xy - sum_x * sum_y / n;
+        let variance = (sum_x2 - sum_x * sum_x / n) * (sum_y2 - sum_y * sum_y / n);
+        correl_values.push(if variance > 0.0 {
+            covariance / variance.sqrt()
+        } else {
+            f64::NAN
+        });
+    }
+
+    Ok(Series::new("correl".into(), correl_values))
+}
@@ -0,0 +1,376 @@
+//! # Cross-Asset Correlation
+//!
+//! Correlation of bar returns across several tickers, each held in its own
+//! DataFrame. The frames must be aligned: row `i` of every frame is the same
+//! bar. The results feed diversification-aware allocation, e.g. capping the
+//! weight given to one cluster of closely correlated tickers.
+
+use crate::util::dataframe_utils::{check_window_size, column_nan_values};
+use polars::prelude::*;
+use std::ops::Range;
+
+/// Pairwise correlation coefficients of a set of tickers
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrelationMatrix {
+    /// Ticker names, in the order of the rows and columns of `values`
+    pub tickers: Vec<String>,
+    /// Symmetric matrix of correlation coefficients, NaN where a ticker has
+    /// a missing value or no variance
+    pub values: Vec<Vec<f64>>,
+}
+
+impl CorrelationMatrix {
+    /// Correlation between two tickers, or `None` when either is unknown
+    pub fn get(&self, a: &str, b: &str) -> Option<f64> {
+        let i = self.tickers.iter().position(|t| t == a)?;
+        let j = self.tickers.iter().position(|t| t == b)?;
+        Some(self.values[i][j])
+    }
+
+    /// Mean of the correlations of all distinct pairs, skipping NaN pairs
+    pub fn average_correlation(&self) -> f64 {
+        let pairs: Vec<f64> = (0..self.values.len())
+            .flat_map(|i| (i + 1..self.values.len()).map(move |j| (i, j)))
+            .map(|(i, j)| self.values[i][j])
+            .filter(|c| !c.is_nan())
+            .collect();
+        if pairs.is_empty() {
+            f64::NAN
+        } else {
+            pairs.iter().sum::<f64>() / pairs.len() as f64
+        }
+    }
+
+    /// The matrix as a DataFrame with a "ticker" column and one column per
+    /// ticker
+    pub fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        let mut columns = vec![Column::new("ticker".into(), &self.tickers)];
+        for (j, ticker) in self.tickers.iter().enumerate() {
+            let values: Vec<f64> = self.values.iter().map(|row| row[j]).collect();
+            columns.push(Column::new(ticker.into(), values));
+        }
+        DataFrame::new(columns)
+    }
+}
+
+/// Correlation matrix of bar returns over the whole history
+///
+/// # Arguments
+///
+/// * `assets` - Ticker name and aligned price DataFrame of each ticker
+/// * `column` - Price column of every DataFrame (typically "close")
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`CorrelationMatrix`] of the simple
+/// returns of `column`, computed over the bars at which every ticker has a
+/// return. Fails when fewer than two tickers are given or the frames have
+/// different heights.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stats::cross_asset::correlation_matrix;
+///
+/// let spy = df! { "close" => [100.0, 101.0, 100.0, 102.0, 103.0] }.unwrap();
+/// let qqq = df! { "close" => [200.0, 202.0, 200.0, 204.0, 206.0] }.unwrap();
+/// let tlt = df! { "close" => [90.0, 89.0, 90.0, 88.0, 87.0] }.unwrap();
+/// let assets = [("SPY", &spy), ("QQQ", &qqq), ("TLT", &tlt)];
+///
+/// let matrix = correlation_matrix(&assets, "close").unwrap();
+/// assert!(matrix.get("SPY", "QQQ").unwrap() > 0.99);
+/// assert!(matrix.get("SPY", "TLT").unwrap() < -0.9);
+/// ```
+pub fn correlation_matrix(
+    assets: &[(&str, &DataFrame)],
+    column: &str,
+) -> PolarsResult<CorrelationMatrix> {
+    let returns = asset_returns(assets, column)?;
+    let height = returns[0].len();
+    Ok(window_matrix(assets, &returns, 1..height, true))
+}
+
+/// Correlation matrix of bar returns over a rolling window
+///
+/// # Arguments
+///
+/// * `assets` - Ticker name and aligned price DataFrame of each ticker
+/// * `column` - Price column of every DataFrame (typically "close")
+/// * `window` - Number of returns in each window (typically 60)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing one [`CorrelationMatrix`] per bar, of
+/// the `window` returns ending at that bar. The matrices of the first
+/// `window` bars, which have fewer returns, and of windows with a missing
+/// price are NaN off the diagonal.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stats::cross_asset::rolling_correlation_matrices;
+///
+/// let a = df! { "close" => [10.0, 11.0, 10.0, 11.0, 12.0, 11.0] }.unwrap();
+/// let b = df! { "close" => [20.0, 22.0, 20.0, 20.0, 22.0, 26.0] }.unwrap();
+/// let assets = [("A", &a), ("B", &b)];
+///
+/// let matrices = rolling_correlation_matrices(&assets, "close", 2).unwrap();
+/// assert_eq!(matrices.len(), 6);
+/// assert!(matrices[1].get("A", "B").unwrap().is_nan());
+/// assert!((matrices[2].get("A", "B").unwrap() - 1.0).abs() < 1e-12);
+/// assert!((matrices[5].get("A", "B").unwrap() + 1.0).abs() < 1e-12);
+/// ```
+pub fn rolling_correlation_matrices(
+    assets: &[(&str, &DataFrame)],
+    column: &str,
+    window: usize,
+) -> PolarsResult<Vec<CorrelationMatrix>> {
+    let returns = asset_returns(assets, column)?;
+    check_window_size(assets[0].1, window, "Cross-asset correlation")?;
+
+    Ok((0..returns[0].len())
+        .map(|i| {
+            let start = if i < window { i + 1 } else { i + 1 - window };
+            window_matrix(assets, &returns, start..i + 1, false)
+        })
+        .collect())
+}
+
+/// Average pairwise correlation of bar returns over a rolling window
+///
+/// A rising average correlation means the tickers increasingly move
+/// together and a portfolio of them is less diversified than its ticker
+/// count suggests, as happens in market-wide selloffs.
+///
+/// # Arguments
+///
+/// * `assets` - Ticker name and aligned price DataFrame of each ticker
+/// * `column` - Price column of every DataFrame (typically "close")
+/// * `window` - Number of returns in each window (typically 60)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the "avg_correlation_{window}" Series,
+/// the mean of the pairwise correlations of each window; NaN for the first
+/// `window` bars and where no pair has a defined correlation
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stats::cross_asset::calculate_average_correlation;
+///
+/// let a = df! { "close" => [10.0, 11.0, 10.0, 11.0] }.unwrap();
+/// let b = df! { "close" => [20.0, 22.0, 20.0, 22.0] }.unwrap();
+/// let c = df! { "close" => [30.0, 27.0, 30.0, 27.0] }.unwrap();
+/// let assets = [("A", &a), ("B", &b), ("C", &c)];
+///
+/// let avg = calculate_average_correlation(&assets, "close", 3).unwrap();
+/// assert_eq!(avg.name().as_str(), "avg_correlation_3");
+/// // Pairs: A-B = 1, A-C = -1, B-C = -1
+/// assert!((avg.f64().unwrap().get(3).unwrap() + 1.0 / 3.0).abs() < 1e-12);
+/// ```
+pub fn calculate_average_correlation(
+    assets: &[(&str, &DataFrame)],
+    column: &str,
+    window: usize,
+) -> PolarsResult<Series> {
+    let values: Vec<f64> = rolling_correlation_matrices(assets, column, window)?
+        .iter()
+        .map(CorrelationMatrix::average_correlation)
+        .collect();
+    Ok(Series::new(
+        format!("avg_correlation_{window}").into(),
+        values,
+    ))
+}
+
+/// Groups tickers by hierarchical clustering of their correlations
+///
+/// Agglomerative clustering with average linkage on the correlation
+/// distance `sqrt((1 - correlation) / 2)`, which is 0 for perfectly
+/// correlated tickers and 1 for perfectly anti-correlated ones. Starting
+/// from one cluster per ticker, the two clusters with the smallest mean
+/// distance between their members are merged until `n_clusters` remain.
+/// Undefined (NaN) correlations count as uncorrelated.
+///
+/// # Arguments
+///
+/// * `matrix` - Correlation matrix of the tickers
+/// * `n_clusters` - Number of clusters to stop at
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the clusters, each a list of tickers in
+/// matrix order, ordered by their first ticker. Fails when `n_clusters` is
+/// 0 or larger than the number of tickers.
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::stats::cross_asset::{cluster_tickers, CorrelationMatrix};
+///
+/// let matrix = CorrelationMatrix {
+///     tickers: ["SPY", "TLT", "QQQ", "IEF"].map(String::from).to_vec(),
+///     values: vec![
+///         vec![1.0, -0.4, 0.9, -0.3],
+///         vec![-0.4, 1.0, -0.3, 0.8],
+///         vec![0.9, -0.3, 1.0, -0.2],
+///         vec![-0.3, 0.8, -0.2, 1.0],
+///     ],
+/// };
+///
+/// let clusters = cluster_tickers(&matrix, 2).unwrap();
+/// assert_eq!(clusters, [vec!["SPY", "QQQ"], vec!["TLT", "IEF"]]);
+/// ```
+pub fn cluster_tickers(
+    matrix: &CorrelationMatrix,
+    n_clusters: usize,
+) -> PolarsResult<Vec<Vec<String>>> {
+    let n = matrix.tickers.len();
+    if n_clusters == 0 || n_clusters > n {
+        return Err(PolarsError::ComputeError(
+            format!("Cannot cut {n} tickers into {n_clusters} clusters").into(),
+        ));
+    }
+
+    let distance = |i: usize, j: usize| {
+        let correlation = matrix.values[i][j];
+        let correlation = if correlation.is_nan() {
+            0.0
+        } else {
+            correlation
+        };
+        ((1.0 - correlation.clamp(-1.0, 1.0)) / 2.0).sqrt()
+    };
+    let linkage = |a: &[usize], b: &[usize]| {
+        let total: f64 = a
+            .iter()
+            .flat_map(|&i| b.iter().map(move |&j| (i, j)))
+            .map(|(i, j)| distance(i, j))
+            .sum();
+        total / (a.len() * b.len()) as f64
+    };
+
+    let mut clusters: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+    while clusters.len() > n_clusters {
+        let mut closest = (0, 1, f64::INFINITY);
+        for a in 0..clusters.len() {
+            for b in a + 1..clusters.len() {
+                let d = linkage(&clusters[a], &clusters[b]);
+                if d < closest.2 {
+                    closest = (a, b, d);
+                }
+            }
+        }
+        let merged = clusters.remove(closest.1);
+        clusters[closest.0].extend(merged);
+        clusters[closest.0].sort_unstable();
+    }
+
+    clusters.sort_by_key(|cluster| cluster[0]);
+    Ok(clusters
+        .into_iter()
+        .map(|cluster| {
+            cluster
+                .into_iter()
+                .map(|i| matrix.tickers[i].clone())
+                .collect()
+        })
+        .collect())
+}
+
+/// Simple returns of `column` for every asset, NaN at the first bar
+fn asset_returns(assets: &[(&str, &DataFrame)], column: &str) -> PolarsResult<Vec<Vec<f64>>> {
+    if assets.len() < 2 {
+        return Err(PolarsError::ComputeError(
+            "Cross-asset correlation requires at least 2 tickers".into(),
+        ));
+    }
+    let height = assets[0].1.height();
+    assets
+        .iter()
+        .map(|(ticker, df)| {
+            if df.height() != height {
+                return Err(PolarsError::ComputeError(
+                    format!(
+                        "{ticker} has {} rows, expected {height}; the frames must be aligned",
+                        df.height()
+                    )
+                    .into(),
+                ));
+            }
+            let prices = column_nan_values(df, column)?;
+            Ok((0..height)
+                .map(|i| {
+                    if i == 0 {
+                        f64::NAN
+                    } else {
+                        prices[i] / prices[i - 1] - 1.0
+                    }
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// Correlation matrix of the returns at `bars`. With `skip_missing`, bars
+/// where any ticker lacks a return are left out; otherwise such a bar makes
+/// the matrix NaN, like [`calculate_correl`](super::calculate_correl).
+fn window_matrix(
+    assets: &[(&str, &DataFrame)],
+    returns: &[Vec<f64>],
+    bars: Range<usize>,
+    skip_missing: bool,
+) -> CorrelationMatrix {
+    let rows: Vec<usize> = bars
+        .clone()
+        .filter(|&i| returns.iter().all(|r| !r[i].is_nan()))
+        .collect();
+    let complete = skip_missing || rows.len() == bars.len();
+    let k = returns.len();
+
+    let mut values = vec![vec![f64::NAN; k]; k];
+    for a in 0..k {
+        values[a][a] = 1.0;
+        for b in a + 1..k {
+            let correlation = if complete && rows.len() >= 2 {
+                pearson(rows.iter().map(|&i| (returns[a][i], returns[b][i])))
+            } else {
+                f64::NAN
+            };
+            values[a][b] = correlation;
+            values[b][a] = correlation;
+        }
+    }
+
+    CorrelationMatrix {
+        tickers: assets
+            .iter()
+            .map(|(ticker, _)| ticker.to_string())
+            .collect(),
+        values,
+    }
+}
+
+fn pearson(pairs: impl Iterator<Item = (f64, f64)>) -> f64 {
+    let (mut n, mut sum_x, mut sum_y, mut sum_xy, mut sum_x2, mut sum_y2) =
+        (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        n += 1.0;
+        sum_x += x;
+        sum_y += y;
+        sum_xy += x * y;
+        sum_x2 += x * x;
+        sum_y2 += y * y;
+    }
+    let covariance = sum_xy - sum_x * sum_y / n;
+    let variance = (sum_x2 - sum_x * sum_x / n) * (sum_y2 - sum_y * sum_y / n);
+    if variance > 0.0 {
+        covariance / variance.sqrt()
+    } else {
+        f64::NAN
+    }
+}
@@ -0,0 +1,125 @@
+use crate::util::dataframe_utils::check_window_size;
+use polars::prelude::*;
+
+/// Smallest sub-period used in the rescaled range analysis
+const MIN_CHUNK: usize = 8;
+
+/// Calculates the rolling Hurst exponent with rescaled range (R/S) analysis
+///
+/// Each window of `window` log returns is split into non-overlapping chunks
+/// of 8, 16, 32, ... returns (aligned to the newest return). The exponent is
+/// the slope of ln(mean R/S) against ln(chunk size). Values above 0.5 point
+/// to a persistent, trending series, values below 0.5 to a mean-reverting
+/// one and 0.5 to a random walk. R/S overstates the exponent on short
+/// windows, so compare values computed with the same window.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data
+/// * `column` - Price column; non-positive prices have no log return
+/// * `window` - Number of log returns in each window, at least 16 (typically 100)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the Hurst exponent Series, NaN until
+/// index `window` and for windows with a missing return or no variation
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stats::calculate_hurst;
+///
+/// // Returns that drift slowly between up and down runs are persistent
+/// let mut close = vec![100.0];
+/// for i in 1..80 {
+///     close.push(close[i - 1] * (0.01 * (i as f64 / 8.0).sin()).exp());
+/// }
+/// let df = df! { "close" => close }.unwrap();
+/// let hurst = calculate_hurst(&df, "close", 64).unwrap();
+/// let hurst = hurst.f64().unwrap();
+/// assert!(hurst.get(63).unwrap().is_nan());
+/// assert!(hurst.get(79).unwrap() > 0.5);
+///
+/// // Bar-to-bar reversals are mean-reverting
+/// let close: Vec<f64> = (0..80).map(|i| if i % 2 == 0 { 100.0 } else { 101.0 }).collect();
+/// let df = df! { "close" => close }.unwrap();
+/// let hurst = calculate_hurst(&df, "close", 64).unwrap();
+/// assert!(hurst.f64().unwrap().get(79).unwrap() < 0.5);
+/// ```
+pub fn calculate_hurst(df: &DataFrame, column: &str, window: usize) -> PolarsResult<Series> {
+    check_window_size(df, window + 1, "Hurst exponent")?;
+    if window < 2 * MIN_CHUNK {
+        return Err(PolarsError::ComputeError(
+            format!("Hurst exponent window must be at least {}", 2 * MIN_CHUNK).into(),
+        ));
+    }
+
+    let prices = df.column(column)?.f64()?;
+    let mut returns = vec![f64::NAN; df.height()];
+    for (i, value) in returns.iter_mut().enumerate().skip(1) {
+        if let (Some(previous), Some(current)) = (prices.get(i - 1), prices.get(i)) {
+            if previous > 0.0 && current > 0.0 {
+                *value = (current / previous).ln();
+            }
+        }
+    }
+
+    let mut hurst = vec![f64::NAN; df.height()];
+    for i in window..df.height() {
+        hurst[i] = hurst_exponent(&returns[i + 1 - window..=i]);
+    }
+
+    Ok(Series::new("hurst".into(), hurst))
+}
+
+/// Hurst exponent of a slice of returns, NaN if it has a missing value or
+/// fewer than two chunk sizes with a usable R/S
+fn hurst_exponent(returns: &[f64]) -> f64 {
+    if returns.iter().any(|r| r.is_nan()) {
+        return f64::NAN;
+    }
+
+    let mut points = Vec::new();
+    let mut size = MIN_CHUNK;
+    while size <= returns.len() {
+        let ratios: Vec<f64> = returns
+            .rchunks_exact(size)
+            .filter_map(rescaled_range)
+            .collect();
+        if !ratios.is_empty() {
+            let mean = ratios.iter().sum::<f64>() / ratios.len() as f64;
+            points.push(((size as f64).ln(), mean.ln()));
+        }
+        size *= 2;
+    }
+
+    if points.len() < 2 {
+        return f64::NAN;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    covariance / variance
+}
+
+/// Range of the cumulative mean-adjusted sums divided by the population
+/// standard deviation, or None for a chunk with no variation
+fn rescaled_range(chunk: &[f64]) -> Option<f64> {
+    let mean = chunk.iter().sum::<f64>() / chunk.len() as f64;
+    let mut cumulative = 0.0;
+    let mut max = f64::NEG_INFINITY;
+    let mut min = f64::INFINITY;
+    for value in chunk {
+        cumulative += value - mean;
+        max = max.max(cumulative);
+        min = min.min(cumulative);
+    }
+    let std = (chunk.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / chunk.len() as f64).sqrt();
+    (std > 0.0 && max > min).then(|| (max - min) / std)
+}
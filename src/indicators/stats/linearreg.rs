@@ -0,0 +1,77 @@
+use crate::util::dataframe_utils::check_window_size;
+use polars::prelude::*;
+
+/// Least-squares line through each window of a column
+///
+/// Bars are numbered 0 to `window - 1` from the oldest in the window. Windows
+/// with a missing value have no line.
+///
+/// # Returns
+///
+/// One `(slope, intercept)` per row, the intercept being the line's value at
+/// the oldest bar of the window
+pub(super) fn regression_lines(
+    df: &DataFrame,
+    column: &str,
+    window: usize,
+    indicator: &str,
+) -> PolarsResult<Vec<Option<(f64, f64)>>> {
+    check_window_size(df, window, indicator)?;
+
+    let values = df.column(column)?.f64()?;
+    let n = window as f64;
+    let sum_x = n * (n - 1.0) / 2.0;
+    let sum_x2 = n * (n - 1.0) * (2.0 * n - 1.0) / 6.0;
+    let divisor = n * sum_x2 - sum_x * sum_x;
+
+    let mut lines = vec![None; window - 1];
+    for i in window - 1..df.height() {
+        let mut sum_y = 0.0;
+        let mut sum_xy = 0.0;
+        for (x, j) in (i + 1 - window..=i).enumerate() {
+            let y = values.get(j).unwrap_or(f64::NAN);
+            sum_y += y;
+            sum_xy += x as f64 * y;
+        }
+
+        let slope = (n * sum_xy - sum_x * sum_y) / divisor;
+        let intercept = (sum_y - slope * sum_x) / n;
+        lines.push((!slope.is_nan() && !intercept.is_nan()).then_some((slope, intercept)));
+    }
+
+    Ok(lines)
+}
+
+/// Calculates Linear Regression - the value of the least-squares line at the
+/// latest bar of each window
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the data
+/// * `column` - Column to fit
+/// * `window` - Number of bars in each regression (typically 14)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the Linear Regression Series
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stats::calculate_linearreg;
+///
+/// let df = df! { "close" => [1.0, 3.0, 2.0, 4.0] }.unwrap();
+/// let linearreg = calculate_linearreg(&df, "close", 3).unwrap();
+/// // The line through 3, 2, 4 has slope 0.5 and ends at 3.5
+/// assert_eq!(linearreg.f64().unwrap().get(3), Some(3.5));
+/// ```
+pub fn calculate_linearreg(df: &DataFrame, column: &str, window: usize) -> PolarsResult<Series> {
+    let end = (window - 1) as f64;
+    let values: Vec<f64> = regression_lines(df, column, window, "Linear Regression")?
+        .into_iter()
+        .map(|line| line.map_or(f64::NAN, |(slope, intercept)| intercept + slope * end))
+        .collect();
+
+    Ok(Series::new("linearreg".into(), values))
+}
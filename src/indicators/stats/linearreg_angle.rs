@@ -0,0 +1,27 @@
+use super::linearreg::regression_lines;
+use polars::prelude::*;
+
+/// Calculates Linear Regression Angle - the slope of the least-squares line
+/// through each window, in degrees
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the data
+/// * `column` - Column to fit
+/// * `window` - Number of bars in each regression (typically 14)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the Linear Regression Angle Series
+pub fn calculate_linearreg_angle(
+    df: &DataFrame,
+    column: &str,
+    window: usize,
+) -> PolarsResult<Series> {
+    let values: Vec<f64> = regression_lines(df, column, window, "Linear Regression Angle")?
+        .into_iter()
+        .map(|line| line.map_or(f64::NAN, |(slope, _)| slope.atan().to_degrees()))
+        .collect();
+
+    Ok(Series::new("linearreg_angle".into(), values))
+}
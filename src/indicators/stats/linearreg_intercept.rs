@@ -0,0 +1,27 @@
+use super::linearreg::regression_lines;
+use polars::prelude::*;
+
+/// Calculates Linear Regression Intercept - the value of the least-squares
+/// line at the oldest bar of each window
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the data
+/// * `column` - Column to fit
+/// * `window` - Number of bars in each regression (typically 14)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the Linear Regression Intercept Series
+pub fn calculate_linearreg_intercept(
+    df: &DataFrame,
+    column: &str,
+    window: usize,
+) -> PolarsResult<Series> {
+    let values: Vec<f64> = regression_lines(df, column, window, "Linear Regression Intercept")?
+        .into_iter()
+        .map(|line| line.map_or(f64::NAN, |(_, intercept)| intercept))
+        .collect();
+
+    Ok(Series::new("linearreg_intercept".into(), values))
+}
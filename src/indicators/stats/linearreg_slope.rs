@@ -0,0 +1,27 @@
+use super::linearreg::regression_lines;
+use polars::prelude::*;
+
+/// Calculates Linear Regression Slope - the change per bar of the
+/// least-squares line through each window
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the data
+/// * `column` - Column to fit
+/// * `window` - Number of bars in each regression (typically 14)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the Linear Regression Slope Series
+pub fn calculate_linearreg_slope(
+    df: &DataFrame,
+    column: &str,
+    window: usize,
+) -> PolarsResult<Series> {
+    let values: Vec<f64> = regression_lines(df, column, window, "Linear Regression Slope")?
+        .into_iter()
+        .map(|line| line.map_or(f64::NAN, |(slope, _)| slope))
+        .collect();
+
+    Ok(Series::new("linearreg_slope".into(), values))
+}
@@ -1,24 +1,35 @@
 // Stats indicators module
 
 mod beta;
-// Uncomment as you add more indicators
-// mod correl;
-// mod linearreg;
-// mod linearreg_slope;
-// mod linearreg_intercept;
-// mod linearreg_angle;
-// mod stddev;
-// mod var;
-// mod tsf;
+mod cointegration;
+mod correl;
+pub mod cross_asset;
+mod hurst;
+mod linearreg;
+mod linearreg_angle;
+mod linearreg_intercept;
+mod linearreg_slope;
+mod percent_rank;
+mod tsf;
+mod var;
+mod zscore;
 
 // Re-export indicators
 pub use beta::calculate_beta;
-// Uncomment as you add more indicators
-// pub use correl::calculate_correl;
-// pub use linearreg::calculate_linearreg;
-// pub use linearreg_slope::calculate_linearreg_slope;
-// pub use linearreg_intercept::calculate_linearreg_intercept;
-// pub use linearreg_angle::calculate_linearreg_angle;
-// pub use stddev::calculate_stddev;
-// pub use var::calculate_var;
-// pub use tsf::calculate_tsf;
+pub use cointegration::{
+    adf_test, engle_granger_test, estimate_half_life, AdfLags, AdfResult, CointegrationResult,
+};
+pub use correl::calculate_correl;
+pub use hurst::calculate_hurst;
+pub use linearreg::calculate_linearreg;
+pub use linearreg_angle::calculate_linearreg_angle;
+pub use linearreg_intercept::calculate_linearreg_intercept;
+pub use linearreg_slope::calculate_linearreg_slope;
+pub use percent_rank::calculate_percent_rank;
+pub use tsf::calculate_tsf;
+pub use var::calculate_var;
+pub use zscore::calculate_zscore;
+pub(crate) use zscore::rolling_zscore;
+
+// Standard deviation lives with the volatility indicators
+pub use crate::indicators::volatility::calculate_stddev;
@@ -0,0 +1,50 @@
+use crate::util::dataframe_utils::{check_window_size, column_nan_values};
+use polars::prelude::*;
+
+/// Calculates the rolling Percent Rank - the percentage of the preceding
+/// `window` values that are below each value
+///
+/// Ranges from 0 (the lowest of the lookback) to 100 (above all of it), as
+/// used by Connors RSI. Values without a full lookback, or with a missing
+/// value in it, have no rank (NaN).
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the data
+/// * `column` - Column to rank
+/// * `window` - Number of preceding values to rank against (typically 100)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the Percent Rank Series
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stats::calculate_percent_rank;
+///
+/// let df = df! { "close" => [3.0, 1.0, 2.0, 4.0, 2.5] }.unwrap();
+/// let rank = calculate_percent_rank(&df, "close", 4).unwrap();
+/// let rank = rank.f64().unwrap();
+/// assert!(rank.get(3).unwrap().is_nan());
+/// // 2.5 is above two of the four values before it
+/// assert_eq!(rank.get(4), Some(50.0));
+/// ```
+pub fn calculate_percent_rank(df: &DataFrame, column: &str, window: usize) -> PolarsResult<Series> {
+    check_window_size(df, window, "Percent Rank")?;
+
+    let values = column_nan_values(df, column)?;
+
+    let mut ranks = vec![f64::NAN; values.len()];
+    for i in window..values.len() {
+        let lookback = &values[i - window..i];
+        if values[i].is_nan() || lookback.iter().any(|v| v.is_nan()) {
+            continue;
+        }
+        let below = lookback.iter().filter(|&&v| v < values[i]).count();
+        ranks[i] = 100.0 * below as f64 / window as f64;
+    }
+
+    Ok(Series::new("percent_rank".into(), ranks))
+}
@@ -0,0 +1,35 @@
+use super::linearreg::regression_lines;
+use polars::prelude::*;
+
+/// Calculates Time Series Forecast - the least-squares line through each
+/// window extended one bar past its end
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the data
+/// * `column` - Column to fit
+/// * `window` - Number of bars in each regression (typically 14)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the Time Series Forecast Series
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stats::calculate_tsf;
+///
+/// let df = df! { "close" => [1.0, 3.0, 2.0, 4.0] }.unwrap();
+/// let tsf = calculate_tsf(&df, "close", 3).unwrap();
+/// assert_eq!(tsf.f64().unwrap().get(3), Some(4.0));
+/// ```
+pub fn calculate_tsf(df: &DataFrame, column: &str, window: usize) -> PolarsResult<Series> {
+    let next = window as f64;
+    let values: Vec<f64> = regression_lines(df, column, window, "Time Series Forecast")?
+        .into_iter()
+        .map(|line| line.map_or(f64::NAN, |(slope, intercept)| intercept + slope * next))
+        .collect();
+
+    Ok(Series::new("tsf".into(), values))
+}
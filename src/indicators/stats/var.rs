@@ -0,0 +1,26 @@
+use crate::util::dataframe_utils::{check_window_size, fixed_window, rolling_values};
+use polars::prelude::*;
+
+/// Calculates Variance - the population variance of each window
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the data
+/// * `column` - Column to measure
+/// * `window` - Number of bars in each window (typically 5)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the Variance Series, NaN for windows
+/// with a missing value
+pub fn calculate_var(df: &DataFrame, column: &str, window: usize) -> PolarsResult<Series> {
+    check_window_size(df, window, "Variance")?;
+
+    let values = df.column(column)?.f64()?.clone().into_series();
+    let var = values.rolling_var(RollingOptionsFixedWindow {
+        fn_params: Some(RollingFnParams::Var(RollingVarParams { ddof: 0 })),
+        ..fixed_window(window, window)
+    })?;
+
+    Ok(Series::new("var".into(), rolling_values(&var, window - 1)?))
+}
@@ -0,0 +1,68 @@
+use crate::util::dataframe_utils::{check_window_size, column_nan_values};
+use polars::prelude::*;
+
+/// Calculates the rolling Z-Score - how many standard deviations each value
+/// lies from the mean of the trailing window that ends at it
+///
+/// The sample standard deviation is used. A window of identical values has
+/// a Z-Score of 0.0; a window with a missing value has none (NaN).
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the data
+/// * `column` - Column to standardize
+/// * `window` - Number of bars in each window, at least 2 (typically 20)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the Z-Score Series
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stats::calculate_zscore;
+///
+/// let df = df! { "close" => [1.0, 2.0, 3.0, 3.0] }.unwrap();
+/// let zscore = calculate_zscore(&df, "close", 3).unwrap();
+/// let zscore = zscore.f64().unwrap();
+/// assert!(zscore.get(1).unwrap().is_nan());
+/// // 3 against 1, 2, 3: mean 2, standard deviation 1
+/// assert_eq!(zscore.get(2), Some(1.0));
+/// ```
+pub fn calculate_zscore(df: &DataFrame, column: &str, window: usize) -> PolarsResult<Series> {
+    check_window_size(df, window, "Z-Score")?;
+    if window < 2 {
+        return Err(PolarsError::ComputeError(
+            "Z-Score window must be at least 2".into(),
+        ));
+    }
+
+    let values = column_nan_values(df, column)?;
+
+    Ok(Series::new(
+        "zscore".into(),
+        rolling_zscore(&values, window),
+    ))
+}
+
+/// Z-score of each value against the trailing window that ends at it
+///
+/// NaN until the first full window; `window` must be at least 2.
+pub(crate) fn rolling_zscore(values: &[f64], window: usize) -> Vec<f64> {
+    let mut zscores = vec![f64::NAN; values.len()];
+    for i in window.saturating_sub(1)..values.len() {
+        let slice = &values[i + 1 - window..=i];
+        let mean = slice.iter().sum::<f64>() / window as f64;
+        let variance = slice.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (window - 1) as f64;
+        let std = variance.sqrt();
+        zscores[i] = if std > 0.0 {
+            (values[i] - mean) / std
+        } else if std == 0.0 {
+            0.0
+        } else {
+            f64::NAN
+        };
+    }
+    zscores
+}
@@ -0,0 +1,296 @@
+//! # Earnings Event Analytics
+//!
+//! Measures how an instrument reacts to earnings announcements and marks the
+//! bars around them so strategies can stand aside.
+
+use crate::util::dataframe_utils::column_nan_values;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+
+/// Parameters for [`analyze_earnings_impact`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EarningsConfig {
+    /// Bars before the event bar in the pre-event return and event window
+    pub pre_bars: usize,
+    /// Bars after the event bar in the post-event return and event window
+    pub post_bars: usize,
+    /// Implied volatility column for the IV crush metrics, if any
+    pub iv_column: Option<String>,
+}
+
+impl Default for EarningsConfig {
+    fn default() -> Self {
+        Self {
+            pre_bars: 5,
+            post_bars: 5,
+            iv_column: None,
+        }
+    }
+}
+
+/// Result of [`analyze_earnings_impact`]
+#[derive(Debug, Clone)]
+pub struct EarningsImpact {
+    /// One row per event
+    pub events: DataFrame,
+    /// One row per event and bar offset from the event bar
+    pub event_window: DataFrame,
+}
+
+/// Analyze price reactions to earnings announcements
+///
+/// The event bar of an announcement is the first bar at or after its
+/// timestamp, so a report after the close falls on the next session's bar.
+/// With daily bars stamped at midnight, pass the date itself for reports
+/// before the open. Announcements after the last bar are ignored, and
+/// announcements sharing an event bar count once.
+///
+/// The `events` frame has one row per event bar with the bar's time column
+/// and these columns, in percent and NaN where the bars are missing:
+///
+/// * "event_bar" - row index of the event bar
+/// * "gap_pct" - event bar open against the prior close
+/// * "pre_return_pct" - prior close against the close `pre_bars` bars earlier
+/// * "event_return_pct" - event bar close against the prior close
+/// * "post_return_pct" - close `post_bars` bars after the event bar against
+///   its close
+/// * "iv_before", "iv_after", "iv_crush_pct" - with `iv_column`, the implied
+///   volatility on the prior and the event bar and its drop between them
+///
+/// The `event_window` frame lines the events up from offset `-pre_bars` to
+/// `post_bars` with "event" (row of `events`), "offset", the time column,
+/// "close" and "return_pct", the close against the close before the event.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with "open" and "close" columns, sorted by time
+/// * `time_column` - Name of the timestamp column
+/// * `earnings` - Announcement timestamps
+/// * `config` - Window lengths and the optional IV column
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`EarningsImpact`]
+///
+/// # Example
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use polars::prelude::*;
+/// use rustalib::indicators::stock::{analyze_earnings_impact, EarningsConfig};
+///
+/// let df = df! {
+///     "date" => ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05", "2024-01-08"],
+///     "open" => [100.0, 101.0, 110.0, 108.0, 107.0],
+///     "close" => [101.0, 100.0, 108.0, 107.0, 110.0],
+///     "iv" => [0.50, 0.60, 0.30, 0.28, 0.27],
+/// }
+/// .unwrap();
+///
+/// // Reported after the close on Jan 3, so Jan 4 is the event bar
+/// let report = Utc.with_ymd_and_hms(2024, 1, 3, 21, 5, 0).unwrap();
+/// let config = EarningsConfig {
+///     pre_bars: 1,
+///     post_bars: 2,
+///     iv_column: Some("iv".to_string()),
+/// };
+/// let impact = analyze_earnings_impact(&df, "date", &[report], &config).unwrap();
+///
+/// let events = &impact.events;
+/// assert_eq!(events.column("event_bar").unwrap().u32().unwrap().get(0), Some(2));
+/// assert_eq!(events.column("gap_pct").unwrap().f64().unwrap().get(0), Some(10.0));
+/// assert_eq!(events.column("event_return_pct").unwrap().f64().unwrap().get(0), Some(8.0));
+/// assert_eq!(events.column("iv_crush_pct").unwrap().f64().unwrap().get(0), Some(50.0));
+/// // Offsets -1..=2 around the event bar
+/// assert_eq!(impact.event_window.height(), 4);
+/// ```
+pub fn analyze_earnings_impact(
+    df: &DataFrame,
+    time_column: &str,
+    earnings: &[DateTime<Utc>],
+    config: &EarningsConfig,
+) -> PolarsResult<EarningsImpact> {
+    let mut required = vec!["open", "close"];
+    if let Some(iv) = &config.iv_column {
+        required.push(iv.as_str());
+    }
+    for col in required {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Earnings analysis requires '{col}' column").into(),
+            ));
+        }
+    }
+
+    let to_vec = |name: &str| column_nan_values(df, name);
+    let open = to_vec("open")?;
+    let close = to_vec("close")?;
+    let iv = config.iv_column.as_deref().map(to_vec).transpose()?;
+
+    let event_bars = find_event_bars(df, time_column, earnings)?;
+    // Percent change from `from` to `to`, NaN when either bar is missing
+    let change = |series: &[f64], from: Option<usize>, to: Option<usize>| match (from, to) {
+        (Some(from), Some(to)) if to < series.len() => {
+            (series[to] - series[from]) / series[from] * 100.0
+        }
+        _ => f64::NAN,
+    };
+
+    let mut gap = Vec::with_capacity(event_bars.len());
+    let mut pre_return = Vec::with_capacity(event_bars.len());
+    let mut event_return = Vec::with_capacity(event_bars.len());
+    let mut post_return = Vec::with_capacity(event_bars.len());
+    let (mut iv_before, mut iv_after, mut iv_crush) = (Vec::new(), Vec::new(), Vec::new());
+
+    let mut window_event = Vec::new();
+    let mut window_offset = Vec::new();
+    let mut window_rows: Vec<IdxSize> = Vec::new();
+    let mut window_return = Vec::new();
+
+    for (event, &bar) in event_bars.iter().enumerate() {
+        let prior = bar.checked_sub(1);
+        gap.push(prior.map_or(f64::NAN, |p| (open[bar] - close[p]) / close[p] * 100.0));
+        pre_return.push(change(
+            &close,
+            prior.and_then(|p| p.checked_sub(config.pre_bars)),
+            prior,
+        ));
+        event_return.push(change(&close, prior, Some(bar)));
+        post_return.push(change(&close, Some(bar), Some(bar + config.post_bars)));
+        if let Some(iv) = &iv {
+            let before = prior.map_or(f64::NAN, |p| iv[p]);
+            iv_before.push(before);
+            iv_after.push(iv[bar]);
+            iv_crush.push((before - iv[bar]) / before * 100.0);
+        }
+
+        let first = bar.saturating_sub(config.pre_bars);
+        let last = (bar + config.post_bars).min(df.height() - 1);
+        for row in first..=last {
+            window_event.push(event as u32);
+            window_offset.push(row as i32 - bar as i32);
+            window_rows.push(row as IdxSize);
+            window_return.push(change(&close, prior, Some(row)));
+        }
+    }
+
+    let take_times = |rows: Vec<IdxSize>| -> PolarsResult<Column> {
+        let rows = IdxCa::from_vec("".into(), rows);
+        Ok(df
+            .column(time_column)?
+            .as_materialized_series()
+            .take(&rows)?
+            .into())
+    };
+
+    let mut events = DataFrame::new(vec![
+        take_times(event_bars.iter().map(|&b| b as IdxSize).collect())?,
+        Series::new(
+            "event_bar".into(),
+            event_bars.iter().map(|&b| b as u32).collect::<Vec<_>>(),
+        )
+        .into(),
+        Series::new("gap_pct".into(), gap).into(),
+        Series::new("pre_return_pct".into(), pre_return).into(),
+        Series::new("event_return_pct".into(), event_return).into(),
+        Series::new("post_return_pct".into(), post_return).into(),
+    ])?;
+    if iv.is_some() {
+        events.with_column(Series::new("iv_before".into(), iv_before))?;
+        events.with_column(Series::new("iv_after".into(), iv_after))?;
+        events.with_column(Series::new("iv_crush_pct".into(), iv_crush))?;
+    }
+
+    let window_close: Vec<f64> = window_rows.iter().map(|&r| close[r as usize]).collect();
+    let event_window = DataFrame::new(vec![
+        Series::new("event".into(), window_event).into(),
+        Series::new("offset".into(), window_offset).into(),
+        take_times(window_rows)?,
+        Series::new("close".into(), window_close).into(),
+        Series::new("return_pct".into(), window_return).into(),
+    ])?;
+
+    Ok(EarningsImpact {
+        events,
+        event_window,
+    })
+}
+
+/// Flag the bars around earnings announcements
+///
+/// Marks the event bar of each announcement (see [`analyze_earnings_impact`])
+/// together with `bars_before` bars before and `bars_after` bars after it.
+/// Announcement dates are published ahead of time, so the flag can gate
+/// entries without lookahead.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame sorted by time
+/// * `time_column` - Name of the timestamp column
+/// * `earnings` - Announcement timestamps
+/// * `bars_before` - Bars to block before each event bar
+/// * `bars_after` - Bars to block after each event bar
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Boolean Series named
+/// "earnings_blackout", true on blocked bars
+///
+/// # Example
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use polars::prelude::*;
+/// use rustalib::indicators::stock::earnings_blackout;
+///
+/// let df = df! {
+///     "date" => ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05", "2024-01-08"],
+/// }
+/// .unwrap();
+///
+/// let report = Utc.with_ymd_and_hms(2024, 1, 4, 0, 0, 0).unwrap();
+/// let blackout = earnings_blackout(&df, "date", &[report], 1, 0).unwrap();
+/// let flags: Vec<_> = blackout.bool().unwrap().into_no_null_iter().collect();
+/// assert_eq!(flags, vec![false, true, true, false, false]);
+/// ```
+pub fn earnings_blackout(
+    df: &DataFrame,
+    time_column: &str,
+    earnings: &[DateTime<Utc>],
+    bars_before: usize,
+    bars_after: usize,
+) -> PolarsResult<Series> {
+    let mut blackout = vec![false; df.height()];
+    for bar in find_event_bars(df, time_column, earnings)? {
+        let last = (bar + bars_after).min(df.height() - 1);
+        for flag in &mut blackout[bar.saturating_sub(bars_before)..=last] {
+            *flag = true;
+        }
+    }
+    Ok(Series::new("earnings_blackout".into(), blackout))
+}
+
+/// Sorted, distinct row indices of the first bar at or after each announcement
+fn find_event_bars(
+    df: &DataFrame,
+    time_column: &str,
+    earnings: &[DateTime<Utc>],
+) -> PolarsResult<Vec<usize>> {
+    let bars: Vec<(DateTime<Utc>, usize)> = parse_timestamp_column(df, time_column)?
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, t)| Some((t?, i)))
+        .collect();
+
+    let mut event_bars: Vec<usize> = earnings
+        .iter()
+        .filter_map(|announcement| {
+            let idx = bars.partition_point(|(t, _)| t < announcement);
+            bars.get(idx).map(|&(_, row)| row)
+        })
+        .collect();
+    event_bars.sort_unstable();
+    event_bars.dedup();
+    Ok(event_bars)
+}
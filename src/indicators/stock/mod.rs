@@ -6,10 +6,16 @@
 //!
 //! - [`price_action`](price_action/index.html): Indicators based on price action specific to stocks
 //! - [`fundamental`](fundamental/index.html): Indicators incorporating fundamental data with technical indicators
+//! - [`earnings`](earnings/index.html): Earnings event reactions and blackout windows
+//! - [`relative_strength`](relative_strength/index.html): Relative strength, alpha and beta against a benchmark
 
+pub mod earnings;
 pub mod fundamental;
 pub mod price_action;
+pub mod relative_strength;
 
 // Re-export common types and functions for convenient access
+pub use earnings::{analyze_earnings_impact, earnings_blackout, EarningsConfig, EarningsImpact};
 pub use fundamental::FundamentalIndicators;
 pub use price_action::StockPricePatterns;
+pub use relative_strength::{add_relative_strength, RelativeStrengthConfig};
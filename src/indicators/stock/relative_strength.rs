@@ -0,0 +1,204 @@
+//! # Relative Strength Against a Benchmark
+//!
+//! Compares an instrument with a benchmark (an index ETF such as SPY, or a
+//! sector ETF) held in a separate DataFrame, aligning the two by time.
+
+use crate::util::dataframe_utils::column_nan_values;
+use crate::util::time_utils::parse_timestamp_column;
+use polars::prelude::*;
+
+/// Parameters for [`add_relative_strength`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelativeStrengthConfig {
+    /// Close column of the benchmark DataFrame
+    pub benchmark_close_column: String,
+    /// Moving average window of the RS ratio for Mansfield RS (52 for
+    /// weekly bars, 200 or 252 for daily bars)
+    pub mansfield_period: usize,
+    /// Number of returns in each rolling alpha/beta regression
+    pub beta_window: usize,
+}
+
+impl Default for RelativeStrengthConfig {
+    fn default() -> Self {
+        Self {
+            benchmark_close_column: "close".to_string(),
+            mansfield_period: 252,
+            beta_window: 60,
+        }
+    }
+}
+
+/// Add benchmark-relative strength columns to a DataFrame
+///
+/// Each bar is matched with the latest benchmark close at or before its
+/// time, so the frames may have different calendars or gaps. Columns:
+///
+/// * "benchmark_close" - aligned benchmark close
+/// * "rs_ratio" - close / benchmark close, the relative strength line
+/// * "rs_mansfield" - Mansfield relative strength, the percent distance of
+///   the RS ratio from its `mansfield_period` simple moving average
+/// * "rs_beta", "rs_alpha" - slope and intercept of the least-squares
+///   regression of the instrument's bar returns on the benchmark's over the
+///   trailing `beta_window` returns; alpha is per bar, not annualized
+///
+/// # Arguments
+///
+/// * `df` - Instrument DataFrame with a "close" column
+/// * `time_column` - Timestamp column of `df`
+/// * `benchmark` - Benchmark DataFrame
+/// * `benchmark_time_column` - Timestamp column of `benchmark`
+/// * `config` - Benchmark column and windows
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a copy of `df` with the columns above,
+/// NaN where there is no benchmark close yet or not enough history
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::stock::relative_strength::{
+///     add_relative_strength, RelativeStrengthConfig,
+/// };
+///
+/// let stock = df! {
+///     "date" => ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"],
+///     "close" => [50.0, 51.0, 53.04, 53.04],
+/// }
+/// .unwrap();
+/// // The benchmark has no bar on Jan 4; its Jan 3 close carries over
+/// let spy = df! {
+///     "date" => ["2024-01-02", "2024-01-03", "2024-01-05"],
+///     "close" => [400.0, 404.0, 404.0],
+/// }
+/// .unwrap();
+///
+/// let config = RelativeStrengthConfig { mansfield_period: 2, beta_window: 2, ..Default::default() };
+/// let rs = add_relative_strength(&stock, "date", &spy, "date", &config).unwrap();
+/// let ratio = rs.column("rs_ratio").unwrap().f64().unwrap();
+/// assert_eq!(ratio.get(0), Some(0.125));
+/// assert_eq!(rs.column("benchmark_close").unwrap().f64().unwrap().get(2), Some(404.0));
+/// assert!(rs.column("rs_mansfield").unwrap().f64().unwrap().get(2).unwrap() > 0.0);
+/// ```
+pub fn add_relative_strength(
+    df: &DataFrame,
+    time_column: &str,
+    benchmark: &DataFrame,
+    benchmark_time_column: &str,
+    config: &RelativeStrengthConfig,
+) -> PolarsResult<DataFrame> {
+    if config.mansfield_period == 0 || config.beta_window < 2 {
+        return Err(PolarsError::ComputeError(
+            "Relative strength needs a positive Mansfield period and a beta window of at least 2"
+                .into(),
+        ));
+    }
+    for (frame, col) in [
+        (df, "close"),
+        (benchmark, config.benchmark_close_column.as_str()),
+    ] {
+        if !frame.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Relative strength requires '{col}' column").into(),
+            ));
+        }
+    }
+
+    let close = column_nan_values(df, "close")?;
+    let benchmark_close = align_benchmark(
+        df,
+        time_column,
+        benchmark,
+        benchmark_time_column,
+        &config.benchmark_close_column,
+    )?;
+
+    let ratio: Vec<f64> = close
+        .iter()
+        .zip(&benchmark_close)
+        .map(|(c, b)| if *b != 0.0 { c / b } else { f64::NAN })
+        .collect();
+
+    let period = config.mansfield_period;
+    let mut mansfield = vec![f64::NAN; df.height()];
+    for i in period.saturating_sub(1)..df.height() {
+        let mean = ratio[i + 1 - period..=i].iter().sum::<f64>() / period as f64;
+        if mean != 0.0 {
+            mansfield[i] = (ratio[i] / mean - 1.0) * 100.0;
+        }
+    }
+
+    let returns = |prices: &[f64]| -> Vec<f64> {
+        std::iter::once(f64::NAN)
+            .chain(prices.windows(2).map(|w| w[1] / w[0] - 1.0))
+            .collect()
+    };
+    let instrument_returns = returns(&close);
+    let benchmark_returns = returns(&benchmark_close);
+
+    let window = config.beta_window;
+    let mut beta = vec![f64::NAN; df.height()];
+    let mut alpha = vec![f64::NAN; df.height()];
+    for i in window..df.height() {
+        let y = &instrument_returns[i + 1 - window..=i];
+        let x = &benchmark_returns[i + 1 - window..=i];
+        if y.iter().chain(x).any(|v| !v.is_finite()) {
+            continue;
+        }
+        let mean_x = x.iter().sum::<f64>() / window as f64;
+        let mean_y = y.iter().sum::<f64>() / window as f64;
+        let covariance: f64 = x
+            .iter()
+            .zip(y)
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum();
+        let variance: f64 = x.iter().map(|x| (x - mean_x).powi(2)).sum();
+        if variance > 0.0 {
+            beta[i] = covariance / variance;
+            alpha[i] = mean_y - beta[i] * mean_x;
+        }
+    }
+
+    let mut result = df.clone();
+    result.with_column(Series::new("benchmark_close".into(), benchmark_close))?;
+    result.with_column(Series::new("rs_ratio".into(), ratio))?;
+    result.with_column(Series::new("rs_mansfield".into(), mansfield))?;
+    result.with_column(Series::new("rs_beta".into(), beta))?;
+    result.with_column(Series::new("rs_alpha".into(), alpha))?;
+    Ok(result)
+}
+
+/// Latest benchmark value at or before each bar of `df`
+fn align_benchmark(
+    df: &DataFrame,
+    time_column: &str,
+    benchmark: &DataFrame,
+    benchmark_time_column: &str,
+    value_column: &str,
+) -> PolarsResult<Vec<f64>> {
+    let bar_times = parse_timestamp_column(df, time_column)?;
+    let times = parse_timestamp_column(benchmark, benchmark_time_column)?;
+    let values = benchmark.column(value_column)?.cast(&DataType::Float64)?;
+
+    let mut observations: Vec<_> = times
+        .into_iter()
+        .zip(values.f64()?)
+        .filter_map(|(t, v)| Some((t?, v.filter(|v| !v.is_nan())?)))
+        .collect();
+    observations.sort_by_key(|(t, _)| *t);
+
+    Ok(bar_times
+        .iter()
+        .map(|bar_time| {
+            let Some(bar_time) = bar_time else {
+                return f64::NAN;
+            };
+            match observations.partition_point(|(t, _)| t <= bar_time) {
+                0 => f64::NAN,
+                idx => observations[idx - 1].1,
+            }
+        })
+        .collect())
+}
@@ -54,3 +54,64 @@ pub fn create_test_ohlcv_df() -> DataFrame {
     ])
     .unwrap()
 }
+
+/// Compares an indicator series with reference values, such as TA-Lib output
+///
+/// NaN reference values mark the reference's warm-up and are not compared,
+/// so indicators that fill their warm-up differently can still match. Every
+/// other value must be present and agree within `tolerance`, relative to the
+/// reference value when its magnitude is above 1.0 and absolute otherwise.
+///
+/// # Arguments
+///
+/// * `actual` - Indicator output to check
+/// * `expected` - Reference values, one per row
+/// * `tolerance` - Allowed difference
+///
+/// # Returns
+///
+/// Returns Ok when the series matches, or an error naming the first row
+/// that differs
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::test_util::compare_to_reference;
+///
+/// let sma = Series::new("sma_2".into(), [0.0, 1.5, 2.5]);
+/// assert!(compare_to_reference(&sma, &[f64::NAN, 1.5, 2.5], 1e-9).is_ok());
+/// assert!(compare_to_reference(&sma, &[f64::NAN, 1.5, 2.6], 1e-9).is_err());
+/// ```
+pub fn compare_to_reference(actual: &Series, expected: &[f64], tolerance: f64) -> PolarsResult<()> {
+    if actual.len() != expected.len() {
+        return Err(PolarsError::ShapeMismatch(
+            format!(
+                "{} has {} rows, the reference has {}",
+                actual.name(),
+                actual.len(),
+                expected.len()
+            )
+            .into(),
+        ));
+    }
+
+    let actual_values = actual.cast(&DataType::Float64)?;
+    for (row, (value, reference)) in actual_values.f64()?.iter().zip(expected).enumerate() {
+        if reference.is_nan() {
+            continue;
+        }
+        let value = value.unwrap_or(f64::NAN);
+        let within = (value - reference).abs() <= tolerance * reference.abs().max(1.0);
+        if !within {
+            return Err(PolarsError::ComputeError(
+                format!(
+                    "{} differs from the reference at row {row}: {value} vs {reference}",
+                    actual.name()
+                )
+                .into(),
+            ));
+        }
+    }
+    Ok(())
+}
@@ -1,7 +1,9 @@
 use super::minus_di::calculate_minus_di;
 use super::plus_di::calculate_plus_di;
+use crate::error::TaError;
 use crate::util::dataframe_utils::check_window_size;
 use polars::prelude::*;
+use std::cmp::Ordering;
 
 /// Calculates the Average Directional Movement Index (ADX)
 ///
@@ -14,6 +16,184 @@ use polars::prelude::*;
 ///
 /// Returns a PolarsResult containing the ADX Series
 pub fn calculate_adx(df: &DataFrame, window: usize) -> PolarsResult<Series> {
+    let (adx, _, _) = adx_components(df, window)?;
+    Ok(adx)
+}
+
+/// Calculates the ADX together with the +DI and -DI it is built from
+///
+/// The ADX measures how strongly price trends, whatever the direction; the
+/// directional indicators tell the direction: an uptrend while +DI is above
+/// -DI, a downtrend while it is below.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data with high, low, close columns
+/// * `window` - Window size for all three (typically 14)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with "adx", "plus_di" and
+/// "minus_di" columns, one row per input row
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::trend::calculate_dmi;
+///
+/// let close: Vec<f64> = (0..10).map(|i| 100.0 + i as f64).collect();
+/// let high: Vec<f64> = close.iter().map(|c| c + 0.5).collect();
+/// let low: Vec<f64> = close.iter().map(|c| c - 0.5).collect();
+/// let df = df! { "high" => high, "low" => low, "close" => close }.unwrap();
+///
+/// let dmi = calculate_dmi(&df, 3).unwrap();
+/// let value = |name: &str| dmi.column(name).unwrap().f64().unwrap().get(9).unwrap();
+/// assert_eq!(value("adx"), 100.0);
+/// assert!(value("plus_di") > value("minus_di"));
+/// ```
+pub fn calculate_dmi(df: &DataFrame, window: usize) -> PolarsResult<DataFrame> {
+    let (adx, plus_di, minus_di) = adx_components(df, window)?;
+    DataFrame::new(vec![adx.into(), plus_di.into(), minus_di.into()])
+}
+
+/// ADX thresholds of [`classify_adx_trend`]
+///
+/// The defaults follow Wilder's reading of the ADX: below 20 there is no
+/// trend worth trading, 20 to 25 a trend is emerging, above 25 it is
+/// established and above 50 exceptionally strong.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdxTrendThresholds {
+    /// ADX at or above which a trend is moderate rather than weak
+    pub moderate: f64,
+    /// ADX at or above which a trend is strong
+    pub strong: f64,
+    /// ADX at or above which a trend is very strong
+    pub very_strong: f64,
+}
+
+impl Default for AdxTrendThresholds {
+    fn default() -> Self {
+        Self {
+            moderate: 20.0,
+            strong: 25.0,
+            very_strong: 50.0,
+        }
+    }
+}
+
+impl AdxTrendThresholds {
+    /// Strength level of an ADX value: 0 weak, 1 moderate, 2 strong,
+    /// 3 very strong; 0 for NaN
+    pub fn strength(&self, adx: f64) -> i32 {
+        if adx >= self.very_strong {
+            3
+        } else if adx >= self.strong {
+            2
+        } else if adx >= self.moderate {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+/// Classifies the trend of each bar by ADX strength and DI direction
+///
+/// The strength level from [`AdxTrendThresholds::strength`] is signed by
+/// the direction: positive while +DI is above -DI, negative while it is
+/// below. Weak trends, ties between the DIs and bars still warming up are 0.
+///
+/// | Value | Trend |
+/// |-------|-------|
+/// | 3 / -3 | very strong up / down |
+/// | 2 / -2 | strong up / down |
+/// | 1 / -1 | moderate up / down |
+/// | 0 | weak or none |
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data with high, low, close columns
+/// * `window` - Window size for the ADX and DIs (typically 14)
+/// * `thresholds` - ADX levels separating the strength classes, ascending
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing an i32 Series named "adx_trend", or
+/// InvalidParameter when the thresholds are not ascending
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::trend::{calculate_adx, classify_adx_trend, AdxTrendThresholds};
+///
+/// let bars = |step: f64| {
+///     let close: Vec<f64> = (0..10).map(|i| 100.0 + step * i as f64).collect();
+///     let high: Vec<f64> = close.iter().map(|c| c + 0.5).collect();
+///     let low: Vec<f64> = close.iter().map(|c| c - 0.5).collect();
+///     df! { "high" => high, "low" => low, "close" => close }.unwrap()
+/// };
+/// let last = |df: &DataFrame, thresholds: &AdxTrendThresholds| {
+///     let trend = classify_adx_trend(df, 3, thresholds).unwrap();
+///     trend.i32().unwrap().get(9).unwrap()
+/// };
+///
+/// // Steady moves: very strong trends, signed by direction
+/// let (falling, rising) = (bars(-1.0), bars(1.0));
+/// assert_eq!(last(&falling, &AdxTrendThresholds::default()), -3);
+/// assert_eq!(last(&rising, &AdxTrendThresholds::default()), 3);
+///
+/// // Each threshold is inclusive: an ADX just below the moderate level is
+/// // weak, one at a level belongs to that level's class
+/// let adx = calculate_adx(&falling, 3).unwrap().f64().unwrap().get(9).unwrap();
+/// let at = |moderate: f64, strong: f64, very_strong: f64| AdxTrendThresholds {
+///     moderate,
+///     strong,
+///     very_strong,
+/// };
+/// assert_eq!(last(&falling, &at(adx + 0.01, adx + 1.0, adx + 2.0)), 0);
+/// assert_eq!(last(&falling, &at(adx, adx + 0.01, adx + 1.0)), -1);
+/// assert_eq!(last(&falling, &at(adx - 1.0, adx, adx + 0.01)), -2);
+/// assert_eq!(last(&falling, &at(adx - 2.0, adx - 1.0, adx)), -3);
+/// ```
+pub fn classify_adx_trend(
+    df: &DataFrame,
+    window: usize,
+    thresholds: &AdxTrendThresholds,
+) -> PolarsResult<Series> {
+    if !(thresholds.moderate <= thresholds.strong && thresholds.strong <= thresholds.very_strong) {
+        return Err(TaError::invalid_parameter(
+            "thresholds",
+            "must satisfy moderate <= strong <= very_strong",
+        )
+        .into());
+    }
+
+    let (adx, plus_di, minus_di) = adx_components(df, window)?;
+    let trend: Vec<i32> = adx
+        .f64()?
+        .iter()
+        .zip(plus_di.f64()?.iter())
+        .zip(minus_di.f64()?.iter())
+        .map(|((adx, plus), minus)| match (adx, plus, minus) {
+            (Some(adx), Some(plus), Some(minus)) => {
+                let direction = match plus.partial_cmp(&minus) {
+                    Some(Ordering::Greater) => 1,
+                    Some(Ordering::Less) => -1,
+                    _ => 0,
+                };
+                direction * thresholds.strength(adx)
+            }
+            _ => 0,
+        })
+        .collect();
+
+    Ok(Series::new("adx_trend".into(), trend))
+}
+
+/// ADX, +DI and -DI
+fn adx_components(df: &DataFrame, window: usize) -> PolarsResult<(Series, Series, Series)> {
     check_window_size(df, window, "ADX")?;
 
     // Calculate +DI and -DI first
@@ -46,5 +226,5 @@ pub fn calculate_adx(df: &DataFrame, window: usize) -> PolarsResult<Series> {
         fn_params: None,
     })?;
 
-    Ok(adx.with_name("adx".into()))
+    Ok((adx.with_name("adx".into()), plus_di, minus_di))
 }
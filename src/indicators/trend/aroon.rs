@@ -1,8 +1,29 @@
-use crate::util::dataframe_utils::check_window_size;
+use crate::indicators::signal_utils::crosses;
+use crate::util::dataframe_utils::{
+    check_window_size, series_valid_values, sliding_extreme_positions,
+};
 use polars::prelude::*;
 
+/// How many bars each Aroon value looks back over
+///
+/// See CHANGELOG.md for the versions in which the default changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AroonLookback {
+    /// Behavior up to 1.0.8: the current bar and the `window - 1` bars
+    /// before it, with the first value at index `window - 1`
+    Legacy,
+    /// The current bar and the `window` bars before it, as in Chande's
+    /// definition and TA-Lib: the first value is at index `window`
+    #[default]
+    WindowPlusCurrent,
+}
+
 /// Calculates the Aroon indicator (Aroon Up and Aroon Down)
 ///
+/// Each value looks back over the current bar and the `window` bars before
+/// it ([`AroonLookback::WindowPlusCurrent`]); use
+/// [`calculate_aroon_with_lookback`] to reproduce the legacy behavior.
+///
 /// # Arguments
 ///
 /// * `df` - DataFrame containing the price data with high, low columns
@@ -10,54 +31,163 @@ use polars::prelude::*;
 ///
 /// # Returns
 ///
-/// Returns a PolarsResult containing a tuple of (Aroon Up, Aroon Down) Series
+/// Returns a PolarsResult containing a tuple of (Aroon Up, Aroon Down)
+/// Series, with the first value at index `window`
 pub fn calculate_aroon(df: &DataFrame, window: usize) -> PolarsResult<(Series, Series)> {
-    check_window_size(df, window, "Aroon")?;
-
-    let high = df.column("high")?.f64()?;
-    let low = df.column("low")?.f64()?;
-
-    let mut aroon_up = Vec::with_capacity(df.height());
-    let mut aroon_down = Vec::with_capacity(df.height());
-
-    // First values are NaN until we have enough data for window
-    for _ in 0..window - 1 {
-        aroon_up.push(f64::NAN);
-        aroon_down.push(f64::NAN);
-    }
-
-    for i in window - 1..df.height() {
-        let mut high_idx = 0;
-        let mut low_idx = 0;
-        let mut high_val = f64::MIN;
-        let mut low_val = f64::MAX;
-
-        // Find highest high and lowest low in window
-        for j in 0..window {
-            let h = high.get(i - j).unwrap_or(0.0);
-            let l = low.get(i - j).unwrap_or(0.0);
-
-            if h > high_val {
-                high_val = h;
-                high_idx = j;
-            }
-
-            if l < low_val {
-                low_val = l;
-                low_idx = j;
-            }
-        }
-
-        // Calculate Aroon Up and Aroon Down
-        let up = 100.0 * ((window as f64 - high_idx as f64) / window as f64);
-        let down = 100.0 * ((window as f64 - low_idx as f64) / window as f64);
-
-        aroon_up.push(up);
-        aroon_down.push(down);
-    }
+    calculate_aroon_with_lookback(df, window, AroonLookback::default())
+}
+
+/// Calculates the Aroon indicator with an explicit lookback
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data with high, low columns
+/// * `window` - Window size for calculation (typically 25)
+/// * `lookback` - Bars each value looks back over
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a tuple of (Aroon Up, Aroon Down) Series
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::trend::{calculate_aroon_with_lookback, AroonLookback};
+///
+/// let df = df! {
+///     "high" => [3.0, 2.0, 1.0, 1.5],
+///     "low" => [2.0, 1.0, 0.5, 1.0],
+/// }
+/// .unwrap();
+///
+/// // The high of bar 0 is still in the span of bar 2
+/// let (up, _) = calculate_aroon_with_lookback(&df, 2, AroonLookback::WindowPlusCurrent).unwrap();
+/// assert_eq!(up.f64().unwrap().get(2), Some(0.0));
+///
+/// // Bars 1 and 2 only, with the highest high one bar back
+/// let (up, _) = calculate_aroon_with_lookback(&df, 2, AroonLookback::Legacy).unwrap();
+/// assert_eq!(up.f64().unwrap().get(2), Some(50.0));
+/// ```
+pub fn calculate_aroon_with_lookback(
+    df: &DataFrame,
+    window: usize,
+    lookback: AroonLookback,
+) -> PolarsResult<(Series, Series)> {
+    let span = match lookback {
+        AroonLookback::Legacy => window,
+        AroonLookback::WindowPlusCurrent => window + 1,
+    };
+    check_window_size(df, span, "Aroon")?;
+
+    let high: Vec<f64> = df
+        .column("high")?
+        .f64()?
+        .iter()
+        .map(|v| v.unwrap_or(0.0))
+        .collect();
+    let low: Vec<f64> = df
+        .column("low")?
+        .f64()?
+        .iter()
+        .map(|v| v.unwrap_or(0.0))
+        .collect();
+
+    // Most recent highest high and lowest low of each `span` bars; a span
+    // without any value counts as an extreme on the current bar
+    let aroon = |positions: Vec<Option<usize>>| -> Vec<f64> {
+        positions
+            .into_iter()
+            .enumerate()
+            .map(|(i, position)| {
+                if i + 1 < span {
+                    f64::NAN
+                } else {
+                    let bars_since = position.map_or(0, |position| i - position);
+                    100.0 * ((window as f64 - bars_since as f64) / window as f64)
+                }
+            })
+            .collect()
+    };
+
+    Ok((
+        Series::new(
+            "aroon_up".into(),
+            aroon(sliding_extreme_positions(&high, span, |new, old| {
+                new >= old
+            })),
+        ),
+        Series::new(
+            "aroon_down".into(),
+            aroon(sliding_extreme_positions(&low, span, |new, old| new <= old)),
+        ),
+    ))
+}
+
+/// Crossover and threshold signals of the Aroon indicator
+///
+/// * cross - 1 on the bar Aroon Up crosses above Aroon Down, -1 on the bar
+///   it crosses below, 0 otherwise
+/// * trend - 1 while Aroon Up is at or above `threshold` and Aroon Down at
+///   or below `100 - threshold` (a recent high and no recent low), -1 for the
+///   mirror image, 0 otherwise
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data with high, low columns
+/// * `window` - Window size for calculation (typically 25)
+/// * `threshold` - Aroon level marking a strong trend (typically 70)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a tuple of i32 Series named
+/// "aroon_cross_{window}" and "aroon_trend_{window}"
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::trend::calculate_aroon_signals;
+///
+/// // Falling for six bars, then rising
+/// let close: Vec<f64> = (0..12).map(|i| (i as f64 - 6.0).abs()).collect();
+/// let df = df! {
+///     "high" => close.iter().map(|c| c + 0.5).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 0.5).collect::<Vec<_>>(),
+/// }
+/// .unwrap();
+///
+/// let (cross, trend) = calculate_aroon_signals(&df, 4, 70.0).unwrap();
+/// let cross: Vec<i32> = cross.i32().unwrap().into_no_null_iter().collect();
+/// assert_eq!(cross.iter().filter(|&&c| c == 1).count(), 1);
+/// assert_eq!(trend.i32().unwrap().get(5), Some(-1));
+/// assert_eq!(trend.i32().unwrap().get(11), Some(1));
+/// ```
+pub fn calculate_aroon_signals(
+    df: &DataFrame,
+    window: usize,
+    threshold: f64,
+) -> PolarsResult<(Series, Series)> {
+    let (up, down) = calculate_aroon(df, window)?;
+    let (up, down) = (series_valid_values(&up)?, series_valid_values(&down)?);
+
+    let cross: Vec<i32> = crosses(&up, &down, true)
+        .into_iter()
+        .zip(crosses(&up, &down, false))
+        .map(|(above, below)| i32::from(above) - i32::from(below))
+        .collect();
+    let trend: Vec<i32> = up
+        .iter()
+        .zip(&down)
+        .map(|pair| match pair {
+            (Some(up), Some(down)) if *up >= threshold && *down <= 100.0 - threshold => 1,
+            (Some(up), Some(down)) if *down >= threshold && *up <= 100.0 - threshold => -1,
+            _ => 0,
+        })
+        .collect();
 
     Ok((
-        Series::new("aroon_up".into(), aroon_up),
-        Series::new("aroon_down".into(), aroon_down),
+        Series::new(format!("aroon_cross_{window}").into(), cross),
+        Series::new(format!("aroon_trend_{window}").into(), trend),
     ))
 }
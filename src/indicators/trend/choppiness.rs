@@ -0,0 +1,74 @@
+use crate::error::TaError;
+use crate::util::dataframe_utils::{check_window_size, column_nan_values};
+use polars::prelude::*;
+
+/// Calculates the Choppiness Index (CHOP)
+///
+/// Formula: 100 * log10(sum of true ranges / (highest high - lowest low)) / log10(window),
+/// over the last `window` bars
+///
+/// The index compares the path price travelled with the ground it covered:
+/// a market moving in one direction covers its whole path and scores low,
+/// one swinging back and forth scores high. Values above 61.8 are usually
+/// read as choppy and below 38.2 as trending, so strategies can skip entries
+/// while the index is high.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data with high, low, close columns
+/// * `window` - Window size for calculation (typically 14, at least 2)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the Choppiness Index Series named
+/// "choppiness", from 0 to 100. The first bar has no true range, so the
+/// first value is at index `window`; windows without a range are NaN.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::trend::calculate_choppiness_index;
+///
+/// let steady: Vec<f64> = (0..10).map(|i| 100.0 + i as f64).collect();
+/// let swinging: Vec<f64> = (0..10).map(|i| 100.0 + (i % 2) as f64).collect();
+/// let chop = |close: &[f64]| {
+///     let high: Vec<f64> = close.iter().map(|c| c + 0.5).collect();
+///     let low: Vec<f64> = close.iter().map(|c| c - 0.5).collect();
+///     let df = df! { "high" => high, "low" => low, "close" => close }.unwrap();
+///     calculate_choppiness_index(&df, 4).unwrap().f64().unwrap().get(9).unwrap()
+/// };
+///
+/// assert!(chop(&steady) < 38.2);
+/// assert!(chop(&swinging) > 61.8);
+/// ```
+pub fn calculate_choppiness_index(df: &DataFrame, window: usize) -> PolarsResult<Series> {
+    if window < 2 {
+        return Err(TaError::invalid_parameter("window", "must be at least 2").into());
+    }
+    check_window_size(df, window + 1, "Choppiness Index")?;
+
+    let values = |col: &str| column_nan_values(df, col);
+    let (high, low, close) = (values("high")?, values("low")?, values("close")?);
+
+    let mut choppiness = vec![f64::NAN; df.height()];
+    for (i, value) in choppiness.iter_mut().enumerate().skip(window) {
+        let bars = i + 1 - window..=i;
+        let true_range: f64 = bars
+            .clone()
+            .map(|j| {
+                (high[j] - low[j])
+                    .max((high[j] - close[j - 1]).abs())
+                    .max((low[j] - close[j - 1]).abs())
+            })
+            .sum();
+        let highest = bars.clone().map(|j| high[j]).fold(f64::NAN, f64::max);
+        let lowest = bars.map(|j| low[j]).fold(f64::NAN, f64::min);
+        let range = highest - lowest;
+        if range > 0.0 {
+            *value = 100.0 * (true_range / range).log10() / (window as f64).log10();
+        }
+    }
+
+    Ok(Series::new("choppiness".into(), choppiness))
+}
@@ -4,29 +4,103 @@ pub mod adx;
 mod adxr;
 mod aroon;
 mod aroon_osc;
+mod choppiness;
 pub mod ichimoku;
 mod minus_di;
 mod minus_dm;
 mod plus_di;
 mod plus_dm;
 pub mod psar;
+mod vhf;
 mod vortex;
+pub mod zigzag;
 
 // Re-export indicators
-pub use adx::calculate_adx;
+pub use adx::{calculate_adx, calculate_dmi, classify_adx_trend, AdxTrendThresholds};
 pub use adxr::calculate_adxr;
-pub use aroon::calculate_aroon;
+pub use aroon::{
+    calculate_aroon, calculate_aroon_signals, calculate_aroon_with_lookback, AroonLookback,
+};
 pub use aroon_osc::calculate_aroon_osc;
+pub use choppiness::calculate_choppiness_index;
 pub use ichimoku::calculate_ichimoku_cloud;
 pub use minus_di::calculate_minus_di;
 pub use minus_dm::calculate_minus_dm;
 pub use plus_di::calculate_plus_di;
 pub use plus_dm::calculate_plus_dm;
 pub use psar::calculate_psar;
-pub use vortex::calculate_vortex;
+pub use vhf::calculate_vhf;
+pub use vortex::{calculate_vortex, calculate_vortex_signals};
+pub use zigzag::{calculate_zigzag, swing_pivots, SwingPivot, ZigZagThreshold};
 
+use crate::indicators::naming::indicator_name;
 use polars::prelude::*;
 
+/// Indicators added by [`add_trend_indicators_with_config`]
+///
+/// The default adds the columns of [`add_trend_indicators`]; the remaining
+/// indicators are opt-in and use their standard periods.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrendIndicatorConfig {
+    /// psar_0_02_0_20
+    pub psar: bool,
+    /// adx_14, plus_di_14 and minus_di_14
+    pub adx: bool,
+    /// adxr_14
+    pub adxr: bool,
+    /// aroon_up_25, aroon_down_25, aroon_osc_25 and the signal columns
+    /// aroon_cross_25 and aroon_trend_25
+    pub aroon: bool,
+    /// vi_plus_14, vi_minus_14 and the signal columns vortex_cross_14 and
+    /// vortex_trend_14
+    pub vortex: bool,
+    /// tenkan_sen, kijun_sen, senkou_span_a, senkou_span_b and chikou_span
+    /// (9, 26 and 52 bars)
+    pub ichimoku: bool,
+    /// choppiness_14
+    pub choppiness: bool,
+    /// vhf_28 of the close
+    pub vhf: bool,
+    /// Aroon level of aroon_trend_25 (see [`calculate_aroon_signals`])
+    pub aroon_threshold: f64,
+    /// VI level of vortex_trend_14 (see [`calculate_vortex_signals`])
+    pub vortex_threshold: f64,
+}
+
+impl Default for TrendIndicatorConfig {
+    fn default() -> Self {
+        Self {
+            psar: true,
+            adx: false,
+            adxr: false,
+            aroon: false,
+            vortex: false,
+            ichimoku: false,
+            choppiness: false,
+            vhf: false,
+            aroon_threshold: 70.0,
+            vortex_threshold: 1.1,
+        }
+    }
+}
+
+impl TrendIndicatorConfig {
+    /// A config with every indicator enabled
+    pub fn all() -> Self {
+        Self {
+            psar: true,
+            adx: true,
+            adxr: true,
+            aroon: true,
+            vortex: true,
+            ichimoku: true,
+            choppiness: true,
+            vhf: true,
+            ..Self::default()
+        }
+    }
+}
+
 /// Add trend indicators to a DataFrame
 ///
 /// # Arguments
@@ -41,20 +115,121 @@ use polars::prelude::*;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::indicators::trend::add_trend_indicators;
+/// use rustalib::indicators::trend::add_trend_indicators;
 ///
-/// // Create or load a DataFrame with OHLCV data
-/// let df = DataFrame::default(); // Replace with actual data
+/// let close: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 / 4.0).sin() * 5.0).collect();
+/// let high: Vec<f64> = close.iter().map(|c| c + 1.0).collect();
+/// let low: Vec<f64> = close.iter().map(|c| c - 1.0).collect();
+/// let df = df! {
+///     "open" => &close,
+///     "high" => high,
+///     "low" => low,
+///     "close" => &close,
+///     "volume" => vec![1000.0; 60],
+/// }
+/// .unwrap();
 ///
-/// // Add trend indicators
 /// let df_with_indicators = add_trend_indicators(&df).unwrap();
+/// assert_eq!(df_with_indicators.width(), df.width() + 1);
 /// ```
 pub fn add_trend_indicators(df: &DataFrame) -> PolarsResult<DataFrame> {
+    add_trend_indicators_with_config(df, &TrendIndicatorConfig::default())
+}
+
+/// Add the trend indicators selected by `config` to a DataFrame
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing OHLC data
+/// * `config` - Indicators to add and signal thresholds
+///
+/// # Returns
+///
+/// * `PolarsResult<DataFrame>` - DataFrame with added trend indicators
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::trend::{add_trend_indicators_with_config, TrendIndicatorConfig};
+///
+/// let close: Vec<f64> = (0..80).map(|i| 100.0 + (i as f64 / 4.0).sin() * 5.0).collect();
+/// let df = df! {
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+/// }
+/// .unwrap();
+///
+/// let result = add_trend_indicators_with_config(&df, &TrendIndicatorConfig::all()).unwrap();
+/// for name in ["adx_14", "plus_di_14", "aroon_trend_25", "vortex_cross_14", "choppiness_14"] {
+///     assert!(result.schema().contains(name));
+/// }
+/// ```
+pub fn add_trend_indicators_with_config(
+    df: &DataFrame,
+    config: &TrendIndicatorConfig,
+) -> PolarsResult<DataFrame> {
     let mut result_df = df.clone();
 
     // Parabolic SAR
-    let psar = calculate_psar(df, 0.02, 0.2)?;
-    result_df.with_column(psar)?;
+    if config.psar {
+        result_df.with_column(calculate_psar(df, 0.02, 0.2)?)?;
+    }
+
+    if config.adx {
+        let dmi = calculate_dmi(df, 14)?;
+        for column in dmi.get_columns() {
+            let series = column.as_materialized_series().clone();
+            result_df.with_column(named(series, column.name().as_str(), 14))?;
+        }
+    }
+
+    if config.adxr {
+        result_df.with_column(named(calculate_adxr(df, 14)?, "adxr", 14))?;
+    }
+
+    if config.aroon {
+        let (up, down) = calculate_aroon(df, 25)?;
+        result_df.with_column(named(up, "aroon_up", 25))?;
+        result_df.with_column(named(down, "aroon_down", 25))?;
+        result_df.with_column(named(calculate_aroon_osc(df, 25)?, "aroon_osc", 25))?;
+        let (cross, trend) = calculate_aroon_signals(df, 25, config.aroon_threshold)?;
+        result_df.with_column(cross)?;
+        result_df.with_column(trend)?;
+    }
+
+    if config.vortex {
+        let (plus, minus) = calculate_vortex(df, "high", "low", "close", 14)?;
+        result_df.with_column(named(plus, "vi_plus", 14))?;
+        result_df.with_column(named(minus, "vi_minus", 14))?;
+        let (cross, trend) =
+            calculate_vortex_signals(df, "high", "low", "close", 14, config.vortex_threshold)?;
+        result_df.with_column(cross)?;
+        result_df.with_column(trend)?;
+    }
+
+    if config.ichimoku {
+        let (tenkan, kijun, span_a, span_b, chikou) =
+            calculate_ichimoku_cloud(df, "high", "low", "close", 9, 26, 52)?;
+        for series in [tenkan, kijun, span_a, span_b, chikou] {
+            result_df.with_column(series)?;
+        }
+    }
+
+    if config.choppiness {
+        let choppiness = calculate_choppiness_index(df, 14)?;
+        result_df.with_column(named(choppiness, "choppiness", 14))?;
+    }
+
+    if config.vhf {
+        result_df.with_column(named(calculate_vhf(df, "close", 28)?, "vhf", 28))?;
+    }
 
     Ok(result_df)
 }
+
+/// Rename an indicator's output to its canonical name with the period
+fn named(series: Series, indicator: &str, period: usize) -> Series {
+    series.with_name(indicator_name(indicator, &[&period]).into())
+}
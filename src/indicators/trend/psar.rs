@@ -20,13 +20,18 @@ use polars::prelude::*;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::indicators::trend::calculate_psar;
+/// use rustalib::indicators::trend::calculate_psar;
 ///
-/// // Create or load a DataFrame with OHLCV data
-/// let df = DataFrame::default(); // Replace with actual data
+/// // A steady uptrend keeps the SAR below the lows
+/// let high: Vec<f64> = (0..20).map(|i| 101.0 + i as f64).collect();
+/// let low: Vec<f64> = (0..20).map(|i| 99.0 + i as f64).collect();
+/// let df = df! { "high" => &high, "low" => &low }.unwrap();
 ///
-/// // Calculate Parabolic SAR with default parameters
 /// let psar = calculate_psar(&df, 0.02, 0.2).unwrap();
+/// let psar = psar.f64().unwrap();
+/// for i in 2..20 {
+///     assert!(psar.get(i).unwrap() < low[i]);
+/// }
 /// ```
 pub fn calculate_psar(df: &DataFrame, af_step: f64, af_max: f64) -> PolarsResult<Series> {
     // Validate required columns
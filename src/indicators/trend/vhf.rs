@@ -0,0 +1,58 @@
+use crate::error::TaError;
+use crate::util::dataframe_utils::{check_window_size, column_nan_values};
+use polars::prelude::*;
+
+/// Calculates the Vertical Horizontal Filter (VHF)
+///
+/// Formula: (highest close - lowest close) / sum of |close - previous close|,
+/// over the last `window` changes (the last `window + 1` closes)
+///
+/// A close moving straight in one direction scores 1; the more of its
+/// movement is reversed, the closer the filter gets to 0. Rising values
+/// mark a developing trend, low values a congested, range-bound market.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data
+/// * `column` - Column to calculate the filter on (usually "close")
+/// * `window` - Number of changes measured (typically 28)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the VHF Series named "vhf", NaN for
+/// the first `window` bars and windows without movement
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::trend::calculate_vhf;
+///
+/// let df = df! { "close" => [100.0, 101.0, 102.0, 103.0, 102.0, 103.0] }.unwrap();
+/// let vhf = calculate_vhf(&df, "close", 3).unwrap();
+/// let vhf = vhf.f64().unwrap();
+/// assert_eq!(vhf.get(3), Some(1.0));
+/// // The last four closes span 102 to 103 but travel 1 + 1 + 1
+/// assert!((vhf.get(5).unwrap() - 1.0 / 3.0).abs() < 1e-12);
+/// ```
+pub fn calculate_vhf(df: &DataFrame, column: &str, window: usize) -> PolarsResult<Series> {
+    if window == 0 {
+        return Err(TaError::invalid_parameter("window", "must be positive").into());
+    }
+    check_window_size(df, window + 1, "VHF")?;
+
+    let close = column_nan_values(df, column)?;
+
+    let mut vhf = vec![f64::NAN; df.height()];
+    for i in window..df.height() {
+        let closes = &close[i - window..=i];
+        let highest = closes.iter().copied().fold(f64::NAN, f64::max);
+        let lowest = closes.iter().copied().fold(f64::NAN, f64::min);
+        let travelled: f64 = closes.windows(2).map(|w| (w[1] - w[0]).abs()).sum();
+        if travelled > 0.0 {
+            vhf[i] = (highest - lowest) / travelled;
+        }
+    }
+
+    Ok(Series::new("vhf".into(), vhf))
+}
@@ -1,3 +1,5 @@
+use crate::indicators::signal_utils::crosses;
+use crate::util::dataframe_utils::series_valid_values;
 use polars::prelude::*;
 
 /// Calculate Vortex Indicator (VI+ and VI-)
@@ -54,3 +56,71 @@ pub fn calculate_vortex(
         Series::new("vi_minus".into(), vi_minus),
     ))
 }
+
+/// Crossover and threshold signals of the Vortex Indicator
+///
+/// * cross - 1 on the bar VI+ crosses above VI-, -1 on the bar it crosses
+///   below, 0 otherwise
+/// * trend - 1 while VI+ is above VI- and at or above `threshold`, -1 while
+///   VI- is above VI+ and at or above it, 0 otherwise
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data
+/// * `high_col`, `low_col`, `close_col` - Names of the price columns
+/// * `period` - Window size for calculation (typically 14)
+/// * `threshold` - VI level marking a strong trend (typically 1.1)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a tuple of i32 Series named
+/// "vortex_cross_{period}" and "vortex_trend_{period}"
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::trend::calculate_vortex_signals;
+///
+/// let close: Vec<f64> = (0..10).map(|i| 100.0 + i as f64).collect();
+/// let df = df! {
+///     "high" => close.iter().map(|c| c + 0.5).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 0.5).collect::<Vec<_>>(),
+///     "close" => &close,
+/// }
+/// .unwrap();
+///
+/// let (_, trend) = calculate_vortex_signals(&df, "high", "low", "close", 3, 1.1).unwrap();
+/// assert_eq!(trend.i32().unwrap().get(9), Some(1));
+/// ```
+pub fn calculate_vortex_signals(
+    df: &DataFrame,
+    high_col: &str,
+    low_col: &str,
+    close_col: &str,
+    period: usize,
+    threshold: f64,
+) -> PolarsResult<(Series, Series)> {
+    let (plus, minus) = calculate_vortex(df, high_col, low_col, close_col, period)?;
+    let (plus, minus) = (series_valid_values(&plus)?, series_valid_values(&minus)?);
+
+    let cross: Vec<i32> = crosses(&plus, &minus, true)
+        .into_iter()
+        .zip(crosses(&plus, &minus, false))
+        .map(|(above, below)| i32::from(above) - i32::from(below))
+        .collect();
+    let trend: Vec<i32> = plus
+        .iter()
+        .zip(&minus)
+        .map(|pair| match pair {
+            (Some(plus), Some(minus)) if plus > minus && *plus >= threshold => 1,
+            (Some(plus), Some(minus)) if minus > plus && *minus >= threshold => -1,
+            _ => 0,
+        })
+        .collect();
+
+    Ok((
+        Series::new(format!("vortex_cross_{period}").into(), cross),
+        Series::new(format!("vortex_trend_{period}").into(), trend),
+    ))
+}
@@ -0,0 +1,247 @@
+use crate::indicators::volatility::calculate_atr;
+use crate::util::dataframe_utils::{column_nan_values, nan_values};
+use polars::prelude::*;
+
+/// Minimum move that reverses a ZigZag leg
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZigZagThreshold {
+    /// Percentage of the price at the current extreme
+    Percent(f64),
+    /// Multiple of the ATR on the current bar
+    AtrMultiple {
+        /// ATR window
+        period: usize,
+        /// ATR multiple
+        multiple: f64,
+    },
+}
+
+/// A swing high or low found by [`swing_pivots`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwingPivot {
+    /// Row of the pivot bar
+    pub index: usize,
+    /// High of a swing high or low of a swing low
+    pub price: f64,
+    /// Whether the pivot is a swing high
+    pub is_high: bool,
+    /// Row on which the reversal away from the pivot reached the threshold;
+    /// `None` for the last pivot, which later bars may still extend
+    pub confirmed_at: Option<usize>,
+}
+
+/// Extract ZigZag swing pivots from high and low prices
+///
+/// A leg extends while price makes new extremes in its direction and turns
+/// when price moves the threshold against it; the extreme then becomes a
+/// pivot. The first leg starts once price has moved the threshold from the
+/// first bars. The last extreme is returned as an unconfirmed pivot.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with "high" and "low" columns, plus "close" for an ATR
+///   threshold
+/// * `threshold` - Minimum reversal
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the pivots in order, alternating
+/// between highs and lows
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::trend::{swing_pivots, ZigZagThreshold};
+///
+/// let df = df! {
+///     "high" => [10.0, 11.0, 12.0, 11.5, 10.0, 9.5, 10.5, 12.0, 13.0, 12.5],
+///     "low" =>  [9.0, 10.0, 11.0, 10.5, 9.0, 8.5, 9.5, 11.0, 12.0, 11.5],
+/// }
+/// .unwrap();
+///
+/// let pivots = swing_pivots(&df, ZigZagThreshold::Percent(25.0)).unwrap();
+/// let rows: Vec<_> = pivots.iter().map(|p| (p.index, p.is_high)).collect();
+/// assert_eq!(rows, [(0, false), (2, true), (5, false), (8, true)]);
+/// // The fall from the high at row 2 reaches 25% on row 4
+/// assert_eq!(pivots[1].confirmed_at, Some(4));
+/// assert_eq!(pivots[3].confirmed_at, None);
+/// ```
+pub fn swing_pivots(df: &DataFrame, threshold: ZigZagThreshold) -> PolarsResult<Vec<SwingPivot>> {
+    Ok(trace(df, threshold)?.0)
+}
+
+/// Calculates the ZigZag indicator
+///
+/// See [`swing_pivots`] for how pivots are found. Pivots are marked on the
+/// bar of the extreme, before the reversal that confirms them, so
+/// zigzag_pivot and zigzag_price look ahead; only zigzag_direction is safe
+/// to trade on.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with "high" and "low" columns, plus "close" for an ATR
+///   threshold
+/// * `threshold` - Minimum reversal
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with:
+/// * zigzag_pivot - 1 on swing high bars, -1 on swing low bars, 0 otherwise
+///   (the last pivot may still move)
+/// * zigzag_price - Pivot price on pivot bars and the straight line between
+///   pivots in between, NaN outside the first and last pivot
+/// * zigzag_direction - Direction of the leg as known on each bar: 1 up,
+///   -1 down, 0 before the first reversal
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::trend::{calculate_zigzag, ZigZagThreshold};
+///
+/// let df = df! {
+///     "high" => [10.0, 11.0, 12.0, 11.5, 10.0, 9.5, 10.5, 12.0, 13.0, 12.5],
+///     "low" =>  [9.0, 10.0, 11.0, 10.5, 9.0, 8.5, 9.5, 11.0, 12.0, 11.5],
+/// }
+/// .unwrap();
+///
+/// let zigzag = calculate_zigzag(&df, ZigZagThreshold::Percent(25.0)).unwrap();
+/// let pivot: Vec<_> = zigzag.column("zigzag_pivot").unwrap().i32().unwrap().into_no_null_iter().collect();
+/// assert_eq!(pivot, [-1, 0, 1, 0, 0, -1, 0, 0, 1, 0]);
+/// let price = zigzag.column("zigzag_price").unwrap().f64().unwrap();
+/// assert_eq!(price.get(1), Some(10.5));
+/// let direction: Vec<_> = zigzag.column("zigzag_direction").unwrap().i32().unwrap().into_no_null_iter().collect();
+/// assert_eq!(direction, [0, 0, 1, 1, -1, -1, -1, 1, 1, 1]);
+/// ```
+pub fn calculate_zigzag(df: &DataFrame, threshold: ZigZagThreshold) -> PolarsResult<DataFrame> {
+    let (pivots, direction) = trace(df, threshold)?;
+    let n = df.height();
+
+    let mut pivot = vec![0i32; n];
+    let mut price = vec![f64::NAN; n];
+    for p in &pivots {
+        pivot[p.index] = if p.is_high { 1 } else { -1 };
+        price[p.index] = p.price;
+    }
+    for leg in pivots.windows(2) {
+        let (a, b) = (leg[0], leg[1]);
+        let slope = (b.price - a.price) / (b.index - a.index) as f64;
+        for (k, value) in price[a.index + 1..b.index].iter_mut().enumerate() {
+            *value = a.price + slope * (k + 1) as f64;
+        }
+    }
+
+    DataFrame::new(vec![
+        Series::new("zigzag_pivot".into(), pivot).into(),
+        Series::new("zigzag_price".into(), price).into(),
+        Series::new("zigzag_direction".into(), direction).into(),
+    ])
+}
+
+/// Walk the bars once, returning the pivots and the leg direction per bar
+fn trace(df: &DataFrame, threshold: ZigZagThreshold) -> PolarsResult<(Vec<SwingPivot>, Vec<i32>)> {
+    for col in ["high", "low"] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("ZigZag requires '{col}' column").into(),
+            ));
+        }
+    }
+    let n = df.height();
+    let distance: Box<dyn Fn(usize, f64) -> f64> = match threshold {
+        ZigZagThreshold::Percent(pct) if pct > 0.0 => Box::new(move |_, price| price * pct / 100.0),
+        ZigZagThreshold::AtrMultiple { period, multiple } if multiple > 0.0 => {
+            let atr = nan_values(calculate_atr(df, period)?.f64()?);
+            Box::new(move |i, _| multiple * atr[i])
+        }
+        _ => {
+            return Err(PolarsError::ComputeError(
+                "ZigZag threshold must be positive".into(),
+            ))
+        }
+    };
+    let values = |col: &str| column_nan_values(df, col);
+    let (high, low) = (values("high")?, values("low")?);
+
+    let mut pivots = Vec::new();
+    let mut direction = vec![0i32; n];
+    let mut dir = 0;
+    // Extremes of the current leg, or of both sides before the first leg
+    let (mut high_at, mut low_at): (Option<usize>, Option<usize>) = (None, None);
+
+    for i in 0..n {
+        let (h, l) = (high[i], low[i]);
+        if h.is_nan() || l.is_nan() {
+            direction[i] = dir;
+            continue;
+        }
+        match dir {
+            1 => {
+                let top = high_at.unwrap_or(i);
+                if h > high[top] {
+                    high_at = Some(i);
+                } else if high[top] - l >= distance(i, high[top]) {
+                    pivots.push(pivot(top, high[top], true, i));
+                    dir = -1;
+                    low_at = Some(i);
+                }
+            }
+            -1 => {
+                let bottom = low_at.unwrap_or(i);
+                if l < low[bottom] {
+                    low_at = Some(i);
+                } else if h - low[bottom] >= distance(i, low[bottom]) {
+                    pivots.push(pivot(bottom, low[bottom], false, i));
+                    dir = 1;
+                    high_at = Some(i);
+                }
+            }
+            _ => {
+                if high_at.is_none_or(|t| h > high[t]) {
+                    high_at = Some(i);
+                }
+                if low_at.is_none_or(|b| l < low[b]) {
+                    low_at = Some(i);
+                }
+                let (top, bottom) = (high_at.unwrap_or(i), low_at.unwrap_or(i));
+                // The older extreme starts the first leg
+                if bottom < top && high[top] - low[bottom] >= distance(i, low[bottom]) {
+                    pivots.push(pivot(bottom, low[bottom], false, i));
+                    dir = 1;
+                } else if top < bottom && high[top] - low[bottom] >= distance(i, high[top]) {
+                    pivots.push(pivot(top, high[top], true, i));
+                    dir = -1;
+                }
+            }
+        }
+        direction[i] = dir;
+    }
+
+    // The current extreme is the last, unconfirmed pivot
+    match (dir, high_at, low_at) {
+        (1, Some(top), _) => pivots.push(SwingPivot {
+            index: top,
+            price: high[top],
+            is_high: true,
+            confirmed_at: None,
+        }),
+        (-1, _, Some(bottom)) => pivots.push(SwingPivot {
+            index: bottom,
+            price: low[bottom],
+            is_high: false,
+            confirmed_at: None,
+        }),
+        _ => {}
+    }
+    Ok((pivots, direction))
+}
+
+fn pivot(index: usize, price: f64, is_high: bool, confirmed_at: usize) -> SwingPivot {
+    SwingPivot {
+        index,
+        price,
+        is_high,
+        confirmed_at: Some(confirmed_at),
+    }
+}
@@ -1,8 +1,27 @@
 use crate::util::dataframe_utils::check_window_size;
 use polars::prelude::*;
 
+/// Where the ATR's Wilder average starts
+///
+/// See CHANGELOG.md for the versions in which the default changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AtrSeeding {
+    /// Behavior up to 1.0.8: the first bar's true range is its high - low
+    /// and the first value, at index `window - 1`, averages bars 0 to
+    /// `window - 1`
+    Legacy,
+    /// The first bar has no previous close, so its true range is undefined
+    /// and the average starts on the second bar, as in TA-Lib: the first
+    /// value is at index `window`
+    #[default]
+    SecondBar,
+}
+
 /// Calculates Average True Range (ATR)
 ///
+/// The average starts on the second bar ([`AtrSeeding::SecondBar`]); use
+/// [`calculate_atr_with_seeding`] to reproduce the legacy behavior.
+///
 /// # Arguments
 ///
 /// * `df` - DataFrame containing the price data
@@ -12,6 +31,47 @@ use polars::prelude::*;
 ///
 /// Returns a PolarsResult containing the ATR Series
 pub fn calculate_atr(df: &DataFrame, window: usize) -> PolarsResult<Series> {
+    calculate_atr_with_seeding(df, window, AtrSeeding::default())
+}
+
+/// Calculates Average True Range (ATR) with an explicit start of the average
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data
+/// * `window` - Window size for ATR (typically 14)
+/// * `seeding` - Bar on which the average starts
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the ATR Series
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volatility::{calculate_atr_with_seeding, AtrSeeding};
+///
+/// let df = df! {
+///     "high" => [11.0, 12.0, 13.0],
+///     "low" => [9.0, 11.0, 12.0],
+///     "close" => [10.0, 11.5, 12.5],
+/// }
+/// .unwrap();
+///
+/// // True ranges 2.0 (high - low of the first bar), 2.0 and 1.5
+/// let atr = calculate_atr_with_seeding(&df, 2, AtrSeeding::SecondBar).unwrap();
+/// assert!(atr.f64().unwrap().get(1).unwrap().is_nan());
+/// assert_eq!(atr.f64().unwrap().get(2), Some(1.75));
+///
+/// let legacy = calculate_atr_with_seeding(&df, 2, AtrSeeding::Legacy).unwrap();
+/// assert_eq!(legacy.f64().unwrap().get(1), Some(2.0));
+/// ```
+pub fn calculate_atr_with_seeding(
+    df: &DataFrame,
+    window: usize,
+    seeding: AtrSeeding,
+) -> PolarsResult<Series> {
     check_window_size(df, window, "ATR")?;
 
     let high = df.column("high")?.f64()?.clone().into_series();
@@ -21,10 +81,14 @@ pub fn calculate_atr(df: &DataFrame, window: usize) -> PolarsResult<Series> {
     let prev_close = close.shift(1);
     let mut tr_values = Vec::with_capacity(df.height());
 
-    let first_tr = {
-        let h = high.f64()?.get(0).unwrap_or(0.0);
-        let l = low.f64()?.get(0).unwrap_or(0.0);
-        h - l
+    // Under SecondBar the first true range is left undefined
+    let first_tr = match seeding {
+        AtrSeeding::Legacy => {
+            let h = high.f64()?.get(0).unwrap_or(0.0);
+            let l = low.f64()?.get(0).unwrap_or(0.0);
+            h - l
+        }
+        AtrSeeding::SecondBar => f64::NAN,
     };
     tr_values.push(first_tr);
 
@@ -42,25 +106,24 @@ pub fn calculate_atr(df: &DataFrame, window: usize) -> PolarsResult<Series> {
     }
 
     // Implement Wilder's smoothing for ATR
-    let mut atr_values = Vec::with_capacity(df.height());
-
-    // Fill with NaN for the first window-1 elements
-    for _ in 0..(window - 1) {
-        atr_values.push(f64::NAN);
+    let first = match seeding {
+        AtrSeeding::Legacy => 0,
+        AtrSeeding::SecondBar => 1,
+    };
+    let seed = first + window - 1;
+    let mut atr_values = vec![f64::NAN; df.height()];
+    if df.height() <= seed {
+        return Ok(Series::new("atr".into(), atr_values));
     }
 
-    // Initialize ATR with simple average of first window TR values
-    let mut atr = 0.0;
-    for &tr in tr_values.iter().take(window) {
-        atr += tr;
-    }
-    atr /= window as f64;
-    atr_values.push(atr);
+    // Initialize ATR with simple average of the first window TR values
+    let mut atr = tr_values[first..=seed].iter().sum::<f64>() / window as f64;
+    atr_values[seed] = atr;
 
     // Apply Wilder's smoothing formula: ATR(t) = ((window-1) * ATR(t-1) + TR(t)) / window
-    for &tr in tr_values.iter().skip(window) {
-        atr = ((window as f64 - 1.0) * atr + tr) / window as f64;
-        atr_values.push(atr);
+    for i in seed + 1..df.height() {
+        atr = ((window as f64 - 1.0) * atr + tr_values[i]) / window as f64;
+        atr_values[i] = atr;
     }
 
     Ok(Series::new("atr".into(), atr_values))
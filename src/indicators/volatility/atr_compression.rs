@@ -0,0 +1,75 @@
+use crate::error::TaError;
+use crate::indicators::volatility::calculate_atr;
+use crate::util::dataframe_utils::nan_values;
+use polars::prelude::*;
+
+/// Detects ATR compression: volatility contracting against its recent average
+///
+/// The ATR ratio is the ATR divided by its mean over the last `lookback`
+/// bars (including the current one); the market is compressed while the
+/// ratio is below `threshold`. Compressed, quiet markets rarely trend, so
+/// the flag can keep trend entries out until volatility expands again, or
+/// arm breakout entries that expect the expansion.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data with high, low, close columns
+/// * `atr_period` - ATR window (typically 14)
+/// * `lookback` - Number of ATR values averaged into the baseline
+/// * `threshold` - Ratio below which the market is compressed (e.g. 0.75)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with "atr_ratio" (NaN until
+/// `lookback` ATR values exist) and the boolean "atr_compression" columns
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volatility::calculate_atr_compression;
+///
+/// // Twenty bars four points wide, then ten bars one point wide
+/// let width: Vec<f64> = (0..30).map(|i| if i < 20 { 2.0 } else { 0.5 }).collect();
+/// let df = df! {
+///     "high" => width.iter().map(|w| 100.0 + w).collect::<Vec<_>>(),
+///     "low" => width.iter().map(|w| 100.0 - w).collect::<Vec<_>>(),
+///     "close" => vec![100.0; 30],
+/// }
+/// .unwrap();
+///
+/// let compression = calculate_atr_compression(&df, 3, 10, 0.75).unwrap();
+/// let flags = compression.column("atr_compression").unwrap().bool().unwrap();
+/// assert_eq!(flags.get(19), Some(false));
+/// assert_eq!(flags.get(29), Some(true));
+/// ```
+pub fn calculate_atr_compression(
+    df: &DataFrame,
+    atr_period: usize,
+    lookback: usize,
+    threshold: f64,
+) -> PolarsResult<DataFrame> {
+    if atr_period == 0 || lookback == 0 {
+        return Err(TaError::invalid_parameter(
+            "atr_period",
+            "ATR period and lookback must be positive",
+        )
+        .into());
+    }
+
+    let atr = nan_values(calculate_atr(df, atr_period)?.f64()?);
+
+    let mut ratio = vec![f64::NAN; df.height()];
+    for i in lookback.saturating_sub(1)..df.height() {
+        let baseline = atr[i + 1 - lookback..=i].iter().sum::<f64>() / lookback as f64;
+        if baseline > 0.0 {
+            ratio[i] = atr[i] / baseline;
+        }
+    }
+    let compression: Vec<bool> = ratio.iter().map(|r| *r < threshold).collect();
+
+    DataFrame::new(vec![
+        Series::new("atr_ratio".into(), ratio).into(),
+        Series::new("atr_compression".into(), compression).into(),
+    ])
+}
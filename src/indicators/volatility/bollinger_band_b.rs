@@ -19,7 +19,8 @@ pub fn calculate_bb_b(
     num_std: f64,
     column: &str,
 ) -> PolarsResult<Series> {
-    let (_, bb_upper, bb_lower) = calculate_bollinger_bands(df, window, num_std, column)?;
+    let bands = calculate_bollinger_bands(df, window, num_std, column)?;
+    let (bb_upper, bb_lower) = (bands.upper, bands.lower);
 
     let close = df.column(column)?.f64()?;
 
@@ -1,8 +1,45 @@
 use crate::util::dataframe_utils::check_window_size;
 use polars::prelude::*;
 
+/// Bollinger Bands with optional %B and bandwidth
+#[derive(Debug, Clone)]
+pub struct BollingerBandsOutput {
+    /// SMA of the column, named "bb_middle"
+    pub middle: Series,
+    /// Middle band plus `num_std` standard deviations, named "bb_upper"
+    pub upper: Series,
+    /// Middle band minus `num_std` standard deviations, named "bb_lower"
+    pub lower: Series,
+    /// Position of the price between the bands, (price - lower) / (upper -
+    /// lower), named "bb_percent_b": 0 at the lower band, 1 at the upper
+    pub percent_b: Option<Series>,
+    /// Band width relative to the middle band, (upper - lower) / middle,
+    /// named "bb_bandwidth"
+    pub bandwidth: Option<Series>,
+}
+
+/// Which standard deviation widens the Bollinger Bands
+///
+/// See CHANGELOG.md for the versions in which the default changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BollingerDeviation {
+    /// Behavior up to 1.0.8: the sample standard deviation (divides by
+    /// `window - 1`)
+    Legacy,
+    /// The population standard deviation (divides by `window`), as in
+    /// Bollinger's definition and TA-Lib
+    #[default]
+    Population,
+}
+
 /// Calculates Bollinger Bands
 ///
+/// The bands are 0.0 until the first full window and widened by the
+/// population standard deviation ([`BollingerDeviation::Population`]); use
+/// [`calculate_bollinger_bands_with_deviation`] to reproduce the legacy
+/// behavior. The returned %B and bandwidth are `None`; use
+/// [`calculate_bollinger_bands_with_metrics`] to get them as well.
+///
 /// # Arguments
 ///
 /// * `df` - DataFrame containing the price data
@@ -12,13 +49,79 @@ use polars::prelude::*;
 ///
 /// # Returns
 ///
-/// Returns a PolarsResult containing a tuple of (middle, upper, lower) bands
+/// Returns a PolarsResult containing the middle, upper and lower bands
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volatility::calculate_bollinger_bands;
+///
+/// let df = df! { "close" => [1.0, 3.0, 1.0, 3.0] }.unwrap();
+/// let bands = calculate_bollinger_bands(&df, 2, 2.0, "close").unwrap();
+/// assert_eq!(bands.middle.f64().unwrap().get(3), Some(2.0));
+/// assert_eq!(bands.upper.f64().unwrap().get(3), Some(4.0));
+/// assert_eq!(bands.lower.f64().unwrap().get(3), Some(0.0));
+/// assert!(bands.percent_b.is_none());
+/// ```
 pub fn calculate_bollinger_bands(
     df: &DataFrame,
     window: usize,
     num_std: f64,
     column: &str,
-) -> PolarsResult<(Series, Series, Series)> {
+) -> PolarsResult<BollingerBandsOutput> {
+    calculate_bollinger_bands_with_deviation(
+        df,
+        window,
+        num_std,
+        column,
+        BollingerDeviation::default(),
+    )
+}
+
+/// Calculates Bollinger Bands with an explicit standard deviation
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data
+/// * `window` - Window size for the SMA (typically 20)
+/// * `num_std` - Number of standard deviations (typically 2.0)
+/// * `column` - Column name to use for calculations (default "close")
+/// * `deviation` - Population or sample standard deviation
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the middle, upper and lower bands
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volatility::{
+///     calculate_bollinger_bands_with_deviation, BollingerDeviation,
+/// };
+///
+/// let df = df! { "close" => [1.0, 3.0] }.unwrap();
+///
+/// let bands =
+///     calculate_bollinger_bands_with_deviation(&df, 2, 1.0, "close", BollingerDeviation::Population)
+///         .unwrap();
+/// assert_eq!(bands.upper.f64().unwrap().get(1), Some(3.0));
+///
+/// // The sample standard deviation of 1.0 and 3.0 is the square root of 2
+/// let legacy =
+///     calculate_bollinger_bands_with_deviation(&df, 2, 1.0, "close", BollingerDeviation::Legacy)
+///         .unwrap();
+/// let upper = legacy.upper.f64().unwrap().get(1).unwrap();
+/// assert!((upper - (2.0 + 2f64.sqrt())).abs() < 1e-12);
+/// ```
+pub fn calculate_bollinger_bands_with_deviation(
+    df: &DataFrame,
+    window: usize,
+    num_std: f64,
+    column: &str,
+    deviation: BollingerDeviation,
+) -> PolarsResult<BollingerBandsOutput> {
     check_window_size(df, window, "Bollinger Bands")?;
 
     let series = df.column(column)?.f64()?.clone().into_series();
@@ -31,12 +134,16 @@ pub fn calculate_bollinger_bands(
         fn_params: None,
     })?;
 
+    let ddof = match deviation {
+        BollingerDeviation::Legacy => 1,
+        BollingerDeviation::Population => 0,
+    };
     let std = series.rolling_std(RollingOptionsFixedWindow {
         window_size: window,
         min_periods: window,
         center: false,
         weights: None,
-        fn_params: None,
+        fn_params: Some(RollingFnParams::Var(RollingVarParams { ddof })),
     })?;
 
     let mut upper_band = Vec::with_capacity(series.len());
@@ -50,9 +157,71 @@ pub fn calculate_bollinger_bands(
         lower_band.push(ma - num_std * std_val);
     }
 
-    Ok((
-        sma.with_name("bb_middle".into()),
-        Series::new("bb_upper".into(), upper_band),
-        Series::new("bb_lower".into(), lower_band),
-    ))
+    Ok(BollingerBandsOutput {
+        middle: sma.with_name("bb_middle".into()),
+        upper: Series::new("bb_upper".into(), upper_band),
+        lower: Series::new("bb_lower".into(), lower_band),
+        percent_b: None,
+        bandwidth: None,
+    })
+}
+
+/// Calculates Bollinger Bands together with %B and bandwidth
+///
+/// %B and bandwidth are NaN until the first full window and where the bands
+/// have no width.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price data
+/// * `window` - Window size for the SMA (typically 20)
+/// * `num_std` - Number of standard deviations (typically 2.0)
+/// * `column` - Column name to use for calculations (default "close")
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the bands with `percent_b` and
+/// `bandwidth` set
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volatility::calculate_bollinger_bands_with_metrics;
+///
+/// let df = df! { "close" => [1.0, 3.0, 1.0, 3.0] }.unwrap();
+/// let bands = calculate_bollinger_bands_with_metrics(&df, 2, 2.0, "close").unwrap();
+/// // 3.0 sits three quarters of the way from 0.0 to 4.0
+/// assert_eq!(bands.percent_b.unwrap().f64().unwrap().get(3), Some(0.75));
+/// assert_eq!(bands.bandwidth.unwrap().f64().unwrap().get(3), Some(2.0));
+/// ```
+pub fn calculate_bollinger_bands_with_metrics(
+    df: &DataFrame,
+    window: usize,
+    num_std: f64,
+    column: &str,
+) -> PolarsResult<BollingerBandsOutput> {
+    let mut bands = calculate_bollinger_bands(df, window, num_std, column)?;
+
+    let price = df.column(column)?.f64()?.clone();
+    let (middle, upper, lower) = (bands.middle.f64()?, bands.upper.f64()?, bands.lower.f64()?);
+    let mut percent_b = vec![f64::NAN; df.height()];
+    let mut bandwidth = vec![f64::NAN; df.height()];
+
+    for i in 0..df.height() {
+        let (Some(m), Some(u), Some(l)) = (middle.get(i), upper.get(i), lower.get(i)) else {
+            continue;
+        };
+        let width = u - l;
+        if width > 0.0 {
+            percent_b[i] = (price.get(i).unwrap_or(f64::NAN) - l) / width;
+            if m != 0.0 {
+                bandwidth[i] = width / m;
+            }
+        }
+    }
+
+    bands.percent_b = Some(Series::new("bb_percent_b".into(), percent_b));
+    bands.bandwidth = Some(Series::new("bb_bandwidth".into(), bandwidth));
+    Ok(bands)
 }
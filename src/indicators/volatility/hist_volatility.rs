@@ -23,7 +23,7 @@ use polars::prelude::*;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::indicators::volatility::calculate_hist_volatility;
+/// use rustalib::indicators::volatility::calculate_hist_volatility;
 ///
 /// let close = Series::new("close".into(), &[100.0, 102.0, 104.0, 103.0, 105.0, 107.0]);
 /// let df = DataFrame::new(vec![close.into()]).unwrap();
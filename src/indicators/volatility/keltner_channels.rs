@@ -1,7 +1,85 @@
-use crate::indicators::volatility::calculate_atr;
-use crate::util::dataframe_utils::check_window_size;
+use crate::error::TaError;
+use crate::indicators::moving_averages::calculate_sma;
+use crate::indicators::naming::indicator_name;
+use crate::indicators::volatility::{calculate_atr, calculate_trange};
+use crate::util::dataframe_utils::{check_window_size, column_nan_values, nan_values};
 use polars::prelude::*;
 
+/// Moving average forming the middle of a Keltner Channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeltnerBasis {
+    /// EMA of the close seeded with the SMA of the first `window` closes
+    #[default]
+    Ema,
+    /// SMA of the close
+    Sma,
+}
+
+/// Volatility measure setting the distance of the bands from the middle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeltnerWidth {
+    /// Wilder's Average True Range
+    #[default]
+    Atr,
+    /// EMA of the true range, seeded like the EMA basis; reacts faster to
+    /// volatility changes than Wilder's smoothing
+    TrEma,
+}
+
+/// Parameters for [`calculate_keltner_channels_with_config`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeltnerChannelConfig {
+    /// Window of the middle band and of the width measure
+    pub window: usize,
+    /// Middle band average
+    pub basis: KeltnerBasis,
+    /// Width measure
+    pub width: KeltnerWidth,
+    /// Band distances from the middle in units of the width, one upper and
+    /// lower band each
+    pub multipliers: Vec<f64>,
+}
+
+impl Default for KeltnerChannelConfig {
+    fn default() -> Self {
+        Self {
+            window: 20,
+            basis: KeltnerBasis::Ema,
+            width: KeltnerWidth::Atr,
+            multipliers: vec![2.0],
+        }
+    }
+}
+
+/// Upper and lower band for one multiplier
+#[derive(Debug, Clone)]
+pub struct KeltnerBand {
+    /// Distance of the bands from the middle, in units of the width
+    pub multiplier: f64,
+    /// Middle band + multiplier * width
+    pub upper: Series,
+    /// Middle band - multiplier * width
+    pub lower: Series,
+}
+
+/// Keltner Channels sharing one middle band and width
+#[derive(Debug, Clone)]
+pub struct KeltnerBandSet {
+    /// Middle band, named "keltner_middle"
+    pub middle: Series,
+    /// ATR or true range EMA the bands are scaled by, named "keltner_width"
+    pub width: Series,
+    /// One entry per configured multiplier, in order
+    pub bands: Vec<KeltnerBand>,
+}
+
+impl KeltnerBandSet {
+    /// The bands for `multiplier`, if it was configured
+    pub fn band(&self, multiplier: f64) -> Option<&KeltnerBand> {
+        self.bands.iter().find(|band| band.multiplier == multiplier)
+    }
+}
+
 /// Calculates Keltner Channels
 ///
 /// Keltner Channels are volatility-based bands that surround the price of an asset.
@@ -10,6 +88,9 @@ use polars::prelude::*;
 /// - Upper band: Middle band + (multiplier * Average True Range)
 /// - Lower band: Middle band - (multiplier * Average True Range)
 ///
+/// See [`calculate_keltner_channels_with_config`] for an SMA middle band, a
+/// true range EMA width or several multipliers at once.
+///
 /// # Arguments
 ///
 /// * `df` - DataFrame containing the price data (must include 'high', 'low', 'close' columns)
@@ -27,7 +108,7 @@ use polars::prelude::*;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::indicators::volatility::calculate_keltner_channels;
+/// use rustalib::indicators::volatility::calculate_keltner_channels;
 ///
 /// // Create example data
 /// let open = Series::new("open".into(), &[10.0, 10.5, 11.0, 11.5, 12.0]);
@@ -46,8 +127,90 @@ pub fn calculate_keltner_channels(
     window: usize,
     multiplier: f64,
 ) -> PolarsResult<DataFrame> {
-    // Check window size
-    check_window_size(df, window, "Keltner Channels")?;
+    let config = KeltnerChannelConfig {
+        window,
+        multipliers: vec![multiplier],
+        ..KeltnerChannelConfig::default()
+    };
+    let mut set = calculate_keltner_channels_with_config(df, &config)?;
+    let band = set.bands.remove(0);
+
+    DataFrame::new(vec![
+        band.upper.with_name("keltner_upper".into()).into(),
+        set.middle.into(),
+        band.lower.with_name("keltner_lower".into()).into(),
+    ])
+}
+
+/// Calculates a set of Keltner Channels in one pass
+///
+/// The middle band and the width are computed once and scaled by every
+/// multiplier, so squeeze detection or channel breakouts at several
+/// distances don't recompute the channel.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing "high", "low" and "close" columns
+/// * `config` - Window, middle band basis, width measure and multipliers
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a [`KeltnerBandSet`]. The bands are
+/// named "keltner_upper_{m}" and "keltner_lower_{m}", where `m` is written
+/// with an underscore for the decimal point (1.5 becomes "1_5"). Values are
+/// NaN until both the middle band and the width are defined.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::moving_averages::calculate_sma;
+/// use rustalib::indicators::volatility::{
+///     calculate_keltner_channels_with_config, KeltnerBasis, KeltnerChannelConfig, KeltnerWidth,
+/// };
+///
+/// let close: Vec<f64> = (0..30).map(|i| 100.0 + (i % 5) as f64).collect();
+/// let df = df! {
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+/// }
+/// .unwrap();
+///
+/// let config = KeltnerChannelConfig {
+///     window: 10,
+///     basis: KeltnerBasis::Sma,
+///     width: KeltnerWidth::TrEma,
+///     multipliers: vec![1.0, 1.5, 2.0],
+/// };
+/// let set = calculate_keltner_channels_with_config(&df, &config).unwrap();
+/// assert_eq!(set.bands.len(), 3);
+///
+/// let sma = calculate_sma(&df, "close", 10).unwrap();
+/// assert_eq!(set.middle.f64().unwrap().get(20), sma.f64().unwrap().get(20));
+///
+/// let wide = set.band(1.5).unwrap();
+/// assert_eq!(wide.upper.name().as_str(), "keltner_upper_1_5");
+/// let middle = set.middle.f64().unwrap().get(20).unwrap();
+/// let width = set.width.f64().unwrap().get(20).unwrap();
+/// let upper = wide.upper.f64().unwrap().get(20).unwrap();
+/// assert!((upper - (middle + 1.5 * width)).abs() < 1e-9);
+/// ```
+pub fn calculate_keltner_channels_with_config(
+    df: &DataFrame,
+    config: &KeltnerChannelConfig,
+) -> PolarsResult<KeltnerBandSet> {
+    check_window_size(df, config.window, "Keltner Channels")?;
+    if config.multipliers.is_empty() {
+        return Err(TaError::invalid_parameter("multipliers", "must not be empty").into());
+    }
+    if config
+        .multipliers
+        .iter()
+        .any(|m| !(m.is_finite() && *m > 0.0))
+    {
+        return Err(TaError::invalid_parameter("multipliers", "must be positive").into());
+    }
 
     // Check required columns
     if !df.schema().contains("high")
@@ -59,78 +222,161 @@ pub fn calculate_keltner_channels(
         ));
     }
 
-    // Calculate the middle band (EMA of close)
-    let close = df.column("close")?.f64()?;
+    let middle = match config.basis {
+        KeltnerBasis::Ema => seeded_ema(&column_nan_values(df, "close")?, config.window),
+        KeltnerBasis::Sma => nan_values(calculate_sma(df, "close", config.window)?.f64()?),
+    };
+    let width = match config.width {
+        KeltnerWidth::Atr => nan_values(calculate_atr(df, config.window)?.f64()?),
+        KeltnerWidth::TrEma => seeded_ema(&nan_values(calculate_trange(df)?.f64()?), config.window),
+    };
+
+    let bands = config
+        .multipliers
+        .iter()
+        .map(|&multiplier| {
+            let band = |sign: f64| -> Vec<f64> {
+                middle
+                    .iter()
+                    .zip(&width)
+                    .map(|(mid, w)| {
+                        if mid.is_nan() || w.is_nan() {
+                            f64::NAN
+                        } else {
+                            mid + sign * multiplier * w
+                        }
+                    })
+                    .collect()
+            };
+            let m = multiplier.to_string().replace('.', "_");
+            KeltnerBand {
+                multiplier,
+                upper: Series::new(indicator_name("keltner_upper", &[&m]).into(), band(1.0)),
+                lower: Series::new(indicator_name("keltner_lower", &[&m]).into(), band(-1.0)),
+            }
+        })
+        .collect();
 
-    // Calculate EMA
-    let mut middle_band = Vec::with_capacity(df.height());
+    Ok(KeltnerBandSet {
+        middle: Series::new("keltner_middle".into(), middle),
+        width: Series::new("keltner_width".into(), width),
+        bands,
+    })
+}
+
+/// EMA seeded with the mean of the valid values among the first `window`,
+/// NaN before the seed and on missing values
+fn seeded_ema(values: &[f64], window: usize) -> Vec<f64> {
     let smoothing_factor = 2.0 / (window as f64 + 1.0);
 
     // Initialize with SMA for first window elements
-    let mut sum = 0.0;
-    let mut count = 0;
-    for i in 0..window.min(df.height()) {
-        let val = close.get(i).unwrap_or(f64::NAN);
-        if !val.is_nan() {
-            sum += val;
-            count += 1;
-        }
-    }
-
-    let first_ema = if count > 0 {
-        sum / count as f64
-    } else {
+    let seed: Vec<f64> = values
+        .iter()
+        .take(window)
+        .copied()
+        .filter(|v| !v.is_nan())
+        .collect();
+    let first_ema = if seed.is_empty() {
         f64::NAN
+    } else {
+        seed.iter().sum::<f64>() / seed.len() as f64
     };
 
     // Fill NaN for the first window-1 elements
-    for _ in 0..(window - 1) {
-        middle_band.push(f64::NAN);
-    }
-
-    middle_band.push(first_ema);
+    let mut ema = vec![f64::NAN; window - 1];
+    ema.push(first_ema);
 
     // Calculate EMA for the rest of the data
     let mut prev_ema = first_ema;
-    for i in window..df.height() {
-        let close_val = close.get(i).unwrap_or(f64::NAN);
-
-        if !close_val.is_nan() && !prev_ema.is_nan() {
-            let ema = close_val * smoothing_factor + prev_ema * (1.0 - smoothing_factor);
-            middle_band.push(ema);
-            prev_ema = ema;
+    for &value in &values[window..] {
+        if !value.is_nan() && !prev_ema.is_nan() {
+            prev_ema = value * smoothing_factor + prev_ema * (1.0 - smoothing_factor);
+            ema.push(prev_ema);
         } else {
-            middle_band.push(f64::NAN);
+            ema.push(f64::NAN);
         }
     }
+    ema
+}
 
-    // Calculate ATR
-    let atr = calculate_atr(df, window)?;
-
-    // Calculate upper and lower bands
-    let mut upper_band = Vec::with_capacity(df.height());
-    let mut lower_band = Vec::with_capacity(df.height());
-
-    for (i, mid) in middle_band.iter().enumerate().take(df.height()) {
-        let atr_val = atr.f64()?.get(i).unwrap_or(f64::NAN);
-
-        if !mid.is_nan() && !atr_val.is_nan() {
-            upper_band.push(mid + multiplier * atr_val);
-            lower_band.push(mid - multiplier * atr_val);
-        } else {
-            upper_band.push(f64::NAN);
-            lower_band.push(f64::NAN);
-        }
-    }
+/// Calculates a Keltner Channel trend filter
+///
+/// The filter turns to 1.0 when the close ends a bar above the upper
+/// Keltner Channel and to -1.0 when it ends below the lower one. It holds
+/// that state until the close crosses back through the middle band, then
+/// reads 0.0 until the next breakout. Use it to take long entries only
+/// while it is 1.0 and short entries only while it is -1.0.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing "high", "low" and "close" columns
+/// * `window` - Window size for the channel's EMA and ATR (typically 20)
+/// * `multiplier` - Band distance from the middle, in ATRs (typically 2.0)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named "keltner_trend_{window}",
+/// NaN until the channel is defined
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volatility::calculate_keltner_trend;
+///
+/// // A flat market breaks out upwards, then falls back below its average
+/// let close: Vec<f64> = [vec![100.0; 20], vec![110.0, 112.0, 111.0], vec![90.0]].concat();
+/// let df = df! {
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+/// }
+/// .unwrap();
+///
+/// let trend = calculate_keltner_trend(&df, 10, 2.0).unwrap();
+/// let trend = trend.f64().unwrap();
+/// assert_eq!(trend.get(19), Some(0.0));
+/// assert_eq!(trend.get(20), Some(1.0));
+/// assert_eq!(trend.get(22), Some(1.0));
+/// assert_eq!(trend.get(23), Some(-1.0));
+/// ```
+pub fn calculate_keltner_trend(
+    df: &DataFrame,
+    window: usize,
+    multiplier: f64,
+) -> PolarsResult<Series> {
+    let channels = calculate_keltner_channels(df, window, multiplier)?;
+    let upper = channels.column("keltner_upper")?.f64()?;
+    let middle = channels.column("keltner_middle")?.f64()?;
+    let lower = channels.column("keltner_lower")?.f64()?;
+    let close = df.column("close")?.f64()?;
 
-    // Create the result DataFrame
-    let middle_series = Series::new("keltner_middle".into(), middle_band);
-    let upper_series = Series::new("keltner_upper".into(), upper_band);
-    let lower_series = Series::new("keltner_lower".into(), lower_band);
+    let mut state = 0.0;
+    let trend: Vec<f64> = (0..df.height())
+        .map(|i| {
+            let (Some(c), Some(u), Some(m), Some(l)) =
+                (close.get(i), upper.get(i), middle.get(i), lower.get(i))
+            else {
+                return f64::NAN;
+            };
+            if c.is_nan() || u.is_nan() || m.is_nan() || l.is_nan() {
+                return f64::NAN;
+            }
+            state = if c > u {
+                1.0
+            } else if c < l {
+                -1.0
+            } else if (state > 0.0 && c < m) || (state < 0.0 && c > m) {
+                0.0
+            } else {
+                state
+            };
+            state
+        })
+        .collect();
 
-    DataFrame::new(vec![
-        upper_series.into(),
-        middle_series.into(),
-        lower_series.into(),
-    ])
+    Ok(Series::new(
+        indicator_name("keltner_trend", &[&window]).into(),
+        trend,
+    ))
 }
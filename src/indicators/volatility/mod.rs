@@ -1,6 +1,7 @@
 // Volatility indicators module
 
 pub mod atr;
+pub mod atr_compression;
 pub mod bollinger_band_b;
 pub mod bollinger_bands;
 pub mod donchian_channels;
@@ -13,6 +14,7 @@ pub mod trange;
 
 // Re-export indicators
 pub use atr::*;
+pub use atr_compression::*;
 pub use bollinger_band_b::*;
 pub use bollinger_bands::*;
 pub use donchian_channels::calculate_donchian_channels;
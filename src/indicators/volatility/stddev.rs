@@ -1,4 +1,4 @@
-use crate::util::dataframe_utils::check_window_size;
+use crate::util::dataframe_utils::{check_window_size, fixed_window, nan_to_null, rolling_values};
 use polars::prelude::*;
 
 /// Calculates Standard Deviation (StdDev) of a series over a window
@@ -17,7 +17,7 @@ use polars::prelude::*;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::indicators::volatility::calculate_stddev;
+/// use rustalib::indicators::volatility::calculate_stddev;
 ///
 /// let close = Series::new("close".into(), &[10.0, 11.0, 12.0, 9.0, 8.0, 10.0]);
 /// let df = DataFrame::new(vec![close.into()]).unwrap();
@@ -39,41 +39,16 @@ pub fn calculate_stddev(df: &DataFrame, window: usize, column: &str) -> PolarsRe
         ));
     }
 
-    // Get the column to calculate StdDev on
-    let col = df.column(column)?.f64()?;
-
-    // Calculate rolling standard deviation
-    let mut stddev_values = Vec::with_capacity(df.height());
-
-    // Fill NaN for the first window-1 elements
-    for _ in 0..(window - 1) {
-        stddev_values.push(f64::NAN);
-    }
-
-    // Calculate StdDev for each window
-    for i in (window - 1)..df.height() {
-        let mut sum = 0.0;
-        let mut sum_sq = 0.0;
-        let mut count = 0;
-
-        for j in (i - window + 1)..=i {
-            let val = col.get(j).unwrap_or(f64::NAN);
-            if !val.is_nan() {
-                sum += val;
-                sum_sq += val * val;
-                count += 1;
-            }
-        }
-
-        if count > 1 {
-            let mean = sum / count as f64;
-            let variance = sum_sq / count as f64 - mean * mean;
-            let stddev = if variance > 0.0 { variance.sqrt() } else { 0.0 };
-            stddev_values.push(stddev);
-        } else {
-            stddev_values.push(f64::NAN);
-        }
-    }
-
-    Ok(Series::new("stddev".into(), stddev_values))
+    // Population standard deviation of the non-NaN values in each window,
+    // NaN when a window holds fewer than two of them
+    let values = nan_to_null(df.column(column)?.f64()?);
+    let stddev = values.rolling_std(RollingOptionsFixedWindow {
+        fn_params: Some(RollingFnParams::Var(RollingVarParams { ddof: 0 })),
+        ..fixed_window(window, 2)
+    })?;
+
+    Ok(Series::new(
+        "stddev".into(),
+        rolling_values(&stddev, window - 1)?,
+    ))
 }
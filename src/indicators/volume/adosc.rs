@@ -0,0 +1,87 @@
+use crate::indicators::naming::indicator_name;
+use crate::indicators::volume::calculate_adl;
+use crate::util::dataframe_utils::check_window_size;
+use polars::prelude::*;
+
+/// Calculates the Chaikin A/D Oscillator (ADOSC)
+///
+/// The difference between a fast and a slow EMA of the Accumulation/
+/// Distribution Line ([`calculate_adl`]). It turns positive when money flows
+/// into the instrument faster than its longer-run pace. As in TA-Lib, both
+/// EMAs start from the first ADL value rather than from an SMA seed.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing "high", "low", "close" and "volume" columns
+/// * `fast_period` - Fast EMA period (typically 3)
+/// * `slow_period` - Slow EMA period (typically 10)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named
+/// "adosc_{fast}_{slow}", NaN for the first `max(fast, slow) - 1` bars
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volume::calculate_adosc;
+///
+/// // Closing on the high accumulates, so the fast EMA leads the slow one
+/// let close: Vec<f64> = (0..20).map(|i| 100.0 + i as f64).collect();
+/// let df = df! {
+///     "high" => &close,
+///     "low" => close.iter().map(|c| c - 2.0).collect::<Vec<_>>(),
+///     "close" => &close,
+///     "volume" => vec![1000.0; 20],
+/// }
+/// .unwrap();
+///
+/// let adosc = calculate_adosc(&df, 3, 10).unwrap();
+/// let adosc = adosc.f64().unwrap();
+/// assert!(adosc.get(8).unwrap().is_nan());
+/// assert!(adosc.get(9).unwrap() > 0.0);
+/// ```
+pub fn calculate_adosc(
+    df: &DataFrame,
+    fast_period: usize,
+    slow_period: usize,
+) -> PolarsResult<Series> {
+    if fast_period == 0 || slow_period == 0 {
+        return Err(PolarsError::ComputeError(
+            "ADOSC periods must be greater than 0".into(),
+        ));
+    }
+    let lookback = fast_period.max(slow_period) - 1;
+    check_window_size(df, lookback + 1, "ADOSC")?;
+
+    let adl = calculate_adl(df, "high", "low", "close", "volume")?;
+    let adl = adl.f64()?;
+
+    let fast_k = 2.0 / (fast_period as f64 + 1.0);
+    let slow_k = 2.0 / (slow_period as f64 + 1.0);
+    let mut fast_ema = adl.get(0).unwrap_or(f64::NAN);
+    let mut slow_ema = fast_ema;
+
+    let adosc: Vec<f64> = adl
+        .iter()
+        .enumerate()
+        .map(|(i, ad)| {
+            if i > 0 {
+                let ad = ad.unwrap_or(f64::NAN);
+                fast_ema = fast_k * ad + (1.0 - fast_k) * fast_ema;
+                slow_ema = slow_k * ad + (1.0 - slow_k) * slow_ema;
+            }
+            if i < lookback {
+                f64::NAN
+            } else {
+                fast_ema - slow_ema
+            }
+        })
+        .collect();
+
+    Ok(Series::new(
+        indicator_name("adosc", &[&fast_period, &slow_period]).into(),
+        adosc,
+    ))
+}
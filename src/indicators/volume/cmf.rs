@@ -1,3 +1,4 @@
+use crate::indicators::naming::indicator_name;
 use polars::prelude::*;
 
 /// Calculates the Chaikin Money Flow (CMF) indicator
@@ -24,13 +25,19 @@ use polars::prelude::*;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::indicators::volume::calculate_cmf;
+/// use rustalib::indicators::volume::calculate_cmf;
 ///
-/// // Create or load a DataFrame with OHLCV data
-/// let df = DataFrame::default(); // Replace with actual data
+/// // Closing on the high puts all volume on the buying side
+/// let df = df! {
+///     "high" => [11.0, 12.0, 13.0, 14.0],
+///     "low" => [9.0, 10.0, 11.0, 12.0],
+///     "close" => [11.0, 12.0, 13.0, 14.0],
+///     "volume" => [100.0, 200.0, 150.0, 300.0],
+/// }
+/// .unwrap();
 ///
-/// // Calculate CMF with period 20
-/// let cmf = calculate_cmf(&df, 20).unwrap();
+/// let cmf = calculate_cmf(&df, 3).unwrap();
+/// assert_eq!(cmf.f64().unwrap().get(3), Some(1.0));
 /// ```
 pub fn calculate_cmf(df: &DataFrame, window: usize) -> PolarsResult<Series> {
     // Validate that necessary columns exist
@@ -123,5 +130,8 @@ pub fn calculate_cmf(df: &DataFrame, window: usize) -> PolarsResult<Series> {
     }
 
     // Return the CMF as a Polars Series
-    Ok(Series::new(format!("cmf_{}", window).into(), cmf_values))
+    Ok(Series::new(
+        indicator_name("cmf", &[&window]).into(),
+        cmf_values,
+    ))
 }
@@ -0,0 +1,73 @@
+use crate::indicators::moving_averages::calculate_ema;
+use crate::indicators::naming::indicator_name;
+use polars::prelude::*;
+
+/// Calculates Elder's Force Index
+///
+/// The raw force of a bar is its close-to-close change times its volume:
+/// large moves on heavy volume push it furthest from zero. The index is the
+/// EMA of the raw force; a window of 1 returns the raw force itself.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing "close" and "volume" columns
+/// * `window` - EMA period (typically 13, or 2 for short-term entries)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named "force_index_{window}",
+/// NaN until the first bar with a previous close and `window` raw values
+///
+/// # Formula
+///
+/// 1. Raw force = (close - previous close) * volume
+/// 2. Force Index = EMA(raw force, window)
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volume::calculate_force_index;
+///
+/// let df = df! {
+///     "close" => [10.0, 11.0, 10.5, 12.0],
+///     "volume" => [100.0, 200.0, 400.0, 100.0],
+/// }
+/// .unwrap();
+///
+/// let force = calculate_force_index(&df, 1).unwrap();
+/// let force = force.f64().unwrap();
+/// assert!(force.get(0).unwrap().is_nan());
+/// assert_eq!(force.get(1), Some(200.0));
+/// assert_eq!(force.get(2), Some(-200.0));
+/// assert_eq!(force.get(3), Some(150.0));
+/// ```
+pub fn calculate_force_index(df: &DataFrame, window: usize) -> PolarsResult<Series> {
+    if window == 0 {
+        return Err(PolarsError::ComputeError(
+            "Window size must be greater than 0".into(),
+        ));
+    }
+
+    let close = df.column("close")?.f64()?;
+    let volume = df.column("volume")?.f64()?;
+
+    let raw: Vec<f64> = (0..df.height())
+        .map(|i| {
+            match (
+                i.checked_sub(1).and_then(|p| close.get(p)),
+                close.get(i),
+                volume.get(i),
+            ) {
+                (Some(prev), Some(curr), Some(vol)) => (curr - prev) * vol,
+                _ => f64::NAN,
+            }
+        })
+        .collect();
+
+    // The EMA skips the undefined first bar and seeds on the next `window`
+    let temp_df = DataFrame::new(vec![Series::new("force".into(), raw).into()])?;
+    let force = calculate_ema(&temp_df, "force", window)?;
+
+    Ok(force.with_name(indicator_name("force_index", &[&window]).into()))
+}
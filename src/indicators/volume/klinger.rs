@@ -0,0 +1,147 @@
+use crate::indicators::moving_averages::calculate_ema;
+use crate::indicators::naming::indicator_name;
+use crate::util::dataframe_utils::check_window_size;
+use polars::prelude::*;
+use std::fmt::Display;
+
+/// Calculates the Klinger Volume Oscillator (KVO) and its signal line
+///
+/// Each bar's volume is signed by the direction of the typical price sum
+/// (high + low + close) and scaled by how the bar's range compares with the
+/// cumulative range of the current trend, giving the volume force. The
+/// oscillator is the difference of a fast and a slow EMA of the volume
+/// force; the signal line is an EMA of the oscillator.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing "high", "low", "close" and "volume" columns
+/// * `fast_period` - Fast EMA period (typically 34)
+/// * `slow_period` - Slow EMA period (typically 55)
+/// * `signal_period` - Signal EMA period (typically 13)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing `(kvo, signal)` Series named
+/// "kvo_{fast}_{slow}" and "kvo_signal_{fast}_{slow}_{signal}". Both are
+/// null until their EMAs are seeded.
+///
+/// # Formula
+///
+/// 1. Trend = +1 when high + low + close rises from the previous bar, else -1
+/// 2. dm = high - low
+/// 3. cm = previous cm + dm while the trend holds, previous dm + dm when it flips
+/// 4. Volume force = volume * |2 * (dm / cm - 1)| * trend * 100
+/// 5. KVO = EMA(volume force, fast) - EMA(volume force, slow)
+/// 6. Signal = EMA(KVO, signal)
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volume::calculate_klinger;
+///
+/// // Volume grows through a steady advance, so the fast EMA of the volume
+/// // force stays above the slow one
+/// let close: Vec<f64> = (0..40).map(|i| 100.0 + i as f64).collect();
+/// let df = df! {
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+///     "volume" => (0..40).map(|i| 1000.0 + 100.0 * i as f64).collect::<Vec<_>>(),
+/// }
+/// .unwrap();
+///
+/// let (kvo, signal) = calculate_klinger(&df, 5, 10, 3).unwrap();
+/// assert_eq!(kvo.name().as_str(), "kvo_5_10");
+/// assert_eq!(kvo.f64().unwrap().get(9), None);
+/// assert!(kvo.f64().unwrap().get(39).unwrap() > 0.0);
+/// assert!(signal.f64().unwrap().get(39).unwrap() > 0.0);
+/// ```
+pub fn calculate_klinger(
+    df: &DataFrame,
+    fast_period: usize,
+    slow_period: usize,
+    signal_period: usize,
+) -> PolarsResult<(Series, Series)> {
+    if fast_period == 0 || slow_period == 0 || signal_period == 0 {
+        return Err(PolarsError::ComputeError(
+            "Klinger periods must be greater than 0".into(),
+        ));
+    }
+    check_window_size(df, slow_period.max(fast_period) + 1, "Klinger")?;
+
+    let high = df.column("high")?.f64()?;
+    let low = df.column("low")?.f64()?;
+    let close = df.column("close")?.f64()?;
+    let volume = df.column("volume")?.f64()?;
+
+    let mut force = vec![f64::NAN; df.height()];
+    let mut prev_sum = f64::NAN;
+    let mut prev_trend = 0.0;
+    let mut prev_dm = f64::NAN;
+    let mut cm = 0.0;
+
+    for (i, force) in force.iter_mut().enumerate() {
+        let h = high.get(i).unwrap_or(f64::NAN);
+        let l = low.get(i).unwrap_or(f64::NAN);
+        let c = close.get(i).unwrap_or(f64::NAN);
+        let v = volume.get(i).unwrap_or(f64::NAN);
+        let sum = h + l + c;
+        let dm = h - l;
+
+        if !prev_sum.is_nan() && !sum.is_nan() {
+            let trend = if sum > prev_sum { 1.0 } else { -1.0 };
+            cm = if trend == prev_trend {
+                cm + dm
+            } else {
+                prev_dm + dm
+            };
+            // A trend without any range carries no force
+            *force = if cm == 0.0 {
+                0.0
+            } else {
+                v * (2.0 * (dm / cm - 1.0)).abs() * trend * 100.0
+            };
+            prev_trend = trend;
+        }
+
+        prev_sum = sum;
+        prev_dm = dm;
+    }
+
+    let temp_df = DataFrame::new(vec![Series::new("force".into(), force).into()])?;
+    let fast = calculate_ema(&temp_df, "force", fast_period)?;
+    let slow = calculate_ema(&temp_df, "force", slow_period)?;
+
+    let kvo: Float64Chunked = fast
+        .f64()?
+        .iter()
+        .zip(slow.f64()?.iter())
+        .map(|(fast, slow)| match (fast, slow) {
+            (Some(fast), Some(slow)) if !fast.is_nan() && !slow.is_nan() => Some(fast - slow),
+            _ => None,
+        })
+        .collect();
+
+    // The signal EMA skips the warm-up and seeds on the first
+    // `signal_period` oscillator values
+    let temp_df = DataFrame::new(vec![kvo
+        .clone()
+        .into_series()
+        .with_name("kvo".into())
+        .into()])?;
+    let signal: Float64Chunked = calculate_ema(&temp_df, "kvo", signal_period)?
+        .f64()?
+        .iter()
+        .map(|v| v.filter(|v| !v.is_nan()))
+        .collect();
+
+    let params: [&dyn Display; 3] = [&fast_period, &slow_period, &signal_period];
+    Ok((
+        kvo.with_name(indicator_name("kvo", &params[..2]).into())
+            .into_series(),
+        signal
+            .with_name(indicator_name("kvo_signal", &params).into())
+            .into_series(),
+    ))
+}
@@ -1,3 +1,5 @@
+use crate::indicators::naming::indicator_name;
+use crate::util::dataframe_utils::{fixed_window, rolling_values};
 use polars::prelude::*;
 
 /// Calculates the Money Flow Index (MFI), a volume-weighted version of RSI
@@ -29,13 +31,20 @@ use polars::prelude::*;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::indicators::volume::calculate_mfi;
+/// use rustalib::indicators::volume::calculate_mfi;
 ///
-/// // Create or load a DataFrame with OHLCV data
-/// let df = DataFrame::default(); // Replace with actual data
+/// // A typical price rising on every bar has only positive money flow
+/// let close: Vec<f64> = (0..10).map(|i| 100.0 + i as f64).collect();
+/// let df = df! {
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+///     "volume" => vec![1000.0; 10],
+/// }
+/// .unwrap();
 ///
-/// // Calculate MFI with period 14
-/// let mfi = calculate_mfi(&df, 14).unwrap();
+/// let mfi = calculate_mfi(&df, 5).unwrap();
+/// assert_eq!(mfi.f64().unwrap().get(9), Some(100.0));
 /// ```
 pub fn calculate_mfi(df: &DataFrame, window: usize) -> PolarsResult<Series> {
     // Validate that necessary columns exist
@@ -112,40 +121,35 @@ pub fn calculate_mfi(df: &DataFrame, window: usize) -> PolarsResult<Series> {
         }
     }
 
-    // Calculate MFI values
-    let mut mfi_values = Vec::with_capacity(df.height());
-
-    // Fill in NaN values for the initial window
-    for _ in 0..window {
-        mfi_values.push(f64::NAN);
-    }
+    // Sum positive and negative money flows over the window
+    let positive_flow_sums = Series::new("positive_flow".into(), positive_money_flows)
+        .rolling_sum(fixed_window(window, window))?;
+    let negative_flow_sums = Series::new("negative_flow".into(), negative_money_flows)
+        .rolling_sum(fixed_window(window, window))?;
 
     // Calculate MFI for each period after the initial window
-    for i in window..df.height() {
-        let mut positive_flow_sum = 0.0;
-        let mut negative_flow_sum = 0.0;
-
-        // Sum up positive and negative money flows over the window
-        for j in (i - window + 1)..=i {
-            positive_flow_sum += positive_money_flows[j];
-            negative_flow_sum += negative_money_flows[j];
-        }
-
-        if negative_flow_sum.abs() < 1e-10 {
-            // Avoid division by zero or very small numbers
-            if positive_flow_sum.abs() < 1e-10 {
-                mfi_values.push(50.0); // No money flow in either direction
+    let mfi_values: Vec<f64> = rolling_values(&positive_flow_sums, window)?
+        .into_iter()
+        .zip(rolling_values(&negative_flow_sums, window)?)
+        .enumerate()
+        .map(|(i, (positive_flow_sum, negative_flow_sum))| {
+            if i < window {
+                f64::NAN
+            } else if negative_flow_sum.abs() < 1e-10 {
+                // Avoid division by zero or very small numbers
+                if positive_flow_sum.abs() < 1e-10 {
+                    50.0 // No money flow in either direction
+                } else {
+                    100.0 // All positive money flow
+                }
             } else {
-                mfi_values.push(100.0); // All positive money flow
+                let money_ratio = positive_flow_sum / negative_flow_sum;
+                100.0 - (100.0 / (1.0 + money_ratio))
             }
-        } else {
-            let money_ratio = positive_flow_sum / negative_flow_sum;
-            let mfi = 100.0 - (100.0 / (1.0 + money_ratio));
-            mfi_values.push(mfi);
-        }
-    }
+        })
+        .collect();
 
     // Create a Series with the MFI values
-    let name = format!("mfi_{}", window);
+    let name = indicator_name("mfi", &[&window]);
     Ok(Series::new(name.into(), mfi_values))
 }
@@ -4,19 +4,80 @@ use polars::prelude::*;
 
 // Modules for volume indicators
 mod adl;
+mod adosc;
 mod cmf;
 mod eom;
+mod force_index;
+mod klinger;
 mod mfi;
 mod obv;
 mod pvt;
+mod volume_profile;
+mod vwma;
 
 // Re-export volume indicators
 pub use adl::calculate_adl;
+pub use adosc::calculate_adosc;
 pub use cmf::calculate_cmf;
 pub use eom::calculate_eom;
+pub use force_index::calculate_force_index;
+pub use klinger::calculate_klinger;
 pub use mfi::calculate_mfi;
 pub use obv::calculate_obv;
 pub use pvt::calculate_pvt;
+pub use volume_profile::{volume_histogram, volume_profile};
+pub use vwma::calculate_vwma;
+
+/// Indicators added by [`add_volume_indicators_with_config`]
+///
+/// The default adds the columns of [`add_volume_indicators`]; the remaining
+/// indicators are opt-in and use their standard periods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumeIndicatorConfig {
+    /// obv
+    pub obv: bool,
+    /// cmf_20
+    pub cmf: bool,
+    /// mfi_14
+    pub mfi: bool,
+    /// vwma_20 of the close
+    pub vwma: bool,
+    /// force_index_13
+    pub force_index: bool,
+    /// kvo_34_55 and kvo_signal_34_55_13
+    pub klinger: bool,
+    /// adosc_3_10
+    pub adosc: bool,
+}
+
+impl Default for VolumeIndicatorConfig {
+    fn default() -> Self {
+        Self {
+            obv: true,
+            cmf: true,
+            mfi: true,
+            vwma: false,
+            force_index: false,
+            klinger: false,
+            adosc: false,
+        }
+    }
+}
+
+impl VolumeIndicatorConfig {
+    /// A config with every indicator enabled
+    pub fn all() -> Self {
+        Self {
+            obv: true,
+            cmf: true,
+            mfi: true,
+            vwma: true,
+            force_index: true,
+            klinger: true,
+            adosc: true,
+        }
+    }
+}
 
 /// Add volume-based indicators to a DataFrame
 ///
@@ -35,28 +96,104 @@ pub use pvt::calculate_pvt;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::indicators::volume::add_volume_indicators;
+/// use rustalib::indicators::volume::add_volume_indicators;
 ///
-/// // Create or load a DataFrame with OHLCV data
-/// let df = DataFrame::default(); // Replace with actual data
+/// let close: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 / 4.0).sin() * 5.0).collect();
+/// let high: Vec<f64> = close.iter().map(|c| c + 1.0).collect();
+/// let low: Vec<f64> = close.iter().map(|c| c - 1.0).collect();
+/// let df = df! {
+///     "open" => &close,
+///     "high" => high,
+///     "low" => low,
+///     "close" => &close,
+///     "volume" => vec![1000.0; 60],
+/// }
+/// .unwrap();
 ///
-/// // Add volume indicators
 /// let df_with_indicators = add_volume_indicators(&df).unwrap();
+/// for name in ["cmf_20", "mfi_14"] {
+///     assert!(df_with_indicators.schema().contains(name));
+/// }
 /// ```
 pub fn add_volume_indicators(df: &DataFrame) -> PolarsResult<DataFrame> {
+    add_volume_indicators_with_config(df, &VolumeIndicatorConfig::default())
+}
+
+/// Add the volume indicators selected by `config` to a DataFrame
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing OHLCV data
+/// * `config` - Indicators to add
+///
+/// # Returns
+///
+/// * `PolarsResult<DataFrame>` - DataFrame with added volume indicators
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volume::{add_volume_indicators_with_config, VolumeIndicatorConfig};
+///
+/// let close: Vec<f64> = (0..80).map(|i| 100.0 + (i as f64 / 4.0).sin() * 5.0).collect();
+/// let df = df! {
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+///     "volume" => vec![1000.0; 80],
+/// }
+/// .unwrap();
+///
+/// let config = VolumeIndicatorConfig {
+///     klinger: true,
+///     adosc: true,
+///     ..VolumeIndicatorConfig::default()
+/// };
+/// let result = add_volume_indicators_with_config(&df, &config).unwrap();
+/// for name in ["obv", "kvo_34_55", "kvo_signal_34_55_13", "adosc_3_10"] {
+///     assert!(result.schema().contains(name));
+/// }
+/// assert!(!result.schema().contains("vwma_20"));
+/// ```
+pub fn add_volume_indicators_with_config(
+    df: &DataFrame,
+    config: &VolumeIndicatorConfig,
+) -> PolarsResult<DataFrame> {
     let mut result_df = df.clone();
 
     // Calculate On Balance Volume (OBV)
-    let obv = calculate_obv(df)?;
-    result_df.with_column(obv)?;
+    if config.obv {
+        result_df.with_column(calculate_obv(df)?)?;
+    }
 
     // Calculate Chaikin Money Flow (CMF) with default period of 20
-    let cmf = calculate_cmf(df, 20)?;
-    result_df.with_column(cmf)?;
+    if config.cmf {
+        result_df.with_column(calculate_cmf(df, 20)?)?;
+    }
 
     // Calculate Money Flow Index (MFI) with default period of 14
-    let mfi = calculate_mfi(df, 14)?;
-    result_df.with_column(mfi)?;
+    if config.mfi {
+        result_df.with_column(calculate_mfi(df, 14)?)?;
+    }
+
+    if config.vwma {
+        result_df.with_column(calculate_vwma(df, 20, "close")?)?;
+    }
+
+    if config.force_index {
+        result_df.with_column(calculate_force_index(df, 13)?)?;
+    }
+
+    if config.klinger {
+        let (kvo, signal) = calculate_klinger(df, 34, 55, 13)?;
+        result_df.with_column(kvo)?;
+        result_df.with_column(signal)?;
+    }
+
+    if config.adosc {
+        result_df.with_column(calculate_adosc(df, 3, 10)?)?;
+    }
 
     Ok(result_df)
 }
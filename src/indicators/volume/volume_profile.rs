@@ -0,0 +1,286 @@
+use crate::util::dataframe_utils::{check_window_size, column_nan_values};
+use polars::prelude::*;
+use std::ops::Range;
+
+/// Share of the window's volume inside the value area
+const VALUE_AREA_FRACTION: f64 = 0.7;
+
+/// Calculates a rolling volume profile
+///
+/// For every window of `lookback` bars the range from the lowest low to the
+/// highest high is split into `bins` equal price buckets, and each bar's
+/// volume is spread evenly over its own high-low range (see
+/// [`volume_histogram`]). The point of control (POC) is the middle of the
+/// bucket with the most volume, the lowest such bucket on a tie. The value area grows from the POC bucket
+/// towards the heavier neighbouring bucket until it holds 70% of the
+/// window's volume; its high and low are the outer bucket edges.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with high, low, close and volume columns
+/// * `bins` - Number of price buckets (typically 24 to 50)
+/// * `lookback` - Number of bars in each profile
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with one row per input row
+/// and "poc", "value_area_high", "value_area_low" and "poc_distance_pct"
+/// (close relative to the POC, in percent) columns, NaN until the first
+/// full window and for windows without volume
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volume::volume_profile;
+///
+/// // Most of the volume trades between 100 and 102, split evenly between
+/// // the 100-101 and 101-102 buckets; ties go to the lower price
+/// let df = df! {
+///     "high" => [102.0, 101.5, 101.5, 104.0],
+///     "low" => [100.0, 100.5, 100.5, 102.0],
+///     "close" => [101.0, 101.0, 101.0, 104.0],
+///     "volume" => [100.0, 500.0, 500.0, 100.0],
+/// }
+/// .unwrap();
+///
+/// let profile = volume_profile(&df, 4, 4).unwrap();
+/// let poc = profile.column("poc").unwrap().f64().unwrap();
+/// assert_eq!(poc.get(3), Some(100.5));
+/// assert!(profile.column("poc_distance_pct").unwrap().f64().unwrap().get(3).unwrap() > 0.0);
+/// ```
+pub fn volume_profile(df: &DataFrame, bins: usize, lookback: usize) -> PolarsResult<DataFrame> {
+    check_window_size(df, lookback, "Volume profile")?;
+    if bins == 0 || lookback == 0 {
+        return Err(PolarsError::ComputeError(
+            "Volume profile needs at least one bin and one bar".into(),
+        ));
+    }
+
+    let (high, low, volume) = price_volume(df)?;
+    let close = df.column("close")?.f64()?;
+
+    let mut poc = vec![f64::NAN; df.height()];
+    let mut value_area_high = vec![f64::NAN; df.height()];
+    let mut value_area_low = vec![f64::NAN; df.height()];
+    let mut poc_distance = vec![f64::NAN; df.height()];
+
+    for i in lookback - 1..df.height() {
+        let Some(profile) = histogram(&high, &low, &volume, i + 1 - lookback..i + 1, bins) else {
+            continue;
+        };
+        let (point_of_control, val, vah) = profile.levels();
+        poc[i] = point_of_control;
+        value_area_low[i] = val;
+        value_area_high[i] = vah;
+        if let Some(c) = close.get(i) {
+            if point_of_control != 0.0 {
+                poc_distance[i] = (c - point_of_control) / point_of_control * 100.0;
+            }
+        }
+    }
+
+    DataFrame::new(vec![
+        Series::new("poc".into(), poc).into(),
+        Series::new("value_area_high".into(), value_area_high).into(),
+        Series::new("value_area_low".into(), value_area_low).into(),
+        Series::new("poc_distance_pct".into(), poc_distance).into(),
+    ])
+}
+
+/// Calculates the volume histogram of a range of bars
+///
+/// The range from the lowest low to the highest high of `rows` is split into
+/// `bins` equal price buckets. Each bar's volume is spread over the buckets
+/// its high-low range overlaps, in proportion to the overlap; a bar with no
+/// range puts all its volume in its bucket. Bars with a missing high, low or
+/// volume are skipped.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with high, low and volume columns
+/// * `bins` - Number of price buckets
+/// * `rows` - Rows to include, e.g. `df.height() - 50..df.height()`
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with one row per bucket,
+/// lowest price first, and "price_low", "price_high" and "volume" columns
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volume::volume_histogram;
+///
+/// let df = df! {
+///     "high" => [12.0, 11.0],
+///     "low" => [10.0, 11.0],
+///     "volume" => [100.0, 40.0],
+/// }
+/// .unwrap();
+///
+/// let histogram = volume_histogram(&df, 2, 0..2).unwrap();
+/// let volume = histogram.column("volume").unwrap().f64().unwrap();
+/// // Half of the first bar's range lies in each bucket; the second bar
+/// // trades at the edge and falls in the upper one
+/// assert_eq!(volume.get(0), Some(50.0));
+/// assert_eq!(volume.get(1), Some(90.0));
+/// ```
+pub fn volume_histogram(
+    df: &DataFrame,
+    bins: usize,
+    rows: Range<usize>,
+) -> PolarsResult<DataFrame> {
+    if bins == 0 || rows.is_empty() || rows.end > df.height() {
+        return Err(PolarsError::ComputeError(
+            format!(
+                "Volume histogram needs at least one bin and rows within 0..{}",
+                df.height()
+            )
+            .into(),
+        ));
+    }
+
+    let (high, low, volume) = price_volume(df)?;
+    let (price_low, price_high, volumes) = match histogram(&high, &low, &volume, rows, bins) {
+        Some(profile) => (
+            (0..bins).map(|b| profile.edge(b)).collect(),
+            (0..bins).map(|b| profile.edge(b + 1)).collect(),
+            profile.volumes,
+        ),
+        None => (vec![f64::NAN; bins], vec![f64::NAN; bins], vec![0.0; bins]),
+    };
+
+    DataFrame::new(vec![
+        Series::new("price_low".into(), price_low).into(),
+        Series::new("price_high".into(), price_high).into(),
+        Series::new("volume".into(), volumes).into(),
+    ])
+}
+
+/// Volume per equal-width price bucket
+struct Histogram {
+    low: f64,
+    width: f64,
+    volumes: Vec<f64>,
+}
+
+impl Histogram {
+    /// Lower edge of bucket `bin`; `edge(bins)` is the upper edge of the last
+    fn edge(&self, bin: usize) -> f64 {
+        self.low + bin as f64 * self.width
+    }
+
+    /// Point of control, value area low and value area high
+    fn levels(&self) -> (f64, f64, f64) {
+        let mut poc = 0;
+        for (b, &v) in self.volumes.iter().enumerate() {
+            if v > self.volumes[poc] {
+                poc = b;
+            }
+        }
+
+        let target = self.volumes.iter().sum::<f64>() * VALUE_AREA_FRACTION;
+        let (mut bottom, mut top) = (poc, poc);
+        let mut included = self.volumes[poc];
+        while included < target {
+            let above = self.volumes.get(top + 1).copied();
+            let below = bottom.checked_sub(1).map(|b| self.volumes[b]);
+            match (above, below) {
+                (Some(a), Some(b)) if b > a => {
+                    bottom -= 1;
+                    included += b;
+                }
+                (Some(a), _) => {
+                    top += 1;
+                    included += a;
+                }
+                (None, Some(b)) => {
+                    bottom -= 1;
+                    included += b;
+                }
+                (None, None) => break,
+            }
+        }
+
+        (
+            (self.edge(poc) + self.edge(poc + 1)) / 2.0,
+            self.edge(bottom),
+            self.edge(top + 1),
+        )
+    }
+}
+
+/// Build the histogram of `rows`, or None if they traded no volume
+fn histogram(
+    high: &[f64],
+    low: &[f64],
+    volume: &[f64],
+    rows: Range<usize>,
+    bins: usize,
+) -> Option<Histogram> {
+    let bars: Vec<(f64, f64, f64)> = rows
+        .map(|i| (high[i], low[i], volume[i]))
+        .filter(|(h, l, v)| !h.is_nan() && !l.is_nan() && !v.is_nan() && h >= l)
+        .collect();
+    if bars.iter().map(|(_, _, v)| v).sum::<f64>() <= 0.0 {
+        return None;
+    }
+
+    let min = bars
+        .iter()
+        .map(|(_, l, _)| *l)
+        .fold(f64::INFINITY, f64::min);
+    let max = bars
+        .iter()
+        .map(|(h, _, _)| *h)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let width = (max - min) / bins as f64;
+    let mut volumes = vec![0.0; bins];
+
+    let bucket = |price: f64| {
+        if width > 0.0 {
+            (((price - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        }
+    };
+    for (h, l, v) in bars {
+        if h > l {
+            for (b, bin_volume) in volumes
+                .iter_mut()
+                .enumerate()
+                .take(bucket(h) + 1)
+                .skip(bucket(l))
+            {
+                let bin_low = min + b as f64 * width;
+                let overlap = h.min(bin_low + width) - l.max(bin_low);
+                if overlap > 0.0 {
+                    *bin_volume += v * overlap / (h - l);
+                }
+            }
+        } else {
+            volumes[bucket(h)] += v;
+        }
+    }
+
+    Some(Histogram {
+        low: min,
+        width,
+        volumes,
+    })
+}
+
+/// High, low and volume columns with missing values as NaN
+fn price_volume(df: &DataFrame) -> PolarsResult<(Vec<f64>, Vec<f64>, Vec<f64>)> {
+    let to_vec = |name: &str| -> PolarsResult<Vec<f64>> {
+        if !df.schema().contains(name) {
+            return Err(PolarsError::ComputeError(
+                format!("Volume profile requires '{name}' column").into(),
+            ));
+        }
+        column_nan_values(df, name)
+    };
+    Ok((to_vec("high")?, to_vec("low")?, to_vec("volume")?))
+}
@@ -0,0 +1,79 @@
+use crate::indicators::naming::indicator_name;
+use crate::util::dataframe_utils::{check_window_size, nan_values};
+use polars::prelude::*;
+
+/// Calculates the Volume-Weighted Moving Average (VWMA)
+///
+/// Each price in the window is weighted by the volume traded on its bar, so
+/// the average leans towards the prices where most volume changed hands.
+/// With constant volume it equals the SMA.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price column and a "volume" column
+/// * `window` - Number of bars averaged (typically 20)
+/// * `column` - Name of the price column
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named "vwma_{window}", NaN for
+/// the first `window - 1` bars and for windows without volume
+///
+/// # Formula
+///
+/// VWMA = sum(price * volume) / sum(volume) over the window
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::volume::calculate_vwma;
+///
+/// // The 30.0 close traded three times the volume of the 10.0 close
+/// let df = df! {
+///     "close" => [10.0, 30.0, 20.0],
+///     "volume" => [100.0, 300.0, 200.0],
+/// }
+/// .unwrap();
+///
+/// let vwma = calculate_vwma(&df, 2, "close").unwrap();
+/// let vwma = vwma.f64().unwrap();
+/// assert!(vwma.get(0).unwrap().is_nan());
+/// assert_eq!(vwma.get(1), Some(25.0));
+/// assert_eq!(vwma.get(2), Some(26.0));
+/// ```
+pub fn calculate_vwma(df: &DataFrame, window: usize, column: &str) -> PolarsResult<Series> {
+    if window == 0 {
+        return Err(PolarsError::ComputeError(
+            "Window size must be greater than 0".into(),
+        ));
+    }
+    check_window_size(df, window, "VWMA")?;
+
+    let price = df.column(column)?.f64()?;
+    let volume = df.column("volume")?.f64()?;
+
+    let weighted: Vec<f64> = price
+        .iter()
+        .zip(volume.iter())
+        .map(|(p, v)| p.unwrap_or(f64::NAN) * v.unwrap_or(f64::NAN))
+        .collect();
+    let volumes = nan_values(volume);
+
+    let vwma: Vec<f64> = (0..df.height())
+        .map(|i| {
+            if i + 1 < window {
+                return f64::NAN;
+            }
+            let start = i + 1 - window;
+            let total_volume: f64 = volumes[start..=i].iter().sum();
+            if total_volume == 0.0 {
+                f64::NAN
+            } else {
+                weighted[start..=i].iter().sum::<f64>() / total_volume
+            }
+        })
+        .collect();
+
+    Ok(Series::new(indicator_name("vwma", &[&window]).into(), vwma))
+}
@@ -0,0 +1,341 @@
+//! Download OHLCV bars from market data providers
+//!
+//! Available with the `fetch` feature. Providers implement [`DataProvider`];
+//! [`YahooFinance`] and [`AlphaVantage`] are included. Every provider returns
+//! the layout of [`load_ohlcv`](super::load_ohlcv): a UTC "timestamp" column
+//! followed by Float64 "open", "high", "low", "close" and "volume" columns,
+//! sorted by time, which `add_technical_indicators` accepts directly.
+//!
+//! Requests are blocking; run them on a worker thread inside async code.
+
+use crate::error::{TaError, TaResult};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use polars::prelude::*;
+use reqwest::blocking::Client;
+use serde_json::Value;
+
+/// Bar length to download
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarInterval {
+    /// One-minute bars
+    Minute1,
+    /// Five-minute bars
+    Minute5,
+    /// Fifteen-minute bars
+    Minute15,
+    /// Thirty-minute bars
+    Minute30,
+    /// Hourly bars
+    Minute60,
+    /// Daily bars
+    Daily,
+}
+
+impl BarInterval {
+    /// Bar length in minutes, `None` for daily bars
+    pub fn minutes(&self) -> Option<u32> {
+        match self {
+            BarInterval::Minute1 => Some(1),
+            BarInterval::Minute5 => Some(5),
+            BarInterval::Minute15 => Some(15),
+            BarInterval::Minute30 => Some(30),
+            BarInterval::Minute60 => Some(60),
+            BarInterval::Daily => None,
+        }
+    }
+}
+
+/// A source of historical OHLCV bars
+pub trait DataProvider {
+    /// Download the bars of one ticker between `start` and `end`, inclusive
+    ///
+    /// # Arguments
+    ///
+    /// * `ticker` - Symbol as the provider spells it
+    /// * `interval` - Bar length
+    /// * `start` - Time of the first bar to include
+    /// * `end` - Time of the last bar to include
+    ///
+    /// # Returns
+    ///
+    /// Returns a TaResult containing the bars in the layout described in the
+    /// [module documentation](self)
+    fn fetch_bars(
+        &self,
+        ticker: &str,
+        interval: BarInterval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> TaResult<DataFrame>;
+}
+
+/// Download the bars of several tickers
+///
+/// # Arguments
+///
+/// * `provider` - Data source
+/// * `tickers` - Symbols to download
+/// * `interval` - Bar length
+/// * `start` - Time of the first bar to include
+/// * `end` - Time of the last bar to include
+///
+/// # Returns
+///
+/// Returns a TaResult containing a (ticker, bars) pair per ticker in input
+/// order, or the first download error
+///
+/// # Example
+///
+/// ```no_run
+/// use chrono::{Duration, Utc};
+/// use rustalib::indicators::add_technical_indicators;
+/// use rustalib::io::fetch::{fetch_ohlcv, BarInterval, YahooFinance};
+///
+/// let end = Utc::now();
+/// let start = end - Duration::days(365);
+/// let bars = fetch_ohlcv(&YahooFinance::new(), &["AAPL", "MSFT"], BarInterval::Daily, start, end)
+///     .unwrap();
+/// for (ticker, mut df) in bars {
+///     let with_indicators = add_technical_indicators(&mut df).unwrap();
+///     println!("{ticker}: {} columns", with_indicators.width());
+/// }
+/// ```
+pub fn fetch_ohlcv(
+    provider: &dyn DataProvider,
+    tickers: &[&str],
+    interval: BarInterval,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> TaResult<Vec<(String, DataFrame)>> {
+    tickers
+        .iter()
+        .map(|ticker| {
+            let bars = provider.fetch_bars(ticker, interval, start, end)?;
+            Ok((ticker.to_string(), bars))
+        })
+        .collect()
+}
+
+/// Bars from the Yahoo Finance chart API
+///
+/// Yahoo keeps intraday bars for a limited time only (about 30 days of
+/// one-minute bars and 60 days of other intraday lengths).
+#[derive(Debug, Clone)]
+pub struct YahooFinance {
+    client: Client,
+    base_url: String,
+}
+
+impl YahooFinance {
+    /// Create a provider using the public Yahoo Finance endpoint
+    pub fn new() -> Self {
+        Self::with_base_url("https://query1.finance.yahoo.com")
+    }
+
+    /// Create a provider using another endpoint, such as a proxy or mock server
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.into(),
+        }
+    }
+}
+
+impl Default for YahooFinance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DataProvider for YahooFinance {
+    fn fetch_bars(
+        &self,
+        ticker: &str,
+        interval: BarInterval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> TaResult<DataFrame> {
+        let interval = match interval.minutes() {
+            Some(60) => "60m".to_string(),
+            Some(minutes) => format!("{minutes}m"),
+            None => "1d".to_string(),
+        };
+        let url = format!("{}/v8/finance/chart/{ticker}", self.base_url);
+        let body = get_json(self.client.get(url).query(&[
+            ("period1", start.timestamp().to_string()),
+            ("period2", (end.timestamp() + 1).to_string()),
+            ("interval", interval),
+        ]))?;
+        let bars = parse_yahoo_chart(&body)?;
+        bars_frame(bars, start, end)
+    }
+}
+
+/// Bars from the Alpha Vantage time series API
+///
+/// Requests the full history and keeps the bars between `start` and `end`.
+/// Intraday timestamps are US Eastern time and converted to UTC; daily bars
+/// are stamped at midnight UTC of their date. Free API keys are rate
+/// limited, so space out requests for long ticker lists.
+#[derive(Debug, Clone)]
+pub struct AlphaVantage {
+    client: Client,
+    api_key: String,
+    base_url: String,
+}
+
+impl AlphaVantage {
+    /// Create a provider using the public Alpha Vantage endpoint
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            api_key: api_key.into(),
+            base_url: "https://www.alphavantage.co".to_string(),
+        }
+    }
+
+    /// Use another endpoint, such as a proxy or mock server
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+}
+
+impl DataProvider for AlphaVantage {
+    fn fetch_bars(
+        &self,
+        ticker: &str,
+        interval: BarInterval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> TaResult<DataFrame> {
+        let mut query = vec![
+            ("symbol", ticker.to_string()),
+            ("outputsize", "full".to_string()),
+            ("apikey", self.api_key.clone()),
+        ];
+        match interval.minutes() {
+            Some(minutes) => {
+                query.push(("function", "TIME_SERIES_INTRADAY".to_string()));
+                query.push(("interval", format!("{minutes}min")));
+            }
+            None => query.push(("function", "TIME_SERIES_DAILY".to_string())),
+        }
+        let url = format!("{}/query", self.base_url);
+        let body = get_json(self.client.get(url).query(&query))?;
+        let bars = parse_alpha_vantage_series(&body)?;
+        bars_frame(bars, start, end)
+    }
+}
+
+/// Bar time in milliseconds and OHLCV values
+type Bar = (i64, [Option<f64>; 5]);
+
+/// Send a request and parse the JSON response
+fn get_json(request: reqwest::blocking::RequestBuilder) -> TaResult<Value> {
+    let response = request
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| TaError::ComputationError(format!("Download failed: {e}")))?;
+    response
+        .json()
+        .map_err(|e| TaError::ComputationError(format!("Invalid response: {e}")))
+}
+
+/// Bars of a Yahoo Finance chart response
+fn parse_yahoo_chart(body: &Value) -> TaResult<Vec<Bar>> {
+    let chart = &body["chart"];
+    if let Some(error) = chart["error"].as_object() {
+        let description = error.get("description").and_then(Value::as_str);
+        return Err(TaError::ComputationError(format!(
+            "Yahoo Finance error: {}",
+            description.unwrap_or("unknown")
+        )));
+    }
+    let result = &chart["result"][0];
+    let quote = &result["indicators"]["quote"][0];
+    let Some(times) = result["timestamp"].as_array() else {
+        // No bars in the requested range
+        return Ok(Vec::new());
+    };
+
+    let field = |name: &str, i: usize| quote[name][i].as_f64();
+    Ok(times
+        .iter()
+        .enumerate()
+        .filter_map(|(i, time)| {
+            let values = ["open", "high", "low", "close", "volume"].map(|name| field(name, i));
+            Some((time.as_i64()? * 1000, values))
+        })
+        // Yahoo reports halted or unfinished bars with null prices
+        .filter(|(_, values)| values[3].is_some())
+        .collect())
+}
+
+/// Bars of an Alpha Vantage time series response
+fn parse_alpha_vantage_series(body: &Value) -> TaResult<Vec<Bar>> {
+    for key in ["Error Message", "Note", "Information"] {
+        if let Some(message) = body[key].as_str() {
+            return Err(TaError::ComputationError(format!(
+                "Alpha Vantage error: {message}"
+            )));
+        }
+    }
+    let series = body
+        .as_object()
+        .and_then(|fields| {
+            fields
+                .iter()
+                .find(|(key, _)| key.starts_with("Time Series"))
+        })
+        .and_then(|(_, series)| series.as_object())
+        .ok_or_else(|| {
+            TaError::ComputationError("Alpha Vantage response has no time series".to_string())
+        })?;
+
+    let eastern: Tz = chrono_tz::America::New_York;
+    let parse_time = |time: &str| -> Option<i64> {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S") {
+            return eastern
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|dt| dt.timestamp_millis());
+        }
+        let date = NaiveDate::parse_from_str(time, "%Y-%m-%d").ok()?;
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp_millis())
+    };
+
+    Ok(series
+        .iter()
+        .filter_map(|(time, bar)| {
+            let values = ["1. open", "2. high", "3. low", "4. close", "5. volume"]
+                .map(|name| bar[name].as_str().and_then(|v| v.parse().ok()));
+            Some((parse_time(time)?, values))
+        })
+        .collect())
+}
+
+/// DataFrame of the bars between `start` and `end`, sorted by time
+fn bars_frame(mut bars: Vec<Bar>, start: DateTime<Utc>, end: DateTime<Utc>) -> TaResult<DataFrame> {
+    let (start, end) = (start.timestamp_millis(), end.timestamp_millis());
+    bars.retain(|(time, _)| (start..=end).contains(time));
+    bars.sort_by_key(|(time, _)| *time);
+    bars.dedup_by_key(|(time, _)| *time);
+
+    let times: Vec<i64> = bars.iter().map(|(time, _)| *time).collect();
+    let mut columns: Vec<Column> = vec![Series::new("timestamp".into(), times)
+        .cast(&DataType::Datetime(
+            TimeUnit::Milliseconds,
+            Some("UTC".into()),
+        ))?
+        .into()];
+    for (i, name) in ["open", "high", "low", "close", "volume"]
+        .iter()
+        .enumerate()
+    {
+        let values: Vec<Option<f64>> = bars.iter().map(|(_, values)| values[i]).collect();
+        columns.push(Series::new((*name).into(), values).into());
+    }
+    Ok(DataFrame::new(columns)?)
+}
@@ -0,0 +1,373 @@
+use crate::error::{TaError, TaResult};
+use crate::util::column_map::ColumnMap;
+use crate::util::time_utils::parse_timestamp_column;
+use crate::util::validation::{validate_ohlcv, OhlcvRequirements};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use polars::prelude::*;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// File formats read by [`load_ohlcv`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// Apache Parquet
+    Parquet,
+    /// One JSON object per line
+    JsonLines,
+}
+
+impl DataFormat {
+    /// Format implied by a file extension
+    ///
+    /// Recognizes "csv", "parquet"/"pq" and "jsonl"/"ndjson"/"json", in any
+    /// case.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "csv" => Some(DataFormat::Csv),
+            "parquet" | "pq" => Some(DataFormat::Parquet),
+            "jsonl" | "ndjson" | "json" => Some(DataFormat::JsonLines),
+            _ => None,
+        }
+    }
+}
+
+/// How [`load_ohlcv_with_options`] reads and normalizes a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadOptions {
+    /// Names of the OHLCV columns in the file; `None` detects them with
+    /// [`ColumnMap::detect`]
+    pub columns: Option<ColumnMap>,
+    /// Timestamp column in the file; `None` picks the first column named
+    /// "timestamp", "datetime", "date" or "time" (in any case)
+    pub time_column: Option<String>,
+    /// chrono format strings tried in order for string timestamps; date-only
+    /// formats parse as midnight
+    pub time_formats: Vec<String>,
+    /// Timezone of timestamps without an offset
+    pub timezone: Tz,
+    /// Field separator of CSV files
+    pub separator: u8,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            columns: None,
+            time_column: None,
+            time_formats: [
+                "%Y-%m-%d %H:%M:%S",
+                "%Y-%m-%dT%H:%M:%S",
+                "%Y-%m-%d %H:%M",
+                "%Y-%m-%d",
+            ]
+            .iter()
+            .map(|f| f.to_string())
+            .collect(),
+            timezone: Tz::UTC,
+            separator: b',',
+        }
+    }
+}
+
+/// Load an OHLCV file into a normalized DataFrame
+///
+/// Shorthand for [`load_ohlcv_with_options`] with the given column names and
+/// the default time parsing.
+///
+/// # Arguments
+///
+/// * `path` - File to read
+/// * `format` - File format
+/// * `columns` - Names of the OHLCV columns in the file
+///
+/// # Returns
+///
+/// Returns a TaResult containing the normalized DataFrame
+///
+/// # Example
+///
+/// ```
+/// use rustalib::io::{load_ohlcv, DataFormat};
+/// use rustalib::util::column_map::ColumnMap;
+///
+/// let columns = ColumnMap {
+///     open: "Open".to_string(),
+///     high: "High".to_string(),
+///     low: "Low".to_string(),
+///     close: "Close".to_string(),
+///     volume: "Volume".to_string(),
+/// };
+/// let df = load_ohlcv("examples/csv/AAPL_daily_ohlcv.csv", DataFormat::Csv, &columns).unwrap();
+/// assert_eq!(df.get_column_names()[..6], ["timestamp", "open", "high", "low", "close", "volume"]);
+/// assert_eq!(df.column("volume").unwrap().dtype(), &polars::prelude::DataType::Float64);
+/// ```
+pub fn load_ohlcv(
+    path: impl AsRef<Path>,
+    format: DataFormat,
+    columns: &ColumnMap,
+) -> TaResult<DataFrame> {
+    let options = LoadOptions {
+        columns: Some(columns.clone()),
+        ..Default::default()
+    };
+    load_ohlcv_with_options(path, format, &options)
+}
+
+/// Load an OHLCV file into a normalized DataFrame
+///
+/// Reads the file, renames the OHLCV columns to "open", "high", "low",
+/// "close" and "volume" and the timestamp column to "timestamp", parses the
+/// timestamps into a UTC `Datetime` column, sorts the rows by time and checks
+/// the result with [`validate_ohlcv`]: open, high, low and close must be
+/// present, prices and volume are cast to Float64, and unparseable or
+/// duplicate timestamps are errors.
+///
+/// String timestamps in RFC 3339 or ending in " UTC" keep their offset;
+/// others are parsed with `time_formats` and read in `timezone`, as are
+/// `Date` and timezone-less `Datetime` columns. The output columns come in
+/// the order timestamp, OHLCV, then the remaining file columns.
+///
+/// # Arguments
+///
+/// * `path` - File to read
+/// * `format` - File format
+/// * `options` - Column names, time parsing and CSV settings
+///
+/// # Returns
+///
+/// Returns a TaResult containing the normalized DataFrame, or
+/// [`TaError::InvalidData`] listing every validation problem
+///
+/// # Example
+///
+/// ```
+/// use chrono_tz::America::New_York;
+/// use polars::prelude::*;
+/// use rustalib::io::{load_ohlcv_with_options, DataFormat, LoadOptions};
+/// use std::io::Write;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("bars.jsonl");
+/// let mut file = std::fs::File::create(&path).unwrap();
+/// writeln!(file, r#"{{"t": "01/03/2024 09:31", "o": 10.5, "h": 11, "l": 10.4, "c": 10.9, "v": 900}}"#).unwrap();
+/// writeln!(file, r#"{{"t": "01/03/2024 09:30", "o": 10, "h": 10.6, "l": 9.9, "c": 10.5, "v": 1200}}"#).unwrap();
+///
+/// let options = LoadOptions {
+///     time_column: Some("t".to_string()),
+///     time_formats: vec!["%m/%d/%Y %H:%M".to_string()],
+///     timezone: New_York,
+///     ..Default::default()
+/// };
+/// let df = load_ohlcv_with_options(&path, DataFormat::JsonLines, &options).unwrap();
+///
+/// // Sorted by time, with 09:30 New York read as 14:30 UTC
+/// assert_eq!(df.column("open").unwrap().f64().unwrap().get(0), Some(10.0));
+/// let first = df.column("timestamp").unwrap().cast(&DataType::Int64).unwrap();
+/// assert_eq!(first.i64().unwrap().get(0), Some(1_704_292_200_000));
+/// ```
+pub fn load_ohlcv_with_options(
+    path: impl AsRef<Path>,
+    format: DataFormat,
+    options: &LoadOptions,
+) -> TaResult<DataFrame> {
+    let path = path.as_ref();
+    let raw = match format {
+        DataFormat::Csv => {
+            let csv_options = CsvReadOptions::default()
+                .with_has_header(true)
+                .map_parse_options(|opts| opts.with_separator(options.separator));
+            CsvReader::new(File::open(path)?)
+                .with_options(csv_options)
+                .finish()?
+        }
+        DataFormat::Parquet => ParquetReader::new(File::open(path)?).finish()?,
+        DataFormat::JsonLines => read_json_lines(path)?,
+    };
+
+    let time_column = match &options.time_column {
+        Some(name) => name.clone(),
+        None => detect_time_column(&raw).ok_or_else(|| {
+            TaError::MissingColumn("timestamp (or datetime, date, time)".to_string())
+        })?,
+    };
+    if !raw.schema().contains(&time_column) {
+        return Err(TaError::MissingColumn(time_column));
+    }
+
+    let columns = options
+        .columns
+        .clone()
+        .unwrap_or_else(|| ColumnMap::detect(&raw));
+    let timestamps = parse_times(&raw, &time_column, options)?;
+
+    let mut ordered: Vec<Column> = vec![Series::new("timestamp".into(), timestamps)
+        .cast(&DataType::Datetime(
+            TimeUnit::Milliseconds,
+            Some("UTC".into()),
+        ))?
+        .into()];
+    for (standard, name) in columns.pairs() {
+        if raw.schema().contains(name) {
+            ordered.push(raw.column(name)?.clone().with_name(standard.into()));
+        }
+    }
+    let taken: Vec<&str> = columns.pairs().iter().map(|(_, name)| *name).collect();
+    for column in raw.get_columns() {
+        let name = column.name().as_str();
+        if name == time_column || taken.contains(&name) {
+            continue;
+        }
+        if ordered.iter().any(|c| c.name() == name) {
+            return Err(TaError::ComputationError(format!(
+                "Cannot normalize column names: '{name}' is already used by a mapped column"
+            )));
+        }
+        ordered.push(column.clone());
+    }
+
+    let df = DataFrame::new(ordered)?.sort(["timestamp"], SortMultipleOptions::default())?;
+    let requirements = OhlcvRequirements {
+        time_column: Some("timestamp".to_string()),
+        ..Default::default()
+    };
+    Ok(validate_ohlcv(&df, &requirements)?)
+}
+
+/// First column with a common timestamp name
+pub(super) fn detect_time_column(df: &DataFrame) -> Option<String> {
+    ["timestamp", "datetime", "date", "time"]
+        .iter()
+        .find_map(|candidate| {
+            df.get_column_names()
+                .into_iter()
+                .find(|name| name.eq_ignore_ascii_case(candidate))
+                .map(|name| name.to_string())
+        })
+}
+
+/// Timestamps as milliseconds since the epoch
+fn parse_times(
+    df: &DataFrame,
+    time_column: &str,
+    options: &LoadOptions,
+) -> PolarsResult<Vec<Option<i64>>> {
+    let localize = |naive: NaiveDateTime| {
+        options
+            .timezone
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+    };
+
+    let column = df.column(time_column)?;
+    let times: Vec<Option<DateTime<Utc>>> = match column.dtype() {
+        DataType::String => column
+            .str()?
+            .iter()
+            .map(|value| parse_time_str(value?, &options.time_formats, &localize))
+            .collect(),
+        // Values without a timezone are wall-clock times in `timezone`
+        DataType::Date | DataType::Datetime(_, None) => parse_timestamp_column(df, time_column)?
+            .into_iter()
+            .map(|t| localize(t?.naive_utc()))
+            .collect(),
+        _ => parse_timestamp_column(df, time_column)?,
+    };
+    Ok(times
+        .into_iter()
+        .map(|t| t.map(|t| t.timestamp_millis()))
+        .collect())
+}
+
+/// Parse one string timestamp, honoring an explicit offset
+fn parse_time_str(
+    value: &str,
+    formats: &[String],
+    localize: &impl Fn(NaiveDateTime) -> Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Some(naive) = value.strip_suffix(" UTC") {
+        return formats
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(naive, format).ok())
+            .map(|dt| dt.and_utc());
+    }
+
+    formats.iter().find_map(|format| {
+        if let Ok(dt) = DateTime::parse_from_str(value, format) {
+            return Some(dt.with_timezone(&Utc));
+        }
+        let naive = NaiveDateTime::parse_from_str(value, format)
+            .ok()
+            .or_else(|| {
+                NaiveDate::parse_from_str(value, format)
+                    .ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+            })?;
+        localize(naive)
+    })
+}
+
+/// Read a JSON lines file with one flat object per line
+///
+/// Fields become columns: numbers Float64, strings String and booleans
+/// Boolean; missing fields and nulls are null. Columns are in alphabetical
+/// order.
+fn read_json_lines(path: &Path) -> TaResult<DataFrame> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut rows = Vec::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let row: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(&line).map_err(|e| {
+                TaError::ComputationError(format!("Invalid JSON on line {}: {e}", line_number + 1))
+            })?;
+        rows.push(row);
+    }
+
+    let mut names: Vec<&String> = rows.iter().flat_map(|row| row.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    let mut columns = Vec::with_capacity(names.len());
+    for name in names {
+        let values: Vec<&serde_json::Value> = rows
+            .iter()
+            .map(|row| row.get(name).unwrap_or(&serde_json::Value::Null))
+            .collect();
+        let present = || values.iter().filter(|v| !v.is_null());
+        let series = if present().all(|v| v.is_number()) {
+            Series::new(
+                name.into(),
+                values.iter().map(|v| v.as_f64()).collect::<Vec<_>>(),
+            )
+        } else if present().all(|v| v.is_string()) {
+            Series::new(
+                name.into(),
+                values.iter().map(|v| v.as_str()).collect::<Vec<_>>(),
+            )
+        } else if present().all(|v| v.is_boolean()) {
+            Series::new(
+                name.into(),
+                values.iter().map(|v| v.as_bool()).collect::<Vec<_>>(),
+            )
+        } else {
+            return Err(TaError::ComputationError(format!(
+                "JSON field '{name}' mixes value types or holds nested values"
+            )));
+        };
+        columns.push(series.into());
+    }
+    Ok(DataFrame::new(columns)?)
+}
@@ -0,0 +1,23 @@
+//! # Data Input and Output
+//!
+//! Loading price data from files into the DataFrame layout the indicators
+//! and strategies expect, and saving strategy results.
+//!
+//! - [`load_ohlcv`]: Read CSV, Parquet or JSON lines files into a sorted,
+//!   validated OHLCV DataFrame with standard column names
+//! - [`save_results`]: Write strategy output as CSV or Parquet with a JSON
+//!   metadata sidecar (strategy, parameters, data range, crate version)
+//! - [`fetch`](fetch/index.html): Download bars from Yahoo Finance or Alpha
+//!   Vantage (`fetch` feature)
+//! - [`stream`](stream/index.html): Feed live bars into a rolling DataFrame
+//!   and call back on every completed bar (`stream` feature)
+
+#[cfg(feature = "fetch")]
+pub mod fetch;
+mod loader;
+mod results;
+#[cfg(feature = "stream")]
+pub mod stream;
+
+pub use loader::{load_ohlcv, load_ohlcv_with_options, DataFormat, LoadOptions};
+pub use results::{load_metadata, metadata_path, save_results, RunMetadata};
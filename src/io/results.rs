@@ -0,0 +1,169 @@
+use crate::error::{TaError, TaResult};
+use crate::io::DataFormat;
+use crate::strategy::StrategySignals;
+use crate::util::time_utils::parse_timestamp_column;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Sidecar description of saved strategy results
+///
+/// Written by [`save_results`] as JSON next to the results file, so runs can
+/// be reproduced and compared later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunMetadata {
+    /// Strategy name
+    pub strategy: String,
+    /// Strategy parameters as serialized by the params struct
+    pub parameters: serde_json::Value,
+    /// Version of this crate that produced the results
+    pub crate_version: String,
+    /// Number of bars
+    pub rows: usize,
+    /// Time of the first bar in RFC 3339, when a time column was found
+    pub data_start: Option<String>,
+    /// Time of the last bar in RFC 3339, when a time column was found
+    pub data_end: Option<String>,
+    /// Number of completed trades
+    pub trades: usize,
+}
+
+/// Path of the metadata file belonging to a results file
+///
+/// The results file name with its extension replaced by "meta.json", e.g.
+/// "run.meta.json" for "run.csv".
+pub fn metadata_path(path: impl AsRef<Path>) -> PathBuf {
+    path.as_ref().with_extension("meta.json")
+}
+
+/// Save strategy results with a metadata sidecar
+///
+/// Writes [`StrategySignals::indicator_values`] with "buy_signals" and
+/// "sell_signals" columns to `path`, and a [`RunMetadata`] JSON file to
+/// [`metadata_path`]. The data range comes from the first `Datetime` or
+/// `Date` column, or else the first column named "timestamp", "datetime",
+/// "date" or "time".
+///
+/// # Arguments
+///
+/// * `signals` - Output of a strategy run
+/// * `path` - Results file to write
+/// * `format` - [`DataFormat::Csv`] or [`DataFormat::Parquet`]
+/// * `strategy` - Strategy name recorded in the metadata
+/// * `parameters` - Strategy parameters recorded in the metadata
+///
+/// # Returns
+///
+/// Returns a TaResult containing the metadata written
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::io::{load_metadata, metadata_path, save_results, DataFormat};
+/// use rustalib::strategy::crypto::grid_trading::{run_strategy, StrategyParams};
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"],
+///     "open" => [100.0, 96.0, 104.0, 101.0],
+///     "high" => [101.0, 97.0, 106.0, 102.0],
+///     "low" => [99.0, 94.0, 103.0, 99.0],
+///     "close" => [100.0, 95.0, 105.0, 100.0],
+/// }
+/// .unwrap();
+/// let params = StrategyParams::new(90.0, 110.0);
+/// let signals = run_strategy(&df, &params).unwrap();
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("grid.csv");
+/// save_results(&signals, &path, DataFormat::Csv, "grid_trading", &params).unwrap();
+///
+/// let metadata = load_metadata(metadata_path(&path)).unwrap();
+/// assert_eq!(metadata.strategy, "grid_trading");
+/// assert_eq!(metadata.parameters["upper_price"], 110.0);
+/// assert_eq!(metadata.data_start.as_deref(), Some("2024-01-02T00:00:00+00:00"));
+/// assert_eq!(metadata.rows, 4);
+/// ```
+pub fn save_results(
+    signals: &StrategySignals,
+    path: impl AsRef<Path>,
+    format: DataFormat,
+    strategy: &str,
+    parameters: &impl Serialize,
+) -> TaResult<RunMetadata> {
+    let path = path.as_ref();
+    let mut output = signals.indicator_values.clone();
+    output.with_column(Series::new(
+        "buy_signals".into(),
+        signals.buy_signals.clone(),
+    ))?;
+    output.with_column(Series::new(
+        "sell_signals".into(),
+        signals.sell_signals.clone(),
+    ))?;
+
+    match format {
+        DataFormat::Csv => CsvWriter::new(File::create(path)?).finish(&mut output)?,
+        DataFormat::Parquet => {
+            ParquetWriter::new(File::create(path)?).finish(&mut output)?;
+        }
+        DataFormat::JsonLines => {
+            return Err(TaError::invalid_parameter(
+                "format",
+                "results can be saved as CSV or Parquet",
+            ))
+        }
+    }
+
+    let (data_start, data_end) = data_range(&output)?;
+    let metadata = RunMetadata {
+        strategy: strategy.to_string(),
+        parameters: serde_json::to_value(parameters)
+            .map_err(|e| TaError::ComputationError(format!("Cannot serialize parameters: {e}")))?,
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        rows: output.height(),
+        data_start,
+        data_end,
+        trades: signals.trades.len(),
+    };
+    let text = serde_json::to_string_pretty(&metadata)
+        .map_err(|e| TaError::ComputationError(format!("Cannot serialize metadata: {e}")))?;
+    std::fs::write(metadata_path(path), text)?;
+    Ok(metadata)
+}
+
+/// Read a metadata file written by [`save_results`]
+///
+/// # Arguments
+///
+/// * `path` - Metadata file, see [`metadata_path`]
+///
+/// # Returns
+///
+/// Returns a TaResult containing the metadata
+pub fn load_metadata(path: impl AsRef<Path>) -> TaResult<RunMetadata> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)?;
+    serde_json::from_str(&text)
+        .map_err(|e| TaError::invalid_parameter(path.display().to_string(), e.to_string()))
+}
+
+/// First and last time of the results, as RFC 3339 strings
+fn data_range(df: &DataFrame) -> PolarsResult<(Option<String>, Option<String>)> {
+    let temporal = df
+        .get_columns()
+        .iter()
+        .find(|c| matches!(c.dtype(), DataType::Datetime(_, _) | DataType::Date))
+        .map(|c| c.name().to_string());
+    let Some(time_column) = temporal.or_else(|| super::loader::detect_time_column(df)) else {
+        return Ok((None, None));
+    };
+
+    let times: Vec<_> = parse_timestamp_column(df, &time_column)?
+        .into_iter()
+        .flatten()
+        .collect();
+    let format = |t: Option<&chrono::DateTime<chrono::Utc>>| t.map(|t| t.to_rfc3339());
+    Ok((format(times.iter().min()), format(times.iter().max())))
+}
@@ -0,0 +1,291 @@
+//! Live bar ingestion for streaming strategies
+//!
+//! Available with the `stream` feature. A [`BarFeed`] yields bar updates
+//! from any live source; [`JsonMessageFeed`] parses the JSON text messages
+//! that websocket market data APIs send, so a websocket client only has to
+//! hand over each message. [`run_feed`] collects the completed bars in a
+//! [`BarBuffer`] and calls back with the recent history as a DataFrame after
+//! every completed bar, ready for indicators and strategies.
+//!
+//! Feeds often send several updates for the bar being formed. An update
+//! completes the bar when it is flagged closed, and otherwise a bar is
+//! complete once an update for a later bar arrives.
+
+use crate::error::{TaError, TaResult};
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde_json::Value;
+use std::collections::VecDeque;
+
+/// One OHLCV bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LiveBar {
+    /// Bar start time
+    pub timestamp: DateTime<Utc>,
+    /// Opening price
+    pub open: f64,
+    /// Highest price
+    pub high: f64,
+    /// Lowest price
+    pub low: f64,
+    /// Latest or closing price
+    pub close: f64,
+    /// Traded volume
+    pub volume: f64,
+}
+
+/// A bar as reported by a feed, possibly still forming
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BarUpdate {
+    /// Bar values so far
+    pub bar: LiveBar,
+    /// Whether the feed marks the bar as final; `None` when it does not say
+    pub closed: Option<bool>,
+}
+
+/// A source of live bar updates
+pub trait BarFeed {
+    /// Wait for the next update; `None` once the feed has ended
+    fn next_update(&mut self) -> Option<TaResult<BarUpdate>>;
+}
+
+/// Feed of bars encoded as JSON text messages
+///
+/// Each message is an object with the bar start time under "timestamp" or
+/// "t" (epoch milliseconds or an RFC 3339 string), prices under
+/// "open"/"o", "high"/"h", "low"/"l" and "close"/"c", volume under
+/// "volume"/"v" (0 when absent) and optionally a boolean "closed"/"x" flag.
+/// Numbers may be sent as strings. Messages without a bar, such as
+/// subscription acknowledgements or heartbeats, are skipped.
+pub struct JsonMessageFeed<I> {
+    messages: I,
+}
+
+impl<I: Iterator<Item = String>> JsonMessageFeed<I> {
+    /// Create a feed over text messages, e.g. the frames of a websocket
+    /// connection or the lines of a recorded session
+    pub fn new(messages: I) -> Self {
+        Self { messages }
+    }
+}
+
+impl<I: Iterator<Item = String>> BarFeed for JsonMessageFeed<I> {
+    fn next_update(&mut self) -> Option<TaResult<BarUpdate>> {
+        for message in self.messages.by_ref() {
+            match parse_bar_message(&message) {
+                Ok(Some(update)) => return Some(Ok(update)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+/// Parse one JSON bar message
+///
+/// See [`JsonMessageFeed`] for the accepted fields.
+///
+/// # Arguments
+///
+/// * `message` - JSON text of the message
+///
+/// # Returns
+///
+/// Returns a TaResult containing the update, `None` for messages without a
+/// timestamp or close, or an error for invalid JSON
+pub fn parse_bar_message(message: &str) -> TaResult<Option<BarUpdate>> {
+    let value: Value = serde_json::from_str(message)
+        .map_err(|e| TaError::ComputationError(format!("Invalid bar message: {e}")))?;
+    let field = |long: &str, short: &str| {
+        let value = value.get(long).or_else(|| value.get(short))?;
+        value
+            .as_f64()
+            .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+    };
+
+    let timestamp = match value.get("timestamp").or_else(|| value.get("t")) {
+        Some(Value::Number(ms)) => ms.as_i64().and_then(DateTime::from_timestamp_millis),
+        Some(Value::String(text)) => DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc)),
+        _ => None,
+    };
+    let (Some(timestamp), Some(close)) = (timestamp, field("close", "c")) else {
+        return Ok(None);
+    };
+
+    let bar = LiveBar {
+        timestamp,
+        open: field("open", "o").unwrap_or(close),
+        high: field("high", "h").unwrap_or(close),
+        low: field("low", "l").unwrap_or(close),
+        close,
+        volume: field("volume", "v").unwrap_or(0.0),
+    };
+    let closed = value
+        .get("closed")
+        .or_else(|| value.get("x"))
+        .and_then(Value::as_bool);
+    Ok(Some(BarUpdate { bar, closed }))
+}
+
+/// The most recent completed bars, up to a fixed capacity
+#[derive(Debug, Clone)]
+pub struct BarBuffer {
+    bars: VecDeque<LiveBar>,
+    capacity: usize,
+}
+
+impl BarBuffer {
+    /// Create a buffer keeping the last `capacity` bars
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            bars: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Append a bar, dropping the oldest when the buffer is full
+    pub fn push(&mut self, bar: LiveBar) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.bars.len() == self.capacity {
+            self.bars.pop_front();
+        }
+        self.bars.push_back(bar);
+    }
+
+    /// Number of buffered bars
+    pub fn len(&self) -> usize {
+        self.bars.len()
+    }
+
+    /// Whether the buffer holds no bars
+    pub fn is_empty(&self) -> bool {
+        self.bars.is_empty()
+    }
+
+    /// The latest bar
+    pub fn last(&self) -> Option<&LiveBar> {
+        self.bars.back()
+    }
+
+    /// The buffered bars as a DataFrame
+    ///
+    /// # Returns
+    ///
+    /// Returns a PolarsResult containing a DataFrame with a UTC "timestamp"
+    /// column and Float64 "open", "high", "low", "close" and "volume"
+    /// columns, oldest bar first
+    pub fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        let times: Vec<i64> = self
+            .bars
+            .iter()
+            .map(|bar| bar.timestamp.timestamp_millis())
+            .collect();
+        let values = |get: fn(&LiveBar) -> f64| self.bars.iter().map(get).collect::<Vec<_>>();
+
+        DataFrame::new(vec![
+            Series::new("timestamp".into(), times)
+                .cast(&DataType::Datetime(
+                    TimeUnit::Milliseconds,
+                    Some("UTC".into()),
+                ))?
+                .into(),
+            Series::new("open".into(), values(|bar| bar.open)).into(),
+            Series::new("high".into(), values(|bar| bar.high)).into(),
+            Series::new("low".into(), values(|bar| bar.low)).into(),
+            Series::new("close".into(), values(|bar| bar.close)).into(),
+            Series::new("volume".into(), values(|bar| bar.volume)).into(),
+        ])
+    }
+}
+
+/// Collect the completed bars of a feed and call back after each one
+///
+/// The callback receives the buffered history, oldest bar first and the
+/// new bar last, and stops the run by returning an error. Updates for bars
+/// older than the last completed one are ignored. A bar still forming when
+/// the feed ends is not delivered.
+///
+/// # Arguments
+///
+/// * `feed` - Source of bar updates
+/// * `buffer` - Buffer receiving the completed bars; pass one holding
+///   historical bars to give indicators a warm start
+/// * `on_bar` - Callback run after every completed bar
+///
+/// # Returns
+///
+/// Returns a TaResult containing the number of completed bars, or the first
+/// feed or callback error
+///
+/// # Example
+///
+/// ```
+/// use rustalib::indicators::moving_averages::calculate_sma;
+/// use rustalib::io::stream::{run_feed, BarBuffer, JsonMessageFeed};
+///
+/// let messages = vec![
+///     r#"{"type": "subscribed"}"#,
+///     r#"{"t": 1704292200000, "o": 10.0, "h": 10.2, "l": 9.9, "c": 10.1, "v": 500}"#,
+///     r#"{"t": 1704292200000, "o": 10.0, "h": 10.4, "l": 9.9, "c": 10.3, "v": 800}"#,
+///     r#"{"t": 1704292260000, "o": 10.3, "h": 10.6, "l": 10.3, "c": 10.5, "v": 600, "x": true}"#,
+///     r#"{"t": 1704292320000, "o": 10.5, "h": 10.5, "l": 10.2, "c": 10.2, "v": 100}"#,
+/// ];
+/// let mut feed = JsonMessageFeed::new(messages.into_iter().map(String::from));
+/// let mut buffer = BarBuffer::new(500);
+///
+/// let mut smas = Vec::new();
+/// let completed = run_feed(&mut feed, &mut buffer, |bars| {
+///     let sma = calculate_sma(bars, "close", 1)?;
+///     smas.push(sma.f64()?.get(bars.height() - 1));
+///     Ok(())
+/// })
+/// .unwrap();
+///
+/// // The first bar completes with its last update, the second by its flag;
+/// // the third is still forming
+/// assert_eq!(completed, 2);
+/// assert_eq!(smas, vec![Some(10.3), Some(10.5)]);
+/// ```
+pub fn run_feed<F>(
+    feed: &mut impl BarFeed,
+    buffer: &mut BarBuffer,
+    mut on_bar: F,
+) -> TaResult<usize>
+where
+    F: FnMut(&DataFrame) -> TaResult<()>,
+{
+    let mut pending: Option<LiveBar> = None;
+    let mut last_completed = buffer.last().map(|bar| bar.timestamp);
+    let mut completed = 0;
+
+    let mut complete = |bar: LiveBar, buffer: &mut BarBuffer| -> TaResult<()> {
+        buffer.push(bar);
+        completed += 1;
+        on_bar(&buffer.to_dataframe()?)
+    };
+
+    while let Some(update) = feed.next_update() {
+        let BarUpdate { bar, closed } = update?;
+        if last_completed.is_some_and(|last| bar.timestamp <= last) {
+            continue;
+        }
+        if let Some(previous) = pending.take() {
+            if previous.timestamp < bar.timestamp {
+                complete(previous, buffer)?;
+                last_completed = Some(previous.timestamp);
+            }
+        }
+        if closed == Some(true) {
+            complete(bar, buffer)?;
+            last_completed = Some(bar.timestamp);
+        } else {
+            pending = Some(bar);
+        }
+    }
+    Ok(completed)
+}
@@ -22,7 +22,7 @@
 //!
 //! ```rust
 //! use polars::prelude::*;
-//! use ta_lib_in_rust::indicators::moving_averages::calculate_sma;
+//! use rustalib::indicators::moving_averages::calculate_sma;
 //!
 //! fn main() -> PolarsResult<()> {
 //!     let close_prices = Series::new(
@@ -54,12 +54,27 @@
 //! - Calculating and printing performance metrics
 //! - Saving all signals and indicators to a CSV file (`enhanced_minute_strategy_results.csv`) for further analysis
 //!
+//! ## Errors
+//!
+//! Failures can be classified with [`TaError`]; see the [`error`] module.
+//!
 //! See the documentation for each module for more detailed information and examples.
 
+pub mod backtest;
+pub mod error;
 pub mod indicators;
+pub mod io;
+pub mod ml;
+pub mod performance;
+#[cfg(feature = "plot")]
+pub mod plot;
+pub mod portfolio;
+pub mod risk;
+pub mod strategy;
 pub mod util;
 
 // Re-export commonly used items
+pub use error::{TaError, TaResult};
 pub use indicators::*;
 
 // This is a placeholder function - should be removed before final release
@@ -0,0 +1,184 @@
+use crate::error::{TaError, TaResult};
+use polars::prelude::*;
+use std::ops::Range;
+
+/// Parameters of [`purged_splits`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurgedCvConfig {
+    /// Number of train/test splits
+    pub n_splits: usize,
+    /// Rows after each test fold left out of training, on top of purging
+    pub embargo: usize,
+    /// Train only on rows before each test fold. The rows are cut into
+    /// `n_splits + 1` blocks and block `k` is tested on the blocks before
+    /// it. Otherwise the rows are cut into `n_splits` folds and each fold
+    /// is tested on all the others (purged K-fold).
+    pub walk_forward: bool,
+}
+
+impl Default for PurgedCvConfig {
+    fn default() -> Self {
+        Self {
+            n_splits: 5,
+            embargo: 0,
+            walk_forward: false,
+        }
+    }
+}
+
+/// Row ranges of one train/test split
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CvSplit {
+    /// Training rows, as ascending non-overlapping ranges
+    pub train: Vec<Range<usize>>,
+    /// Test rows
+    pub test: Range<usize>,
+}
+
+impl CvSplit {
+    /// Row indices of the training set
+    pub fn train_indices(&self) -> Vec<usize> {
+        self.train.iter().cloned().flatten().collect()
+    }
+
+    /// Training rows of `df`
+    pub fn train_frame(&self, df: &DataFrame) -> PolarsResult<DataFrame> {
+        let mut frame = df.clear();
+        for range in &self.train {
+            frame.vstack_mut(&df.slice(range.start as i64, range.len()))?;
+        }
+        Ok(frame)
+    }
+
+    /// Test rows of `df`
+    pub fn test_frame(&self, df: &DataFrame) -> DataFrame {
+        df.slice(self.test.start as i64, self.test.len())
+    }
+}
+
+/// Splits rows into train/test sets for time-series cross-validation
+///
+/// The test sets are contiguous folds in time order. A label computed from
+/// later bars (a forward return, a triple-barrier outcome) leaks the test
+/// period into training when its row is trained on, so training rows are
+/// purged: before a test fold, rows whose label ends inside or after the
+/// fold are dropped; after it, rows up to the last bar any test label
+/// reaches are dropped. The `embargo` drops a further stretch of rows after
+/// each test fold, for features such as long moving averages that still
+/// carry test data.
+///
+/// # Arguments
+///
+/// * `n_rows` - Number of rows to split
+/// * `label_end` - Row at which each row's label is known (the end columns
+///   of [`fixed_horizon_labels`](super::labels::fixed_horizon_labels) and
+///   [`triple_barrier_labels`](super::labels::triple_barrier_labels)); null
+///   rows and `None` treat each label as known on its own row
+/// * `config` - Number of splits, embargo and scheme
+///
+/// # Returns
+///
+/// Returns a TaResult containing the splits in time order, or
+/// InvalidParameter when there are fewer rows than blocks, too few splits,
+/// or `label_end` does not have `n_rows` rows
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::ml::cv::{purged_splits, PurgedCvConfig};
+///
+/// // Every label is known 2 bars after its row
+/// let label_end = Series::new("end".into(), (0..12u32).map(|i| i + 2).collect::<Vec<_>>());
+/// let config = PurgedCvConfig {
+///     n_splits: 3,
+///     embargo: 1,
+///     walk_forward: false,
+/// };
+/// let splits = purged_splits(12, Some(&label_end), &config).unwrap();
+///
+/// assert_eq!(splits[1].test, 4..8);
+/// // Rows 2-3 label into the test fold; rows 8-9 are reached by its labels
+/// assert_eq!(splits[1].train, [0..2, 10..12]);
+///
+/// let walk_forward = PurgedCvConfig { walk_forward: true, ..config };
+/// let splits = purged_splits(12, Some(&label_end), &walk_forward).unwrap();
+/// assert_eq!(splits[0].test, 3..6);
+/// assert_eq!(splits[0].train, [0..1]);
+/// ```
+pub fn purged_splits(
+    n_rows: usize,
+    label_end: Option<&Series>,
+    config: &PurgedCvConfig,
+) -> TaResult<Vec<CvSplit>> {
+    let min_splits = if config.walk_forward { 1 } else { 2 };
+    if config.n_splits < min_splits {
+        return Err(TaError::invalid_parameter(
+            "n_splits",
+            format!("must be at least {min_splits}"),
+        ));
+    }
+    let blocks = config.n_splits + usize::from(config.walk_forward);
+    if n_rows < blocks {
+        return Err(TaError::invalid_parameter(
+            "n_rows",
+            format!("{n_rows} rows cannot be cut into {blocks} blocks"),
+        ));
+    }
+
+    let ends: Vec<usize> = match label_end {
+        Some(series) => {
+            if series.len() != n_rows {
+                return Err(TaError::invalid_parameter(
+                    "label_end",
+                    format!("has {} rows, expected {n_rows}", series.len()),
+                ));
+            }
+            series
+                .cast(&DataType::UInt64)?
+                .u64()?
+                .iter()
+                .enumerate()
+                .map(|(row, end)| end.map_or(row, |end| (end as usize).max(row)))
+                .collect()
+        }
+        None => (0..n_rows).collect(),
+    };
+
+    // The first `n_rows % blocks` blocks take one extra row
+    let bounds: Vec<usize> = (0..=blocks)
+        .map(|k| k * (n_rows / blocks) + k.min(n_rows % blocks))
+        .collect();
+    let first_test = usize::from(config.walk_forward);
+
+    Ok((first_test..blocks)
+        .map(|k| {
+            let test = bounds[k]..bounds[k + 1];
+            let reach = ends[test.clone()].iter().copied().max().unwrap_or(test.end);
+            let resume = (reach + 1).max(test.end + config.embargo);
+            let keep = |row: usize| {
+                if row < test.start {
+                    ends[row] < test.start
+                } else {
+                    !config.walk_forward && row >= resume
+                }
+            };
+            CvSplit {
+                train: ranges((0..n_rows).filter(|&row| !test.contains(&row) && keep(row))),
+                test,
+            }
+        })
+        .collect())
+}
+
+/// Coalesce ascending row indices into ranges
+fn ranges(rows: impl Iterator<Item = usize>) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for row in rows {
+        match ranges.last_mut() {
+            Some(last) if last.end == row => last.end += 1,
+            _ => ranges.push(row..row + 1),
+        }
+    }
+    ranges
+}
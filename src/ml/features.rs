@@ -0,0 +1,333 @@
+use crate::error::{TaError, TaResult};
+use crate::indicators::add_indicators::{add_technical_indicators_with_config, IndicatorConfig};
+use crate::util::dataframe_utils::column_nan_values;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{Datelike, Timelike};
+use polars::prelude::*;
+use std::f64::consts::PI;
+
+/// Scaling applied to the feature columns by [`build_feature_matrix`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Normalization {
+    /// Features keep their raw values
+    #[default]
+    None,
+    /// Each feature has its mean subtracted and is divided by its
+    /// (population) standard deviation
+    ZScore,
+    /// Each feature is mapped onto 0-1 by its minimum and maximum
+    MinMax,
+}
+
+/// Contents of the matrix built by [`build_feature_matrix`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureConfig {
+    /// Indicators added as features; every column in its
+    /// [`output_columns`](IndicatorConfig::output_columns) becomes a feature
+    pub indicators: IndicatorConfig,
+    /// Trailing returns over each number of bars: "return_{n}"
+    pub return_periods: Vec<usize>,
+    /// Windows of the rolling mean and standard deviation of one-bar
+    /// returns: "return_mean_{n}" and "return_std_{n}"
+    pub rolling_windows: Vec<usize>,
+    /// Timestamp column for the calendar features, None for none. The
+    /// column is kept as the first column of the matrix.
+    pub time_column: Option<String>,
+    /// Horizons of the forward-return labels, in bars: "forward_return_{n}"
+    pub label_horizons: Vec<usize>,
+    /// Drop rows with a missing feature or label (indicator warm-up and the
+    /// last bars, whose forward returns are unknown)
+    pub drop_missing: bool,
+    /// Scaling applied to the features; labels are never scaled
+    pub normalization: Normalization,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self {
+            indicators: IndicatorConfig {
+                moving_averages: true,
+                oscillators: true,
+                volatility: true,
+                ..IndicatorConfig::none()
+            },
+            return_periods: vec![1, 5, 10],
+            rolling_windows: vec![5, 20],
+            time_column: None,
+            label_horizons: vec![1, 5],
+            drop_missing: true,
+            normalization: Normalization::None,
+        }
+    }
+}
+
+/// Scaling of one feature column: `scaled = (raw - offset) / scale`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureScale {
+    /// Feature column name
+    pub column: String,
+    /// Value subtracted from the raw feature
+    pub offset: f64,
+    /// Divisor applied after subtracting the offset
+    pub scale: f64,
+}
+
+/// Feature matrix with the roles of its columns
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureMatrix {
+    /// The time column (when configured), then the features, then the labels
+    pub data: DataFrame,
+    /// Names of the feature columns, in column order
+    pub features: Vec<String>,
+    /// Names of the label columns, in column order
+    pub labels: Vec<String>,
+    /// Scaling applied to each feature; empty without normalization. Apply
+    /// the same scaling to data the trained model is later run on.
+    pub scales: Vec<FeatureScale>,
+}
+
+/// Builds a feature matrix with forward-return labels from OHLCV bars
+///
+/// Features are the indicators of `config.indicators`, trailing returns,
+/// the rolling mean and standard deviation of one-bar returns and, with a
+/// time column, calendar features: "day_of_week_sin"/"_cos" and
+/// "month_sin"/"_cos", plus "hour_sin"/"_cos" when any bar falls after
+/// midnight. Every feature of a row uses only that bar and earlier ones;
+/// the labels are the returns from the row's close to the close `n` bars
+/// later.
+///
+/// Normalization parameters are fitted on every row of the matrix, so
+/// scaling a matrix that is later split into training and test sets leaks
+/// test statistics into training; fit on the training rows in that case.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with OHLCV columns, named as in `config.indicators.columns`
+/// * `config` - Features, labels and post-processing
+///
+/// # Returns
+///
+/// Returns a TaResult containing the feature matrix, or InvalidParameter
+/// for a zero return period, window or horizon, or a feature named twice
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::ml::features::{build_feature_matrix, FeatureConfig, Normalization};
+///
+/// let close: Vec<f64> = (0..100).map(|i| 100.0 + (i as f64 / 6.0).sin() * 5.0).collect();
+/// let df = df! {
+///     "open" => &close,
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+/// }
+/// .unwrap();
+///
+/// let config = FeatureConfig {
+///     normalization: Normalization::ZScore,
+///     ..FeatureConfig::default()
+/// };
+/// let matrix = build_feature_matrix(&df, &config).unwrap();
+///
+/// // sma_50 is defined from row 49 and the 5-bar label up to row 94
+/// assert_eq!(matrix.data.height(), 46);
+/// assert!(matrix.features.contains(&"rsi_14".to_string()));
+/// assert_eq!(matrix.labels, ["forward_return_1", "forward_return_5"]);
+/// let rsi = matrix.data.column("rsi_14").unwrap().f64().unwrap();
+/// assert!(rsi.mean().unwrap().abs() < 1e-9);
+/// ```
+pub fn build_feature_matrix(df: &DataFrame, config: &FeatureConfig) -> TaResult<FeatureMatrix> {
+    for (name, values) in [
+        ("return_periods", &config.return_periods),
+        ("rolling_windows", &config.rolling_windows),
+        ("label_horizons", &config.label_horizons),
+    ] {
+        if values.contains(&0) {
+            return Err(TaError::invalid_parameter(name, "must not contain 0"));
+        }
+    }
+
+    let with_indicators =
+        add_technical_indicators_with_config(&mut df.clone(), &config.indicators)?;
+    let mut features: Vec<Series> = config
+        .indicators
+        .output_columns()
+        .iter()
+        // Time features are only added for input with a "time" column
+        .filter_map(|name| with_indicators.column(name).ok())
+        .map(|column| column.as_materialized_series().cast(&DataType::Float64))
+        .collect::<PolarsResult<_>>()?;
+
+    let close = column_nan_values(df, config.indicators.columns.resolve("close"))?;
+    let one_bar: Vec<f64> = trailing_returns(&close, 1);
+
+    for &period in &config.return_periods {
+        features.push(Series::new(
+            format!("return_{period}").into(),
+            trailing_returns(&close, period),
+        ));
+    }
+
+    for &window in &config.rolling_windows {
+        let (mean, std) = rolling_mean_std(&one_bar, window);
+        features.push(Series::new(format!("return_mean_{window}").into(), mean));
+        features.push(Series::new(format!("return_std_{window}").into(), std));
+    }
+
+    if let Some(time_column) = &config.time_column {
+        features.extend(calendar_features(df, time_column)?);
+    }
+
+    let labels: Vec<Series> = config
+        .label_horizons
+        .iter()
+        .map(|&horizon| {
+            let forward = (0..close.len())
+                .map(|i| match close.get(i + horizon) {
+                    Some(later) => later / close[i] - 1.0,
+                    None => f64::NAN,
+                })
+                .collect::<Vec<f64>>();
+            Series::new(format!("forward_return_{horizon}").into(), forward)
+        })
+        .collect();
+
+    let feature_names: Vec<String> = features.iter().map(|s| s.name().to_string()).collect();
+    let label_names: Vec<String> = labels.iter().map(|s| s.name().to_string()).collect();
+
+    let mut columns: Vec<Column> = Vec::new();
+    if let Some(time_column) = &config.time_column {
+        columns.push(df.column(time_column)?.clone());
+    }
+    columns.extend(features.into_iter().map(Column::from));
+    columns.extend(labels.into_iter().map(Column::from));
+    let mut data = DataFrame::new(columns).map_err(|err| match err {
+        PolarsError::Duplicate(msg) => TaError::invalid_parameter("indicators", msg.to_string()),
+        err => err.into(),
+    })?;
+
+    if config.drop_missing {
+        let mut keep = vec![true; data.height()];
+        for name in feature_names.iter().chain(&label_names) {
+            for (keep, value) in keep.iter_mut().zip(column_nan_values(&data, name)?) {
+                *keep &= !value.is_nan();
+            }
+        }
+        data = data.filter(&BooleanChunked::from_slice("keep".into(), &keep))?;
+    }
+
+    let mut scales = Vec::new();
+    if config.normalization != Normalization::None {
+        for name in &feature_names {
+            let raw = column_nan_values(&data, name)?;
+            let valid: Vec<f64> = raw.iter().copied().filter(|v| !v.is_nan()).collect();
+            let (offset, scale) = fit_scale(&valid, config.normalization);
+            let scaled: Vec<f64> = raw.iter().map(|v| (v - offset) / scale).collect();
+            data.with_column(Series::new(name.into(), scaled))?;
+            scales.push(FeatureScale {
+                column: name.clone(),
+                offset,
+                scale,
+            });
+        }
+    }
+
+    Ok(FeatureMatrix {
+        data,
+        features: feature_names,
+        labels: label_names,
+        scales,
+    })
+}
+
+/// Return from the close `period` bars earlier to each close
+fn trailing_returns(close: &[f64], period: usize) -> Vec<f64> {
+    (0..close.len())
+        .map(|i| match i.checked_sub(period) {
+            Some(earlier) => close[i] / close[earlier] - 1.0,
+            None => f64::NAN,
+        })
+        .collect()
+}
+
+/// Mean and sample standard deviation of the last `window` values
+fn rolling_mean_std(values: &[f64], window: usize) -> (Vec<f64>, Vec<f64>) {
+    (0..values.len())
+        .map(|i| {
+            if i + 1 < window {
+                return (f64::NAN, f64::NAN);
+            }
+            let slice = &values[i + 1 - window..=i];
+            let mean = slice.iter().sum::<f64>() / window as f64;
+            let std = if window > 1 {
+                let var =
+                    slice.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (window - 1) as f64;
+                var.sqrt()
+            } else {
+                0.0
+            };
+            (mean, std)
+        })
+        .unzip()
+}
+
+/// Cyclical day-of-week, month and (for intraday bars) hour features
+fn calendar_features(df: &DataFrame, time_column: &str) -> PolarsResult<Vec<Series>> {
+    let times = parse_timestamp_column(df, time_column)?;
+    let cyclical = |name: &str, period: f64, value: &dyn Fn(usize) -> Option<f64>| {
+        let (sin, cos): (Vec<f64>, Vec<f64>) = (0..times.len())
+            .map(|i| match value(i) {
+                Some(v) => {
+                    let angle = 2.0 * PI * v / period;
+                    (angle.sin(), angle.cos())
+                }
+                None => (f64::NAN, f64::NAN),
+            })
+            .unzip();
+        [
+            Series::new(format!("{name}_sin").into(), sin),
+            Series::new(format!("{name}_cos").into(), cos),
+        ]
+    };
+
+    let mut features = Vec::new();
+    let intraday = times
+        .iter()
+        .flatten()
+        .any(|t| t.num_seconds_from_midnight() != 0);
+    if intraday {
+        features.extend(cyclical("hour", 24.0, &|i| {
+            times[i].map(|t| t.hour() as f64 + t.minute() as f64 / 60.0)
+        }));
+    }
+    features.extend(cyclical("day_of_week", 7.0, &|i| {
+        times[i].map(|t| t.weekday().num_days_from_monday() as f64)
+    }));
+    features.extend(cyclical("month", 12.0, &|i| {
+        times[i].map(|t| t.month0() as f64)
+    }));
+    Ok(features)
+}
+
+/// Offset and scale of a feature; constant features get a scale of 1.0
+fn fit_scale(values: &[f64], normalization: Normalization) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 1.0);
+    }
+    let (offset, scale) = match normalization {
+        Normalization::None => (0.0, 1.0),
+        Normalization::ZScore => {
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+            (mean, var.sqrt())
+        }
+        Normalization::MinMax => {
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            (min, max - min)
+        }
+    };
+    (offset, if scale > 0.0 { scale } else { 1.0 })
+}
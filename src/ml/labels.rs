@@ -0,0 +1,221 @@
+use crate::error::{TaError, TaResult};
+use crate::indicators::volatility::calculate_atr;
+use polars::prelude::*;
+
+/// Parameters of [`triple_barrier_labels`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TripleBarrierConfig {
+    /// ATR window setting the barrier distances
+    pub atr_window: usize,
+    /// Profit-taking barrier above the entry close, in ATRs
+    pub profit_multiple: f64,
+    /// Stop-loss barrier below the entry close, in ATRs
+    pub stop_multiple: f64,
+    /// Bars after entry at which the vertical (time) barrier closes the trade
+    pub max_holding: usize,
+}
+
+impl Default for TripleBarrierConfig {
+    fn default() -> Self {
+        Self {
+            atr_window: 14,
+            profit_multiple: 2.0,
+            stop_multiple: 1.0,
+            max_holding: 10,
+        }
+    }
+}
+
+/// Labels each bar by the direction of its close over a fixed horizon
+///
+/// The label is 1 when the close `horizon` bars later is more than
+/// `threshold` above the bar's close, -1 when it is more than `threshold`
+/// below, and 0 otherwise.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the price column
+/// * `horizon` - Bars between the bar and the close it is compared with
+/// * `threshold` - Smallest return counted as a move, as a fraction (0.01 = 1%)
+/// * `column` - Name of the price column
+///
+/// # Returns
+///
+/// Returns a TaResult containing `(label, end)` Series named
+/// "fh_label_{horizon}" (Int32) and "fh_end_{horizon}" (UInt32, the row of
+/// the compared close). Both are null for the last `horizon` rows.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::ml::labels::fixed_horizon_labels;
+///
+/// let df = df! { "close" => [100.0, 102.0, 100.5, 99.0] }.unwrap();
+///
+/// let (label, end) = fixed_horizon_labels(&df, 1, 0.01, "close").unwrap();
+/// let label: Vec<Option<i32>> = label.i32().unwrap().into_iter().collect();
+/// assert_eq!(label, [Some(1), Some(-1), Some(-1), None]);
+/// assert_eq!(end.u32().unwrap().get(0), Some(1));
+/// ```
+pub fn fixed_horizon_labels(
+    df: &DataFrame,
+    horizon: usize,
+    threshold: f64,
+    column: &str,
+) -> TaResult<(Series, Series)> {
+    if horizon == 0 {
+        return Err(TaError::invalid_parameter(
+            "horizon",
+            "must be greater than 0",
+        ));
+    }
+    if threshold.is_nan() || threshold < 0.0 {
+        return Err(TaError::invalid_parameter(
+            "threshold",
+            "must be a non-negative fraction",
+        ));
+    }
+
+    let close = df.column(column)?.f64()?;
+    let mut labels = Vec::with_capacity(df.height());
+    let mut ends = Vec::with_capacity(df.height());
+
+    for i in 0..df.height() {
+        let end = i + horizon;
+        let change = match (close.get(i), (end < df.height()).then(|| close.get(end))) {
+            (Some(entry), Some(Some(exit))) if !entry.is_nan() && !exit.is_nan() => {
+                Some(exit / entry - 1.0)
+            }
+            _ => None,
+        };
+        labels.push(change.map(|change| direction(change, threshold)));
+        ends.push(change.map(|_| end as u32));
+    }
+
+    Ok((
+        Series::new(format!("fh_label_{horizon}").into(), labels),
+        Series::new(format!("fh_end_{horizon}").into(), ends),
+    ))
+}
+
+/// Labels each bar with the triple-barrier method
+///
+/// Lopez de Prado's method, for a long position entered at each bar's close:
+/// a profit-taking barrier `profit_multiple` ATRs above the entry, a stop
+/// `stop_multiple` ATRs below it, and a time barrier `max_holding` bars
+/// later. The label is 1 when the high reaches the profit barrier first,
+/// -1 when the low reaches the stop first, and 0 when the time barrier is
+/// reached first. A bar touching both price barriers counts as a stop, since
+/// bar data cannot tell which came first.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing "high", "low" and "close" columns
+/// * `config` - ATR window, barrier multiples and holding limit
+///
+/// # Returns
+///
+/// Returns a TaResult containing `(label, end)` Series named "tb_label"
+/// (Int32) and "tb_end" (UInt32, the row at which the first barrier was
+/// touched). Both are null while the ATR is undefined and for the last bars,
+/// whose barriers are not resolved by the end of the data.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::ml::labels::{triple_barrier_labels, TripleBarrierConfig};
+///
+/// // Bars 1.0 wide on a flat close; bar 5 jumps to 103
+/// let close = [100.0, 100.0, 100.0, 100.0, 100.0, 103.0, 100.0, 100.0];
+/// let df = df! {
+///     "high" => close.iter().map(|c| c + 0.5).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 0.5).collect::<Vec<_>>(),
+///     "close" => close,
+/// }
+/// .unwrap();
+///
+/// let config = TripleBarrierConfig {
+///     atr_window: 2,
+///     profit_multiple: 2.0,
+///     stop_multiple: 2.0,
+///     max_holding: 2,
+/// };
+/// let (label, end) = triple_barrier_labels(&df, &config).unwrap();
+/// let label: Vec<Option<i32>> = label.i32().unwrap().into_iter().collect();
+/// assert_eq!(label, [None, None, Some(0), Some(1), Some(1), Some(0), None, None]);
+/// assert_eq!(end.u32().unwrap().get(3), Some(5));
+/// ```
+pub fn triple_barrier_labels(
+    df: &DataFrame,
+    config: &TripleBarrierConfig,
+) -> TaResult<(Series, Series)> {
+    if config.max_holding == 0 {
+        return Err(TaError::invalid_parameter(
+            "max_holding",
+            "must be greater than 0",
+        ));
+    }
+    for (name, multiple) in [
+        ("profit_multiple", config.profit_multiple),
+        ("stop_multiple", config.stop_multiple),
+    ] {
+        if multiple.is_nan() || multiple <= 0.0 {
+            return Err(TaError::invalid_parameter(name, "must be positive"));
+        }
+    }
+
+    let atr = calculate_atr(df, config.atr_window)?;
+    let atr = atr.f64()?;
+    let high = df.column("high")?.f64()?;
+    let low = df.column("low")?.f64()?;
+    let close = df.column("close")?.f64()?;
+    let height = df.height();
+
+    let mut labels = Vec::with_capacity(height);
+    let mut ends = Vec::with_capacity(height);
+
+    for i in 0..height {
+        let (entry, atr) = match (close.get(i), atr.get(i)) {
+            (Some(entry), Some(atr)) if !entry.is_nan() && !atr.is_nan() => (entry, atr),
+            _ => {
+                labels.push(None);
+                ends.push(None);
+                continue;
+            }
+        };
+        let upper = entry + config.profit_multiple * atr;
+        let lower = entry - config.stop_multiple * atr;
+        let time_limit = i + config.max_holding;
+
+        let touch = (i + 1..=time_limit.min(height - 1)).find_map(|j| {
+            if low.get(j).is_some_and(|low| low <= lower) {
+                Some((-1, j))
+            } else if high.get(j).is_some_and(|high| high >= upper) {
+                Some((1, j))
+            } else {
+                None
+            }
+        });
+        let outcome = touch.or((time_limit < height).then_some((0, time_limit)));
+
+        labels.push(outcome.map(|(label, _)| label));
+        ends.push(outcome.map(|(_, end)| end as u32));
+    }
+
+    Ok((
+        Series::new("tb_label".into(), labels),
+        Series::new("tb_end".into(), ends),
+    ))
+}
+
+fn direction(change: f64, threshold: f64) -> i32 {
+    if change > threshold {
+        1
+    } else if change < -threshold {
+        -1
+    } else {
+        0
+    }
+}
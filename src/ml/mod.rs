@@ -0,0 +1,26 @@
+//! # Machine Learning
+//!
+//! Preparation of price data for models trained outside this crate.
+//!
+//! - [`build_feature_matrix`] turns OHLCV bars into a feature matrix of
+//!   indicators, trailing returns, rolling return statistics and calendar
+//!   features, with forward-return label columns
+//! - [`fixed_horizon_labels`] and [`triple_barrier_labels`] label each bar
+//!   as an up, down or flat outcome, with the row at which it is known
+//! - [`purged_splits`] cuts the rows into train/test splits for time-series
+//!   cross-validation, purging training rows whose labels overlap the test
+//!   period
+//! - [`onnx`](onnx/index.html) evaluates a trained ONNX model on a feature matrix,
+//!   giving a prediction column for strategies (`onnx` feature)
+
+pub mod cv;
+pub mod features;
+pub mod labels;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+
+pub use cv::{purged_splits, CvSplit, PurgedCvConfig};
+pub use features::{
+    build_feature_matrix, FeatureConfig, FeatureMatrix, FeatureScale, Normalization,
+};
+pub use labels::{fixed_horizon_labels, triple_barrier_labels, TripleBarrierConfig};
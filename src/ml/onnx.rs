@@ -0,0 +1,213 @@
+//! # ONNX Model Inference
+//!
+//! Evaluates a model trained outside Rust and exported to ONNX on every row
+//! of a feature matrix, giving a prediction column that strategies and the
+//! [`SignalCombiner`](crate::strategy::combiner::SignalCombiner) read like
+//! any indicator. Requires the `onnx` feature; models run on the pure-Rust
+//! [tract](https://github.com/sonos/tract) engine.
+
+use crate::error::{TaError, TaResult};
+use crate::ml::features::FeatureMatrix;
+use polars::prelude::*;
+use std::path::Path;
+use std::sync::Arc;
+use tract_onnx::prelude::*;
+
+/// A loaded ONNX model taking one row of features at a time
+///
+/// The model's first input must accept a `[1, n_features]` float tensor.
+/// Each row's prediction is one element of one of the model's outputs; the
+/// default is the first element of the first output, which is the score of
+/// a regressor. For a classifier exporting probabilities as its second
+/// output, [`with_output`](Self::with_output)`(1, class)` reads the
+/// probability of `class`.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::ml::onnx::OnnxModel;
+///
+/// // prediction = 0.5 * a - 1.0 * b + 2.0 * c + 0.25
+/// let model = OnnxModel::load("tests/onnx_inference/linear.onnx", 3).unwrap();
+/// let df = df! {
+///     "a" => [1.0, 2.0, f64::NAN],
+///     "b" => [0.0, 1.0, 0.0],
+///     "c" => [1.0, 0.5, 1.0],
+/// }
+/// .unwrap();
+///
+/// let features = ["a", "b", "c"].map(String::from);
+/// let prediction = model.predict(&df, &features, "prediction").unwrap();
+/// let prediction: Vec<Option<f64>> = prediction.f64().unwrap().into_iter().collect();
+/// assert_eq!(prediction, [Some(2.75), Some(1.25), None]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OnnxModel {
+    plan: Arc<TypedRunnableModel>,
+    n_features: usize,
+    output: usize,
+    element: usize,
+}
+
+impl OnnxModel {
+    /// Load a model from an ONNX file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the .onnx file
+    /// * `n_features` - Number of features the model takes per row
+    ///
+    /// # Returns
+    ///
+    /// Returns a TaResult containing the model, or ComputationError when the
+    /// file cannot be read or the model does not accept `[1, n_features]`
+    /// float input
+    pub fn load(path: impl AsRef<Path>, n_features: usize) -> TaResult<Self> {
+        let model = tract_onnx::onnx()
+            .model_for_path(path)
+            .map_err(model_error)?;
+        Self::from_inference_model(model, n_features)
+    }
+
+    /// Load a model from the bytes of an ONNX file
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Contents of the .onnx file
+    /// * `n_features` - Number of features the model takes per row
+    ///
+    /// # Returns
+    ///
+    /// Returns a TaResult containing the model, or ComputationError when the
+    /// bytes are not a model accepting `[1, n_features]` float input
+    pub fn from_bytes(mut bytes: &[u8], n_features: usize) -> TaResult<Self> {
+        let model = tract_onnx::onnx()
+            .model_for_read(&mut bytes)
+            .map_err(model_error)?;
+        Self::from_inference_model(model, n_features)
+    }
+
+    fn from_inference_model(model: InferenceModel, n_features: usize) -> TaResult<Self> {
+        if n_features == 0 {
+            return Err(TaError::invalid_parameter(
+                "n_features",
+                "must be greater than 0",
+            ));
+        }
+        let plan = model
+            .with_input_fact(0, f32::fact([1, n_features]).into())
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(model_error)?;
+        Ok(Self {
+            plan,
+            n_features,
+            output: 0,
+            element: 0,
+        })
+    }
+
+    /// Read predictions from element `element` of output `output`
+    pub fn with_output(mut self, output: usize, element: usize) -> Self {
+        self.output = output;
+        self.element = element;
+        self
+    }
+
+    /// Number of features the model takes per row
+    pub fn n_features(&self) -> usize {
+        self.n_features
+    }
+
+    /// Evaluate the model on every row of `df`
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - DataFrame holding the feature columns
+    /// * `features` - Feature columns, in the order the model was trained on
+    /// * `name` - Name of the prediction Series
+    ///
+    /// # Returns
+    ///
+    /// Returns a TaResult containing a Float64 Series with one prediction
+    /// per row, null for rows with a missing or NaN feature. Fails with
+    /// InvalidParameter when the number of features differs from the
+    /// model's, and with ComputationError when the model fails to run or
+    /// has no such output element.
+    pub fn predict(&self, df: &DataFrame, features: &[String], name: &str) -> TaResult<Series> {
+        if features.len() != self.n_features {
+            return Err(TaError::invalid_parameter(
+                "features",
+                format!(
+                    "model takes {} features, got {}",
+                    self.n_features,
+                    features.len()
+                ),
+            ));
+        }
+        let columns = features
+            .iter()
+            .map(|name| Ok(df.column(name)?.cast(&DataType::Float64)?))
+            .collect::<TaResult<Vec<Column>>>()?;
+        let columns = columns
+            .iter()
+            .map(|column| column.f64())
+            .collect::<PolarsResult<Vec<_>>>()?;
+
+        let mut predictions = Vec::with_capacity(df.height());
+        let mut row = vec![0f32; self.n_features];
+        for i in 0..df.height() {
+            let mut complete = true;
+            for (value, column) in row.iter_mut().zip(&columns) {
+                match column.get(i).filter(|v| !v.is_nan()) {
+                    Some(v) => *value = v as f32,
+                    None => complete = false,
+                }
+            }
+            predictions.push(if complete {
+                Some(self.evaluate(&row)?)
+            } else {
+                None
+            });
+        }
+
+        Ok(Series::new(name.into(), predictions))
+    }
+
+    /// Evaluate the model on the features of a [`FeatureMatrix`]
+    ///
+    /// Same as [`predict`](Self::predict) on `matrix.data` with
+    /// `matrix.features`, so the model must have been trained on a matrix
+    /// built with the same config.
+    pub fn predict_matrix(&self, matrix: &FeatureMatrix, name: &str) -> TaResult<Series> {
+        self.predict(&matrix.data, &matrix.features, name)
+    }
+
+    fn evaluate(&self, row: &[f32]) -> TaResult<f64> {
+        let input = Tensor::from_shape(&[1, self.n_features], row).map_err(model_error)?;
+        let outputs = self
+            .plan
+            .run(tvec!(input.into_tvalue()))
+            .map_err(model_error)?;
+        let output = outputs.get(self.output).ok_or_else(|| {
+            TaError::ComputationError(format!("ONNX model has no output {}", self.output))
+        })?;
+        let values = output.cast_to::<f32>().map_err(model_error)?;
+        let values = values.to_plain_array_view::<f32>().map_err(model_error)?;
+        values
+            .iter()
+            .nth(self.element)
+            .map(|v| *v as f64)
+            .ok_or_else(|| {
+                TaError::ComputationError(format!(
+                    "ONNX output {} has no element {}",
+                    self.output, self.element
+                ))
+            })
+    }
+}
+
+fn model_error(err: TractError) -> TaError {
+    TaError::ComputationError(format!("ONNX model: {err:#}"))
+}
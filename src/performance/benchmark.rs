@@ -0,0 +1,308 @@
+use crate::util::dataframe_utils::check_window_size;
+use polars::prelude::*;
+
+/// Calculates the drawdown of a strategy relative to a benchmark
+///
+/// The relative wealth line is the strategy equity divided by the benchmark
+/// price, both rebased to their first valid value. Its drawdown from the
+/// running peak shows periods where the strategy loses ground against simply
+/// holding the benchmark, even when both are rising.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with aligned strategy equity and benchmark prices
+/// * `equity_column` - Column with strategy equity (or price of the instrument)
+/// * `benchmark_column` - Column with benchmark prices
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the relative drawdown Series in percent
+/// (zero or negative) named "relative_drawdown"
+pub fn calculate_relative_drawdown(
+    df: &DataFrame,
+    equity_column: &str,
+    benchmark_column: &str,
+) -> PolarsResult<Series> {
+    let relative = relative_wealth(df, equity_column, benchmark_column)?;
+
+    let mut peak = f64::NAN;
+    let drawdown: Vec<f64> = relative
+        .iter()
+        .map(|&value| {
+            if value.is_nan() {
+                return f64::NAN;
+            }
+            if peak.is_nan() || value > peak {
+                peak = value;
+            }
+            (value / peak - 1.0) * 100.0
+        })
+        .collect();
+
+    Ok(Series::new("relative_drawdown".into(), drawdown))
+}
+
+/// Calculates rolling alpha and beta of a strategy against a benchmark
+///
+/// Per-period returns of the strategy are regressed on benchmark returns
+/// over a rolling window. Use a window of one year of bars (e.g. 252 for
+/// daily data) for the usual 12-month alpha/beta.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with aligned strategy equity and benchmark prices
+/// * `equity_column` - Column with strategy equity (or price of the instrument)
+/// * `benchmark_column` - Column with benchmark prices
+/// * `window` - Number of return observations per regression
+/// * `periods_per_year` - Bars per year used to annualize alpha (252 for daily)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a tuple of (alpha, beta) Series named
+/// "rolling_alpha" (annualized, in percent) and "rolling_beta"
+pub fn calculate_rolling_alpha_beta(
+    df: &DataFrame,
+    equity_column: &str,
+    benchmark_column: &str,
+    window: usize,
+    periods_per_year: f64,
+) -> PolarsResult<(Series, Series)> {
+    check_window_size(df, window + 1, "Rolling alpha/beta")?;
+
+    let strategy_returns = period_returns(df, equity_column)?;
+    let benchmark_returns = period_returns(df, benchmark_column)?;
+
+    let mut alpha = vec![f64::NAN; df.height()];
+    let mut beta = vec![f64::NAN; df.height()];
+
+    for i in window..df.height() {
+        let pairs: Vec<(f64, f64)> = (i + 1 - window..=i)
+            .map(|j| (benchmark_returns[j], strategy_returns[j]))
+            .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+            .collect();
+
+        if pairs.len() < 2 {
+            continue;
+        }
+
+        let n = pairs.len() as f64;
+        let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let covariance: f64 = pairs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+        let variance: f64 = pairs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+        if variance > 0.0 {
+            let b = covariance / variance;
+            beta[i] = b;
+            alpha[i] = (mean_y - b * mean_x) * periods_per_year * 100.0;
+        }
+    }
+
+    Ok((
+        Series::new("rolling_alpha".into(), alpha),
+        Series::new("rolling_beta".into(), beta),
+    ))
+}
+
+/// Performance of a strategy relative to a benchmark over a whole backtest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchmarkReport {
+    /// Number of bars with both a strategy and a benchmark return
+    pub periods: usize,
+    /// Intercept of the regression of strategy returns on benchmark returns,
+    /// annualized, in percent
+    pub alpha: f64,
+    /// Slope of the same regression
+    pub beta: f64,
+    /// Correlation of strategy and benchmark returns
+    pub correlation: f64,
+    /// Mean strategy return less mean benchmark return, annualized, in percent
+    pub active_return: f64,
+    /// Standard deviation of the return differences, annualized, in percent
+    pub tracking_error: f64,
+    /// Active return divided by tracking error
+    pub information_ratio: f64,
+    /// Mean strategy return on bars the benchmark rose, in percent of the
+    /// mean benchmark return on those bars
+    pub up_capture: f64,
+    /// Mean strategy return on bars the benchmark fell, in percent of the
+    /// mean benchmark return on those bars; below 100 loses less
+    pub down_capture: f64,
+    /// Deepest relative drawdown, in percent (zero or negative); see
+    /// [`calculate_relative_drawdown`]
+    pub max_relative_drawdown: f64,
+}
+
+/// Calculates benchmark-relative performance statistics
+///
+/// Statistics are NaN when undefined, e.g. a beta against a benchmark that
+/// never moves or an up capture without rising bars.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with aligned strategy equity and benchmark prices
+/// * `equity_column` - Column with strategy equity (or price of the instrument)
+/// * `benchmark_column` - Column with benchmark prices
+/// * `periods_per_year` - Bars per year used to annualize (252 for daily)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`BenchmarkReport`], or a
+/// ComputeError with fewer than two bars having both returns
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::performance::calculate_benchmark_report;
+///
+/// // The strategy earns 0.1% a bar plus 1.5 times the benchmark's return
+/// let benchmark_returns = [0.01, -0.02, 0.03, -0.01, 0.02];
+/// let (mut benchmark, mut equity) = (vec![100.0], vec![1000.0]);
+/// for r in benchmark_returns {
+///     benchmark.push(benchmark.last().unwrap() * (1.0 + r));
+///     equity.push(equity.last().unwrap() * (1.0 + 0.001 + 1.5 * r));
+/// }
+/// let df = df! { "equity" => equity, "spy" => benchmark }.unwrap();
+///
+/// let report = calculate_benchmark_report(&df, "equity", "spy", 252.0).unwrap();
+/// assert!((report.beta - 1.5).abs() < 1e-9);
+/// assert!((report.alpha - 25.2).abs() < 1e-9);
+/// // Rising bars average 2%, on which the strategy makes 3.1%
+/// assert!((report.up_capture - 155.0).abs() < 1e-9);
+/// ```
+pub fn calculate_benchmark_report(
+    df: &DataFrame,
+    equity_column: &str,
+    benchmark_column: &str,
+    periods_per_year: f64,
+) -> PolarsResult<BenchmarkReport> {
+    let relative_drawdown = calculate_relative_drawdown(df, equity_column, benchmark_column)?;
+    let strategy_returns = period_returns(df, equity_column)?;
+    let benchmark_returns = period_returns(df, benchmark_column)?;
+
+    let pairs: Vec<(f64, f64)> = benchmark_returns
+        .into_iter()
+        .zip(strategy_returns)
+        .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+        .collect();
+    if pairs.len() < 2 {
+        return Err(PolarsError::ComputeError(
+            format!(
+                "Benchmark report needs at least 2 bars with both returns, got {}",
+                pairs.len()
+            )
+            .into(),
+        ));
+    }
+
+    let n = pairs.len() as f64;
+    let mean = |values: &mut dyn Iterator<Item = f64>| {
+        let (sum, count) = values.fold((0.0, 0usize), |(s, c), v| (s + v, c + 1));
+        if count > 0 {
+            sum / count as f64
+        } else {
+            f64::NAN
+        }
+    };
+    let mean_x = mean(&mut pairs.iter().map(|(x, _)| *x));
+    let mean_y = mean(&mut pairs.iter().map(|(_, y)| *y));
+    let covariance: f64 = pairs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance_x: f64 = pairs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let variance_y: f64 = pairs.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+    let ratio = |a: f64, b: f64| if b != 0.0 { a / b } else { f64::NAN };
+
+    let beta = ratio(covariance, variance_x);
+    let active_mean = mean_y - mean_x;
+    let active_std = (pairs
+        .iter()
+        .map(|(x, y)| (y - x - active_mean).powi(2))
+        .sum::<f64>()
+        / (n - 1.0))
+        .sqrt();
+    let capture = |up: bool| {
+        let bars = || {
+            pairs
+                .iter()
+                .filter(move |(x, _)| if up { *x > 0.0 } else { *x < 0.0 })
+        };
+        ratio(
+            mean(&mut bars().map(|(_, y)| *y)),
+            mean(&mut bars().map(|(x, _)| *x)),
+        ) * 100.0
+    };
+
+    Ok(BenchmarkReport {
+        periods: pairs.len(),
+        alpha: (mean_y - beta * mean_x) * periods_per_year * 100.0,
+        beta,
+        correlation: ratio(covariance, (variance_x * variance_y).sqrt()),
+        active_return: active_mean * periods_per_year * 100.0,
+        tracking_error: active_std * periods_per_year.sqrt() * 100.0,
+        information_ratio: ratio(active_mean * periods_per_year.sqrt(), active_std),
+        up_capture: capture(true),
+        down_capture: capture(false),
+        max_relative_drawdown: relative_drawdown
+            .f64()?
+            .into_iter()
+            .flatten()
+            .filter(|v| !v.is_nan())
+            .fold(0.0, f64::min),
+    })
+}
+
+/// Simple per-period returns of a price or equity column (NaN for the first row)
+pub(crate) fn period_returns(df: &DataFrame, column: &str) -> PolarsResult<Vec<f64>> {
+    let values = df.column(column)?.cast(&DataType::Float64)?;
+    let values = values.f64()?;
+
+    let mut returns = Vec::with_capacity(values.len());
+    let mut previous: Option<f64> = None;
+    for value in values.into_iter() {
+        let value = value.unwrap_or(f64::NAN);
+        returns.push(match previous {
+            Some(prev) if prev != 0.0 && !prev.is_nan() && !value.is_nan() => value / prev - 1.0,
+            _ => f64::NAN,
+        });
+        previous = Some(value);
+    }
+
+    Ok(returns)
+}
+
+/// Strategy equity divided by benchmark price, both rebased to their first valid value
+fn relative_wealth(
+    df: &DataFrame,
+    equity_column: &str,
+    benchmark_column: &str,
+) -> PolarsResult<Vec<f64>> {
+    for col in [equity_column, benchmark_column] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Benchmark comparison requires '{col}' column").into(),
+            ));
+        }
+    }
+
+    let equity = df.column(equity_column)?.cast(&DataType::Float64)?;
+    let benchmark = df.column(benchmark_column)?.cast(&DataType::Float64)?;
+    let equity = equity.f64()?;
+    let benchmark = benchmark.f64()?;
+
+    let mut base: Option<(f64, f64)> = None;
+    Ok(equity
+        .into_iter()
+        .zip(benchmark)
+        .map(|(e, b)| match (e, b) {
+            (Some(e), Some(b)) if !e.is_nan() && !b.is_nan() && b != 0.0 => {
+                let (e0, b0) = *base.get_or_insert((e, b));
+                if e0 == 0.0 {
+                    f64::NAN
+                } else {
+                    (e / e0) / (b / b0)
+                }
+            }
+            _ => f64::NAN,
+        })
+        .collect())
+}
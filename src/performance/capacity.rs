@@ -0,0 +1,212 @@
+use crate::performance::trades::RoundTripTrade;
+use crate::util::dataframe_utils::check_window_size;
+use polars::prelude::*;
+
+/// Assumptions used to scale a backtest to larger capital
+#[derive(Debug, Clone)]
+pub struct CapacityParams {
+    /// Position notional as a fraction of AUM (1.0 = fully invested per trade)
+    pub position_fraction: f64,
+    /// Maximum share of average daily dollar volume one order may take (e.g. 0.05)
+    pub participation_limit: f64,
+    /// Coefficient of the square-root market impact model
+    pub impact_coefficient: f64,
+    /// Quoted bid-ask spread in basis points; half is paid on each side
+    pub spread_bps: f64,
+    /// Number of bars used for the liquidity averages
+    pub liquidity_window: usize,
+}
+
+impl Default for CapacityParams {
+    fn default() -> Self {
+        Self {
+            position_fraction: 1.0,
+            participation_limit: 0.05,
+            impact_coefficient: 1.0,
+            spread_bps: 2.0,
+            liquidity_window: 20,
+        }
+    }
+}
+
+/// Result of a capacity analysis
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapacityEstimate {
+    /// Mean return per trade from the backtest, in percent
+    pub edge_pct: f64,
+    /// Average close * volume over the liquidity window
+    pub average_dollar_volume: f64,
+    /// Standard deviation of close-to-close returns over the liquidity window
+    pub volatility: f64,
+    /// AUM at which spread and impact costs consume the whole edge
+    pub breakeven_aum: f64,
+    /// AUM at which positions reach the participation limit
+    pub participation_aum: f64,
+    /// Smaller of the breakeven and participation AUM
+    pub capacity_aum: f64,
+    impact_coefficient: f64,
+    spread_bps: f64,
+    position_fraction: f64,
+}
+
+impl CapacityEstimate {
+    /// Expected round-trip cost in percent of position notional at a given AUM
+    ///
+    /// Each side pays half the spread plus square-root impact:
+    /// impact = coefficient * volatility * sqrt(notional / average_dollar_volume)
+    pub fn expected_cost_pct(&self, aum: f64) -> f64 {
+        let notional = aum * self.position_fraction;
+        let participation = if self.average_dollar_volume > 0.0 {
+            notional / self.average_dollar_volume
+        } else {
+            f64::INFINITY
+        };
+        let per_side = self.spread_bps / 2.0 / 10_000.0
+            + self.impact_coefficient * self.volatility * participation.sqrt();
+        2.0 * per_side * 100.0
+    }
+
+    /// Backtested edge minus expected costs at a given AUM, in percent per trade
+    pub fn net_edge_pct(&self, aum: f64) -> f64 {
+        self.edge_pct - self.expected_cost_pct(aum)
+    }
+}
+
+/// Estimate how much capital a strategy can run before costs erode its edge
+///
+/// The edge is the mean `return_pct` of the backtested trades, which already
+/// includes the fees charged in the backtest. Liquidity is measured on the
+/// last `liquidity_window` bars of the instrument. Capacity is the lower of
+/// the AUM where spread plus square-root impact equals the edge and the AUM
+/// where a position reaches the participation limit.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with "close" and "volume" columns for the traded instrument
+///   (daily bars, so that volume is daily volume)
+/// * `trades` - Backtested round-trip trades
+/// * `params` - Sizing, participation and cost assumptions
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the CapacityEstimate
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::performance::{estimate_capacity, CapacityParams, RoundTripTrade, TradeSide};
+/// use chrono::{TimeZone, Utc};
+///
+/// let df = df! {
+///     "close" => [100.0, 101.0, 100.0, 101.0, 100.0],
+///     "volume" => [10_000.0; 5],
+/// }
+/// .unwrap();
+///
+/// let trade = RoundTripTrade {
+///     entry_time: Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+///     exit_time: Utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap(),
+///     side: TradeSide::Long,
+///     quantity: 1.0,
+///     entry_price: 100.0,
+///     exit_price: 101.0,
+///     fees: 0.0,
+///     pnl: 1.0,
+///     return_pct: 1.0,
+/// };
+///
+/// let params = CapacityParams { liquidity_window: 5, ..Default::default() };
+/// let estimate = estimate_capacity(&df, &[trade], &params).unwrap();
+/// assert!(estimate.capacity_aum > 0.0);
+/// assert!(estimate.net_edge_pct(estimate.breakeven_aum).abs() < 1e-9);
+/// ```
+pub fn estimate_capacity(
+    df: &DataFrame,
+    trades: &[RoundTripTrade],
+    params: &CapacityParams,
+) -> PolarsResult<CapacityEstimate> {
+    check_window_size(df, params.liquidity_window, "Capacity estimation")?;
+    for col in ["close", "volume"] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Capacity estimation requires '{col}' column").into(),
+            ));
+        }
+    }
+    if trades.is_empty() {
+        return Err(PolarsError::ComputeError(
+            "Capacity estimation requires at least one trade".into(),
+        ));
+    }
+    if params.position_fraction <= 0.0 {
+        return Err(PolarsError::ComputeError(
+            "Position fraction must be positive".into(),
+        ));
+    }
+
+    let close = df.column("close")?.cast(&DataType::Float64)?;
+    let volume = df.column("volume")?.cast(&DataType::Float64)?;
+    let close = close.f64()?;
+    let volume = volume.f64()?;
+
+    let start = df.height() - params.liquidity_window;
+    let mut dollar_volume = Vec::with_capacity(params.liquidity_window);
+    let mut returns = Vec::with_capacity(params.liquidity_window);
+    for i in start..df.height() {
+        let c = close.get(i).unwrap_or(f64::NAN);
+        let v = volume.get(i).unwrap_or(f64::NAN);
+        if !c.is_nan() && !v.is_nan() {
+            dollar_volume.push(c * v);
+        }
+        if i > 0 {
+            let prev = close.get(i - 1).unwrap_or(f64::NAN);
+            if prev != 0.0 && !prev.is_nan() && !c.is_nan() {
+                returns.push(c / prev - 1.0);
+            }
+        }
+    }
+
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let average_dollar_volume = if dollar_volume.is_empty() {
+        0.0
+    } else {
+        mean(&dollar_volume)
+    };
+    let volatility = if returns.len() < 2 {
+        0.0
+    } else {
+        let m = mean(&returns);
+        (returns.iter().map(|r| (r - m).powi(2)).sum::<f64>() / (returns.len() - 1) as f64).sqrt()
+    };
+
+    let edge_pct = trades.iter().map(|t| t.return_pct).sum::<f64>() / trades.len() as f64;
+
+    // Solve edge = 2 * (spread / 2 + k * sigma * sqrt(N / ADV)) for the notional N
+    let edge = edge_pct / 100.0;
+    let spread_cost = params.spread_bps / 10_000.0;
+    let breakeven_notional = if edge <= spread_cost || average_dollar_volume <= 0.0 {
+        0.0
+    } else if volatility <= 0.0 || params.impact_coefficient <= 0.0 {
+        f64::INFINITY
+    } else {
+        let root = (edge - spread_cost) / (2.0 * params.impact_coefficient * volatility);
+        average_dollar_volume * root * root
+    };
+
+    let breakeven_aum = breakeven_notional / params.position_fraction;
+    let participation_aum =
+        params.participation_limit * average_dollar_volume / params.position_fraction;
+
+    Ok(CapacityEstimate {
+        edge_pct,
+        average_dollar_volume,
+        volatility,
+        breakeven_aum,
+        participation_aum,
+        capacity_aum: breakeven_aum.min(participation_aum),
+        impact_coefficient: params.impact_coefficient,
+        spread_bps: params.spread_bps,
+        position_fraction: params.position_fraction,
+    })
+}
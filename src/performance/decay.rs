@@ -0,0 +1,307 @@
+use crate::util::dataframe_utils::series_nan_values;
+use polars::prelude::*;
+
+/// Segmentation and overfitting thresholds for [`calculate_decay_report`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecayParams {
+    /// Number of consecutive segments the history is cut into
+    pub segments: usize,
+    /// Bars per year used to annualize the Sharpe ratio (252 for daily)
+    pub periods_per_year: f64,
+    /// Capital the P&L is earned on, turning P&L changes into returns
+    pub initial_capital: f64,
+    /// Flag when the mean Sharpe of the later segments falls below this
+    /// share of the first segment's Sharpe
+    pub min_efficiency: f64,
+    /// Flag when the returns of the later segments taken together have a
+    /// t-statistic below this value
+    pub min_oos_t_stat: f64,
+}
+
+impl Default for DecayParams {
+    fn default() -> Self {
+        Self {
+            segments: 4,
+            periods_per_year: 252.0,
+            initial_capital: 100_000.0,
+            min_efficiency: 0.5,
+            min_oos_t_stat: 2.0,
+        }
+    }
+}
+
+/// Performance over one segment of the history
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentPerformance {
+    /// First bar of the segment
+    pub start: usize,
+    /// Bar after the last bar of the segment
+    pub end: usize,
+    /// P&L earned over the segment
+    pub pnl: f64,
+    /// Mean per-bar return
+    pub mean_return: f64,
+    /// Annualized Sharpe ratio of the per-bar returns (0.0 without variance)
+    pub sharpe: f64,
+    /// t-statistic of the mean per-bar return (0.0 without variance)
+    pub t_stat: f64,
+}
+
+/// Stability of a strategy's performance across consecutive segments
+///
+/// The first segment plays the part of the in-sample period a parameter
+/// set was chosen on and the later ones the out-of-sample periods that
+/// followed, so a parameter set fitted to noise shows a high first Sharpe
+/// and weak later ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecayReport {
+    /// Performance of each segment, in time order
+    pub segments: Vec<SegmentPerformance>,
+    /// Least-squares slope of the segment Sharpe ratios against segment
+    /// number; negative when performance decays
+    pub sharpe_slope: f64,
+    /// Standard deviation of the segment Sharpe ratios
+    pub sharpe_std: f64,
+    /// Share of segments with a positive Sharpe ratio (0-1)
+    pub positive_share: f64,
+    /// Mean Sharpe of the later segments divided by the first segment's
+    /// Sharpe; NaN when the first Sharpe is not positive
+    pub efficiency: f64,
+    /// t-statistic of the mean per-bar return over all later segments
+    pub oos_t_stat: f64,
+    /// Human-readable descriptions of every threshold breached
+    pub alerts: Vec<String>,
+}
+
+impl DecayReport {
+    /// Whether the performance looks overfit to the first segment
+    pub fn is_overfit(&self) -> bool {
+        !self.alerts.is_empty()
+    }
+}
+
+/// Measures how a strategy's performance holds up over consecutive periods
+///
+/// The history is cut into `params.segments` segments of equal length and
+/// the per-bar returns (P&L changes over capital plus the P&L so far) of
+/// each are summarized. The report is flagged as overfit when the later
+/// segments keep less than `min_efficiency` of the first segment's Sharpe
+/// ratio, or when their combined returns are not significant at
+/// `min_oos_t_stat`.
+///
+/// # Arguments
+///
+/// * `equity` - Cumulative P&L per bar, such as
+///   [`StrategySignals::equity_curve`](crate::strategy::StrategySignals)
+/// * `params` - Segmentation and alert thresholds
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`DecayReport`], or an error when
+/// there are fewer than two segments or fewer than two bars per segment
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::performance::{calculate_decay_report, DecayParams};
+///
+/// // Steady gains for the first 100 bars, then a flat, noisy P&L
+/// let pnl: Vec<f64> = (0..400)
+///     .map(|i| if i < 100 { 50.0 * i as f64 } else { 4950.0 + (i % 2) as f64 * 10.0 })
+///     .collect();
+/// let equity = Series::new("equity".into(), pnl);
+///
+/// let report = calculate_decay_report(&equity, &DecayParams::default()).unwrap();
+/// assert_eq!(report.segments.len(), 4);
+/// assert!(report.segments[0].sharpe > report.segments[3].sharpe);
+/// assert!(report.sharpe_slope < 0.0);
+/// assert!(report.is_overfit());
+/// ```
+pub fn calculate_decay_report(equity: &Series, params: &DecayParams) -> PolarsResult<DecayReport> {
+    if params.segments < 2 {
+        return Err(PolarsError::ComputeError(
+            "Decay report needs at least 2 segments".into(),
+        ));
+    }
+    let pnl = series_nan_values(equity)?;
+    let length = pnl.len() / params.segments;
+    if length < 2 {
+        return Err(PolarsError::ComputeError(
+            format!(
+                "Decay report needs at least 2 bars per segment, got {} bars for {} segments",
+                pnl.len(),
+                params.segments
+            )
+            .into(),
+        ));
+    }
+
+    // Return of bar i over the capital at the close of bar i - 1
+    let returns: Vec<f64> = (0..pnl.len())
+        .map(|i| {
+            let previous = if i == 0 { 0.0 } else { pnl[i - 1] };
+            let current = if i == 0 { pnl[0] } else { pnl[i] };
+            (current - previous) / (params.initial_capital + previous)
+        })
+        .collect();
+
+    // The last segment absorbs the bars left over by the division
+    let segments: Vec<SegmentPerformance> = (0..params.segments)
+        .map(|k| {
+            let start = k * length;
+            let end = if k + 1 == params.segments {
+                pnl.len()
+            } else {
+                start + length
+            };
+            let opening = if start == 0 { 0.0 } else { pnl[start - 1] };
+            let (mean_return, sharpe, t_stat) =
+                return_stats(&returns[start..end], params.periods_per_year);
+            SegmentPerformance {
+                start,
+                end,
+                pnl: pnl[end - 1] - opening,
+                mean_return,
+                sharpe,
+                t_stat,
+            }
+        })
+        .collect();
+
+    let sharpes: Vec<f64> = segments.iter().map(|s| s.sharpe).collect();
+    let n = sharpes.len() as f64;
+    let mean_sharpe = sharpes.iter().sum::<f64>() / n;
+    let mean_index = (n - 1.0) / 2.0;
+    let sharpe_slope = sharpes
+        .iter()
+        .enumerate()
+        .map(|(k, s)| (k as f64 - mean_index) * (s - mean_sharpe))
+        .sum::<f64>()
+        / (0..sharpes.len())
+            .map(|k| (k as f64 - mean_index).powi(2))
+            .sum::<f64>();
+    let sharpe_std = (sharpes
+        .iter()
+        .map(|s| (s - mean_sharpe).powi(2))
+        .sum::<f64>()
+        / (n - 1.0))
+        .sqrt();
+    let positive_share = sharpes.iter().filter(|s| **s > 0.0).count() as f64 / n;
+
+    let first_sharpe = sharpes[0];
+    let later_sharpe = sharpes[1..].iter().sum::<f64>() / (n - 1.0);
+    let efficiency = if first_sharpe > 0.0 {
+        later_sharpe / first_sharpe
+    } else {
+        f64::NAN
+    };
+    let (_, _, oos_t_stat) = return_stats(&returns[segments[1].start..], params.periods_per_year);
+
+    let mut alerts = Vec::new();
+    if efficiency < params.min_efficiency {
+        alerts.push(format!(
+            "later segments keep {:.0}% of the first segment's Sharpe ({:.2} of {:.2}), below {:.0}%",
+            efficiency * 100.0,
+            later_sharpe,
+            first_sharpe,
+            params.min_efficiency * 100.0
+        ));
+    }
+    if oos_t_stat < params.min_oos_t_stat {
+        alerts.push(format!(
+            "later segments have a return t-statistic of {:.2}, below {:.2}",
+            oos_t_stat, params.min_oos_t_stat
+        ));
+    }
+
+    Ok(DecayReport {
+        segments,
+        sharpe_slope,
+        sharpe_std,
+        positive_share,
+        efficiency,
+        oos_t_stat,
+        alerts,
+    })
+}
+
+/// Compares the decay of several parameter sets of one strategy
+///
+/// Runs [`calculate_decay_report`] on the equity curve of each parameter
+/// set, e.g. the candidates of a parameter search, and tabulates the
+/// results so overfit sets can be filtered out.
+///
+/// # Arguments
+///
+/// * `curves` - Name and cumulative P&L per bar of each parameter set
+/// * `params` - Segmentation and alert thresholds
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with one row per parameter
+/// set and the columns "name", "first_sharpe", "later_sharpe",
+/// "efficiency", "sharpe_slope", "positive_share", "oos_t_stat" and
+/// "overfit"
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::performance::{compare_parameter_decay, DecayParams};
+///
+/// let steady: Vec<f64> = (0..400).map(|i| 20.0 * i as f64 + (i % 2) as f64 * 15.0).collect();
+/// let fitted: Vec<f64> = (0..400)
+///     .map(|i| if i < 100 { 50.0 * i as f64 } else { 4950.0 + (i % 2) as f64 * 10.0 })
+///     .collect();
+/// let curves = [
+///     ("fast=5".to_string(), Series::new("equity".into(), steady)),
+///     ("fast=7".to_string(), Series::new("equity".into(), fitted)),
+/// ];
+///
+/// let table = compare_parameter_decay(&curves, &DecayParams::default()).unwrap();
+/// let overfit: Vec<bool> = table.column("overfit").unwrap().bool().unwrap().into_no_null_iter().collect();
+/// assert_eq!(overfit, [false, true]);
+/// ```
+pub fn compare_parameter_decay(
+    curves: &[(String, Series)],
+    params: &DecayParams,
+) -> PolarsResult<DataFrame> {
+    let reports = curves
+        .iter()
+        .map(|(_, equity)| calculate_decay_report(equity, params))
+        .collect::<PolarsResult<Vec<_>>>()?;
+
+    let column = |f: &dyn Fn(&DecayReport) -> f64| reports.iter().map(f).collect::<Vec<f64>>();
+    df! {
+        "name" => curves.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(),
+        "first_sharpe" => column(&|r| r.segments[0].sharpe),
+        "later_sharpe" => column(&|r| {
+            r.segments[1..].iter().map(|s| s.sharpe).sum::<f64>() / (r.segments.len() - 1) as f64
+        }),
+        "efficiency" => column(&|r| r.efficiency),
+        "sharpe_slope" => column(&|r| r.sharpe_slope),
+        "positive_share" => column(&|r| r.positive_share),
+        "oos_t_stat" => column(&|r| r.oos_t_stat),
+        "overfit" => reports.iter().map(DecayReport::is_overfit).collect::<Vec<_>>(),
+    }
+}
+
+/// Mean, annualized Sharpe ratio and t-statistic of per-bar returns
+fn return_stats(returns: &[f64], periods_per_year: f64) -> (f64, f64, f64) {
+    let valid: Vec<f64> = returns.iter().copied().filter(|r| r.is_finite()).collect();
+    if valid.len() < 2 {
+        return (valid.first().copied().unwrap_or(0.0), 0.0, 0.0);
+    }
+    let n = valid.len() as f64;
+    let mean = valid.iter().sum::<f64>() / n;
+    let std = (valid.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    if std <= 0.0 {
+        return (mean, 0.0, 0.0);
+    }
+    (
+        mean,
+        mean / std * periods_per_year.sqrt(),
+        mean / std * n.sqrt(),
+    )
+}
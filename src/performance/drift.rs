@@ -0,0 +1,280 @@
+use crate::performance::trades::{match_fills, parse_fills, Fill};
+use crate::performance::MatchingMethod;
+use chrono::{DateTime, Duration, Utc};
+use polars::prelude::*;
+
+/// Settings and alert thresholds for [`calculate_drift_report`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriftParams {
+    /// Largest time difference at which a live fill still matches a backtest fill
+    pub max_fill_delay: Duration,
+    /// Only fills at or after this time are compared (None: no lower bound)
+    pub start: Option<DateTime<Utc>>,
+    /// Only fills at or before this time are compared (None: no upper bound)
+    pub end: Option<DateTime<Utc>>,
+    /// Alert when the signal match rate falls below this share (0-1)
+    pub min_signal_match_rate: f64,
+    /// Alert when mean adverse slippage exceeds this many basis points
+    pub max_mean_slippage_bps: f64,
+    /// Alert when live P&L deviates from the backtest by more than this percentage
+    pub max_pnl_deviation_pct: f64,
+}
+
+impl Default for DriftParams {
+    fn default() -> Self {
+        Self {
+            max_fill_delay: Duration::minutes(5),
+            start: None,
+            end: None,
+            min_signal_match_rate: 0.9,
+            max_mean_slippage_bps: 10.0,
+            max_pnl_deviation_pct: 25.0,
+        }
+    }
+}
+
+/// Comparison of live trading with the backtest over the same period
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriftReport {
+    /// Backtest fills in the compared period
+    pub backtest_fills: usize,
+    /// Live fills in the compared period
+    pub live_fills: usize,
+    /// Backtest fills with a live fill on the same side within the allowed delay
+    pub matched_fills: usize,
+    /// Backtest fills without a live counterpart
+    pub missed_fills: usize,
+    /// Live fills without a backtest counterpart
+    pub unexpected_fills: usize,
+    /// Matched fills divided by all distinct fills (matched + missed + unexpected)
+    pub signal_match_rate: f64,
+    /// Matched fills whose quantities differ
+    pub quantity_mismatches: usize,
+    /// Mean delay of live fills after their backtest fill, in seconds
+    pub mean_fill_delay_secs: f64,
+    /// Slippage of each matched fill in basis points; positive means the live
+    /// price was worse than the backtest price
+    pub slippage_bps: Vec<f64>,
+    /// Mean of `slippage_bps`
+    pub mean_slippage_bps: f64,
+    /// Median of `slippage_bps`
+    pub median_slippage_bps: f64,
+    /// Standard deviation of `slippage_bps`
+    pub slippage_std_bps: f64,
+    /// 95th percentile of `slippage_bps`
+    pub slippage_p95_bps: f64,
+    /// Realized P&L of the backtest fills after fees
+    pub backtest_pnl: f64,
+    /// Realized P&L of the live fills after fees
+    pub live_pnl: f64,
+    /// Live minus backtest P&L
+    pub pnl_deviation: f64,
+    /// `pnl_deviation` relative to the absolute backtest P&L, in percent
+    pub pnl_deviation_pct: f64,
+    /// Human-readable descriptions of every threshold breached
+    pub alerts: Vec<String>,
+}
+
+impl DriftReport {
+    /// Whether any alert threshold was breached
+    pub fn is_drifting(&self) -> bool {
+        !self.alerts.is_empty()
+    }
+}
+
+/// Compare live (or paper) fills with backtest fills
+///
+/// Backtest fills are matched in time order to the earliest unmatched live
+/// fill on the same side within `max_fill_delay`. The report covers how
+/// many signals were reproduced, the slippage distribution of matched fills,
+/// and the difference in realized P&L (FIFO round trips of each fill set).
+///
+/// # Arguments
+///
+/// * `backtest_fills` - Backtest fills in the format of
+///   [`match_round_trips`](crate::performance::match_round_trips): timestamp,
+///   side, qty, price and optional fee. Use
+///   [`trades_to_fills`](crate::performance::trades_to_fills) to convert a
+///   strategy's trades.
+/// * `live_fills` - Live fills in the same format
+/// * `params` - Matching window, period and alert thresholds
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`DriftReport`]
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::performance::{calculate_drift_report, DriftParams};
+///
+/// let backtest = df! {
+///     "timestamp" => ["2024-01-02 10:00:00", "2024-01-02 11:00:00", "2024-01-02 12:00:00", "2024-01-02 13:00:00"],
+///     "side" => ["buy", "sell", "buy", "sell"],
+///     "qty" => [10.0, 10.0, 10.0, 10.0],
+///     "price" => [100.0, 102.0, 101.0, 103.0],
+/// }
+/// .unwrap();
+/// // Live filled the first round trip a minute late with slippage and missed the second
+/// let live = df! {
+///     "timestamp" => ["2024-01-02 10:01:00", "2024-01-02 11:01:00"],
+///     "side" => ["buy", "sell"],
+///     "qty" => [10.0, 10.0],
+///     "price" => [100.1, 101.9],
+/// }
+/// .unwrap();
+///
+/// let report = calculate_drift_report(&backtest, &live, &DriftParams::default()).unwrap();
+/// assert_eq!(report.matched_fills, 2);
+/// assert_eq!(report.missed_fills, 2);
+/// assert!(report.mean_slippage_bps > 0.0);
+/// assert!(report.is_drifting());
+/// ```
+pub fn calculate_drift_report(
+    backtest_fills: &DataFrame,
+    live_fills: &DataFrame,
+    params: &DriftParams,
+) -> PolarsResult<DriftReport> {
+    let in_period = |fill: &Fill| {
+        params.start.is_none_or(|start| fill.time >= start)
+            && params.end.is_none_or(|end| fill.time <= end)
+    };
+    let mut backtest: Vec<Fill> = parse_fills(backtest_fills)?
+        .into_iter()
+        .filter(|f| in_period(f))
+        .collect();
+    let mut live: Vec<Fill> = parse_fills(live_fills)?
+        .into_iter()
+        .filter(|f| in_period(f))
+        .collect();
+    backtest.sort_by_key(|f| f.time);
+    live.sort_by_key(|f| f.time);
+
+    // Greedy time-ordered matching
+    let mut live_used = vec![false; live.len()];
+    let mut slippage_bps = Vec::new();
+    let mut delays = Vec::new();
+    let mut quantity_mismatches = 0;
+    for fill in &backtest {
+        let candidate = (0..live.len()).find(|&j| {
+            !live_used[j]
+                && live[j].side == fill.side
+                && (live[j].time - fill.time).abs() <= params.max_fill_delay
+        });
+        let Some(j) = candidate else {
+            continue;
+        };
+        live_used[j] = true;
+        let matched = &live[j];
+
+        if fill.price != 0.0 {
+            slippage_bps
+                .push(fill.side.sign() * (matched.price - fill.price) / fill.price * 10_000.0);
+        }
+        delays.push((matched.time - fill.time).num_milliseconds() as f64 / 1000.0);
+        if (matched.quantity - fill.quantity).abs() > 1e-9 * fill.quantity.abs().max(1.0) {
+            quantity_mismatches += 1;
+        }
+    }
+
+    let matched_fills = delays.len();
+    let missed_fills = backtest.len() - matched_fills;
+    let unexpected_fills = live.len() - matched_fills;
+    let distinct = matched_fills + missed_fills + unexpected_fills;
+    let signal_match_rate = if distinct > 0 {
+        matched_fills as f64 / distinct as f64
+    } else {
+        1.0
+    };
+
+    let mut sorted = slippage_bps.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mean_slippage_bps = mean(&sorted);
+    let slippage_std_bps = if sorted.len() > 1 {
+        (sorted
+            .iter()
+            .map(|s| (s - mean_slippage_bps).powi(2))
+            .sum::<f64>()
+            / (sorted.len() - 1) as f64)
+            .sqrt()
+    } else {
+        f64::NAN
+    };
+
+    let backtest_pnl = realized_pnl(&backtest);
+    let live_pnl = realized_pnl(&live);
+    let pnl_deviation = live_pnl - backtest_pnl;
+    let pnl_deviation_pct = if backtest_pnl != 0.0 {
+        pnl_deviation / backtest_pnl.abs() * 100.0
+    } else {
+        f64::NAN
+    };
+
+    let mut alerts = Vec::new();
+    if signal_match_rate < params.min_signal_match_rate {
+        alerts.push(format!(
+            "Signal match rate {:.1}% below {:.1}% ({missed_fills} missed, {unexpected_fills} unexpected fills)",
+            signal_match_rate * 100.0,
+            params.min_signal_match_rate * 100.0
+        ));
+    }
+    if mean_slippage_bps > params.max_mean_slippage_bps {
+        alerts.push(format!(
+            "Mean slippage {mean_slippage_bps:.2} bps above {:.2} bps",
+            params.max_mean_slippage_bps
+        ));
+    }
+    if pnl_deviation_pct.abs() > params.max_pnl_deviation_pct {
+        alerts.push(format!(
+            "Live P&L {live_pnl:.2} deviates {pnl_deviation_pct:.1}% from backtest P&L {backtest_pnl:.2}"
+        ));
+    }
+
+    Ok(DriftReport {
+        backtest_fills: backtest.len(),
+        live_fills: live.len(),
+        matched_fills,
+        missed_fills,
+        unexpected_fills,
+        signal_match_rate,
+        quantity_mismatches,
+        mean_fill_delay_secs: mean(&delays),
+        mean_slippage_bps,
+        median_slippage_bps: percentile(&sorted, 0.5),
+        slippage_std_bps,
+        slippage_p95_bps: percentile(&sorted, 0.95),
+        slippage_bps,
+        backtest_pnl,
+        live_pnl,
+        pnl_deviation,
+        pnl_deviation_pct,
+        alerts,
+    })
+}
+
+/// Realized P&L of fills matched FIFO into round trips
+fn realized_pnl(fills: &[Fill]) -> f64 {
+    match_fills(fills.iter().cloned(), MatchingMethod::Fifo)
+        .iter()
+        .map(|t| t.pnl)
+        .sum()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        f64::NAN
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+/// Linear-interpolated percentile of sorted values
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let rank = q * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
@@ -0,0 +1,41 @@
+//! # Performance Analysis
+//!
+//! This module turns trading activity into trade logs and summary statistics.
+//!
+//! - [`match_round_trips`] pairs externally supplied fills into round-trip trades
+//! - [`trades_to_dataframe`] renders trades as a trade-log DataFrame
+//! - [`PerformanceReport`] summarizes win rate, P&L, drawdown and holding time
+//! - [`calculate_relative_drawdown`] and [`calculate_rolling_alpha_beta`] compare
+//!   a strategy with a buy-and-hold benchmark over time, and
+//!   [`calculate_benchmark_report`] summarizes alpha, beta, tracking error,
+//!   information ratio and up/down capture over a whole backtest
+//! - [`estimate_capacity`] estimates the AUM at which trading costs erode the
+//!   backtested edge
+//! - [`calculate_drift_report`] compares live fills with the backtest to detect
+//!   when live behavior diverges from the simulation
+//! - [`calculate_decay_report`] and [`compare_parameter_decay`] measure how
+//!   performance holds up over consecutive periods, flagging overfit
+//!   parameter sets
+
+mod benchmark;
+mod capacity;
+mod decay;
+mod drift;
+mod report;
+mod trades;
+
+pub use benchmark::{
+    calculate_benchmark_report, calculate_relative_drawdown, calculate_rolling_alpha_beta,
+    BenchmarkReport,
+};
+pub use capacity::{estimate_capacity, CapacityEstimate, CapacityParams};
+pub use decay::{
+    calculate_decay_report, compare_parameter_decay, DecayParams, DecayReport, SegmentPerformance,
+};
+pub use drift::{calculate_drift_report, DriftParams, DriftReport};
+pub use report::PerformanceReport;
+pub(crate) use trades::{match_fills, Fill};
+pub use trades::{
+    match_round_trips, trades_to_dataframe, trades_to_fills, MatchingMethod, RoundTripTrade,
+    TradeSide,
+};
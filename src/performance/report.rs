@@ -0,0 +1,106 @@
+use crate::performance::trades::RoundTripTrade;
+
+/// Summary statistics for a list of round-trip trades
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerformanceReport {
+    /// Number of completed trades
+    pub total_trades: usize,
+    /// Trades with positive P&L
+    pub winning_trades: usize,
+    /// Trades with negative P&L
+    pub losing_trades: usize,
+    /// Share of winning trades in percent
+    pub win_rate: f64,
+    /// Sum of trade P&L after fees
+    pub total_pnl: f64,
+    /// Sum of fees paid
+    pub total_fees: f64,
+    /// Mean P&L of winning trades
+    pub average_win: f64,
+    /// Mean P&L of losing trades (negative)
+    pub average_loss: f64,
+    /// Largest single-trade profit
+    pub largest_win: f64,
+    /// Largest single-trade loss (negative)
+    pub largest_loss: f64,
+    /// Gross profit divided by gross loss
+    pub profit_factor: f64,
+    /// Mean P&L per trade
+    pub expectancy: f64,
+    /// Largest peak-to-trough decline of cumulative trade P&L
+    pub max_drawdown: f64,
+    /// Mean holding period in minutes
+    pub average_holding_minutes: f64,
+}
+
+impl PerformanceReport {
+    /// Build a report from completed trades
+    ///
+    /// Trades are assumed to be in order of exit; the drawdown is measured on
+    /// cumulative P&L in that order.
+    ///
+    /// # Arguments
+    ///
+    /// * `trades` - Completed round-trip trades
+    pub fn from_trades(trades: &[RoundTripTrade]) -> Self {
+        let total_trades = trades.len();
+        let wins: Vec<f64> = trades.iter().map(|t| t.pnl).filter(|&p| p > 0.0).collect();
+        let losses: Vec<f64> = trades.iter().map(|t| t.pnl).filter(|&p| p < 0.0).collect();
+
+        let gross_profit: f64 = wins.iter().sum();
+        let gross_loss: f64 = losses.iter().sum::<f64>().abs();
+        let total_pnl: f64 = trades.iter().map(|t| t.pnl).sum();
+        let mean = |values: &[f64]| {
+            if values.is_empty() {
+                0.0
+            } else {
+                values.iter().sum::<f64>() / values.len() as f64
+            }
+        };
+
+        let mut peak = 0.0_f64;
+        let mut equity = 0.0;
+        let mut max_drawdown = 0.0_f64;
+        for trade in trades {
+            equity += trade.pnl;
+            peak = peak.max(equity);
+            max_drawdown = max_drawdown.max(peak - equity);
+        }
+
+        let holding_minutes: Vec<f64> = trades
+            .iter()
+            .map(|t| t.holding_period().num_seconds() as f64 / 60.0)
+            .collect();
+
+        Self {
+            total_trades,
+            winning_trades: wins.len(),
+            losing_trades: losses.len(),
+            win_rate: if total_trades > 0 {
+                wins.len() as f64 / total_trades as f64 * 100.0
+            } else {
+                0.0
+            },
+            total_pnl,
+            total_fees: trades.iter().map(|t| t.fees).sum(),
+            average_win: mean(&wins),
+            average_loss: mean(&losses),
+            largest_win: wins.iter().copied().fold(0.0, f64::max),
+            largest_loss: losses.iter().copied().fold(0.0, f64::min),
+            profit_factor: if gross_loss > 0.0 {
+                gross_profit / gross_loss
+            } else if gross_profit > 0.0 {
+                f64::INFINITY
+            } else {
+                0.0
+            },
+            expectancy: if total_trades > 0 {
+                total_pnl / total_trades as f64
+            } else {
+                0.0
+            },
+            max_drawdown,
+            average_holding_minutes: mean(&holding_minutes),
+        }
+    }
+}
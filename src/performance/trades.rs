@@ -0,0 +1,378 @@
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Direction of a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TradeSide {
+    Long,
+    Short,
+}
+
+impl TradeSide {
+    /// +1.0 for long positions, -1.0 for short positions
+    pub fn sign(&self) -> f64 {
+        match self {
+            TradeSide::Long => 1.0,
+            TradeSide::Short => -1.0,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            TradeSide::Long => "long",
+            TradeSide::Short => "short",
+        }
+    }
+}
+
+/// Order in which open lots are closed by opposing fills
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchingMethod {
+    /// First in, first out
+    Fifo,
+    /// Last in, first out
+    Lifo,
+}
+
+/// A completed round-trip trade
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundTripTrade {
+    /// Time of the opening fill
+    pub entry_time: DateTime<Utc>,
+    /// Time of the closing fill
+    pub exit_time: DateTime<Utc>,
+    /// Direction of the position
+    pub side: TradeSide,
+    /// Quantity closed by this round trip
+    pub quantity: f64,
+    /// Average entry price
+    pub entry_price: f64,
+    /// Average exit price
+    pub exit_price: f64,
+    /// Fees attributed to this round trip (entry and exit share)
+    pub fees: f64,
+    /// Profit or loss after fees
+    pub pnl: f64,
+    /// Return relative to the entry notional, after fees
+    pub return_pct: f64,
+}
+
+impl RoundTripTrade {
+    /// Time between entry and exit
+    pub fn holding_period(&self) -> chrono::Duration {
+        self.exit_time - self.entry_time
+    }
+}
+
+/// A single parsed fill
+#[derive(Debug, Clone)]
+pub(crate) struct Fill {
+    pub time: DateTime<Utc>,
+    pub side: TradeSide,
+    pub quantity: f64,
+    pub price: f64,
+    pub fee: f64,
+}
+
+/// Parse a fills DataFrame (see [`match_round_trips`]) into fills in row order
+pub(crate) fn parse_fills(fills: &DataFrame) -> PolarsResult<Vec<Fill>> {
+    for col in ["timestamp", "side", "qty", "price"] {
+        if !fills.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Fills DataFrame requires '{col}' column").into(),
+            ));
+        }
+    }
+
+    let timestamps = parse_timestamp_column(fills, "timestamp")?;
+    let sides = fills.column("side")?.str()?;
+    let qty = fills.column("qty")?.cast(&DataType::Float64)?;
+    let qty = qty.f64()?;
+    let price = fills.column("price")?.cast(&DataType::Float64)?;
+    let price = price.f64()?;
+    let fee = if fills.schema().contains("fee") {
+        Some(fills.column("fee")?.cast(&DataType::Float64)?)
+    } else {
+        None
+    };
+    let fee = fee.as_ref().map(|f| f.f64()).transpose()?;
+
+    let mut parsed = Vec::with_capacity(fills.height());
+    for (i, time) in timestamps.into_iter().enumerate() {
+        let time = time.ok_or_else(|| {
+            PolarsError::ComputeError(format!("Invalid fill timestamp at row {i}").into())
+        })?;
+        let side = match sides.get(i).map(|s| s.to_ascii_lowercase()).as_deref() {
+            Some("buy") | Some("b") => TradeSide::Long,
+            Some("sell") | Some("s") => TradeSide::Short,
+            other => {
+                return Err(PolarsError::ComputeError(
+                    format!("Invalid fill side {other:?} at row {i}").into(),
+                ))
+            }
+        };
+        let (Some(quantity), Some(price)) = (qty.get(i), price.get(i)) else {
+            return Err(PolarsError::ComputeError(
+                format!("Missing fill quantity or price at row {i}").into(),
+            ));
+        };
+        if !(quantity.is_finite() && quantity > 0.0 && price.is_finite() && price > 0.0) {
+            return Err(PolarsError::ComputeError(
+                format!("Invalid fill quantity {quantity} or price {price} at row {i}").into(),
+            ));
+        }
+        parsed.push(Fill {
+            time,
+            side,
+            quantity,
+            price,
+            fee: fee.and_then(|f| f.get(i)).unwrap_or(0.0),
+        });
+    }
+
+    Ok(parsed)
+}
+
+/// Open lot waiting to be matched
+struct Lot {
+    time: DateTime<Utc>,
+    side: TradeSide,
+    quantity: f64,
+    price: f64,
+    fee_per_unit: f64,
+}
+
+/// Match externally supplied fills into round-trip trades
+///
+/// Fills are processed in row order. A fill in the direction of the open
+/// position (or when flat) opens a new lot; an opposing fill closes open
+/// lots in FIFO or LIFO order, and any quantity left over after the position
+/// is flat opens a position in the other direction. Lots still open after
+/// the last fill are not reported.
+///
+/// # Arguments
+///
+/// * `fills` - DataFrame with columns:
+///   - `timestamp`: fill time (string, Datetime or Date)
+///   - `side`: "buy"/"sell" (or "b"/"s", case-insensitive)
+///   - `qty`: filled quantity (positive)
+///   - `price`: fill price (positive)
+///   - `fee` (optional): total fee charged for the fill
+/// * `method` - Lot matching method
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the completed trades in order of exit,
+/// or ComputeError naming the row of a fill with a missing, non-positive or
+/// non-finite quantity or price
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::performance::{match_round_trips, MatchingMethod};
+///
+/// let fills = df! {
+///     "timestamp" => ["2024-01-02 14:30:00", "2024-01-02 15:00:00", "2024-01-03 15:00:00"],
+///     "side" => ["buy", "buy", "sell"],
+///     "qty" => [10.0, 10.0, 15.0],
+///     "price" => [100.0, 102.0, 105.0],
+/// }
+/// .unwrap();
+///
+/// let trades = match_round_trips(&fills, MatchingMethod::Fifo).unwrap();
+/// assert_eq!(trades.len(), 2);
+/// assert_eq!(trades[0].pnl, 50.0); // 10 @ 100 -> 105
+/// assert_eq!(trades[1].pnl, 15.0); // 5 @ 102 -> 105
+///
+/// // A signed quantity is rejected, not skipped
+/// let mut signed = fills.clone();
+/// signed.replace("qty", Series::new("qty".into(), [10.0, 10.0, -15.0])).unwrap();
+/// let err = match_round_trips(&signed, MatchingMethod::Fifo).unwrap_err();
+/// assert!(err.to_string().contains("row 2"));
+/// ```
+pub fn match_round_trips(
+    fills: &DataFrame,
+    method: MatchingMethod,
+) -> PolarsResult<Vec<RoundTripTrade>> {
+    Ok(match_fills(parse_fills(fills)?, method))
+}
+
+/// Match parsed fills into round trips (see [`match_round_trips`])
+pub(crate) fn match_fills(
+    fills: impl IntoIterator<Item = Fill>,
+    method: MatchingMethod,
+) -> Vec<RoundTripTrade> {
+    let mut open_lots: VecDeque<Lot> = VecDeque::new();
+    let mut trades = Vec::new();
+
+    for fill in fills {
+        let Fill {
+            time,
+            side,
+            quantity: mut remaining,
+            price: fill_price,
+            fee,
+        } = fill;
+        if remaining <= 0.0 {
+            continue;
+        }
+        let fee_per_unit = fee / remaining;
+
+        // Close opposing lots first
+        while remaining > f64::EPSILON {
+            // All open lots share one direction, so checking the front suffices
+            if open_lots.front().is_none_or(|lot| lot.side == side) {
+                break;
+            }
+            let lot = match method {
+                MatchingMethod::Fifo => open_lots.front_mut(),
+                MatchingMethod::Lifo => open_lots.back_mut(),
+            };
+            let Some(lot) = lot else { break };
+
+            let matched = remaining.min(lot.quantity);
+            let fees = (lot.fee_per_unit + fee_per_unit) * matched;
+            let gross = (fill_price - lot.price) * matched * lot.side.sign();
+            let pnl = gross - fees;
+            let notional = lot.price * matched;
+
+            trades.push(RoundTripTrade {
+                entry_time: lot.time,
+                exit_time: time,
+                side: lot.side,
+                quantity: matched,
+                entry_price: lot.price,
+                exit_price: fill_price,
+                fees,
+                pnl,
+                return_pct: if notional != 0.0 {
+                    pnl / notional * 100.0
+                } else {
+                    0.0
+                },
+            });
+
+            lot.quantity -= matched;
+            remaining -= matched;
+            if lot.quantity <= f64::EPSILON {
+                match method {
+                    MatchingMethod::Fifo => open_lots.pop_front(),
+                    MatchingMethod::Lifo => open_lots.pop_back(),
+                };
+            }
+        }
+
+        // Whatever is left opens (or adds to) a position in the fill's direction
+        if remaining > f64::EPSILON {
+            open_lots.push_back(Lot {
+                time,
+                side,
+                quantity: remaining,
+                price: fill_price,
+                fee_per_unit,
+            });
+        }
+    }
+
+    trades
+}
+
+/// Convert round-trip trades into a trade-log DataFrame
+///
+/// # Arguments
+///
+/// * `trades` - Completed trades, e.g. from [`match_round_trips`]
+///
+/// # Returns
+///
+/// Returns a DataFrame with one row per trade and columns entry_time,
+/// exit_time, side, quantity, entry_price, exit_price, fees, pnl and return_pct
+pub fn trades_to_dataframe(trades: &[RoundTripTrade]) -> PolarsResult<DataFrame> {
+    let format_time = |t: &DateTime<Utc>| t.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+
+    df! {
+        "entry_time" => trades.iter().map(|t| format_time(&t.entry_time)).collect::<Vec<_>>(),
+        "exit_time" => trades.iter().map(|t| format_time(&t.exit_time)).collect::<Vec<_>>(),
+        "side" => trades.iter().map(|t| t.side.as_str()).collect::<Vec<_>>(),
+        "quantity" => trades.iter().map(|t| t.quantity).collect::<Vec<_>>(),
+        "entry_price" => trades.iter().map(|t| t.entry_price).collect::<Vec<_>>(),
+        "exit_price" => trades.iter().map(|t| t.exit_price).collect::<Vec<_>>(),
+        "fees" => trades.iter().map(|t| t.fees).collect::<Vec<_>>(),
+        "pnl" => trades.iter().map(|t| t.pnl).collect::<Vec<_>>(),
+        "return_pct" => trades.iter().map(|t| t.return_pct).collect::<Vec<_>>(),
+    }
+}
+
+/// Convert round-trip trades back into fills
+///
+/// Each trade yields an opening and a closing fill, each carrying half of the
+/// trade's fees, so a backtest can be compared fill by fill with live trading
+/// or re-matched with [`match_round_trips`].
+///
+/// # Arguments
+///
+/// * `trades` - Completed trades, e.g. `StrategySignals::trades`
+///
+/// # Returns
+///
+/// Returns a fills DataFrame sorted by time with columns timestamp, side
+/// ("buy"/"sell"), qty, price and fee
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::performance::{match_round_trips, trades_to_fills, MatchingMethod};
+///
+/// let fills = df! {
+///     "timestamp" => ["2024-01-02 14:30:00", "2024-01-02 15:00:00"],
+///     "side" => ["sell", "buy"],
+///     "qty" => [5.0, 5.0],
+///     "price" => [100.0, 98.0],
+///     "fee" => [1.0, 1.0],
+/// }
+/// .unwrap();
+/// let trades = match_round_trips(&fills, MatchingMethod::Fifo).unwrap();
+///
+/// let rebuilt = trades_to_fills(&trades).unwrap();
+/// assert_eq!(rebuilt.height(), 2);
+/// assert_eq!(match_round_trips(&rebuilt, MatchingMethod::Fifo).unwrap(), trades);
+/// ```
+pub fn trades_to_fills(trades: &[RoundTripTrade]) -> PolarsResult<DataFrame> {
+    let mut fills: Vec<(DateTime<Utc>, &str, f64, f64, f64)> = Vec::with_capacity(trades.len() * 2);
+    for trade in trades {
+        let (open, close) = match trade.side {
+            TradeSide::Long => ("buy", "sell"),
+            TradeSide::Short => ("sell", "buy"),
+        };
+        let fee = trade.fees / 2.0;
+        fills.push((
+            trade.entry_time,
+            open,
+            trade.quantity,
+            trade.entry_price,
+            fee,
+        ));
+        fills.push((
+            trade.exit_time,
+            close,
+            trade.quantity,
+            trade.exit_price,
+            fee,
+        ));
+    }
+    fills.sort_by_key(|f| f.0);
+
+    df! {
+        "timestamp" => fills.iter().map(|f| f.0.format("%Y-%m-%d %H:%M:%S").to_string()).collect::<Vec<_>>(),
+        "side" => fills.iter().map(|f| f.1).collect::<Vec<_>>(),
+        "qty" => fills.iter().map(|f| f.2).collect::<Vec<_>>(),
+        "price" => fills.iter().map(|f| f.3).collect::<Vec<_>>(),
+        "fee" => fills.iter().map(|f| f.4).collect::<Vec<_>>(),
+    }
+}
@@ -0,0 +1,436 @@
+//! # Charts
+//!
+//! SVG charts for checking indicators and strategy signals by eye (`plot`
+//! feature).
+//!
+//! - [`plot_indicators`]: Candlesticks (or the close line) with indicator
+//!   overlays
+//! - [`plot_strategy_result`]: The same price panel with buy and sell
+//!   markers, above equity and drawdown panels
+//!
+//! The x axis counts bars and is labelled with their times, so nights,
+//! weekends and other gaps take no space. Charts are written as SVG; PNG
+//! output would need plotters' bitmap backend, which this feature leaves out
+//! to keep the dependency small.
+
+use crate::error::{TaError, TaResult};
+use crate::strategy::StrategySignals;
+use crate::util::column_map::ColumnMap;
+use crate::util::dataframe_utils::{column_nan_values, series_nan_values};
+use crate::util::time_utils::parse_timestamp_column;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use polars::prelude::*;
+use std::path::Path;
+
+/// Chart size and the columns to draw
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotOptions {
+    /// Chart width in pixels
+    pub width: u32,
+    /// Chart height in pixels
+    pub height: u32,
+    /// Caption above the price panel
+    pub title: Option<String>,
+    /// Name of the timestamp column used for the axis labels
+    pub time_column: String,
+    /// Price columns; candlesticks need open, high, low and close, otherwise
+    /// the close is drawn as a line
+    pub columns: ColumnMap,
+    /// Indicator columns drawn over the prices, such as moving averages or
+    /// bands
+    pub overlays: Vec<String>,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        Self {
+            width: 1200,
+            height: 800,
+            title: None,
+            time_column: "timestamp".to_string(),
+            columns: ColumnMap::default(),
+            overlays: Vec::new(),
+        }
+    }
+}
+
+/// Draw prices with indicator overlays to an SVG file
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with the time column, the price columns and the
+///   overlay columns named in `options`
+/// * `path` - SVG file to write
+/// * `options` - Chart size and columns
+///
+/// # Returns
+///
+/// Returns a TaResult that is an error when a column is missing, `path` is
+/// not an ".svg" file or the file cannot be written
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::indicators::moving_averages::calculate_sma;
+/// use rustalib::plot::{plot_indicators, PlotOptions};
+///
+/// let mut df = df! {
+///     "timestamp" => ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"],
+///     "open" => [100.0, 101.0, 103.0, 102.0],
+///     "high" => [102.0, 104.0, 104.0, 103.0],
+///     "low" => [99.0, 100.0, 101.0, 100.0],
+///     "close" => [101.0, 103.0, 102.0, 101.0],
+/// }
+/// .unwrap();
+/// let sma = calculate_sma(&df, "close", 2).unwrap().with_name("sma_2".into());
+/// df.with_column(sma).unwrap();
+///
+/// let options = PlotOptions {
+///     overlays: vec!["sma_2".to_string()],
+///     ..Default::default()
+/// };
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("sma.svg");
+/// plot_indicators(&df, &path, &options).unwrap();
+/// assert!(std::fs::read_to_string(&path).unwrap().starts_with("<svg"));
+/// ```
+pub fn plot_indicators(
+    df: &DataFrame,
+    path: impl AsRef<Path>,
+    options: &PlotOptions,
+) -> TaResult<()> {
+    draw(df, None, path.as_ref(), options)
+}
+
+/// Draw a strategy run to an SVG file with the default [`PlotOptions`]
+///
+/// See [`plot_strategy_result_with_options`].
+pub fn plot_strategy_result(
+    df: &DataFrame,
+    signals: &StrategySignals,
+    path: impl AsRef<Path>,
+) -> TaResult<()> {
+    plot_strategy_result_with_options(df, signals, path, &PlotOptions::default())
+}
+
+/// Draw a strategy run to an SVG file
+///
+/// The price panel marks bars with buy signals by a green triangle below the
+/// bar and bars with sell signals by a red triangle above it. Below it are
+/// [`StrategySignals::equity_curve`] and [`StrategySignals::drawdown`].
+/// Overlay columns are taken from `df` or else from
+/// [`StrategySignals::indicator_values`].
+///
+/// # Arguments
+///
+/// * `df` - The bars the strategy ran on
+/// * `signals` - Output of the strategy run, one value per bar of `df`
+/// * `path` - SVG file to write
+/// * `options` - Chart size and columns
+///
+/// # Returns
+///
+/// Returns a TaResult that is an error when a column is missing, the signals
+/// do not match the bars, `path` is not an ".svg" file or the file cannot be
+/// written
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::plot::{plot_strategy_result_with_options, PlotOptions};
+/// use rustalib::strategy::crypto::grid_trading::{run_strategy, StrategyParams};
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"],
+///     "open" => [100.0, 96.0, 104.0, 101.0],
+///     "high" => [101.0, 97.0, 106.0, 102.0],
+///     "low" => [99.0, 94.0, 103.0, 99.0],
+///     "close" => [100.0, 95.0, 105.0, 100.0],
+/// }
+/// .unwrap();
+/// let signals = run_strategy(&df, &StrategyParams::new(90.0, 110.0)).unwrap();
+///
+/// let options = PlotOptions {
+///     title: Some("Grid 90-110".to_string()),
+///     overlays: vec!["grid_inventory".to_string()],
+///     ..Default::default()
+/// };
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("grid.svg");
+/// plot_strategy_result_with_options(&df, &signals, &path, &options).unwrap();
+/// assert!(std::fs::read_to_string(&path).unwrap().contains("Grid 90-110"));
+/// ```
+pub fn plot_strategy_result_with_options(
+    df: &DataFrame,
+    signals: &StrategySignals,
+    path: impl AsRef<Path>,
+    options: &PlotOptions,
+) -> TaResult<()> {
+    if signals.buy_signals.len() != df.height() {
+        return Err(TaError::invalid_parameter(
+            "signals",
+            format!(
+                "{} bars of signals for {} bars of data",
+                signals.buy_signals.len(),
+                df.height()
+            ),
+        ));
+    }
+    draw(df, Some(signals), path.as_ref(), options)
+}
+
+type Chart<'a, 'b> = ChartContext<'a, SVGBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>;
+
+fn draw(
+    df: &DataFrame,
+    signals: Option<&StrategySignals>,
+    path: &Path,
+    options: &PlotOptions,
+) -> TaResult<()> {
+    if !path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+    {
+        return Err(TaError::invalid_parameter(
+            "path",
+            "charts are written as SVG, use a \".svg\" file",
+        ));
+    }
+    let n = df.height();
+    if n == 0 {
+        return Err(TaError::InsufficientData {
+            indicator: "chart".to_string(),
+            needed: 1,
+            got: 0,
+        });
+    }
+
+    let labels: Vec<String> = parse_timestamp_column(df, &options.time_column)?
+        .into_iter()
+        .map(|t| t.map_or_else(String::new, |t| t.format("%Y-%m-%d %H:%M").to_string()))
+        .collect();
+    let columns = &options.columns;
+    let close = values(df, &columns.close)?;
+    let candles = if [&columns.open, &columns.high, &columns.low]
+        .iter()
+        .all(|col| df.schema().contains(col))
+    {
+        Some((
+            values(df, &columns.open)?,
+            values(df, &columns.high)?,
+            values(df, &columns.low)?,
+        ))
+    } else {
+        None
+    };
+    let mut overlays = Vec::with_capacity(options.overlays.len());
+    for name in &options.overlays {
+        let source = match signals {
+            Some(signals) if !df.schema().contains(name) => &signals.indicator_values,
+            _ => df,
+        };
+        overlays.push((name.as_str(), values(source, name)?));
+    }
+
+    let root = SVGBackend::new(path, (options.width, options.height)).into_drawing_area();
+    root.fill(&WHITE).map_err(draw_error)?;
+    let (price_area, lower_area) = match signals {
+        Some(_) => {
+            let (upper, lower) = root.split_vertically(options.height * 3 / 5);
+            (upper, Some(lower))
+        }
+        None => (root.clone(), None),
+    };
+
+    // Price panel
+    let (low, high) = match &candles {
+        Some((_, high, low)) => (low, high),
+        None => (&close, &close),
+    };
+    let y_range = bounds(
+        low.iter()
+            .chain(high)
+            .chain(overlays.iter().flat_map(|(_, v)| v)),
+    );
+    let mut chart = panel(
+        &price_area,
+        &labels,
+        y_range,
+        "Price",
+        options.title.as_deref(),
+    )?;
+    match &candles {
+        Some((open, high, low)) => {
+            let width = (options.width as f64 / n as f64 * 0.6).clamp(1.0, 15.0) as u32;
+            let bars = (0..n)
+                .filter(|&i| {
+                    [open[i], high[i], low[i], close[i]]
+                        .iter()
+                        .all(|v| v.is_finite())
+                })
+                .map(|i| {
+                    CandleStick::new(
+                        i as f64,
+                        open[i],
+                        high[i],
+                        low[i],
+                        close[i],
+                        GREEN.filled(),
+                        RED.filled(),
+                        width,
+                    )
+                });
+            chart.draw_series(bars).map_err(draw_error)?;
+        }
+        None => {
+            chart
+                .draw_series(LineSeries::new(points(&close), BLACK))
+                .map_err(draw_error)?;
+        }
+    }
+    for (k, (name, values)) in overlays.iter().enumerate() {
+        let color = Palette99::pick(k);
+        chart
+            .draw_series(LineSeries::new(points(values), color.stroke_width(2)))
+            .map_err(draw_error)?
+            .label(*name)
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color.stroke_width(2)));
+    }
+
+    if let (Some(signals), Some(lower_area)) = (signals, lower_area) {
+        let buys = (0..n)
+            .filter(|&i| signals.buy_signals[i] > 0 && low[i].is_finite())
+            .map(|i| {
+                EmptyElement::at((i as f64, low[i]))
+                    + Polygon::new([(0, 4), (-5, 12), (5, 12)], GREEN.filled())
+            });
+        chart
+            .draw_series(buys)
+            .map_err(draw_error)?
+            .label("buy")
+            .legend(|(x, y)| TriangleMarker::new((x + 10, y), 5, GREEN.filled()));
+        let sells = (0..n)
+            .filter(|&i| signals.sell_signals[i] > 0 && high[i].is_finite())
+            .map(|i| {
+                EmptyElement::at((i as f64, high[i]))
+                    + Polygon::new([(0, -4), (-5, -12), (5, -12)], RED.filled())
+            });
+        chart
+            .draw_series(sells)
+            .map_err(draw_error)?
+            .label("sell")
+            .legend(|(x, y)| Cross::new((x + 10, y), 4, RED));
+        draw_legend(&mut chart)?;
+
+        // Equity and drawdown panels
+        let (equity_area, drawdown_area) = lower_area.split_vertically(options.height / 5);
+        let equity = series_nan_values(&signals.equity_curve)?;
+        let mut chart = panel(&equity_area, &labels, bounds(&equity), "Equity", None)?;
+        chart
+            .draw_series(LineSeries::new(points(&equity), BLUE.stroke_width(2)))
+            .map_err(draw_error)?;
+
+        let drawdown: Vec<f64> = series_nan_values(&signals.drawdown)?
+            .iter()
+            .map(|d| -d)
+            .collect();
+        let (low, _) = bounds(&drawdown);
+        let mut chart = panel(&drawdown_area, &labels, (low, 0.0), "Drawdown", None)?;
+        chart
+            .draw_series(AreaSeries::new(points(&drawdown), 0.0, RED.mix(0.3)).border_style(RED))
+            .map_err(draw_error)?;
+    } else if !overlays.is_empty() {
+        draw_legend(&mut chart)?;
+    }
+
+    root.present().map_err(draw_error)?;
+    Ok(())
+}
+
+/// Chart on `area` with bar numbers on the x axis, labelled with bar times
+fn panel<'a, 'b>(
+    area: &'a DrawingArea<SVGBackend<'b>, Shift>,
+    labels: &[String],
+    y_range: (f64, f64),
+    y_desc: &str,
+    caption: Option<&str>,
+) -> TaResult<Chart<'a, 'b>> {
+    let mut builder = ChartBuilder::on(area);
+    builder
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(70);
+    if let Some(caption) = caption {
+        builder.caption(caption, ("sans-serif", 20));
+    }
+    let mut chart = builder
+        .build_cartesian_2d(-0.5..labels.len() as f64 - 0.5, y_range.0..y_range.1)
+        .map_err(draw_error)?;
+
+    let label = |x: &f64| {
+        let i = x.round();
+        if i >= 0.0 && (i as usize) < labels.len() {
+            labels[i as usize].clone()
+        } else {
+            String::new()
+        }
+    };
+    chart
+        .configure_mesh()
+        .x_labels(8)
+        .x_label_formatter(&label)
+        .y_desc(y_desc)
+        .draw()
+        .map_err(draw_error)?;
+    Ok(chart)
+}
+
+fn draw_legend<'a, 'b: 'a>(chart: &mut Chart<'a, 'b>) -> TaResult<()> {
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(draw_error)
+}
+
+/// Column as `f64` values with nulls as NaN
+fn values(df: &DataFrame, column: &str) -> TaResult<Vec<f64>> {
+    if !df.schema().contains(column) {
+        return Err(TaError::MissingColumn(column.to_string()));
+    }
+    Ok(column_nan_values(df, column)?)
+}
+
+/// Finite values paired with their bar number
+fn points(values: &[f64]) -> Vec<(f64, f64)> {
+    values
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.is_finite())
+        .map(|(i, &v)| (i as f64, v))
+        .collect()
+}
+
+/// Range of the finite values with 5% padding
+fn bounds<'a>(values: impl IntoIterator<Item = &'a f64>) -> (f64, f64) {
+    let (low, high) = values
+        .into_iter()
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), &v| {
+            (low.min(v), high.max(v))
+        });
+    if low > high {
+        return (0.0, 1.0);
+    }
+    let pad = ((high - low) * 0.05).max(1e-6 * high.abs().max(1.0));
+    (low - pad, high + pad)
+}
+
+fn draw_error(err: impl std::fmt::Display) -> TaError {
+    TaError::ComputationError(format!("Cannot draw chart: {err}"))
+}
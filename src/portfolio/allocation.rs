@@ -0,0 +1,474 @@
+//! # Portfolio Weights
+//!
+//! Minimum-variance, maximum-Sharpe and risk-parity weights from the sample
+//! covariance of asset returns. Every solver takes a DataFrame with one
+//! return column per asset and uses the rows where every asset has a
+//! return. Weights sum to 1; long-only weights are found iteratively by
+//! projected gradient steps on the weight simplex.
+
+use crate::error::TaError;
+use crate::util::dataframe_utils::series_nan_values;
+use polars::prelude::*;
+
+/// Steps of the iterative solvers before they stop
+const MAX_ITERATIONS: usize = 10_000;
+/// Largest weight change between two steps at which a solver has converged
+const TOLERANCE: f64 = 1e-12;
+
+/// Weights of a portfolio with its risk decomposition
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortfolioWeights {
+    /// Asset names, the column names of the returns
+    pub assets: Vec<String>,
+    /// Capital weight of each asset, summing to 1
+    pub weights: Vec<f64>,
+    /// Standard deviation of the portfolio's per-bar returns
+    pub volatility: f64,
+    /// Share of the portfolio variance from each asset, summing to 1
+    pub risk_contributions: Vec<f64>,
+}
+
+impl PortfolioWeights {
+    /// Weight of `asset`, if it is in the portfolio
+    pub fn weight(&self, asset: &str) -> Option<f64> {
+        self.assets
+            .iter()
+            .position(|a| a == asset)
+            .map(|i| self.weights[i])
+    }
+
+    /// The weights as a DataFrame with "asset", "weight" and
+    /// "risk_contribution" columns
+    pub fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        DataFrame::new(vec![
+            Column::new("asset".into(), &self.assets),
+            Column::new("weight".into(), &self.weights),
+            Column::new("risk_contribution".into(), &self.risk_contributions),
+        ])
+    }
+}
+
+/// Calculates minimum-variance weights
+///
+/// Without the long-only constraint the weights are Σ⁻¹1 / 1'Σ⁻¹1 for the
+/// covariance matrix Σ and may be negative (short).
+///
+/// # Arguments
+///
+/// * `returns` - DataFrame with one per-bar return column per asset
+/// * `long_only` - Restrict the weights to be non-negative
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`PortfolioWeights`], or a
+/// ComputeError when fewer than two complete rows remain or, without
+/// `long_only`, the covariance matrix is singular
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::portfolio::allocation::min_variance_weights;
+///
+/// // Uncorrelated assets, the second twice as volatile as the first
+/// let returns = df! {
+///     "bonds" => [0.01, -0.01, 0.01, -0.01],
+///     "stocks" => [0.02, 0.02, -0.02, -0.02],
+/// }
+/// .unwrap();
+///
+/// // Weights proportional to the inverse variances, 1 : 1/4
+/// let portfolio = min_variance_weights(&returns, false).unwrap();
+/// assert!((portfolio.weight("bonds").unwrap() - 0.8).abs() < 1e-9);
+/// assert!((portfolio.weight("stocks").unwrap() - 0.2).abs() < 1e-9);
+/// ```
+pub fn min_variance_weights(
+    returns: &DataFrame,
+    long_only: bool,
+) -> PolarsResult<PortfolioWeights> {
+    let (assets, covariance) = sample_moments(returns)?;
+    let n = assets.len();
+
+    let weights = if long_only {
+        let step = 1.0 / (2.0 * spectral_bound(&covariance));
+        let mut weights = vec![1.0 / n as f64; n];
+        for _ in 0..MAX_ITERATIONS {
+            let gradient = mat_vec(&covariance, &weights);
+            let next = project_to_simplex(
+                &weights
+                    .iter()
+                    .zip(&gradient)
+                    .map(|(w, g)| w - step * 2.0 * g)
+                    .collect::<Vec<_>>(),
+            );
+            let change = max_change(&weights, &next);
+            weights = next;
+            if change < TOLERANCE {
+                break;
+            }
+        }
+        weights
+    } else {
+        let inverse_ones = solve(&covariance, &vec![1.0; n]).ok_or_else(singular)?;
+        normalized(&inverse_ones).ok_or_else(singular)?
+    };
+
+    Ok(portfolio_weights(assets, &covariance, weights))
+}
+
+/// Calculates maximum-Sharpe (tangency) weights
+///
+/// The weights maximize (μ - r_f)'w / √(w'Σw) for the expected returns μ,
+/// the risk-free rate r_f and the covariance matrix Σ. Without the
+/// long-only constraint they are Σ⁻¹(μ - r_f) scaled to sum to 1.
+///
+/// # Arguments
+///
+/// * `returns` - DataFrame with one per-bar return column per asset
+/// * `expected_returns` - Expected per-bar return of each asset, in column
+///   order
+/// * `risk_free_rate` - Per-bar risk-free return
+/// * `long_only` - Restrict the weights to be non-negative
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`PortfolioWeights`], an
+/// InvalidParameter error when `expected_returns` has the wrong length or
+/// no portfolio has a positive expected excess return, or a ComputeError
+/// when fewer than two complete rows remain or, without `long_only`, the
+/// covariance matrix is singular
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::portfolio::allocation::max_sharpe_weights;
+///
+/// let returns = df! {
+///     "bonds" => [0.01, -0.01, 0.01, -0.01],
+///     "stocks" => [0.02, 0.02, -0.02, -0.02],
+/// }
+/// .unwrap();
+///
+/// // Stocks are twice as volatile and expected to earn four times as much
+/// let portfolio = max_sharpe_weights(&returns, &[0.001, 0.004], 0.0, true).unwrap();
+/// assert!((portfolio.weight("bonds").unwrap() - 0.5).abs() < 1e-6);
+/// assert!((portfolio.weight("stocks").unwrap() - 0.5).abs() < 1e-6);
+/// ```
+pub fn max_sharpe_weights(
+    returns: &DataFrame,
+    expected_returns: &[f64],
+    risk_free_rate: f64,
+    long_only: bool,
+) -> PolarsResult<PortfolioWeights> {
+    let (assets, covariance) = sample_moments(returns)?;
+    let n = assets.len();
+    if expected_returns.len() != n {
+        return Err(TaError::invalid_parameter(
+            "expected_returns",
+            format!(
+                "needs one value per asset, got {} for {n}",
+                expected_returns.len()
+            ),
+        )
+        .into());
+    }
+    let excess: Vec<f64> = expected_returns
+        .iter()
+        .map(|r| r - risk_free_rate)
+        .collect();
+    let no_excess = || {
+        PolarsError::from(TaError::invalid_parameter(
+            "expected_returns",
+            "no portfolio has a positive expected excess return",
+        ))
+    };
+
+    let weights = if long_only {
+        let sharpe = |w: &[f64]| dot(&excess, w) / dot(w, &mat_vec(&covariance, w)).sqrt();
+        // Start from the single asset with the best Sharpe ratio
+        let best = (0..n)
+            .filter(|&i| excess[i] > 0.0)
+            .max_by(|&a, &b| {
+                let ratio = |i: usize| excess[i] / covariance[i][i].sqrt();
+                ratio(a).total_cmp(&ratio(b))
+            })
+            .ok_or_else(no_excess)?;
+        let mut weights = vec![0.0; n];
+        weights[best] = 1.0;
+        let mut current = sharpe(&weights);
+        let mut step = 1.0;
+
+        for _ in 0..MAX_ITERATIONS {
+            let risk = mat_vec(&covariance, &weights);
+            let variance = dot(&weights, &risk);
+            let mean = dot(&excess, &weights);
+            let gradient: Vec<f64> = (0..n)
+                .map(|i| excess[i] / variance.sqrt() - mean * risk[i] / variance.powf(1.5))
+                .collect();
+
+            // Backtrack until the step improves the ratio
+            let mut next = None;
+            while step > 1e-20 {
+                let candidate = project_to_simplex(
+                    &weights
+                        .iter()
+                        .zip(&gradient)
+                        .map(|(w, g)| w + step * g)
+                        .collect::<Vec<_>>(),
+                );
+                let value = sharpe(&candidate);
+                if value > current {
+                    next = Some((candidate, value));
+                    break;
+                }
+                step /= 2.0;
+            }
+            let Some((candidate, value)) = next else {
+                break;
+            };
+            let change = max_change(&weights, &candidate);
+            (weights, current) = (candidate, value);
+            step *= 2.0;
+            if change < TOLERANCE {
+                break;
+            }
+        }
+        weights
+    } else {
+        let direction = solve(&covariance, &excess).ok_or_else(singular)?;
+        if direction.iter().sum::<f64>() <= 0.0 {
+            return Err(no_excess());
+        }
+        normalized(&direction).ok_or_else(no_excess)?
+    };
+
+    Ok(portfolio_weights(assets, &covariance, weights))
+}
+
+/// Calculates risk-parity weights
+///
+/// Every asset contributes the same share of the portfolio variance,
+/// w_i (Σw)_i = w'Σw / n. The weights are positive and found by cyclical
+/// coordinate descent.
+///
+/// # Arguments
+///
+/// * `returns` - DataFrame with one per-bar return column per asset
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`PortfolioWeights`], or a
+/// ComputeError when fewer than two complete rows remain or an asset's
+/// returns do not vary
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::portfolio::allocation::risk_parity_weights;
+///
+/// let returns = df! {
+///     "bonds" => [0.01, -0.01, 0.01, -0.01],
+///     "stocks" => [0.02, 0.02, -0.02, -0.02],
+/// }
+/// .unwrap();
+///
+/// // Uncorrelated assets get weights proportional to their inverse volatility
+/// let portfolio = risk_parity_weights(&returns).unwrap();
+/// assert!((portfolio.weight("bonds").unwrap() - 2.0 / 3.0).abs() < 1e-9);
+/// assert!((portfolio.risk_contributions[0] - 0.5).abs() < 1e-9);
+/// ```
+pub fn risk_parity_weights(returns: &DataFrame) -> PolarsResult<PortfolioWeights> {
+    let (assets, covariance) = sample_moments(returns)?;
+    let n = assets.len();
+    if (0..n).any(|i| covariance[i][i] <= 0.0) {
+        return Err(PolarsError::ComputeError(
+            "Risk parity requires every asset's returns to vary".into(),
+        ));
+    }
+
+    // Minimizes w'Σw / 2 - Σ ln(w_i) / n one coordinate at a time; the
+    // minimum has equal risk contributions
+    let budget = 1.0 / n as f64;
+    let mut weights: Vec<f64> = (0..n).map(|i| 1.0 / covariance[i][i].sqrt()).collect();
+    for _ in 0..MAX_ITERATIONS {
+        let mut change = 0.0_f64;
+        for (i, row) in covariance.iter().enumerate() {
+            let cross: f64 = (0..n)
+                .filter(|&j| j != i)
+                .map(|j| row[j] * weights[j])
+                .sum();
+            let variance = row[i];
+            let next =
+                (-cross + (cross * cross + 4.0 * variance * budget).sqrt()) / (2.0 * variance);
+            change = change.max((next - weights[i]).abs() / next);
+            weights[i] = next;
+        }
+        if change < TOLERANCE {
+            break;
+        }
+    }
+    let weights = normalized(&weights).ok_or_else(singular)?;
+
+    Ok(portfolio_weights(assets, &covariance, weights))
+}
+
+/// Asset names and the sample covariance matrix of the complete rows
+fn sample_moments(returns: &DataFrame) -> PolarsResult<(Vec<String>, Vec<Vec<f64>>)> {
+    if returns.width() == 0 {
+        return Err(PolarsError::ComputeError(
+            "Portfolio allocation requires at least one return column".into(),
+        ));
+    }
+    let mut assets = Vec::with_capacity(returns.width());
+    let mut columns = Vec::with_capacity(returns.width());
+    for column in returns.get_columns() {
+        assets.push(column.name().to_string());
+        columns.push(series_nan_values(column.as_materialized_series())?);
+    }
+
+    let rows: Vec<usize> = (0..returns.height())
+        .filter(|&t| columns.iter().all(|c| c[t].is_finite()))
+        .collect();
+    if rows.len() < 2 {
+        return Err(PolarsError::ComputeError(
+            format!(
+                "Portfolio allocation needs at least 2 complete rows of returns, got {}",
+                rows.len()
+            )
+            .into(),
+        ));
+    }
+
+    let count = rows.len() as f64;
+    let means: Vec<f64> = columns
+        .iter()
+        .map(|c| rows.iter().map(|&t| c[t]).sum::<f64>() / count)
+        .collect();
+    let covariance = (0..columns.len())
+        .map(|i| {
+            (0..columns.len())
+                .map(|j| {
+                    rows.iter()
+                        .map(|&t| (columns[i][t] - means[i]) * (columns[j][t] - means[j]))
+                        .sum::<f64>()
+                        / (count - 1.0)
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok((assets, covariance))
+}
+
+fn portfolio_weights(
+    assets: Vec<String>,
+    covariance: &[Vec<f64>],
+    weights: Vec<f64>,
+) -> PortfolioWeights {
+    let risk = mat_vec(covariance, &weights);
+    let variance = dot(&weights, &risk);
+    let risk_contributions = weights
+        .iter()
+        .zip(&risk)
+        .map(|(w, r)| {
+            if variance > 0.0 {
+                w * r / variance
+            } else {
+                f64::NAN
+            }
+        })
+        .collect();
+
+    PortfolioWeights {
+        assets,
+        weights,
+        volatility: variance.max(0.0).sqrt(),
+        risk_contributions,
+    }
+}
+
+fn singular() -> PolarsError {
+    PolarsError::ComputeError("Portfolio allocation covariance matrix is singular".into())
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn mat_vec(matrix: &[Vec<f64>], vector: &[f64]) -> Vec<f64> {
+    matrix.iter().map(|row| dot(row, vector)).collect()
+}
+
+fn max_change(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).abs())
+        .fold(0.0, f64::max)
+}
+
+/// `values` scaled to sum to 1, None when the sum is not positive
+fn normalized(values: &[f64]) -> Option<Vec<f64>> {
+    let sum: f64 = values.iter().sum();
+    (sum > 0.0 && sum.is_finite()).then(|| values.iter().map(|v| v / sum).collect())
+}
+
+/// Upper bound of the largest eigenvalue: the largest absolute row sum
+fn spectral_bound(matrix: &[Vec<f64>]) -> f64 {
+    matrix
+        .iter()
+        .map(|row| row.iter().map(|v| v.abs()).sum::<f64>())
+        .fold(f64::MIN_POSITIVE, f64::max)
+}
+
+/// Euclidean projection onto {w : w ≥ 0, Σw = 1}
+fn project_to_simplex(values: &[f64]) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    let mut sum = 0.0;
+    let mut threshold = 0.0;
+    for (k, v) in sorted.iter().enumerate() {
+        sum += v;
+        let candidate = (sum - 1.0) / (k + 1) as f64;
+        if v - candidate > 0.0 {
+            threshold = candidate;
+        }
+    }
+    values.iter().map(|v| (v - threshold).max(0.0)).collect()
+}
+
+/// Solve `matrix * x = rhs` by Gaussian elimination with partial pivoting,
+/// None when the matrix is singular
+fn solve(matrix: &[Vec<f64>], rhs: &[f64]) -> Option<Vec<f64>> {
+    let n = rhs.len();
+    let scale = spectral_bound(matrix);
+    let mut a: Vec<Vec<f64>> = matrix
+        .iter()
+        .zip(rhs)
+        .map(|(row, b)| row.iter().copied().chain([*b]).collect())
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() <= scale * 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        let (upper, lower) = a.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for row in lower {
+            let factor = row[col] / pivot_row[col];
+            for (value, p) in row.iter_mut().zip(pivot_row).skip(col) {
+                *value -= factor * p;
+            }
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let known: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (a[row][n] - known) / a[row][row];
+    }
+    Some(x)
+}
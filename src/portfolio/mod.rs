@@ -0,0 +1,15 @@
+//! # Portfolio Construction
+//!
+//! Tools for sizing positions across several assets, complementing the
+//! portfolio risk measures in [`risk`](crate::risk).
+//!
+//! - [`allocation`](allocation/index.html): Minimum-variance, maximum-Sharpe
+//!   and risk-parity weights from a DataFrame of asset returns; the weights
+//!   plug into [`portfolio_returns`](crate::risk::portfolio_returns) and
+//!   [`calculate_portfolio_var`](crate::risk::calculate_portfolio_var)
+
+pub mod allocation;
+
+pub use allocation::{
+    max_sharpe_weights, min_variance_weights, risk_parity_weights, PortfolioWeights,
+};
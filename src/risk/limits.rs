@@ -0,0 +1,263 @@
+use crate::strategy::{equity_outputs, ParamsFile, StrategySignals};
+use crate::util::dataframe_utils::series_nan_values;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::NaiveDate;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Exposure, position size, daily loss and financing limits applied to a
+/// strategy run
+///
+/// The same limits work for every strategy: [`apply_risk_limits`] runs them
+/// over the equity curve and exposure that each strategy reports. Limits
+/// left at `None` are not enforced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskLimits {
+    /// Account value at the start, which `equity_curve` P&L is added to
+    pub initial_capital: f64,
+    /// Largest gross exposure as a multiple of account value (e.g. 2.0)
+    pub max_gross_exposure: Option<f64>,
+    /// Largest entry notional of a single position as a share of account
+    /// value (e.g. 0.25)
+    pub max_position_weight: Option<f64>,
+    /// Loss since the day's open, as a share of the opening account value,
+    /// that halts trading for the rest of the day (e.g. 0.03)
+    pub max_daily_loss: Option<f64>,
+    /// Annual interest rate paid on exposure above account value
+    pub financing_rate: f64,
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            initial_capital: 100_000.0,
+            max_gross_exposure: None,
+            max_position_weight: None,
+            max_daily_loss: None,
+            financing_rate: 0.0,
+        }
+    }
+}
+
+impl ParamsFile for RiskLimits {}
+
+/// How often and how hard the limits bound a strategy run
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskLimitReport {
+    /// Bars whose positions were scaled down by a limit
+    pub scaled_bars: usize,
+    /// Bars where the unconstrained exposure exceeded `max_gross_exposure`
+    pub exposure_breaches: usize,
+    /// Positions whose entry notional exceeded `max_position_weight`
+    pub position_breaches: usize,
+    /// Days on which `max_daily_loss` halted trading
+    pub halted_days: Vec<NaiveDate>,
+    /// Total interest paid on leveraged exposure
+    pub financing_cost: f64,
+    /// Highest exposure over account value after the limits
+    pub max_leverage: f64,
+}
+
+/// Enforces risk limits on a strategy run
+///
+/// The strategy's positions are scaled by a factor between zero and one,
+/// set at the close of each bar: the largest factor that keeps gross
+/// exposure and every open position within their limits, or zero once the
+/// day's loss reaches `max_daily_loss` (flat until the first bar of the
+/// next day). Each bar's P&L is scaled by the factor set on the bar before,
+/// and interest is charged on the exposure held above account value for the
+/// time between the bars. The cost of trimming positions is not modelled.
+///
+/// # Arguments
+///
+/// * `signals` - Output of a strategy's `run_strategy`
+/// * `time_column` - Column of `signals.indicator_values` with the bar
+///   timestamps
+/// * `limits` - Limits to enforce
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the constrained run and a
+/// [`RiskLimitReport`]. The constrained run has the limited `equity_curve`,
+/// `drawdown` and `exposure` and two added indicator columns,
+/// "risk_scale" and "risk_halted"; signals and trades are kept as
+/// simulated, unscaled.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::risk::{apply_risk_limits, RiskLimits};
+/// use rustalib::strategy::StrategySignals;
+///
+/// // Three times leveraged and losing on the first day
+/// let signals = StrategySignals {
+///     buy_signals: vec![1, 0, 0, 0, 0],
+///     sell_signals: vec![0; 5],
+///     indicator_values: df! {
+///         "timestamp" => [
+///             "2024-01-02 10:00:00", "2024-01-02 11:00:00", "2024-01-02 12:00:00",
+///             "2024-01-03 10:00:00", "2024-01-03 11:00:00",
+///         ],
+///     }
+///     .unwrap(),
+///     trades: Vec::new(),
+///     equity_curve: Series::new("equity".into(), [0.0, -6000.0, -12000.0, -12000.0, -9000.0]),
+///     drawdown: Series::new("drawdown".into(), [0.0, 6000.0, 12000.0, 12000.0, 12000.0]),
+///     exposure: Series::new("exposure".into(), [300_000.0; 5]),
+/// };
+/// let limits = RiskLimits {
+///     max_gross_exposure: Some(2.0),
+///     max_daily_loss: Some(0.05),
+///     ..RiskLimits::default()
+/// };
+///
+/// let (limited, report) = apply_risk_limits(&signals, "timestamp", &limits).unwrap();
+/// let equity = limited.equity_curve.f64().unwrap();
+/// // Scaled to 2x at the first close, 1.92x at the second, then halted
+/// assert!((equity.get(2).unwrap() + 7840.0).abs() < 1e-6);
+/// assert_eq!(limited.exposure.f64().unwrap().get(2), Some(0.0));
+/// // Trading resumes the next day at 2x the reduced account value
+/// assert!((equity.get(4).unwrap() + 5996.8).abs() < 1e-6);
+/// assert_eq!(report.halted_days.len(), 1);
+/// assert_eq!(report.exposure_breaches, 5);
+/// ```
+pub fn apply_risk_limits(
+    signals: &StrategySignals,
+    time_column: &str,
+    limits: &RiskLimits,
+) -> PolarsResult<(StrategySignals, RiskLimitReport)> {
+    if limits.initial_capital <= 0.0 {
+        return Err(PolarsError::ComputeError(
+            "Risk limits need a positive initial capital".into(),
+        ));
+    }
+    let equity = series_nan_values(&signals.equity_curve)?;
+    let exposure = series_nan_values(&signals.exposure)?;
+    let times = parse_timestamp_column(&signals.indicator_values, time_column)?;
+    if equity.len() != times.len() || exposure.len() != times.len() {
+        return Err(PolarsError::ComputeError(
+            "Equity curve, exposure and indicator values must have the same length".into(),
+        ));
+    }
+
+    // Open positions by entry time, activated and retired as bars pass
+    let mut entries: Vec<usize> = (0..signals.trades.len()).collect();
+    entries.sort_by_key(|&i| signals.trades[i].entry_time);
+    let mut next_entry = 0;
+    let mut open: Vec<usize> = Vec::new();
+    let mut breached = vec![false; signals.trades.len()];
+
+    let mut report = RiskLimitReport {
+        scaled_bars: 0,
+        exposure_breaches: 0,
+        position_breaches: 0,
+        halted_days: Vec::new(),
+        financing_cost: 0.0,
+        max_leverage: 0.0,
+    };
+    let mut adjusted = Vec::with_capacity(equity.len());
+    let mut held = Vec::with_capacity(equity.len());
+    let mut scales = Vec::with_capacity(equity.len());
+    let mut halts = Vec::with_capacity(equity.len());
+
+    let (mut pnl, mut previous_equity, mut scale, mut holding) = (0.0, 0.0, 1.0, 0.0);
+    let (mut day, mut day_open, mut halted) = (None, 0.0, false);
+
+    for t in 0..equity.len() {
+        let date = times[t].map(|time| time.date_naive());
+        if date.is_some() && date != day {
+            (day, day_open, halted) = (date, pnl, false);
+        }
+
+        if let (Some(now), Some(Some(before))) = (times[t], t.checked_sub(1).map(|p| times[p])) {
+            let borrowed = (holding - (limits.initial_capital + pnl)).max(0.0);
+            let years = (now - before).num_seconds() as f64 / (365.0 * 86_400.0);
+            let interest = borrowed * limits.financing_rate * years;
+            pnl -= interest;
+            report.financing_cost += interest;
+        }
+        if equity[t].is_finite() {
+            pnl += scale * (equity[t] - previous_equity);
+            previous_equity = equity[t];
+        }
+        let value = limits.initial_capital + pnl;
+
+        if let (Some(max_loss), Some(date)) = (limits.max_daily_loss, day) {
+            if !halted && pnl - day_open <= -max_loss * (limits.initial_capital + day_open) {
+                halted = true;
+                report.halted_days.push(date);
+            }
+        }
+
+        let gross = if exposure[t].is_finite() {
+            exposure[t]
+        } else {
+            0.0
+        };
+        let mut limit = 1.0_f64;
+        if let Some(max_gross) = limits.max_gross_exposure {
+            if gross > max_gross * value {
+                limit = limit.min(max_gross * value / gross);
+                report.exposure_breaches += 1;
+            }
+        }
+        if let (Some(max_weight), Some(now)) = (limits.max_position_weight, times[t]) {
+            while next_entry < entries.len()
+                && signals.trades[entries[next_entry]].entry_time <= now
+            {
+                open.push(entries[next_entry]);
+                next_entry += 1;
+            }
+            open.retain(|&i| signals.trades[i].exit_time > now);
+            for &i in &open {
+                let trade = &signals.trades[i];
+                let notional = (trade.quantity * trade.entry_price).abs();
+                if notional > max_weight * value {
+                    limit = limit.min(max_weight * value / notional);
+                    if !breached[i] {
+                        breached[i] = true;
+                        report.position_breaches += 1;
+                    }
+                }
+            }
+        }
+
+        scale = if halted || value <= 0.0 {
+            0.0
+        } else {
+            limit.max(0.0)
+        };
+        if scale > 0.0 && scale < 1.0 {
+            report.scaled_bars += 1;
+        }
+        holding = scale * gross;
+        if value > 0.0 {
+            report.max_leverage = report.max_leverage.max(holding / value);
+        }
+
+        adjusted.push(pnl);
+        held.push(holding);
+        scales.push(scale);
+        halts.push(halted);
+    }
+
+    let [equity_curve, drawdown, exposure] = equity_outputs(adjusted, held);
+    let mut indicator_values = signals.indicator_values.clone();
+    indicator_values.with_column(Series::new("risk_scale".into(), scales))?;
+    indicator_values.with_column(Series::new("risk_halted".into(), halts))?;
+
+    Ok((
+        StrategySignals {
+            buy_signals: signals.buy_signals.clone(),
+            sell_signals: signals.sell_signals.clone(),
+            indicator_values,
+            trades: signals.trades.clone(),
+            equity_curve,
+            drawdown,
+            exposure,
+        },
+        report,
+    ))
+}
@@ -0,0 +1,29 @@
+//! # Risk Analysis
+//!
+//! This module measures the tail risk of returns, complementing the drawdown
+//! statistics in [`performance`](crate::performance), and enforces risk
+//! limits on strategy runs.
+//!
+//! - [`calculate_var`] computes historical or parametric value at risk and
+//!   expected shortfall of a return series
+//! - [`calculate_rolling_var`] tracks both over a rolling window
+//! - [`calculate_portfolio_var`] measures a weighted portfolio and splits its
+//!   expected shortfall into per-asset contributions
+//! - [`returns_from_equity`] and [`portfolio_returns`] prepare the inputs
+//! - [`apply_risk_limits`] caps gross exposure and position size, halts
+//!   trading for the day after a maximum loss and charges financing on
+//!   leverage, for any strategy's [`StrategySignals`](crate::strategy::StrategySignals)
+//! - [`TradeStops`] tracks the stops and partial exits of an open position
+//!   for a strategy's run loop, following [`StopRules`]
+
+pub mod limits;
+pub mod stops;
+pub mod var;
+
+pub use limits::{apply_risk_limits, RiskLimitReport, RiskLimits};
+pub use stops::{ScaleOut, StopAction, StopBar, StopPolicy, StopReason, StopRules, TradeStops};
+
+pub use var::{
+    calculate_portfolio_var, calculate_rolling_var, calculate_var, portfolio_returns,
+    returns_from_equity, PortfolioTailRisk, TailRisk, VarMethod,
+};
@@ -0,0 +1,418 @@
+use crate::error::{TaError, TaResult};
+use crate::performance::TradeSide;
+use serde::{Deserialize, Serialize};
+
+/// A rule that places or moves the protective stop of a position
+///
+/// ATR distances use the ATR of the entry bar for the fixed stop and the
+/// ATR of the latest bar for the trailing ones.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StopPolicy {
+    /// Stop `multiple` ATRs from the entry price
+    FixedAtr { multiple: f64 },
+    /// Stop `multiple` ATRs behind the best close since entry
+    TrailingAtr { multiple: f64 },
+    /// Stop `multiple` ATRs from the highest high (lowest low for shorts)
+    /// since entry
+    Chandelier { multiple: f64 },
+    /// Move the stop to the entry price once the position has gained
+    /// `after_r` times its initial risk
+    Breakeven { after_r: f64 },
+    /// Close at the close of the bar `bars` bars after entry
+    TimeStop { bars: usize },
+}
+
+impl StopPolicy {
+    fn atr_multiple(&self) -> Option<f64> {
+        match self {
+            StopPolicy::FixedAtr { multiple }
+            | StopPolicy::TrailingAtr { multiple }
+            | StopPolicy::Chandelier { multiple } => Some(*multiple),
+            StopPolicy::Breakeven { .. } | StopPolicy::TimeStop { .. } => None,
+        }
+    }
+}
+
+/// Close part of a position once it has gained `at_r` times its initial risk
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScaleOut {
+    /// Gain, in multiples of the initial risk, at which to sell
+    pub at_r: f64,
+    /// Share of the original quantity to sell (e.g. 0.5)
+    pub fraction: f64,
+}
+
+/// Stop policies and scale-out rules for the positions of a strategy
+///
+/// The initial risk ("1R") of a position is the distance from its entry
+/// price to the tightest ATR stop at entry, so R-based rules need at least
+/// one ATR policy.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StopRules {
+    /// Stop policies; the tightest stop of all applies
+    pub policies: Vec<StopPolicy>,
+    /// Partial exits, in any order
+    pub scale_outs: Vec<ScaleOut>,
+}
+
+impl StopRules {
+    /// Whether there are no rules at all
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty() && self.scale_outs.is_empty()
+    }
+
+    /// Whether any policy reads the ATR
+    pub fn needs_atr(&self) -> bool {
+        self.policies.iter().any(|p| p.atr_multiple().is_some())
+    }
+
+    /// Check that the rules are consistent
+    pub fn validate(&self) -> TaResult<()> {
+        for policy in &self.policies {
+            let valid = match policy {
+                StopPolicy::FixedAtr { multiple }
+                | StopPolicy::TrailingAtr { multiple }
+                | StopPolicy::Chandelier { multiple } => *multiple > 0.0,
+                StopPolicy::Breakeven { after_r } => *after_r > 0.0,
+                StopPolicy::TimeStop { bars } => *bars > 0,
+            };
+            if !valid {
+                return Err(TaError::invalid_parameter(
+                    "stops.policies",
+                    format!("{policy:?} needs a positive distance"),
+                ));
+            }
+        }
+        if self
+            .scale_outs
+            .iter()
+            .any(|s| s.at_r <= 0.0 || s.fraction <= 0.0)
+        {
+            return Err(TaError::invalid_parameter(
+                "stops.scale_outs",
+                "levels and fractions must be positive",
+            ));
+        }
+        if self.scale_outs.iter().map(|s| s.fraction).sum::<f64>() > 1.0 + 1e-9 {
+            return Err(TaError::invalid_parameter(
+                "stops.scale_outs",
+                "fractions must not add up to more than 1",
+            ));
+        }
+        let uses_r = !self.scale_outs.is_empty()
+            || self
+                .policies
+                .iter()
+                .any(|p| matches!(p, StopPolicy::Breakeven { .. }));
+        if uses_r && !self.needs_atr() {
+            return Err(TaError::invalid_parameter(
+                "stops",
+                "breakeven and scale-out rules need an ATR stop to define the initial risk",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Rule that closed or reduced a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// Fixed ATR stop hit
+    FixedAtr,
+    /// Trailing ATR stop hit
+    TrailingAtr,
+    /// Chandelier stop hit
+    Chandelier,
+    /// Stop moved to the entry price hit
+    Breakeven,
+    /// Holding period reached
+    TimeStop,
+    /// Last scale-out target reached
+    ScaleOut,
+}
+
+impl StopReason {
+    /// Snake-case name used in exit reason columns
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StopReason::FixedAtr => "fixed_atr_stop",
+            StopReason::TrailingAtr => "trailing_atr_stop",
+            StopReason::Chandelier => "chandelier_stop",
+            StopReason::Breakeven => "breakeven_stop",
+            StopReason::TimeStop => "time_stop",
+            StopReason::ScaleOut => "scale_out",
+        }
+    }
+}
+
+/// Prices of one bar as seen by the stops
+///
+/// `open` may be NaN when unknown; fills are then assumed at the stop or
+/// target level even when the bar gapped through it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StopBar {
+    /// Opening price
+    pub open: f64,
+    /// Highest price
+    pub high: f64,
+    /// Lowest price
+    pub low: f64,
+    /// Closing price
+    pub close: f64,
+    /// ATR at this bar; NaN leaves the trailing stops where they are
+    pub atr: f64,
+}
+
+/// What the stops ask the strategy to do on a bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopAction {
+    /// Keep the position
+    Hold,
+    /// Sell `fraction` of the original quantity at `price`
+    ScaleOut { fraction: f64, price: f64 },
+    /// Close the remaining position at `price`
+    Exit { price: f64, reason: StopReason },
+}
+
+/// Stop state of one open position
+///
+/// Create it when the position is opened and call [`TradeStops::update`]
+/// with every later bar; the strategy carries out the returned
+/// [`StopAction`]. Within a bar the stop is checked first, then the time
+/// stop and then the scale-out targets, which is the conservative order
+/// when one bar reaches several levels. Stops only ever move in the
+/// position's favor.
+///
+/// # Example
+///
+/// ```
+/// use rustalib::performance::TradeSide;
+/// use rustalib::risk::{ScaleOut, StopAction, StopBar, StopPolicy, StopReason, StopRules, TradeStops};
+///
+/// // Stop 2 ATR below entry, sell half at 1R and then move the stop to entry
+/// let rules = StopRules {
+///     policies: vec![StopPolicy::FixedAtr { multiple: 2.0 }, StopPolicy::Breakeven { after_r: 1.0 }],
+///     scale_outs: vec![ScaleOut { at_r: 1.0, fraction: 0.5 }],
+/// };
+/// let mut stops = TradeStops::new(&rules, TradeSide::Long, 100.0, 2.0);
+/// assert_eq!(stops.stop_price(), Some(96.0));
+/// assert_eq!(stops.initial_risk(), Some(4.0));
+///
+/// let bar = |open, high, low, close| StopBar { open, high, low, close, atr: 2.0 };
+/// assert_eq!(stops.update(&bar(101.0, 103.0, 100.0, 102.0)), StopAction::Hold);
+/// assert_eq!(
+///     stops.update(&bar(103.0, 105.0, 102.0, 104.0)),
+///     StopAction::ScaleOut { fraction: 0.5, price: 104.0 }
+/// );
+/// assert_eq!(stops.stop_price(), Some(100.0));
+/// assert_eq!(
+///     stops.update(&bar(103.0, 103.0, 99.0, 99.5)),
+///     StopAction::Exit { price: 100.0, reason: StopReason::Breakeven }
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeStops {
+    policies: Vec<StopPolicy>,
+    scale_outs: Vec<ScaleOut>,
+    sign: f64,
+    entry_price: f64,
+    initial_risk: Option<f64>,
+    stop: Option<(f64, StopReason)>,
+    best_close: f64,
+    extreme: f64,
+    bars: usize,
+    remaining: f64,
+    next_scale_out: usize,
+}
+
+impl TradeStops {
+    /// Start tracking a position opened at `entry_price`
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - Validated stop rules
+    /// * `side` - Direction of the position
+    /// * `entry_price` - Fill price of the entry
+    /// * `atr` - ATR at the entry bar; when NaN the position starts without
+    ///   an ATR stop and R-based rules stay inactive
+    pub fn new(rules: &StopRules, side: TradeSide, entry_price: f64, atr: f64) -> Self {
+        let sign = side.sign();
+        let mut scale_outs = rules.scale_outs.clone();
+        scale_outs.sort_by(|a, b| a.at_r.total_cmp(&b.at_r));
+
+        let mut stops = Self {
+            policies: rules.policies.clone(),
+            scale_outs,
+            sign,
+            entry_price,
+            initial_risk: None,
+            stop: None,
+            best_close: entry_price,
+            extreme: entry_price,
+            bars: 0,
+            remaining: 1.0,
+            next_scale_out: 0,
+        };
+        if atr.is_finite() {
+            for policy in &rules.policies {
+                if let Some(multiple) = policy.atr_multiple() {
+                    stops.tighten(entry_price - sign * multiple * atr, reason_of(policy));
+                }
+            }
+        }
+        stops.initial_risk = stops
+            .stop
+            .map(|(level, _)| sign * (entry_price - level))
+            .filter(|risk| *risk > 0.0);
+        stops
+    }
+
+    /// Current stop level, if any
+    pub fn stop_price(&self) -> Option<f64> {
+        self.stop.map(|(level, _)| level)
+    }
+
+    /// Distance from the entry price to the initial stop ("1R")
+    pub fn initial_risk(&self) -> Option<f64> {
+        self.initial_risk
+    }
+
+    /// Share of the original quantity still open
+    pub fn remaining(&self) -> f64 {
+        self.remaining
+    }
+
+    /// Bars seen since entry
+    pub fn bars_held(&self) -> usize {
+        self.bars
+    }
+
+    /// Process the next bar of the position
+    pub fn update(&mut self, bar: &StopBar) -> StopAction {
+        self.bars += 1;
+        let sign = self.sign;
+
+        if let Some((level, reason)) = self.stop {
+            let adverse = if sign > 0.0 { bar.low } else { bar.high };
+            if sign * (adverse - level) <= 0.0 {
+                // A gap through the stop fills at the open
+                let price = if sign * (bar.open - level) < 0.0 {
+                    bar.open
+                } else {
+                    level
+                };
+                return StopAction::Exit { price, reason };
+            }
+        }
+
+        let time_stop = self.policies.iter().any(|p| match p {
+            StopPolicy::TimeStop { bars } => self.bars >= *bars,
+            _ => false,
+        });
+        if time_stop {
+            return StopAction::Exit {
+                price: bar.close,
+                reason: StopReason::TimeStop,
+            };
+        }
+
+        let mut sold = 0.0;
+        let mut proceeds = 0.0;
+        if let Some(risk) = self.initial_risk {
+            let favorable = if sign > 0.0 { bar.high } else { bar.low };
+            while let Some(scale_out) = self.scale_outs.get(self.next_scale_out) {
+                let target = self.entry_price + sign * scale_out.at_r * risk;
+                if sign * (favorable - target) < 0.0 {
+                    break;
+                }
+                let price = if sign * (bar.open - target) > 0.0 {
+                    bar.open
+                } else {
+                    target
+                };
+                let fraction = scale_out.fraction.min(self.remaining - sold);
+                sold += fraction;
+                proceeds += fraction * price;
+                self.next_scale_out += 1;
+            }
+        }
+        self.remaining -= sold;
+        if sold > 0.0 && self.remaining <= 1e-9 {
+            self.remaining = 0.0;
+            return StopAction::Exit {
+                price: proceeds / sold,
+                reason: StopReason::ScaleOut,
+            };
+        }
+
+        self.advance(bar);
+        if sold > 0.0 {
+            StopAction::ScaleOut {
+                fraction: sold,
+                price: proceeds / sold,
+            }
+        } else {
+            StopAction::Hold
+        }
+    }
+
+    /// Move the trailing stops with the bar's prices for the next bar
+    fn advance(&mut self, bar: &StopBar) {
+        let sign = self.sign;
+        if bar.close.is_finite() && sign * (bar.close - self.best_close) > 0.0 {
+            self.best_close = bar.close;
+        }
+        let favorable = if sign > 0.0 { bar.high } else { bar.low };
+        if favorable.is_finite() && sign * (favorable - self.extreme) > 0.0 {
+            self.extreme = favorable;
+        }
+
+        for policy in self.policies.clone() {
+            match policy {
+                StopPolicy::TrailingAtr { multiple } if bar.atr.is_finite() => {
+                    self.tighten(
+                        self.best_close - sign * multiple * bar.atr,
+                        StopReason::TrailingAtr,
+                    );
+                }
+                StopPolicy::Chandelier { multiple } if bar.atr.is_finite() => {
+                    self.tighten(
+                        self.extreme - sign * multiple * bar.atr,
+                        StopReason::Chandelier,
+                    );
+                }
+                StopPolicy::Breakeven { after_r } => {
+                    if let Some(risk) = self.initial_risk {
+                        if sign * (self.extreme - self.entry_price) >= after_r * risk {
+                            self.tighten(self.entry_price, StopReason::Breakeven);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Replace the stop when `level` is closer to the price
+    fn tighten(&mut self, level: f64, reason: StopReason) {
+        let tighter = match self.stop {
+            Some((current, _)) => self.sign * (level - current) > 0.0,
+            None => true,
+        };
+        if tighter {
+            self.stop = Some((level, reason));
+        }
+    }
+}
+
+fn reason_of(policy: &StopPolicy) -> StopReason {
+    match policy {
+        StopPolicy::FixedAtr { .. } => StopReason::FixedAtr,
+        StopPolicy::TrailingAtr { .. } => StopReason::TrailingAtr,
+        StopPolicy::Chandelier { .. } => StopReason::Chandelier,
+        StopPolicy::Breakeven { .. } => StopReason::Breakeven,
+        StopPolicy::TimeStop { .. } => StopReason::TimeStop,
+    }
+}
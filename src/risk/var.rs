@@ -0,0 +1,477 @@
+use crate::indicators::options::pricing::norm_pdf;
+use crate::util::dataframe_utils::{check_window_size, series_nan_values};
+use polars::prelude::*;
+
+/// How the return distribution is estimated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarMethod {
+    /// Empirical quantile of the observed returns; no distribution assumed
+    Historical,
+    /// Normal distribution with the sample mean and standard deviation
+    Parametric,
+}
+
+/// Value at risk and expected shortfall of a return series
+///
+/// Both are losses expressed as positive fractions of capital: a `var` of
+/// 0.02 at 95% confidence means one bar in twenty is expected to lose 2% or
+/// more, and `expected_shortfall` is the mean loss on those bars.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TailRisk {
+    /// Loss not exceeded with probability `confidence`
+    pub var: f64,
+    /// Mean loss beyond the VaR
+    pub expected_shortfall: f64,
+    /// Confidence level, e.g. 0.95
+    pub confidence: f64,
+    /// Estimation method
+    pub method: VarMethod,
+    /// Number of finite returns the estimate is based on
+    pub observations: usize,
+}
+
+/// Tail risk of a weighted portfolio and each asset's share of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortfolioTailRisk {
+    /// VaR and expected shortfall of the combined returns
+    pub portfolio: TailRisk,
+    /// Contribution of each asset to the expected shortfall, in input order;
+    /// the contributions add up to the portfolio's expected shortfall
+    pub contributions: Vec<f64>,
+}
+
+/// Converts an equity curve into per-bar returns
+///
+/// # Arguments
+///
+/// * `equity` - Equity per bar
+/// * `base` - Capital added to every equity value: 0.0 when the curve is
+///   account value, the starting capital when it is cumulative P&L such as
+///   [`StrategySignals::equity_curve`](crate::strategy::StrategySignals)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named "returns" with one
+/// value fewer than `equity`; returns touching a missing or non-positive
+/// value are NaN
+pub fn returns_from_equity(equity: &Series, base: f64) -> PolarsResult<Series> {
+    let values = series_nan_values(equity)?;
+    let returns: Vec<f64> = values
+        .windows(2)
+        .map(|pair| {
+            let (previous, current) = (pair[0] + base, pair[1] + base);
+            if previous > 0.0 && current.is_finite() {
+                current / previous - 1.0
+            } else {
+                f64::NAN
+            }
+        })
+        .collect();
+    Ok(Series::new("returns".into(), returns))
+}
+
+/// Calculates value at risk and expected shortfall of a return series
+///
+/// The historical method takes the worst `n * (1 - confidence)` returns
+/// (at least one) as the tail: the VaR is the best of them and the expected
+/// shortfall their mean. The parametric method fits a normal distribution,
+/// which is smoother on short histories but understates fat tails.
+///
+/// # Arguments
+///
+/// * `returns` - Per-bar returns as fractions; NaN and missing values are
+///   skipped
+/// * `confidence` - Confidence level in (0, 1), typically 0.95 or 0.99
+/// * `method` - Estimation method
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`TailRisk`], or an error when
+/// there are fewer than two finite returns
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::risk::{calculate_var, VarMethod};
+///
+/// // Small gains with a 5% loss every tenth bar
+/// let returns: Vec<f64> = (0..100).map(|i| if i % 10 == 9 { -0.05 } else { 0.01 }).collect();
+/// let returns = Series::new("returns".into(), returns);
+///
+/// let risk = calculate_var(&returns, 0.95, VarMethod::Historical).unwrap();
+/// assert!((risk.var - 0.05).abs() < 1e-12);
+/// assert!((risk.expected_shortfall - 0.05).abs() < 1e-12);
+///
+/// let normal = calculate_var(&returns, 0.95, VarMethod::Parametric).unwrap();
+/// assert!(normal.var > 0.0 && normal.expected_shortfall > normal.var);
+/// ```
+pub fn calculate_var(
+    returns: &Series,
+    confidence: f64,
+    method: VarMethod,
+) -> PolarsResult<TailRisk> {
+    check_confidence(confidence)?;
+    let returns: Vec<f64> = series_nan_values(returns)?
+        .into_iter()
+        .filter(|r| r.is_finite())
+        .collect();
+    if returns.len() < 2 {
+        return Err(PolarsError::ComputeError(
+            format!("VaR needs at least 2 finite returns, got {}", returns.len()).into(),
+        ));
+    }
+    let (var, expected_shortfall) = tail_losses(&returns, confidence, method);
+    Ok(TailRisk {
+        var,
+        expected_shortfall,
+        confidence,
+        method,
+        observations: returns.len(),
+    })
+}
+
+/// Calculates value at risk and expected shortfall over a rolling window
+///
+/// Each bar is measured on the returns of the `window` bars ending with it,
+/// so the output tracks how tail risk changes through time.
+///
+/// # Arguments
+///
+/// * `returns` - Per-bar returns as fractions
+/// * `window` - Number of returns per estimate (e.g. 250 for one year of
+///   daily bars)
+/// * `confidence` - Confidence level in (0, 1)
+/// * `method` - Estimation method
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing `(var, expected_shortfall)` Series
+/// named "var_{window}" and "es_{window}", NaN for the first `window - 1`
+/// bars and for windows with fewer than two finite returns
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::risk::{calculate_rolling_var, VarMethod};
+///
+/// // Calm returns, then a volatile second half
+/// let returns: Vec<f64> = (0..200)
+///     .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 } * if i < 100 { 0.005 } else { 0.03 })
+///     .collect();
+/// let returns = Series::new("returns".into(), returns);
+///
+/// let (var, es) = calculate_rolling_var(&returns, 50, 0.95, VarMethod::Historical).unwrap();
+/// let var = var.f64().unwrap();
+/// assert!(var.get(48).unwrap().is_nan());
+/// assert!((var.get(99).unwrap() - 0.005).abs() < 1e-12);
+/// assert!((var.get(199).unwrap() - 0.03).abs() < 1e-12);
+/// assert_eq!(es.name().as_str(), "es_50");
+/// ```
+pub fn calculate_rolling_var(
+    returns: &Series,
+    window: usize,
+    confidence: f64,
+    method: VarMethod,
+) -> PolarsResult<(Series, Series)> {
+    check_window_size(&returns.clone().into_frame(), window, "Rolling VaR")?;
+    check_confidence(confidence)?;
+    let values = series_nan_values(returns)?;
+
+    let (var, es): (Vec<f64>, Vec<f64>) = (0..values.len())
+        .map(|i| {
+            if i + 1 < window {
+                return (f64::NAN, f64::NAN);
+            }
+            let sample: Vec<f64> = values[i + 1 - window..=i]
+                .iter()
+                .copied()
+                .filter(|r| r.is_finite())
+                .collect();
+            if sample.len() < 2 {
+                (f64::NAN, f64::NAN)
+            } else {
+                tail_losses(&sample, confidence, method)
+            }
+        })
+        .unzip();
+
+    Ok((
+        Series::new(format!("var_{window}").into(), var),
+        Series::new(format!("es_{window}").into(), es),
+    ))
+}
+
+/// Combines aligned asset returns into portfolio returns
+///
+/// # Arguments
+///
+/// * `returns` - Per-bar returns of each asset, all of the same length
+/// * `weights` - Capital weight of each asset, held constant (rebalanced
+///   every bar)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named "portfolio_returns";
+/// a bar is NaN when any asset's return is missing
+pub fn portfolio_returns(returns: &[Series], weights: &[f64]) -> PolarsResult<Series> {
+    let assets = asset_values(returns, weights)?;
+    Ok(Series::new(
+        "portfolio_returns".into(),
+        combine(&assets, weights),
+    ))
+}
+
+/// Calculates VaR and expected shortfall of a weighted portfolio
+///
+/// Besides the portfolio's own tail risk, the expected shortfall is split
+/// into per-asset contributions that add up to it, showing which positions
+/// drive the losses. Historical contributions are each asset's mean
+/// weighted loss on the portfolio's tail bars; parametric ones are the
+/// Euler allocation under the normal model, using the assets' covariance.
+///
+/// # Arguments
+///
+/// * `returns` - Per-bar returns of each asset, all of the same length;
+///   bars where any asset is missing are skipped
+/// * `weights` - Capital weight of each asset
+/// * `confidence` - Confidence level in (0, 1)
+/// * `method` - Estimation method
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`PortfolioTailRisk`], or an
+/// error when the inputs are misaligned or fewer than two bars are complete
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::risk::{calculate_portfolio_var, VarMethod};
+///
+/// // A volatile asset and a calm one that move together
+/// let volatile: Vec<f64> = (0..100).map(|i| ((i * 7 % 13) as f64 - 6.0) * 0.005).collect();
+/// let calm: Vec<f64> = volatile.iter().map(|r| r * 0.2).collect();
+/// let returns = [Series::new("a".into(), volatile), Series::new("b".into(), calm)];
+///
+/// for method in [VarMethod::Historical, VarMethod::Parametric] {
+///     let risk = calculate_portfolio_var(&returns, &[0.5, 0.5], 0.95, method).unwrap();
+///     let total: f64 = risk.contributions.iter().sum();
+///     assert!((total - risk.portfolio.expected_shortfall).abs() < 1e-9);
+///     assert!(risk.contributions[0] > 4.0 * risk.contributions[1]);
+/// }
+/// ```
+pub fn calculate_portfolio_var(
+    returns: &[Series],
+    weights: &[f64],
+    confidence: f64,
+    method: VarMethod,
+) -> PolarsResult<PortfolioTailRisk> {
+    check_confidence(confidence)?;
+    let assets = asset_values(returns, weights)?;
+
+    // Keep only bars where every asset has a return
+    let rows: Vec<usize> = (0..assets[0].len())
+        .filter(|&t| assets.iter().all(|a| a[t].is_finite()))
+        .collect();
+    if rows.len() < 2 {
+        return Err(PolarsError::ComputeError(
+            format!(
+                "Portfolio VaR needs at least 2 complete bars, got {}",
+                rows.len()
+            )
+            .into(),
+        ));
+    }
+    let assets: Vec<Vec<f64>> = assets
+        .iter()
+        .map(|a| rows.iter().map(|&t| a[t]).collect())
+        .collect();
+    let combined = combine(&assets, weights);
+    let (var, expected_shortfall) = tail_losses(&combined, confidence, method);
+
+    let contributions = match method {
+        VarMethod::Historical => {
+            let tail = tail_indices(&combined, confidence);
+            assets
+                .iter()
+                .zip(weights)
+                .map(|(a, w)| -tail.iter().fold(0.0, |sum, &t| sum + w * a[t]) / tail.len() as f64)
+                .collect()
+        }
+        VarMethod::Parametric => {
+            let n = combined.len() as f64;
+            let means: Vec<f64> = assets.iter().map(|a| a.iter().sum::<f64>() / n).collect();
+            // Covariance of each asset with the portfolio, i.e. (Σw)_i
+            let portfolio_mean = combined.iter().sum::<f64>() / n;
+            let covariances: Vec<f64> = assets
+                .iter()
+                .zip(&means)
+                .map(|(a, mean)| {
+                    a.iter()
+                        .zip(&combined)
+                        .fold(0.0, |sum, (r, p)| sum + (r - mean) * (p - portfolio_mean))
+                        / (n - 1.0)
+                })
+                .collect();
+            let sigma = std_dev(&combined);
+            let factor = norm_pdf(inverse_norm_cdf(confidence)) / (1.0 - confidence);
+            weights
+                .iter()
+                .zip(means.iter().zip(&covariances))
+                .map(|(w, (mean, cov))| {
+                    let shock = if sigma > 0.0 {
+                        cov / sigma * factor
+                    } else {
+                        0.0
+                    };
+                    w * (shock - mean)
+                })
+                .collect()
+        }
+    };
+
+    Ok(PortfolioTailRisk {
+        portfolio: TailRisk {
+            var,
+            expected_shortfall,
+            confidence,
+            method,
+            observations: combined.len(),
+        },
+        contributions,
+    })
+}
+
+/// VaR and expected shortfall of finite returns, as positive losses
+fn tail_losses(returns: &[f64], confidence: f64, method: VarMethod) -> (f64, f64) {
+    match method {
+        VarMethod::Historical => {
+            let tail = tail_indices(returns, confidence);
+            let worst: Vec<f64> = tail.iter().map(|&t| returns[t]).collect();
+            let var = -worst.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let es = -worst.iter().fold(0.0, |sum, r| sum + r) / worst.len() as f64;
+            (var, es)
+        }
+        VarMethod::Parametric => {
+            let n = returns.len() as f64;
+            let mean = returns.iter().sum::<f64>() / n;
+            let sigma = std_dev(returns);
+            let z = inverse_norm_cdf(confidence);
+            (
+                z * sigma - mean,
+                sigma * norm_pdf(z) / (1.0 - confidence) - mean,
+            )
+        }
+    }
+}
+
+/// Indices of the worst `n * (1 - confidence)` returns, at least one
+fn tail_indices(returns: &[f64], confidence: f64) -> Vec<usize> {
+    let count = ((returns.len() as f64 * (1.0 - confidence)).floor() as usize).max(1);
+    let mut order: Vec<usize> = (0..returns.len()).collect();
+    order.sort_by(|&a, &b| returns[a].total_cmp(&returns[b]));
+    order.truncate(count);
+    order
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+}
+
+/// Inverse of the standard normal CDF (Acklam's rational approximation,
+/// relative error below 1.2e-9)
+fn inverse_norm_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+fn check_confidence(confidence: f64) -> PolarsResult<()> {
+    if confidence > 0.0 && confidence < 1.0 {
+        Ok(())
+    } else {
+        Err(PolarsError::ComputeError(
+            format!("VaR confidence must lie in (0, 1), got {confidence}").into(),
+        ))
+    }
+}
+
+fn asset_values(returns: &[Series], weights: &[f64]) -> PolarsResult<Vec<Vec<f64>>> {
+    if returns.is_empty() || returns.len() != weights.len() {
+        return Err(PolarsError::ComputeError(
+            format!(
+                "Portfolio needs one weight per asset, got {} assets and {} weights",
+                returns.len(),
+                weights.len()
+            )
+            .into(),
+        ));
+    }
+    let assets = returns
+        .iter()
+        .map(series_nan_values)
+        .collect::<PolarsResult<Vec<_>>>()?;
+    if assets.iter().any(|a| a.len() != assets[0].len()) {
+        return Err(PolarsError::ComputeError(
+            "Portfolio return series must have the same length".into(),
+        ));
+    }
+    Ok(assets)
+}
+
+fn combine(assets: &[Vec<f64>], weights: &[f64]) -> Vec<f64> {
+    (0..assets[0].len())
+        .map(|t| {
+            assets
+                .iter()
+                .zip(weights)
+                .fold(0.0, |sum, (a, w)| sum + w * a[t])
+        })
+        .collect()
+}
@@ -0,0 +1,667 @@
+//! # Strategy Builder
+//!
+//! Builds a runnable strategy from a declarative [`StrategyConfig`]: the
+//! indicators to calculate, entry and exit conditions over their columns, and
+//! risk settings. Configs are plain serde data, so strategies can be kept in
+//! TOML or JSON files and changed without recompiling.
+//!
+//! A position is opened at the close of the first bar on which every entry
+//! condition holds, or with a limit or stop order placed on that bar (see
+//! [`EntryOrder`]), and closed at the close of the first later bar on which
+//! any exit condition, the stop loss, the take profit or the holding limit
+//! triggers, subject to the persistence, minimum holding and cooldown
+//! requirements of [`SignalTiming`]. ATR stops, breakeven moves and partial
+//! scale-outs can be added with [`StopRules`], which act intrabar on the
+//! high and low.
+
+use crate::backtest::{Bar, OrderSide, OrderType};
+use crate::error::{TaError, TaResult};
+use crate::indicators::signal_utils::crosses;
+use crate::indicators::volatility::calculate_atr;
+use crate::performance::{RoundTripTrade, TradeSide};
+use crate::risk::{StopAction, StopBar, StopRules, TradeStops};
+use crate::strategy::runtime::{SignalGate, SignalTiming};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::column_map::ColumnMap;
+use crate::util::dataframe_utils::{column_nan_values, column_valid_values, nan_values};
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+pub use crate::indicators::spec::IndicatorSpec;
+
+/// Right-hand side of a condition: a constant or another column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Operand {
+    /// Constant threshold
+    Value(f64),
+    /// Column read from the data or an indicator
+    Column(String),
+}
+
+/// A comparison evaluated on every bar
+///
+/// `left` names a column of the input or an indicator output. Conditions
+/// involving missing or NaN values are false.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+    /// `left` is above `right`
+    Above { left: String, right: Operand },
+    /// `left` is below `right`
+    Below { left: String, right: Operand },
+    /// `left` moved from at or below `right` to above it on this bar
+    CrossesAbove { left: String, right: Operand },
+    /// `left` moved from at or above `right` to below it on this bar
+    CrossesBelow { left: String, right: Operand },
+}
+
+impl Condition {
+    fn operands(&self) -> (&String, &Operand) {
+        match self {
+            Condition::Above { left, right }
+            | Condition::Below { left, right }
+            | Condition::CrossesAbove { left, right }
+            | Condition::CrossesBelow { left, right } => (left, right),
+        }
+    }
+
+    fn columns(&self) -> Vec<&str> {
+        let (left, right) = self.operands();
+        match right {
+            Operand::Column(column) => vec![left.as_str(), column.as_str()],
+            Operand::Value(_) => vec![left.as_str()],
+        }
+    }
+
+    /// Evaluate the condition on every row of `df`
+    fn evaluate(&self, df: &DataFrame) -> PolarsResult<Vec<bool>> {
+        let (left, right) = self.operands();
+        let n = df.height();
+        let lhs = column_valid_values(df, left)?;
+        let rhs = match right {
+            Operand::Value(v) => vec![Some(*v); n],
+            Operand::Column(column) => column_valid_values(df, column)?,
+        };
+        let compare = |above: bool| {
+            lhs.iter()
+                .zip(&rhs)
+                .map(|pair| match pair {
+                    (Some(l), Some(r)) => {
+                        if above {
+                            l > r
+                        } else {
+                            l < r
+                        }
+                    }
+                    _ => false,
+                })
+                .collect()
+        };
+
+        Ok(match self {
+            Condition::Above { .. } => compare(true),
+            Condition::Below { .. } => compare(false),
+            Condition::CrossesAbove { .. } => crosses(&lhs, &rhs, true),
+            Condition::CrossesBelow { .. } => crosses(&lhs, &rhs, false),
+        })
+    }
+}
+
+/// Position sizing, costs and protective exits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskSettings {
+    /// Units traded per position
+    pub position_size: f64,
+    /// Fee charged per fill as a fraction of notional (e.g. 0.001 = 0.1%)
+    pub fee_rate: f64,
+    /// Close when the position has lost this percentage of its entry price
+    pub stop_loss_pct: Option<f64>,
+    /// Close when the position has gained this percentage of its entry price
+    pub take_profit_pct: Option<f64>,
+    /// Close after this many bars in the position
+    pub max_holding_bars: Option<usize>,
+    /// ATR stops and scale-outs; these need high and low columns
+    pub stops: StopRules,
+    /// ATR period used by the stops
+    pub atr_period: usize,
+}
+
+impl Default for RiskSettings {
+    fn default() -> Self {
+        Self {
+            position_size: 1.0,
+            fee_rate: 0.0,
+            stop_loss_pct: None,
+            take_profit_pct: None,
+            max_holding_bars: None,
+            stops: StopRules::default(),
+            atr_period: 14,
+        }
+    }
+}
+
+/// How a position is entered once the entry conditions hold
+///
+/// Limit and stop entries are priced at the value of `column` on the signal
+/// bar and work from the next bar for `valid_bars` bars, filling against
+/// each bar's range (see [`OrderType::fill_price`]). A new signal while an
+/// order is working re-prices it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EntryOrder {
+    /// Enter at the close of the signal bar
+    #[default]
+    MarketOnClose,
+    /// Enter at the level or better, e.g. a buy at the lower Bollinger band
+    Limit { column: String, valid_bars: usize },
+    /// Enter once the price trades through the level, e.g. a buy stop above
+    /// the opening range high
+    Stop { column: String, valid_bars: usize },
+}
+
+impl EntryOrder {
+    /// Column with the order level and the bars the order works for
+    fn level(&self) -> Option<(&str, usize)> {
+        match self {
+            EntryOrder::MarketOnClose => None,
+            EntryOrder::Limit { column, valid_bars } | EntryOrder::Stop { column, valid_bars } => {
+                Some((column, *valid_bars))
+            }
+        }
+    }
+}
+
+/// Declarative description of a strategy
+///
+/// Every field has a default so config files only need to list what they
+/// change; a usable config needs at least one entry condition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyConfig {
+    /// Name of the strategy
+    pub name: String,
+    /// Direction of the positions opened on entry
+    pub side: TradeSide,
+    /// Indicators calculated before the rules are evaluated
+    pub indicators: Vec<IndicatorSpec>,
+    /// Conditions that must all hold to open a position
+    pub entry: Vec<Condition>,
+    /// Conditions of which any closes an open position
+    pub exit: Vec<Condition>,
+    /// Order type used to enter; limit and stop entries need high and low
+    /// columns
+    pub entry_order: EntryOrder,
+    /// Sizing, costs and protective exits
+    pub risk: RiskSettings,
+    /// Persistence, minimum holding and cooldown requirements
+    pub timing: SignalTiming,
+    /// Column with bar timestamps
+    pub time_column: String,
+    /// Names of the OHLCV columns in the price data
+    pub columns: ColumnMap,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl Default for StrategyConfig {
+    fn default() -> Self {
+        Self {
+            name: "strategy".to_string(),
+            side: TradeSide::Long,
+            indicators: Vec::new(),
+            entry: Vec::new(),
+            exit: Vec::new(),
+            entry_order: EntryOrder::default(),
+            risk: RiskSettings::default(),
+            timing: SignalTiming::default(),
+            time_column: "timestamp".to_string(),
+            columns: ColumnMap::default(),
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for StrategyConfig {}
+
+/// A validated strategy built from a [`StrategyConfig`]
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::strategy::builder::ConfigStrategy;
+///
+/// let strategy = ConfigStrategy::from_toml(
+///     r#"
+///     name = "sma crossover"
+///
+///     [[indicators]]
+///     type = "sma"
+///     name = "sma_3"
+///     period = 3
+///
+///     [[entry]]
+///     type = "crosses_above"
+///     left = "close"
+///     right = "sma_3"
+///
+///     [[exit]]
+///     type = "crosses_below"
+///     left = "close"
+///     right = "sma_3"
+///
+///     [risk]
+///     stop_loss_pct = 10.0
+///     "#,
+/// )
+/// .unwrap();
+///
+/// let closes = [10.0, 9.0, 8.0, 9.0, 10.0, 11.0, 12.0, 11.0, 10.0, 9.0];
+/// let timestamps: Vec<String> = (1..=closes.len())
+///     .map(|d| format!("2024-01-{d:02}"))
+///     .collect();
+/// let df = df! {
+///     "timestamp" => timestamps,
+///     "close" => closes,
+/// }
+/// .unwrap();
+///
+/// let signals = strategy.run(&df).unwrap();
+/// assert_eq!(signals.buy_signals[3], 1); // 9.0 crosses above the SMA of 9, 8, 9
+/// assert_eq!(signals.trades.len(), 1);
+/// assert_eq!(signals.trades[0].exit_price, 11.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConfigStrategy {
+    config: StrategyConfig,
+}
+
+impl ConfigStrategy {
+    /// Validate a config and build the strategy
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Strategy description
+    ///
+    /// # Returns
+    ///
+    /// Returns a TaResult containing the strategy, or an InvalidParameter
+    /// error when the config has no entry conditions, a zero indicator period, duplicate
+    /// indicator outputs, a non-positive position size or a negative risk
+    /// setting
+    pub fn from_config(config: StrategyConfig) -> TaResult<Self> {
+        if config.entry.is_empty() {
+            return Err(TaError::invalid_parameter(
+                "entry",
+                format!("strategy '{}' has no entry conditions", config.name),
+            ));
+        }
+
+        let mut outputs = HashSet::new();
+        for indicator in &config.indicators {
+            if indicator.periods().contains(&0) {
+                return Err(TaError::invalid_parameter(
+                    "indicators",
+                    format!("indicator '{}' has a zero period", indicator.name()),
+                ));
+            }
+            for name in indicator.output_names() {
+                if !outputs.insert(name.clone()) {
+                    return Err(TaError::invalid_parameter(
+                        "indicators",
+                        format!("indicator column '{name}' is defined twice"),
+                    ));
+                }
+            }
+        }
+
+        let risk = &config.risk;
+        if risk.position_size <= 0.0 {
+            return Err(TaError::invalid_parameter(
+                "risk.position_size",
+                "must be positive",
+            ));
+        }
+        if risk.fee_rate < 0.0
+            || risk.stop_loss_pct.is_some_and(|v| v <= 0.0)
+            || risk.take_profit_pct.is_some_and(|v| v <= 0.0)
+            || risk.max_holding_bars == Some(0)
+        {
+            return Err(TaError::invalid_parameter(
+                "risk",
+                "fees must be non-negative and stop loss, take profit and holding limit positive",
+            ));
+        }
+        if risk.atr_period == 0 {
+            return Err(TaError::invalid_parameter(
+                "risk.atr_period",
+                "must be positive",
+            ));
+        }
+        risk.stops.validate()?;
+        if config
+            .entry_order
+            .level()
+            .is_some_and(|(_, valid_bars)| valid_bars == 0)
+        {
+            return Err(TaError::invalid_parameter(
+                "entry_order.valid_bars",
+                "must be positive",
+            ));
+        }
+
+        Ok(Self { config })
+    }
+
+    /// Parse a JSON config and build the strategy
+    pub fn from_json(json: &str) -> TaResult<Self> {
+        let config = serde_json::from_str(json)
+            .map_err(|e| TaError::invalid_parameter("config", e.to_string()))?;
+        Self::from_config(config)
+    }
+
+    /// Parse a TOML config and build the strategy
+    pub fn from_toml(toml: &str) -> TaResult<Self> {
+        let config = toml::from_str(toml)
+            .map_err(|e| TaError::invalid_parameter("config", e.to_string()))?;
+        Self::from_config(config)
+    }
+
+    /// The config the strategy was built from
+    pub fn config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    /// Run the strategy over a price history
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - DataFrame with the close column, the time column and every
+    ///   column the indicators and rules read. Price columns mapped in
+    ///   `columns` can also be referred to by their standard names.
+    ///
+    /// # Returns
+    ///
+    /// Returns a PolarsResult containing StrategySignals where
+    /// `indicator_values` holds the indicator columns (key indicators), a
+    /// "strategy_position" column (+1 long, -1 short, 0 flat; key) and a
+    /// "strategy_exit_reason" column ("exit_rule", "stop_loss",
+    /// "take_profit", "max_holding", a [`StopReason`](crate::risk::StopReason)
+    /// name or "scale_out" on exit bars). Each scale-out is reported as a
+    /// trade of its share of the position; a position still open at the end
+    /// is not reported as a trade.
+    pub fn run(&self, df: &DataFrame) -> PolarsResult<StrategySignals> {
+        let config = &self.config;
+        for col in [config.columns.close.as_str(), config.time_column.as_str()] {
+            if !df.schema().contains(col) {
+                return Err(TaError::MissingColumn(col.to_string()).into());
+            }
+        }
+
+        // Indicators and rules can refer to mapped price columns by standard name
+        let mut data = config.columns.with_standard_names(df)?;
+        let mut indicator_series = Vec::new();
+        for indicator in &config.indicators {
+            for series in indicator.calculate(&data)? {
+                data.with_column(series.clone())?;
+                indicator_series.push(series);
+            }
+        }
+
+        let order_column = config.entry_order.level().map(|(column, _)| column);
+        for column in config
+            .entry
+            .iter()
+            .chain(&config.exit)
+            .flat_map(Condition::columns)
+            .chain(order_column)
+        {
+            if !data.schema().contains(column) {
+                return Err(TaError::MissingColumn(column.to_string()).into());
+            }
+        }
+
+        let n = data.height();
+        let mut entry = vec![true; n];
+        for condition in &config.entry {
+            for (e, holds) in entry.iter_mut().zip(condition.evaluate(&data)?) {
+                *e &= holds;
+            }
+        }
+        let mut exit = vec![false; n];
+        for condition in &config.exit {
+            for (e, holds) in exit.iter_mut().zip(condition.evaluate(&data)?) {
+                *e |= holds;
+            }
+        }
+
+        let timestamps = parse_timestamp_column(df, &config.time_column)?;
+        let close = column_valid_values(df, &config.columns.close)?;
+        let risk = &config.risk;
+        let sign = config.side.sign();
+        // Bar ranges are needed by the stops and by limit and stop entries
+        let stop_bars = if risk.stops.is_empty() && order_column.is_none() {
+            Vec::new()
+        } else {
+            stop_bars(&data, &risk.stops, risk.atr_period)?
+        };
+        let entry_levels = match order_column {
+            Some(column) => column_valid_values(&data, column)?,
+            None => Vec::new(),
+        };
+        let order_side = match config.side {
+            TradeSide::Long => OrderSide::Buy,
+            TradeSide::Short => OrderSide::Sell,
+        };
+
+        let mut buy_signals = vec![0; n];
+        let mut sell_signals = vec![0; n];
+        let mut position = vec![0i32; n];
+        let mut exit_reason: Vec<Option<&str>> = vec![None; n];
+        let mut equity = vec![f64::NAN; n];
+        let mut exposure = vec![f64::NAN; n];
+        let mut trades = Vec::new();
+        let mut realized = 0.0;
+        let mut open: Option<(DateTime<Utc>, f64, usize)> = None;
+        let mut quantity = 0.0;
+        let mut stops: Option<TradeStops> = None;
+        let mut gate = SignalGate::new(config.timing);
+        let mut working: Option<(OrderType, usize)> = None;
+
+        let round_trip = |entry_time, entry_price: f64, exit_time, exit_price: f64, quantity| {
+            let fees = (entry_price + exit_price) * quantity * risk.fee_rate;
+            let pnl = sign * (exit_price - entry_price) * quantity - fees;
+            RoundTripTrade {
+                entry_time,
+                exit_time,
+                side: config.side,
+                quantity,
+                entry_price,
+                exit_price,
+                fees,
+                pnl,
+                return_pct: pnl / (entry_price * quantity) * 100.0,
+            }
+        };
+        let (entry_signals, exit_signals) = match config.side {
+            TradeSide::Long => (&mut buy_signals, &mut sell_signals),
+            TradeSide::Short => (&mut sell_signals, &mut buy_signals),
+        };
+
+        for i in 0..n {
+            let (Some(time), Some(price)) = (timestamps[i], close[i]) else {
+                position[i] = if open.is_some() { sign as i32 } else { 0 };
+                continue;
+            };
+
+            if let Some((entry_time, entry_price, entry_bar)) = open {
+                // Stops act intrabar, before the rules evaluated at the close
+                let mut stop_exit = None;
+                match stops.as_mut().map(|s| s.update(&stop_bars[i])) {
+                    Some(StopAction::Exit { price, reason }) => {
+                        stop_exit = Some((reason.as_str(), price))
+                    }
+                    Some(StopAction::ScaleOut { fraction, price }) => {
+                        let sold = risk.position_size * fraction;
+                        let trade = round_trip(entry_time, entry_price, time, price, sold);
+                        realized += trade.pnl;
+                        trades.push(trade);
+                        quantity -= sold;
+                        exit_signals[i] += 1;
+                        exit_reason[i] = Some("scale_out");
+                    }
+                    Some(StopAction::Hold) | None => {}
+                }
+
+                let change_pct = sign * (price - entry_price) / entry_price * 100.0;
+                let closing = stop_exit.or_else(|| {
+                    let reason = if risk.stop_loss_pct.is_some_and(|sl| change_pct <= -sl) {
+                        Some("stop_loss")
+                    } else if risk.take_profit_pct.is_some_and(|tp| change_pct >= tp) {
+                        Some("take_profit")
+                    } else if exit[i] && gate.can_exit(i) {
+                        Some("exit_rule")
+                    } else if risk.max_holding_bars.is_some_and(|m| i - entry_bar >= m) {
+                        Some("max_holding")
+                    } else {
+                        None
+                    };
+                    reason.map(|reason| (reason, price))
+                });
+
+                if let Some((reason, exit_price)) = closing {
+                    let trade = round_trip(entry_time, entry_price, time, exit_price, quantity);
+                    realized += trade.pnl;
+                    trades.push(trade);
+                    exit_signals[i] += 1;
+                    exit_reason[i] = Some(reason);
+                    open = None;
+                    stops = None;
+                    gate.exited(i);
+                }
+            } else {
+                let mut fill = None;
+                if let Some((order, last_bar)) = working {
+                    let bar = &stop_bars[i];
+                    let bar = Bar {
+                        time,
+                        open: bar.open,
+                        high: bar.high,
+                        low: bar.low,
+                        close: bar.close,
+                        volume: f64::NAN,
+                    };
+                    fill = order.fill_price(order_side, &bar);
+                    if fill.is_some() || i >= last_bar {
+                        working = None;
+                    }
+                }
+
+                if gate.entry(i, entry[i] as i32) && price > 0.0 && fill.is_none() {
+                    match &config.entry_order {
+                        EntryOrder::MarketOnClose => fill = Some(price),
+                        EntryOrder::Limit { valid_bars, .. } => {
+                            working = entry_levels[i]
+                                .map(|level| (OrderType::Limit { price: level }, i + valid_bars));
+                        }
+                        EntryOrder::Stop { valid_bars, .. } => {
+                            working = entry_levels[i]
+                                .map(|level| (OrderType::Stop { price: level }, i + valid_bars));
+                        }
+                    }
+                }
+
+                if let Some(fill) = fill {
+                    gate.entered(i);
+                    entry_signals[i] = 1;
+                    open = Some((time, fill, i));
+                    quantity = risk.position_size;
+                    if !risk.stops.is_empty() {
+                        stops = Some(TradeStops::new(
+                            &risk.stops,
+                            config.side,
+                            fill,
+                            stop_bars[i].atr,
+                        ));
+                    }
+                }
+            }
+
+            position[i] = if open.is_some() { sign as i32 } else { 0 };
+            // The entry fee is charged with the exit but already owed
+            (equity[i], exposure[i]) = match open {
+                Some((_, entry_price, _)) => (
+                    realized + sign * (price - entry_price) * quantity
+                        - entry_price * quantity * risk.fee_rate,
+                    price * quantity,
+                ),
+                None => (realized, 0.0),
+            };
+        }
+
+        let mut columns: Vec<(Series, bool)> =
+            indicator_series.into_iter().map(|s| (s, true)).collect();
+        columns.push((Series::new("strategy_position".into(), position), true));
+        columns.push((
+            Series::new("strategy_exit_reason".into(), exit_reason),
+            false,
+        ));
+        let indicator_values = build_indicator_values(
+            df,
+            &config.time_column,
+            &config.columns.close,
+            columns,
+            config.output_columns,
+        )?;
+
+        let [equity_curve, drawdown, exposure] = equity_outputs(equity, exposure);
+        Ok(StrategySignals {
+            buy_signals,
+            sell_signals,
+            indicator_values,
+            trades,
+            equity_curve,
+            drawdown,
+            exposure,
+        })
+    }
+}
+
+/// Bars seen by the stops, with the ATR when a policy needs it
+fn stop_bars(data: &DataFrame, rules: &StopRules, atr_period: usize) -> PolarsResult<Vec<StopBar>> {
+    let column = |name: &str| column_nan_values(data, name);
+    let n = data.height();
+    let open = if data.schema().contains("open") {
+        column("open")?
+    } else {
+        vec![f64::NAN; n]
+    };
+    let (high, low, close) = (column("high")?, column("low")?, column("close")?);
+    let atr = if rules.needs_atr() {
+        let prices = df! {
+            "high" => &high,
+            "low" => &low,
+            "close" => &close,
+        }?;
+        nan_values(calculate_atr(&prices, atr_period)?.f64()?)
+    } else {
+        vec![f64::NAN; n]
+    };
+
+    Ok((0..n)
+        .map(|i| StopBar {
+            open: open[i],
+            high: high[i],
+            low: low[i],
+            close: close[i],
+            atr: atr[i],
+        })
+        .collect())
+}
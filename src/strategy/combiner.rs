@@ -0,0 +1,245 @@
+//! # Signal Combination
+//!
+//! [`SignalCombiner`] turns a set of indicator columns into one composite
+//! signal from -2 (strong sell) to 2 (strong buy). Each registered column
+//! votes bullish, bearish or neutral on every bar according to its
+//! [`SignalRule`]; votes are weighted and compared with the combiner's
+//! thresholds. The combiner is plain data and can be built in code or
+//! deserialized from a config file.
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How an indicator column votes on each bar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SignalRule {
+    /// Bullish when positive, bearish when negative (for signal columns)
+    Sign,
+    /// Bullish above `bullish_above`, bearish below `bearish_below` (for
+    /// trend and momentum readings)
+    Above {
+        bullish_above: f64,
+        bearish_below: f64,
+    },
+    /// Bullish below `bullish_below`, bearish above `bearish_above` (for
+    /// oscillators such as RSI: oversold is bullish)
+    Below {
+        bullish_below: f64,
+        bearish_above: f64,
+    },
+    /// Bullish when the column is above the named column, bearish when below
+    /// (e.g. close against a moving average)
+    AboveColumn(String),
+}
+
+/// An indicator column registered with a [`SignalCombiner`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignalComponent {
+    /// Column read from the DataFrame
+    pub column: String,
+    /// Voting rule
+    pub rule: SignalRule,
+    /// Weight of the column's vote
+    pub weight: f64,
+}
+
+/// Weighted vote over indicator columns producing a -2..2 composite signal
+///
+/// On each bar the weights of bullish votes and of bearish votes are summed.
+/// The signal is 2 when the bullish weight reaches `strong_threshold` while
+/// the bearish weight is at most `max_opposing_weight`, 1 when bullish minus
+/// bearish weight reaches `moderate_threshold`, and symmetrically -2 and -1.
+/// Missing or NaN values abstain.
+///
+/// # Example
+///
+/// The swing-trading rules — trend class counting double, three bullish
+/// votes without dissent for a strong signal, any bullish majority for a
+/// moderate one — as a combiner:
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::strategy::combiner::{SignalCombiner, SignalRule};
+///
+/// let df = df! {
+///     "trend_classification" => [1, 1, -1, 0],
+///     "swing_signal" => [1, 0, -1, 0],
+///     "rsi" => [50.0, 75.0, 80.0, 25.0],
+/// }
+/// .unwrap();
+///
+/// let combiner = SignalCombiner::new()
+///     .with_component("trend_classification", SignalRule::Sign, 2.0)
+///     .with_component("swing_signal", SignalRule::Sign, 1.0)
+///     .with_component(
+///         "rsi",
+///         SignalRule::Below { bullish_below: 30.0, bearish_above: 70.0 },
+///         1.0,
+///     );
+///
+/// let signal = combiner.combine(&df).unwrap();
+/// let values: Vec<i32> = signal.i32().unwrap().into_no_null_iter().collect();
+/// assert_eq!(values, vec![2, 1, -2, 1]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignalCombiner {
+    /// Registered indicator columns
+    pub components: Vec<SignalComponent>,
+    /// Weight of agreeing votes needed for a strong (±2) signal
+    pub strong_threshold: f64,
+    /// Largest opposing weight still allowing a strong signal
+    pub max_opposing_weight: f64,
+    /// Net weight needed for a moderate (±1) signal
+    pub moderate_threshold: f64,
+    /// Name of the composite signal Series
+    pub output_name: String,
+}
+
+impl Default for SignalCombiner {
+    fn default() -> Self {
+        Self {
+            components: Vec::new(),
+            strong_threshold: 3.0,
+            max_opposing_weight: 0.0,
+            moderate_threshold: f64::MIN_POSITIVE,
+            output_name: "composite_signal".to_string(),
+        }
+    }
+}
+
+impl SignalCombiner {
+    /// Create a combiner without components: strong at a weight of 3 without
+    /// opposing votes, moderate at any positive net weight
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an indicator column
+    pub fn with_component(mut self, column: &str, rule: SignalRule, weight: f64) -> Self {
+        self.components.push(SignalComponent {
+            column: column.to_string(),
+            rule,
+            weight,
+        });
+        self
+    }
+
+    /// Net weighted vote on each bar: bullish minus bearish weight
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - DataFrame containing every registered column
+    ///
+    /// # Returns
+    ///
+    /// Returns a PolarsResult containing a Float64 Series named
+    /// "<output_name>_score"
+    pub fn score(&self, df: &DataFrame) -> PolarsResult<Series> {
+        let (bullish, bearish) = self.vote_weights(df)?;
+        let score: Vec<f64> = bullish.iter().zip(&bearish).map(|(b, s)| b - s).collect();
+        Ok(Series::new(
+            format!("{}_score", self.output_name).into(),
+            score,
+        ))
+    }
+
+    /// Composite signal on each bar
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - DataFrame containing every registered column
+    ///
+    /// # Returns
+    ///
+    /// Returns a PolarsResult containing an Int32 Series named `output_name`
+    /// with 2 (strong buy), 1 (buy), 0 (neutral), -1 (sell) or -2 (strong sell)
+    pub fn combine(&self, df: &DataFrame) -> PolarsResult<Series> {
+        let (bullish, bearish) = self.vote_weights(df)?;
+        let signal: Vec<i32> = bullish
+            .iter()
+            .zip(&bearish)
+            .map(|(&bull, &bear)| {
+                if bull >= self.strong_threshold && bear <= self.max_opposing_weight {
+                    2
+                } else if bear >= self.strong_threshold && bull <= self.max_opposing_weight {
+                    -2
+                } else if bull - bear >= self.moderate_threshold {
+                    1
+                } else if bear - bull >= self.moderate_threshold {
+                    -1
+                } else {
+                    0
+                }
+            })
+            .collect();
+        Ok(Series::new(self.output_name.as_str().into(), signal))
+    }
+
+    /// Summed bullish and bearish weights per bar
+    fn vote_weights(&self, df: &DataFrame) -> PolarsResult<(Vec<f64>, Vec<f64>)> {
+        if self.components.is_empty() {
+            return Err(PolarsError::ComputeError(
+                "Signal combiner has no components".into(),
+            ));
+        }
+        let column = |name: &str| -> PolarsResult<Float64Chunked> {
+            if !df.schema().contains(name) {
+                return Err(PolarsError::ComputeError(
+                    format!("Signal combiner requires '{name}' column").into(),
+                ));
+            }
+            Ok(df.column(name)?.cast(&DataType::Float64)?.f64()?.clone())
+        };
+
+        let n = df.height();
+        let mut bullish = vec![0.0; n];
+        let mut bearish = vec![0.0; n];
+        for component in &self.components {
+            let values = column(&component.column)?;
+            let reference = match &component.rule {
+                SignalRule::AboveColumn(other) => Some(column(other)?),
+                _ => None,
+            };
+            for i in 0..n {
+                let Some(value) = values.get(i).filter(|v| !v.is_nan()) else {
+                    continue;
+                };
+                let direction = match &component.rule {
+                    SignalRule::Sign => vote(value > 0.0, value < 0.0),
+                    SignalRule::Above {
+                        bullish_above,
+                        bearish_below,
+                    } => vote(value > *bullish_above, value < *bearish_below),
+                    SignalRule::Below {
+                        bullish_below,
+                        bearish_above,
+                    } => vote(value < *bullish_below, value > *bearish_above),
+                    SignalRule::AboveColumn(_) => {
+                        match reference
+                            .as_ref()
+                            .and_then(|r| r.get(i))
+                            .filter(|r| !r.is_nan())
+                        {
+                            Some(other) => vote(value > other, value < other),
+                            None => 0.0,
+                        }
+                    }
+                };
+                if direction > 0.0 {
+                    bullish[i] += component.weight;
+                } else if direction < 0.0 {
+                    bearish[i] += component.weight;
+                }
+            }
+        }
+        Ok((bullish, bearish))
+    }
+}
+
+fn vote(bullish: bool, bearish: bool) -> f64 {
+    match (bullish, bearish) {
+        (true, false) => 1.0,
+        (false, true) => -1.0,
+        _ => 0.0,
+    }
+}
@@ -0,0 +1,376 @@
+//! # Cross-Exchange Arbitrage Strategy
+//!
+//! Compares the price of one asset on several exchanges, buys on the
+//! cheapest venue and sells on the most expensive one when the spread net of
+//! fees is unusually wide. The coins bought are assumed to be transferred to
+//! the selling venue, so the sell leg executes after a configurable latency.
+
+use crate::performance::{RoundTripTrade, TradeSide};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Price series of one exchange
+#[derive(Debug, Clone, Copy)]
+pub struct ExchangeFeed<'a> {
+    /// Exchange name, used for column names and signal labels
+    pub name: &'a str,
+    /// Price data for this exchange
+    pub data: &'a DataFrame,
+    /// Taker fee charged on this exchange as a fraction of notional
+    pub fee_rate: f64,
+}
+
+impl<'a> ExchangeFeed<'a> {
+    /// Create a feed with a given fee rate
+    pub fn new(name: &'a str, data: &'a DataFrame, fee_rate: f64) -> Self {
+        Self {
+            name,
+            data,
+            fee_rate,
+        }
+    }
+}
+
+/// Parameters for the arbitrage strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyParams {
+    /// Column with timestamps in every feed
+    pub time_column: String,
+    /// Column with prices in every feed
+    pub price_column: String,
+    /// Number of bars for the spread z-score
+    pub zscore_window: usize,
+    /// Minimum spread z-score required to enter
+    pub entry_zscore: f64,
+    /// Minimum spread after fees, in percent, required to enter
+    pub min_net_spread_pct: f64,
+    /// Bars between buying on one exchange and selling on the other
+    pub transfer_latency_bars: usize,
+    /// Quantity traded per arbitrage
+    pub order_size: f64,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            time_column: "timestamp".to_string(),
+            price_column: "close".to_string(),
+            zscore_window: 60,
+            entry_zscore: 2.0,
+            min_net_spread_pct: 0.05,
+            transfer_latency_bars: 0,
+            order_size: 1.0,
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for StrategyParams {}
+
+/// Align the prices of several exchanges on common timestamps
+///
+/// Only timestamps present in every feed are kept, in ascending order.
+///
+/// # Arguments
+///
+/// * `feeds` - Exchange feeds (at least two)
+/// * `params` - Strategy parameters providing the time and price columns
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with a "timestamp" column
+/// and one "{name}_price" column per exchange
+pub fn align_feeds(feeds: &[ExchangeFeed], params: &StrategyParams) -> PolarsResult<DataFrame> {
+    let (times, prices) = aligned_prices(feeds, params)?;
+    let mut columns: Vec<Column> = Vec::with_capacity(feeds.len() + 1);
+    columns.push(Column::new(
+        "timestamp".into(),
+        times
+            .iter()
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .collect::<Vec<_>>(),
+    ));
+    for (feed, values) in feeds.iter().zip(prices) {
+        columns.push(Column::new(format!("{}_price", feed.name).into(), values));
+    }
+    DataFrame::new(columns)
+}
+
+/// Run the cross-exchange arbitrage strategy
+///
+/// On every aligned bar the cheapest and most expensive exchanges are
+/// compared. The net spread deducts the taker fee of both legs. An
+/// arbitrage is entered when the net spread exceeds `min_net_spread_pct`
+/// and its z-score over the previous `zscore_window` bars exceeds
+/// `entry_zscore`: the buy leg fills on the cheap exchange immediately and
+/// the sell leg fills on the expensive exchange `transfer_latency_bars`
+/// later at that bar's price. Only one transfer is in flight at a time.
+///
+/// # Arguments
+///
+/// * `feeds` - Exchange feeds (at least two)
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing StrategySignals indexed by aligned bar,
+/// where `indicator_values` holds the "{name}_price" columns plus
+/// "arb_spread_pct", "arb_net_spread_pct", "arb_spread_zscore",
+/// "arb_buy_exchange" and "arb_sell_exchange" (all but the gross spread are
+/// key indicators for `OutputColumns`), and `trades` holds one long round
+/// trip per arbitrage (entry on the buy venue, exit on the sell venue)
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::strategy::crypto::arbitrage::{run_strategy, ExchangeFeed, StrategyParams};
+/// use rustalib::strategy::OutputColumns;
+///
+/// let times = ["2024-01-01 00:00:00", "2024-01-01 00:01:00", "2024-01-01 00:02:00", "2024-01-01 00:03:00"];
+/// let a = df! { "timestamp" => times, "close" => [100.0, 100.0, 100.0, 100.0] }.unwrap();
+/// let b = df! { "timestamp" => times, "close" => [100.1, 100.0, 100.1, 101.0] }.unwrap();
+///
+/// let params = StrategyParams { zscore_window: 3, entry_zscore: 1.0, ..Default::default() };
+/// let feeds = [ExchangeFeed::new("a", &a, 0.001), ExchangeFeed::new("b", &b, 0.001)];
+/// let signals = run_strategy(&feeds, &params).unwrap();
+///
+/// assert_eq!(signals.buy_signals, vec![0, 0, 0, 1]);
+/// assert_eq!(signals.trades.len(), 1);
+///
+/// // Key indicators keep the per-exchange prices
+/// let params = StrategyParams { output_columns: OutputColumns::KeyIndicators, ..params };
+/// let signals = run_strategy(&feeds, &params).unwrap();
+/// let names = signals.indicator_values.get_column_names_str();
+/// assert_eq!(&names[..3], ["timestamp", "a_price", "b_price"]);
+/// assert!(!names.contains(&"arb_spread_pct"));
+/// ```
+pub fn run_strategy(
+    feeds: &[ExchangeFeed],
+    params: &StrategyParams,
+) -> PolarsResult<StrategySignals> {
+    if params.zscore_window < 2 {
+        return Err(PolarsError::ComputeError(
+            "Arbitrage z-score window must be at least 2".into(),
+        ));
+    }
+    let (times, prices) = aligned_prices(feeds, params)?;
+    let n = times.len();
+
+    let mut spread = vec![f64::NAN; n];
+    let mut net_spread = vec![f64::NAN; n];
+    let mut cheapest = vec![0; n];
+    let mut richest = vec![0; n];
+    for i in 0..n {
+        let (mut lo, mut hi) = (0, 0);
+        for e in 1..feeds.len() {
+            if prices[e][i] < prices[lo][i] {
+                lo = e;
+            }
+            if prices[e][i] > prices[hi][i] {
+                hi = e;
+            }
+        }
+        let (buy, sell) = (prices[lo][i], prices[hi][i]);
+        spread[i] = (sell - buy) / buy * 100.0;
+        let proceeds = sell * (1.0 - feeds[hi].fee_rate);
+        let cost = buy * (1.0 + feeds[lo].fee_rate);
+        net_spread[i] = (proceeds - cost) / cost * 100.0;
+        cheapest[i] = lo;
+        richest[i] = hi;
+    }
+
+    // Z-score of the current spread against the preceding window
+    let mut zscore = vec![f64::NAN; n];
+    for i in params.zscore_window..n {
+        let history = &spread[i - params.zscore_window..i];
+        let mean = history.iter().sum::<f64>() / history.len() as f64;
+        let var =
+            history.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (history.len() - 1) as f64;
+        let std = var.sqrt();
+        zscore[i] = if std > 0.0 {
+            (spread[i] - mean) / std
+        } else if spread[i] > mean {
+            f64::INFINITY
+        } else {
+            0.0
+        };
+    }
+
+    let mut buy_signals = vec![0; n];
+    let mut sell_signals = vec![0; n];
+    let mut buy_exchange: Vec<Option<&str>> = vec![None; n];
+    let mut sell_exchange: Vec<Option<&str>> = vec![None; n];
+    let mut equity = vec![0.0; n];
+    let mut exposure = vec![0.0; n];
+    let mut trades = Vec::new();
+    let mut realized = 0.0;
+    let mut pending: Option<(usize, usize, usize)> = None; // (entry bar, buy venue, sell venue)
+
+    for i in 0..n {
+        if let Some((entry, lo, hi)) = pending {
+            if i >= entry + params.transfer_latency_bars {
+                sell_signals[i] = 1;
+                sell_exchange[i] = Some(feeds[hi].name);
+                let trade =
+                    arbitrage_trade(feeds, &prices, &times, entry, i, lo, hi, params.order_size);
+                realized += trade.pnl;
+                trades.push(trade);
+                pending = None;
+            }
+        }
+        if pending.is_none()
+            && zscore[i] >= params.entry_zscore
+            && net_spread[i] >= params.min_net_spread_pct
+        {
+            let (lo, hi) = (cheapest[i], richest[i]);
+            buy_signals[i] = 1;
+            buy_exchange[i] = Some(feeds[lo].name);
+            if params.transfer_latency_bars == 0 {
+                sell_signals[i] = 1;
+                sell_exchange[i] = Some(feeds[hi].name);
+                let trade =
+                    arbitrage_trade(feeds, &prices, &times, i, i, lo, hi, params.order_size);
+                realized += trade.pnl;
+                trades.push(trade);
+            } else {
+                pending = Some((i, lo, hi));
+            }
+        }
+
+        // Coins in transfer are marked at the destination venue
+        equity[i] = realized;
+        if let Some((entry, lo, hi)) = pending {
+            let entry_price = prices[lo][entry];
+            let value = prices[hi][i] * params.order_size;
+            equity[i] += value - entry_price * params.order_size * (1.0 + feeds[lo].fee_rate);
+            exposure[i] = value;
+        }
+    }
+
+    // The aligned prices are key indicators, so they are passed as columns
+    let aligned = align_feeds(feeds, params)?;
+    let mut columns: Vec<(Series, bool)> = aligned.get_columns()[1..]
+        .iter()
+        .map(|price| (price.as_materialized_series().clone(), true))
+        .collect();
+    columns.extend([
+        (Series::new("arb_spread_pct".into(), spread), false),
+        (Series::new("arb_net_spread_pct".into(), net_spread), true),
+        (Series::new("arb_spread_zscore".into(), zscore), true),
+        (Series::new("arb_buy_exchange".into(), buy_exchange), true),
+        (Series::new("arb_sell_exchange".into(), sell_exchange), true),
+    ]);
+    let indicator_values = build_indicator_values(
+        &aligned.select(["timestamp"])?,
+        "timestamp",
+        &params.price_column,
+        columns,
+        params.output_columns,
+    )?;
+
+    let [equity_curve, drawdown, exposure] = equity_outputs(equity, exposure);
+    Ok(StrategySignals {
+        buy_signals,
+        sell_signals,
+        indicator_values,
+        trades,
+        equity_curve,
+        drawdown,
+        exposure,
+    })
+}
+
+/// Build the round trip for an arbitrage bought at `entry` and sold at `exit`
+#[allow(clippy::too_many_arguments)]
+fn arbitrage_trade(
+    feeds: &[ExchangeFeed],
+    prices: &[Vec<f64>],
+    times: &[DateTime<Utc>],
+    entry: usize,
+    exit: usize,
+    buy_venue: usize,
+    sell_venue: usize,
+    quantity: f64,
+) -> RoundTripTrade {
+    let entry_price = prices[buy_venue][entry];
+    let exit_price = prices[sell_venue][exit];
+    let fees = (entry_price * feeds[buy_venue].fee_rate + exit_price * feeds[sell_venue].fee_rate)
+        * quantity;
+    let pnl = (exit_price - entry_price) * quantity - fees;
+
+    RoundTripTrade {
+        entry_time: times[entry],
+        exit_time: times[exit],
+        side: TradeSide::Long,
+        quantity,
+        entry_price,
+        exit_price,
+        fees,
+        pnl,
+        return_pct: pnl / (entry_price * quantity) * 100.0,
+    }
+}
+
+/// Common timestamps and the matching prices, one vector per feed
+type AlignedPrices = (Vec<DateTime<Utc>>, Vec<Vec<f64>>);
+
+/// Timestamps present in every feed, with each feed's price at those times
+fn aligned_prices(feeds: &[ExchangeFeed], params: &StrategyParams) -> PolarsResult<AlignedPrices> {
+    if feeds.len() < 2 {
+        return Err(PolarsError::ComputeError(
+            "Arbitrage requires at least two exchange feeds".into(),
+        ));
+    }
+
+    let mut by_time: BTreeMap<DateTime<Utc>, Vec<Option<f64>>> = BTreeMap::new();
+    for (e, feed) in feeds.iter().enumerate() {
+        for col in [params.time_column.as_str(), params.price_column.as_str()] {
+            if !feed.data.schema().contains(col) {
+                return Err(PolarsError::ComputeError(
+                    format!("Exchange feed '{}' requires '{col}' column", feed.name).into(),
+                ));
+            }
+        }
+        let times = parse_timestamp_column(feed.data, &params.time_column)?;
+        let price = feed
+            .data
+            .column(&params.price_column)?
+            .cast(&DataType::Float64)?;
+        let price = price.f64()?;
+
+        for (i, time) in times.into_iter().enumerate() {
+            let (Some(time), Some(p)) = (time, price.get(i)) else {
+                continue;
+            };
+            if p.is_nan() || p <= 0.0 {
+                continue;
+            }
+            by_time
+                .entry(time)
+                .or_insert_with(|| vec![None; feeds.len()])[e] = Some(p);
+        }
+    }
+
+    let mut times = Vec::new();
+    let mut prices = vec![Vec::new(); feeds.len()];
+    for (time, row) in by_time {
+        if row.iter().all(|p| p.is_some()) {
+            times.push(time);
+            for (e, p) in row.into_iter().enumerate() {
+                prices[e].push(p.unwrap_or(f64::NAN));
+            }
+        }
+    }
+
+    Ok((times, prices))
+}
@@ -0,0 +1,421 @@
+//! # Grid Trading Strategy
+//!
+//! Places buy limit orders at fixed price levels inside a range and a sell
+//! limit order one level above every filled buy. Each completed pair books the
+//! distance between two neighbouring levels as profit, so the strategy earns
+//! from oscillation inside the range and accumulates inventory when price
+//! trends down through it.
+//!
+//! Fills are simulated from OHLC bars. The intrabar path is assumed to be
+//! open → low → high → close for up bars and open → high → low → close for
+//! down bars; orders crossed by a gap between bars fill at the open.
+
+use crate::performance::{RoundTripTrade, TradeSide};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::column_map::ColumnMap;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How grid levels are distributed between the bounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GridSpacing {
+    /// Levels separated by a fixed price distance
+    Arithmetic,
+    /// Levels separated by a fixed percentage
+    Geometric,
+}
+
+/// Parameters for the grid trading strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyParams {
+    /// Lowest grid level
+    pub lower_price: f64,
+    /// Highest grid level
+    pub upper_price: f64,
+    /// Number of intervals between the bounds (levels = num_grids + 1)
+    pub num_grids: usize,
+    /// Level distribution
+    pub spacing: GridSpacing,
+    /// Quantity in base units traded at each level
+    pub order_size: f64,
+    /// Fee charged per fill as a fraction of notional (e.g. 0.001 = 0.1%)
+    pub fee_rate: f64,
+    /// Quote currency available at the start
+    pub initial_capital: f64,
+    /// Re-place the buy order of a grid once its sell has filled
+    pub rebalance_on_fill: bool,
+    /// Column with bar timestamps
+    pub time_column: String,
+    /// Names of the OHLCV columns in the price data
+    #[serde(default)]
+    pub columns: ColumnMap,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl StrategyParams {
+    /// Create parameters for a range with default settings for everything else
+    ///
+    /// Defaults: 10 arithmetic grids, order size 1.0, 0.1% fees, enough capital
+    /// to fund every grid at the upper bound, rebalancing enabled, a
+    /// "timestamp" time column and standard OHLC names, keeping all columns in
+    /// the output.
+    pub fn new(lower_price: f64, upper_price: f64) -> Self {
+        let num_grids = 10;
+        Self {
+            lower_price,
+            upper_price,
+            num_grids,
+            spacing: GridSpacing::Arithmetic,
+            order_size: 1.0,
+            fee_rate: 0.001,
+            initial_capital: upper_price * num_grids as f64 * 1.01,
+            rebalance_on_fill: true,
+            time_column: "timestamp".to_string(),
+            columns: ColumnMap::default(),
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for StrategyParams {}
+
+/// State of one grid interval between level k and level k + 1
+#[derive(Debug, Clone, Copy)]
+enum GridCell {
+    /// Buy order resting at the lower level
+    Buying,
+    /// Holding inventory with a sell order resting at the upper level
+    Selling {
+        entry_time: DateTime<Utc>,
+        entry_price: f64,
+        entry_fee: f64,
+    },
+    /// Round trip completed and not re-armed
+    Done,
+}
+
+/// Mutable simulation state shared by the fill routines
+struct GridBook {
+    cells: Vec<GridCell>,
+    cash: f64,
+    inventory: f64,
+    trades: Vec<RoundTripTrade>,
+}
+
+impl GridBook {
+    fn buy(&mut self, k: usize, time: DateTime<Utc>, price: f64, params: &StrategyParams) -> bool {
+        let notional = price * params.order_size;
+        let fee = notional * params.fee_rate;
+        if self.cash < notional + fee {
+            return false;
+        }
+        self.cash -= notional + fee;
+        self.inventory += params.order_size;
+        self.cells[k] = GridCell::Selling {
+            entry_time: time,
+            entry_price: price,
+            entry_fee: fee,
+        };
+        true
+    }
+
+    fn sell(&mut self, k: usize, time: DateTime<Utc>, price: f64, params: &StrategyParams) {
+        let GridCell::Selling {
+            entry_time,
+            entry_price,
+            entry_fee,
+        } = self.cells[k]
+        else {
+            return;
+        };
+        let notional = price * params.order_size;
+        let fee = notional * params.fee_rate;
+        self.cash += notional - fee;
+        self.inventory -= params.order_size;
+
+        let fees = entry_fee + fee;
+        let pnl = (price - entry_price) * params.order_size - fees;
+        let entry_notional = entry_price * params.order_size;
+        self.trades.push(RoundTripTrade {
+            entry_time,
+            exit_time: time,
+            side: TradeSide::Long,
+            quantity: params.order_size,
+            entry_price,
+            exit_price: price,
+            fees,
+            pnl,
+            return_pct: if entry_notional != 0.0 {
+                pnl / entry_notional * 100.0
+            } else {
+                0.0
+            },
+        });
+
+        self.cells[k] = if params.rebalance_on_fill {
+            GridCell::Buying
+        } else {
+            GridCell::Done
+        };
+    }
+
+    /// Fill every order crossed by a price move from `from` to `to`
+    ///
+    /// Orders fill at their level unless `fill_price` is given (gaps).
+    /// Returns the number of (buys, sells) executed.
+    fn cross(
+        &mut self,
+        levels: &[f64],
+        from: f64,
+        to: f64,
+        fill_price: Option<f64>,
+        time: DateTime<Utc>,
+        params: &StrategyParams,
+    ) -> (i32, i32) {
+        let mut buys = 0;
+        let mut sells = 0;
+
+        if to < from {
+            // Falling price reaches the highest buy orders first
+            for k in (0..self.cells.len()).rev() {
+                let level = levels[k];
+                if matches!(self.cells[k], GridCell::Buying)
+                    && to <= level
+                    && level <= from
+                    && self.buy(k, time, fill_price.unwrap_or(level), params)
+                {
+                    buys += 1;
+                }
+            }
+        } else if to > from {
+            for k in 0..self.cells.len() {
+                let level = levels[k + 1];
+                if matches!(self.cells[k], GridCell::Selling { .. }) && from <= level && level <= to
+                {
+                    self.sell(k, time, fill_price.unwrap_or(level), params);
+                    sells += 1;
+                }
+            }
+        }
+
+        (buys, sells)
+    }
+}
+
+/// Calculate the price levels of a grid
+///
+/// # Arguments
+///
+/// * `params` - Grid parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the `num_grids + 1` levels in ascending order
+pub fn grid_levels(params: &StrategyParams) -> PolarsResult<Vec<f64>> {
+    if params.lower_price <= 0.0 || params.upper_price <= params.lower_price {
+        return Err(PolarsError::ComputeError(
+            format!(
+                "Grid bounds must satisfy 0 < lower < upper, got {} and {}",
+                params.lower_price, params.upper_price
+            )
+            .into(),
+        ));
+    }
+    if params.num_grids == 0 {
+        return Err(PolarsError::ComputeError(
+            "Grid trading requires at least one grid".into(),
+        ));
+    }
+
+    let n = params.num_grids as f64;
+    let levels = (0..=params.num_grids)
+        .map(|k| {
+            let k = k as f64;
+            match params.spacing {
+                GridSpacing::Arithmetic => {
+                    params.lower_price + (params.upper_price - params.lower_price) * k / n
+                }
+                GridSpacing::Geometric => {
+                    params.lower_price * (params.upper_price / params.lower_price).powf(k / n)
+                }
+            }
+        })
+        .collect();
+
+    Ok(levels)
+}
+
+/// Run the grid trading strategy over OHLC bars
+///
+/// On the first bar, grids lying entirely above the open are bought at the
+/// open so that each has a sell order above the market; the remaining grids
+/// rest as buy orders. Buys are skipped while cash is insufficient. With
+/// `rebalance_on_fill` disabled each grid trades at most one round trip.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with the open, high, low and close columns named in
+///   `params.columns` and the time column
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing StrategySignals where `buy_signals` and
+/// `sell_signals` count fills per bar, `indicator_values` holds the input with
+/// "grid_inventory", "grid_cash" and "grid_equity" columns (inventory and
+/// equity are the key indicators for `OutputColumns`), and `trades`
+/// lists every completed buy/sell pair. Inventory still held at the end is
+/// reflected in "grid_equity" and `equity_curve` only; `equity_curve` is
+/// "grid_equity" less the initial capital.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::strategy::crypto::grid_trading::{run_strategy, StrategyParams};
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-01 00:00:00", "2024-01-01 01:00:00"],
+///     "open" => [100.0, 96.0],
+///     "high" => [101.0, 111.0],
+///     "low" => [95.0, 89.0],
+///     "close" => [96.0, 110.0],
+/// }
+/// .unwrap();
+///
+/// let mut params = StrategyParams::new(90.0, 110.0);
+/// params.num_grids = 2;
+/// params.fee_rate = 0.0;
+///
+/// let signals = run_strategy(&df, &params).unwrap();
+/// let report = signals.performance_report();
+/// assert_eq!(report.total_trades, 2); // 90 -> 100 and 100 -> 110
+/// assert_eq!(report.total_pnl, 20.0);
+/// ```
+pub fn run_strategy(df: &DataFrame, params: &StrategyParams) -> PolarsResult<StrategySignals> {
+    let columns = &params.columns;
+    for col in [
+        columns.open.as_str(),
+        columns.high.as_str(),
+        columns.low.as_str(),
+        columns.close.as_str(),
+        params.time_column.as_str(),
+    ] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Grid trading requires '{col}' column").into(),
+            ));
+        }
+    }
+    if params.order_size <= 0.0 {
+        return Err(PolarsError::ComputeError(
+            "Grid order size must be positive".into(),
+        ));
+    }
+    let levels = grid_levels(params)?;
+
+    let timestamps = parse_timestamp_column(df, &params.time_column)?;
+    let open = df.column(&columns.open)?.f64()?;
+    let high = df.column(&columns.high)?.f64()?;
+    let low = df.column(&columns.low)?.f64()?;
+    let close = df.column(&columns.close)?.f64()?;
+
+    let n = df.height();
+    let mut buy_signals = vec![0; n];
+    let mut sell_signals = vec![0; n];
+    let mut inventory = Vec::with_capacity(n);
+    let mut cash = Vec::with_capacity(n);
+    let mut equity = Vec::with_capacity(n);
+    let mut exposure = Vec::with_capacity(n);
+
+    let mut book = GridBook {
+        cells: vec![GridCell::Buying; params.num_grids],
+        cash: params.initial_capital,
+        inventory: 0.0,
+        trades: Vec::new(),
+    };
+    let mut prev_close: Option<f64> = None;
+
+    for (i, time) in timestamps.into_iter().enumerate() {
+        let time = time.ok_or_else(|| {
+            PolarsError::ComputeError(format!("Invalid timestamp at row {i}").into())
+        })?;
+        let (o, h, l, c) = match (open.get(i), high.get(i), low.get(i), close.get(i)) {
+            (Some(o), Some(h), Some(l), Some(c)) if ![o, h, l, c].iter().any(|v| v.is_nan()) => {
+                (o, h, l, c)
+            }
+            _ => {
+                // Missing bar: carry the state forward
+                inventory.push(book.inventory);
+                cash.push(book.cash);
+                equity.push(book.cash + book.inventory * prev_close.unwrap_or(f64::NAN));
+                exposure.push(book.inventory * prev_close.unwrap_or(f64::NAN));
+                continue;
+            }
+        };
+
+        let mut buys = 0;
+        let mut sells = 0;
+
+        match prev_close {
+            None => {
+                // Seed inventory for grids that sit above the market
+                for (k, &level) in levels.iter().take(params.num_grids).enumerate() {
+                    if level >= o && book.buy(k, time, o, params) {
+                        buys += 1;
+                    }
+                }
+            }
+            Some(pc) => {
+                let (b, s) = book.cross(&levels, pc, o, Some(o), time, params);
+                buys += b;
+                sells += s;
+            }
+        }
+
+        let path = if c >= o { [o, l, h, c] } else { [o, h, l, c] };
+        for leg in path.windows(2) {
+            let (b, s) = book.cross(&levels, leg[0], leg[1], None, time, params);
+            buys += b;
+            sells += s;
+        }
+
+        buy_signals[i] = buys;
+        sell_signals[i] = sells;
+        inventory.push(book.inventory);
+        cash.push(book.cash);
+        equity.push(book.cash + book.inventory * c);
+        exposure.push(book.inventory * c);
+        prev_close = Some(c);
+    }
+
+    let pnl = equity.iter().map(|e| e - params.initial_capital).collect();
+    let indicator_values = build_indicator_values(
+        df,
+        &params.time_column,
+        &columns.close,
+        vec![
+            (Series::new("grid_inventory".into(), inventory), true),
+            (Series::new("grid_cash".into(), cash), false),
+            (Series::new("grid_equity".into(), equity), true),
+        ],
+        params.output_columns,
+    )?;
+
+    let [equity_curve, drawdown, exposure] = equity_outputs(pnl, exposure);
+    Ok(StrategySignals {
+        buy_signals,
+        sell_signals,
+        indicator_values,
+        trades: book.trades,
+        equity_curve,
+        drawdown,
+        exposure,
+    })
+}
@@ -0,0 +1,472 @@
+//! # Market-Neutral Funding Capture Strategy
+//!
+//! Holds spot against an opposite perpetual futures position of the same
+//! size, so price moves largely cancel and the position earns the funding
+//! the perpetual pays. With positive funding longs pay shorts, so the
+//! strategy buys spot and shorts the perpetual (cash and carry); with
+//! negative funding it can reverse, shorting borrowed spot against a long
+//! perpetual. Funding received, borrow costs and fees are tracked as
+//! separate P&L components.
+
+use crate::performance::{RoundTripTrade, TradeSide};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::dataframe_utils::column_valid_values;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 86_400.0;
+
+/// Parameters for the market-neutral strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyParams {
+    /// Column with timestamps in both price DataFrames
+    pub time_column: String,
+    /// Column with prices in both price DataFrames
+    pub price_column: String,
+    /// Column of the perpetual data with the funding rate paid on each bar
+    /// (e.g. 0.0001 = 0.01%), null or zero between funding times; positive
+    /// rates are paid by longs to shorts
+    pub funding_column: String,
+    /// Funding payments averaged for the entry and exit decisions
+    pub funding_window: usize,
+    /// Average funding rate per payment required to enter
+    pub entry_funding_rate: f64,
+    /// Exit once the average funding rate no longer exceeds this level
+    /// (mirrored for reverse positions)
+    pub exit_funding_rate: f64,
+    /// Short spot against a long perpetual when funding is negative
+    pub allow_reverse: bool,
+    /// Notional of each leg, in quote currency
+    pub notional: f64,
+    /// Bars between decisions; positions are entered, exited and resized
+    /// only on every `rebalance_interval`-th bar
+    pub rebalance_interval: usize,
+    /// Resize a position on a rebalance only when its spot quantity is off
+    /// the target by more than this fraction
+    pub rebalance_tolerance: f64,
+    /// Spot fee per fill as a fraction of notional
+    pub spot_fee_rate: f64,
+    /// Perpetual fee per fill as a fraction of notional
+    pub perp_fee_rate: f64,
+    /// Annual rate charged on the notional of borrowed (short) spot
+    pub borrow_rate: f64,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            time_column: "timestamp".to_string(),
+            price_column: "close".to_string(),
+            funding_column: "funding_rate".to_string(),
+            funding_window: 3,
+            entry_funding_rate: 0.0001,
+            exit_funding_rate: 0.0,
+            allow_reverse: false,
+            notional: 10_000.0,
+            rebalance_interval: 1,
+            rebalance_tolerance: 0.05,
+            spot_fee_rate: 0.001,
+            perp_fee_rate: 0.0005,
+            borrow_rate: 0.1,
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for StrategyParams {}
+
+/// P&L of a market-neutral backtest split by source, in quote currency
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CarryPerformance {
+    /// Price P&L of both legs, i.e. the P&L from changes in the basis
+    pub price: f64,
+    /// Funding received less funding paid
+    pub funding: f64,
+    /// Interest paid on borrowed spot
+    pub borrow: f64,
+    /// Fees paid on both legs
+    pub fees: f64,
+    /// `price + funding - borrow - fees`
+    pub total: f64,
+}
+
+/// Open spot and perpetual position
+struct OpenCarry {
+    entry: usize,
+    /// 1 for long spot / short perp, -1 for the reverse
+    direction: i32,
+    /// Signed spot quantity; the perpetual holds the opposite
+    quantity: f64,
+    pnl: f64,
+    fees: f64,
+}
+
+/// Run the market-neutral funding capture strategy
+///
+/// The spot and perpetual series are aligned on their common timestamps.
+/// The average of the last `funding_window` funding payments (rows with a
+/// non-zero rate) decides the position on every rebalance bar: at least
+/// `entry_funding_rate` enters long spot / short perpetual, at most
+/// `-entry_funding_rate` enters the reverse when allowed, and a position is
+/// closed once the average no longer exceeds `exit_funding_rate` in its
+/// favor. Both legs are sized to `notional` at the spot price and filled at
+/// the bar's prices; open positions are resized to `notional` on rebalance
+/// bars when they have drifted by more than `rebalance_tolerance`.
+///
+/// Funding paid on a bar settles on the position held into it, and borrow
+/// interest accrues on short spot for the time between bars.
+///
+/// # Arguments
+///
+/// * `spot` - Spot price data
+/// * `perp` - Perpetual price data with the funding column
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing StrategySignals indexed by aligned bar.
+/// The buy and sell signals count leg orders. `indicator_values` holds
+/// "timestamp", "spot_price", "perp_price" and "funding_rate" plus
+/// "mn_basis_pct", "mn_avg_funding", "mn_position" (1 long spot, -1 short
+/// spot, 0 flat) and the per-bar P&L components "mn_price_pnl",
+/// "mn_funding_pnl", "mn_borrow_cost" and "mn_costs" read by
+/// [`carry_performance`] (all key indicators for `OutputColumns`). Each
+/// trade spans one position, priced at spot, with every P&L component in
+/// its `pnl`.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::strategy::crypto::market_neutral::{carry_performance, run_strategy, StrategyParams};
+///
+/// let times: Vec<String> = (0..8).map(|h| format!("2024-01-01 {h:02}:00:00")).collect();
+/// let spot = df! { "timestamp" => &times, "close" => [100.0; 8] }.unwrap();
+/// let perp = df! {
+///     "timestamp" => &times,
+///     "close" => [100.0; 8],
+///     "funding_rate" => [0.001, 0.001, 0.001, 0.001, 0.001, 0.001, -0.001, -0.001],
+/// }
+/// .unwrap();
+///
+/// let params = StrategyParams {
+///     funding_window: 2,
+///     notional: 1000.0,
+///     spot_fee_rate: 0.0,
+///     perp_fee_rate: 0.0,
+///     ..Default::default()
+/// };
+/// let signals = run_strategy(&spot, &perp, &params).unwrap();
+///
+/// // Enter on bar 1, collect 1.0 of funding on bars 2-5, pay 1.0 on bar 6 and exit
+/// assert_eq!(signals.buy_signals, [0, 1, 0, 0, 0, 0, 1, 0]);
+/// assert_eq!(signals.trades.len(), 1);
+/// assert_eq!(signals.trades[0].pnl, 3.0);
+///
+/// let performance = carry_performance(&signals).unwrap();
+/// assert_eq!(performance.funding, 3.0);
+/// assert_eq!(performance.price, 0.0);
+/// ```
+pub fn run_strategy(
+    spot: &DataFrame,
+    perp: &DataFrame,
+    params: &StrategyParams,
+) -> PolarsResult<StrategySignals> {
+    validate(params)?;
+    let (times, s, p, funding) = aligned_markets(spot, perp, params)?;
+    let n = times.len();
+
+    let mut avg_funding = vec![f64::NAN; n];
+    let mut payments: Vec<f64> = Vec::new();
+    for i in 0..n {
+        if funding[i] != 0.0 {
+            payments.push(funding[i]);
+        }
+        if payments.len() >= params.funding_window {
+            let recent = &payments[payments.len() - params.funding_window..];
+            avg_funding[i] = recent.iter().sum::<f64>() / recent.len() as f64;
+        }
+    }
+
+    let mut buy_signals = vec![0; n];
+    let mut sell_signals = vec![0; n];
+    let mut position_state = vec![0i32; n];
+    let mut price_pnl = vec![0.0; n];
+    let mut funding_pnl = vec![0.0; n];
+    let mut borrow_cost = vec![0.0; n];
+    let mut costs = vec![0.0; n];
+    let mut equity = vec![0.0; n];
+    let mut exposure = vec![0.0; n];
+    let mut trades = Vec::new();
+    let mut position: Option<OpenCarry> = None;
+    let mut total = 0.0;
+
+    // Trade `delta` spot against `-delta` perpetual, returning the fees
+    let mut trade = |i: usize, delta: f64| -> f64 {
+        if delta != 0.0 {
+            // One leg buys and the other sells
+            buy_signals[i] += 1;
+            sell_signals[i] += 1;
+        }
+        delta.abs() * (s[i] * params.spot_fee_rate + p[i] * params.perp_fee_rate)
+    };
+
+    for i in 0..n {
+        if let Some(pos) = &mut position {
+            let q = pos.quantity;
+            price_pnl[i] = q * (s[i] - s[i - 1]) - q * (p[i] - p[i - 1]);
+            funding_pnl[i] = q * p[i] * funding[i];
+            if q < 0.0 {
+                let years = (times[i] - times[i - 1]).num_seconds() as f64 / SECONDS_PER_YEAR;
+                borrow_cost[i] = -q * s[i] * params.borrow_rate * years;
+            }
+            pos.pnl += price_pnl[i] + funding_pnl[i] - borrow_cost[i];
+        }
+
+        let avg = avg_funding[i];
+        if i % params.rebalance_interval == 0 && !avg.is_nan() {
+            let current = position.as_ref().map_or(0, |pos| pos.direction);
+            let target = match current {
+                1 if avg > params.exit_funding_rate => 1,
+                -1 if avg < -params.exit_funding_rate => -1,
+                _ if avg >= params.entry_funding_rate => 1,
+                _ if params.allow_reverse && avg <= -params.entry_funding_rate => -1,
+                _ => 0,
+            };
+            let quantity = target as f64 * params.notional / s[i];
+
+            if target != current {
+                if let Some(mut pos) = position.take() {
+                    let fees = trade(i, -pos.quantity);
+                    costs[i] += fees;
+                    pos.pnl -= fees;
+                    pos.fees += fees;
+                    trades.push(carry_trade(&times, &s, &pos, i));
+                }
+                if target != 0 {
+                    let fees = trade(i, quantity);
+                    costs[i] += fees;
+                    position = Some(OpenCarry {
+                        entry: i,
+                        direction: target,
+                        quantity,
+                        pnl: -fees,
+                        fees,
+                    });
+                }
+            } else if let Some(pos) = &mut position {
+                let drift = (quantity - pos.quantity).abs() / pos.quantity.abs();
+                if drift > params.rebalance_tolerance {
+                    let fees = trade(i, quantity - pos.quantity);
+                    costs[i] += fees;
+                    pos.pnl -= fees;
+                    pos.fees += fees;
+                    pos.quantity = quantity;
+                }
+            }
+        }
+
+        total += price_pnl[i] + funding_pnl[i] - borrow_cost[i] - costs[i];
+        equity[i] = total;
+        if let Some(pos) = &position {
+            position_state[i] = pos.direction;
+            exposure[i] = pos.quantity.abs() * (s[i] + p[i]);
+        }
+    }
+
+    let basis: Vec<f64> = s.iter().zip(&p).map(|(s, p)| (p - s) / s * 100.0).collect();
+    let aligned = DataFrame::new(vec![
+        Column::new(
+            "timestamp".into(),
+            times
+                .iter()
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                .collect::<Vec<_>>(),
+        ),
+        Column::new("spot_price".into(), &s),
+        Column::new("perp_price".into(), &p),
+        Column::new("funding_rate".into(), &funding),
+    ])?;
+    let indicator_values = build_indicator_values(
+        &aligned,
+        "timestamp",
+        "spot_price",
+        vec![
+            (Series::new("mn_basis_pct".into(), basis), true),
+            (Series::new("mn_avg_funding".into(), avg_funding), true),
+            (Series::new("mn_position".into(), position_state), true),
+            (Series::new("mn_price_pnl".into(), price_pnl), true),
+            (Series::new("mn_funding_pnl".into(), funding_pnl), true),
+            (Series::new("mn_borrow_cost".into(), borrow_cost), true),
+            (Series::new("mn_costs".into(), costs), true),
+        ],
+        params.output_columns,
+    )?;
+
+    let [equity_curve, drawdown, exposure] = equity_outputs(equity, exposure);
+    Ok(StrategySignals {
+        buy_signals,
+        sell_signals,
+        indicator_values,
+        trades,
+        equity_curve,
+        drawdown,
+        exposure,
+    })
+}
+
+/// Split the P&L of a market-neutral backtest by source
+///
+/// # Arguments
+///
+/// * `signals` - Output of [`run_strategy`] with the P&L columns retained
+///   (`OutputColumns::All` or `KeyIndicators`)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`CarryPerformance`]; `total`
+/// equals the final value of the equity curve
+pub fn carry_performance(signals: &StrategySignals) -> PolarsResult<CarryPerformance> {
+    let sum = |name: &str| -> PolarsResult<f64> {
+        let column = signals.indicator_values.column(name).map_err(|_| {
+            PolarsError::ComputeError(
+                format!("Carry performance requires the '{name}' indicator column").into(),
+            )
+        })?;
+        Ok(column
+            .f64()?
+            .into_iter()
+            .flatten()
+            .filter(|v| !v.is_nan())
+            .sum())
+    };
+
+    let mut performance = CarryPerformance {
+        price: sum("mn_price_pnl")?,
+        funding: sum("mn_funding_pnl")?,
+        borrow: sum("mn_borrow_cost")?,
+        fees: sum("mn_costs")?,
+        total: 0.0,
+    };
+    performance.total =
+        performance.price + performance.funding - performance.borrow - performance.fees;
+    Ok(performance)
+}
+
+fn validate(params: &StrategyParams) -> PolarsResult<()> {
+    let error = |message: &str| Err(PolarsError::ComputeError(message.to_string().into()));
+    if params.funding_window == 0 {
+        return error("Market-neutral funding window must be positive");
+    }
+    if params.rebalance_interval == 0 {
+        return error("Market-neutral rebalance interval must be positive");
+    }
+    if params.exit_funding_rate >= params.entry_funding_rate {
+        return error("Market-neutral exit funding rate must be below the entry rate");
+    }
+    if params.notional <= 0.0 {
+        return error("Market-neutral notional must be positive");
+    }
+    if params.rebalance_tolerance < 0.0 {
+        return error("Market-neutral rebalance tolerance must not be negative");
+    }
+    Ok(())
+}
+
+/// Build the round trip of a closed position
+fn carry_trade(times: &[DateTime<Utc>], s: &[f64], pos: &OpenCarry, exit: usize) -> RoundTripTrade {
+    let entry_notional = pos.quantity.abs() * s[pos.entry];
+    RoundTripTrade {
+        entry_time: times[pos.entry],
+        exit_time: times[exit],
+        side: if pos.direction > 0 {
+            TradeSide::Long
+        } else {
+            TradeSide::Short
+        },
+        quantity: pos.quantity.abs(),
+        entry_price: s[pos.entry],
+        exit_price: s[exit],
+        fees: pos.fees,
+        pnl: pos.pnl,
+        return_pct: pos.pnl / entry_notional * 100.0,
+    }
+}
+
+/// Common timestamps with the spot price, perpetual price and funding rate
+type AlignedMarkets = (Vec<DateTime<Utc>>, Vec<f64>, Vec<f64>, Vec<f64>);
+
+/// Timestamps present in both series, with prices and funding at those times
+fn aligned_markets(
+    spot: &DataFrame,
+    perp: &DataFrame,
+    params: &StrategyParams,
+) -> PolarsResult<AlignedMarkets> {
+    for (label, df, columns) in [
+        (
+            "spot",
+            spot,
+            vec![&params.time_column, &params.price_column],
+        ),
+        (
+            "perpetual",
+            perp,
+            vec![
+                &params.time_column,
+                &params.price_column,
+                &params.funding_column,
+            ],
+        ),
+    ] {
+        for col in columns {
+            if !df.schema().contains(col) {
+                return Err(PolarsError::ComputeError(
+                    format!("Market-neutral strategy requires '{col}' column in the {label} data")
+                        .into(),
+                ));
+            }
+        }
+    }
+
+    let mut by_time: BTreeMap<DateTime<Utc>, [Option<f64>; 3]> = BTreeMap::new();
+    let spot_prices = column_valid_values(spot, &params.price_column)?;
+    for (i, time) in parse_timestamp_column(spot, &params.time_column)?
+        .into_iter()
+        .enumerate()
+    {
+        if let (Some(time), Some(price)) = (time, spot_prices[i].filter(|p| *p > 0.0)) {
+            by_time.entry(time).or_insert([None; 3])[0] = Some(price);
+        }
+    }
+    let perp_prices = column_valid_values(perp, &params.price_column)?;
+    let rates = column_valid_values(perp, &params.funding_column)?;
+    for (i, time) in parse_timestamp_column(perp, &params.time_column)?
+        .into_iter()
+        .enumerate()
+    {
+        if let (Some(time), Some(price)) = (time, perp_prices[i].filter(|p| *p > 0.0)) {
+            let row = by_time.entry(time).or_insert([None; 3]);
+            row[1] = Some(price);
+            row[2] = Some(rates[i].unwrap_or(0.0));
+        }
+    }
+
+    let (mut times, mut s, mut p, mut funding) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for (time, row) in by_time {
+        if let [Some(spot), Some(perp), Some(rate)] = row {
+            times.push(time);
+            s.push(spot);
+            p.push(perp);
+            funding.push(rate);
+        }
+    }
+    Ok((times, s, p, funding))
+}
@@ -0,0 +1,11 @@
+//! # Crypto Strategies
+//!
+//! Strategies designed for continuously traded crypto markets.
+//!
+//! - [`arbitrage`](arbitrage/index.html): Cross-exchange arbitrage across several price feeds
+//! - [`grid_trading`](grid_trading/index.html): Range-bound grid trading with limit orders at fixed price levels
+//! - [`market_neutral`](market_neutral/index.html): Spot against perpetual futures capturing funding payments
+
+pub mod arbitrage;
+pub mod grid_trading;
+pub mod market_neutral;
@@ -0,0 +1,200 @@
+//! # Trading Strategies
+//!
+//! This module contains complete, runnable trading strategies organized by
+//! asset class. Each strategy exposes a `StrategyParams` struct with sensible
+//! defaults and a `run_strategy` function returning [`StrategySignals`].
+//! The `output_columns` parameter ([`OutputColumns`]) controls how much of the
+//! input is carried into the result. Every `StrategyParams` implements
+//! [`ParamsFile`], so parameter sets can be saved to and loaded from JSON or
+//! TOML files.
+//!
+//! ## Available Strategy Groups
+//!
+//! - [`builder`](builder/index.html): Strategies built from declarative TOML/JSON configs
+//! - [`combiner`](combiner/index.html): Weighted combination of indicator signals
+//! - [`crypto`](crypto/index.html): Strategies for crypto markets
+//! - [`options`](options/index.html): Multi-leg options strategies
+//! - [`registry`](registry/index.html): Lookup of the bundled strategies by name
+//! - [`runtime`](runtime/index.html): Signal timing controls shared by the run loops
+//! - [`stock`](stock/index.html): Strategies for listed equities
+
+pub mod builder;
+pub mod combiner;
+pub mod crypto;
+pub mod options;
+pub mod registry;
+pub mod runtime;
+pub mod stock;
+
+use crate::error::{TaError, TaResult};
+use crate::performance::{trades_to_dataframe, PerformanceReport, RoundTripTrade};
+use crate::risk::{calculate_var, returns_from_equity, TailRisk, VarMethod};
+use polars::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Columns a strategy keeps in [`StrategySignals::indicator_values`]
+///
+/// Long minute histories make a full copy of the input expensive; the
+/// narrower modes keep only what is needed to line results up with bars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputColumns {
+    /// Only the time column; signals and trades are in the other fields
+    SignalsOnly,
+    /// The time column, the close column when present, and the strategy's key indicators
+    KeyIndicators,
+    /// Every input column plus every indicator and state column
+    #[default]
+    All,
+}
+
+/// Saving and loading strategy parameters as JSON or TOML files
+///
+/// The format follows the file extension: ".toml" for TOML, anything else
+/// for JSON. Params with a `Default` accept files listing only the fields
+/// that differ from it.
+///
+/// # Example
+///
+/// ```
+/// use rustalib::strategy::crypto::grid_trading::{GridSpacing, StrategyParams};
+/// use rustalib::strategy::ParamsFile;
+///
+/// let mut params = StrategyParams::new(90.0, 110.0);
+/// params.spacing = GridSpacing::Geometric;
+///
+/// let path = std::env::temp_dir().join("rustalib_grid_params.toml");
+/// params.to_file(&path).unwrap();
+/// let loaded = StrategyParams::from_file(&path).unwrap();
+/// assert_eq!(loaded.spacing, GridSpacing::Geometric);
+/// assert_eq!(loaded.upper_price, 110.0);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub trait ParamsFile: Serialize + DeserializeOwned {
+    /// Load parameters from a JSON or TOML file
+    fn from_file(path: impl AsRef<Path>) -> TaResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let parsed = if is_toml(path) {
+            toml::from_str(&text).map_err(|e| e.to_string())
+        } else {
+            serde_json::from_str(&text).map_err(|e| e.to_string())
+        };
+        parsed.map_err(|e| TaError::invalid_parameter(path.display().to_string(), e))
+    }
+
+    /// Save parameters to a JSON or TOML file
+    fn to_file(&self, path: impl AsRef<Path>) -> TaResult<()> {
+        let path = path.as_ref();
+        let text = if is_toml(path) {
+            toml::to_string_pretty(self).map_err(|e| e.to_string())
+        } else {
+            serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+        }
+        .map_err(|e| TaError::ComputationError(format!("Cannot serialize parameters: {e}")))?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+}
+
+/// Output of a strategy run
+#[derive(Debug, Clone)]
+pub struct StrategySignals {
+    /// Number of buy orders executed on each bar
+    pub buy_signals: Vec<i32>,
+    /// Number of sell orders executed on each bar
+    pub sell_signals: Vec<i32>,
+    /// Input data with the strategy's indicator and state columns attached
+    pub indicator_values: DataFrame,
+    /// Completed round-trip trades in order of exit
+    pub trades: Vec<RoundTripTrade>,
+    /// "equity": realized plus open P&L after the fees paid so far, per bar
+    pub equity_curve: Series,
+    /// "drawdown": decline of `equity_curve` from its running peak, per bar
+    pub drawdown: Series,
+    /// "exposure": absolute market value of the open positions, per bar
+    pub exposure: Series,
+}
+
+impl StrategySignals {
+    /// Summary statistics of the completed trades
+    pub fn performance_report(&self) -> PerformanceReport {
+        PerformanceReport::from_trades(&self.trades)
+    }
+
+    /// Completed trades as a trade-log DataFrame
+    pub fn trade_log(&self) -> PolarsResult<DataFrame> {
+        trades_to_dataframe(&self.trades)
+    }
+
+    /// Historical VaR and expected shortfall of the per-bar returns of
+    /// `equity_curve` on `initial_capital`
+    pub fn tail_risk(&self, initial_capital: f64, confidence: f64) -> PolarsResult<TailRisk> {
+        let returns = returns_from_equity(&self.equity_curve, initial_capital)?;
+        calculate_var(&returns, confidence, VarMethod::Historical)
+    }
+}
+
+/// Build the equity curve, drawdown and exposure Series from per-bar values
+///
+/// Bars the simulation skipped hold NaN and carry the previous values (zero
+/// before the first simulated bar).
+pub(crate) fn equity_outputs(mut equity: Vec<f64>, mut exposure: Vec<f64>) -> [Series; 3] {
+    let (mut last_equity, mut last_exposure) = (0.0, 0.0);
+    let mut peak = 0.0_f64;
+    let mut drawdown = Vec::with_capacity(equity.len());
+    for (e, x) in equity.iter_mut().zip(exposure.iter_mut()) {
+        if e.is_nan() {
+            *e = last_equity;
+            *x = last_exposure;
+        }
+        (last_equity, last_exposure) = (*e, *x);
+        peak = peak.max(*e);
+        drawdown.push(peak - *e);
+    }
+    [
+        Series::new("equity".into(), equity),
+        Series::new("drawdown".into(), drawdown),
+        Series::new("exposure".into(), exposure),
+    ]
+}
+
+/// Assemble `indicator_values` for the requested output mode
+///
+/// `columns` pairs each strategy column with whether it is a key indicator.
+/// Input columns are shared with `input`, not copied.
+pub(crate) fn build_indicator_values(
+    input: &DataFrame,
+    time_column: &str,
+    close_column: &str,
+    columns: Vec<(Series, bool)>,
+    mode: OutputColumns,
+) -> PolarsResult<DataFrame> {
+    let mut output = match mode {
+        OutputColumns::All => input.clone(),
+        OutputColumns::KeyIndicators if input.schema().contains(close_column) => {
+            input.select([time_column, close_column])?
+        }
+        OutputColumns::KeyIndicators | OutputColumns::SignalsOnly => input.select([time_column])?,
+    };
+
+    for (series, key) in columns {
+        let keep = match mode {
+            OutputColumns::All => true,
+            OutputColumns::KeyIndicators => key,
+            OutputColumns::SignalsOnly => false,
+        };
+        if keep {
+            output.with_column(series)?;
+        }
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,605 @@
+//! # Delta-Neutral Strategy
+//!
+//! Holds an at-the-money straddle and keeps its delta inside a band by
+//! trading the underlying, so that the result is driven by realized versus
+//! implied volatility rather than direction.
+//!
+//! On every bar the straddle is marked to market (see the
+//! [module docs](super)) and its P&L since the previous bar is attributed to
+//! the Greeks of the previous bar: delta (Δ·dS), gamma (½Γ·dS²), theta
+//! (Θ·days elapsed), vega (vega·change in IV) and a residual for higher-order
+//! effects. The hedge is rebalanced to zero delta whenever the net delta of
+//! options plus hedge leaves the band. Option commissions, the bid/ask cost
+//! of opening and closing the straddle, and hedge transaction costs are
+//! tracked separately, and [`pnl_attribution`] sums everything per component.
+
+use crate::indicators::options::pricing::{OptionGreeks, PricingModel};
+use crate::performance::{RoundTripTrade, TradeSide};
+use crate::strategy::options::{
+    days_between, select_expiry, ChainHistory, ChainQuote, OptionLeg, Valuation,
+};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::column_map::ColumnMap;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Whether the straddle is bought or sold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StraddleSide {
+    /// Long straddle: long gamma, pays theta
+    Long,
+    /// Short straddle: short gamma, collects theta
+    Short,
+}
+
+/// Parameters for the delta-neutral strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyParams {
+    /// Whether the straddle is bought or sold
+    pub side: StraddleSide,
+    /// Shortest days to expiry accepted at entry
+    pub min_days_to_expiry: f64,
+    /// Longest days to expiry accepted at entry
+    pub max_days_to_expiry: f64,
+    /// Close the straddle and the hedge when this many days or fewer remain
+    pub exit_days_to_expiry: f64,
+    /// Largest absolute net delta, in units of the underlying, left unhedged
+    pub delta_band: f64,
+    /// Cost of hedge trades in basis points of the traded notional
+    pub hedge_cost_bps: f64,
+    /// Number of straddles traded
+    pub contracts: f64,
+    /// Underlying units per contract
+    pub contract_multiplier: f64,
+    /// Commission per contract per leg, charged on entry and exit
+    pub fee_per_contract: f64,
+    /// Continuously compounded risk-free rate as a decimal
+    pub risk_free_rate: f64,
+    /// Continuous dividend yield of the underlying as a decimal
+    pub dividend_yield: f64,
+    /// Model for IV, Greeks and re-pricing legs without a quote
+    pub model: PricingModel,
+    /// Column with timestamps in the underlying data and quote times in the chain
+    pub time_column: String,
+    /// Names of the OHLCV columns in the price data
+    pub columns: ColumnMap,
+    /// Column with option mark prices in the chain
+    pub price_column: String,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            side: StraddleSide::Long,
+            min_days_to_expiry: 30.0,
+            max_days_to_expiry: 60.0,
+            exit_days_to_expiry: 5.0,
+            delta_band: 20.0,
+            hedge_cost_bps: 1.0,
+            contracts: 1.0,
+            contract_multiplier: 100.0,
+            fee_per_contract: 0.65,
+            risk_free_rate: 0.02,
+            dividend_yield: 0.0,
+            model: PricingModel::BlackScholes,
+            time_column: "timestamp".to_string(),
+            columns: ColumnMap::default(),
+            price_column: "price".to_string(),
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for StrategyParams {}
+
+/// P&L of a delta-neutral backtest split by source, in currency
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PnlAttribution {
+    /// Option P&L explained by delta
+    pub delta: f64,
+    /// Option P&L explained by gamma
+    pub gamma: f64,
+    /// Option P&L explained by time decay
+    pub theta: f64,
+    /// Option P&L explained by changes in implied volatility
+    pub vega: f64,
+    /// Option P&L not explained by the Greeks above
+    pub residual: f64,
+    /// P&L of the underlying hedge
+    pub hedge: f64,
+    /// Option commissions, bid/ask costs and hedge transaction costs
+    pub costs: f64,
+    /// Sum of all components net of costs
+    pub total: f64,
+    /// Number of trades in the underlying, including closing the hedge
+    pub hedge_trades: usize,
+}
+
+/// Straddle and hedge state while a position is open
+struct OpenPosition {
+    entry_time: DateTime<Utc>,
+    legs: [OptionLeg; 2],
+    /// Signed premium per unit at entry fills (negative when sold)
+    premium: f64,
+    hedge: f64,
+    spot: f64,
+    time: DateTime<Utc>,
+    value: f64,
+    greeks: [(OptionGreeks, f64); 2],
+    costs: f64,
+    pnl: f64,
+}
+
+/// Backtest a delta-hedged straddle
+///
+/// A straddle at the strike nearest the underlying is opened on the first bar
+/// with a chain snapshot offering an expiry in the configured window, hedged
+/// bar by bar, and closed together with the hedge at the days-to-expiry
+/// threshold; a new straddle is opened on the next suitable snapshot.
+///
+/// # Arguments
+///
+/// * `underlying` - Underlying bars with the time column and the close column
+/// * `chain` - Options chain history: the time column holding each quote's
+///   snapshot time, plus `strike`, `expiry`, `option_type` and the price
+///   column and/or `bid` and `ask`. Snapshots are matched to bars with the
+///   same timestamp.
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing [`StrategySignals`] where buy and sell
+/// signals mark hedge trades in the underlying. Each straddle is one round
+/// trip (long or short by `side`) in units of the underlying with the
+/// premium per unit as entry and exit price; its P&L includes the hedge and
+/// all costs, and the return is measured against the premium. Indicator
+/// columns: "dn_strike", "dn_days_to_expiry", "dn_option_delta",
+/// "dn_hedge_position" and "dn_net_delta" (units of the underlying after
+/// rebalancing), the per-bar attribution "dn_delta_pnl", "dn_gamma_pnl",
+/// "dn_theta_pnl", "dn_vega_pnl", "dn_residual_pnl", "dn_hedge_pnl" and
+/// "dn_costs", and "dn_pnl" (open P&L of the position net of costs).
+///
+/// # Example
+///
+/// ```
+/// use chrono::{Duration, TimeZone, Utc};
+/// use polars::prelude::*;
+/// use rustalib::indicators::options::black_scholes_price;
+/// use rustalib::strategy::options::delta_neutral::{pnl_attribution, run_strategy, StrategyParams};
+///
+/// let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+/// let expiry = start + Duration::days(45);
+/// let closes: Vec<f64> = (0..45).map(|d| 100.0 + 4.0 * (d as f64 / 3.0).sin()).collect();
+/// let times: Vec<String> = (0..45)
+///     .map(|d| (start + Duration::days(d)).format("%Y-%m-%d").to_string())
+///     .collect();
+///
+/// // One chain snapshot on the first day, priced at 20% volatility
+/// let (mut strikes, mut types, mut prices) = (Vec::new(), Vec::new(), Vec::new());
+/// for k in [95.0, 100.0, 105.0] {
+///     for call in [true, false] {
+///         strikes.push(k);
+///         types.push(if call { "call" } else { "put" });
+///         prices.push(black_scholes_price(100.0, k, 45.0 / 365.0, 0.02, 0.0, 0.2, call));
+///     }
+/// }
+/// let chain = df! {
+///     "timestamp" => vec![times[0].clone(); 6],
+///     "strike" => strikes,
+///     "expiry" => vec![expiry.format("%Y-%m-%d").to_string(); 6],
+///     "option_type" => types,
+///     "price" => prices,
+/// }
+/// .unwrap();
+/// let underlying = df! { "timestamp" => times, "close" => closes }.unwrap();
+///
+/// let signals = run_strategy(&underlying, &chain, &StrategyParams::default()).unwrap();
+/// let attribution = pnl_attribution(&signals).unwrap();
+///
+/// assert_eq!(signals.trades.len(), 1);
+/// assert!(attribution.hedge_trades > 0);
+/// assert!(attribution.gamma > 0.0 && attribution.theta < 0.0);
+/// assert!((attribution.total - signals.trades[0].pnl).abs() < 1e-6);
+/// ```
+pub fn run_strategy(
+    underlying: &DataFrame,
+    chain: &DataFrame,
+    params: &StrategyParams,
+) -> PolarsResult<StrategySignals> {
+    for col in [params.time_column.as_str(), params.columns.close.as_str()] {
+        if !underlying.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Delta-neutral strategy requires '{col}' column in the underlying data")
+                    .into(),
+            ));
+        }
+    }
+    if params.contracts <= 0.0 || params.delta_band < 0.0 {
+        return Err(PolarsError::ComputeError(
+            "Delta-neutral contracts must be positive and the delta band non-negative".into(),
+        ));
+    }
+
+    let history = ChainHistory::new(chain, &params.time_column, &params.price_column)?;
+    let valuation = Valuation {
+        model: &params.model,
+        risk_free_rate: params.risk_free_rate,
+        dividend_yield: params.dividend_yield,
+    };
+    let timestamps = parse_timestamp_column(underlying, &params.time_column)?;
+    let close = underlying
+        .column(&params.columns.close)?
+        .cast(&DataType::Float64)?;
+    let close = close.f64()?;
+
+    let n = underlying.height();
+    let quantity = params.contracts * params.contract_multiplier;
+    let option_fees = 2.0 * params.contracts * params.fee_per_contract;
+    let hedge_cost =
+        |shares: f64, spot: f64| shares.abs() * spot * params.hedge_cost_bps / 10_000.0;
+
+    let mut buy_signals = vec![0; n];
+    let mut sell_signals = vec![0; n];
+    let mut strike = vec![f64::NAN; n];
+    let mut days_left = vec![f64::NAN; n];
+    let mut option_delta = vec![f64::NAN; n];
+    let mut hedge_position = vec![f64::NAN; n];
+    let mut net_delta = vec![f64::NAN; n];
+    let mut delta_pnl = vec![f64::NAN; n];
+    let mut gamma_pnl = vec![f64::NAN; n];
+    let mut theta_pnl = vec![f64::NAN; n];
+    let mut vega_pnl = vec![f64::NAN; n];
+    let mut residual_pnl = vec![f64::NAN; n];
+    let mut hedge_pnl = vec![f64::NAN; n];
+    let mut costs = vec![f64::NAN; n];
+    let mut open_pnl = vec![f64::NAN; n];
+    let mut equity = vec![f64::NAN; n];
+    let mut exposure = vec![f64::NAN; n];
+    let mut trades = Vec::new();
+    let mut realized = 0.0;
+    let mut position: Option<OpenPosition> = None;
+
+    let mut hedge_signal = |i: usize, shares: f64| {
+        if shares > 0.0 {
+            buy_signals[i] = 1;
+        } else if shares < 0.0 {
+            sell_signals[i] = 1;
+        }
+    };
+
+    for (i, time) in timestamps.into_iter().enumerate() {
+        let (Some(time), Some(spot)) = (time, close.get(i).filter(|c| !c.is_nan())) else {
+            continue;
+        };
+        let snapshot = history.snapshot(time);
+
+        if let Some(pos) = position.as_mut() {
+            // Attribute the move since the previous bar to the previous Greeks
+            let value = valuation.position_value(&pos.legs, spot, time, snapshot);
+            let vols = pos
+                .legs
+                .clone()
+                .map(|leg| valuation.leg_volatility(&leg, spot, time, snapshot));
+            let move_ = spot - pos.spot;
+            let elapsed = days_between(pos.time, time);
+            let (mut delta, mut gamma, mut theta, mut vega) = (0.0, 0.0, 0.0, 0.0);
+            for ((leg, (greeks, prev_vol)), vol) in pos.legs.iter().zip(&pos.greeks).zip(vols) {
+                let units = leg.ratio * quantity;
+                delta += units * finite(greeks.delta) * move_;
+                gamma += units * 0.5 * finite(greeks.gamma) * move_ * move_;
+                theta += units * finite(greeks.theta) * elapsed;
+                vega += units * finite(greeks.vega) * (vol - prev_vol) * 100.0;
+            }
+            let option_pnl = (value - pos.value) * quantity;
+            let hedge_move = pos.hedge * move_;
+            delta_pnl[i] = delta;
+            gamma_pnl[i] = gamma;
+            theta_pnl[i] = theta;
+            vega_pnl[i] = vega;
+            residual_pnl[i] = option_pnl - delta - gamma - theta - vega;
+            hedge_pnl[i] = hedge_move;
+            pos.pnl += option_pnl + hedge_move;
+
+            pos.spot = spot;
+            pos.time = time;
+            pos.value = value;
+            let mut cost = 0.0;
+            let dte = days_between(time, pos.legs[0].expiry);
+
+            if dte <= params.exit_days_to_expiry {
+                // Close the straddle at the natural side and flatten the hedge
+                let exit_value = valuation.closing_value(&pos.legs, spot, time, snapshot);
+                let flatten = -pos.hedge;
+                cost += (value - exit_value) * quantity + option_fees + hedge_cost(flatten, spot);
+                hedge_signal(i, flatten);
+                pos.costs += option_fees + hedge_cost(flatten, spot);
+                pos.pnl -= cost;
+
+                strike[i] = pos.legs[0].strike;
+                days_left[i] = dte;
+                option_delta[i] = 0.0;
+                hedge_position[i] = 0.0;
+                net_delta[i] = 0.0;
+                costs[i] = cost;
+                open_pnl[i] = pos.pnl;
+                realized += pos.pnl;
+                equity[i] = realized;
+                exposure[i] = 0.0;
+
+                let (side, sign) = match params.side {
+                    StraddleSide::Long => (TradeSide::Long, 1.0),
+                    StraddleSide::Short => (TradeSide::Short, -1.0),
+                };
+                trades.push(RoundTripTrade {
+                    entry_time: pos.entry_time,
+                    exit_time: time,
+                    side,
+                    quantity,
+                    entry_price: sign * pos.premium,
+                    exit_price: sign * exit_value,
+                    fees: pos.costs,
+                    pnl: pos.pnl,
+                    return_pct: pos.pnl / (pos.premium.abs() * quantity) * 100.0,
+                });
+                position = None;
+                continue;
+            }
+
+            pos.greeks = greeks_at(&valuation, &pos.legs, spot, time, snapshot);
+            cost += rebalance(
+                pos,
+                i,
+                quantity,
+                params.delta_band,
+                &hedge_cost,
+                &mut hedge_signal,
+            );
+            pos.pnl -= cost;
+
+            strike[i] = pos.legs[0].strike;
+            days_left[i] = dte;
+            option_delta[i] = position_delta(pos, quantity);
+            hedge_position[i] = pos.hedge;
+            net_delta[i] = option_delta[i] + pos.hedge;
+            costs[i] = cost;
+            open_pnl[i] = pos.pnl;
+            equity[i] = realized + pos.pnl;
+            exposure[i] = pos.value.abs() * quantity + pos.hedge.abs() * spot;
+            continue;
+        }
+
+        (equity[i], exposure[i]) = (realized, 0.0);
+        let Some(quotes) = snapshot else {
+            continue;
+        };
+        let Some(legs) = select_straddle(quotes, spot, time, params) else {
+            continue;
+        };
+        let premium: f64 = legs.iter().map(|leg| leg.ratio * leg.entry_price).sum();
+        let value = valuation.position_value(&legs, spot, time, snapshot);
+        let mut pos = OpenPosition {
+            entry_time: time,
+            greeks: greeks_at(&valuation, &legs, spot, time, snapshot),
+            legs,
+            premium,
+            hedge: 0.0,
+            spot,
+            time,
+            value,
+            costs: option_fees,
+            pnl: 0.0,
+        };
+        // Paying the ask or receiving the bid costs the distance to the mark
+        let cost = (premium - value) * quantity
+            + option_fees
+            + rebalance(
+                &mut pos,
+                i,
+                quantity,
+                params.delta_band,
+                &hedge_cost,
+                &mut hedge_signal,
+            );
+        pos.pnl = -cost;
+
+        strike[i] = pos.legs[0].strike;
+        days_left[i] = days_between(time, pos.legs[0].expiry);
+        option_delta[i] = position_delta(&pos, quantity);
+        hedge_position[i] = pos.hedge;
+        net_delta[i] = option_delta[i] + pos.hedge;
+        costs[i] = cost;
+        open_pnl[i] = pos.pnl;
+        equity[i] = realized + pos.pnl;
+        exposure[i] = pos.value.abs() * quantity + pos.hedge.abs() * spot;
+        position = Some(pos);
+    }
+
+    let indicator_values = build_indicator_values(
+        underlying,
+        &params.time_column,
+        &params.columns.close,
+        vec![
+            (Series::new("dn_strike".into(), strike), false),
+            (Series::new("dn_days_to_expiry".into(), days_left), false),
+            (Series::new("dn_option_delta".into(), option_delta), false),
+            (
+                Series::new("dn_hedge_position".into(), hedge_position),
+                true,
+            ),
+            (Series::new("dn_net_delta".into(), net_delta), true),
+            (Series::new("dn_delta_pnl".into(), delta_pnl), true),
+            (Series::new("dn_gamma_pnl".into(), gamma_pnl), true),
+            (Series::new("dn_theta_pnl".into(), theta_pnl), true),
+            (Series::new("dn_vega_pnl".into(), vega_pnl), true),
+            (Series::new("dn_residual_pnl".into(), residual_pnl), true),
+            (Series::new("dn_hedge_pnl".into(), hedge_pnl), true),
+            (Series::new("dn_costs".into(), costs), true),
+            (Series::new("dn_pnl".into(), open_pnl), true),
+        ],
+        params.output_columns,
+    )?;
+
+    let [equity_curve, drawdown, exposure] = equity_outputs(equity, exposure);
+    Ok(StrategySignals {
+        buy_signals,
+        sell_signals,
+        indicator_values,
+        trades,
+        equity_curve,
+        drawdown,
+        exposure,
+    })
+}
+
+/// Sum the per-bar attribution columns of a delta-neutral backtest
+///
+/// # Arguments
+///
+/// * `signals` - Output of [`run_strategy`] with the attribution columns
+///   retained (`OutputColumns::All` or `KeyIndicators`)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`PnlAttribution`]; `total` equals
+/// the summed P&L of all closed trades plus the open P&L of a position still
+/// held at the end
+pub fn pnl_attribution(signals: &StrategySignals) -> PolarsResult<PnlAttribution> {
+    let sum = |name: &str| -> PolarsResult<f64> {
+        let values = signals.indicator_values.column(name).map_err(|_| {
+            PolarsError::ComputeError(
+                format!("P&L attribution requires the '{name}' indicator column").into(),
+            )
+        })?;
+        Ok(values
+            .f64()?
+            .into_iter()
+            .flatten()
+            .filter(|v| !v.is_nan())
+            .sum())
+    };
+
+    let mut attribution = PnlAttribution {
+        delta: sum("dn_delta_pnl")?,
+        gamma: sum("dn_gamma_pnl")?,
+        theta: sum("dn_theta_pnl")?,
+        vega: sum("dn_vega_pnl")?,
+        residual: sum("dn_residual_pnl")?,
+        hedge: sum("dn_hedge_pnl")?,
+        costs: sum("dn_costs")?,
+        total: 0.0,
+        hedge_trades: signals.buy_signals.iter().sum::<i32>() as usize
+            + signals.sell_signals.iter().sum::<i32>() as usize,
+    };
+    attribution.total = attribution.delta
+        + attribution.gamma
+        + attribution.theta
+        + attribution.vega
+        + attribution.residual
+        + attribution.hedge
+        - attribution.costs;
+    Ok(attribution)
+}
+
+/// Call and put at the strike nearest `spot` with both quoted, in the expiry window
+fn select_straddle(
+    quotes: &[ChainQuote],
+    spot: f64,
+    time: DateTime<Utc>,
+    params: &StrategyParams,
+) -> Option<[OptionLeg; 2]> {
+    let valuation = Valuation {
+        model: &params.model,
+        risk_free_rate: params.risk_free_rate,
+        dividend_yield: params.dividend_yield,
+    };
+    let expiry = select_expiry(
+        quotes,
+        time,
+        params.min_days_to_expiry,
+        params.max_days_to_expiry,
+    )?;
+    let quote = |strike: f64, is_call: bool| {
+        quotes
+            .iter()
+            .find(|q| q.expiry == expiry && q.strike == strike && q.is_call == is_call)
+    };
+    let (call, put) = quotes
+        .iter()
+        .filter(|q| q.expiry == expiry && q.is_call)
+        .filter_map(|q| Some((q, quote(q.strike, false)?)))
+        .min_by(|a, b| {
+            (a.0.strike - spot)
+                .abs()
+                .total_cmp(&(b.0.strike - spot).abs())
+        })?;
+
+    let ratio = match params.side {
+        StraddleSide::Long => 1.0,
+        StraddleSide::Short => -1.0,
+    };
+    Some([
+        valuation.leg(call, spot, time, ratio),
+        valuation.leg(put, spot, time, ratio),
+    ])
+}
+
+/// Greeks and volatility of each leg at a bar
+fn greeks_at(
+    valuation: &Valuation,
+    legs: &[OptionLeg; 2],
+    spot: f64,
+    time: DateTime<Utc>,
+    snapshot: Option<&[ChainQuote]>,
+) -> [(OptionGreeks, f64); 2] {
+    legs.clone().map(|leg| {
+        let vol = valuation.leg_volatility(&leg, spot, time, snapshot);
+        (valuation.leg_greeks(&leg, spot, time, vol), vol)
+    })
+}
+
+/// Delta of the straddle in units of the underlying
+fn position_delta(pos: &OpenPosition, quantity: f64) -> f64 {
+    pos.legs
+        .iter()
+        .zip(&pos.greeks)
+        .map(|(leg, (greeks, _))| leg.ratio * quantity * finite(greeks.delta))
+        .sum()
+}
+
+/// Bring the net delta back to zero when it leaves the band; returns the cost
+fn rebalance(
+    pos: &mut OpenPosition,
+    bar: usize,
+    quantity: f64,
+    band: f64,
+    hedge_cost: &impl Fn(f64, f64) -> f64,
+    hedge_signal: &mut impl FnMut(usize, f64),
+) -> f64 {
+    let net = position_delta(pos, quantity) + pos.hedge;
+    if net.abs() <= band {
+        return 0.0;
+    }
+    let trade = -net;
+    pos.hedge += trade;
+    hedge_signal(bar, trade);
+    let cost = hedge_cost(trade, pos.spot);
+    pos.costs += cost;
+    cost
+}
+
+/// Greeks are NaN at expiry; they then contribute nothing
+fn finite(value: f64) -> f64 {
+    if value.is_finite() {
+        value
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,499 @@
+//! # Iron Condor Strategy
+//!
+//! Sells an out-of-the-money put and call near a target delta and buys
+//! protective wings a fixed distance further out, collecting a credit that
+//! is kept when the underlying stays between the short strikes.
+//!
+//! A position is opened on the first bar with a chain snapshot offering an
+//! expiry in the configured window, and closed at a profit target (share of
+//! the credit), a stop loss (multiple of the credit), a days-to-expiry
+//! threshold or expiry, whichever comes first.
+
+use crate::indicators::options::pricing::PricingModel;
+use crate::performance::{RoundTripTrade, TradeSide};
+use crate::strategy::options::{
+    chain_quotes, days_between, select_expiry, straddle_expected_move, ChainHistory, ChainQuote,
+    ExitReason, OptionLeg, Valuation,
+};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::column_map::ColumnMap;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Parameters for the iron condor strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyParams {
+    /// Absolute delta targeted for both short strikes (e.g. 0.16)
+    pub short_delta: f64,
+    /// Distance in strike points between each short strike and its wing
+    pub wing_width: f64,
+    /// Shortest days to expiry accepted at entry
+    pub min_days_to_expiry: f64,
+    /// Longest days to expiry accepted at entry
+    pub max_days_to_expiry: f64,
+    /// Close when the profit reaches this percentage of the credit
+    pub profit_target_pct: f64,
+    /// Close when the loss reaches this multiple of the credit
+    pub stop_loss_multiple: f64,
+    /// Close when this many days or fewer remain to expiry
+    pub exit_days_to_expiry: f64,
+    /// Number of condors traded
+    pub contracts: f64,
+    /// Underlying units per contract
+    pub contract_multiplier: f64,
+    /// Commission per contract per leg, charged on entry and exit
+    pub fee_per_contract: f64,
+    /// Continuously compounded risk-free rate as a decimal
+    pub risk_free_rate: f64,
+    /// Continuous dividend yield of the underlying as a decimal
+    pub dividend_yield: f64,
+    /// Model for IV, deltas and re-pricing legs without a quote
+    pub model: PricingModel,
+    /// Column with timestamps in the underlying data and quote times in the chain
+    pub time_column: String,
+    /// Names of the OHLCV columns in the price data
+    pub columns: ColumnMap,
+    /// Column with option prices in the chain
+    pub price_column: String,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            short_delta: 0.16,
+            wing_width: 5.0,
+            min_days_to_expiry: 30.0,
+            max_days_to_expiry: 60.0,
+            profit_target_pct: 50.0,
+            stop_loss_multiple: 2.0,
+            exit_days_to_expiry: 21.0,
+            contracts: 1.0,
+            contract_multiplier: 100.0,
+            fee_per_contract: 0.65,
+            risk_free_rate: 0.02,
+            dividend_yield: 0.0,
+            model: PricingModel::BlackScholes,
+            time_column: "timestamp".to_string(),
+            columns: ColumnMap::default(),
+            price_column: "price".to_string(),
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for StrategyParams {}
+
+/// The four legs of an iron condor
+#[derive(Debug, Clone, PartialEq)]
+pub struct IronCondor {
+    /// Bought put below the short put
+    pub long_put: OptionLeg,
+    /// Sold put near the target delta
+    pub short_put: OptionLeg,
+    /// Sold call near the target delta
+    pub short_call: OptionLeg,
+    /// Bought call above the short call
+    pub long_call: OptionLeg,
+    /// Net premium received per unit of the underlying at entry fills
+    pub credit: f64,
+    /// Move of the underlying to expiry priced by the ATM straddle at entry
+    pub expected_move: f64,
+    /// Probability at entry that the condor held to expiry ends with a
+    /// profit, at the mean IV of its legs
+    pub probability_of_profit: f64,
+}
+
+impl IronCondor {
+    /// Legs ordered by strike: long put, short put, short call, long call
+    pub fn legs(&self) -> [OptionLeg; 4] {
+        [
+            self.long_put.clone(),
+            self.short_put.clone(),
+            self.short_call.clone(),
+            self.long_call.clone(),
+        ]
+    }
+
+    /// Largest loss per unit of the underlying, reached beyond either wing
+    pub fn max_loss(&self) -> f64 {
+        let put_width = self.short_put.strike - self.long_put.strike;
+        let call_width = self.long_call.strike - self.short_call.strike;
+        put_width.max(call_width) - self.credit
+    }
+
+    /// Lower and upper breakeven prices at expiry
+    pub fn breakevens(&self) -> (f64, f64) {
+        (
+            self.short_put.strike - self.credit,
+            self.short_call.strike + self.credit,
+        )
+    }
+}
+
+/// Select iron condor legs from a single chain snapshot
+///
+/// The expiry is the one within the days-to-expiry window closest to the
+/// window's midpoint. Short strikes are the out-of-the-money put and call
+/// whose deltas are closest to `short_delta`; each wing is the strike
+/// nearest to `wing_width` beyond its short strike.
+///
+/// # Arguments
+///
+/// * `chain` - DataFrame with columns `strike`, `expiry`, `option_type` and
+///   the price column named by `params.price_column` and/or `bid` and `ask`
+/// * `underlying_price` - Current price of the underlying
+/// * `valuation_time` - Time the chain was quoted
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the condor, or None when the chain has
+/// no suitable expiry, strikes on both sides or a positive net credit. Sold
+/// legs are priced at the bid and bought legs at the ask when quoted.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use polars::prelude::*;
+/// use rustalib::indicators::options::black_scholes_price;
+/// use rustalib::strategy::options::iron_condor::{select_iron_condor, StrategyParams};
+///
+/// let strikes: Vec<f64> = (80..=120).step_by(5).map(|k| k as f64).collect();
+/// let mut chain_strikes = Vec::new();
+/// let mut types = Vec::new();
+/// let mut prices = Vec::new();
+/// for &k in &strikes {
+///     for call in [true, false] {
+///         chain_strikes.push(k);
+///         types.push(if call { "call" } else { "put" });
+///         prices.push(black_scholes_price(100.0, k, 45.0 / 365.0, 0.02, 0.0, 0.25, call));
+///     }
+/// }
+/// let n = chain_strikes.len();
+/// let chain = df! {
+///     "strike" => chain_strikes,
+///     "expiry" => vec!["2024-02-15"; n],
+///     "option_type" => types,
+///     "price" => prices,
+/// }
+/// .unwrap();
+///
+/// let now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+/// let condor = select_iron_condor(&chain, 100.0, now, &StrategyParams::default())
+///     .unwrap()
+///     .unwrap();
+///
+/// assert_eq!(condor.short_put.strike, 90.0);
+/// assert_eq!(condor.short_call.strike, 110.0);
+/// assert_eq!(condor.long_put.strike, 85.0);
+/// assert_eq!(condor.long_call.strike, 115.0);
+/// assert!(condor.credit > 0.0);
+/// assert!(condor.probability_of_profit > 0.5);
+/// ```
+pub fn select_iron_condor(
+    chain: &DataFrame,
+    underlying_price: f64,
+    valuation_time: DateTime<Utc>,
+    params: &StrategyParams,
+) -> PolarsResult<Option<IronCondor>> {
+    let quotes = chain_quotes(chain, &params.price_column)?;
+    Ok(build_condor(
+        &quotes,
+        underlying_price,
+        valuation_time,
+        params,
+    ))
+}
+
+/// Backtest a short iron condor
+///
+/// # Arguments
+///
+/// * `underlying` - Underlying bars with the time column and the close column
+/// * `chain` - Options chain history: the time column holding each quote's
+///   snapshot time, plus `strike`, `expiry`, `option_type` and the price
+///   column and/or `bid` and `ask` (see the [module docs](super)).
+///   Snapshots are matched to bars with the same timestamp.
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing [`StrategySignals`] where a sell signal
+/// marks opening a condor and a buy signal closing it. Each trade is reported
+/// as a short round trip in units of the underlying (contracts × multiplier)
+/// with the credit as entry price, the debit paid to close as exit price and
+/// the return measured against the maximum loss. The indicator columns are the
+/// four leg strikes, "condor_credit", "condor_expected_move" and
+/// "condor_pop" (probability of profit) from entry, "condor_days_to_expiry",
+/// "condor_pnl" (open P&L in currency before fees) and "condor_exit_reason".
+pub fn run_strategy(
+    underlying: &DataFrame,
+    chain: &DataFrame,
+    params: &StrategyParams,
+) -> PolarsResult<StrategySignals> {
+    for col in [params.time_column.as_str(), params.columns.close.as_str()] {
+        if !underlying.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Iron condor requires '{col}' column in the underlying data").into(),
+            ));
+        }
+    }
+    if params.wing_width <= 0.0 || params.contracts <= 0.0 {
+        return Err(PolarsError::ComputeError(
+            "Iron condor wing width and contracts must be positive".into(),
+        ));
+    }
+
+    let history = ChainHistory::new(chain, &params.time_column, &params.price_column)?;
+    let valuation = Valuation {
+        model: &params.model,
+        risk_free_rate: params.risk_free_rate,
+        dividend_yield: params.dividend_yield,
+    };
+    let timestamps = parse_timestamp_column(underlying, &params.time_column)?;
+    let close = underlying
+        .column(&params.columns.close)?
+        .cast(&DataType::Float64)?;
+    let close = close.f64()?;
+
+    let n = underlying.height();
+    let quantity = params.contracts * params.contract_multiplier;
+    let leg_fees = 4.0 * params.contracts * params.fee_per_contract;
+
+    let mut buy_signals = vec![0; n];
+    let mut sell_signals = vec![0; n];
+    let mut strikes = [
+        vec![f64::NAN; n],
+        vec![f64::NAN; n],
+        vec![f64::NAN; n],
+        vec![f64::NAN; n],
+    ];
+    let mut credit = vec![f64::NAN; n];
+    let mut expected_move = vec![f64::NAN; n];
+    let mut pop = vec![f64::NAN; n];
+    let mut days_left = vec![f64::NAN; n];
+    let mut open_pnl = vec![f64::NAN; n];
+    let mut exit_reason: Vec<Option<&str>> = vec![None; n];
+    let mut equity = vec![f64::NAN; n];
+    let mut exposure = vec![f64::NAN; n];
+    let mut trades = Vec::new();
+    let mut realized = 0.0;
+    let mut position: Option<(DateTime<Utc>, IronCondor)> = None;
+
+    for (i, time) in timestamps.into_iter().enumerate() {
+        let (Some(time), Some(spot)) = (time, close.get(i).filter(|c| !c.is_nan())) else {
+            continue;
+        };
+        let snapshot = history.snapshot(time);
+
+        if let Some((entry_time, condor)) = &position {
+            let legs = condor.legs();
+            let value = valuation.position_value(&legs, spot, time, snapshot);
+            let pnl = condor.credit + value;
+            let dte = days_between(time, condor.short_put.expiry);
+
+            for (k, leg) in legs.iter().enumerate() {
+                strikes[k][i] = leg.strike;
+            }
+            credit[i] = condor.credit;
+            expected_move[i] = condor.expected_move;
+            pop[i] = condor.probability_of_profit;
+            days_left[i] = dte;
+            open_pnl[i] = pnl * quantity;
+            equity[i] = realized + open_pnl[i] - leg_fees;
+            exposure[i] = value.abs() * quantity;
+
+            let reason = ExitReason::check(
+                pnl,
+                condor.credit * params.profit_target_pct / 100.0,
+                condor.credit * params.stop_loss_multiple,
+                dte,
+                params.exit_days_to_expiry,
+            );
+
+            if let Some(reason) = reason {
+                let debit = -valuation.closing_value(&legs, spot, time, snapshot);
+                let fees = 2.0 * leg_fees;
+                let net = (condor.credit - debit) * quantity - fees;
+                realized += net;
+                equity[i] = realized;
+                exposure[i] = 0.0;
+                buy_signals[i] = 1;
+                exit_reason[i] = Some(reason.as_str());
+                trades.push(RoundTripTrade {
+                    entry_time: *entry_time,
+                    exit_time: time,
+                    side: TradeSide::Short,
+                    quantity,
+                    entry_price: condor.credit,
+                    exit_price: debit,
+                    fees,
+                    pnl: net,
+                    return_pct: net / (condor.max_loss() * quantity) * 100.0,
+                });
+                position = None;
+            }
+            continue;
+        }
+
+        (equity[i], exposure[i]) = (realized, 0.0);
+        let Some(quotes) = snapshot else {
+            continue;
+        };
+        if let Some(condor) = build_condor(quotes, spot, time, params) {
+            sell_signals[i] = 1;
+            for (k, leg) in condor.legs().iter().enumerate() {
+                strikes[k][i] = leg.strike;
+            }
+            credit[i] = condor.credit;
+            expected_move[i] = condor.expected_move;
+            pop[i] = condor.probability_of_profit;
+            days_left[i] = days_between(time, condor.short_put.expiry);
+            open_pnl[i] = 0.0;
+            equity[i] = realized - leg_fees;
+            exposure[i] = condor.credit * quantity;
+            position = Some((time, condor));
+        }
+    }
+
+    let [long_put, short_put, short_call, long_call] = strikes;
+    let indicator_values = build_indicator_values(
+        underlying,
+        &params.time_column,
+        &params.columns.close,
+        vec![
+            (Series::new("condor_long_put".into(), long_put), false),
+            (Series::new("condor_short_put".into(), short_put), true),
+            (Series::new("condor_short_call".into(), short_call), true),
+            (Series::new("condor_long_call".into(), long_call), false),
+            (Series::new("condor_credit".into(), credit), false),
+            (
+                Series::new("condor_expected_move".into(), expected_move),
+                false,
+            ),
+            (Series::new("condor_pop".into(), pop), false),
+            (
+                Series::new("condor_days_to_expiry".into(), days_left),
+                false,
+            ),
+            (Series::new("condor_pnl".into(), open_pnl), true),
+            (Series::new("condor_exit_reason".into(), exit_reason), true),
+        ],
+        params.output_columns,
+    )?;
+
+    let [equity_curve, drawdown, exposure] = equity_outputs(equity, exposure);
+    Ok(StrategySignals {
+        buy_signals,
+        sell_signals,
+        indicator_values,
+        trades,
+        equity_curve,
+        drawdown,
+        exposure,
+    })
+}
+
+/// Pick the four legs from one snapshot
+fn build_condor(
+    quotes: &[ChainQuote],
+    spot: f64,
+    time: DateTime<Utc>,
+    params: &StrategyParams,
+) -> Option<IronCondor> {
+    let valuation = Valuation {
+        model: &params.model,
+        risk_free_rate: params.risk_free_rate,
+        dividend_yield: params.dividend_yield,
+    };
+    let expiry = select_expiry(
+        quotes,
+        time,
+        params.min_days_to_expiry,
+        params.max_days_to_expiry,
+    )?;
+    let side = |is_call: bool| -> Vec<&ChainQuote> {
+        quotes
+            .iter()
+            .filter(|q| q.expiry == expiry && q.is_call == is_call)
+            .collect()
+    };
+    let (calls, puts) = (side(true), side(false));
+
+    let short_by_delta = |candidates: &[&ChainQuote]| -> Option<OptionLeg> {
+        candidates
+            .iter()
+            .filter(|q| {
+                if q.is_call {
+                    q.strike > spot
+                } else {
+                    q.strike < spot
+                }
+            })
+            .map(|q| valuation.leg(q, spot, time, -1.0))
+            .filter(|leg| !leg.delta.is_nan())
+            .min_by(|a, b| {
+                let da = (a.delta.abs() - params.short_delta).abs();
+                let db = (b.delta.abs() - params.short_delta).abs();
+                da.total_cmp(&db)
+            })
+    };
+    let wing = |candidates: &[&ChainQuote], short: &OptionLeg| -> Option<OptionLeg> {
+        let target = if short.is_call {
+            short.strike + params.wing_width
+        } else {
+            short.strike - params.wing_width
+        };
+        candidates
+            .iter()
+            .filter(|q| {
+                if short.is_call {
+                    q.strike > short.strike
+                } else {
+                    q.strike < short.strike
+                }
+            })
+            .min_by(|a, b| {
+                (a.strike - target)
+                    .abs()
+                    .total_cmp(&(b.strike - target).abs())
+            })
+            .map(|q| valuation.leg(q, spot, time, 1.0))
+    };
+
+    let short_put = short_by_delta(&puts)?;
+    let short_call = short_by_delta(&calls)?;
+    let long_put = wing(&puts, &short_put)?;
+    let long_call = wing(&calls, &short_call)?;
+
+    let credit = short_put.entry_price + short_call.entry_price
+        - long_put.entry_price
+        - long_call.entry_price;
+    if credit <= 0.0 {
+        return None;
+    }
+
+    let legs = [
+        long_put.clone(),
+        short_put.clone(),
+        short_call.clone(),
+        long_call.clone(),
+    ];
+    Some(IronCondor {
+        expected_move: straddle_expected_move(quotes, expiry, spot),
+        probability_of_profit: valuation.probability_of_profit(&legs, spot, time),
+        long_put,
+        short_put,
+        short_call,
+        long_call,
+        credit,
+    })
+}
@@ -0,0 +1,460 @@
+//! # Options Strategies
+//!
+//! Multi-leg options strategies backtested against an underlying price
+//! series and a history of options chain snapshots.
+//!
+//! Legs are selected from the snapshot quoted at the entry bar. While a
+//! position is open each leg is marked at its chain quote when the bar has a
+//! snapshot containing the contract, and otherwise re-priced with the
+//! configured [`PricingModel`] at the leg's entry implied volatility.
+//!
+//! Chains may carry "bid" and "ask" columns. Quotes are then marked at the
+//! price column (or the mid when the chain has no price column), while
+//! entries and exits fill at the natural side: bought legs at the ask and
+//! sold legs at the bid.
+//!
+//! - [`iron_condor`](iron_condor/index.html): Short iron condor with delta-targeted short strikes
+//! - [`vertical_spreads`](vertical_spreads/index.html): Bull/bear call and put spreads selected by delta
+//! - [`delta_neutral`](delta_neutral/index.html): Delta-hedged straddle with Greek P&L attribution
+//! - [`wheel`](wheel/index.html): Cash-secured puts and covered calls with assignment
+
+pub mod delta_neutral;
+pub mod iron_condor;
+pub mod vertical_spreads;
+pub mod wheel;
+
+use crate::indicators::options::chain::parse_option_type;
+use crate::indicators::options::pricing::{OptionGreeks, OptionSpec, PricingModel};
+use crate::indicators::options::probability::{
+    expected_move_from_straddle, probability_of_profit, PayoffLeg,
+};
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use std::collections::BTreeMap;
+
+/// One option contract of a multi-leg position
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionLeg {
+    /// Strike price
+    pub strike: f64,
+    /// Expiration time
+    pub expiry: DateTime<Utc>,
+    /// Whether the contract is a call (true) or put (false)
+    pub is_call: bool,
+    /// Contracts per position unit: positive when bought, negative when sold
+    pub ratio: f64,
+    /// Price paid or received per contract at entry
+    pub entry_price: f64,
+    /// Implied volatility at entry, used to re-price the leg without a quote
+    pub implied_volatility: f64,
+    /// Delta at entry
+    pub delta: f64,
+}
+
+/// Market inputs shared by every leg valuation
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Valuation<'a> {
+    pub model: &'a PricingModel,
+    pub risk_free_rate: f64,
+    pub dividend_yield: f64,
+}
+
+impl Valuation<'_> {
+    fn spec(
+        &self,
+        spot: f64,
+        strike: f64,
+        expiry: DateTime<Utc>,
+        time: DateTime<Utc>,
+        is_call: bool,
+    ) -> OptionSpec {
+        OptionSpec {
+            spot,
+            strike,
+            time_to_expiry: years_between(time, expiry),
+            risk_free_rate: self.risk_free_rate,
+            dividend_yield: self.dividend_yield,
+            is_call,
+        }
+    }
+
+    /// Build a leg from a chain quote, solving for its IV and delta at the
+    /// mark and filling it at the natural side of the quote
+    pub(crate) fn leg(
+        &self,
+        quote: &ChainQuote,
+        spot: f64,
+        time: DateTime<Utc>,
+        ratio: f64,
+    ) -> OptionLeg {
+        let spec = self.spec(spot, quote.strike, quote.expiry, time, quote.is_call);
+        let implied_volatility = self.model.implied_volatility(&spec, quote.price);
+        let delta = self.model.greeks(&spec, implied_volatility).delta;
+        OptionLeg {
+            strike: quote.strike,
+            expiry: quote.expiry,
+            is_call: quote.is_call,
+            ratio,
+            entry_price: quote.fill_price(ratio > 0.0),
+            implied_volatility,
+            delta,
+        }
+    }
+
+    /// Price of one contract of a leg at a bar
+    ///
+    /// Uses the chain quote when `snapshot` contains the contract (its mark,
+    /// or the natural side when `closing`), otherwise the model at the leg's
+    /// entry IV (intrinsic value at or after expiry).
+    pub(crate) fn leg_price(
+        &self,
+        leg: &OptionLeg,
+        spot: f64,
+        time: DateTime<Utc>,
+        snapshot: Option<&[ChainQuote]>,
+        closing: bool,
+    ) -> f64 {
+        let quoted = snapshot.and_then(|quotes| {
+            quotes.iter().find(|q| {
+                q.expiry == leg.expiry && q.is_call == leg.is_call && q.strike == leg.strike
+            })
+        });
+        match quoted {
+            Some(quote) if closing => quote.fill_price(leg.ratio < 0.0),
+            Some(quote) => quote.price,
+            None => {
+                let spec = self.spec(spot, leg.strike, leg.expiry, time, leg.is_call);
+                self.model.price(&spec, leg.implied_volatility)
+            }
+        }
+    }
+
+    /// Volatility of a leg at a bar: implied by its chain quote when
+    /// `snapshot` contains the contract, otherwise its entry IV
+    pub(crate) fn leg_volatility(
+        &self,
+        leg: &OptionLeg,
+        spot: f64,
+        time: DateTime<Utc>,
+        snapshot: Option<&[ChainQuote]>,
+    ) -> f64 {
+        let quoted = snapshot.and_then(|quotes| {
+            quotes.iter().find(|q| {
+                q.expiry == leg.expiry && q.is_call == leg.is_call && q.strike == leg.strike
+            })
+        });
+        let spec = self.spec(spot, leg.strike, leg.expiry, time, leg.is_call);
+        quoted
+            .map(|quote| self.model.implied_volatility(&spec, quote.price))
+            .filter(|v| !v.is_nan())
+            .unwrap_or(leg.implied_volatility)
+    }
+
+    /// Greeks of one contract of a leg at a bar and volatility
+    pub(crate) fn leg_greeks(
+        &self,
+        leg: &OptionLeg,
+        spot: f64,
+        time: DateTime<Utc>,
+        volatility: f64,
+    ) -> OptionGreeks {
+        let spec = self.spec(spot, leg.strike, leg.expiry, time, leg.is_call);
+        self.model.greeks(&spec, volatility)
+    }
+
+    /// Marked value of a position unit: leg marks weighted by their ratios
+    pub(crate) fn position_value(
+        &self,
+        legs: &[OptionLeg],
+        spot: f64,
+        time: DateTime<Utc>,
+        snapshot: Option<&[ChainQuote]>,
+    ) -> f64 {
+        legs.iter()
+            .map(|leg| leg.ratio * self.leg_price(leg, spot, time, snapshot, false))
+            .sum()
+    }
+
+    /// Value of a position unit if it were closed at the natural side now
+    pub(crate) fn closing_value(
+        &self,
+        legs: &[OptionLeg],
+        spot: f64,
+        time: DateTime<Utc>,
+        snapshot: Option<&[ChainQuote]>,
+    ) -> f64 {
+        legs.iter()
+            .map(|leg| leg.ratio * self.leg_price(leg, spot, time, snapshot, true))
+            .sum()
+    }
+
+    /// Probability that the legs held to expiry end with a profit at their
+    /// entry fills, using the mean entry IV of the legs
+    pub(crate) fn probability_of_profit(
+        &self,
+        legs: &[OptionLeg],
+        spot: f64,
+        time: DateTime<Utc>,
+    ) -> f64 {
+        let Some(expiry) = legs.first().map(|leg| leg.expiry) else {
+            return f64::NAN;
+        };
+        let vols: Vec<f64> = legs
+            .iter()
+            .map(|leg| leg.implied_volatility)
+            .filter(|v| !v.is_nan())
+            .collect();
+        if vols.is_empty() {
+            return f64::NAN;
+        }
+        let volatility = vols.iter().sum::<f64>() / vols.len() as f64;
+        let payoff: Vec<PayoffLeg> = legs
+            .iter()
+            .map(|leg| PayoffLeg {
+                strike: leg.strike,
+                is_call: leg.is_call,
+                quantity: leg.ratio,
+                premium: leg.entry_price,
+            })
+            .collect();
+        probability_of_profit(
+            &payoff,
+            spot,
+            years_between(time, expiry),
+            self.risk_free_rate,
+            self.dividend_yield,
+            volatility,
+        )
+    }
+}
+
+/// Expected move priced by the straddle at the strike nearest `spot` that has
+/// both a call and a put quoted for `expiry`; NaN when there is none
+pub(crate) fn straddle_expected_move(
+    quotes: &[ChainQuote],
+    expiry: DateTime<Utc>,
+    spot: f64,
+) -> f64 {
+    let mark = |strike: f64, is_call: bool| {
+        quotes
+            .iter()
+            .find(|q| q.expiry == expiry && q.strike == strike && q.is_call == is_call)
+            .map(|q| q.price)
+    };
+    quotes
+        .iter()
+        .filter(|q| q.expiry == expiry && q.is_call)
+        .filter_map(|q| Some((q.strike, mark(q.strike, true)?, mark(q.strike, false)?)))
+        .min_by(|a, b| (a.0 - spot).abs().total_cmp(&(b.0 - spot).abs()))
+        .map_or(f64::NAN, |(_, call, put)| {
+            expected_move_from_straddle(call, put)
+        })
+}
+
+/// Why an options position was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExitReason {
+    ProfitTarget,
+    StopLoss,
+    DaysToExpiry,
+    Expiry,
+}
+
+impl ExitReason {
+    /// Check the exit rules in priority order: expiry, profit target, stop
+    /// loss, days to expiry. `pnl`, `profit_target` and `loss_limit` are per
+    /// unit of the underlying; `loss_limit` is a positive amount.
+    pub(crate) fn check(
+        pnl: f64,
+        profit_target: f64,
+        loss_limit: f64,
+        days_to_expiry: f64,
+        exit_days_to_expiry: f64,
+    ) -> Option<Self> {
+        if days_to_expiry <= 0.0 {
+            Some(ExitReason::Expiry)
+        } else if pnl >= profit_target {
+            Some(ExitReason::ProfitTarget)
+        } else if -pnl >= loss_limit {
+            Some(ExitReason::StopLoss)
+        } else if days_to_expiry <= exit_days_to_expiry {
+            Some(ExitReason::DaysToExpiry)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            ExitReason::ProfitTarget => "profit_target",
+            ExitReason::StopLoss => "stop_loss",
+            ExitReason::DaysToExpiry => "days_to_expiry",
+            ExitReason::Expiry => "expiry",
+        }
+    }
+}
+
+/// A single quoted contract
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChainQuote {
+    pub strike: f64,
+    pub expiry: DateTime<Utc>,
+    pub is_call: bool,
+    /// Mark price
+    pub price: f64,
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
+}
+
+impl ChainQuote {
+    /// Price at which a buy (at the ask) or sell (at the bid) fills, falling
+    /// back to the mark when that side is not quoted
+    pub(crate) fn fill_price(&self, buying: bool) -> f64 {
+        let side = if buying { self.ask } else { self.bid };
+        side.unwrap_or(self.price)
+    }
+}
+
+/// Options chain snapshots keyed by quote time
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChainHistory {
+    snapshots: BTreeMap<DateTime<Utc>, Vec<ChainQuote>>,
+}
+
+impl ChainHistory {
+    /// Group a chain DataFrame into snapshots by `time_column`
+    pub(crate) fn new(
+        chain: &DataFrame,
+        time_column: &str,
+        price_column: &str,
+    ) -> PolarsResult<Self> {
+        if !chain.schema().contains(time_column) {
+            return Err(PolarsError::ComputeError(
+                format!("Options chain requires '{time_column}' column").into(),
+            ));
+        }
+        let times = parse_timestamp_column(chain, time_column)?;
+        let quotes = parse_quotes(chain, price_column)?;
+
+        let mut snapshots: BTreeMap<DateTime<Utc>, Vec<ChainQuote>> = BTreeMap::new();
+        for (time, quote) in times.into_iter().zip(quotes) {
+            if let (Some(time), Some(quote)) = (time, quote) {
+                snapshots.entry(time).or_default().push(quote);
+            }
+        }
+
+        Ok(Self { snapshots })
+    }
+
+    /// Quotes observed at exactly `time`
+    pub(crate) fn snapshot(&self, time: DateTime<Utc>) -> Option<&[ChainQuote]> {
+        self.snapshots.get(&time).map(Vec::as_slice)
+    }
+}
+
+/// Valid quotes of a single chain snapshot
+pub(crate) fn chain_quotes(chain: &DataFrame, price_column: &str) -> PolarsResult<Vec<ChainQuote>> {
+    Ok(parse_quotes(chain, price_column)?
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+/// One quote per row; None for rows with a missing strike, expiry, type or
+/// mark, or a non-positive mark
+///
+/// The mark is `price_column` when present, otherwise the bid/ask mid.
+fn parse_quotes(chain: &DataFrame, price_column: &str) -> PolarsResult<Vec<Option<ChainQuote>>> {
+    let schema = chain.schema();
+    let has_bid_ask = schema.contains("bid") && schema.contains("ask");
+    for col in ["strike", "expiry", "option_type"] {
+        if !schema.contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Options chain requires '{col}' column").into(),
+            ));
+        }
+    }
+    if !schema.contains(price_column) && !has_bid_ask {
+        return Err(PolarsError::ComputeError(
+            format!("Options chain requires '{price_column}' column or 'bid' and 'ask' columns")
+                .into(),
+        ));
+    }
+
+    let f64_column = |name: &str| -> PolarsResult<Option<Float64Chunked>> {
+        if schema.contains(name) {
+            Ok(Some(
+                chain.column(name)?.cast(&DataType::Float64)?.f64()?.clone(),
+            ))
+        } else {
+            Ok(None)
+        }
+    };
+    let expiries = parse_timestamp_column(chain, "expiry")?;
+    let strikes = chain.column("strike")?.cast(&DataType::Float64)?;
+    let strikes = strikes.f64()?;
+    let types = chain.column("option_type")?.str()?;
+    let prices = f64_column(price_column)?;
+    let (bids, asks) = if has_bid_ask {
+        (f64_column("bid")?, f64_column("ask")?)
+    } else {
+        (None, None)
+    };
+    let side = |values: &Option<Float64Chunked>, i: usize| {
+        values.as_ref().and_then(|v| v.get(i)).filter(|v| *v > 0.0)
+    };
+
+    Ok((0..chain.height())
+        .map(|i| {
+            let strike = strikes.get(i).filter(|k| !k.is_nan())?;
+            let (bid, ask) = (side(&bids, i), side(&asks, i));
+            let mid = bid.zip(ask).map(|(b, a)| (b + a) / 2.0);
+            let price = match &prices {
+                Some(prices) => prices.get(i),
+                None => mid,
+            }
+            .filter(|p| *p > 0.0)?;
+            Some(ChainQuote {
+                strike,
+                expiry: expiries[i]?,
+                is_call: types.get(i).and_then(parse_option_type)?,
+                price,
+                bid,
+                ask,
+            })
+        })
+        .collect())
+}
+
+/// Expiry whose days to expiry lie in `[min_days, max_days]` and are closest to their midpoint
+pub(crate) fn select_expiry(
+    quotes: &[ChainQuote],
+    time: DateTime<Utc>,
+    min_days: f64,
+    max_days: f64,
+) -> Option<DateTime<Utc>> {
+    let target = (min_days + max_days) / 2.0;
+    quotes
+        .iter()
+        .map(|q| q.expiry)
+        .filter(|&expiry| {
+            let days = days_between(time, expiry);
+            days >= min_days && days <= max_days
+        })
+        .min_by(|&a, &b| {
+            let da = (days_between(time, a) - target).abs();
+            let db = (days_between(time, b) - target).abs();
+            da.total_cmp(&db)
+        })
+}
+
+/// Calendar days from `from` to `to`
+pub(crate) fn days_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_seconds() as f64 / 86_400.0
+}
+
+/// Years from `from` to `to` on a 365-day calendar
+pub(crate) fn years_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    days_between(from, to) / 365.0
+}
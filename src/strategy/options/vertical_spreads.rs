@@ -0,0 +1,580 @@
+//! # Vertical Spreads Strategy
+//!
+//! Trades a two-leg vertical spread: one option near a target delta and a
+//! second option of the same type and expiry a fixed strike distance further
+//! out of the money.
+//!
+//! | Spread    | Near-the-money leg | Further OTM leg | Premium |
+//! |-----------|--------------------|-----------------|---------|
+//! | Bull call | long call          | short call      | debit   |
+//! | Bear put  | long put           | short put       | debit   |
+//! | Bear call | short call         | long call       | credit  |
+//! | Bull put  | short put          | long put        | credit  |
+//!
+//! Strikes are selected by delta from the chain snapshot at the entry bar,
+//! the entry premium is filled at the chain's bid/ask (see the
+//! [module docs](super)), and the position is marked to market on every bar.
+//! It is closed at a profit target (share of the maximum profit), a stop loss
+//! (share of the maximum loss), a days-to-expiry threshold or expiry,
+//! whichever comes first.
+
+use crate::indicators::options::pricing::PricingModel;
+use crate::performance::{RoundTripTrade, TradeSide};
+use crate::strategy::options::{
+    chain_quotes, days_between, select_expiry, straddle_expected_move, ChainHistory, ChainQuote,
+    ExitReason, OptionLeg, Valuation,
+};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::column_map::ColumnMap;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Direction and option type of a vertical spread
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpreadType {
+    /// Long lower-strike call, short higher-strike call (debit)
+    BullCall,
+    /// Short lower-strike call, long higher-strike call (credit)
+    BearCall,
+    /// Short higher-strike put, long lower-strike put (credit)
+    BullPut,
+    /// Long higher-strike put, short lower-strike put (debit)
+    BearPut,
+}
+
+impl SpreadType {
+    /// Whether the spread is built from calls
+    pub fn is_call(&self) -> bool {
+        matches!(self, SpreadType::BullCall | SpreadType::BearCall)
+    }
+
+    /// Whether opening the spread receives a net credit
+    pub fn is_credit(&self) -> bool {
+        matches!(self, SpreadType::BearCall | SpreadType::BullPut)
+    }
+}
+
+/// Parameters for the vertical spreads strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyParams {
+    /// Spread to trade
+    pub spread_type: SpreadType,
+    /// Absolute delta targeted for the near-the-money leg (e.g. 0.30)
+    pub target_delta: f64,
+    /// Distance in strike points between the two legs
+    pub strike_width: f64,
+    /// Shortest days to expiry accepted at entry
+    pub min_days_to_expiry: f64,
+    /// Longest days to expiry accepted at entry
+    pub max_days_to_expiry: f64,
+    /// Close when the profit reaches this percentage of the maximum profit
+    pub profit_target_pct: f64,
+    /// Close when the loss reaches this percentage of the maximum loss
+    pub stop_loss_pct: f64,
+    /// Close when this many days or fewer remain to expiry
+    pub exit_days_to_expiry: f64,
+    /// Number of spreads traded
+    pub contracts: f64,
+    /// Underlying units per contract
+    pub contract_multiplier: f64,
+    /// Commission per contract per leg, charged on entry and exit
+    pub fee_per_contract: f64,
+    /// Continuously compounded risk-free rate as a decimal
+    pub risk_free_rate: f64,
+    /// Continuous dividend yield of the underlying as a decimal
+    pub dividend_yield: f64,
+    /// Model for IV, deltas and re-pricing legs without a quote
+    pub model: PricingModel,
+    /// Column with timestamps in the underlying data and quote times in the chain
+    pub time_column: String,
+    /// Names of the OHLCV columns in the price data
+    pub columns: ColumnMap,
+    /// Column with option mark prices in the chain
+    pub price_column: String,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            spread_type: SpreadType::BullPut,
+            target_delta: 0.30,
+            strike_width: 5.0,
+            min_days_to_expiry: 30.0,
+            max_days_to_expiry: 60.0,
+            profit_target_pct: 50.0,
+            stop_loss_pct: 50.0,
+            exit_days_to_expiry: 7.0,
+            contracts: 1.0,
+            contract_multiplier: 100.0,
+            fee_per_contract: 0.65,
+            risk_free_rate: 0.02,
+            dividend_yield: 0.0,
+            model: PricingModel::BlackScholes,
+            time_column: "timestamp".to_string(),
+            columns: ColumnMap::default(),
+            price_column: "price".to_string(),
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for StrategyParams {}
+
+/// The two legs of a vertical spread
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerticalSpread {
+    /// Spread direction and option type
+    pub spread_type: SpreadType,
+    /// Bought option
+    pub long_leg: OptionLeg,
+    /// Sold option
+    pub short_leg: OptionLeg,
+    /// Net premium per unit of the underlying at entry fills: the debit paid
+    /// for debit spreads, the credit received for credit spreads
+    pub premium: f64,
+    /// Move of the underlying to expiry priced by the ATM straddle at entry
+    pub expected_move: f64,
+    /// Probability at entry that the spread held to expiry ends with a
+    /// profit, at the mean IV of its legs
+    pub probability_of_profit: f64,
+}
+
+impl VerticalSpread {
+    /// Legs in position order: long leg, short leg
+    pub fn legs(&self) -> [OptionLeg; 2] {
+        [self.long_leg.clone(), self.short_leg.clone()]
+    }
+
+    /// Distance between the strikes
+    pub fn width(&self) -> f64 {
+        (self.long_leg.strike - self.short_leg.strike).abs()
+    }
+
+    /// Largest profit per unit of the underlying at expiry
+    pub fn max_profit(&self) -> f64 {
+        if self.spread_type.is_credit() {
+            self.premium
+        } else {
+            self.width() - self.premium
+        }
+    }
+
+    /// Largest loss per unit of the underlying at expiry
+    pub fn max_loss(&self) -> f64 {
+        if self.spread_type.is_credit() {
+            self.width() - self.premium
+        } else {
+            self.premium
+        }
+    }
+
+    /// Underlying price at expiry where the spread breaks even
+    pub fn breakeven(&self) -> f64 {
+        let anchor = if self.spread_type.is_credit() {
+            &self.short_leg
+        } else {
+            &self.long_leg
+        };
+        if anchor.is_call {
+            anchor.strike + self.premium
+        } else {
+            anchor.strike - self.premium
+        }
+    }
+
+    /// Value of the position per unit of the underlying at entry fills
+    /// (negative for credit spreads)
+    fn entry_value(&self) -> f64 {
+        if self.spread_type.is_credit() {
+            -self.premium
+        } else {
+            self.premium
+        }
+    }
+}
+
+/// Select a vertical spread from a single chain snapshot
+///
+/// The expiry is the one within the days-to-expiry window closest to the
+/// window's midpoint. The near-the-money leg is the option of the spread's
+/// type whose delta is closest to `target_delta`; the other leg is the strike
+/// nearest to `strike_width` further out of the money.
+///
+/// # Arguments
+///
+/// * `chain` - DataFrame with columns `strike`, `expiry`, `option_type` and
+///   the price column named by `params.price_column` and/or `bid` and `ask`
+/// * `underlying_price` - Current price of the underlying
+/// * `valuation_time` - Time the chain was quoted
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the spread, or None when the chain has
+/// no suitable expiry or strikes, or the net premium is not between zero and
+/// the strike width. Sold legs are priced at the bid and bought legs at the
+/// ask when quoted.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use polars::prelude::*;
+/// use rustalib::indicators::options::black_scholes_price;
+/// use rustalib::strategy::options::vertical_spreads::{
+///     select_vertical_spread, SpreadType, StrategyParams,
+/// };
+///
+/// let mut strikes = Vec::new();
+/// let mut bids = Vec::new();
+/// let mut asks = Vec::new();
+/// for k in (80..=120).step_by(5) {
+///     let mid = black_scholes_price(100.0, k as f64, 45.0 / 365.0, 0.02, 0.0, 0.25, false);
+///     strikes.push(k as f64);
+///     bids.push(mid - 0.05);
+///     asks.push(mid + 0.05);
+/// }
+/// let n = strikes.len();
+/// let chain = df! {
+///     "strike" => strikes,
+///     "expiry" => vec!["2024-02-15"; n],
+///     "option_type" => vec!["put"; n],
+///     "bid" => bids,
+///     "ask" => asks,
+/// }
+/// .unwrap();
+///
+/// let now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+/// let params = StrategyParams {
+///     spread_type: SpreadType::BullPut,
+///     ..Default::default()
+/// };
+/// let spread = select_vertical_spread(&chain, 100.0, now, &params).unwrap().unwrap();
+///
+/// assert_eq!(spread.short_leg.strike, 95.0);
+/// assert_eq!(spread.long_leg.strike, 90.0);
+/// // Credit received at the natural fills: short bid minus long ask
+/// let natural = spread.short_leg.entry_price - spread.long_leg.entry_price;
+/// assert!((spread.premium - natural).abs() < 1e-12);
+/// assert!(spread.max_loss() > 0.0 && spread.max_profit() > 0.0);
+/// // Only puts are quoted, so there is no straddle to price the expected move
+/// assert!(spread.expected_move.is_nan());
+/// assert!(spread.probability_of_profit > 0.5);
+/// ```
+pub fn select_vertical_spread(
+    chain: &DataFrame,
+    underlying_price: f64,
+    valuation_time: DateTime<Utc>,
+    params: &StrategyParams,
+) -> PolarsResult<Option<VerticalSpread>> {
+    let quotes = chain_quotes(chain, &params.price_column)?;
+    Ok(build_spread(
+        &quotes,
+        underlying_price,
+        valuation_time,
+        params,
+    ))
+}
+
+/// Backtest a vertical spread against an options chain history
+///
+/// # Arguments
+///
+/// * `underlying` - Underlying bars with the time column and the close column
+/// * `chain` - Options chain history: the time column holding each quote's
+///   snapshot time, plus `strike`, `expiry`, `option_type` and the price
+///   column and/or `bid` and `ask` (see the [module docs](super)).
+///   Snapshots are matched to bars with the same timestamp.
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing [`StrategySignals`]. Debit spreads open
+/// on a buy signal and close on a sell signal and are reported as long round
+/// trips; credit spreads open on a sell signal, close on a buy signal and are
+/// reported as short round trips. Quantities are in units of the underlying
+/// (contracts × multiplier), prices are the net premium per unit at entry and
+/// exit fills, and the return is measured against the maximum loss. The
+/// indicator columns are "spread_long_strike", "spread_short_strike",
+/// "spread_entry_premium", "spread_expected_move" and "spread_pop"
+/// (probability of profit) from entry, "spread_mark" (marked value of the
+/// spread per unit, negative for credit spreads), "spread_days_to_expiry",
+/// "spread_pnl" (open P&L in currency before fees) and "spread_exit_reason".
+pub fn run_strategy(
+    underlying: &DataFrame,
+    chain: &DataFrame,
+    params: &StrategyParams,
+) -> PolarsResult<StrategySignals> {
+    for col in [params.time_column.as_str(), params.columns.close.as_str()] {
+        if !underlying.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Vertical spread requires '{col}' column in the underlying data").into(),
+            ));
+        }
+    }
+    if params.strike_width <= 0.0 || params.contracts <= 0.0 {
+        return Err(PolarsError::ComputeError(
+            "Vertical spread strike width and contracts must be positive".into(),
+        ));
+    }
+
+    let history = ChainHistory::new(chain, &params.time_column, &params.price_column)?;
+    let valuation = Valuation {
+        model: &params.model,
+        risk_free_rate: params.risk_free_rate,
+        dividend_yield: params.dividend_yield,
+    };
+    let timestamps = parse_timestamp_column(underlying, &params.time_column)?;
+    let close = underlying
+        .column(&params.columns.close)?
+        .cast(&DataType::Float64)?;
+    let close = close.f64()?;
+
+    let n = underlying.height();
+    let credit = params.spread_type.is_credit();
+    let quantity = params.contracts * params.contract_multiplier;
+    let leg_fees = 2.0 * params.contracts * params.fee_per_contract;
+
+    let mut buy_signals = vec![0; n];
+    let mut sell_signals = vec![0; n];
+    let mut long_strike = vec![f64::NAN; n];
+    let mut short_strike = vec![f64::NAN; n];
+    let mut entry_premium = vec![f64::NAN; n];
+    let mut expected_move = vec![f64::NAN; n];
+    let mut pop = vec![f64::NAN; n];
+    let mut mark = vec![f64::NAN; n];
+    let mut days_left = vec![f64::NAN; n];
+    let mut open_pnl = vec![f64::NAN; n];
+    let mut exit_reason: Vec<Option<&str>> = vec![None; n];
+    let mut equity = vec![f64::NAN; n];
+    let mut exposure = vec![f64::NAN; n];
+    let mut trades = Vec::new();
+    let mut realized = 0.0;
+    let mut position: Option<(DateTime<Utc>, VerticalSpread)> = None;
+
+    for (i, time) in timestamps.into_iter().enumerate() {
+        let (Some(time), Some(spot)) = (time, close.get(i).filter(|c| !c.is_nan())) else {
+            continue;
+        };
+        let snapshot = history.snapshot(time);
+
+        if let Some((entry_time, spread)) = &position {
+            let legs = spread.legs();
+            let value = valuation.position_value(&legs, spot, time, snapshot);
+            let pnl = value - spread.entry_value();
+            let dte = days_between(time, spread.short_leg.expiry);
+
+            long_strike[i] = spread.long_leg.strike;
+            short_strike[i] = spread.short_leg.strike;
+            entry_premium[i] = spread.premium;
+            expected_move[i] = spread.expected_move;
+            pop[i] = spread.probability_of_profit;
+            mark[i] = value;
+            days_left[i] = dte;
+            open_pnl[i] = pnl * quantity;
+            equity[i] = realized + open_pnl[i] - leg_fees;
+            exposure[i] = value.abs() * quantity;
+
+            let reason = ExitReason::check(
+                pnl,
+                spread.max_profit() * params.profit_target_pct / 100.0,
+                spread.max_loss() * params.stop_loss_pct / 100.0,
+                dte,
+                params.exit_days_to_expiry,
+            );
+
+            if let Some(reason) = reason {
+                let exit_value = valuation.closing_value(&legs, spot, time, snapshot);
+                let fees = 2.0 * leg_fees;
+                let net = (exit_value - spread.entry_value()) * quantity - fees;
+                realized += net;
+                equity[i] = realized;
+                exposure[i] = 0.0;
+                let (side, exit_price) = if credit {
+                    buy_signals[i] = 1;
+                    (TradeSide::Short, -exit_value)
+                } else {
+                    sell_signals[i] = 1;
+                    (TradeSide::Long, exit_value)
+                };
+                exit_reason[i] = Some(reason.as_str());
+                trades.push(RoundTripTrade {
+                    entry_time: *entry_time,
+                    exit_time: time,
+                    side,
+                    quantity,
+                    entry_price: spread.premium,
+                    exit_price,
+                    fees,
+                    pnl: net,
+                    return_pct: net / (spread.max_loss() * quantity) * 100.0,
+                });
+                position = None;
+            }
+            continue;
+        }
+
+        (equity[i], exposure[i]) = (realized, 0.0);
+        let Some(quotes) = snapshot else {
+            continue;
+        };
+        if let Some(spread) = build_spread(quotes, spot, time, params) {
+            if credit {
+                sell_signals[i] = 1;
+            } else {
+                buy_signals[i] = 1;
+            }
+            long_strike[i] = spread.long_leg.strike;
+            short_strike[i] = spread.short_leg.strike;
+            entry_premium[i] = spread.premium;
+            expected_move[i] = spread.expected_move;
+            pop[i] = spread.probability_of_profit;
+            mark[i] = valuation.position_value(&spread.legs(), spot, time, snapshot);
+            days_left[i] = days_between(time, spread.short_leg.expiry);
+            open_pnl[i] = (mark[i] - spread.entry_value()) * quantity;
+            equity[i] = realized + open_pnl[i] - leg_fees;
+            exposure[i] = mark[i].abs() * quantity;
+            position = Some((time, spread));
+        }
+    }
+
+    let indicator_values = build_indicator_values(
+        underlying,
+        &params.time_column,
+        &params.columns.close,
+        vec![
+            (Series::new("spread_long_strike".into(), long_strike), true),
+            (
+                Series::new("spread_short_strike".into(), short_strike),
+                true,
+            ),
+            (
+                Series::new("spread_entry_premium".into(), entry_premium),
+                false,
+            ),
+            (
+                Series::new("spread_expected_move".into(), expected_move),
+                false,
+            ),
+            (Series::new("spread_pop".into(), pop), false),
+            (Series::new("spread_mark".into(), mark), false),
+            (
+                Series::new("spread_days_to_expiry".into(), days_left),
+                false,
+            ),
+            (Series::new("spread_pnl".into(), open_pnl), true),
+            (Series::new("spread_exit_reason".into(), exit_reason), true),
+        ],
+        params.output_columns,
+    )?;
+
+    let [equity_curve, drawdown, exposure] = equity_outputs(equity, exposure);
+    Ok(StrategySignals {
+        buy_signals,
+        sell_signals,
+        indicator_values,
+        trades,
+        equity_curve,
+        drawdown,
+        exposure,
+    })
+}
+
+/// Pick both legs from one snapshot
+fn build_spread(
+    quotes: &[ChainQuote],
+    spot: f64,
+    time: DateTime<Utc>,
+    params: &StrategyParams,
+) -> Option<VerticalSpread> {
+    let valuation = Valuation {
+        model: &params.model,
+        risk_free_rate: params.risk_free_rate,
+        dividend_yield: params.dividend_yield,
+    };
+    let spread_type = params.spread_type;
+    let is_call = spread_type.is_call();
+    let expiry = select_expiry(
+        quotes,
+        time,
+        params.min_days_to_expiry,
+        params.max_days_to_expiry,
+    )?;
+    let candidates: Vec<&ChainQuote> = quotes
+        .iter()
+        .filter(|q| q.expiry == expiry && q.is_call == is_call)
+        .collect();
+
+    // The near-the-money leg is bought for debit spreads and sold for credit spreads
+    let (near_ratio, far_ratio) = if spread_type.is_credit() {
+        (-1.0, 1.0)
+    } else {
+        (1.0, -1.0)
+    };
+    let near = candidates
+        .iter()
+        .map(|q| valuation.leg(q, spot, time, near_ratio))
+        .filter(|leg| !leg.delta.is_nan())
+        .min_by(|a, b| {
+            let da = (a.delta.abs() - params.target_delta).abs();
+            let db = (b.delta.abs() - params.target_delta).abs();
+            da.total_cmp(&db)
+        })?;
+
+    // Further out of the money: higher strikes for calls, lower for puts
+    let target = if is_call {
+        near.strike + params.strike_width
+    } else {
+        near.strike - params.strike_width
+    };
+    let far = candidates
+        .iter()
+        .filter(|q| {
+            if is_call {
+                q.strike > near.strike
+            } else {
+                q.strike < near.strike
+            }
+        })
+        .min_by(|a, b| {
+            (a.strike - target)
+                .abs()
+                .total_cmp(&(b.strike - target).abs())
+        })
+        .map(|q| valuation.leg(q, spot, time, far_ratio))?;
+
+    let (long_leg, short_leg) = if spread_type.is_credit() {
+        (far, near)
+    } else {
+        (near, far)
+    };
+    let premium = if spread_type.is_credit() {
+        short_leg.entry_price - long_leg.entry_price
+    } else {
+        long_leg.entry_price - short_leg.entry_price
+    };
+    if premium <= 0.0 || premium >= (long_leg.strike - short_leg.strike).abs() {
+        return None;
+    }
+
+    Some(VerticalSpread {
+        expected_move: straddle_expected_move(quotes, expiry, spot),
+        probability_of_profit: valuation.probability_of_profit(
+            &[long_leg.clone(), short_leg.clone()],
+            spot,
+            time,
+        ),
+        spread_type,
+        long_leg,
+        short_leg,
+        premium,
+    })
+}
@@ -0,0 +1,481 @@
+//! # Wheel Strategy
+//!
+//! Sells cash-secured puts until assigned, then sells covered calls against
+//! the assigned shares until they are called away, and starts over.
+//!
+//! Options are selected by delta from the chain snapshot at the bar they are
+//! sold and marked to market every bar (see the [module docs](super)). A
+//! short option can be bought back at a profit target or rolled to a later
+//! expiry when it is in the money close to expiry. Options held to expiry are
+//! settled against the underlying close: an in-the-money put is assigned
+//! (shares bought at the strike) and an in-the-money call is exercised
+//! (shares sold at the strike); otherwise the option expires worthless.
+
+use crate::indicators::options::pricing::PricingModel;
+use crate::performance::{RoundTripTrade, TradeSide};
+use crate::strategy::options::{
+    days_between, select_expiry, ChainHistory, ChainQuote, OptionLeg, Valuation,
+};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::column_map::ColumnMap;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Parameters for the wheel strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyParams {
+    /// Absolute delta targeted for cash-secured puts (e.g. 0.30)
+    pub put_delta: f64,
+    /// Delta targeted for covered calls (e.g. 0.30)
+    pub call_delta: f64,
+    /// Shortest days to expiry accepted when selling an option
+    pub min_days_to_expiry: f64,
+    /// Longest days to expiry accepted when selling an option
+    pub max_days_to_expiry: f64,
+    /// Buy the option back when the profit reaches this percentage of its
+    /// premium (None: hold to expiry)
+    pub profit_target_pct: Option<f64>,
+    /// Roll an in-the-money option to a later expiry when this many days or
+    /// fewer remain (None: never roll)
+    pub roll_days_to_expiry: Option<f64>,
+    /// Only sell calls at or above the cost basis of the assigned shares
+    pub calls_above_cost_basis: bool,
+    /// Number of contracts per option; shares held are contracts × multiplier
+    pub contracts: f64,
+    /// Underlying units per contract
+    pub contract_multiplier: f64,
+    /// Commission per contract for each option bought or sold
+    pub fee_per_contract: f64,
+    /// Continuously compounded risk-free rate as a decimal
+    pub risk_free_rate: f64,
+    /// Continuous dividend yield of the underlying as a decimal
+    pub dividend_yield: f64,
+    /// Model for IV, deltas and re-pricing options without a quote
+    pub model: PricingModel,
+    /// Column with timestamps in the underlying data and quote times in the chain
+    pub time_column: String,
+    /// Names of the OHLCV columns in the price data
+    pub columns: ColumnMap,
+    /// Column with option mark prices in the chain
+    pub price_column: String,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            put_delta: 0.30,
+            call_delta: 0.30,
+            min_days_to_expiry: 30.0,
+            max_days_to_expiry: 45.0,
+            profit_target_pct: Some(50.0),
+            roll_days_to_expiry: Some(5.0),
+            calls_above_cost_basis: true,
+            contracts: 1.0,
+            contract_multiplier: 100.0,
+            fee_per_contract: 0.65,
+            risk_free_rate: 0.02,
+            dividend_yield: 0.0,
+            model: PricingModel::BlackScholes,
+            time_column: "timestamp".to_string(),
+            columns: ColumnMap::default(),
+            price_column: "price".to_string(),
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for StrategyParams {}
+
+/// Short option currently held
+struct ShortOption {
+    entry_time: DateTime<Utc>,
+    leg: OptionLeg,
+}
+
+/// Assigned shares currently held
+struct Shares {
+    entry_time: DateTime<Utc>,
+    cost_basis: f64,
+}
+
+/// Backtest the wheel
+///
+/// # Arguments
+///
+/// * `underlying` - Underlying bars with the time column and the close column
+/// * `chain` - Options chain history: the time column holding each quote's
+///   snapshot time, plus `strike`, `expiry`, `option_type` and the price
+///   column and/or `bid` and `ask`. Snapshots are matched to bars with the
+///   same timestamp.
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing [`StrategySignals`] where a buy signal
+/// marks shares assigned through a put and a sell signal shares called away
+/// through a call. Every option sold is a short round trip with the premium
+/// as entry price and the buy-back price (or intrinsic value at expiry) as
+/// exit price, and every share holding a long round trip from the put strike
+/// to the call strike, all in units of the underlying. Indicator columns:
+/// "wheel_phase" ("cash", "short_put", "shares" or "covered_call"),
+/// "wheel_event" ("sold", "profit_target", "rolled", "expired", "assigned"
+/// or "called_away"), "wheel_strike", "wheel_days_to_expiry",
+/// "wheel_shares", "wheel_cost_basis", "wheel_premium" (net premium
+/// collected so far) and "wheel_pnl" (realized plus open P&L).
+///
+/// # Example
+///
+/// ```
+/// use chrono::{Duration, TimeZone, Utc};
+/// use polars::prelude::*;
+/// use rustalib::indicators::options::black_scholes_price;
+/// use rustalib::strategy::options::wheel::{run_strategy, StrategyParams};
+///
+/// // The underlying falls through the put strike, then recovers
+/// let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+/// let closes: Vec<f64> = (0..90)
+///     .map(|d| if d < 40 { 100.0 - 0.3 * d as f64 } else { 88.0 + 0.4 * (d - 40) as f64 })
+///     .collect();
+/// let times: Vec<_> = (0..90).map(|d| start + Duration::days(d)).collect();
+///
+/// // Weekly snapshots with expiries every 35 days, priced at 30% volatility
+/// let (mut quote_time, mut strikes, mut expiries, mut types, mut prices) =
+///     (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+/// for d in (0..90).step_by(7) {
+///     for expiry in (1..=4).map(|m| start + Duration::days(35 * m)) {
+///         let days = (expiry - times[d]).num_days();
+///         if !(1..=60).contains(&days) {
+///             continue;
+///         }
+///         for k in (70..=120).step_by(5).map(|k| k as f64) {
+///             for call in [true, false] {
+///                 quote_time.push(times[d].format("%Y-%m-%d").to_string());
+///                 strikes.push(k);
+///                 expiries.push(expiry.format("%Y-%m-%d").to_string());
+///                 types.push(if call { "call" } else { "put" });
+///                 prices.push(black_scholes_price(closes[d], k, days as f64 / 365.0, 0.02, 0.0, 0.3, call));
+///             }
+///         }
+///     }
+/// }
+/// let chain = df! {
+///     "timestamp" => quote_time,
+///     "strike" => strikes,
+///     "expiry" => expiries,
+///     "option_type" => types,
+///     "price" => prices,
+/// }
+/// .unwrap();
+/// let underlying = df! {
+///     "timestamp" => times.iter().map(|t| t.format("%Y-%m-%d").to_string()).collect::<Vec<_>>(),
+///     "close" => closes,
+/// }
+/// .unwrap();
+///
+/// let params = StrategyParams {
+///     profit_target_pct: None,
+///     roll_days_to_expiry: None,
+///     ..Default::default()
+/// };
+/// let signals = run_strategy(&underlying, &chain, &params).unwrap();
+///
+/// // The first put is assigned
+/// assert_eq!(signals.buy_signals.iter().sum::<i32>(), 1);
+/// let events = signals.indicator_values.column("wheel_event").unwrap();
+/// assert!(events.str().unwrap().into_iter().any(|e| e == Some("assigned")));
+/// ```
+pub fn run_strategy(
+    underlying: &DataFrame,
+    chain: &DataFrame,
+    params: &StrategyParams,
+) -> PolarsResult<StrategySignals> {
+    for col in [params.time_column.as_str(), params.columns.close.as_str()] {
+        if !underlying.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Wheel strategy requires '{col}' column in the underlying data").into(),
+            ));
+        }
+    }
+    if params.contracts <= 0.0 {
+        return Err(PolarsError::ComputeError(
+            "Wheel contracts must be positive".into(),
+        ));
+    }
+
+    let history = ChainHistory::new(chain, &params.time_column, &params.price_column)?;
+    let valuation = Valuation {
+        model: &params.model,
+        risk_free_rate: params.risk_free_rate,
+        dividend_yield: params.dividend_yield,
+    };
+    let timestamps = parse_timestamp_column(underlying, &params.time_column)?;
+    let close = underlying
+        .column(&params.columns.close)?
+        .cast(&DataType::Float64)?;
+    let close = close.f64()?;
+
+    let n = underlying.height();
+    let quantity = params.contracts * params.contract_multiplier;
+    let fee = params.contracts * params.fee_per_contract;
+
+    let mut buy_signals = vec![0; n];
+    let mut sell_signals = vec![0; n];
+    let mut phase: Vec<Option<&str>> = vec![None; n];
+    let mut event: Vec<Option<&str>> = vec![None; n];
+    let mut strike = vec![f64::NAN; n];
+    let mut days_left = vec![f64::NAN; n];
+    let mut shares_held = vec![0.0; n];
+    let mut cost_basis = vec![f64::NAN; n];
+    let mut premium_collected = vec![0.0; n];
+    let mut total_pnl = vec![f64::NAN; n];
+    let mut exposure = vec![f64::NAN; n];
+    let mut trades: Vec<RoundTripTrade> = Vec::new();
+
+    let mut option: Option<ShortOption> = None;
+    let mut shares: Option<Shares> = None;
+    let mut premium = 0.0;
+    let mut realized = 0.0;
+
+    let close_option = |open: &ShortOption, time: DateTime<Utc>, exit_price: f64, exit_fee: f64| {
+        let fees = fee + exit_fee;
+        let pnl = (open.leg.entry_price - exit_price) * quantity - fees;
+        RoundTripTrade {
+            entry_time: open.entry_time,
+            exit_time: time,
+            side: TradeSide::Short,
+            quantity,
+            entry_price: open.leg.entry_price,
+            exit_price,
+            fees,
+            pnl,
+            return_pct: pnl / (open.leg.strike * quantity) * 100.0,
+        }
+    };
+
+    for (i, time) in timestamps.into_iter().enumerate() {
+        let (Some(time), Some(spot)) = (time, close.get(i).filter(|c| !c.is_nan())) else {
+            continue;
+        };
+        let snapshot = history.snapshot(time);
+
+        if let Some(open) = &option {
+            let leg = &open.leg;
+            let dte = days_between(time, leg.expiry);
+            let intrinsic = if leg.is_call {
+                (spot - leg.strike).max(0.0)
+            } else {
+                (leg.strike - spot).max(0.0)
+            };
+
+            if dte <= 0.0 {
+                let trade = close_option(open, time, intrinsic, 0.0);
+                realized += trade.pnl;
+                trades.push(trade);
+                if intrinsic <= 0.0 {
+                    event[i] = Some("expired");
+                } else if leg.is_call {
+                    // Shares called away at the strike
+                    if let Some(held) = shares.take() {
+                        let pnl = (leg.strike - held.cost_basis) * quantity;
+                        realized += pnl;
+                        trades.push(RoundTripTrade {
+                            entry_time: held.entry_time,
+                            exit_time: time,
+                            side: TradeSide::Long,
+                            quantity,
+                            entry_price: held.cost_basis,
+                            exit_price: leg.strike,
+                            fees: 0.0,
+                            pnl,
+                            return_pct: pnl / (held.cost_basis * quantity) * 100.0,
+                        });
+                    }
+                    sell_signals[i] = 1;
+                    event[i] = Some("called_away");
+                } else {
+                    shares = Some(Shares {
+                        entry_time: time,
+                        cost_basis: leg.strike,
+                    });
+                    buy_signals[i] = 1;
+                    event[i] = Some("assigned");
+                }
+                option = None;
+            } else {
+                let mark =
+                    -valuation.position_value(std::slice::from_ref(leg), spot, time, snapshot);
+                let profit = leg.entry_price - mark;
+                let take_profit = params
+                    .profit_target_pct
+                    .is_some_and(|pct| profit >= leg.entry_price * pct / 100.0);
+                let in_the_money = intrinsic > 0.0;
+                let roll_due = params
+                    .roll_days_to_expiry
+                    .is_some_and(|days| dte <= days && in_the_money);
+                let replacement = if roll_due {
+                    snapshot.and_then(|quotes| {
+                        let later: Vec<ChainQuote> = quotes
+                            .iter()
+                            .filter(|q| q.expiry > leg.expiry)
+                            .cloned()
+                            .collect();
+                        sell_option(&later, spot, time, leg.is_call, shares.as_ref(), params)
+                    })
+                } else {
+                    None
+                };
+
+                if take_profit || replacement.is_some() {
+                    let exit_price =
+                        -valuation.closing_value(std::slice::from_ref(leg), spot, time, snapshot);
+                    let trade = close_option(open, time, exit_price, fee);
+                    realized += trade.pnl;
+                    premium -= exit_price * quantity;
+                    trades.push(trade);
+                    option = None;
+                    event[i] = Some("profit_target");
+
+                    if let Some(new_leg) = replacement {
+                        premium += new_leg.entry_price * quantity;
+                        option = Some(ShortOption {
+                            entry_time: time,
+                            leg: new_leg,
+                        });
+                        event[i] = Some("rolled");
+                    }
+                }
+            }
+        }
+
+        if option.is_none() {
+            if let Some(quotes) = snapshot {
+                let is_call = shares.is_some();
+                if let Some(leg) = sell_option(quotes, spot, time, is_call, shares.as_ref(), params)
+                {
+                    premium += leg.entry_price * quantity;
+                    option = Some(ShortOption {
+                        entry_time: time,
+                        leg,
+                    });
+                    event[i] = event[i].or(Some("sold"));
+                }
+            }
+        }
+
+        // Open P&L of the option and the shares
+        let mut open_pnl = 0.0;
+        exposure[i] = 0.0;
+        if let Some(open) = &option {
+            let leg = &open.leg;
+            let mark = -valuation.position_value(std::slice::from_ref(leg), spot, time, snapshot);
+            open_pnl += (leg.entry_price - mark) * quantity - fee;
+            exposure[i] += mark.abs() * quantity;
+            strike[i] = leg.strike;
+            days_left[i] = days_between(time, leg.expiry);
+        }
+        if let Some(held) = &shares {
+            open_pnl += (spot - held.cost_basis) * quantity;
+            shares_held[i] = quantity;
+            exposure[i] += spot * quantity;
+            cost_basis[i] = held.cost_basis;
+        }
+        phase[i] = Some(match (&shares, &option) {
+            (None, None) => "cash",
+            (None, Some(_)) => "short_put",
+            (Some(_), None) => "shares",
+            (Some(_), Some(_)) => "covered_call",
+        });
+        premium_collected[i] = premium;
+        total_pnl[i] = realized + open_pnl;
+    }
+
+    let indicator_values = build_indicator_values(
+        underlying,
+        &params.time_column,
+        &params.columns.close,
+        vec![
+            (Series::new("wheel_phase".into(), phase), true),
+            (Series::new("wheel_event".into(), event), true),
+            (Series::new("wheel_strike".into(), strike), false),
+            (Series::new("wheel_days_to_expiry".into(), days_left), false),
+            (Series::new("wheel_shares".into(), shares_held), true),
+            (Series::new("wheel_cost_basis".into(), cost_basis), false),
+            (
+                Series::new("wheel_premium".into(), premium_collected),
+                false,
+            ),
+            (Series::new("wheel_pnl".into(), total_pnl.clone()), true),
+        ],
+        params.output_columns,
+    )?;
+
+    let [equity_curve, drawdown, exposure] = equity_outputs(total_pnl, exposure);
+    Ok(StrategySignals {
+        buy_signals,
+        sell_signals,
+        indicator_values,
+        trades,
+        equity_curve,
+        drawdown,
+        exposure,
+    })
+}
+
+/// Out-of-the-money put or call closest to the target delta, sold short
+///
+/// Calls are restricted to strikes at or above the cost basis of `shares`
+/// when `calls_above_cost_basis` is set.
+fn sell_option(
+    quotes: &[ChainQuote],
+    spot: f64,
+    time: DateTime<Utc>,
+    is_call: bool,
+    shares: Option<&Shares>,
+    params: &StrategyParams,
+) -> Option<OptionLeg> {
+    let valuation = Valuation {
+        model: &params.model,
+        risk_free_rate: params.risk_free_rate,
+        dividend_yield: params.dividend_yield,
+    };
+    let expiry = select_expiry(
+        quotes,
+        time,
+        params.min_days_to_expiry,
+        params.max_days_to_expiry,
+    )?;
+    let min_call_strike = match shares {
+        Some(held) if params.calls_above_cost_basis => held.cost_basis,
+        _ => f64::NEG_INFINITY,
+    };
+    let target = if is_call {
+        params.call_delta
+    } else {
+        params.put_delta
+    };
+
+    quotes
+        .iter()
+        .filter(|q| q.expiry == expiry && q.is_call == is_call)
+        .filter(|q| {
+            if is_call {
+                q.strike > spot && q.strike >= min_call_strike
+            } else {
+                q.strike < spot
+            }
+        })
+        .map(|q| valuation.leg(q, spot, time, -1.0))
+        .filter(|leg| !leg.delta.is_nan())
+        .min_by(|a, b| {
+            let da = (a.delta.abs() - target).abs();
+            let db = (b.delta.abs() - target).abs();
+            da.total_cmp(&db)
+        })
+}
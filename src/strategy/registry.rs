@@ -0,0 +1,405 @@
+//! # Strategy Registry
+//!
+//! Lists the bundled strategies with the inputs they need and runs them by
+//! name with JSON parameters, so applications can offer a strategy picker or
+//! load a strategy choice from configuration without matching on names
+//! themselves.
+
+use crate::error::{TaError, TaResult};
+use crate::strategy::builder::{ConfigStrategy, StrategyConfig};
+use crate::strategy::crypto::arbitrage::{self, ExchangeFeed};
+use crate::strategy::crypto::{grid_trading, market_neutral};
+use crate::strategy::options::{delta_neutral, iron_condor, vertical_spreads, wheel};
+use crate::strategy::stock::momentum_rotation::{self, UniverseMember};
+use crate::strategy::stock::{donchian_breakout, pairs_trading};
+use crate::strategy::StrategySignals;
+use polars::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Market a strategy is designed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetClass {
+    /// Any instrument with a price history
+    Any,
+    /// Crypto spot markets
+    Crypto,
+    /// Options on an underlying
+    Options,
+    /// Listed equities
+    Stock,
+}
+
+/// Data passed to [`run_by_name`]
+#[derive(Debug, Clone, Copy)]
+pub enum StrategyData<'a> {
+    /// A single price history
+    Bars(&'a DataFrame),
+    /// Underlying price history and options chain snapshots
+    Options {
+        underlying: &'a DataFrame,
+        chain: &'a DataFrame,
+    },
+    /// Price histories of the same asset on several exchanges
+    Feeds(&'a [ExchangeFeed<'a>]),
+    /// Price histories of the tickers of a universe
+    Universe(&'a [UniverseMember<'a>]),
+    /// Price histories of two related instruments (for `market_neutral`,
+    /// spot first and the perpetual with its funding rates second)
+    Pair {
+        first: &'a DataFrame,
+        second: &'a DataFrame,
+    },
+}
+
+/// Description of a registered strategy
+#[derive(Debug, Clone)]
+pub struct StrategyInfo {
+    /// Name accepted by [`run_by_name`]
+    pub name: &'static str,
+    /// One-line description
+    pub description: &'static str,
+    /// Market the strategy is designed for
+    pub asset_class: AssetClass,
+    /// Columns required in the price data under the default params
+    pub required_columns: Vec<&'static str>,
+    /// Columns required in the options chain, empty for non-options strategies
+    pub chain_columns: Vec<&'static str>,
+    /// Default params as JSON, or None when some params have no sensible
+    /// default and must be supplied
+    pub default_params: Option<String>,
+}
+
+const CHAIN_COLUMNS: [&str; 5] = ["timestamp", "strike", "expiry", "option_type", "price"];
+
+fn to_json<T: Serialize>(params: &T) -> Option<String> {
+    serde_json::to_string_pretty(params).ok()
+}
+
+fn options_info(
+    name: &'static str,
+    description: &'static str,
+    default_params: Option<String>,
+) -> StrategyInfo {
+    StrategyInfo {
+        name,
+        description,
+        asset_class: AssetClass::Options,
+        required_columns: vec!["timestamp", "close"],
+        chain_columns: CHAIN_COLUMNS.to_vec(),
+        default_params,
+    }
+}
+
+/// A registered strategy: its description and how to run it by name
+struct Registered {
+    info: fn() -> StrategyInfo,
+    run: fn(&str, StrategyData, &str) -> TaResult<StrategySignals>,
+}
+
+/// Every registered strategy, in alphabetical order of name
+const STRATEGIES: &[Registered] = &[
+    Registered {
+        info: || StrategyInfo {
+            name: "arbitrage",
+            description: "Cross-exchange arbitrage across several price feeds",
+            asset_class: AssetClass::Crypto,
+            required_columns: vec!["timestamp", "close"],
+            chain_columns: Vec::new(),
+            default_params: to_json(&arbitrage::StrategyParams::default()),
+        },
+        run: |name, data, params| {
+            let StrategyData::Feeds(feeds) = data else {
+                return Err(wrong_data(name, "exchange feed"));
+            };
+            Ok(arbitrage::run_strategy(
+                feeds,
+                &parse_params(name, params)?,
+            )?)
+        },
+    },
+    Registered {
+        info: || StrategyInfo {
+            name: "config",
+            description: "Strategy described by a builder StrategyConfig",
+            asset_class: AssetClass::Any,
+            required_columns: vec!["timestamp", "close"],
+            chain_columns: Vec::new(),
+            default_params: None,
+        },
+        run: |name, data, params| {
+            let df = bars(name, data)?;
+            let config: StrategyConfig = parse_params(name, params)?;
+            Ok(ConfigStrategy::from_config(config)?.run(df)?)
+        },
+    },
+    Registered {
+        info: || {
+            options_info(
+                "delta_neutral",
+                "Delta-hedged straddle with Greek P&L attribution",
+                to_json(&delta_neutral::StrategyParams::default()),
+            )
+        },
+        run: |name, data, params| {
+            let (underlying, chain) = options(name, data)?;
+            Ok(delta_neutral::run_strategy(
+                underlying,
+                chain,
+                &parse_params(name, params)?,
+            )?)
+        },
+    },
+    Registered {
+        info: || StrategyInfo {
+            name: "donchian_breakout",
+            description:
+                "Turtle trading on Donchian channel breakouts with ATR sizing and pyramiding",
+            asset_class: AssetClass::Stock,
+            required_columns: vec!["timestamp", "open", "high", "low", "close"],
+            chain_columns: Vec::new(),
+            default_params: to_json(&donchian_breakout::StrategyParams::default()),
+        },
+        run: |name, data, params| {
+            let df = bars(name, data)?;
+            Ok(donchian_breakout::run_strategy(
+                df,
+                &parse_params(name, params)?,
+            )?)
+        },
+    },
+    Registered {
+        info: || StrategyInfo {
+            name: "grid_trading",
+            description: "Range-bound grid trading with limit orders at fixed price levels",
+            asset_class: AssetClass::Crypto,
+            required_columns: vec!["timestamp", "open", "high", "low", "close"],
+            chain_columns: Vec::new(),
+            default_params: None,
+        },
+        run: |name, data, params| {
+            let df = bars(name, data)?;
+            Ok(grid_trading::run_strategy(
+                df,
+                &parse_params(name, params)?,
+            )?)
+        },
+    },
+    Registered {
+        info: || {
+            options_info(
+                "iron_condor",
+                "Short iron condor with delta-targeted short strikes",
+                to_json(&iron_condor::StrategyParams::default()),
+            )
+        },
+        run: |name, data, params| {
+            let (underlying, chain) = options(name, data)?;
+            Ok(iron_condor::run_strategy(
+                underlying,
+                chain,
+                &parse_params(name, params)?,
+            )?)
+        },
+    },
+    Registered {
+        info: || StrategyInfo {
+            name: "market_neutral",
+            description: "Spot against perpetual futures capturing funding payments",
+            asset_class: AssetClass::Crypto,
+            required_columns: vec!["timestamp", "close", "funding_rate"],
+            chain_columns: Vec::new(),
+            default_params: to_json(&market_neutral::StrategyParams::default()),
+        },
+        run: |name, data, params| {
+            let StrategyData::Pair { first, second } = data else {
+                return Err(wrong_data(name, "spot and perpetual price pair"));
+            };
+            Ok(market_neutral::run_strategy(
+                first,
+                second,
+                &parse_params(name, params)?,
+            )?)
+        },
+    },
+    Registered {
+        info: || StrategyInfo {
+            name: "momentum_rotation",
+            description: "Cross-sectional momentum rotation into the top-ranked stocks",
+            asset_class: AssetClass::Stock,
+            required_columns: vec!["timestamp", "close"],
+            chain_columns: Vec::new(),
+            default_params: to_json(&momentum_rotation::StrategyParams::default()),
+        },
+        run: |name, data, params| {
+            let StrategyData::Universe(universe) = data else {
+                return Err(wrong_data(name, "ticker universe"));
+            };
+            Ok(momentum_rotation::run_strategy(
+                universe,
+                &parse_params(name, params)?,
+            )?)
+        },
+    },
+    Registered {
+        info: || StrategyInfo {
+            name: "pairs_trading",
+            description: "Statistical arbitrage on the spread of two co-moving stocks",
+            asset_class: AssetClass::Stock,
+            required_columns: vec!["timestamp", "close"],
+            chain_columns: Vec::new(),
+            default_params: to_json(&pairs_trading::StrategyParams::default()),
+        },
+        run: |name, data, params| {
+            let StrategyData::Pair { first, second } = data else {
+                return Err(wrong_data(name, "price pair"));
+            };
+            Ok(pairs_trading::run_strategy(
+                first,
+                second,
+                &parse_params(name, params)?,
+            )?)
+        },
+    },
+    Registered {
+        info: || {
+            options_info(
+                "vertical_spreads",
+                "Bull/bear call and put spreads selected by delta",
+                to_json(&vertical_spreads::StrategyParams::default()),
+            )
+        },
+        run: |name, data, params| {
+            let (underlying, chain) = options(name, data)?;
+            Ok(vertical_spreads::run_strategy(
+                underlying,
+                chain,
+                &parse_params(name, params)?,
+            )?)
+        },
+    },
+    Registered {
+        info: || {
+            options_info(
+                "wheel",
+                "Cash-secured puts and covered calls with assignment",
+                to_json(&wheel::StrategyParams::default()),
+            )
+        },
+        run: |name, data, params| {
+            let (underlying, chain) = options(name, data)?;
+            Ok(wheel::run_strategy(
+                underlying,
+                chain,
+                &parse_params(name, params)?,
+            )?)
+        },
+    },
+];
+
+/// List every registered strategy
+///
+/// # Returns
+///
+/// Returns the strategies in alphabetical order of name
+///
+/// # Example
+///
+/// ```
+/// use rustalib::strategy::registry::registry;
+///
+/// let names: Vec<&str> = registry().iter().map(|s| s.name).collect();
+/// assert!(names.contains(&"grid_trading"));
+/// assert!(names.contains(&"iron_condor"));
+/// ```
+pub fn registry() -> Vec<StrategyInfo> {
+    STRATEGIES.iter().map(|s| (s.info)()).collect()
+}
+
+/// Look up a registered strategy by name
+pub fn find_strategy(name: &str) -> Option<StrategyInfo> {
+    registry().into_iter().find(|s| s.name == name)
+}
+
+fn parse_params<T: DeserializeOwned>(name: &str, params_json: &str) -> TaResult<T> {
+    let json = if params_json.trim().is_empty() {
+        "{}"
+    } else {
+        params_json
+    };
+    serde_json::from_str(json).map_err(|e| TaError::invalid_parameter(name, e.to_string()))
+}
+
+fn wrong_data(name: &str, expected: &str) -> TaError {
+    TaError::invalid_parameter(
+        "data",
+        format!("strategy '{name}' requires {expected} data"),
+    )
+}
+
+fn bars<'a>(name: &str, data: StrategyData<'a>) -> TaResult<&'a DataFrame> {
+    match data {
+        StrategyData::Bars(df) => Ok(df),
+        _ => Err(wrong_data(name, "price bar")),
+    }
+}
+
+fn options<'a>(name: &str, data: StrategyData<'a>) -> TaResult<(&'a DataFrame, &'a DataFrame)> {
+    match data {
+        StrategyData::Options { underlying, chain } => Ok((underlying, chain)),
+        _ => Err(wrong_data(name, "underlying and options chain")),
+    }
+}
+
+/// Run a registered strategy by name
+///
+/// # Arguments
+///
+/// * `name` - Strategy name as listed by [`registry`]
+/// * `data` - Input data of the kind the strategy needs
+/// * `params_json` - Strategy params as JSON; fields left out take their
+///   defaults, and an empty string uses the default params. Strategies
+///   listed without `default_params` have fields that must be supplied, and
+///   fail with InvalidParameter naming them when they are left out
+///
+/// # Returns
+///
+/// Returns a TaResult containing the strategy's StrategySignals, or an
+/// InvalidParameter error for an unknown name, invalid params or the wrong
+/// kind of data
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::strategy::registry::{run_by_name, StrategyData};
+/// use rustalib::TaError;
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-01 00:00:00", "2024-01-01 01:00:00"],
+///     "open" => [100.0, 96.0],
+///     "high" => [101.0, 111.0],
+///     "low" => [95.0, 89.0],
+///     "close" => [96.0, 110.0],
+/// }
+/// .unwrap();
+///
+/// let params = r#"{
+///     "lower_price": 90.0, "upper_price": 110.0, "num_grids": 2,
+///     "spacing": "arithmetic", "order_size": 1.0, "fee_rate": 0.0,
+///     "initial_capital": 1000.0, "rebalance_on_fill": true,
+///     "time_column": "timestamp", "output_columns": "all"
+/// }"#;
+/// let signals = run_by_name("grid_trading", StrategyData::Bars(&df), params).unwrap();
+/// assert_eq!(signals.trades.len(), 2);
+///
+/// let err = run_by_name("unknown", StrategyData::Bars(&df), "").unwrap_err();
+/// assert!(matches!(err, TaError::InvalidParameter { .. }));
+/// ```
+pub fn run_by_name(name: &str, data: StrategyData, params_json: &str) -> TaResult<StrategySignals> {
+    let strategy = STRATEGIES
+        .iter()
+        .find(|s| (s.info)().name == name)
+        .ok_or_else(|| TaError::invalid_parameter("name", format!("unknown strategy '{name}'")))?;
+    (strategy.run)(name, data, params_json)
+}
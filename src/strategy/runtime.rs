@@ -0,0 +1,126 @@
+//! # Strategy Runtime
+//!
+//! Helpers shared by the run loops of the strategies. [`SignalTiming`] is
+//! the config that keeps a strategy from flip-flopping between consecutive
+//! bars and [`SignalGate`] the state that enforces it during a run.
+
+use serde::{Deserialize, Serialize};
+
+/// Holding period, cooldown and persistence requirements for entries and
+/// exits
+///
+/// The defaults act on every signal immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SignalTiming {
+    /// Consecutive bars an entry signal must agree on before a position is
+    /// opened (1 enters on the first bar)
+    pub persistence_bars: usize,
+    /// Bars a position is held before exit signals are acted on; protective
+    /// stops are not delayed
+    pub min_holding_bars: usize,
+    /// Bars after an exit during which no position is opened
+    pub cooldown_bars: usize,
+}
+
+impl Default for SignalTiming {
+    fn default() -> Self {
+        Self {
+            persistence_bars: 1,
+            min_holding_bars: 0,
+            cooldown_bars: 0,
+        }
+    }
+}
+
+/// Enforces a [`SignalTiming`] over one strategy run
+///
+/// Call [`SignalGate::entry`] on every bar the strategy is flat, and
+/// [`SignalGate::entered`] and [`SignalGate::exited`] when it opens and
+/// closes a position.
+///
+/// # Example
+///
+/// ```
+/// use rustalib::strategy::runtime::{SignalGate, SignalTiming};
+///
+/// let timing = SignalTiming { persistence_bars: 2, min_holding_bars: 3, cooldown_bars: 1 };
+/// let mut gate = SignalGate::new(timing);
+///
+/// assert!(!gate.entry(0, 1)); // first bar of a long signal
+/// assert!(gate.entry(1, 1)); // second bar in a row
+/// gate.entered(1);
+/// assert!(!gate.can_exit(3));
+/// assert!(gate.can_exit(4));
+/// gate.exited(4);
+/// assert!(!gate.entry(5, -1)); // cooling down
+/// assert!(gate.entry(6, -1)); // the signal persisted through the cooldown
+/// ```
+#[derive(Debug, Clone)]
+pub struct SignalGate {
+    timing: SignalTiming,
+    direction: i32,
+    streak: usize,
+    entry_bar: Option<usize>,
+    exit_bar: Option<usize>,
+}
+
+impl SignalGate {
+    /// Create a gate for a run that starts flat
+    pub fn new(timing: SignalTiming) -> Self {
+        Self {
+            timing,
+            direction: 0,
+            streak: 0,
+            entry_bar: None,
+            exit_bar: None,
+        }
+    }
+
+    /// Whether to act on the entry signal of a bar
+    ///
+    /// # Arguments
+    ///
+    /// * `bar` - Index of the bar
+    /// * `direction` - Direction of the raw signal: positive for long,
+    ///   negative for short, 0 for none; a change of direction restarts the
+    ///   persistence count
+    pub fn entry(&mut self, bar: usize, direction: i32) -> bool {
+        let direction = direction.signum();
+        if direction == 0 {
+            self.streak = 0;
+        } else if direction == self.direction {
+            self.streak += 1;
+        } else {
+            self.streak = 1;
+        }
+        self.direction = direction;
+
+        let cooling = self.timing.cooldown_bars > 0
+            && self
+                .exit_bar
+                .is_some_and(|exit| bar <= exit + self.timing.cooldown_bars);
+        direction != 0 && !cooling && self.streak >= self.timing.persistence_bars
+    }
+
+    /// Whether an exit signal may close the position at `bar`
+    pub fn can_exit(&self, bar: usize) -> bool {
+        self.entry_bar
+            .is_none_or(|entry| bar - entry >= self.timing.min_holding_bars)
+    }
+
+    /// Record that a position was opened at `bar`
+    pub fn entered(&mut self, bar: usize) {
+        self.entry_bar = Some(bar);
+        self.streak = 0;
+        self.direction = 0;
+    }
+
+    /// Record that the position was closed at `bar`
+    pub fn exited(&mut self, bar: usize) {
+        self.entry_bar = None;
+        self.exit_bar = Some(bar);
+        self.streak = 0;
+        self.direction = 0;
+    }
+}
@@ -0,0 +1,455 @@
+//! # Donchian Breakout (Turtle) Strategy
+//!
+//! The classic turtle trading rules. A position opens when price breaks out
+//! of the Donchian channel of the previous `entry_window` bars and closes
+//! when it breaks the opposite side of the shorter `exit_window` channel.
+//! Position size is set in units that risk `risk_per_unit` of equity per
+//! move of N, the Average True Range, and winners are pyramided with one
+//! more unit every `pyramid_step` N in their favor. Every unit shares a stop
+//! `stop_atr` N behind the latest fill.
+//!
+//! Orders are simulated as stop orders on OHLC bars: entries, adds, exits
+//! and stops fill at their level, or at the open when the bar gaps through
+//! it. Within a bar the stop and the exit are checked before any add.
+
+use crate::error::TaError;
+use crate::indicators::volatility::{calculate_atr, calculate_donchian_channels};
+use crate::performance::{RoundTripTrade, TradeSide};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::dataframe_utils::{column_nan_values, series_nan_values};
+use crate::util::time_utils::parse_timestamp_column;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Parameters for the Donchian breakout strategy
+///
+/// The defaults are the turtles' System 1 with the System 2 breakout as a
+/// failsafe. For System 2 alone, set `entry_window` to 55, `exit_window` to
+/// 20 and `skip_after_winner` to false.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyParams {
+    /// Column with bar timestamps
+    pub time_column: String,
+    /// Bars in the entry channel
+    pub entry_window: usize,
+    /// Bars in the failsafe channel, whose breakouts enter even after a
+    /// winning trade
+    pub failsafe_window: usize,
+    /// Bars in the exit channel
+    pub exit_window: usize,
+    /// Skip `entry_window` breakouts after a winning trade, entering only on
+    /// `failsafe_window` breakouts until the next losing trade
+    pub skip_after_winner: bool,
+    /// Bars in the ATR that measures N
+    pub atr_window: usize,
+    /// Fraction of equity lost by one unit on a move of N against it
+    pub risk_per_unit: f64,
+    /// Most units held at once
+    pub max_units: usize,
+    /// Favorable move in N from the latest fill that adds a unit
+    pub pyramid_step: f64,
+    /// Stop distance in N from the latest fill
+    pub stop_atr: f64,
+    /// Trade downside breakouts short
+    pub allow_short: bool,
+    /// Equity at the start, sizing the first unit
+    pub initial_capital: f64,
+    /// Commission per fill as a fraction of notional
+    pub fee_rate: f64,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            time_column: "timestamp".to_string(),
+            entry_window: 20,
+            failsafe_window: 55,
+            exit_window: 10,
+            skip_after_winner: true,
+            atr_window: 20,
+            risk_per_unit: 0.01,
+            max_units: 4,
+            pyramid_step: 0.5,
+            stop_atr: 2.0,
+            allow_short: true,
+            initial_capital: 100_000.0,
+            fee_rate: 0.0005,
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for StrategyParams {}
+
+/// Open position of one or more units
+struct TurtlePosition {
+    side: TradeSide,
+    entry: usize,
+    /// Shares per unit, fixed at the first entry
+    unit_size: f64,
+    units: usize,
+    /// N at the first entry, spacing the adds and the stop
+    n: f64,
+    last_fill: f64,
+    stop: f64,
+    /// Shares times price summed over the fills
+    cost: f64,
+    fees: f64,
+}
+
+impl TurtlePosition {
+    fn sign(&self) -> f64 {
+        if self.side == TradeSide::Long {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+
+    fn quantity(&self) -> f64 {
+        self.unit_size * self.units as f64
+    }
+
+    fn average_price(&self) -> f64 {
+        self.cost / self.quantity()
+    }
+
+    /// Record a filled unit and move the stop behind it
+    fn add(&mut self, price: f64, stop_atr: f64, fee_rate: f64) {
+        self.units += 1;
+        self.last_fill = price;
+        self.stop = price - self.sign() * stop_atr * self.n;
+        self.cost += self.unit_size * price;
+        self.fees += self.unit_size * price * fee_rate;
+    }
+
+    /// Open P&L at `price` after the fees paid so far
+    fn open_pnl(&self, price: f64) -> f64 {
+        self.sign() * (self.quantity() * price - self.cost) - self.fees
+    }
+}
+
+/// Run the Donchian breakout strategy
+///
+/// While flat, a high above the highest high of the previous `entry_window`
+/// bars buys one unit and a low below their lowest low sells one short
+/// (with `allow_short`). After a winning trade and with `skip_after_winner`,
+/// only breakouts of the `failsafe_window` channel enter, until a trade
+/// loses. A unit is `risk_per_unit` of equity divided by N, the ATR of the
+/// previous bar, in whole shares; its size is set at the first entry and
+/// kept for the adds.
+///
+/// While in a position, the stop is checked first, then the exit channel:
+/// a long exits on a low below the lowest low of the previous `exit_window`
+/// bars, a short on a high above their highest high. A position still open
+/// adds a unit for every `pyramid_step` N the price moves past the latest
+/// fill, up to `max_units`, and each add moves the stop for all units to
+/// `stop_atr` N behind the new fill. No position opens on the bar another
+/// one closed.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with "open", "high", "low" and "close" columns and
+///   the time column
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing StrategySignals where the buy and sell
+/// signals count unit orders per bar, and each trade is the round trip of a
+/// whole position at its average entry price. `indicator_values` holds the
+/// input with "donchian_entry_upper", "donchian_entry_lower",
+/// "donchian_exit_upper" and "donchian_exit_lower" (the channels of the
+/// bars before each bar), "turtle_n", "turtle_units" (positive long,
+/// negative short) and "turtle_stop" (NaN while flat); the channels are not
+/// key indicators for `OutputColumns`. `equity_curve` is realized plus open
+/// P&L after fees, without the initial capital. Parameters out of range
+/// fail with InvalidParameter naming the field.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::performance::TradeSide;
+/// use rustalib::strategy::stock::donchian_breakout::{run_strategy, StrategyParams};
+///
+/// // A range of 99.5 to 100.5, a rally of one point a bar, then a sell-off
+/// let close: Vec<f64> = (0..30)
+///     .map(|i| if i % 2 == 0 { 100.5 } else { 99.5 })
+///     .chain((0..30).map(|k| 101.0 + k as f64))
+///     .chain((0..10).map(|d| 127.0 - 3.0 * d as f64))
+///     .collect();
+/// let times: Vec<String> = (0..close.len()).map(|i| format!("2024-01-01 {:02}:{:02}:00", i / 60, i % 60)).collect();
+/// let df = df! {
+///     "timestamp" => times,
+///     "open" => &close,
+///     "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+///     "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+///     "close" => &close,
+/// }
+/// .unwrap();
+///
+/// let params = StrategyParams { fee_rate: 0.0, ..Default::default() };
+/// let signals = run_strategy(&df, &params).unwrap();
+///
+/// // N is 2, so each unit is 1% of 100,000 / 2 = 500 shares, bought at
+/// // 101.5 on the breakout and at 102.5, 103.5 and 104.5 on the way up
+/// let trade = &signals.trades[0];
+/// assert_eq!(trade.side, TradeSide::Long);
+/// assert_eq!(trade.quantity, 2000.0);
+/// assert_eq!(trade.entry_price, 103.0);
+/// // The sell-off gaps through the 10-bar low of 122 and exits at the open
+/// assert_eq!(trade.exit_price, 121.0);
+/// assert_eq!(trade.pnl, 36_000.0);
+/// ```
+pub fn run_strategy(df: &DataFrame, params: &StrategyParams) -> PolarsResult<StrategySignals> {
+    validate(params)?;
+    for col in ["open", "high", "low", "close", params.time_column.as_str()] {
+        if !df.schema().contains(col) {
+            return Err(PolarsError::ComputeError(
+                format!("Donchian breakout requires '{col}' column").into(),
+            ));
+        }
+    }
+
+    let n_bars = df.height();
+    let column = |name: &str| column_nan_values(df, name);
+    let (open, high, low, close) = (
+        column("open")?,
+        column("high")?,
+        column("low")?,
+        column("close")?,
+    );
+    let channel = |window: usize| -> PolarsResult<(Vec<f64>, Vec<f64>)> {
+        if window > n_bars {
+            return Ok((vec![f64::NAN; n_bars], vec![f64::NAN; n_bars]));
+        }
+        let (upper, lower, _) = calculate_donchian_channels(df, "high", "low", window)?;
+        Ok((series_nan_values(&upper)?, series_nan_values(&lower)?))
+    };
+    let (entry_upper, entry_lower) = channel(params.entry_window)?;
+    let (failsafe_upper, failsafe_lower) = channel(params.failsafe_window)?;
+    let (exit_upper, exit_lower) = channel(params.exit_window)?;
+    let atr = if params.atr_window > n_bars {
+        vec![f64::NAN; n_bars]
+    } else {
+        series_nan_values(&calculate_atr(df, params.atr_window)?)?
+    };
+    let timestamps = parse_timestamp_column(df, &params.time_column)?;
+
+    let mut buy_signals = vec![0; n_bars];
+    let mut sell_signals = vec![0; n_bars];
+    let mut units_held = vec![0i32; n_bars];
+    let mut stops = vec![f64::NAN; n_bars];
+    let mut equity = vec![f64::NAN; n_bars];
+    let mut exposure = vec![f64::NAN; n_bars];
+    let mut trades = Vec::new();
+    let mut realized = 0.0;
+    let mut last_trade_won = false;
+    let mut position: Option<TurtlePosition> = None;
+
+    for i in 1..n_bars {
+        let (o, h, l, c) = (open[i], high[i], low[i], close[i]);
+        let Some(time) = timestamps[i] else {
+            continue;
+        };
+        if [o, h, l, c].iter().any(|v| v.is_nan()) {
+            continue;
+        }
+        let mut order = |sign: f64| {
+            if sign > 0.0 {
+                buy_signals[i] += 1;
+            } else {
+                sell_signals[i] += 1;
+            }
+        };
+
+        let mut exited = false;
+        if let Some(pos) = position.as_mut() {
+            let exit_price = match pos.side {
+                TradeSide::Long if l <= pos.stop => Some(o.min(pos.stop)),
+                TradeSide::Long if l < exit_lower[i - 1] => Some(o.min(exit_lower[i - 1])),
+                TradeSide::Short if h >= pos.stop => Some(o.max(pos.stop)),
+                TradeSide::Short if h > exit_upper[i - 1] => Some(o.max(exit_upper[i - 1])),
+                _ => None,
+            };
+            if let Some(price) = exit_price {
+                order(-pos.sign());
+                let exit_fee = pos.quantity() * price * params.fee_rate;
+                let pnl = pos.open_pnl(price) - exit_fee;
+                trades.push(RoundTripTrade {
+                    entry_time: timestamps[pos.entry].unwrap_or(time),
+                    exit_time: time,
+                    side: pos.side,
+                    quantity: pos.quantity(),
+                    entry_price: pos.average_price(),
+                    exit_price: price,
+                    fees: pos.fees + exit_fee,
+                    pnl,
+                    return_pct: pnl / pos.cost * 100.0,
+                });
+                realized += pnl;
+                last_trade_won = pnl > 0.0;
+                exited = true;
+            } else {
+                let step = params.pyramid_step * pos.n;
+                while pos.units < params.max_units {
+                    let trigger = pos.last_fill + pos.sign() * step;
+                    let fill = match pos.side {
+                        TradeSide::Long if h >= trigger => o.max(trigger),
+                        TradeSide::Short if l <= trigger => o.min(trigger),
+                        _ => break,
+                    };
+                    order(pos.sign());
+                    pos.add(fill, params.stop_atr, params.fee_rate);
+                }
+            }
+        }
+        if exited {
+            position = None;
+        }
+
+        let n = atr[i - 1];
+        if position.is_none() && !exited && n > 0.0 {
+            let skip = params.skip_after_winner && last_trade_won;
+            let breakout = |channel: &[f64], above: bool| {
+                let level = channel[i - 1];
+                if above && h > level {
+                    Some(o.max(level))
+                } else if !above && l < level {
+                    Some(o.min(level))
+                } else {
+                    None
+                }
+            };
+            let long = if skip {
+                breakout(&failsafe_upper, true)
+            } else {
+                breakout(&entry_upper, true).or_else(|| breakout(&failsafe_upper, true))
+            };
+            let short = if skip {
+                breakout(&failsafe_lower, false)
+            } else {
+                breakout(&entry_lower, false).or_else(|| breakout(&failsafe_lower, false))
+            };
+            let entry = match (long, short.filter(|_| params.allow_short)) {
+                (Some(price), None) => Some((TradeSide::Long, price)),
+                (None, Some(price)) => Some((TradeSide::Short, price)),
+                // An outside bar breaking both ways gives no direction
+                _ => None,
+            };
+
+            let capital = params.initial_capital + realized;
+            let unit_size = (params.risk_per_unit * capital / n).floor();
+            if let Some((side, price)) = entry.filter(|_| unit_size > 0.0) {
+                let mut pos = TurtlePosition {
+                    side,
+                    entry: i,
+                    unit_size,
+                    units: 0,
+                    n,
+                    last_fill: price,
+                    stop: f64::NAN,
+                    cost: 0.0,
+                    fees: 0.0,
+                };
+                order(pos.sign());
+                pos.add(price, params.stop_atr, params.fee_rate);
+                position = Some(pos);
+            }
+        }
+
+        equity[i] = realized;
+        exposure[i] = 0.0;
+        if let Some(pos) = &position {
+            units_held[i] = pos.sign() as i32 * pos.units as i32;
+            stops[i] = pos.stop;
+            equity[i] += pos.open_pnl(c);
+            exposure[i] = pos.quantity() * c;
+        }
+    }
+
+    let indicator_values = build_indicator_values(
+        df,
+        &params.time_column,
+        "close",
+        vec![
+            (
+                Series::new("donchian_entry_upper".into(), shifted(&entry_upper)),
+                false,
+            ),
+            (
+                Series::new("donchian_entry_lower".into(), shifted(&entry_lower)),
+                false,
+            ),
+            (
+                Series::new("donchian_exit_upper".into(), shifted(&exit_upper)),
+                false,
+            ),
+            (
+                Series::new("donchian_exit_lower".into(), shifted(&exit_lower)),
+                false,
+            ),
+            (Series::new("turtle_n".into(), shifted(&atr)), true),
+            (Series::new("turtle_units".into(), units_held), true),
+            (Series::new("turtle_stop".into(), stops), true),
+        ],
+        params.output_columns,
+    )?;
+
+    let [equity_curve, drawdown, exposure] = equity_outputs(equity, exposure);
+    Ok(StrategySignals {
+        buy_signals,
+        sell_signals,
+        indicator_values,
+        trades,
+        equity_curve,
+        drawdown,
+        exposure,
+    })
+}
+
+/// Values of the previous bar, NaN on the first
+fn shifted(values: &[f64]) -> Vec<f64> {
+    std::iter::once(f64::NAN)
+        .chain(values.iter().copied())
+        .take(values.len())
+        .collect()
+}
+
+fn validate(params: &StrategyParams) -> PolarsResult<()> {
+    let invalid = |name: &str, reason: &str| -> PolarsResult<()> {
+        Err(TaError::invalid_parameter(name, reason).into())
+    };
+    for (name, window) in [
+        ("entry_window", params.entry_window),
+        ("failsafe_window", params.failsafe_window),
+        ("exit_window", params.exit_window),
+        ("atr_window", params.atr_window),
+    ] {
+        if window == 0 {
+            return invalid(name, "must be positive");
+        }
+    }
+    if params.risk_per_unit <= 0.0 || params.risk_per_unit > 1.0 {
+        return invalid("risk_per_unit", "must lie in (0, 1]");
+    }
+    if params.max_units == 0 {
+        return invalid("max_units", "must be at least 1");
+    }
+    if params.pyramid_step <= 0.0 {
+        return invalid("pyramid_step", "must be positive");
+    }
+    if params.stop_atr <= 0.0 {
+        return invalid("stop_atr", "must be positive");
+    }
+    if params.initial_capital <= 0.0 {
+        return invalid("initial_capital", "must be positive");
+    }
+    Ok(())
+}
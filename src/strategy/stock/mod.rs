@@ -0,0 +1,11 @@
+//! # Stock Strategies
+//!
+//! Strategies designed for listed equities.
+//!
+//! - [`donchian_breakout`](donchian_breakout/index.html): Turtle trading rules on Donchian channel breakouts
+//! - [`momentum_rotation`](momentum_rotation/index.html): Cross-sectional momentum across a universe of stocks
+//! - [`pairs_trading`](pairs_trading/index.html): Statistical arbitrage on the spread of two co-moving stocks
+
+pub mod donchian_breakout;
+pub mod momentum_rotation;
+pub mod pairs_trading;
@@ -0,0 +1,523 @@
+//! # Momentum Rotation Strategy
+//!
+//! Cross-sectional momentum across a universe of stocks. On every rebalance
+//! date the tickers are ranked by their returns over several lookbacks
+//! (3, 6 and 12 months by default), the best `top_n` are held with equal,
+//! inverse-volatility, minimum-variance or risk-parity weights, and the rest
+//! are sold. The turnover of every rebalance is reported, as it drives the
+//! trading costs of rotation.
+
+use crate::performance::{RoundTripTrade, TradeSide};
+use crate::portfolio::allocation::{min_variance_weights, risk_parity_weights};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Price history of one ticker of the universe
+#[derive(Debug, Clone, Copy)]
+pub struct UniverseMember<'a> {
+    /// Ticker, used for column names
+    pub ticker: &'a str,
+    /// Price data for this ticker
+    pub data: &'a DataFrame,
+}
+
+impl<'a> UniverseMember<'a> {
+    /// Create a member
+    pub fn new(ticker: &'a str, data: &'a DataFrame) -> Self {
+        Self { ticker, data }
+    }
+}
+
+/// How the selected tickers are weighted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationWeighting {
+    /// The same weight for every holding
+    #[default]
+    Equal,
+    /// Weights proportional to the inverse standard deviation of each
+    /// holding's bar returns over `volatility_window`, so every holding
+    /// contributes similar risk
+    InverseVolatility,
+    /// Long-only minimum-variance weights of the holdings' bar returns over
+    /// `volatility_window`
+    MinVariance,
+    /// Risk-parity weights of the holdings' bar returns over
+    /// `volatility_window`, with equal contributions to portfolio variance
+    /// including correlations
+    RiskParity,
+}
+
+/// Parameters for the momentum rotation strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyParams {
+    /// Column with timestamps in every price DataFrame
+    pub time_column: String,
+    /// Column with prices in every price DataFrame
+    pub price_column: String,
+    /// Return lookbacks in bars; the defaults are 3, 6 and 12 months of
+    /// daily bars
+    pub lookbacks: Vec<usize>,
+    /// Number of tickers held
+    pub top_n: usize,
+    /// Bars between rebalances
+    pub rebalance_every: usize,
+    /// Weighting of the holdings
+    pub weighting: RotationWeighting,
+    /// Bar returns in the risk estimates of weightings other than equal
+    pub volatility_window: usize,
+    /// Equity at the start
+    pub initial_capital: f64,
+    /// Commission per order as a fraction of notional
+    pub fee_rate: f64,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            time_column: "timestamp".to_string(),
+            price_column: "close".to_string(),
+            lookbacks: vec![63, 126, 252],
+            top_n: 3,
+            rebalance_every: 21,
+            weighting: RotationWeighting::Equal,
+            volatility_window: 63,
+            initial_capital: 100_000.0,
+            fee_rate: 0.0005,
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for StrategyParams {}
+
+/// Turnover of a momentum rotation backtest
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TurnoverSummary {
+    /// Number of rebalances
+    pub rebalances: usize,
+    /// Turnover summed over the rebalances
+    pub total: f64,
+    /// Mean turnover per rebalance, 0 without rebalances
+    pub average: f64,
+}
+
+/// Holding of one ticker since it entered the portfolio
+#[derive(Default)]
+struct Holding {
+    entry: usize,
+    shares: f64,
+    /// Average price paid for the shares held
+    cost_basis: f64,
+    /// P&L of shares sold at rebalances that kept the ticker, and all fees
+    realized: f64,
+    fees: f64,
+}
+
+/// Run the momentum rotation strategy
+///
+/// The price series are aligned on the timestamps present for every
+/// ticker. The first rebalance is on the first bar with a full longest
+/// lookback, then every `rebalance_every` bars, trading at that bar's
+/// prices. Each lookback ranks the tickers by their return over it, and the
+/// `top_n` tickers with the best mean rank are held; ties go to the ticker
+/// listed first. Weightings other than equal fall back to equal weights
+/// when the holdings' risk cannot be estimated, e.g. with fewer than two
+/// returns or a holding whose price did not move.
+///
+/// Turnover is the sum of the absolute weight changes at a rebalance, so
+/// the initial investment counts 1 and replacing every holding counts 2.
+///
+/// # Arguments
+///
+/// * `universe` - Price data of the tickers (at least `top_n`)
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing StrategySignals indexed by aligned bar.
+/// The buy and sell signals count orders per bar. `indicator_values` holds
+/// "timestamp" and a "{ticker}_close" column per ticker, then the key
+/// indicators "{ticker}_weight" (weight after each bar's trades) and
+/// "rotation_turnover" (0 between rebalances). Each trade is one stay of a
+/// ticker in the portfolio, from its first purchase to the rebalance that
+/// sells it; resizes in between are folded into its P&L and fees.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::strategy::stock::momentum_rotation::{
+///     rotation_turnover, run_strategy, StrategyParams, UniverseMember,
+/// };
+///
+/// let times: Vec<String> = (0..30).map(|i| format!("2024-01-01 00:{i:02}:00")).collect();
+/// let prices = |growth: f64| -> DataFrame {
+///     let close: Vec<f64> = (0..30).map(|i| 100.0 * growth.powi(i)).collect();
+///     df! { "timestamp" => &times, "close" => close }.unwrap()
+/// };
+/// let (rising, flat, falling) = (prices(1.01), prices(1.0), prices(0.99));
+/// let universe = [
+///     UniverseMember::new("AAA", &rising),
+///     UniverseMember::new("BBB", &flat),
+///     UniverseMember::new("CCC", &falling),
+/// ];
+///
+/// let params = StrategyParams {
+///     lookbacks: vec![5, 10],
+///     top_n: 2,
+///     rebalance_every: 5,
+///     fee_rate: 0.0,
+///     ..Default::default()
+/// };
+/// let signals = run_strategy(&universe, &params).unwrap();
+/// let weight = |ticker: &str, bar: usize| {
+///     let name = format!("{ticker}_weight");
+///     signals.indicator_values.column(&name).unwrap().f64().unwrap().get(bar).unwrap()
+/// };
+/// // The two strongest tickers are held from the first rebalance on
+/// assert!((weight("AAA", 10) - 0.5).abs() < 1e-9);
+/// assert!((weight("BBB", 10) - 0.5).abs() < 1e-9);
+/// assert_eq!(weight("CCC", 10), 0.0);
+///
+/// let turnover = rotation_turnover(&signals).unwrap();
+/// assert_eq!(turnover.rebalances, 4);
+/// // The initial investment, then small trims of the rising holding
+/// assert!(turnover.total > 1.0 && turnover.total < 1.1);
+/// ```
+pub fn run_strategy(
+    universe: &[UniverseMember],
+    params: &StrategyParams,
+) -> PolarsResult<StrategySignals> {
+    validate(universe, params)?;
+    let (times, prices) = aligned_prices(universe, params)?;
+    let (n, k) = (times.len(), universe.len());
+    let longest = params.lookbacks.iter().copied().max().unwrap_or(0);
+
+    let mut buy_signals = vec![0; n];
+    let mut sell_signals = vec![0; n];
+    let mut weights = vec![vec![0.0; n]; k];
+    let mut turnover = vec![0.0; n];
+    let mut equity = vec![0.0; n];
+    let mut exposure = vec![0.0; n];
+    let mut trades = Vec::new();
+    let mut cash = params.initial_capital;
+    let mut holdings: Vec<Option<Holding>> = (0..k).map(|_| None).collect();
+
+    for i in 0..n {
+        let value = |holdings: &[Option<Holding>]| -> f64 {
+            holdings
+                .iter()
+                .enumerate()
+                .filter_map(|(t, h)| Some(h.as_ref()?.shares * prices[t][i]))
+                .sum()
+        };
+
+        if i >= longest && (i - longest) % params.rebalance_every == 0 {
+            let targets = target_weights(&prices, i, params);
+            let total = cash + value(&holdings);
+            for (t, target) in targets.into_iter().enumerate() {
+                let price = prices[t][i];
+                let held = holdings[t].as_ref().map_or(0.0, |h| h.shares);
+                turnover[i] += (target - held * price / total).abs();
+                let traded = target * total / price - held;
+                if traded == 0.0 {
+                    continue;
+                }
+
+                let fee = traded.abs() * price * params.fee_rate;
+                cash -= traded * price + fee;
+                if traded > 0.0 {
+                    buy_signals[i] += 1;
+                } else {
+                    sell_signals[i] += 1;
+                }
+
+                let holding = holdings[t].get_or_insert_with(|| Holding {
+                    entry: i,
+                    ..Holding::default()
+                });
+                holding.fees += fee;
+                holding.realized -= fee;
+                if traded > 0.0 {
+                    holding.cost_basis = (holding.cost_basis * holding.shares + traded * price)
+                        / (holding.shares + traded);
+                    holding.shares += traded;
+                } else if target > 0.0 {
+                    holding.realized += -traded * (price - holding.cost_basis);
+                    holding.shares += traded;
+                } else {
+                    let pnl = holding.realized + holding.shares * (price - holding.cost_basis);
+                    trades.push(RoundTripTrade {
+                        entry_time: times[holding.entry],
+                        exit_time: times[i],
+                        side: TradeSide::Long,
+                        quantity: holding.shares,
+                        entry_price: holding.cost_basis,
+                        exit_price: price,
+                        fees: holding.fees,
+                        pnl,
+                        return_pct: pnl / (holding.cost_basis * holding.shares) * 100.0,
+                    });
+                    holdings[t] = None;
+                }
+            }
+        }
+
+        let invested = value(&holdings);
+        let total = cash + invested;
+        for (t, holding) in holdings.iter().enumerate() {
+            if let Some(holding) = holding {
+                weights[t][i] = holding.shares * prices[t][i] / total;
+            }
+        }
+        equity[i] = total - params.initial_capital;
+        exposure[i] = invested;
+    }
+
+    let aligned = DataFrame::new(
+        std::iter::once(Column::new(
+            "timestamp".into(),
+            times
+                .iter()
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                .collect::<Vec<_>>(),
+        ))
+        .chain(
+            universe
+                .iter()
+                .zip(&prices)
+                .map(|(member, p)| Column::new(format!("{}_close", member.ticker).into(), p)),
+        )
+        .collect(),
+    )?;
+    let mut columns: Vec<(Series, bool)> = universe
+        .iter()
+        .zip(weights)
+        .map(|(member, w)| {
+            (
+                Series::new(format!("{}_weight", member.ticker).into(), w),
+                true,
+            )
+        })
+        .collect();
+    columns.push((Series::new("rotation_turnover".into(), turnover), true));
+    let indicator_values = build_indicator_values(
+        &aligned,
+        "timestamp",
+        "close",
+        columns,
+        params.output_columns,
+    )?;
+
+    let [equity_curve, drawdown, exposure] = equity_outputs(equity, exposure);
+    Ok(StrategySignals {
+        buy_signals,
+        sell_signals,
+        indicator_values,
+        trades,
+        equity_curve,
+        drawdown,
+        exposure,
+    })
+}
+
+/// Summarize the turnover of a momentum rotation backtest
+///
+/// # Arguments
+///
+/// * `signals` - Output of [`run_strategy`] with the "rotation_turnover"
+///   column retained (`OutputColumns::All` or `KeyIndicators`)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`TurnoverSummary`]; rebalances
+/// are the bars with orders or a non-zero turnover
+pub fn rotation_turnover(signals: &StrategySignals) -> PolarsResult<TurnoverSummary> {
+    let turnover = signals
+        .indicator_values
+        .column("rotation_turnover")
+        .map_err(|_| {
+            PolarsError::ComputeError(
+                "Rotation turnover requires the 'rotation_turnover' indicator column".into(),
+            )
+        })?
+        .f64()?
+        .clone();
+
+    let mut summary = TurnoverSummary::default();
+    for (i, value) in turnover.into_iter().enumerate() {
+        let value = value.unwrap_or(0.0);
+        let traded = signals.buy_signals.get(i).is_some_and(|&b| b > 0)
+            || signals.sell_signals.get(i).is_some_and(|&s| s > 0);
+        if traded || value > 0.0 {
+            summary.rebalances += 1;
+            summary.total += value;
+        }
+    }
+    if summary.rebalances > 0 {
+        summary.average = summary.total / summary.rebalances as f64;
+    }
+    Ok(summary)
+}
+
+/// Target weight of every ticker at rebalance bar `i`
+fn target_weights(prices: &[Vec<f64>], i: usize, params: &StrategyParams) -> Vec<f64> {
+    let k = prices.len();
+    let mut rank_sum = vec![0.0; k];
+    for &lookback in &params.lookbacks {
+        let returns: Vec<f64> = prices.iter().map(|p| p[i] / p[i - lookback]).collect();
+        let mut order: Vec<usize> = (0..k).collect();
+        order.sort_by(|&a, &b| returns[b].total_cmp(&returns[a]));
+        for (rank, t) in order.into_iter().enumerate() {
+            rank_sum[t] += rank as f64;
+        }
+    }
+    let mut order: Vec<usize> = (0..k).collect();
+    order.sort_by(|&a, &b| rank_sum[a].total_cmp(&rank_sum[b]));
+    let selected = &order[..params.top_n];
+
+    let estimated: Option<Vec<f64>> = match params.weighting {
+        RotationWeighting::Equal => None,
+        RotationWeighting::InverseVolatility => Some(
+            selected
+                .iter()
+                .map(|&t| 1.0 / volatility(&window_returns(&prices[t], i, params)))
+                .collect(),
+        ),
+        RotationWeighting::MinVariance => selected_returns(prices, selected, i, params)
+            .and_then(|returns| min_variance_weights(&returns, true))
+            .ok()
+            .map(|portfolio| portfolio.weights),
+        RotationWeighting::RiskParity => selected_returns(prices, selected, i, params)
+            .and_then(|returns| risk_parity_weights(&returns))
+            .ok()
+            .map(|portfolio| portfolio.weights),
+    };
+    let raw = estimated
+        .filter(|w| w.iter().all(|w| w.is_finite() && *w >= 0.0) && w.iter().sum::<f64>() > 0.0)
+        .unwrap_or_else(|| vec![1.0; selected.len()]);
+
+    let sum: f64 = raw.iter().sum();
+    let mut weights = vec![0.0; k];
+    for (&t, w) in selected.iter().zip(raw) {
+        weights[t] = w / sum;
+    }
+    weights
+}
+
+/// Bar returns over the `volatility_window` bars ending at bar `i`
+fn window_returns(prices: &[f64], i: usize, params: &StrategyParams) -> Vec<f64> {
+    let start = i.saturating_sub(params.volatility_window);
+    prices[start..=i]
+        .windows(2)
+        .map(|w| w[1] / w[0] - 1.0)
+        .collect()
+}
+
+/// Window returns of the selected tickers, one column each
+fn selected_returns(
+    prices: &[Vec<f64>],
+    selected: &[usize],
+    i: usize,
+    params: &StrategyParams,
+) -> PolarsResult<DataFrame> {
+    DataFrame::new(
+        selected
+            .iter()
+            .map(|&t| Column::new(t.to_string().into(), window_returns(&prices[t], i, params)))
+            .collect(),
+    )
+}
+
+/// Sample standard deviation of `returns`, NaN without two returns
+fn volatility(returns: &[f64]) -> f64 {
+    if returns.len() < 2 {
+        return f64::NAN;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64).sqrt()
+}
+
+fn validate(universe: &[UniverseMember], params: &StrategyParams) -> PolarsResult<()> {
+    let error = |message: &str| Err(PolarsError::ComputeError(message.to_string().into()));
+    if params.top_n == 0 {
+        return error("Momentum rotation must hold at least one ticker");
+    }
+    if universe.len() < params.top_n {
+        return error("Momentum rotation universe is smaller than the number of holdings");
+    }
+    if params.lookbacks.is_empty() || params.lookbacks.contains(&0) {
+        return error("Momentum rotation lookbacks must be positive");
+    }
+    if params.rebalance_every == 0 {
+        return error("Momentum rotation rebalance interval must be positive");
+    }
+    if params.volatility_window < 2 {
+        return error("Momentum rotation volatility window must be at least 2");
+    }
+    if params.initial_capital <= 0.0 {
+        return error("Momentum rotation initial capital must be positive");
+    }
+    Ok(())
+}
+
+/// Common timestamps and the matching prices of every ticker
+type AlignedPrices = (Vec<DateTime<Utc>>, Vec<Vec<f64>>);
+
+/// Timestamps present for every ticker, with each ticker's price at those times
+fn aligned_prices(
+    universe: &[UniverseMember],
+    params: &StrategyParams,
+) -> PolarsResult<AlignedPrices> {
+    let mut by_time: BTreeMap<DateTime<Utc>, Vec<Option<f64>>> = BTreeMap::new();
+    for (t, member) in universe.iter().enumerate() {
+        for col in [params.time_column.as_str(), params.price_column.as_str()] {
+            if !member.data.schema().contains(col) {
+                return Err(PolarsError::ComputeError(
+                    format!("Ticker '{}' requires '{col}' column", member.ticker).into(),
+                ));
+            }
+        }
+        let times = parse_timestamp_column(member.data, &params.time_column)?;
+        let price = member
+            .data
+            .column(&params.price_column)?
+            .cast(&DataType::Float64)?;
+        let price = price.f64()?;
+
+        for (i, time) in times.into_iter().enumerate() {
+            let (Some(time), Some(p)) = (time, price.get(i)) else {
+                continue;
+            };
+            if p.is_nan() || p <= 0.0 {
+                continue;
+            }
+            by_time
+                .entry(time)
+                .or_insert_with(|| vec![None; universe.len()])[t] = Some(p);
+        }
+    }
+
+    let mut times = Vec::new();
+    let mut prices = vec![Vec::new(); universe.len()];
+    for (time, row) in by_time {
+        if row.iter().all(|p| p.is_some()) {
+            times.push(time);
+            for (t, p) in row.into_iter().enumerate() {
+                prices[t].push(p.unwrap_or(f64::NAN));
+            }
+        }
+    }
+
+    Ok((times, prices))
+}
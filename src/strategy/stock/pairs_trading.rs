@@ -0,0 +1,571 @@
+//! # Pairs Trading Strategy
+//!
+//! Statistical arbitrage between two co-moving stocks. The hedge ratio of
+//! stock A on stock B is estimated by a rolling least-squares regression or
+//! a Kalman filter, and the spread `A - hedge_ratio * B - intercept` is
+//! traded when its z-score stretches away from zero: a rich spread is sold
+//! (short A, long B) and a cheap one bought (long A, short B), expecting it
+//! to revert. Each position holds both legs, sized at entry and kept until
+//! exit.
+
+use crate::indicators::state_space::kalman_regression;
+pub use crate::indicators::stats::estimate_half_life;
+use crate::performance::{RoundTripTrade, TradeSide};
+use crate::strategy::runtime::{SignalGate, SignalTiming};
+use crate::strategy::{
+    build_indicator_values, equity_outputs, OutputColumns, ParamsFile, StrategySignals,
+};
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How the hedge ratio of A on B is estimated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HedgeRatioMethod {
+    /// Least-squares regression of A on B over the `hedge_window` bars
+    /// before each bar
+    #[default]
+    RollingOls,
+    /// Kalman filter tracking the hedge ratio and intercept as a random walk
+    Kalman,
+}
+
+/// Parameters for the pairs trading strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyParams {
+    /// Column with timestamps in both price DataFrames
+    pub time_column: String,
+    /// Column with prices in both price DataFrames
+    pub price_column: String,
+    /// Hedge ratio estimator
+    pub hedge_method: HedgeRatioMethod,
+    /// Bars in each rolling regression; with the Kalman filter, bars of
+    /// warm-up before its estimates are used
+    pub hedge_window: usize,
+    /// Kalman filter: variance of the hedge ratio's random walk relative to
+    /// the observation noise; larger values adapt faster
+    pub kalman_delta: f64,
+    /// Kalman filter: variance of the observation noise, in price units squared
+    pub kalman_observation_variance: f64,
+    /// Number of bars for the spread z-score and half-life
+    pub zscore_window: usize,
+    /// Absolute spread z-score required to enter
+    pub entry_zscore: f64,
+    /// Exit once the z-score has reverted to within this distance of zero
+    pub exit_zscore: f64,
+    /// Exit when the absolute z-score widens to this level, and do not
+    /// enter beyond it
+    pub stop_zscore: Option<f64>,
+    /// Enter only while the estimated half-life of the spread, in bars, is
+    /// at most this long
+    pub max_half_life: Option<f64>,
+    /// Shares of stock A per trade; stock B is sized by the hedge ratio
+    pub order_size: f64,
+    /// Commission per leg as a fraction of notional
+    pub fee_rate: f64,
+    /// Persistence, minimum holding and cooldown requirements; the z-score
+    /// stop is not delayed by the minimum holding period
+    pub timing: SignalTiming,
+    /// Columns retained in `indicator_values`
+    pub output_columns: OutputColumns,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            time_column: "timestamp".to_string(),
+            price_column: "close".to_string(),
+            hedge_method: HedgeRatioMethod::RollingOls,
+            hedge_window: 60,
+            kalman_delta: 1e-4,
+            kalman_observation_variance: 1e-3,
+            zscore_window: 20,
+            entry_zscore: 2.0,
+            exit_zscore: 0.5,
+            stop_zscore: Some(4.0),
+            max_half_life: None,
+            order_size: 100.0,
+            fee_rate: 0.0005,
+            timing: SignalTiming::default(),
+            output_columns: OutputColumns::All,
+        }
+    }
+}
+
+impl ParamsFile for StrategyParams {}
+
+/// P&L of a pairs trading backtest split by leg, in currency
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PairPerformance {
+    /// Price P&L of the stock A leg
+    pub leg_a: f64,
+    /// Price P&L of the stock B leg
+    pub leg_b: f64,
+    /// Commissions paid on both legs
+    pub costs: f64,
+    /// `leg_a + leg_b - costs`
+    pub total: f64,
+    /// P&L of the closed long-spread trades, net of costs
+    pub long_spread_pnl: f64,
+    /// P&L of the closed short-spread trades, net of costs
+    pub short_spread_pnl: f64,
+    /// Number of closed trades
+    pub trades: usize,
+    /// Share of closed trades with a positive P&L, in percent
+    pub win_rate: f64,
+    /// Number of bars a position was held at the close of the bar
+    pub bars_in_market: usize,
+}
+
+/// Open two-legged position
+struct OpenPair {
+    entry: usize,
+    side: TradeSide,
+    hedge_ratio: f64,
+    /// Signed shares of A and B
+    quantity_a: f64,
+    quantity_b: f64,
+    entry_fees: f64,
+}
+
+/// Run the pairs trading strategy
+///
+/// The two price series are aligned on their common timestamps. On every
+/// bar the hedge ratio and intercept are estimated from earlier bars only,
+/// and the spread is A's price less the hedged B price. Its z-score is taken
+/// over the last `zscore_window` spread values and its half-life is
+/// estimated over the same values with [`estimate_half_life`].
+///
+/// While flat, an absolute z-score of at least `entry_zscore` (and below
+/// `stop_zscore`, with a half-life no longer than `max_half_life`) enters
+/// against the move: short the spread when it is rich, long when it is
+/// cheap. A position is `order_size` shares of A and `hedge_ratio *
+/// order_size` shares of B the other way, filled at the bar's prices. It
+/// exits when the z-score reverts to `exit_zscore` from zero or past it, or
+/// widens to `stop_zscore`; a position stopped out is not re-entered on the
+/// same bar.
+///
+/// # Arguments
+///
+/// * `first` - Price data of stock A
+/// * `second` - Price data of stock B
+/// * `params` - Strategy parameters
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing StrategySignals indexed by aligned bar.
+/// The buy and sell signals count leg orders, so entering a pair with a
+/// positive hedge ratio is one buy and one sell. `indicator_values` holds
+/// "timestamp", "price_a" and "price_b" plus "pair_hedge_ratio",
+/// "pair_intercept", "pair_spread", "pair_zscore", "pair_half_life",
+/// "pair_position" (1 long spread, -1 short, 0 flat) and the per-bar leg
+/// P&L columns "pair_pnl_a", "pair_pnl_b" and "pair_costs" read by
+/// [`pair_performance`] (all but the intercept are key indicators for
+/// `OutputColumns`). Each trade is a round trip of one unit of the spread,
+/// priced at `A - hedge_ratio * B` with the hedge ratio fixed at entry.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::strategy::stock::pairs_trading::{pair_performance, run_strategy, StrategyParams};
+///
+/// // A tracks 2 * B + 5 with a spread oscillating every few bars
+/// let times: Vec<String> = (0..100).map(|i| format!("2024-01-01 {:02}:{:02}:00", i / 60, i % 60)).collect();
+/// let b: Vec<f64> = (0..100).map(|i| 50.0 + 5.0 * (i as f64 / 17.0).sin() + 0.05 * i as f64).collect();
+/// let a: Vec<f64> = (0..100).map(|i| 2.0 * b[i] + 5.0 + (i as f64 * 1.3).sin()).collect();
+/// let first = df! { "timestamp" => &times, "close" => a }.unwrap();
+/// let second = df! { "timestamp" => &times, "close" => b }.unwrap();
+///
+/// let params = StrategyParams {
+///     hedge_window: 40,
+///     zscore_window: 10,
+///     entry_zscore: 1.2,
+///     exit_zscore: 0.0,
+///     fee_rate: 0.0,
+///     ..Default::default()
+/// };
+/// let signals = run_strategy(&first, &second, &params).unwrap();
+/// let hedge = signals.indicator_values.column("pair_hedge_ratio").unwrap().f64().unwrap();
+/// assert!((hedge.get(60).unwrap() - 2.0).abs() < 0.05);
+///
+/// let performance = pair_performance(&signals).unwrap();
+/// assert_eq!(performance.trades, 12);
+/// assert_eq!(performance.win_rate, 100.0);
+/// assert!(performance.total > 0.0);
+/// ```
+pub fn run_strategy(
+    first: &DataFrame,
+    second: &DataFrame,
+    params: &StrategyParams,
+) -> PolarsResult<StrategySignals> {
+    validate(params)?;
+    let (times, a, b) = aligned_pair(first, second, params)?;
+    let n = times.len();
+
+    let (hedge_ratio, intercept) = match params.hedge_method {
+        HedgeRatioMethod::RollingOls => rolling_ols(&a, &b, params.hedge_window),
+        HedgeRatioMethod::Kalman => kalman_filter(&a, &b, params)?,
+    };
+    let spread: Vec<f64> = (0..n)
+        .map(|i| a[i] - hedge_ratio[i] * b[i] - intercept[i])
+        .collect();
+
+    let window = params.zscore_window;
+    let mut zscore = vec![f64::NAN; n];
+    let mut half_life = vec![f64::NAN; n];
+    for i in window.saturating_sub(1)..n {
+        let history = &spread[i + 1 - window..=i];
+        if history.iter().any(|s| s.is_nan()) {
+            continue;
+        }
+        let mean = history.iter().sum::<f64>() / window as f64;
+        let std =
+            (history.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (window - 1) as f64).sqrt();
+        if std > 0.0 {
+            zscore[i] = (spread[i] - mean) / std;
+        }
+        half_life[i] = estimate_half_life(history);
+    }
+
+    let mut buy_signals = vec![0; n];
+    let mut sell_signals = vec![0; n];
+    let mut position_state = vec![0i32; n];
+    let mut pnl_a = vec![0.0; n];
+    let mut pnl_b = vec![0.0; n];
+    let mut costs = vec![0.0; n];
+    let mut equity = vec![0.0; n];
+    let mut exposure = vec![0.0; n];
+    let mut trades = Vec::new();
+    let mut realized = 0.0;
+    let mut position: Option<OpenPair> = None;
+    let mut gate = SignalGate::new(params.timing);
+
+    let mut order = |i: usize, quantity: f64| {
+        if quantity > 0.0 {
+            buy_signals[i] += 1;
+        } else if quantity < 0.0 {
+            sell_signals[i] += 1;
+        }
+    };
+    let fees = |i: usize, quantity_a: f64, quantity_b: f64| {
+        (quantity_a.abs() * a[i] + quantity_b.abs() * b[i]) * params.fee_rate
+    };
+
+    for i in 0..n {
+        let z = zscore[i];
+        let mut stopped = false;
+
+        if let Some(pos) = &position {
+            if i > pos.entry {
+                pnl_a[i] = pos.quantity_a * (a[i] - a[i - 1]);
+                pnl_b[i] = pos.quantity_b * (b[i] - b[i - 1]);
+            }
+            stopped = params.stop_zscore.is_some_and(|stop| z.abs() >= stop);
+            let reverted = match pos.side {
+                TradeSide::Long => z >= -params.exit_zscore,
+                TradeSide::Short => z <= params.exit_zscore,
+            };
+            if stopped || (reverted && gate.can_exit(i)) {
+                let exit_fees = fees(i, pos.quantity_a, pos.quantity_b);
+                costs[i] += exit_fees;
+                order(i, -pos.quantity_a);
+                order(i, -pos.quantity_b);
+                let trade = pair_trade(&times, &a, &b, pos, i, exit_fees, params.order_size);
+                realized += trade.pnl;
+                trades.push(trade);
+                position = None;
+                gate.exited(i);
+            }
+        }
+
+        let tradable = z.abs() >= params.entry_zscore
+            && params.stop_zscore.is_none_or(|stop| z.abs() < stop)
+            && params.max_half_life.is_none_or(|max| half_life[i] <= max)
+            && hedge_ratio[i].is_finite();
+        let (side, sign) = if z > 0.0 {
+            (TradeSide::Short, -1.0)
+        } else {
+            (TradeSide::Long, 1.0)
+        };
+        if position.is_none() && gate.entry(i, if tradable { sign as i32 } else { 0 }) && !stopped {
+            gate.entered(i);
+            let quantity_a = sign * params.order_size;
+            let quantity_b = -sign * hedge_ratio[i] * params.order_size;
+            let entry_fees = fees(i, quantity_a, quantity_b);
+            costs[i] += entry_fees;
+            order(i, quantity_a);
+            order(i, quantity_b);
+            position = Some(OpenPair {
+                entry: i,
+                side,
+                hedge_ratio: hedge_ratio[i],
+                quantity_a,
+                quantity_b,
+                entry_fees,
+            });
+        }
+
+        equity[i] = realized;
+        if let Some(pos) = &position {
+            position_state[i] = if pos.side == TradeSide::Long { 1 } else { -1 };
+            equity[i] += pos.quantity_a * (a[i] - a[pos.entry])
+                + pos.quantity_b * (b[i] - b[pos.entry])
+                - pos.entry_fees;
+            exposure[i] = pos.quantity_a.abs() * a[i] + pos.quantity_b.abs() * b[i];
+        }
+    }
+
+    let aligned = DataFrame::new(vec![
+        Column::new(
+            "timestamp".into(),
+            times
+                .iter()
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                .collect::<Vec<_>>(),
+        ),
+        Column::new("price_a".into(), &a),
+        Column::new("price_b".into(), &b),
+    ])?;
+    let indicator_values = build_indicator_values(
+        &aligned,
+        "timestamp",
+        "price_a",
+        vec![
+            (Series::new("pair_hedge_ratio".into(), hedge_ratio), true),
+            (Series::new("pair_intercept".into(), intercept), false),
+            (Series::new("pair_spread".into(), spread), true),
+            (Series::new("pair_zscore".into(), zscore), true),
+            (Series::new("pair_half_life".into(), half_life), true),
+            (Series::new("pair_position".into(), position_state), true),
+            (Series::new("pair_pnl_a".into(), pnl_a), true),
+            (Series::new("pair_pnl_b".into(), pnl_b), true),
+            (Series::new("pair_costs".into(), costs), true),
+        ],
+        params.output_columns,
+    )?;
+
+    let [equity_curve, drawdown, exposure] = equity_outputs(equity, exposure);
+    Ok(StrategySignals {
+        buy_signals,
+        sell_signals,
+        indicator_values,
+        trades,
+        equity_curve,
+        drawdown,
+        exposure,
+    })
+}
+
+/// Split the P&L of a pairs trading backtest by leg and direction
+///
+/// # Arguments
+///
+/// * `signals` - Output of [`run_strategy`] with the leg P&L columns
+///   retained (`OutputColumns::All` or `KeyIndicators`)
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing the [`PairPerformance`]; `total`
+/// equals the summed P&L of all closed trades plus the open P&L of a
+/// position still held at the end
+pub fn pair_performance(signals: &StrategySignals) -> PolarsResult<PairPerformance> {
+    let column = |name: &str| -> PolarsResult<Column> {
+        signals.indicator_values.column(name).cloned().map_err(|_| {
+            PolarsError::ComputeError(
+                format!("Pair performance requires the '{name}' indicator column").into(),
+            )
+        })
+    };
+    let sum = |name: &str| -> PolarsResult<f64> {
+        Ok(column(name)?
+            .f64()?
+            .into_iter()
+            .flatten()
+            .filter(|v| !v.is_nan())
+            .sum())
+    };
+
+    let closed = |side: TradeSide| -> f64 {
+        signals
+            .trades
+            .iter()
+            .filter(|t| t.side == side)
+            .fold(0.0, |total, t| total + t.pnl)
+    };
+    let winners = signals.trades.iter().filter(|t| t.pnl > 0.0).count();
+
+    let mut performance = PairPerformance {
+        leg_a: sum("pair_pnl_a")?,
+        leg_b: sum("pair_pnl_b")?,
+        costs: sum("pair_costs")?,
+        total: 0.0,
+        long_spread_pnl: closed(TradeSide::Long),
+        short_spread_pnl: closed(TradeSide::Short),
+        trades: signals.trades.len(),
+        win_rate: if signals.trades.is_empty() {
+            0.0
+        } else {
+            winners as f64 / signals.trades.len() as f64 * 100.0
+        },
+        bars_in_market: column("pair_position")?
+            .i32()?
+            .into_iter()
+            .filter(|p| p.is_some_and(|p| p != 0))
+            .count(),
+    };
+    performance.total = performance.leg_a + performance.leg_b - performance.costs;
+    Ok(performance)
+}
+
+fn validate(params: &StrategyParams) -> PolarsResult<()> {
+    let error = |message: &str| Err(PolarsError::ComputeError(message.to_string().into()));
+    if params.hedge_window < 2 {
+        return error("Pairs trading hedge window must be at least 2");
+    }
+    if params.zscore_window < 3 {
+        return error("Pairs trading z-score window must be at least 3");
+    }
+    if params.exit_zscore < 0.0 || params.exit_zscore >= params.entry_zscore {
+        return error("Pairs trading exit z-score must lie in [0, entry z-score)");
+    }
+    if params
+        .stop_zscore
+        .is_some_and(|stop| stop <= params.entry_zscore)
+    {
+        return error("Pairs trading stop z-score must exceed the entry z-score");
+    }
+    if params.kalman_delta <= 0.0 || params.kalman_delta >= 1.0 {
+        return error("Pairs trading Kalman delta must lie in (0, 1)");
+    }
+    if params.kalman_observation_variance <= 0.0 {
+        return error("Pairs trading Kalman observation variance must be positive");
+    }
+    Ok(())
+}
+
+/// Hedge ratio and intercept at each bar from the `window` bars before it
+fn rolling_ols(a: &[f64], b: &[f64], window: usize) -> (Vec<f64>, Vec<f64>) {
+    let n = a.len();
+    let mut hedge_ratio = vec![f64::NAN; n];
+    let mut intercept = vec![f64::NAN; n];
+    for i in window..n {
+        let (xs, ys) = (&b[i - window..i], &a[i - window..i]);
+        let mean_x = xs.iter().sum::<f64>() / window as f64;
+        let mean_y = ys.iter().sum::<f64>() / window as f64;
+        let (mut sxy, mut sxx) = (0.0, 0.0);
+        for (x, y) in xs.iter().zip(ys) {
+            sxy += (x - mean_x) * (y - mean_y);
+            sxx += (x - mean_x).powi(2);
+        }
+        if sxx > 0.0 {
+            hedge_ratio[i] = sxy / sxx;
+            intercept[i] = mean_y - hedge_ratio[i] * mean_x;
+        }
+    }
+    (hedge_ratio, intercept)
+}
+
+/// Hedge ratio and intercept at each bar predicted by a Kalman filter
+/// updated with the bars before it; NaN during the `hedge_window` warm-up
+fn kalman_filter(
+    a: &[f64],
+    b: &[f64],
+    params: &StrategyParams,
+) -> PolarsResult<(Vec<f64>, Vec<f64>)> {
+    let states = kalman_regression(
+        a,
+        b,
+        params.kalman_delta,
+        params.kalman_observation_variance,
+    )?;
+    // The coefficients random-walk, so the prediction for a bar is the
+    // estimate after the bar before it
+    Ok((0..a.len())
+        .map(|i| {
+            if i >= params.hedge_window {
+                (states[i - 1][0], states[i - 1][1])
+            } else {
+                (f64::NAN, f64::NAN)
+            }
+        })
+        .unzip())
+}
+
+/// Build the round trip of one unit of the spread
+fn pair_trade(
+    times: &[DateTime<Utc>],
+    a: &[f64],
+    b: &[f64],
+    pos: &OpenPair,
+    exit: usize,
+    exit_fees: f64,
+    quantity: f64,
+) -> RoundTripTrade {
+    let entry_price = a[pos.entry] - pos.hedge_ratio * b[pos.entry];
+    let exit_price = a[exit] - pos.hedge_ratio * b[exit];
+    let fees = pos.entry_fees + exit_fees;
+    let pnl = pos.quantity_a * (a[exit] - a[pos.entry]) + pos.quantity_b * (b[exit] - b[pos.entry])
+        - fees;
+    let gross = pos.quantity_a.abs() * a[pos.entry] + pos.quantity_b.abs() * b[pos.entry];
+
+    RoundTripTrade {
+        entry_time: times[pos.entry],
+        exit_time: times[exit],
+        side: pos.side,
+        quantity,
+        entry_price,
+        exit_price,
+        fees,
+        pnl,
+        return_pct: pnl / gross * 100.0,
+    }
+}
+
+/// Common timestamps and the matching prices of A and B
+type AlignedPair = (Vec<DateTime<Utc>>, Vec<f64>, Vec<f64>);
+
+/// Timestamps present in both price series, with each series' price at those times
+fn aligned_pair(
+    first: &DataFrame,
+    second: &DataFrame,
+    params: &StrategyParams,
+) -> PolarsResult<AlignedPair> {
+    let mut by_time: BTreeMap<DateTime<Utc>, [Option<f64>; 2]> = BTreeMap::new();
+    for (leg, (label, df)) in [("A", first), ("B", second)].into_iter().enumerate() {
+        for col in [params.time_column.as_str(), params.price_column.as_str()] {
+            if !df.schema().contains(col) {
+                return Err(PolarsError::ComputeError(
+                    format!("Pairs trading requires '{col}' column in stock {label}").into(),
+                ));
+            }
+        }
+        let times = parse_timestamp_column(df, &params.time_column)?;
+        let price = df.column(&params.price_column)?.cast(&DataType::Float64)?;
+        let price = price.f64()?;
+
+        for (i, time) in times.into_iter().enumerate() {
+            let (Some(time), Some(p)) = (time, price.get(i)) else {
+                continue;
+            };
+            if p.is_nan() || p <= 0.0 {
+                continue;
+            }
+            by_time.entry(time).or_insert([None; 2])[leg] = Some(p);
+        }
+    }
+
+    let (mut times, mut a, mut b) = (Vec::new(), Vec::new(), Vec::new());
+    for (time, prices) in by_time {
+        if let [Some(pa), Some(pb)] = prices {
+            times.push(time);
+            a.push(pa);
+            b.push(pb);
+        }
+    }
+    Ok((times, a, b))
+}
@@ -0,0 +1,562 @@
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use polars::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Trading hours of a single session in exchange-local time
+///
+/// A session whose `close` is at or before its `open` runs past midnight and
+/// closes on the following calendar day (e.g. futures or 24-hour markets).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionSpec {
+    /// Local time the session opens
+    pub open: NaiveTime,
+    /// Local time the session closes
+    pub close: NaiveTime,
+    /// Exchange timezone used to interpret `open` and `close`
+    pub timezone: Tz,
+}
+
+impl SessionSpec {
+    /// Create a new session specification
+    ///
+    /// # Arguments
+    ///
+    /// * `open` - Local opening time
+    /// * `close` - Local closing time
+    /// * `timezone` - Exchange timezone
+    pub fn new(open: NaiveTime, close: NaiveTime, timezone: Tz) -> Self {
+        Self {
+            open,
+            close,
+            timezone,
+        }
+    }
+
+    /// Return a copy of this session with a different closing time
+    pub fn with_close(&self, close: NaiveTime) -> Self {
+        Self { close, ..*self }
+    }
+
+    /// Length of the session
+    pub fn duration(&self) -> Duration {
+        let length = self.close - self.open;
+        if length <= Duration::zero() {
+            length + Duration::days(1)
+        } else {
+            length
+        }
+    }
+
+    /// UTC open and close instants of the session starting on `date`
+    pub fn bounds(&self, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let open = localize(self.timezone, date, self.open);
+        (open, open + self.duration())
+    }
+}
+
+/// Trading calendar describing when an exchange is open
+///
+/// Combines the regular session hours with non-trading weekdays, full-day
+/// holidays and early-close (half) days. Sessions are keyed by the local
+/// date on which they open.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{NaiveDate, NaiveTime};
+/// use chrono_tz::America::New_York;
+/// use rustalib::util::calendar::{MarketCalendar, SessionSpec};
+///
+/// let hours = SessionSpec::new(
+///     NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+///     NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+///     New_York,
+/// );
+/// let black_friday = NaiveDate::from_ymd_opt(2023, 11, 24).unwrap();
+/// let calendar = MarketCalendar::new("US equities", hours)
+///     .with_half_day(black_friday, NaiveTime::from_hms_opt(13, 0, 0).unwrap());
+///
+/// let close = calendar.session_close(black_friday).unwrap();
+/// assert_eq!(close.to_rfc3339(), "2023-11-24T18:00:00+00:00");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MarketCalendar {
+    /// Human readable calendar name
+    pub name: String,
+    /// Regular session hours
+    pub session: SessionSpec,
+    /// Weekdays on which the market is closed
+    pub closed_weekdays: Vec<Weekday>,
+    /// Full-day market holidays
+    pub holidays: BTreeSet<NaiveDate>,
+    /// Early-close days mapped to their local closing time
+    pub half_days: BTreeMap<NaiveDate, NaiveTime>,
+}
+
+impl MarketCalendar {
+    /// Create a calendar that trades `session` hours Monday to Friday
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Calendar name
+    /// * `session` - Regular session hours
+    pub fn new(name: &str, session: SessionSpec) -> Self {
+        Self {
+            name: name.to_string(),
+            session,
+            closed_weekdays: vec![Weekday::Sat, Weekday::Sun],
+            holidays: BTreeSet::new(),
+            half_days: BTreeMap::new(),
+        }
+    }
+
+    /// New York Stock Exchange calendar
+    ///
+    /// Regular hours 9:30-16:00 America/New_York with the exchange's standing
+    /// holiday rules and 13:00 early closes (July 3, the day after
+    /// Thanksgiving and Christmas Eve) generated for 1990-2100. One-off
+    /// closures such as national days of mourning are not included and can
+    /// be added with [`MarketCalendar::with_holidays`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use rustalib::util::calendar::MarketCalendar;
+    ///
+    /// let nyse = MarketCalendar::nyse();
+    /// let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+    ///
+    /// assert!(!nyse.is_trading_day(date(3, 29))); // Good Friday
+    /// assert!(!nyse.is_trading_day(date(6, 19))); // Juneteenth
+    /// assert!(nyse.is_half_day(date(11, 29))); // Day after Thanksgiving
+    /// assert_eq!(nyse.next_trading_day(date(12, 25)), Some(date(12, 26)));
+    /// ```
+    pub fn nyse() -> Self {
+        Self::us_equities("NYSE")
+    }
+
+    /// Nasdaq calendar
+    ///
+    /// Nasdaq follows the same session hours, holidays and early closes as
+    /// the NYSE.
+    pub fn nasdaq() -> Self {
+        Self::us_equities("NASDAQ")
+    }
+
+    /// Round-the-clock calendar for crypto markets
+    ///
+    /// Sessions run from 00:00 to 00:00 UTC every day with no holidays, so
+    /// each UTC date is its own session.
+    pub fn crypto() -> Self {
+        let midnight = NaiveTime::MIN;
+        Self::new("Crypto 24/7", SessionSpec::new(midnight, midnight, Tz::UTC))
+            .with_closed_weekdays(&[])
+    }
+
+    fn us_equities(name: &str) -> Self {
+        let hours = SessionSpec::new(
+            NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+            NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+            Tz::America__New_York,
+        );
+        let early_close = NaiveTime::from_hms_opt(13, 0, 0).unwrap();
+
+        let mut calendar = Self::new(name, hours);
+        for year in US_RULES_FIRST_YEAR..=US_RULES_LAST_YEAR {
+            calendar.holidays.extend(us_equity_holidays(year));
+            for date in us_equity_half_days(year) {
+                calendar.half_days.insert(date, early_close);
+            }
+        }
+        calendar
+    }
+
+    /// Add full-day holidays to the calendar
+    pub fn with_holidays<I: IntoIterator<Item = NaiveDate>>(mut self, holidays: I) -> Self {
+        self.holidays.extend(holidays);
+        self
+    }
+
+    /// Add an early-close day closing at `close` local time
+    pub fn with_half_day(mut self, date: NaiveDate, close: NaiveTime) -> Self {
+        self.half_days.insert(date, close);
+        self
+    }
+
+    /// Set the weekdays on which the market does not trade
+    pub fn with_closed_weekdays(mut self, weekdays: &[Weekday]) -> Self {
+        self.closed_weekdays = weekdays.to_vec();
+        self
+    }
+
+    /// Whether a session opens on `date`
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        !self.closed_weekdays.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// Whether `date` is an early-close day
+    pub fn is_half_day(&self, date: NaiveDate) -> bool {
+        self.is_trading_day(date) && self.half_days.contains_key(&date)
+    }
+
+    /// Effective session hours on `date`, accounting for early closes
+    ///
+    /// Returns `None` when the market is closed on `date`.
+    pub fn session_spec(&self, date: NaiveDate) -> Option<SessionSpec> {
+        if !self.is_trading_day(date) {
+            return None;
+        }
+
+        Some(match self.half_days.get(&date) {
+            Some(&close) => self.session.with_close(close),
+            None => self.session,
+        })
+    }
+
+    /// UTC instant at which the session on `date` opens
+    pub fn session_open(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        self.session_spec(date).map(|spec| spec.bounds(date).0)
+    }
+
+    /// UTC instant at which the session on `date` closes
+    pub fn session_close(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        self.session_spec(date).map(|spec| spec.bounds(date).1)
+    }
+
+    /// Local date of the session containing `timestamp`
+    ///
+    /// The open is inclusive and the close exclusive. Returns `None` when
+    /// the timestamp falls outside every session.
+    pub fn session_date(&self, timestamp: DateTime<Utc>) -> Option<NaiveDate> {
+        let local_date = timestamp.with_timezone(&self.session.timezone).date_naive();
+
+        // A session that runs past midnight may have opened the day before
+        [local_date, local_date.pred_opt()?]
+            .into_iter()
+            .find(|&date| match self.session_spec(date) {
+                Some(spec) => {
+                    let (open, close) = spec.bounds(date);
+                    timestamp >= open && timestamp < close
+                }
+                None => false,
+            })
+    }
+
+    /// Whether the market is open at `timestamp`
+    pub fn is_open_at(&self, timestamp: DateTime<Utc>) -> bool {
+        self.session_date(timestamp).is_some()
+    }
+
+    /// Minutes elapsed since the open of the session containing `timestamp`
+    pub fn minutes_since_open(&self, timestamp: DateTime<Utc>) -> Option<i64> {
+        let date = self.session_date(timestamp)?;
+        self.session_open(date)
+            .map(|open| (timestamp - open).num_minutes())
+    }
+
+    /// Next date on or after `date` on which a session opens
+    pub fn next_trading_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        date.iter_days()
+            .take(MAX_CLOSED_RUN)
+            .find(|&d| self.is_trading_day(d))
+    }
+
+    /// Last date strictly before `date` on which a session opened
+    pub fn previous_trading_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        date.pred_opt()?
+            .iter_days()
+            .rev()
+            .take(MAX_CLOSED_RUN)
+            .find(|&d| self.is_trading_day(d))
+    }
+
+    /// Minutes remaining until the close of the session containing `timestamp`
+    pub fn minutes_until_close(&self, timestamp: DateTime<Utc>) -> Option<i64> {
+        let date = self.session_date(timestamp)?;
+        self.session_close(date)
+            .map(|close| (close - timestamp).num_minutes())
+    }
+}
+
+/// Assign each bar the index of the trading session it belongs to
+///
+/// Sessions are numbered from 0 in order of appearance. Bars outside every
+/// session (pre/post market, holidays) get a null id. This replaces day
+/// detection based on dividing the row index by a fixed number of bars per
+/// day, which breaks on half days, missing bars and extended hours.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with a timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar to evaluate the timestamps against
+///
+/// # Returns
+///
+/// Returns a UInt32 Series named "session_id"
+pub fn calculate_session_ids(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+) -> PolarsResult<Series> {
+    let timestamps = parse_timestamp_column(df, time_column)?;
+
+    let mut ids: Vec<Option<u32>> = Vec::with_capacity(timestamps.len());
+    let mut current: Option<(NaiveDate, u32)> = None;
+    for ts in timestamps {
+        let Some(session) = ts.and_then(|ts| calendar.session_date(ts)) else {
+            ids.push(None);
+            continue;
+        };
+
+        let id = match current {
+            Some((date, id)) if date == session => id,
+            Some((_, id)) => id + 1,
+            None => 0,
+        };
+        current = Some((session, id));
+        ids.push(Some(id));
+    }
+
+    Ok(Series::new("session_id".into(), ids))
+}
+
+/// Flag the first bar of every trading session
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with a timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar to evaluate the timestamps against
+///
+/// # Returns
+///
+/// Returns a boolean Series named "new_session"
+pub fn calculate_new_session_flags(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+) -> PolarsResult<Series> {
+    let session_ids = calculate_session_ids(df, time_column, calendar)?;
+
+    let mut previous: Option<u32> = None;
+    let flags: Vec<bool> = session_ids
+        .u32()?
+        .into_iter()
+        .map(|id| match id {
+            Some(id) => {
+                let is_new = previous != Some(id);
+                previous = Some(id);
+                is_new
+            }
+            None => false,
+        })
+        .collect();
+
+    Ok(Series::new("new_session".into(), flags))
+}
+
+/// Flag bars that fall inside the tradable part of each session
+///
+/// Replaces hardcoded "skip the first/last N minutes of a 9:30-16:00 day"
+/// filters: the session bounds come from `calendar`, so half days, holidays
+/// and non-US exchange hours are handled.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with a timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar to evaluate the timestamps against
+/// * `skip_open_minutes` - Minutes after the open during which trading is avoided
+/// * `skip_close_minutes` - Minutes before the close during which trading is avoided
+///
+/// # Returns
+///
+/// Returns a boolean Series named "in_trading_window"
+pub fn session_time_filter(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+    skip_open_minutes: i64,
+    skip_close_minutes: i64,
+) -> PolarsResult<Series> {
+    let timestamps = parse_timestamp_column(df, time_column)?;
+
+    let in_window: Vec<bool> = timestamps
+        .iter()
+        .map(|ts| {
+            ts.and_then(|ts| {
+                let since_open = calendar.minutes_since_open(ts)?;
+                let until_close = calendar.minutes_until_close(ts)?;
+                Some(since_open >= skip_open_minutes && until_close > skip_close_minutes)
+            })
+            .unwrap_or(false)
+        })
+        .collect();
+
+    Ok(Series::new("in_trading_window".into(), in_window))
+}
+
+/// Flag bars on which open positions should be closed before the session ends
+///
+/// A bar is flagged when it starts within `minutes_before_close` of its
+/// session's close, or when it is the last bar of its session present in
+/// the data (e.g. an early close or a gap in the feed).
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with a timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `calendar` - Trading calendar to evaluate the timestamps against
+/// * `minutes_before_close` - Exit window before the session close
+///
+/// # Returns
+///
+/// Returns a boolean Series named "session_close_exit"
+pub fn session_close_flags(
+    df: &DataFrame,
+    time_column: &str,
+    calendar: &MarketCalendar,
+    minutes_before_close: i64,
+) -> PolarsResult<Series> {
+    let timestamps = parse_timestamp_column(df, time_column)?;
+    let sessions: Vec<Option<NaiveDate>> = timestamps
+        .iter()
+        .map(|ts| ts.and_then(|ts| calendar.session_date(ts)))
+        .collect();
+
+    // Walk backwards so each bar knows the session of the next in-session bar
+    let mut flags = vec![false; timestamps.len()];
+    let mut next_session: Option<NaiveDate> = None;
+    for i in (0..timestamps.len()).rev() {
+        let (Some(ts), Some(session)) = (timestamps[i], sessions[i]) else {
+            continue;
+        };
+
+        let near_close = calendar
+            .minutes_until_close(ts)
+            .is_some_and(|minutes| minutes <= minutes_before_close);
+        let last_bar_of_session = next_session != Some(session);
+
+        flags[i] = near_close || last_bar_of_session;
+        next_session = Some(session);
+    }
+
+    Ok(Series::new("session_close_exit".into(), flags))
+}
+
+/// First year covered by the generated US equity holiday rules
+const US_RULES_FIRST_YEAR: i32 = 1990;
+/// Last year covered by the generated US equity holiday rules
+const US_RULES_LAST_YEAR: i32 = 2100;
+/// Longest run of closed days searched when looking for a trading day
+const MAX_CLOSED_RUN: usize = 30;
+
+/// Full-day holidays observed by US equity exchanges in `year`
+///
+/// Holidays falling on a Sunday are observed the following Monday and those
+/// falling on a Saturday the preceding Friday, except New Year's Day, which
+/// is not observed when it falls on a Saturday.
+pub fn us_equity_holidays(year: i32) -> Vec<NaiveDate> {
+    let ymd = |month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    let mut holidays = Vec::with_capacity(10);
+
+    let new_year = ymd(1, 1);
+    match new_year.weekday() {
+        Weekday::Sat => {}
+        Weekday::Sun => holidays.push(ymd(1, 2)),
+        _ => holidays.push(new_year),
+    }
+
+    if year >= 1998 {
+        holidays.push(nth_weekday(year, 1, Weekday::Mon, 3)); // Martin Luther King Jr. Day
+    }
+    holidays.push(nth_weekday(year, 2, Weekday::Mon, 3)); // Washington's Birthday
+    holidays.push(easter_sunday(year) - Duration::days(2)); // Good Friday
+    holidays.push(last_weekday(year, 5, Weekday::Mon)); // Memorial Day
+    if year >= 2022 {
+        holidays.push(observed(ymd(6, 19))); // Juneteenth
+    }
+    holidays.push(observed(ymd(7, 4))); // Independence Day
+    holidays.push(nth_weekday(year, 9, Weekday::Mon, 1)); // Labor Day
+    holidays.push(nth_weekday(year, 11, Weekday::Thu, 4)); // Thanksgiving
+    holidays.push(observed(ymd(12, 25))); // Christmas
+
+    holidays
+}
+
+/// Early-close (13:00) days on US equity exchanges in `year`
+///
+/// July 3 and Christmas Eve close early when they fall Monday to Thursday;
+/// the day after Thanksgiving always closes early.
+pub fn us_equity_half_days(year: i32) -> Vec<NaiveDate> {
+    let ymd = |month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    let mon_to_thu = |date: NaiveDate| date.weekday().num_days_from_monday() < 4;
+
+    let mut half_days = Vec::with_capacity(3);
+    if mon_to_thu(ymd(7, 3)) {
+        half_days.push(ymd(7, 3));
+    }
+    half_days.push(nth_weekday(year, 11, Weekday::Thu, 4) + Duration::days(1));
+    if mon_to_thu(ymd(12, 24)) {
+        half_days.push(ymd(12, 24));
+    }
+    half_days
+}
+
+/// Weekend holiday observance: Saturday moves to Friday, Sunday to Monday
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+/// The `n`-th occurrence of `weekday` in the given month
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).unwrap()
+}
+
+/// The last occurrence of `weekday` in the given month
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .unwrap_or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+/// Gregorian Easter Sunday (anonymous Gregorian algorithm)
+fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap()
+}
+
+/// Convert a local date and time to UTC, resolving DST gaps and overlaps
+fn localize(timezone: Tz, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    let local = date.and_time(time);
+    match timezone.from_local_datetime(&local).earliest() {
+        Some(dt) => dt.with_timezone(&Utc),
+        // The local time does not exist (spring-forward gap); use the first
+        // valid instant after it
+        None => timezone
+            .from_local_datetime(&(local + Duration::hours(1)))
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| local.and_utc()),
+    }
+}
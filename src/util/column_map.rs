@@ -0,0 +1,226 @@
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Names of the OHLCV columns in a DataFrame
+///
+/// Indicators and strategies refer to price data by the standard names
+/// "open", "high", "low", "close" and "volume". A `ColumnMap` records which
+/// columns hold those values when a data source names them differently, so
+/// data with columns like "Adj Close" or "vol" can be used as is.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::util::column_map::ColumnMap;
+///
+/// let df = df! {
+///     "Date" => ["2024-01-02", "2024-01-03"],
+///     "Open" => [10.0, 11.0],
+///     "High" => [11.5, 12.0],
+///     "Low" => [9.5, 10.5],
+///     "Adj Close" => [11.0, 11.5],
+///     "vol" => [1000.0, 1200.0],
+/// }
+/// .unwrap();
+///
+/// let columns = ColumnMap::detect(&df);
+/// assert_eq!(columns.close, "Adj Close");
+/// assert_eq!(columns.volume, "vol");
+///
+/// let standard = columns.standardize(&df).unwrap();
+/// assert!(standard.column("close").is_ok());
+/// assert!(standard.column("Date").is_ok());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnMap {
+    /// Column holding opening prices
+    pub open: String,
+    /// Column holding high prices
+    pub high: String,
+    /// Column holding low prices
+    pub low: String,
+    /// Column holding closing prices
+    pub close: String,
+    /// Column holding traded volume
+    pub volume: String,
+}
+
+impl Default for ColumnMap {
+    fn default() -> Self {
+        Self {
+            open: "open".to_string(),
+            high: "high".to_string(),
+            low: "low".to_string(),
+            close: "close".to_string(),
+            volume: "volume".to_string(),
+        }
+    }
+}
+
+/// Common spellings of each standard column, most preferred first, compared
+/// case-insensitively with '_' and '-' read as spaces
+const ALIASES: [&[&str]; 5] = [
+    &["open", "o", "open price", "opening price"],
+    &["high", "h", "high price"],
+    &["low", "l", "low price"],
+    &[
+        "close",
+        "c",
+        "close price",
+        "closing price",
+        "adj close",
+        "adjusted close",
+        "last",
+        "price",
+    ],
+    &["volume", "vol", "v", "qty", "quantity"],
+];
+
+impl ColumnMap {
+    /// Create a map using the standard column names
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Guess the OHLCV columns of a DataFrame from common spellings
+    ///
+    /// Names are compared case-insensitively with '_' and '-' treated as
+    /// spaces, so "Adj_Close" matches "adj close". Values without a match keep
+    /// their standard name.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - DataFrame whose column names are inspected
+    ///
+    /// # Returns
+    ///
+    /// Returns the detected ColumnMap
+    pub fn detect(df: &DataFrame) -> Self {
+        let names: Vec<(String, String)> = df
+            .get_column_names()
+            .into_iter()
+            .map(|name| {
+                let key = name.to_lowercase().replace(['_', '-'], " ");
+                (key.trim().to_string(), name.to_string())
+            })
+            .collect();
+        let find = |aliases: &[&str], fallback: &str| {
+            aliases
+                .iter()
+                .find_map(|alias| names.iter().find(|(key, _)| key == alias))
+                .map_or_else(|| fallback.to_string(), |(_, name)| name.clone())
+        };
+
+        Self {
+            open: find(ALIASES[0], "open"),
+            high: find(ALIASES[1], "high"),
+            low: find(ALIASES[2], "low"),
+            close: find(ALIASES[3], "close"),
+            volume: find(ALIASES[4], "volume"),
+        }
+    }
+
+    /// Column holding the values of a standard column
+    ///
+    /// Names other than the five standard ones are returned unchanged.
+    pub fn resolve<'a>(&'a self, standard: &'a str) -> &'a str {
+        match standard {
+            "open" => &self.open,
+            "high" => &self.high,
+            "low" => &self.low,
+            "close" => &self.close,
+            "volume" => &self.volume,
+            other => other,
+        }
+    }
+
+    /// Pairs of (standard name, mapped name) for every column
+    pub fn pairs(&self) -> [(&'static str, &str); 5] {
+        [
+            ("open", &self.open),
+            ("high", &self.high),
+            ("low", &self.low),
+            ("close", &self.close),
+            ("volume", &self.volume),
+        ]
+    }
+
+    /// Whether every column uses its standard name
+    pub fn is_standard(&self) -> bool {
+        self.pairs().iter().all(|(standard, name)| standard == name)
+    }
+
+    /// The OHLCV columns of a DataFrame under their standard names
+    ///
+    /// Only mapped columns present in `df` are included; other columns are
+    /// left out, so the result never has name collisions. Columns are shared
+    /// with `df`, not copied.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - DataFrame with price data
+    ///
+    /// # Returns
+    ///
+    /// Returns a PolarsResult containing the OHLCV-only DataFrame
+    pub fn select_ohlcv(&self, df: &DataFrame) -> PolarsResult<DataFrame> {
+        let columns = self
+            .pairs()
+            .into_iter()
+            .filter(|(_, name)| df.schema().contains(name))
+            .map(|(standard, name)| Ok(df.column(name)?.clone().with_name(standard.into())))
+            .collect::<PolarsResult<Vec<Column>>>()?;
+        DataFrame::new(columns)
+    }
+
+    /// Add the mapped columns to a DataFrame again under the standard names
+    ///
+    /// Unlike [`standardize`](Self::standardize) the original columns are
+    /// kept; standard names already used by another column are left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - DataFrame with price data
+    ///
+    /// # Returns
+    ///
+    /// Returns a PolarsResult containing the DataFrame with the aliases added
+    pub fn with_standard_names(&self, df: &DataFrame) -> PolarsResult<DataFrame> {
+        let mut output = df.clone();
+        for (standard, name) in self.pairs() {
+            if df.schema().contains(name) && !df.schema().contains(standard) {
+                output.with_column(df.column(name)?.clone().with_name(standard.into()))?;
+            }
+        }
+        Ok(output)
+    }
+
+    /// Rename the mapped columns of a DataFrame to the standard names
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - DataFrame with price data
+    ///
+    /// # Returns
+    ///
+    /// Returns a PolarsResult containing the renamed DataFrame, or an error
+    /// when a standard name is already used by another column (e.g. both
+    /// "close" and "Adj Close" when the map selects "Adj Close")
+    pub fn standardize(&self, df: &DataFrame) -> PolarsResult<DataFrame> {
+        let mut output = df.clone();
+        for (standard, name) in self.pairs() {
+            if standard == name || !df.schema().contains(name) {
+                continue;
+            }
+            if df.schema().contains(standard) {
+                return Err(PolarsError::ComputeError(
+                    format!("Cannot rename '{name}' to '{standard}': column already exists").into(),
+                ));
+            }
+            output.rename(name, standard.into())?;
+        }
+        Ok(output)
+    }
+}
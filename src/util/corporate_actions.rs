@@ -0,0 +1,263 @@
+//! Split and dividend adjustment of OHLCV data
+//!
+//! Corporate actions are given as a DataFrame with one row per ex-date: a
+//! timestamp column plus a "split_ratio" column (new shares per old share, 2.0
+//! for a 2-for-1 split) and/or a "dividend" column (cash per share, in
+//! post-split shares when both fall on the same day). Null and NaN entries
+//! mean no action of that kind. An action applies from the first bar at or
+//! after its ex-date.
+
+use crate::util::dataframe_utils::column_nan_values;
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+
+/// Which corporate actions [`adjust_for_corporate_actions`] removes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PriceAdjustment {
+    /// Splits only; dividend drops stay in the prices
+    Splits,
+    /// Splits and cash dividends, so price changes match total returns
+    #[default]
+    SplitsAndDividends,
+}
+
+/// Back-adjust OHLCV data for splits and dividends
+///
+/// Prices before each ex-date are scaled so the series has no jumps from
+/// corporate actions and the latest bars keep their traded prices. A split
+/// divides earlier prices by its ratio and multiplies earlier volume by it; a
+/// dividend scales earlier prices by `1 - dividend / previous close`.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with price columns ("open", "high", "low", "close"; at
+///   least "close") and optionally "volume", sorted by time
+/// * `time_column` - Name of the timestamp column of `df`
+/// * `actions` - Corporate actions DataFrame (see the module documentation)
+/// * `actions_time_column` - Name of the ex-date column of `actions`
+/// * `adjustment` - Whether dividends are adjusted as well as splits
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a copy of `df` with adjusted prices and
+/// volume and an "adjustment_factor" column, the factor applied to each
+/// bar's prices
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::util::corporate_actions::{adjust_for_corporate_actions, PriceAdjustment};
+///
+/// let df = df! {
+///     "date" => ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"],
+///     "close" => [100.0, 102.0, 51.0, 50.0],
+///     "volume" => [1000.0, 1000.0, 2000.0, 2000.0],
+/// }
+/// .unwrap();
+/// let actions = df! {
+///     "date" => ["2024-01-04", "2024-01-05"],
+///     "split_ratio" => [Some(2.0), None],
+///     "dividend" => [None, Some(0.51)],
+/// }
+/// .unwrap();
+///
+/// let adjusted = adjust_for_corporate_actions(
+///     &df, "date", &actions, "date", PriceAdjustment::SplitsAndDividends,
+/// )
+/// .unwrap();
+/// let factor = adjusted.column("adjustment_factor").unwrap().f64().unwrap();
+/// // Halved for the split, then scaled by 1 - 0.51 / 51 for the dividend
+/// assert!((factor.get(0).unwrap() - 0.495).abs() < 1e-12);
+/// assert_eq!(factor.get(3), Some(1.0));
+/// assert_eq!(adjusted.column("volume").unwrap().f64().unwrap().get(0), Some(2000.0));
+/// ```
+pub fn adjust_for_corporate_actions(
+    df: &DataFrame,
+    time_column: &str,
+    actions: &DataFrame,
+    actions_time_column: &str,
+    adjustment: PriceAdjustment,
+) -> PolarsResult<DataFrame> {
+    if !df.schema().contains("close") {
+        return Err(PolarsError::ComputeError(
+            "Corporate action adjustment requires 'close' column".into(),
+        ));
+    }
+
+    let close = column_nan_values(df, "close")?;
+    let (ratios, dividends) = actions_per_bar(df, time_column, actions, actions_time_column)?;
+
+    let height = df.height();
+    let mut price_factor = vec![1.0; height];
+    let mut volume_factor = vec![1.0; height];
+    for i in (0..height.saturating_sub(1)).rev() {
+        // Actions on the next bar scale this bar and every earlier one
+        let next = i + 1;
+        let ratio = ratios[next];
+        let mut factor = 1.0 / ratio;
+        if adjustment == PriceAdjustment::SplitsAndDividends && dividends[next] != 0.0 {
+            // The dividend is per post-split share, the previous close per old share
+            let dividend_factor = 1.0 - dividends[next] * ratio / close[i];
+            if dividend_factor.is_finite() && dividend_factor > 0.0 {
+                factor *= dividend_factor;
+            }
+        }
+        price_factor[i] = price_factor[next] * factor;
+        volume_factor[i] = volume_factor[next] * ratio;
+    }
+
+    let mut result = df.clone();
+    for col in ["open", "high", "low", "close"] {
+        if df.schema().contains(col) {
+            let adjusted: Vec<f64> = column_nan_values(df, col)?
+                .iter()
+                .zip(&price_factor)
+                .map(|(p, f)| p * f)
+                .collect();
+            result.with_column(Series::new(col.into(), adjusted))?;
+        }
+    }
+    if df.schema().contains("volume") {
+        let adjusted: Vec<f64> = column_nan_values(df, "volume")?
+            .iter()
+            .zip(&volume_factor)
+            .map(|(v, f)| v * f)
+            .collect();
+        result.with_column(Series::new("volume".into(), adjusted))?;
+    }
+    result.with_column(Series::new("adjustment_factor".into(), price_factor))?;
+    Ok(result)
+}
+
+/// Calculate a total-return close series
+///
+/// The value of a position that reinvests every dividend at the close of its
+/// ex-date, starting from the first close. On each bar the value grows by
+/// `split_ratio * (close + dividend) / previous close`, so unadjusted closes
+/// should be passed together with their splits, and already split-adjusted
+/// closes without them. Bars with a missing close are NaN and the value
+/// carries over them.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with a "close" column, sorted by time
+/// * `time_column` - Name of the timestamp column of `df`
+/// * `actions` - Corporate actions DataFrame (see the module documentation)
+/// * `actions_time_column` - Name of the ex-date column of `actions`
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a Series named "total_return_close"
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::util::corporate_actions::total_return_close;
+///
+/// let df = df! {
+///     "date" => ["2024-01-02", "2024-01-03", "2024-01-04"],
+///     "close" => [50.0, 50.0, 49.0],
+/// }
+/// .unwrap();
+/// let actions = df! {
+///     "date" => ["2024-01-04"],
+///     "dividend" => [1.0],
+/// }
+/// .unwrap();
+///
+/// let total_return = total_return_close(&df, "date", &actions, "date").unwrap();
+/// // The close drops by the dividend, the total return does not
+/// assert_eq!(total_return.f64().unwrap().get(2), Some(50.0));
+/// ```
+pub fn total_return_close(
+    df: &DataFrame,
+    time_column: &str,
+    actions: &DataFrame,
+    actions_time_column: &str,
+) -> PolarsResult<Series> {
+    if !df.schema().contains("close") {
+        return Err(PolarsError::ComputeError(
+            "Total return requires 'close' column".into(),
+        ));
+    }
+
+    let close = column_nan_values(df, "close")?;
+    let (ratios, dividends) = actions_per_bar(df, time_column, actions, actions_time_column)?;
+
+    let mut total_return = vec![f64::NAN; df.height()];
+    let mut last: Option<(f64, f64)> = None;
+    // Splits and dividends since the last valid close
+    let (mut ratio, mut dividend) = (1.0, 0.0);
+    for i in 0..df.height() {
+        ratio *= ratios[i];
+        dividend += dividends[i];
+        if close[i].is_nan() {
+            continue;
+        }
+        let value = match last {
+            Some((prev_close, prev_value)) => {
+                prev_value * ratio * (close[i] + dividend) / prev_close
+            }
+            None => close[i],
+        };
+        total_return[i] = value;
+        last = Some((close[i], value));
+        (ratio, dividend) = (1.0, 0.0);
+    }
+
+    Ok(Series::new("total_return_close".into(), total_return))
+}
+
+/// Split ratio (1.0 without a split) and dividend (0.0 without one) per bar
+fn actions_per_bar(
+    df: &DataFrame,
+    time_column: &str,
+    actions: &DataFrame,
+    actions_time_column: &str,
+) -> PolarsResult<(Vec<f64>, Vec<f64>)> {
+    let has_splits = actions.schema().contains("split_ratio");
+    let has_dividends = actions.schema().contains("dividend");
+    if !has_splits && !has_dividends {
+        return Err(PolarsError::ComputeError(
+            "Corporate actions require a 'split_ratio' or 'dividend' column".into(),
+        ));
+    }
+
+    let bars: Vec<(DateTime<Utc>, usize)> = parse_timestamp_column(df, time_column)?
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, t)| Some((t?, i)))
+        .collect();
+    let ex_dates = parse_timestamp_column(actions, actions_time_column)?;
+    let action_vec = |name: &str, present: bool| -> PolarsResult<Vec<f64>> {
+        if present {
+            column_nan_values(actions, name)
+        } else {
+            Ok(vec![f64::NAN; actions.height()])
+        }
+    };
+    let split_ratio = action_vec("split_ratio", has_splits)?;
+    let dividend = action_vec("dividend", has_dividends)?;
+
+    let mut ratios = vec![1.0; df.height()];
+    let mut dividends = vec![0.0; df.height()];
+    for (i, ex_date) in ex_dates.iter().enumerate() {
+        let Some(ex_date) = ex_date else {
+            continue;
+        };
+        let idx = bars.partition_point(|(t, _)| t < ex_date);
+        let Some(&(_, row)) = bars.get(idx) else {
+            continue;
+        };
+        if split_ratio[i].is_finite() && split_ratio[i] > 0.0 {
+            ratios[row] *= split_ratio[i];
+        }
+        if dividend[i].is_finite() {
+            dividends[row] += dividend[i];
+        }
+    }
+    Ok((ratios, dividends))
+}
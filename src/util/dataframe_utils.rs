@@ -1,5 +1,9 @@
+use crate::error::{TaError, TaResult};
+use crate::util::time_utils::parse_timestamp_column;
+use chrono::Duration;
 use polars::frame::column::Column;
 use polars::prelude::*;
+use std::collections::VecDeque;
 
 /// Ensure a column in a DataFrame is of Float64 type
 ///
@@ -16,10 +20,10 @@ use polars::prelude::*;
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::util::dataframe_utils::ensure_f64_column;
+/// use rustalib::util::dataframe_utils::ensure_f64_column;
 ///
 /// let mut df = DataFrame::new(vec![
-///     Series::new("price", &[1, 2, 3]),
+///     Series::new("price".into(), &[1, 2, 3]).into(),
 /// ]).unwrap();
 /// ensure_f64_column(&mut df, "price").unwrap();
 /// assert_eq!(df.column("price").unwrap().dtype(), &DataType::Float64);
@@ -50,29 +54,526 @@ pub fn ensure_f64_column(df: &mut DataFrame, column_name: &str) -> PolarsResult<
 ///
 /// # Returns
 ///
-/// Returns a PolarsResult<()> or an error if there are not enough rows
+/// Returns a TaResult<()>, with [`TaError::InsufficientData`] if there are
+/// not enough rows
 ///
 /// # Example
 ///
 /// ```
 /// use polars::prelude::*;
-/// use ta_lib_in_rust::util::dataframe_utils::check_window_size;
+/// use rustalib::util::dataframe_utils::check_window_size;
+/// use rustalib::TaError;
 ///
-/// let df = DataFrame::new(vec![Series::new("close", &[1.0, 2.0, 3.0, 4.0])]).unwrap();
+/// let df = DataFrame::new(vec![Series::new("close".into(), &[1.0, 2.0, 3.0, 4.0]).into()]).unwrap();
 /// assert!(check_window_size(&df, 3, "test").is_ok());
-/// assert!(check_window_size(&df, 5, "test").is_err());
+/// assert!(matches!(
+///     check_window_size(&df, 5, "test"),
+///     Err(TaError::InsufficientData { needed: 5, got: 4, .. })
+/// ));
 /// ```
-pub fn check_window_size(df: &DataFrame, window: usize, indicator_name: &str) -> PolarsResult<()> {
+pub fn check_window_size(df: &DataFrame, window: usize, indicator_name: &str) -> TaResult<()> {
     if df.height() < window {
+        return Err(TaError::InsufficientData {
+            indicator: indicator_name.to_string(),
+            needed: window,
+            got: df.height(),
+        });
+    }
+    Ok(())
+}
+
+/// Options for a trailing window of `window_size` rows in Polars rolling kernels
+///
+/// Windows with fewer than `min_periods` non-null values produce null.
+pub(crate) fn fixed_window(window_size: usize, min_periods: usize) -> RollingOptionsFixedWindow {
+    RollingOptionsFixedWindow {
+        window_size,
+        min_periods,
+        center: false,
+        weights: None,
+        fn_params: None,
+    }
+}
+
+/// A Float64 column with NaN replaced by null, so rolling kernels skip it
+pub(crate) fn nan_to_null(values: &Float64Chunked) -> Series {
+    values
+        .iter()
+        .map(|v| v.filter(|v| !v.is_nan()))
+        .collect::<Float64Chunked>()
+        .with_name(values.name().clone())
+        .into_series()
+}
+
+/// Values of a rolling result with nulls read as NaN
+///
+/// The first `warmup` values are NaN regardless of the result, matching
+/// indicators that leave incomplete windows undefined.
+pub(crate) fn rolling_values(result: &Series, warmup: usize) -> PolarsResult<Vec<f64>> {
+    Ok(result
+        .f64()?
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            if i < warmup {
+                f64::NAN
+            } else {
+                v.unwrap_or(f64::NAN)
+            }
+        })
+        .collect())
+}
+
+/// Position of the extreme value in each trailing window of `window` values
+///
+/// `dominates(new, old)` tells whether a newer value replaces an older one as
+/// the extreme, so ties go to the newer value when it returns true for equal
+/// values. A monotonic queue of candidate positions keeps the pass linear in
+/// the number of values. NaN values are never the extreme; windows holding
+/// only NaN give None.
+pub(crate) fn sliding_extreme_positions(
+    values: &[f64],
+    window: usize,
+    dominates: impl Fn(f64, f64) -> bool,
+) -> Vec<Option<usize>> {
+    let mut candidates = VecDeque::new();
+    let mut positions = Vec::with_capacity(values.len());
+
+    for (i, &value) in values.iter().enumerate() {
+        if candidates.front().is_some_and(|&front| front + window <= i) {
+            candidates.pop_front();
+        }
+        if !value.is_nan() {
+            while candidates
+                .back()
+                .is_some_and(|&back| dominates(value, values[back]))
+            {
+                candidates.pop_back();
+            }
+            candidates.push_back(i);
+        }
+        positions.push(candidates.front().copied());
+    }
+
+    positions
+}
+
+/// Maximum of each trailing window of `window` values, skipping NaN
+///
+/// The first `window - 1` values and windows holding only NaN are NaN.
+pub(crate) fn sliding_max(values: &[f64], window: usize) -> Vec<f64> {
+    sliding_extreme(values, window, |new, old| new >= old)
+}
+
+/// Minimum of each trailing window of `window` values, skipping NaN
+///
+/// The first `window - 1` values and windows holding only NaN are NaN.
+pub(crate) fn sliding_min(values: &[f64], window: usize) -> Vec<f64> {
+    sliding_extreme(values, window, |new, old| new <= old)
+}
+
+fn sliding_extreme(values: &[f64], window: usize, dominates: fn(f64, f64) -> bool) -> Vec<f64> {
+    sliding_extreme_positions(values, window, dominates)
+        .into_iter()
+        .enumerate()
+        .map(|(i, position)| match position {
+            Some(position) if i + 1 >= window => values[position],
+            _ => f64::NAN,
+        })
+        .collect()
+}
+
+/// Whether each trailing window of `window` rows is free of NaN
+///
+/// The first `window - 1` rows never hold a full window and are false.
+pub(crate) fn complete_windows(values: &[f64], window: usize) -> Vec<bool> {
+    let mut last_nan = None;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            if value.is_nan() {
+                last_nan = Some(i);
+            }
+            i + 1 >= window && last_nan.is_none_or(|last| last + window <= i)
+        })
+        .collect()
+}
+
+/// Values of a Float64 column with nulls read as NaN
+pub(crate) fn nan_values(values: &Float64Chunked) -> Vec<f64> {
+    values.iter().map(|v| v.unwrap_or(f64::NAN)).collect()
+}
+
+/// Values of a series cast to Float64, with nulls read as NaN
+pub(crate) fn series_nan_values(series: &Series) -> PolarsResult<Vec<f64>> {
+    Ok(nan_values(series.cast(&DataType::Float64)?.f64()?))
+}
+
+/// Values of a column cast to Float64, with nulls read as NaN
+pub(crate) fn column_nan_values(df: &DataFrame, column: &str) -> PolarsResult<Vec<f64>> {
+    series_nan_values(df.column(column)?.as_materialized_series())
+}
+
+/// Values of a series cast to Float64, with nulls and NaNs as `None`
+pub(crate) fn series_valid_values(series: &Series) -> PolarsResult<Vec<Option<f64>>> {
+    Ok(series_nan_values(series)?
+        .into_iter()
+        .map(|v| (!v.is_nan()).then_some(v))
+        .collect())
+}
+
+/// Values of a column cast to Float64, with nulls and NaNs as `None`
+pub(crate) fn column_valid_values(df: &DataFrame, column: &str) -> PolarsResult<Vec<Option<f64>>> {
+    series_valid_values(df.column(column)?.as_materialized_series())
+}
+
+/// How [`aggregate_ticks`] and [`resample_bars`] group rows into bars
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BarSampling {
+    /// A new bar every fixed interval of time, aligned to the Unix epoch
+    Time(Duration),
+    /// A new bar once the traded size reaches the threshold
+    Volume(f64),
+    /// A new bar once the traded value (price × size) reaches the threshold
+    Dollar(f64),
+    /// A new bar once the running sum of tick-rule signs (+1 for an up tick,
+    /// -1 for a down tick, the previous sign when unchanged) exceeds the
+    /// expected imbalance in absolute value
+    ///
+    /// The expected imbalance is the expected bar length times the expected
+    /// absolute imbalance per row, both exponentially weighted averages over
+    /// past bars with span `span`. They start at `expected_ticks` and full
+    /// imbalance, so the first bar closes after about `expected_ticks`
+    /// one-directional rows. The threshold never drops below one.
+    TickImbalance { expected_ticks: f64, span: usize },
+}
+
+/// Aggregate trade ticks into OHLCV bars
+///
+/// Volume and dollar bars close on the trade that reaches the threshold, so
+/// a bar can overshoot it by part of one trade; trades are never split.
+/// Trades with a missing price or size, and for time bars an unparseable
+/// timestamp, are skipped. The last bar may be incomplete.
+///
+/// # Arguments
+///
+/// * `df` - Tick DataFrame with "price" and "size" columns and a timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `sampling` - When to start a new bar
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with one row per bar and
+/// `time_column` (the time of the bar's last trade, with the input type),
+/// "open", "high", "low", "close", "volume" and "ticks" columns, ready for
+/// the indicators in this crate
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::util::dataframe_utils::{aggregate_ticks, BarSampling};
+///
+/// let ticks = df! {
+///     "timestamp" => ["2024-01-02 14:30:01", "2024-01-02 14:30:02", "2024-01-02 14:30:05"],
+///     "price" => [100.0, 101.0, 99.5],
+///     "size" => [60.0, 50.0, 30.0],
+/// }
+/// .unwrap();
+///
+/// let bars = aggregate_ticks(&ticks, "timestamp", BarSampling::Volume(100.0)).unwrap();
+/// assert_eq!(bars.height(), 2);
+/// assert_eq!(bars.column("high").unwrap().f64().unwrap().get(0), Some(101.0));
+/// assert_eq!(bars.column("volume").unwrap().f64().unwrap().get(0), Some(110.0));
+///
+/// // Three consecutive up ticks close a tick imbalance bar expecting three
+/// let ticks = df! {
+///     "timestamp" => ["2024-01-02 14:30:01"; 6],
+///     "price" => [10.0, 10.1, 10.2, 10.3, 10.2, 10.1],
+///     "size" => [1.0; 6],
+/// }
+/// .unwrap();
+/// let sampling = BarSampling::TickImbalance { expected_ticks: 3.0, span: 10 };
+/// let bars = aggregate_ticks(&ticks, "timestamp", sampling).unwrap();
+/// assert_eq!(bars.column("ticks").unwrap().u32().unwrap().to_vec(), vec![Some(4), Some(2)]);
+/// ```
+pub fn aggregate_ticks(
+    df: &DataFrame,
+    time_column: &str,
+    sampling: BarSampling,
+) -> PolarsResult<DataFrame> {
+    let price = df.column("price")?.f64()?;
+    let size = df.column("size")?.f64()?;
+
+    let rows = price
+        .iter()
+        .zip(size)
+        .map(|(p, s)| match (p, s) {
+            (Some(p), Some(s)) if !p.is_nan() && !s.is_nan() => Some((p, p, p, p, s)),
+            _ => None,
+        })
+        .collect();
+
+    sample_bars(df, time_column, sampling, rows)
+}
+
+/// Resample OHLCV bars, e.g. 1-minute bars, into coarser or
+/// information-driven bars
+///
+/// Each input bar is treated as one indivisible trade at its close: volume
+/// and dollar thresholds use its volume and close × volume, tick imbalance
+/// uses the direction of its close, and time bars group by its timestamp.
+/// Output bars take the first open, highest high, lowest low and last close
+/// of their input bars. Rows with a missing value are skipped and the last
+/// bar may be incomplete.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with "open", "high", "low", "close" and "volume"
+///   columns and a timestamp column
+/// * `time_column` - Name of the timestamp column
+/// * `sampling` - When to start a new bar
+///
+/// # Returns
+///
+/// Returns a PolarsResult containing a DataFrame with one row per bar and
+/// `time_column` (the time of the bar's last input row), "open", "high",
+/// "low", "close", "volume" and "ticks" (input rows per bar) columns
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::util::dataframe_utils::{resample_bars, BarSampling};
+///
+/// let minutes = df! {
+///     "timestamp" => ["2024-01-02 14:30", "2024-01-02 14:31", "2024-01-02 14:32", "2024-01-02 14:33"],
+///     "open" => [10.0, 10.2, 10.1, 10.4],
+///     "high" => [10.3, 10.2, 10.5, 10.6],
+///     "low" => [9.9, 10.0, 10.1, 10.3],
+///     "close" => [10.2, 10.1, 10.4, 10.5],
+///     "volume" => [1000.0, 800.0, 1500.0, 700.0],
+/// }
+/// .unwrap();
+///
+/// // A bar per $20,000 traded
+/// let bars = resample_bars(&minutes, "timestamp", BarSampling::Dollar(20_000.0)).unwrap();
+/// assert_eq!(bars.height(), 2);
+/// assert_eq!(bars.column("high").unwrap().f64().unwrap().get(0), Some(10.5));
+/// assert_eq!(bars.column("ticks").unwrap().u32().unwrap().get(0), Some(3));
+/// ```
+pub fn resample_bars(
+    df: &DataFrame,
+    time_column: &str,
+    sampling: BarSampling,
+) -> PolarsResult<DataFrame> {
+    let column = |name: &str| column_valid_values(df, name);
+    let (open, high, low, close, volume) = (
+        column("open")?,
+        column("high")?,
+        column("low")?,
+        column("close")?,
+        column("volume")?,
+    );
+
+    let rows = (0..df.height())
+        .map(|i| Some((open[i]?, high[i]?, low[i]?, close[i]?, volume[i]?)))
+        .collect();
+
+    sample_bars(df, time_column, sampling, rows)
+}
+
+/// One input row for bar sampling: open, high, low, close and volume
+type BarRow = (f64, f64, f64, f64, f64);
+
+/// Group rows into bars; None rows are skipped
+fn sample_bars(
+    df: &DataFrame,
+    time_column: &str,
+    sampling: BarSampling,
+    rows: Vec<Option<BarRow>>,
+) -> PolarsResult<DataFrame> {
+    let threshold_ok = match sampling {
+        BarSampling::Time(interval) => interval.num_milliseconds() > 0,
+        BarSampling::Volume(threshold) | BarSampling::Dollar(threshold) => threshold > 0.0,
+        BarSampling::TickImbalance {
+            expected_ticks,
+            span,
+        } => expected_ticks > 0.0 && span > 0,
+    };
+    if !threshold_ok {
         return Err(PolarsError::ComputeError(
-            format!(
-                "Not enough data points ({}) for {} window ({})",
-                df.height(),
-                indicator_name,
-                window
-            )
-            .into(),
+            "Bar sampling interval or threshold must be positive".into(),
         ));
     }
-    Ok(())
+
+    let timestamps = parse_timestamp_column(df, time_column)?;
+
+    let mut bars = BarBuilder::default();
+    let mut current_period = None;
+    let mut imbalance = TickImbalance::default();
+    if let BarSampling::TickImbalance {
+        expected_ticks,
+        span,
+    } = sampling
+    {
+        imbalance = TickImbalance::new(expected_ticks, span);
+    }
+
+    for (i, (ts, row)) in timestamps.iter().zip(rows).enumerate() {
+        let Some((open, high, low, close, volume)) = row else {
+            continue;
+        };
+
+        match sampling {
+            BarSampling::Time(interval) => {
+                let Some(ts) = ts else {
+                    continue;
+                };
+                let period = ts
+                    .timestamp_millis()
+                    .div_euclid(interval.num_milliseconds());
+                if current_period.is_some_and(|current| current != period) {
+                    bars.close();
+                }
+                current_period = Some(period);
+                bars.push(i, open, high, low, close, volume);
+            }
+            BarSampling::Volume(threshold) => {
+                bars.push(i, open, high, low, close, volume);
+                if bars.volume >= threshold {
+                    bars.close();
+                }
+            }
+            BarSampling::Dollar(threshold) => {
+                bars.push(i, open, high, low, close, volume);
+                if bars.value >= threshold {
+                    bars.close();
+                }
+            }
+            BarSampling::TickImbalance { .. } => {
+                bars.push(i, open, high, low, close, volume);
+                if imbalance.update(close) {
+                    bars.close();
+                }
+            }
+        }
+    }
+
+    bars.finish(df, time_column)
+}
+
+/// Running state of tick imbalance bar sampling
+#[derive(Default)]
+struct TickImbalance {
+    alpha: f64,
+    expected_ticks: f64,
+    expected_imbalance: f64,
+    last_price: Option<f64>,
+    sign: f64,
+    theta: f64,
+    ticks: f64,
+}
+
+impl TickImbalance {
+    fn new(expected_ticks: f64, span: usize) -> Self {
+        Self {
+            alpha: 2.0 / (span as f64 + 1.0),
+            expected_ticks,
+            expected_imbalance: 1.0,
+            ..Self::default()
+        }
+    }
+
+    /// Add a row closing at `price`; true if it closes the bar
+    fn update(&mut self, price: f64) -> bool {
+        if let Some(last) = self.last_price {
+            if price > last {
+                self.sign = 1.0;
+            } else if price < last {
+                self.sign = -1.0;
+            }
+        }
+        self.last_price = Some(price);
+        self.theta += self.sign;
+        self.ticks += 1.0;
+
+        let threshold = (self.expected_ticks * self.expected_imbalance).max(1.0);
+        if self.theta.abs() < threshold {
+            return false;
+        }
+
+        self.expected_ticks += self.alpha * (self.ticks - self.expected_ticks);
+        self.expected_imbalance +=
+            self.alpha * (self.theta.abs() / self.ticks - self.expected_imbalance);
+        self.theta = 0.0;
+        self.ticks = 0.0;
+        true
+    }
+}
+
+/// Accumulates rows into OHLCV bars
+#[derive(Default)]
+struct BarBuilder {
+    last_rows: Vec<IdxSize>,
+    open: Vec<f64>,
+    high: Vec<f64>,
+    low: Vec<f64>,
+    close: Vec<f64>,
+    volumes: Vec<f64>,
+    ticks: Vec<u32>,
+    in_bar: bool,
+    volume: f64,
+    value: f64,
+}
+
+impl BarBuilder {
+    /// Add row `row` to the open bar, starting one if needed
+    fn push(&mut self, row: usize, open: f64, high: f64, low: f64, close: f64, volume: f64) {
+        if !self.in_bar {
+            self.in_bar = true;
+            self.volume = 0.0;
+            self.value = 0.0;
+            self.last_rows.push(row as IdxSize);
+            self.open.push(open);
+            self.high.push(high);
+            self.low.push(low);
+            self.close.push(close);
+            self.volumes.push(0.0);
+            self.ticks.push(0);
+        }
+        let bar = self.open.len() - 1;
+        self.last_rows[bar] = row as IdxSize;
+        self.high[bar] = self.high[bar].max(high);
+        self.low[bar] = self.low[bar].min(low);
+        self.close[bar] = close;
+        self.volumes[bar] += volume;
+        self.ticks[bar] += 1;
+        self.volume += volume;
+        self.value += close * volume;
+    }
+
+    /// Close the open bar; the next row starts a new one
+    fn close(&mut self) {
+        self.in_bar = false;
+    }
+
+    fn finish(self, df: &DataFrame, time_column: &str) -> PolarsResult<DataFrame> {
+        let rows = IdxCa::from_vec("".into(), self.last_rows);
+        let times = df
+            .column(time_column)?
+            .as_materialized_series()
+            .take(&rows)?;
+
+        DataFrame::new(vec![
+            times.into(),
+            Series::new("open".into(), self.open).into(),
+            Series::new("high".into(), self.high).into(),
+            Series::new("low".into(), self.low).into(),
+            Series::new("close".into(), self.close).into(),
+            Series::new("volume".into(), self.volumes).into(),
+            Series::new("ticks".into(), self.ticks).into(),
+        ])
+    }
 }
@@ -29,10 +29,13 @@ pub struct FinancialColumns {
 /// # Example
 ///
 /// ```
-/// use ta_lib_in_rust::util::file_utils::read_csv;
+/// use rustalib::util::file_utils::read_csv;
 ///
-/// let df = read_csv("data/prices.csv", true, ',').unwrap();
-/// println!("{:?}", df);
+/// let path = std::env::temp_dir().join("rustalib_read_csv_example.csv");
+/// std::fs::write(&path, "date,close\n2024-01-02,101.5\n2024-01-03,102.0\n").unwrap();
+///
+/// let df = read_csv(&path, true, ',').unwrap();
+/// assert_eq!(df.shape(), (2, 2));
 /// ```
 pub fn read_csv<P: AsRef<Path>>(
     file_path: P,
@@ -75,10 +78,17 @@ pub fn read_csv_default<P: AsRef<Path>>(file_path: P) -> PolarsResult<DataFrame>
 /// # Example
 ///
 /// ```
-/// use ta_lib_in_rust::util::file_utils::read_parquet;
+/// use polars::prelude::*;
+/// use rustalib::util::file_utils::read_parquet;
+///
+/// let path = std::env::temp_dir().join("rustalib_read_parquet_example.parquet");
+/// let mut prices = df! { "close" => [101.5, 102.0] }.unwrap();
+/// ParquetWriter::new(std::fs::File::create(&path).unwrap())
+///     .finish(&mut prices)
+///     .unwrap();
 ///
-/// let df = read_parquet("data/prices.parquet").unwrap();
-/// println!("{:?}", df);
+/// let df = read_parquet(&path).unwrap();
+/// assert!(df.equals(&prices));
 /// ```
 pub fn read_parquet<P: AsRef<Path>>(file_path: P) -> PolarsResult<DataFrame> {
     let file = File::open(file_path)?;
@@ -122,15 +132,16 @@ pub fn read_parquet<P: AsRef<Path>>(file_path: P) -> PolarsResult<DataFrame> {
 /// # Example
 ///
 /// ```
-/// use ta_lib_in_rust::util::file_utils::read_financial_data;
+/// use rustalib::util::file_utils::read_financial_data;
 ///
-/// // Read a CSV file - automatically detects headers and delimiter
-/// let (df, columns) = read_financial_data("data/prices.csv").unwrap();
-/// println!("Close column: {:?}", columns.close);
+/// // Abbreviated column names are recognized
+/// let path = std::env::temp_dir().join("rustalib_read_financial_data_example.csv");
+/// std::fs::write(&path, "Date,O,H,L,C,Vol\n2024-01-02,100,102,99,101,5000\n").unwrap();
 ///
-/// // Read a Parquet file
-/// let (df, columns) = read_financial_data("data/prices.parquet").unwrap();
-/// println!("Volume column: {:?}", columns.volume);
+/// let (df, columns) = read_financial_data(&path).unwrap();
+/// assert_eq!(df.height(), 1);
+/// assert_eq!(columns.close.as_deref(), Some("C"));
+/// assert_eq!(columns.volume.as_deref(), Some("Vol"));
 /// ```
 ///
 /// # Supported File Types
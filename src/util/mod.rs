@@ -3,6 +3,12 @@
 // This module contains utility functions for working with DataFrames,
 // time series data, and other common operations needed for technical analysis.
 
+pub mod calendar;
+pub mod column_map;
+pub mod corporate_actions;
 pub mod dataframe_utils;
 pub mod file_utils;
 pub mod time_utils;
+pub mod validation;
+
+pub use validation::{validate_ohlcv, OhlcvRequirements, ValidationIssue, ValidationReport};
@@ -1,4 +1,4 @@
-use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Timelike, Utc};
 use polars::prelude::*;
 use std::f64::consts::PI;
 
@@ -28,6 +28,84 @@ pub fn format_date(date: &NaiveDate) -> String {
     date.format("%Y-%m-%d").to_string()
 }
 
+/// Parse a timestamp column into UTC date-times
+///
+/// Accepts `Datetime` and `Date` columns as well as string columns in the
+/// formats found in the bundled data files (`%Y-%m-%d %H:%M:%S UTC`,
+/// `%Y-%m-%d %H:%M:%S`, `%Y-%m-%d` and RFC 3339). Naive values are
+/// interpreted as UTC.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame containing the timestamp column
+/// * `time_column` - Name of the timestamp column
+///
+/// # Returns
+///
+/// Returns a vector with one entry per row; rows that cannot be parsed are `None`
+pub fn parse_timestamp_column(
+    df: &DataFrame,
+    time_column: &str,
+) -> PolarsResult<Vec<Option<DateTime<Utc>>>> {
+    let column = df.column(time_column)?;
+
+    match column.dtype() {
+        DataType::String => Ok(column
+            .str()?
+            .into_iter()
+            .map(|value| value.and_then(parse_timestamp))
+            .collect()),
+        DataType::Datetime(unit, _) => {
+            let unit = *unit;
+            let raw = column.cast(&DataType::Int64)?;
+            Ok(raw
+                .i64()?
+                .into_iter()
+                .map(|value| {
+                    value.and_then(|v| match unit {
+                        TimeUnit::Nanoseconds => Some(DateTime::from_timestamp_nanos(v)),
+                        TimeUnit::Microseconds => DateTime::from_timestamp_micros(v),
+                        TimeUnit::Milliseconds => DateTime::from_timestamp_millis(v),
+                    })
+                })
+                .collect())
+        }
+        DataType::Date => {
+            let raw = column.cast(&DataType::Int32)?;
+            Ok(raw
+                .i32()?
+                .into_iter()
+                .map(|value| {
+                    value.and_then(|days| DateTime::from_timestamp(days as i64 * 86_400, 0))
+                })
+                .collect())
+        }
+        dtype => Err(PolarsError::ComputeError(
+            format!("Column '{time_column}' has unsupported timestamp type {dtype}").into(),
+        )),
+    }
+}
+
+/// Parse a single timestamp string into a UTC date-time
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    let naive = value.strip_suffix(" UTC").unwrap_or(value);
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(naive, format) {
+            return Some(dt.and_utc());
+        }
+    }
+
+    parse_date(naive)
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+}
+
 /// Create time-based cyclical features from a time column
 ///
 /// # Arguments
@@ -0,0 +1,330 @@
+use crate::util::column_map::ColumnMap;
+use crate::util::time_utils::parse_timestamp_column;
+use polars::prelude::*;
+use std::fmt;
+
+/// What [`validate_ohlcv`] checks
+#[derive(Debug, Clone, PartialEq)]
+pub struct OhlcvRequirements {
+    /// Names of the OHLCV columns in the data
+    pub columns: ColumnMap,
+    /// Standard names ("open", "high", "low", "close", "volume") of the
+    /// columns that must be present
+    pub required: Vec<String>,
+    /// Timestamp column that must parse and strictly increase, if any
+    pub time_column: Option<String>,
+    /// Accept null and NaN prices and volumes
+    pub allow_missing: bool,
+}
+
+impl Default for OhlcvRequirements {
+    fn default() -> Self {
+        Self {
+            columns: ColumnMap::default(),
+            required: ["open", "high", "low", "close"]
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+            time_column: None,
+            allow_missing: true,
+        }
+    }
+}
+
+/// A problem found by [`validate_ohlcv`]
+///
+/// Row numbers are zero-based and refer to the first offending row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// A required column is absent
+    MissingColumn { column: String },
+    /// A price, volume or time column has a type that cannot be used
+    InvalidType { column: String, dtype: String },
+    /// Null or NaN values in a price or volume column
+    MissingValues {
+        column: String,
+        count: usize,
+        first_row: usize,
+    },
+    /// Timestamps that cannot be parsed
+    InvalidTimestamps { count: usize, first_row: usize },
+    /// A timestamp earlier than the one before it
+    UnsortedTimestamps { count: usize, first_row: usize },
+    /// A timestamp equal to the one before it
+    DuplicateTimestamps { count: usize, first_row: usize },
+    /// Negative prices
+    NegativePrice {
+        column: String,
+        count: usize,
+        first_row: usize,
+    },
+    /// Negative volume
+    NegativeVolume { count: usize, first_row: usize },
+    /// High below low
+    HighBelowLow { count: usize, first_row: usize },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::MissingColumn { column } => {
+                write!(f, "missing column '{column}'")
+            }
+            ValidationIssue::InvalidType { column, dtype } => {
+                write!(f, "column '{column}' has unsupported type {dtype}")
+            }
+            ValidationIssue::MissingValues {
+                column,
+                count,
+                first_row,
+            } => write!(
+                f,
+                "column '{column}' has {count} missing values (first at row {first_row})"
+            ),
+            ValidationIssue::InvalidTimestamps { count, first_row } => write!(
+                f,
+                "{count} timestamps cannot be parsed (first at row {first_row})"
+            ),
+            ValidationIssue::UnsortedTimestamps { count, first_row } => write!(
+                f,
+                "{count} timestamps go backwards (first at row {first_row})"
+            ),
+            ValidationIssue::DuplicateTimestamps { count, first_row } => write!(
+                f,
+                "{count} timestamps repeat the previous one (first at row {first_row})"
+            ),
+            ValidationIssue::NegativePrice {
+                column,
+                count,
+                first_row,
+            } => write!(
+                f,
+                "column '{column}' has {count} negative prices (first at row {first_row})"
+            ),
+            ValidationIssue::NegativeVolume { count, first_row } => write!(
+                f,
+                "{count} bars have negative volume (first at row {first_row})"
+            ),
+            ValidationIssue::HighBelowLow { count, first_row } => write!(
+                f,
+                "{count} bars have high below low (first at row {first_row})"
+            ),
+        }
+    }
+}
+
+/// Every problem found by [`validate_ohlcv`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ValidationReport {
+    /// Problems in the order they were found
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid OHLCV data: ")?;
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationReport {}
+
+impl From<ValidationReport> for PolarsError {
+    fn from(report: ValidationReport) -> Self {
+        PolarsError::ComputeError(report.to_string().into())
+    }
+}
+
+/// Count of rows matching a predicate and the first of them
+fn count_rows(flags: impl Iterator<Item = bool>) -> Option<(usize, usize)> {
+    let mut count = 0;
+    let mut first = None;
+    for (i, flag) in flags.enumerate() {
+        if flag {
+            count += 1;
+            first.get_or_insert(i);
+        }
+    }
+    first.map(|first_row| (count, first_row))
+}
+
+/// Check OHLCV data before handing it to indicators or strategies
+///
+/// Checks that the required columns exist and are numeric, casting integer
+/// and Float32 columns to Float64. Optionally rejects missing values. Prices
+/// must not be negative, high must not be below low, volume must not be
+/// negative, and timestamps, when a time column is given, must parse and
+/// strictly increase. Every problem is collected instead of stopping at the
+/// first.
+///
+/// # Arguments
+///
+/// * `df` - DataFrame with price data
+/// * `requirements` - Columns and checks to apply
+///
+/// # Returns
+///
+/// Returns the DataFrame with price and volume columns cast to Float64, or a
+/// ValidationReport listing every problem found. The report converts into a
+/// PolarsError, so `?` works in functions returning PolarsResult.
+///
+/// # Example
+///
+/// ```
+/// use polars::prelude::*;
+/// use rustalib::util::{validate_ohlcv, OhlcvRequirements, ValidationIssue};
+///
+/// let df = df! {
+///     "timestamp" => ["2024-01-02", "2024-01-03", "2024-01-03"],
+///     "open" => [10, 11, 12],
+///     "high" => [11.0, 10.0, 13.0],
+///     "low" => [9.0, 10.5, 11.0],
+///     "close" => [10.5, 10.2, 12.5],
+/// }
+/// .unwrap();
+///
+/// let requirements = OhlcvRequirements {
+///     time_column: Some("timestamp".to_string()),
+///     ..Default::default()
+/// };
+/// let report = validate_ohlcv(&df, &requirements).unwrap_err();
+/// assert!(report.issues.contains(&ValidationIssue::HighBelowLow { count: 1, first_row: 1 }));
+/// assert!(report
+///     .issues
+///     .contains(&ValidationIssue::DuplicateTimestamps { count: 1, first_row: 2 }));
+///
+/// let clean = df.slice(0, 1);
+/// let validated = validate_ohlcv(&clean, &requirements).unwrap();
+/// assert_eq!(validated.column("open").unwrap().dtype(), &DataType::Float64);
+/// ```
+pub fn validate_ohlcv(
+    df: &DataFrame,
+    requirements: &OhlcvRequirements,
+) -> Result<DataFrame, ValidationReport> {
+    let mut issues = Vec::new();
+    let mut output = df.clone();
+    let columns = &requirements.columns;
+    let mut values: Vec<(&str, Vec<Option<f64>>)> = Vec::new();
+
+    for (standard, name) in columns.pairs() {
+        if !df.schema().contains(name) {
+            if requirements.required.iter().any(|r| r == standard) {
+                issues.push(ValidationIssue::MissingColumn {
+                    column: name.to_string(),
+                });
+            }
+            continue;
+        }
+
+        let column = &df[name];
+        let dtype = column.dtype();
+        let cast = match column.cast(&DataType::Float64) {
+            Ok(cast) if dtype.is_primitive_numeric() => cast,
+            _ => {
+                issues.push(ValidationIssue::InvalidType {
+                    column: name.to_string(),
+                    dtype: dtype.to_string(),
+                });
+                continue;
+            }
+        };
+        let series: Vec<Option<f64>> = cast.f64().into_iter().flatten().collect();
+        if dtype != &DataType::Float64 && output.with_column(cast).is_err() {
+            issues.push(ValidationIssue::InvalidType {
+                column: name.to_string(),
+                dtype: dtype.to_string(),
+            });
+        }
+
+        if !requirements.allow_missing {
+            if let Some((count, first_row)) =
+                count_rows(series.iter().map(|v| v.is_none_or(f64::is_nan)))
+            {
+                issues.push(ValidationIssue::MissingValues {
+                    column: name.to_string(),
+                    count,
+                    first_row,
+                });
+            }
+        }
+        if let Some((count, first_row)) =
+            count_rows(series.iter().map(|v| v.is_some_and(|v| v < 0.0)))
+        {
+            issues.push(if standard == "volume" {
+                ValidationIssue::NegativeVolume { count, first_row }
+            } else {
+                ValidationIssue::NegativePrice {
+                    column: name.to_string(),
+                    count,
+                    first_row,
+                }
+            });
+        }
+        values.push((standard, series));
+    }
+
+    let find = |standard: &str| values.iter().find(|(s, _)| *s == standard);
+    if let (Some((_, high)), Some((_, low))) = (find("high"), find("low")) {
+        let below = high.iter().zip(low).map(|(h, l)| match (h, l) {
+            (Some(h), Some(l)) => h < l,
+            _ => false,
+        });
+        if let Some((count, first_row)) = count_rows(below) {
+            issues.push(ValidationIssue::HighBelowLow { count, first_row });
+        }
+    }
+
+    if let Some(time_column) = &requirements.time_column {
+        if !df.schema().contains(time_column) {
+            issues.push(ValidationIssue::MissingColumn {
+                column: time_column.clone(),
+            });
+        } else {
+            match parse_timestamp_column(df, time_column) {
+                Ok(times) => {
+                    if let Some((count, first_row)) = count_rows(times.iter().map(Option::is_none))
+                    {
+                        issues.push(ValidationIssue::InvalidTimestamps { count, first_row });
+                    }
+                    let steps: Vec<Option<std::cmp::Ordering>> = (0..times.len())
+                        .map(
+                            |i| match (i.checked_sub(1).and_then(|p| times[p]), times[i]) {
+                                (Some(prev), Some(time)) => Some(time.cmp(&prev)),
+                                _ => None,
+                            },
+                        )
+                        .collect();
+                    if let Some((count, first_row)) =
+                        count_rows(steps.iter().map(|s| *s == Some(std::cmp::Ordering::Less)))
+                    {
+                        issues.push(ValidationIssue::UnsortedTimestamps { count, first_row });
+                    }
+                    if let Some((count, first_row)) =
+                        count_rows(steps.iter().map(|s| *s == Some(std::cmp::Ordering::Equal)))
+                    {
+                        issues.push(ValidationIssue::DuplicateTimestamps { count, first_row });
+                    }
+                }
+                Err(_) => issues.push(ValidationIssue::InvalidType {
+                    column: time_column.clone(),
+                    dtype: df
+                        .column(time_column)
+                        .map(|c| c.dtype().to_string())
+                        .unwrap_or_default(),
+                }),
+            }
+        }
+    }
+
+    if issues.is_empty() {
+        Ok(output)
+    } else {
+        Err(ValidationReport { issues })
+    }
+}
@@ -0,0 +1,107 @@
+//! Provider parsing against canned responses from a local HTTP server
+#![cfg(feature = "fetch")]
+
+use chrono::{TimeZone, Utc};
+use polars::prelude::*;
+use rustalib::io::fetch::{fetch_ohlcv, AlphaVantage, BarInterval, DataProvider, YahooFinance};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+/// Serve `body` as JSON to `requests` connections and return the base URL
+fn serve(body: &'static str, requests: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+    });
+    url
+}
+
+fn close_values(df: &DataFrame) -> Vec<Option<f64>> {
+    df.column("close").unwrap().f64().unwrap().to_vec()
+}
+
+#[test]
+fn yahoo_chart_skips_null_bars() {
+    let body = r#"{"chart": {"result": [{
+        "timestamp": [1704205800, 1704292200, 1704378600],
+        "indicators": {"quote": [{
+            "open": [10.0, null, 11.0],
+            "high": [10.5, null, 11.5],
+            "low": [9.5, null, 10.5],
+            "close": [10.2, null, 11.2],
+            "volume": [1000, null, 1200]
+        }]}
+    }], "error": null}}"#;
+    let provider = YahooFinance::with_base_url(serve(body, 1));
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap();
+
+    let df = provider
+        .fetch_bars("AAPL", BarInterval::Daily, start, end)
+        .unwrap();
+    assert_eq!(
+        df.get_column_names(),
+        ["timestamp", "open", "high", "low", "close", "volume"]
+    );
+    assert_eq!(close_values(&df), vec![Some(10.2), Some(11.2)]);
+}
+
+#[test]
+fn alpha_vantage_intraday_is_sorted_and_converted_to_utc() {
+    let body = r#"{
+        "Meta Data": {"6. Time Zone": "US/Eastern"},
+        "Time Series (5min)": {
+            "2024-01-03 09:35:00": {"1. open": "10.1", "2. high": "10.3", "3. low": "10.0", "4. close": "10.2", "5. volume": "800"},
+            "2024-01-03 09:30:00": {"1. open": "10.0", "2. high": "10.2", "3. low": "9.9", "4. close": "10.1", "5. volume": "500"}
+        }
+    }"#;
+    let provider = AlphaVantage::new("demo").with_base_url(serve(body, 2));
+    let start = Utc.with_ymd_and_hms(2024, 1, 3, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2024, 1, 4, 0, 0, 0).unwrap();
+
+    let bars = fetch_ohlcv(
+        &provider,
+        &["IBM", "MSFT"],
+        BarInterval::Minute5,
+        start,
+        end,
+    )
+    .unwrap();
+    assert_eq!(bars[1].0, "MSFT");
+    let df = &bars[0].1;
+    assert_eq!(close_values(df), vec![Some(10.1), Some(10.2)]);
+    let first = df
+        .column("timestamp")
+        .unwrap()
+        .cast(&DataType::Int64)
+        .unwrap();
+    // 09:30 New York is 14:30 UTC in January
+    assert_eq!(first.i64().unwrap().get(0), Some(1_704_292_200_000));
+}
+
+#[test]
+fn alpha_vantage_reports_api_errors() {
+    let body = r#"{"Error Message": "Invalid API call."}"#;
+    let provider = AlphaVantage::new("demo").with_base_url(serve(body, 1));
+    let now = Utc::now();
+
+    let err = provider
+        .fetch_bars("NOPE", BarInterval::Daily, now, now)
+        .unwrap_err();
+    assert!(err.to_string().contains("Invalid API call"));
+}
@@ -0,0 +1,53 @@
+//! The lazy indicator pipeline must reproduce the eager one.
+
+use polars::prelude::*;
+use rustalib::indicators::{
+    add_technical_indicators_lazy, add_technical_indicators_with_config, IndicatorConfig,
+};
+
+fn ohlcv() -> DataFrame {
+    let close: Vec<f64> = (0..80)
+        .map(|i| 100.0 + (i as f64 / 4.0).sin() * 3.0 + i as f64 * 0.1)
+        .collect();
+    df! {
+        "open" => close.iter().map(|c| c - 0.3).collect::<Vec<_>>(),
+        "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+        "low" => close.iter().map(|c| c - 1.2).collect::<Vec<_>>(),
+        "close" => &close,
+        "volume" => close.iter().map(|c| c * 10.0).collect::<Vec<_>>(),
+    }
+    .unwrap()
+}
+
+fn values(df: &DataFrame, column: &str) -> Vec<f64> {
+    df.column(column)
+        .unwrap()
+        .f64()
+        .unwrap()
+        .iter()
+        .map(|v| v.unwrap_or(f64::NAN))
+        .collect()
+}
+
+#[test]
+fn lazy_bollinger_bands_match_eager() {
+    let config = IndicatorConfig {
+        volatility: true,
+        ..IndicatorConfig::none()
+    };
+    let eager = add_technical_indicators_with_config(&mut ohlcv(), &config).unwrap();
+    let lazy = add_technical_indicators_lazy(ohlcv().lazy(), &config)
+        .collect()
+        .unwrap();
+
+    for column in ["bb_middle", "bb_upper", "bb_lower", "bb_b"] {
+        let (e, l) = (values(&eager, column), values(&lazy, column));
+        assert_eq!(e.len(), l.len());
+        for (i, (e, l)) in e.iter().zip(&l).enumerate() {
+            assert!(
+                e == l || (e.is_nan() && l.is_nan()) || (e - l).abs() < 1e-9,
+                "{column}[{i}]: eager {e}, lazy {l}"
+            );
+        }
+    }
+}
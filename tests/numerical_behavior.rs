@@ -0,0 +1,235 @@
+//! Tests for the behavior flags listed in CHANGELOG.md.
+//!
+//! Each flag keeps its legacy path reproducible, so both the corrected and
+//! the legacy output are pinned here.
+
+use approx::assert_abs_diff_eq;
+use polars::prelude::*;
+use rustalib::indicators::momentum::{
+    calculate_cmo, calculate_cmo_with_smoothing, calculate_rsi, calculate_rsi_with_alignment,
+    CmoSmoothing, RsiChangeAlignment,
+};
+use rustalib::indicators::moving_averages::{
+    calculate_ema, calculate_ema_with_nan_handling, EmaNanHandling,
+};
+use rustalib::indicators::trend::{calculate_aroon, calculate_aroon_with_lookback, AroonLookback};
+use rustalib::indicators::volatility::{
+    calculate_atr, calculate_atr_with_seeding, calculate_bollinger_bands,
+    calculate_bollinger_bands_with_deviation, AtrSeeding, BollingerDeviation,
+};
+
+fn ema_values(df: &DataFrame, window: usize, handling: EmaNanHandling) -> Vec<f64> {
+    calculate_ema_with_nan_handling(df, "close", window, handling)
+        .unwrap()
+        .f64()
+        .unwrap()
+        .into_no_null_iter()
+        .collect()
+}
+
+fn assert_values(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        if e.is_nan() {
+            assert!(a.is_nan(), "expected NaN, got {a}");
+        } else {
+            assert_abs_diff_eq!(*a, *e, epsilon = 1e-12);
+        }
+    }
+}
+
+#[test]
+fn ema_paths_agree_without_missing_values() {
+    let df = df! { "close" => [1.0, 2.0, 3.0, 4.0, 5.0, 6.0] }.unwrap();
+    let skip = ema_values(&df, 3, EmaNanHandling::Skip);
+    let legacy = ema_values(&df, 3, EmaNanHandling::Legacy);
+    assert_values(&skip, &legacy);
+    assert_values(&skip, &[f64::NAN, f64::NAN, 2.0, 3.0, 4.0, 5.0]);
+}
+
+#[test]
+fn ema_default_skips_missing_values() {
+    let df = df! { "close" => [1.0, 2.0, 3.0, f64::NAN, 5.0] }.unwrap();
+    let default: Vec<f64> = calculate_ema(&df, "close", 2)
+        .unwrap()
+        .f64()
+        .unwrap()
+        .into_no_null_iter()
+        .collect();
+    // seed 1.5, then 3 -> 2.5, NaN carries 2.5, then 5 -> 4.1666...
+    assert_values(
+        &default,
+        &[f64::NAN, 1.5, 2.5, 2.5, 5.0 * 2.0 / 3.0 + 2.5 / 3.0],
+    );
+    assert_values(&default, &ema_values(&df, 2, EmaNanHandling::Skip));
+}
+
+#[test]
+fn ema_skip_seeds_from_valid_values() {
+    let df = df! { "close" => [Some(2.0), None, Some(4.0), Some(6.0)] }.unwrap();
+    assert_values(
+        &ema_values(&df, 2, EmaNanHandling::Skip),
+        &[f64::NAN, f64::NAN, 3.0, 3.0 / 3.0 + 6.0 * 2.0 / 3.0],
+    );
+}
+
+#[test]
+fn ema_legacy_reproduces_previous_output() {
+    let nan_df = df! { "close" => [1.0, 2.0, 3.0, f64::NAN, 5.0] }.unwrap();
+    assert_values(
+        &ema_values(&nan_df, 2, EmaNanHandling::Legacy),
+        &[f64::NAN, 1.5, 2.5, f64::NAN, f64::NAN],
+    );
+
+    // Nulls were read as zero
+    let null_df = df! { "close" => [Some(2.0), None, Some(4.0), Some(6.0)] }.unwrap();
+    assert_values(
+        &ema_values(&null_df, 2, EmaNanHandling::Legacy),
+        &[
+            f64::NAN,
+            1.0,
+            4.0 * 2.0 / 3.0 + 1.0 / 3.0,
+            6.0 * 2.0 / 3.0 + 3.0 / 3.0,
+        ],
+    );
+}
+
+fn values(series: Series) -> Vec<f64> {
+    series
+        .f64()
+        .unwrap()
+        .iter()
+        .map(|v| v.unwrap_or(f64::NAN))
+        .collect()
+}
+
+fn hlc() -> DataFrame {
+    df! {
+        "high" => [11.0, 12.0, 13.0, 12.5],
+        "low" => [9.0, 11.0, 12.0, 11.0],
+        "close" => [10.0, 11.5, 12.5, 11.5],
+    }
+    .unwrap()
+}
+
+#[test]
+fn atr_default_starts_on_second_bar() {
+    // True ranges: undefined, 2.0, 1.5, 1.5
+    let expected = [f64::NAN, f64::NAN, 1.75, 1.625];
+    assert_values(&values(calculate_atr(&hlc(), 2).unwrap()), &expected);
+    assert_values(
+        &values(calculate_atr_with_seeding(&hlc(), 2, AtrSeeding::SecondBar).unwrap()),
+        &expected,
+    );
+}
+
+#[test]
+fn atr_legacy_reproduces_previous_output() {
+    // The first true range was the first bar's high - low
+    assert_values(
+        &values(calculate_atr_with_seeding(&hlc(), 2, AtrSeeding::Legacy).unwrap()),
+        &[f64::NAN, 2.0, 1.75, 1.625],
+    );
+}
+
+#[test]
+fn bollinger_default_uses_population_deviation() {
+    let df = df! { "close" => [1.0, 3.0, 5.0] }.unwrap();
+    let default = calculate_bollinger_bands(&df, 2, 1.0, "close").unwrap();
+    assert_values(&values(default.upper), &[0.0, 3.0, 5.0]);
+    let population = calculate_bollinger_bands_with_deviation(
+        &df,
+        2,
+        1.0,
+        "close",
+        BollingerDeviation::Population,
+    )
+    .unwrap();
+    assert_values(&values(population.lower), &[0.0, 1.0, 3.0]);
+}
+
+#[test]
+fn bollinger_legacy_reproduces_previous_output() {
+    let df = df! { "close" => [1.0, 3.0, 5.0] }.unwrap();
+    let legacy =
+        calculate_bollinger_bands_with_deviation(&df, 2, 1.0, "close", BollingerDeviation::Legacy)
+            .unwrap();
+    let sqrt2 = 2f64.sqrt();
+    assert_values(&values(legacy.upper), &[0.0, 2.0 + sqrt2, 4.0 + sqrt2]);
+    assert_values(&values(legacy.lower), &[0.0, 2.0 - sqrt2, 4.0 - sqrt2]);
+}
+
+#[test]
+fn cmo_default_uses_wilder_smoothing() {
+    // Changes +2, -1, +1, 0
+    let df = df! { "close" => [10.0, 12.0, 11.0, 12.0, 12.0] }.unwrap();
+    let expected = [f64::NAN, f64::NAN, 100.0 / 3.0, 60.0, 60.0];
+    assert_values(&values(calculate_cmo(&df, 2, "close").unwrap()), &expected);
+    assert_values(
+        &values(calculate_cmo_with_smoothing(&df, 2, "close", CmoSmoothing::Wilder).unwrap()),
+        &expected,
+    );
+}
+
+#[test]
+fn cmo_legacy_reproduces_previous_output() {
+    let df = df! { "close" => [10.0, 12.0, 11.0, 12.0, 12.0] }.unwrap();
+    assert_values(
+        &values(calculate_cmo_with_smoothing(&df, 2, "close", CmoSmoothing::Legacy).unwrap()),
+        &[f64::NAN, f64::NAN, 100.0 / 3.0, 0.0, 100.0],
+    );
+}
+
+fn highs_and_lows() -> DataFrame {
+    df! {
+        "high" => [3.0, 2.0, 1.0, 1.5, 2.5],
+        "low" => [2.0, 1.0, 0.5, 1.0, 1.5],
+    }
+    .unwrap()
+}
+
+#[test]
+fn aroon_default_looks_back_over_window_plus_current_bar() {
+    let expected_up = [f64::NAN, f64::NAN, 0.0, 0.0, 100.0];
+    let expected_down = [f64::NAN, f64::NAN, 100.0, 50.0, 0.0];
+    for (up, down) in [
+        calculate_aroon(&highs_and_lows(), 2).unwrap(),
+        calculate_aroon_with_lookback(&highs_and_lows(), 2, AroonLookback::WindowPlusCurrent)
+            .unwrap(),
+    ] {
+        assert_values(&values(up), &expected_up);
+        assert_values(&values(down), &expected_down);
+    }
+}
+
+#[test]
+fn aroon_legacy_reproduces_previous_output() {
+    let (up, down) =
+        calculate_aroon_with_lookback(&highs_and_lows(), 2, AroonLookback::Legacy).unwrap();
+    assert_values(&values(up), &[f64::NAN, 50.0, 50.0, 100.0, 100.0]);
+    assert_values(&values(down), &[f64::NAN, 100.0, 100.0, 50.0, 50.0]);
+}
+
+#[test]
+fn rsi_default_smooths_each_bar_with_its_own_change() {
+    // Changes +2, -2, +2, +2
+    let df = df! { "close" => [10.0, 12.0, 10.0, 12.0, 14.0] }.unwrap();
+    let expected = [f64::NAN, f64::NAN, 50.0, 75.0, 87.5];
+    assert_values(&values(calculate_rsi(&df, 2, "close").unwrap()), &expected);
+    assert_values(
+        &values(
+            calculate_rsi_with_alignment(&df, 2, "close", RsiChangeAlignment::CurrentBar).unwrap(),
+        ),
+        &expected,
+    );
+}
+
+#[test]
+fn rsi_legacy_reproduces_previous_output() {
+    // Bar 3 adds bar 2's -2 and bar 4 adds bar 3's +2
+    let df = df! { "close" => [10.0, 12.0, 10.0, 12.0, 14.0] }.unwrap();
+    assert_values(
+        &values(calculate_rsi_with_alignment(&df, 2, "close", RsiChangeAlignment::Legacy).unwrap()),
+        &[f64::NAN, f64::NAN, 50.0, 25.0, 62.5],
+    );
+}
@@ -0,0 +1,76 @@
+#!/usr/bin/env python3
+"""Write linear.onnx, the model evaluated by the ONNX inference tests.
+
+    python3 tests/onnx_inference/generate.py
+
+The model is `prediction = features @ WEIGHTS + BIAS` for a [1, 3] float
+input named "features", giving a [1, 1] output named "prediction". The
+protobuf is encoded by hand so the script needs no `onnx` package.
+"""
+
+import os
+import struct
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+WEIGHTS = [0.5, -1.0, 2.0]
+BIAS = 0.25
+FLOAT = 1  # TensorProto.FLOAT
+
+
+def varint(n):
+    out = b""
+    while True:
+        byte = n & 0x7F
+        n >>= 7
+        if n:
+            out += bytes([byte | 0x80])
+        else:
+            return out + bytes([byte])
+
+
+def field(number, value):
+    """Length-delimited field for bytes/str/submessages, varint field for ints"""
+    if isinstance(value, int):
+        return varint(number << 3) + varint(value)
+    if isinstance(value, str):
+        value = value.encode()
+    return varint(number << 3 | 2) + varint(len(value)) + value
+
+
+def tensor(name, dims, values):
+    # TensorProto: dims = 1, data_type = 2, raw_data = 9, name = 8
+    body = b"".join(field(1, d) for d in dims) + field(2, FLOAT) + field(8, name)
+    return body + field(9, struct.pack(f"<{len(values)}f", *values))
+
+
+def value_info(name, dims):
+    # ValueInfoProto { name = 1, type = 2 { tensor_type = 1 { elem_type = 1, shape = 2 } } }
+    shape = b"".join(field(1, field(1, d)) for d in dims)
+    tensor_type = field(1, FLOAT) + field(2, shape)
+    return field(1, name) + field(2, field(1, tensor_type))
+
+
+def node(op, inputs, outputs):
+    # NodeProto: input = 1, output = 2, op_type = 4
+    return b"".join(field(1, i) for i in inputs) + b"".join(field(2, o) for o in outputs) + field(4, op)
+
+
+def main():
+    # GraphProto: node = 1, name = 2, initializer = 5, input = 11, output = 12
+    graph = (
+        field(1, node("MatMul", ["features", "weights"], ["product"]))
+        + field(1, node("Add", ["product", "bias"], ["prediction"]))
+        + field(2, "linear")
+        + field(5, tensor("weights", [3, 1], WEIGHTS))
+        + field(5, tensor("bias", [1], [BIAS]))
+        + field(11, value_info("features", [1, 3]))
+        + field(12, value_info("prediction", [1, 1]))
+    )
+    # ModelProto: ir_version = 1, producer_name = 2, graph = 7, opset_import = 8 { version = 2 }
+    model = field(1, 7) + field(2, "rustalib") + field(7, graph) + field(8, field(2, 13))
+    with open(os.path.join(HERE, "linear.onnx"), "wb") as f:
+        f.write(model)
+
+
+if __name__ == "__main__":
+    main()
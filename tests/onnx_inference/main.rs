@@ -0,0 +1,117 @@
+//! ONNX inference on a hand-built linear model.
+//!
+//! `linear.onnx` is written by `generate.py` and computes
+//! `0.5 * x0 - 1.0 * x1 + 2.0 * x2 + 0.25` for a `[1, 3]` input.
+#![cfg(feature = "onnx")]
+
+use approx::assert_abs_diff_eq;
+use polars::prelude::*;
+use rustalib::error::TaError;
+use rustalib::ml::features::{build_feature_matrix, FeatureConfig};
+use rustalib::ml::onnx::OnnxModel;
+use rustalib::strategy::combiner::{SignalCombiner, SignalRule};
+
+const MODEL: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/onnx_inference/linear.onnx"
+);
+
+fn bars() -> DataFrame {
+    let close: Vec<f64> = (0..40)
+        .map(|i| 100.0 + (i as f64 / 3.0).sin() * 4.0)
+        .collect();
+    df! {
+        "open" => &close,
+        "high" => close.iter().map(|c| c + 1.0).collect::<Vec<_>>(),
+        "low" => close.iter().map(|c| c - 1.0).collect::<Vec<_>>(),
+        "close" => &close,
+    }
+    .unwrap()
+}
+
+#[test]
+fn predicts_on_feature_matrix() {
+    let config = FeatureConfig {
+        indicators: rustalib::indicators::IndicatorConfig::none(),
+        return_periods: vec![1, 2],
+        rolling_windows: vec![3],
+        label_horizons: vec![1],
+        ..FeatureConfig::default()
+    };
+    let matrix = build_feature_matrix(&bars(), &config).unwrap();
+    assert_eq!(
+        matrix.features,
+        ["return_1", "return_2", "return_mean_3", "return_std_3"]
+    );
+
+    let features = matrix.features[..3].to_vec();
+    let model = OnnxModel::load(MODEL, 3).unwrap();
+    let prediction = model.predict(&matrix.data, &features, "score").unwrap();
+    assert_eq!(prediction.name().as_str(), "score");
+
+    let column = |name: &str| matrix.data.column(name).unwrap().f64().unwrap().clone();
+    let (r1, r2, mean) = (
+        column("return_1"),
+        column("return_2"),
+        column("return_mean_3"),
+    );
+    for (i, value) in prediction.f64().unwrap().into_iter().enumerate() {
+        let expected =
+            0.5 * r1.get(i).unwrap() - r2.get(i).unwrap() + 2.0 * mean.get(i).unwrap() + 0.25;
+        assert_abs_diff_eq!(value.unwrap(), expected, epsilon = 1e-5);
+    }
+}
+
+#[test]
+fn from_bytes_matches_load() {
+    let df = df! { "a" => [1.0], "b" => [2.0], "c" => [3.0] }.unwrap();
+    let features = ["a", "b", "c"].map(String::from);
+    let loaded = OnnxModel::load(MODEL, 3).unwrap();
+    let read = OnnxModel::from_bytes(&std::fs::read(MODEL).unwrap(), 3).unwrap();
+    assert!(loaded
+        .predict(&df, &features, "p")
+        .unwrap()
+        .equals(&read.predict(&df, &features, "p").unwrap()));
+}
+
+#[test]
+fn prediction_feeds_signal_combiner() {
+    let mut df = df! {
+        "a" => [1.0, -1.0, 0.0],
+        "b" => [0.0, 1.0, 0.0],
+        "c" => [0.0, 0.0, 0.0],
+    }
+    .unwrap();
+    let model = OnnxModel::load(MODEL, 3).unwrap();
+    let features = ["a", "b", "c"].map(String::from);
+    df.with_column(model.predict(&df, &features, "prediction").unwrap())
+        .unwrap();
+
+    let combiner = SignalCombiner {
+        strong_threshold: 1.0,
+        ..SignalCombiner::new()
+    }
+    .with_component("prediction", SignalRule::Sign, 1.0);
+    let signal = combiner.combine(&df).unwrap();
+    let values: Vec<i32> = signal.i32().unwrap().into_no_null_iter().collect();
+    assert_eq!(values, [2, -2, 2]);
+}
+
+#[test]
+fn rejects_wrong_feature_count_and_output() {
+    let df = df! { "a" => [1.0], "b" => [2.0] }.unwrap();
+    let model = OnnxModel::load(MODEL, 3).unwrap();
+    let err = model
+        .predict(&df, &["a", "b"].map(String::from), "p")
+        .unwrap_err();
+    assert!(matches!(err, TaError::InvalidParameter { .. }));
+
+    let df = df! { "a" => [1.0], "b" => [2.0], "c" => [3.0] }.unwrap();
+    let err = model
+        .with_output(0, 1)
+        .predict(&df, &["a", "b", "c"].map(String::from), "p")
+        .unwrap_err();
+    assert!(matches!(err, TaError::ComputationError(_)));
+
+    assert!(OnnxModel::from_bytes(b"not a model", 3).is_err());
+}
@@ -0,0 +1,784 @@
+//! Snapshot tests for the bundled strategies.
+//!
+//! Each strategy runs on a fixed synthetic dataset and its signal vectors,
+//! trades and performance metrics are rendered to text and compared with the
+//! files in `tests/snapshots/`. A refactor that changes trading behavior
+//! fails here instead of passing silently.
+//!
+//! After an intentional behavior change, regenerate the snapshots with
+//!
+//! ```text
+//! UPDATE_SNAPSHOTS=1 cargo test --test strategy_snapshots
+//! ```
+//!
+//! and review the diff before committing.
+
+use chrono::{Duration, TimeZone, Utc};
+use polars::prelude::*;
+use rustalib::backtest::{
+    run_backtest, BacktestParams, BarContext, EventStrategy, Fill, OrderId, OrderSide, OrderType,
+};
+use rustalib::indicators::options::black_scholes_price;
+use rustalib::performance::TradeSide;
+use rustalib::strategy::builder::ConfigStrategy;
+use rustalib::strategy::crypto::{arbitrage, grid_trading, market_neutral};
+use rustalib::strategy::options::{delta_neutral, iron_condor, vertical_spreads, wheel};
+use rustalib::strategy::stock::{donchian_breakout, momentum_rotation, pairs_trading};
+use rustalib::strategy::StrategySignals;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+const BARS: usize = 240;
+
+/// Deterministic pseudo-random noise in [-0.5, 0.5)
+fn noise(seed: u64, i: usize) -> f64 {
+    let mut x = seed
+        .wrapping_add(i as u64)
+        .wrapping_mul(6364136223846793005);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xff51afd7ed558ccd);
+    x ^= x >> 33;
+    (x % 10_000) as f64 / 10_000.0 - 0.5
+}
+
+fn timestamps(bars: usize) -> Vec<String> {
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+    (0..bars)
+        .map(|i| {
+            (start + Duration::minutes(i as i64))
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .collect()
+}
+
+/// Oscillating minute bars drifting slowly upward around 100
+fn synthetic_ohlcv() -> DataFrame {
+    let close: Vec<f64> = (0..BARS)
+        .map(|i| 100.0 + 4.0 * (i as f64 / 12.0).sin() + 0.01 * i as f64 + noise(1, i))
+        .collect();
+    let open: Vec<f64> = (0..BARS)
+        .map(|i| if i == 0 { 100.0 } else { close[i - 1] })
+        .collect();
+    let high: Vec<f64> = (0..BARS)
+        .map(|i| open[i].max(close[i]) + 0.3 + noise(2, i).abs())
+        .collect();
+    let low: Vec<f64> = (0..BARS)
+        .map(|i| open[i].min(close[i]) - 0.3 - noise(3, i).abs())
+        .collect();
+    let volume: Vec<f64> = (0..BARS).map(|i| 1_000.0 + 500.0 * noise(4, i)).collect();
+
+    df! {
+        "timestamp" => timestamps(BARS),
+        "open" => open,
+        "high" => high,
+        "low" => low,
+        "close" => close,
+        "volume" => volume,
+    }
+    .unwrap()
+}
+
+/// A price feed that tracks `base` with a venue-specific premium and noise
+fn synthetic_feed(base: &[f64], premium: f64, seed: u64) -> DataFrame {
+    let close: Vec<f64> = base
+        .iter()
+        .enumerate()
+        .map(|(i, p)| p * (1.0 + premium) + 0.4 * noise(seed, i))
+        .collect();
+    df! {
+        "timestamp" => timestamps(base.len()),
+        "close" => close,
+    }
+    .unwrap()
+}
+
+/// Two stocks whose spread `A - 2 * B - 5` is a mean-reverting AR(1) process
+fn synthetic_pair() -> (DataFrame, DataFrame) {
+    let mut b = vec![50.0];
+    let mut spread = vec![0.0];
+    for i in 1..BARS {
+        b.push(b[i - 1] + noise(40, i));
+        spread.push(0.7 * spread[i - 1] + noise(41, i));
+    }
+    let a: Vec<f64> = (0..BARS).map(|i| 2.0 * b[i] + 5.0 + spread[i]).collect();
+    (
+        df! { "timestamp" => timestamps(BARS), "close" => a }.unwrap(),
+        df! { "timestamp" => timestamps(BARS), "close" => b }.unwrap(),
+    )
+}
+
+/// Daily closes for 120 days and weekly chain snapshots with a volatility skew
+fn synthetic_options_market() -> (DataFrame, DataFrame) {
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+    let days = 120;
+    let times: Vec<_> = (0..days).map(|d| start + Duration::days(d)).collect();
+    let close: Vec<f64> = (0..days as usize)
+        .map(|d| 100.0 + 6.0 * (d as f64 / 15.0).sin() + 2.0 * noise(5, d))
+        .collect();
+    let expiries: Vec<_> = (1..=6).map(|m| start + Duration::days(30 * m)).collect();
+
+    let (mut quote_time, mut strike, mut expiry, mut option_type, mut price) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for (d, time) in times.iter().enumerate().step_by(7) {
+        for exp in expiries.iter().filter(|e| *e > time) {
+            let t = (*exp - *time).num_days() as f64 / 365.0;
+            for k in (70..=130).step_by(5) {
+                let k = k as f64;
+                let vol = 0.22 + 0.3 * (k / close[d]).ln().powi(2) - 0.1 * (k / close[d]).ln();
+                for call in [true, false] {
+                    quote_time.push(time.format("%Y-%m-%d %H:%M:%S").to_string());
+                    strike.push(k);
+                    expiry.push(exp.format("%Y-%m-%d").to_string());
+                    option_type.push(if call { "call" } else { "put" });
+                    price.push(black_scholes_price(close[d], k, t, 0.02, 0.0, vol, call));
+                }
+            }
+        }
+    }
+
+    let underlying = df! {
+        "timestamp" => times.iter().map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()).collect::<Vec<_>>(),
+        "close" => close,
+    }
+    .unwrap();
+    let chain = df! {
+        "timestamp" => quote_time,
+        "strike" => strike,
+        "expiry" => expiry,
+        "option_type" => option_type,
+        "price" => price,
+    }
+    .unwrap();
+    (underlying, chain)
+}
+
+fn render(signals: &StrategySignals) -> String {
+    let join = |values: &[i32]| {
+        values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+
+    let mut out = String::new();
+    writeln!(out, "buy_signals: {}", join(&signals.buy_signals)).unwrap();
+    writeln!(out, "sell_signals: {}", join(&signals.sell_signals)).unwrap();
+
+    writeln!(out, "trades:").unwrap();
+    for trade in &signals.trades {
+        writeln!(
+            out,
+            "  {} -> {} {:?} qty={:.4} entry={:.4} exit={:.4} fees={:.4} pnl={:.4}",
+            trade.entry_time.format("%Y-%m-%d %H:%M"),
+            trade.exit_time.format("%Y-%m-%d %H:%M"),
+            trade.side,
+            trade.quantity,
+            trade.entry_price,
+            trade.exit_price,
+            trade.fees,
+            trade.pnl,
+        )
+        .unwrap();
+    }
+
+    let report = signals.performance_report();
+    writeln!(out, "report:").unwrap();
+    writeln!(out, "  total_trades: {}", report.total_trades).unwrap();
+    writeln!(out, "  winning_trades: {}", report.winning_trades).unwrap();
+    writeln!(out, "  losing_trades: {}", report.losing_trades).unwrap();
+    for (name, value) in [
+        ("win_rate", report.win_rate),
+        ("total_pnl", report.total_pnl),
+        ("total_fees", report.total_fees),
+        ("profit_factor", report.profit_factor),
+        ("expectancy", report.expectancy),
+        ("max_drawdown", report.max_drawdown),
+        ("average_holding_minutes", report.average_holding_minutes),
+    ] {
+        writeln!(out, "  {name}: {value:.6}").unwrap();
+    }
+
+    let values = |series: &Series| -> Vec<f64> {
+        assert_eq!(series.len(), signals.buy_signals.len());
+        series.f64().unwrap().into_no_null_iter().collect()
+    };
+    let equity = values(&signals.equity_curve);
+    let drawdown = values(&signals.drawdown);
+    let exposure = values(&signals.exposure);
+    let max = |values: &[f64]| values.iter().copied().fold(0.0, f64::max);
+    writeln!(out, "equity:").unwrap();
+    for (name, value) in [
+        ("final_equity", equity[equity.len() - 1]),
+        ("max_drawdown", max(&drawdown)),
+        ("max_exposure", max(&exposure)),
+        (
+            "bars_exposed",
+            exposure.iter().filter(|&&x| x > 0.0).count() as f64,
+        ),
+    ] {
+        writeln!(out, "  {name}: {value:.6}").unwrap();
+    }
+    out
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.snap"));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing snapshot {}; run with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+    if expected != actual {
+        let first_diff = expected
+            .lines()
+            .zip(actual.lines())
+            .position(|(e, a)| e != a)
+            .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+        panic!(
+            "snapshot {name} changed at line {}\n--- expected\n{}\n+++ actual\n{}\n\
+             If the change is intended, rerun with UPDATE_SNAPSHOTS=1 and review the diff.",
+            first_diff + 1,
+            expected.lines().nth(first_diff).unwrap_or("<end of file>"),
+            actual.lines().nth(first_diff).unwrap_or("<end of file>"),
+        );
+    }
+}
+
+#[test]
+fn grid_trading_arithmetic_snapshot() {
+    let df = synthetic_ohlcv();
+    let params = grid_trading::StrategyParams::new(95.0, 107.0);
+    let signals = grid_trading::run_strategy(&df, &params).unwrap();
+    assert_snapshot("grid_trading_arithmetic", &render(&signals));
+}
+
+#[test]
+fn grid_trading_geometric_snapshot() {
+    let df = synthetic_ohlcv();
+    let params = grid_trading::StrategyParams {
+        num_grids: 6,
+        spacing: grid_trading::GridSpacing::Geometric,
+        rebalance_on_fill: false,
+        ..grid_trading::StrategyParams::new(96.0, 106.0)
+    };
+    let signals = grid_trading::run_strategy(&df, &params).unwrap();
+    assert_snapshot("grid_trading_geometric", &render(&signals));
+}
+
+#[test]
+fn config_strategy_snapshot() {
+    let df = synthetic_ohlcv();
+    let strategy = ConfigStrategy::from_json(
+        r#"{
+            "name": "rsi fade",
+            "side": "short",
+            "indicators": [
+                { "type": "rsi", "name": "rsi", "period": 14 },
+                { "type": "macd", "name": "macd", "fast_period": 12, "slow_period": 26, "signal_period": 9 }
+            ],
+            "entry": [
+                { "type": "above", "left": "rsi", "right": 60 },
+                { "type": "crosses_below", "left": "macd", "right": "macd_signal" }
+            ],
+            "exit": [{ "type": "below", "left": "rsi", "right": 45 }],
+            "risk": { "fee_rate": 0.001, "stop_loss_pct": 2.0, "max_holding_bars": 30 }
+        }"#,
+    )
+    .unwrap();
+    let signals = strategy.run(&df).unwrap();
+    assert_snapshot("config_strategy_rsi_fade", &render(&signals));
+}
+
+#[test]
+fn config_strategy_atr_stops_snapshot() {
+    let df = synthetic_ohlcv();
+    let strategy = ConfigStrategy::from_toml(
+        r#"
+        name = "sma trend with atr stops"
+
+        [[indicators]]
+        type = "sma"
+        name = "sma_20"
+        period = 20
+
+        [[entry]]
+        type = "crosses_above"
+        left = "close"
+        right = "sma_20"
+
+        [risk]
+        fee_rate = 0.001
+
+        [[risk.stops.policies]]
+        type = "fixed_atr"
+        multiple = 1.5
+
+        [[risk.stops.policies]]
+        type = "chandelier"
+        multiple = 3.0
+
+        [[risk.stops.policies]]
+        type = "breakeven"
+        after_r = 1.0
+
+        [[risk.stops.policies]]
+        type = "time_stop"
+        bars = 40
+
+        [[risk.stops.scale_outs]]
+        at_r = 1.0
+        fraction = 0.5
+        "#,
+    )
+    .unwrap();
+    let signals = strategy.run(&df).unwrap();
+    assert_snapshot("config_strategy_atr_stops", &render(&signals));
+}
+
+#[test]
+fn config_strategy_signal_timing_snapshot() {
+    let df = synthetic_ohlcv();
+    let strategy = ConfigStrategy::from_toml(
+        r#"
+        name = "sma flip with timing controls"
+
+        [[indicators]]
+        type = "sma"
+        name = "sma_5"
+        period = 5
+
+        [[entry]]
+        type = "above"
+        left = "close"
+        right = "sma_5"
+
+        [[exit]]
+        type = "below"
+        left = "close"
+        right = "sma_5"
+
+        [timing]
+        persistence_bars = 3
+        min_holding_bars = 4
+        cooldown_bars = 2
+        "#,
+    )
+    .unwrap();
+    let signals = strategy.run(&df).unwrap();
+    assert_snapshot("config_strategy_signal_timing", &render(&signals));
+}
+
+#[test]
+fn config_strategy_limit_entry_snapshot() {
+    let df = synthetic_ohlcv();
+    let strategy = ConfigStrategy::from_toml(
+        r#"
+        name = "buy the lower band on a limit"
+
+        [[indicators]]
+        type = "bollinger_bands"
+        name = "bb"
+        period = 20
+        num_std = 1.5
+
+        [[entry]]
+        type = "below"
+        left = "close"
+        right = "bb_middle"
+
+        [[exit]]
+        type = "above"
+        left = "close"
+        right = "bb_upper"
+
+        [entry_order]
+        type = "limit"
+        column = "bb_lower"
+        valid_bars = 3
+
+        [risk]
+        max_holding_bars = 15
+        "#,
+    )
+    .unwrap();
+    let signals = strategy.run(&df).unwrap();
+    assert_snapshot("config_strategy_limit_entry", &render(&signals));
+}
+
+#[test]
+fn arbitrage_snapshot() {
+    let base = synthetic_ohlcv();
+    let base: Vec<f64> = base
+        .column("close")
+        .unwrap()
+        .f64()
+        .unwrap()
+        .into_no_null_iter()
+        .collect();
+    let venue_a = synthetic_feed(&base, 0.0, 10);
+    let venue_b = synthetic_feed(&base, 0.001, 20);
+    let venue_c = synthetic_feed(&base, -0.0005, 30);
+    let feeds = [
+        arbitrage::ExchangeFeed::new("alpha", &venue_a, 0.0005),
+        arbitrage::ExchangeFeed::new("beta", &venue_b, 0.001),
+        arbitrage::ExchangeFeed::new("gamma", &venue_c, 0.0005),
+    ];
+    let params = arbitrage::StrategyParams {
+        zscore_window: 30,
+        entry_zscore: 1.5,
+        min_net_spread_pct: 0.1,
+        transfer_latency_bars: 2,
+        ..Default::default()
+    };
+    let signals = arbitrage::run_strategy(&feeds, &params).unwrap();
+    assert_snapshot("arbitrage", &render(&signals));
+}
+
+#[test]
+fn market_neutral_snapshot() {
+    let base = synthetic_ohlcv();
+    let base: Vec<f64> = base
+        .column("close")
+        .unwrap()
+        .f64()
+        .unwrap()
+        .into_no_null_iter()
+        .collect();
+    let spot = synthetic_feed(&base, 0.0, 50);
+    let mut perp = synthetic_feed(&base, 0.0005, 60);
+    // Funding every 8 bars, negative in the middle third and positive around it
+    let funding: Vec<f64> = (0..BARS)
+        .map(|i| match i % 8 {
+            0 if (BARS / 3..2 * BARS / 3).contains(&i) => -0.0003 + 0.0002 * noise(61, i),
+            0 => 0.0003 + 0.0002 * noise(61, i),
+            _ => 0.0,
+        })
+        .collect();
+    perp.with_column(Series::new("funding_rate".into(), funding))
+        .unwrap();
+    let params = market_neutral::StrategyParams {
+        funding_window: 2,
+        allow_reverse: true,
+        rebalance_interval: 4,
+        ..Default::default()
+    };
+    let signals = market_neutral::run_strategy(&spot, &perp, &params).unwrap();
+    let performance = market_neutral::carry_performance(&signals).unwrap();
+    assert!(signals
+        .trades
+        .iter()
+        .any(|trade| trade.side == TradeSide::Short));
+    assert!(performance.borrow > 0.0);
+
+    let mut rendered = render(&signals);
+    writeln!(rendered, "carry_performance:").unwrap();
+    for (name, value) in [
+        ("price", performance.price),
+        ("funding", performance.funding),
+        ("borrow", performance.borrow),
+        ("fees", performance.fees),
+        ("total", performance.total),
+    ] {
+        writeln!(rendered, "  {name}: {value:.6}").unwrap();
+    }
+    assert_snapshot("market_neutral", &rendered);
+}
+
+#[test]
+fn iron_condor_snapshot() {
+    let (underlying, chain) = synthetic_options_market();
+    let params = iron_condor::StrategyParams {
+        profit_target_pct: 40.0,
+        ..Default::default()
+    };
+    let signals = iron_condor::run_strategy(&underlying, &chain, &params).unwrap();
+    assert_snapshot("iron_condor", &render(&signals));
+}
+
+#[test]
+fn vertical_spread_bull_put_snapshot() {
+    let (underlying, chain) = synthetic_options_market();
+    let params = vertical_spreads::StrategyParams {
+        spread_type: vertical_spreads::SpreadType::BullPut,
+        ..Default::default()
+    };
+    let signals = vertical_spreads::run_strategy(&underlying, &chain, &params).unwrap();
+    assert_snapshot("vertical_spread_bull_put", &render(&signals));
+}
+
+#[test]
+fn vertical_spread_bull_call_bid_ask_snapshot() {
+    let (underlying, chain) = synthetic_options_market();
+    // Quote a spread of 4% plus two cents around the model price, without a mark column
+    let chain = chain
+        .lazy()
+        .with_columns([
+            (col("price") * lit(0.98) - lit(0.02)).alias("bid"),
+            (col("price") * lit(1.02) + lit(0.02)).alias("ask"),
+        ])
+        .drop(["price"])
+        .collect()
+        .unwrap();
+    let params = vertical_spreads::StrategyParams {
+        spread_type: vertical_spreads::SpreadType::BullCall,
+        target_delta: 0.5,
+        ..Default::default()
+    };
+    let signals = vertical_spreads::run_strategy(&underlying, &chain, &params).unwrap();
+    assert!(signals.trades.iter().all(|t| t.side == TradeSide::Long));
+    assert_snapshot("vertical_spread_bull_call_bid_ask", &render(&signals));
+}
+
+#[test]
+fn delta_neutral_short_straddle_snapshot() {
+    let (underlying, chain) = synthetic_options_market();
+    let params = delta_neutral::StrategyParams {
+        side: delta_neutral::StraddleSide::Short,
+        ..Default::default()
+    };
+    let signals = delta_neutral::run_strategy(&underlying, &chain, &params).unwrap();
+    let attribution = delta_neutral::pnl_attribution(&signals).unwrap();
+    let closed: f64 = signals.trades.iter().map(|t| t.pnl).sum();
+    // A straddle is still open on the last bar
+    let open = signals
+        .indicator_values
+        .column("dn_pnl")
+        .unwrap()
+        .f64()
+        .unwrap()
+        .last();
+    let open = open.filter(|v| !v.is_nan()).unwrap();
+    assert!((attribution.total - closed - open).abs() < 1e-6);
+    let mut rendered = render(&signals);
+    writeln!(rendered, "attribution: {attribution:.6?}").unwrap();
+    assert_snapshot("delta_neutral_short_straddle", &rendered);
+}
+
+#[test]
+fn wheel_snapshot() {
+    let (underlying, chain) = synthetic_options_market();
+    let signals =
+        wheel::run_strategy(&underlying, &chain, &wheel::StrategyParams::default()).unwrap();
+    let events = signals.indicator_values.column("wheel_event").unwrap();
+    let mut rendered = render(&signals);
+    let events: Vec<_> = events.str().unwrap().into_iter().flatten().collect();
+    writeln!(rendered, "events: {}", events.join(",")).unwrap();
+    assert_snapshot("wheel", &rendered);
+}
+
+#[test]
+fn pairs_trading_rolling_ols_snapshot() {
+    let (first, second) = synthetic_pair();
+    let params = pairs_trading::StrategyParams {
+        hedge_window: 40,
+        ..Default::default()
+    };
+    let signals = pairs_trading::run_strategy(&first, &second, &params).unwrap();
+    let performance = pairs_trading::pair_performance(&signals).unwrap();
+    let mut rendered = render(&signals);
+    writeln!(rendered, "performance: {performance:.6?}").unwrap();
+    assert_snapshot("pairs_trading_rolling_ols", &rendered);
+}
+
+#[test]
+fn pairs_trading_kalman_snapshot() {
+    let (first, second) = synthetic_pair();
+    let params = pairs_trading::StrategyParams {
+        hedge_method: pairs_trading::HedgeRatioMethod::Kalman,
+        hedge_window: 40,
+        max_half_life: Some(5.0),
+        ..Default::default()
+    };
+    let signals = pairs_trading::run_strategy(&first, &second, &params).unwrap();
+    let performance = pairs_trading::pair_performance(&signals).unwrap();
+    let mut rendered = render(&signals);
+    writeln!(rendered, "performance: {performance:.6?}").unwrap();
+    assert_snapshot("pairs_trading_kalman", &rendered);
+}
+
+#[test]
+fn donchian_breakout_snapshot() {
+    let df = synthetic_ohlcv();
+    let params = donchian_breakout::StrategyParams {
+        entry_window: 20,
+        exit_window: 20,
+        atr_window: 14,
+        pyramid_step: 0.25,
+        stop_atr: 1.0,
+        ..Default::default()
+    };
+    let signals = donchian_breakout::run_strategy(&df, &params).unwrap();
+    let column = |name: &str| -> Vec<f64> {
+        let values = signals.indicator_values.column(name).unwrap();
+        let values = values.cast(&DataType::Float64).unwrap();
+        values
+            .f64()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.unwrap_or(f64::NAN))
+            .collect()
+    };
+    let (units, stops) = (column("turtle_units"), column("turtle_stop"));
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+    // Exits at the stop held on the bar before, not at the exit channel
+    let stopped: Vec<String> = signals
+        .trades
+        .iter()
+        .enumerate()
+        .filter(|(_, trade)| {
+            let exit = (trade.exit_time - start).num_minutes() as usize;
+            trade.exit_price == stops[exit - 1]
+        })
+        .map(|(k, _)| k.to_string())
+        .collect();
+    let max_units = units.iter().fold(0.0, |max: f64, u| max.max(u.abs()));
+    assert!(max_units > 1.0, "no position pyramided");
+    assert!(!stopped.is_empty(), "no position stopped out");
+    let mut rendered = render(&signals);
+    writeln!(rendered, "max_units: {max_units}").unwrap();
+    writeln!(rendered, "stopped_out: {}", stopped.join(",")).unwrap();
+    assert_snapshot("donchian_breakout", &rendered);
+}
+
+#[test]
+fn momentum_rotation_snapshot() {
+    // Five tickers whose trends peak at different times, so the leaders rotate
+    let tickers = ["AAA", "BBB", "CCC", "DDD", "EEE"];
+    let feeds: Vec<DataFrame> = (0..tickers.len())
+        .map(|t| {
+            let mut close = vec![100.0];
+            for i in 1..BARS {
+                let drift = 0.002 * (i as f64 / 40.0 + t as f64 * 1.3).sin();
+                close.push(close[i - 1] * (1.0 + drift + 0.004 * noise(70 + t as u64, i)));
+            }
+            df! { "timestamp" => timestamps(BARS), "close" => close }.unwrap()
+        })
+        .collect();
+    let universe: Vec<_> = tickers
+        .iter()
+        .zip(&feeds)
+        .map(|(ticker, feed)| momentum_rotation::UniverseMember::new(ticker, feed))
+        .collect();
+    let params = momentum_rotation::StrategyParams {
+        lookbacks: vec![20, 40],
+        top_n: 2,
+        rebalance_every: 20,
+        weighting: momentum_rotation::RotationWeighting::InverseVolatility,
+        volatility_window: 20,
+        ..Default::default()
+    };
+    let signals = momentum_rotation::run_strategy(&universe, &params).unwrap();
+
+    let mut rendered = render(&signals);
+    writeln!(rendered, "weights:").unwrap();
+    let weights: Vec<Vec<f64>> = tickers
+        .iter()
+        .map(|ticker| {
+            let name = format!("{ticker}_weight");
+            let column = signals.indicator_values.column(&name).unwrap();
+            column.f64().unwrap().into_no_null_iter().collect()
+        })
+        .collect();
+    for bar in (40..BARS).step_by(params.rebalance_every) {
+        let row: Vec<String> = weights.iter().map(|w| format!("{:.4}", w[bar])).collect();
+        writeln!(rendered, "  {bar}: {}", row.join(",")).unwrap();
+    }
+    let turnover = momentum_rotation::rotation_turnover(&signals).unwrap();
+    writeln!(rendered, "turnover:").unwrap();
+    writeln!(rendered, "  rebalances: {}", turnover.rebalances).unwrap();
+    writeln!(rendered, "  total: {:.6}", turnover.total).unwrap();
+    writeln!(rendered, "  average: {:.6}", turnover.average).unwrap();
+    assert_snapshot("momentum_rotation", &rendered);
+}
+
+/// Buys a breakout of the 10-bar high with a stop order and exits with a
+/// linked stop loss and limit target, cancelling whichever does not fill
+struct BreakoutWithBracket {
+    highs: Vec<f64>,
+    entry: Option<OrderId>,
+    exits: Vec<OrderId>,
+}
+
+impl EventStrategy for BreakoutWithBracket {
+    fn on_bar(&mut self, ctx: &mut BarContext<'_>) {
+        let i = ctx.index;
+        if i < 10 || !ctx.exchange.position().is_flat() {
+            return;
+        }
+        if let Some(entry) = self.entry.take() {
+            ctx.exchange.cancel(entry);
+        }
+        let breakout = self.highs[i - 9..=i]
+            .iter()
+            .copied()
+            .fold(f64::MIN, f64::max);
+        self.entry = Some(ctx.exchange.submit(
+            OrderSide::Buy,
+            2.0,
+            OrderType::Stop { price: breakout },
+        ));
+    }
+
+    fn on_fill(&mut self, fill: &Fill, ctx: &mut BarContext<'_>) {
+        if fill.side == OrderSide::Buy {
+            self.entry = None;
+            self.exits = vec![
+                ctx.exchange.submit(
+                    OrderSide::Sell,
+                    fill.quantity,
+                    OrderType::Stop {
+                        price: fill.price - 1.5,
+                    },
+                ),
+                ctx.exchange.submit(
+                    OrderSide::Sell,
+                    fill.quantity,
+                    OrderType::Limit {
+                        price: fill.price + 3.0,
+                    },
+                ),
+            ];
+        } else {
+            for id in self.exits.drain(..) {
+                ctx.exchange.cancel(id);
+            }
+        }
+    }
+}
+
+#[test]
+fn event_driven_breakout_snapshot() {
+    let df = synthetic_ohlcv();
+    let highs = df
+        .column("high")
+        .unwrap()
+        .f64()
+        .unwrap()
+        .into_no_null_iter()
+        .collect();
+    let params = BacktestParams {
+        fee_rate: 0.001,
+        slippage_bps: 5.0,
+        ..Default::default()
+    };
+    let mut strategy = BreakoutWithBracket {
+        highs,
+        entry: None,
+        exits: Vec::new(),
+    };
+    let signals = run_backtest(&df, &params, &mut strategy).unwrap();
+    assert_snapshot("event_driven_breakout", &render(&signals));
+}
@@ -0,0 +1,162 @@
+#!/usr/bin/env python3
+"""Regenerate the reference dataset and golden vectors for the TA-Lib parity tests.
+
+    python3 tests/talib_parity/generate.py [path/to/libta_lib.so]
+
+The golden vectors are TA-Lib's own output, computed by calling the TA-Lib C
+library through ctypes, so only a build of TA-Lib is needed (the Python
+bindings are thin wrappers around the same functions). Without a path the
+library is looked up as "ta_lib" / "ta-lib" on the system library path.
+The "# source:" line of golden.csv records the TA-Lib version used.
+
+Missing values (TA-Lib's lookback period) are written as "nan".
+"""
+
+import ctypes
+import ctypes.util
+import math
+import os
+import sys
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+ROWS = 250
+
+
+def dataset():
+    """Deterministic OHLCV random walk (xorshift64)"""
+    state = 0x2545F4914F6CDD1D
+    mask = (1 << 64) - 1
+
+    def next_unit():
+        nonlocal state
+        state ^= (state << 13) & mask
+        state ^= state >> 7
+        state ^= (state << 17) & mask
+        return (state % 2001) / 1000.0 - 1.0
+
+    rows = []
+    close = 100.0
+    for _ in range(ROWS):
+        prev = close
+        close = round(prev * (1.0 + next_unit() / 50.0), 4)
+        open_ = round(prev * (1.0 + next_unit() / 200.0), 4)
+        high = round(max(open_, close) * (1.0 + abs(next_unit()) / 100.0), 4)
+        low = round(min(open_, close) * (1.0 - abs(next_unit()) / 100.0), 4)
+        volume = float(1000 + int((next_unit() + 1.0) * 4500))
+        rows.append((open_, high, low, close, volume))
+    return rows
+
+
+NAN = float("nan")
+
+
+def load_talib():
+    path = sys.argv[1] if len(sys.argv) > 1 else None
+    path = path or ctypes.util.find_library("ta_lib") or ctypes.util.find_library("ta-lib")
+    if path is None:
+        sys.exit("TA-Lib C library not found; pass the path to libta_lib as the first argument")
+    lib = ctypes.CDLL(path)
+    if lib.TA_Initialize() != 0:
+        sys.exit("TA_Initialize failed")
+    lib.TA_GetVersionString.restype = ctypes.c_char_p
+    return lib
+
+
+def call(lib, name, inputs, params=(), outputs=1, integer=False):
+    """Calls TA_<name> over the whole series, NaN-padding its lookback period"""
+    n = len(inputs[0])
+    out_type = ctypes.c_int * n if integer else ctypes.c_double * n
+    out = [out_type() for _ in range(outputs)]
+    beg, count = ctypes.c_int(), ctypes.c_int()
+    args = [ctypes.c_int(0), ctypes.c_int(n - 1)]
+    args += [(ctypes.c_double * n)(*x) for x in inputs]
+    args += [ctypes.c_int(p) if isinstance(p, int) else ctypes.c_double(p) for p in params]
+    args += [ctypes.byref(beg), ctypes.byref(count)] + out
+    code = getattr(lib, "TA_" + name)(*args)
+    if code != 0:
+        sys.exit(f"TA_{name} returned {code}")
+    result = [[NAN] * beg.value + [float(v) for v in o[: count.value]] for o in out]
+    result = [r + [NAN] * (n - len(r)) for r in result]
+    return result[0] if outputs == 1 else result
+
+
+def talib_outputs(o, h, l, c, v):
+    lib = load_talib()
+    ta = lambda name, inputs, *params, **kw: call(lib, name, inputs, params, **kw)
+
+    upper, middle, lower = ta("BBANDS", [c], 20, 2.0, 2.0, 0, outputs=3)
+    aroon_down, aroon_up = ta("AROON", [h, l], 14, outputs=2)
+    inphase, quadrature = ta("HT_PHASOR", [c], outputs=2)
+    sine, leadsine = ta("HT_SINE", [c], outputs=2)
+    stoch_k, stoch_d = ta("STOCH", [h, l, c], 14, 3, 0, 3, 0, outputs=2)
+    outputs = {
+        "sma_10": ta("SMA", [c], 10),
+        "ema_10": ta("EMA", [c], 10),
+        "wma_10": ta("WMA", [c], 10),
+        "mom_10": ta("MOM", [c], 10),
+        "roc_10": ta("ROC", [c], 10),
+        "rocp_10": ta("ROCP", [c], 10),
+        "rocr_10": ta("ROCR", [c], 10),
+        "rocr100_10": ta("ROCR100", [c], 10),
+        "rsi_14": ta("RSI", [c], 14),
+        "stddev_10": ta("STDDEV", [c], 10, 1.0),
+        "max_10": ta("MAX", [c], 10),
+        "min_10": ta("MIN", [c], 10),
+        "sum_10": ta("SUM", [c], 10),
+        "trange": ta("TRANGE", [h, l, c]),
+        "obv": ta("OBV", [c, v]),
+        "ad": ta("AD", [h, l, c, v]),
+        "adosc_3_10": ta("ADOSC", [h, l, c, v], 3, 10),
+        "bop": ta("BOP", [o, h, l, c]),
+        "cci_14": ta("CCI", [h, l, c], 14),
+        "willr_14": ta("WILLR", [h, l, c], 14),
+        "medprice": ta("MEDPRICE", [h, l]),
+        "typprice": ta("TYPPRICE", [h, l, c]),
+        "avgprice": ta("AVGPRICE", [o, h, l, c]),
+        "wclprice": ta("WCLPRICE", [h, l, c]),
+        "var_5": ta("VAR", [c], 5, 1.0),
+        "correl_30": ta("CORREL", [h, l], 30),
+        "linearreg_14": ta("LINEARREG", [c], 14),
+        "linearreg_slope_14": ta("LINEARREG_SLOPE", [c], 14),
+        "linearreg_intercept_14": ta("LINEARREG_INTERCEPT", [c], 14),
+        "linearreg_angle_14": ta("LINEARREG_ANGLE", [c], 14),
+        "tsf_14": ta("TSF", [c], 14),
+        "atr_14": ta("ATR", [h, l, c], 14),
+        "natr_14": ta("NATR", [h, l, c], 14),
+        "bb_middle_20": middle,
+        "bb_upper_20": upper,
+        "bb_lower_20": lower,
+        "cmo_14": ta("CMO", [c], 14),
+        "aroon_up_14": aroon_up,
+        "aroon_down_14": aroon_down,
+        "stoch_k_14_3_3": stoch_k,
+        "stoch_d_14_3_3": stoch_d,
+        "ht_dcperiod": ta("HT_DCPERIOD", [c]),
+        "ht_dcphase": ta("HT_DCPHASE", [c]),
+        "ht_inphase": inphase,
+        "ht_quadrature": quadrature,
+        "ht_sine": sine,
+        "ht_leadsine": leadsine,
+        "ht_trendmode": ta("HT_TRENDMODE", [c], integer=True),
+    }
+    return outputs, f"TA-Lib {lib.TA_GetVersionString().decode().split(' (')[0]}"
+
+
+def main():
+    rows = dataset()
+    with open(os.path.join(HERE, "ohlcv.csv"), "w") as f:
+        f.write("open,high,low,close,volume\n")
+        for row in rows:
+            f.write(",".join(repr(x) for x in row) + "\n")
+
+    golden, source = talib_outputs(*zip(*rows))
+
+    with open(os.path.join(HERE, "golden.csv"), "w") as f:
+        f.write(f"# source: {source}\n")
+        f.write(",".join(golden) + "\n")
+        for i in range(ROWS):
+            f.write(",".join("nan" if math.isnan(x[i]) else repr(x[i]) for x in golden.values()) + "\n")
+
+
+if __name__ == "__main__":
+    main()